@@ -10,6 +10,12 @@ pub enum CfgExpr {
     All(Vec<CfgExpr>),
     Any(Vec<CfgExpr>),
     Value(Cfg),
+    /// A predicate Cargo does not know about, like `accessible(::std::foo)`
+    /// or the syntax of a newer version of Rust, kept as written.
+    ///
+    /// It cannot be evaluated from the cfg values printed by `rustc`, so an
+    /// expression using it anywhere never matches, even under `not()`.
+    Unknown(String),
 }
 
 /// A cfg value.
@@ -82,11 +88,18 @@ impl CfgExpr {
     }
 
     pub fn matches(&self, cfg: &[Cfg]) -> bool {
+        self.eval(cfg) == Some(true)
+    }
+
+    /// Evaluates the expression against `cfg`, or returns `None` if it
+    /// contains an [`CfgExpr::Unknown`] predicate, whose value is unknown.
+    fn eval(&self, cfg: &[Cfg]) -> Option<bool> {
         match *self {
-            CfgExpr::Not(ref e) => !e.matches(cfg),
-            CfgExpr::All(ref e) => e.iter().all(|e| e.matches(cfg)),
-            CfgExpr::Any(ref e) => e.iter().any(|e| e.matches(cfg)),
-            CfgExpr::Value(ref e) => cfg.contains(e),
+            CfgExpr::Not(ref e) => e.eval(cfg).map(|b| !b),
+            CfgExpr::All(ref e) => e.iter().try_fold(true, |all, e| Some(e.eval(cfg)? && all)),
+            CfgExpr::Any(ref e) => e.iter().try_fold(false, |any, e| Some(e.eval(cfg)? || any)),
+            CfgExpr::Value(ref e) => Some(cfg.contains(e)),
+            CfgExpr::Unknown(_) => None,
         }
     }
 }
@@ -114,6 +127,7 @@ impl fmt::Display for CfgExpr {
             CfgExpr::All(ref e) => write!(f, "all({})", CommaSep(e)),
             CfgExpr::Any(ref e) => write!(f, "any({})", CommaSep(e)),
             CfgExpr::Value(ref e) => write!(f, "{}", e),
+            CfgExpr::Unknown(ref e) => e.fmt(f),
         }
     }
 }
@@ -168,6 +182,7 @@ impl<'a> Parser<'a> {
                 self.eat(&Token::RightParen)?;
                 Ok(CfgExpr::Not(Box::new(e)))
             }
+            Some(Ok(Token::Ident(_))) if self.is_predicate() => self.unknown_predicate(),
             Some(Ok(..)) => self.cfg().map(CfgExpr::Value),
             Some(Err(..)) => Err(self.t.next().unwrap().err().unwrap()),
             None => Err(ParseError::new(
@@ -215,6 +230,50 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Whether the next identifier is followed by `(`, like the name of a
+    /// predicate.
+    fn is_predicate(&self) -> bool {
+        let mut t = self.t.clone();
+        t.next();
+        matches!(t.next(), Some(Ok(Token::LeftParen)))
+    }
+
+    /// Parses a predicate with an unknown name, skipping its arguments
+    /// without tokenizing them, as they may use any syntax.
+    fn unknown_predicate(&mut self) -> Result<CfgExpr, ParseError> {
+        while let Some((_, ' ')) = self.t.s.peek() {
+            self.t.s.next();
+        }
+        let start = match self.t.s.peek() {
+            Some(&(start, _)) => start,
+            None => unreachable!("a predicate was found"),
+        };
+        self.t.next();
+        self.eat(&Token::LeftParen)?;
+        let mut depth = 1;
+        let mut in_string = false;
+        for (i, ch) in self.t.s.by_ref() {
+            match ch {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(CfgExpr::Unknown(self.t.orig[start..=i].to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if in_string {
+            return Err(ParseError::new(self.t.orig, UnterminatedString));
+        }
+        Err(ParseError::new(
+            self.t.orig,
+            IncompleteExpr(Token::RightParen.classify()),
+        ))
+    }
+
     fn peek(&mut self) -> Option<Result<Token<'a>, ParseError>> {
         self.t.clone().next()
     }
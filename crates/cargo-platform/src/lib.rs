@@ -71,6 +71,12 @@ impl Platform {
                         check_cfg_expr(e, warnings);
                     }
                 }
+                CfgExpr::Unknown(ref e) => warnings.push(format!(
+                    "Found `{}` in `target.'cfg(...)'.dependencies`. \
+                     This predicate is not supported by this version of Cargo, \
+                     the table using it will never match.",
+                    e
+                )),
                 CfgExpr::Value(ref e) => match e {
                     Cfg::Name(name) => match name.as_str() {
                         "test" | "debug_assertions" | "proc_macro" =>
@@ -97,6 +97,25 @@ fn cfg_expr() {
     good("all(a, )", e!(all(a)));
     good("not(a = \"b\")", e!(not(a = "b")));
     good("not(all(a))", e!(not(all(a))));
+
+    good("foo(a)", CfgExpr::Unknown("foo(a)".to_string()));
+    good(
+        " accessible(::std::collections::HashMap) ",
+        CfgExpr::Unknown("accessible(::std::collections::HashMap)".to_string()),
+    );
+    good(
+        "any(unix, version(\"1.80\"))",
+        CfgExpr::Any(vec![
+            e!(unix),
+            CfgExpr::Unknown("version(\"1.80\")".to_string()),
+        ]),
+    );
+    good(
+        "not(foo(bar(\")\"), baz))",
+        CfgExpr::Not(Box::new(CfgExpr::Unknown(
+            "foo(bar(\")\"), baz)".to_string(),
+        ))),
+    );
 }
 
 #[test]
@@ -108,9 +127,11 @@ fn cfg_expr_bad() {
     bad::<CfgExpr>("not(a", "expected `)`");
     bad::<CfgExpr>("a = ", "expected a string");
     bad::<CfgExpr>("all(not())", "expected identifier");
+    bad::<CfgExpr>("foo(a", "expected `)`, but cfg expression ended");
+    bad::<CfgExpr>("foo(\"a)", "unterminated string");
     bad::<CfgExpr>(
-        "foo(a)",
-        "unexpected content `(a)` found after cfg expression",
+        "foo(a) b",
+        "unexpected content `b` found after cfg expression",
     );
 }
 
@@ -140,6 +161,13 @@ fn cfg_matches() {
     assert!(!e!(not(bar)).matches(&[c!(bar)]));
     assert!(!e!(not(bar)).matches(&[c!(baz), c!(bar)]));
     assert!(!e!(any((not(foo)), (all(foo, bar)))).matches(&[c!(foo)]));
+
+    let unknown = || CfgExpr::Unknown("accessible(::std::foo)".to_string());
+    assert!(!unknown().matches(&[c!(foo)]));
+    assert!(!CfgExpr::Not(Box::new(unknown())).matches(&[c!(foo)]));
+    assert!(!CfgExpr::Any(vec![unknown(), e!(foo)]).matches(&[c!(foo)]));
+    assert!(!CfgExpr::All(vec![unknown(), e!(foo)]).matches(&[c!(foo)]));
+    assert!(!CfgExpr::Not(Box::new(CfgExpr::All(vec![unknown(), e!(bar)]))).matches(&[c!(foo)]));
 }
 
 #[test]
@@ -169,6 +197,8 @@ fn round_trip_platform() {
     rt("foo");
     rt("cfg(windows)");
     rt("cfg(target_os = \"windows\")");
+    rt("cfg(accessible(::std::foo))");
+    rt("cfg(all(unix, version(\"1.80\")))");
     rt(
         "cfg(any(all(any(target_os = \"android\", target_os = \"linux\"), \
          any(target_arch = \"aarch64\", target_arch = \"arm\", target_arch = \"powerpc64\", \
@@ -230,6 +260,7 @@ fn check_cfg_attributes() {
     warn("debug_assertions", &["debug_assertions"]);
     warn("proc_macro", &["proc_macro"]);
     warn("feature = \"abc\"", &["feature"]);
+    warn("accessible(::std::foo)", &["accessible(::std::foo)"]);
 
     warn("any(not(debug_assertions), windows)", &["debug_assertions"]);
     warn(
@@ -204,6 +204,7 @@ fn substitute_macros(input: &str) -> String {
         ("[FRESH]", "       Fresh"),
         ("[DIRTY]", "       Dirty"),
         ("[UPDATING]", "    Updating"),
+        ("[UPGRADING]", "   Upgrading"),
         ("[ADDING]", "      Adding"),
         ("[REMOVING]", "    Removing"),
         ("[DOCTEST]", "   Doc-tests"),
@@ -235,6 +236,8 @@ fn substitute_macros(input: &str) -> String {
         ("[SKIPPING]", "    Skipping"),
         ("[WAITING]", "     Waiting"),
         ("[PUBLISHED]", "   Published"),
+        ("[CHANGED]", "     Changed"),
+        ("[TESTING]", "     Testing"),
     ];
     let mut result = input.to_owned();
     for &(pat, subst) in &macros {
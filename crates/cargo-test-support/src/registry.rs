@@ -20,6 +20,10 @@ use time::format_description::well_known::Rfc3339;
 use time::{Duration, OffsetDateTime};
 use url::Url;
 
+mod replay;
+
+pub use replay::RECORD_ENV;
+
 /// Gets the path to the local index pretending to be crates.io. This is a Git repo
 /// initialized with a `config.json` file pointing to `dl_path` for downloads
 /// and `api_path` for uploads.
@@ -106,6 +110,8 @@ pub struct RegistryBuilder {
     delayed_index_update: usize,
     /// Credential provider in configuration
     credential_provider: Option<String>,
+    /// If set, the HTTP server replays the exchanges recorded in this file.
+    replay: Option<PathBuf>,
 }
 
 pub struct TestRegistry {
@@ -175,6 +181,7 @@ impl RegistryBuilder {
             not_found_handler: Box::new(not_found),
             delayed_index_update: 0,
             credential_provider: None,
+            replay: None,
         }
     }
 
@@ -262,6 +269,16 @@ impl RegistryBuilder {
         self
     }
 
+    /// Serve the responses recorded in the `fixture` file instead of the
+    /// published packages, see [`RECORD_ENV`] to record it.
+    /// Implies `http_index`.
+    #[must_use]
+    pub fn replay(mut self, fixture: impl Into<PathBuf>) -> Self {
+        self.replay = Some(fixture.into());
+        self.http_index = true;
+        self
+    }
+
     /// The registry has no api.
     #[must_use]
     pub fn no_api(mut self) -> Self {
@@ -309,6 +326,7 @@ impl RegistryBuilder {
                 self.custom_responders,
                 self.not_found_handler,
                 self.delayed_index_update,
+                self.replay,
             );
             let index_url = if self.http_index {
                 server.index_url()
@@ -666,6 +684,7 @@ pub struct HttpServer {
     custom_responders: HashMap<String, RequestCallback>,
     not_found_handler: RequestCallback,
     delayed_index_update: usize,
+    replay: Option<replay::Replay>,
 }
 
 /// A helper struct that collects the arguments for [`HttpServer::check_authorized`].
@@ -687,6 +706,7 @@ impl HttpServer {
         custom_responders: HashMap<String, RequestCallback>,
         not_found_handler: RequestCallback,
         delayed_index_update: usize,
+        replay: Option<PathBuf>,
     ) -> HttpServerHandle {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
@@ -701,6 +721,7 @@ impl HttpServer {
             custom_responders,
             not_found_handler,
             delayed_index_update,
+            replay: replay.map(replay::Replay::new),
         };
         let handle = Some(thread::spawn(move || server.start()));
         HttpServerHandle { addr, handle }
@@ -917,6 +938,9 @@ impl HttpServer {
 
     /// Route the request
     fn route(&self, req: &Request) -> Response {
+        if let Some(replay) = &self.replay {
+            return replay.respond(req, self.addr);
+        }
         // Check for custom responder
         if let Some(responder) = self.custom_responders.get(req.url.path()) {
             return responder(&req, self);
@@ -0,0 +1,306 @@
+//! Record and replay of the HTTP exchanges of a test registry.
+//!
+//! A registry built with [`RegistryBuilder::replay`] doesn't serve the
+//! packages published with [`Package`]. Instead, the test server answers each
+//! request with the response recorded for it in a fixture file, so that tests
+//! can exercise the responses of a real registry without reaching it.
+//!
+//! Setting the `CARGO_TEST_RECORD_REGISTRY` environment variable to the URL of
+//! a sparse index records the fixture instead: each request is forwarded to
+//! that registry, and the exchange is appended to the fixture file. The `dl`
+//! and `api` URLs of the recorded `config.json` are rewritten to point back to
+//! the test server, so that downloads and API calls are recorded as well.
+//!
+//! Requests are matched by method, path and query. When the same request is
+//! made several times, the recorded responses are replayed in order, and the
+//! last one is repeated after that.
+//!
+//! [`RegistryBuilder::replay`]: super::RegistryBuilder::replay
+//! [`Package`]: super::Package
+
+use super::{Request, Response};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use url::Url;
+
+/// Environment variable with the URL of the sparse index to record from.
+pub const RECORD_ENV: &str = "CARGO_TEST_RECORD_REGISTRY";
+
+/// Stands for `http://{addr}` of the test server in recorded responses.
+const ROOT_PLACEHOLDER: &str = "{{root}}";
+
+/// Response headers worth recording, the others are specific to the server.
+const RECORDED_HEADERS: &[&str] = &["content-type", "etag", "last-modified", "www-authenticate"];
+
+/// A request and the response to it, as stored in a fixture file.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Exchange {
+    method: String,
+    /// The path and query of the request to the test server.
+    path: String,
+    status: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<String>,
+    /// The body of the response, if it is UTF-8.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    /// The body of the response, hex encoded, if it isn't UTF-8.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_hex: Option<String>,
+}
+
+impl Exchange {
+    fn to_response(&self, root: &str) -> Response {
+        let body = match (&self.body, &self.body_hex) {
+            (Some(body), _) => body.replace(ROOT_PLACEHOLDER, root).into_bytes(),
+            (None, Some(hex)) => hex::decode(hex).expect("invalid `body_hex` in replay fixture"),
+            (None, None) => Vec::new(),
+        };
+        Response {
+            code: self.status,
+            headers: self.headers.clone(),
+            body,
+        }
+    }
+}
+
+pub(crate) enum Replay {
+    Record {
+        fixture: PathBuf,
+        /// The upstream index, with a trailing slash.
+        index: Url,
+        /// The upstream `dl` and `api` URLs, known once `config.json` is recorded.
+        dl: RefCell<Option<String>>,
+        api: RefCell<Option<String>>,
+        exchanges: RefCell<Vec<Exchange>>,
+    },
+    Replay {
+        exchanges: Vec<Exchange>,
+        /// How many times each request has been answered.
+        served: RefCell<HashMap<(String, String), usize>>,
+    },
+}
+
+impl Replay {
+    /// Replays `fixture`, or records it if [`RECORD_ENV`] is set.
+    pub(crate) fn new(fixture: PathBuf) -> Replay {
+        if let Ok(index) = std::env::var(RECORD_ENV) {
+            let index = index.strip_prefix("sparse+").unwrap_or(&index);
+            let index = Url::parse(&format!("{}/", index.trim_end_matches('/')))
+                .unwrap_or_else(|e| panic!("invalid URL in `{RECORD_ENV}`: {e}"));
+            return Replay::Record {
+                fixture,
+                index,
+                dl: RefCell::new(None),
+                api: RefCell::new(None),
+                exchanges: RefCell::new(Vec::new()),
+            };
+        }
+        let contents = fs::read_to_string(&fixture).unwrap_or_else(|e| {
+            panic!(
+                "failed to read replay fixture `{}`: {e}\n\
+                 set `{RECORD_ENV}` to the URL of a sparse index to record it",
+                fixture.display()
+            )
+        });
+        let exchanges = serde_json::from_str(&contents)
+            .unwrap_or_else(|e| panic!("invalid replay fixture `{}`: {e}", fixture.display()));
+        Replay::Replay {
+            exchanges,
+            served: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Answers `req` made to the test server at `addr`.
+    pub(crate) fn respond(&self, req: &Request, addr: SocketAddr) -> Response {
+        let path = match req.url.query() {
+            Some(query) => format!("{}?{query}", req.url.path()),
+            None => req.url.path().to_string(),
+        };
+        let root = format!("http://{addr}");
+        match self {
+            Replay::Record {
+                fixture,
+                index,
+                dl,
+                api,
+                exchanges,
+            } => {
+                let Some(url) = upstream_url(index, &dl.borrow(), &api.borrow(), req) else {
+                    return not_recorded(req, &path);
+                };
+                let mut exchange = forward(&url, req, &path);
+                if path == "/index/config.json" && exchange.status == 200 {
+                    rewrite_config(&mut exchange, &mut dl.borrow_mut(), &mut api.borrow_mut());
+                }
+                let response = exchange.to_response(&root);
+                let mut exchanges = exchanges.borrow_mut();
+                exchanges.push(exchange);
+                // Written after each exchange, as the server thread is never
+                // joined when a test fails.
+                if let Some(parent) = fixture.parent() {
+                    fs::create_dir_all(parent).unwrap();
+                }
+                let json = serde_json::to_string_pretty(&*exchanges).unwrap();
+                fs::write(fixture, json + "\n").unwrap();
+                response
+            }
+            Replay::Replay { exchanges, served } => {
+                let matching: Vec<_> = exchanges
+                    .iter()
+                    .filter(|exchange| exchange.method == req.method && exchange.path == path)
+                    .collect();
+                let mut served = served.borrow_mut();
+                let count = served
+                    .entry((req.method.clone(), path.clone()))
+                    .or_insert(0);
+                let Some(exchange) = matching.get(*count).or(matching.last()) else {
+                    return not_recorded(req, &path);
+                };
+                *count += 1;
+                exchange.to_response(&root)
+            }
+        }
+    }
+}
+
+fn not_recorded(req: &Request, path: &str) -> Response {
+    let message = format!("no recorded response for `{} {path}`", req.method);
+    eprintln!("{message}");
+    Response {
+        code: 500,
+        headers: vec![],
+        body: message.into_bytes(),
+    }
+}
+
+/// Maps a request to the test server onto the upstream registry.
+fn upstream_url(
+    index: &Url,
+    dl: &Option<String>,
+    api: &Option<String>,
+    req: &Request,
+) -> Option<String> {
+    let path = req.url.path();
+    let query = req.url.query().map(|q| format!("?{q}")).unwrap_or_default();
+    if let Some(rest) = path.strip_prefix("/index/") {
+        return Some(format!("{index}{rest}{query}"));
+    }
+    if let Some(rest) = path.strip_prefix("/dl/") {
+        // Downloads are requested as `/dl/{crate}/{version}/download`.
+        let mut parts = rest.split('/');
+        let (Some(name), Some(vers)) = (parts.next(), parts.next()) else {
+            return None;
+        };
+        let dl = dl.as_deref()?;
+        if !dl.contains('{') {
+            return Some(format!("{dl}/{name}/{vers}/download"));
+        }
+        let prefix = match name.len() {
+            1 => String::from("1"),
+            2 => String::from("2"),
+            3 => format!("3/{}", &name[..1]),
+            _ => format!("{}/{}", &name[0..2], &name[2..4]),
+        };
+        return Some(
+            dl.replace("{crate}", name)
+                .replace("{version}", vers)
+                .replace("{lowerprefix}", &prefix.to_lowercase())
+                .replace("{prefix}", &prefix),
+        );
+    }
+    if path.starts_with("/api/") {
+        let api = api.as_deref()?;
+        return Some(format!("{}{path}{query}", api.trim_end_matches('/')));
+    }
+    None
+}
+
+/// Points the `dl` and `api` URLs of a recorded `config.json` to the test
+/// server, remembering the upstream ones to forward to.
+fn rewrite_config(exchange: &mut Exchange, dl: &mut Option<String>, api: &mut Option<String>) {
+    let Some(body) = &exchange.body else {
+        return;
+    };
+    let mut config: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(body).expect("invalid `config.json` in upstream registry");
+    if let Some(serde_json::Value::String(url)) = config.get("dl") {
+        *dl = Some(url.clone());
+        config.insert("dl".into(), format!("{ROOT_PLACEHOLDER}/dl").into());
+    }
+    if let Some(serde_json::Value::String(url)) = config.get("api") {
+        *api = Some(url.clone());
+        config.insert("api".into(), ROOT_PLACEHOLDER.into());
+    }
+    exchange.body = Some(serde_json::to_string(&config).unwrap());
+}
+
+/// Sends `req` to `url` in the upstream registry, and records its response.
+fn forward(url: &str, req: &Request, path: &str) -> Exchange {
+    let mut handle = curl::easy::Easy::new();
+    handle.url(url).unwrap();
+    handle
+        .custom_request(&req.method.to_ascii_uppercase())
+        .unwrap();
+    let mut headers = curl::easy::List::new();
+    for (name, value) in [
+        ("Authorization", &req.authorization),
+        ("If-Modified-Since", &req.if_modified_since),
+        ("If-None-Match", &req.if_none_match),
+    ] {
+        if let Some(value) = value {
+            headers.append(&format!("{name}: {value}")).unwrap();
+        }
+    }
+    handle.http_headers(headers).unwrap();
+    if let Some(body) = &req.body {
+        handle.post_fields_copy(body).unwrap();
+    }
+
+    let mut body = Vec::new();
+    let mut response_headers = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer
+            .header_function(|header| {
+                if let Ok(header) = std::str::from_utf8(header) {
+                    let header = header.trim();
+                    let recorded = header.split_once(':').map_or(false, |(name, _)| {
+                        RECORDED_HEADERS.contains(&name.trim().to_ascii_lowercase().as_str())
+                    });
+                    if recorded {
+                        response_headers.push(header.to_string());
+                    }
+                }
+                true
+            })
+            .unwrap();
+        transfer
+            .write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            })
+            .unwrap();
+        transfer
+            .perform()
+            .unwrap_or_else(|e| panic!("failed to forward `{url}`: {e}"));
+    }
+
+    let (body, body_hex) = match String::from_utf8(body) {
+        Ok(body) if body.is_empty() => (None, None),
+        Ok(body) => (Some(body), None),
+        Err(e) => (None, Some(hex::encode(e.into_bytes()))),
+    };
+    Exchange {
+        method: req.method.clone(),
+        path: path.to_string(),
+        status: handle.response_code().unwrap(),
+        headers: response_headers,
+        body,
+        body_hex,
+    }
+}
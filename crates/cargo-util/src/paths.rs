@@ -10,6 +10,7 @@ use std::io::prelude::*;
 use std::iter;
 use std::path::{Component, Path, PathBuf};
 use tempfile::Builder as TempFileBuilder;
+use tempfile::TempPath;
 
 /// Joins paths into a string suitable for the `PATH` environment variable.
 ///
@@ -202,6 +203,92 @@ pub fn append(path: &Path, contents: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Equivalent to [`write()`], but either replaces the whole file or leaves it
+/// untouched, even if the process is interrupted.
+///
+/// The contents are written to a temporary file in the same directory, which
+/// is synced to disk and then renamed over `path`. If `path` is a symlink, the
+/// file it points to is replaced instead. The file keeps its permissions if it
+/// already exists, and otherwise gets the default permissions of new files.
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let path = path.as_ref();
+    (|| -> Result<()> {
+        let is_symlink = fs::symlink_metadata(path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        let target = if is_symlink {
+            fs::canonicalize(path)?
+        } else {
+            path.to_path_buf()
+        };
+        let parent = match target.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        };
+        let perms = match fs::metadata(&target) {
+            Ok(metadata) => Some(metadata.permissions()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let (mut file, tmp) = create_temp_sibling(&target, parent)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        if let Some(perms) = perms {
+            file.set_permissions(perms)?;
+        }
+        drop(file);
+        persist(tmp, &target)
+    })()
+    .with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+/// Creates a new file in `parent` to be renamed over `target`. Unlike the
+/// files of [`tempfile`], it gets the default permissions of new files.
+fn create_temp_sibling(target: &Path, parent: &Path) -> io::Result<(File, TempPath)> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let mut attempt = 0;
+    loop {
+        let tmp = parent.join(format!(".{name}.{}.{attempt}.tmp", std::process::id()));
+        match OpenOptions::new().write(true).create_new(true).open(&tmp) {
+            Ok(file) => {
+                return match TempPath::try_from_path(&tmp) {
+                    Ok(tmp) => Ok((file, tmp)),
+                    Err(e) => {
+                        let _ = fs::remove_file(&tmp);
+                        Err(e)
+                    }
+                };
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn persist(tmp: TempPath, path: &Path) -> Result<()> {
+    tmp.persist(path)?;
+    Ok(())
+}
+
+/// On Windows, replacing a file fails while another process, like a virus
+/// scanner or an indexer, briefly has it open, so this retries a few times.
+#[cfg(windows)]
+fn persist(mut tmp: TempPath, path: &Path) -> Result<()> {
+    let mut attempts = 0;
+    loop {
+        match tmp.persist(path) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.error.kind() == io::ErrorKind::PermissionDenied && attempts < 10 => {
+                attempts += 1;
+                tmp = e.path;
+                std::thread::sleep(std::time::Duration::from_millis(50 * attempts));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 /// Creates a new file.
 pub fn create<P: AsRef<Path>>(path: P) -> Result<File> {
     let path = path.as_ref();
@@ -498,19 +585,58 @@ fn set_not_readonly(p: &Path) -> io::Result<bool> {
     Ok(true)
 }
 
+/// How [`link_or_copy_with`] places a file at its destination.
+///
+/// Directories are always symlinked, regardless of the strategy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkStrategy {
+    /// Hardlink the file. On macOS the file is copied instead, see the
+    /// comments in the implementation.
+    #[default]
+    Hardlink,
+    /// Symlink the file.
+    Symlink,
+    /// Copy the file.
+    Copy,
+}
+
 /// Hardlink (file) or symlink (dir) src to dst if possible, otherwise copy it.
 ///
 /// If the destination already exists, it is removed before linking.
 pub fn link_or_copy(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<()> {
+    link_or_copy_with(src, dst, LinkStrategy::Hardlink)
+}
+
+/// Like [`link_or_copy`], but files are linked according to `strategy`.
+///
+/// If linking fails, for example because the filesystem does not support it
+/// or the process lacks the privilege to create symlinks on Windows, the file
+/// is copied instead.
+pub fn link_or_copy_with(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    strategy: LinkStrategy,
+) -> Result<()> {
     let src = src.as_ref();
     let dst = dst.as_ref();
-    _link_or_copy(src, dst)
+    _link_or_copy(src, dst, strategy)
 }
 
-fn _link_or_copy(src: &Path, dst: &Path) -> Result<()> {
+fn _link_or_copy(src: &Path, dst: &Path, strategy: LinkStrategy) -> Result<()> {
     tracing::debug!("linking {} to {}", src.display(), dst.display());
     if same_file::is_same_file(src, dst).unwrap_or(false) {
-        return Ok(());
+        // Keep the destination unless it was placed with another strategy,
+        // a copy is never the same file as its source.
+        let is_symlink = fs::symlink_metadata(dst).map_or(false, |m| m.file_type().is_symlink());
+        let in_place = match strategy {
+            _ if src.is_dir() => true,
+            LinkStrategy::Hardlink => !is_symlink,
+            LinkStrategy::Symlink => is_symlink,
+            LinkStrategy::Copy => false,
+        };
+        if in_place {
+            return Ok(());
+        }
     }
 
     // NB: we can't use dst.exists(), as if dst is a broken symlink,
@@ -540,7 +666,9 @@ fn _link_or_copy(src: &Path, dst: &Path) -> Result<()> {
             src
         };
         symlink(src, dst)
-    } else if env::var_os("__CARGO_COPY_DONT_LINK_DO_NOT_USE_THIS").is_some() {
+    } else if strategy == LinkStrategy::Copy
+        || env::var_os("__CARGO_COPY_DONT_LINK_DO_NOT_USE_THIS").is_some()
+    {
         // This is a work-around for a bug in macOS 10.15. When running on
         // APFS, there seems to be a strange race condition with
         // Gatekeeper where it will forcefully kill a process launched via
@@ -551,6 +679,15 @@ fn _link_or_copy(src: &Path, dst: &Path) -> Result<()> {
         // See https://github.com/rust-lang/cargo/issues/7821 for the
         // gory details.
         fs::copy(src, dst).map(|_| ())
+    } else if strategy == LinkStrategy::Symlink {
+        #[cfg(target_os = "redox")]
+        use std::os::redox::fs::symlink;
+        #[cfg(unix)]
+        use std::os::unix::fs::symlink;
+        #[cfg(windows)]
+        use std::os::windows::fs::symlink_file as symlink;
+
+        symlink(src, dst)
     } else {
         if cfg!(target_os = "macos") {
             // This is a work-around for a bug on macos. There seems to be a race condition
@@ -761,7 +898,7 @@ fn exclude_from_time_machine(path: &Path) {
 
 #[cfg(test)]
 mod tests {
-    use super::join_paths;
+    use super::{join_paths, write_atomic};
 
     #[test]
     fn join_paths_lists_paths_on_error() {
@@ -798,4 +935,45 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn write_atomic_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Cargo.lock");
+        write_atomic(&path, "a longer first version\n").unwrap();
+        write_atomic(&path, "second\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\n");
+        // The temporary file was renamed over the original.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.toml");
+        std::fs::write(&path, "").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        write_atomic(&path, "token = \"x\"\n").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_through_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real.lock");
+        let link = dir.path().join("Cargo.lock");
+        std::fs::write(&real, "old\n").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        write_atomic(&link, "new\n").unwrap();
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(std::fs::read_to_string(&real).unwrap(), "new\n");
+    }
 }
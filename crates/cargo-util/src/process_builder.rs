@@ -335,6 +335,32 @@ impl ProcessBuilder {
         on_stderr_line: &mut dyn FnMut(&str) -> Result<()>,
         capture_output: bool,
     ) -> Result<Output> {
+        self.exec_with_streaming_inner(on_stdout_line, on_stderr_line, capture_output, false)
+            .map(|(output, _)| output)
+    }
+
+    /// Like [`ProcessBuilder::exec_with_streaming`], but also returns the
+    /// peak resident set size of the process in bytes.
+    ///
+    /// The peak RSS is only available on Unix platforms, and is `None`
+    /// elsewhere.
+    pub fn exec_with_streaming_peak_rss(
+        &self,
+        on_stdout_line: &mut dyn FnMut(&str) -> Result<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> Result<()>,
+        capture_output: bool,
+    ) -> Result<(Output, Option<u64>)> {
+        self.exec_with_streaming_inner(on_stdout_line, on_stderr_line, capture_output, true)
+    }
+
+    fn exec_with_streaming_inner(
+        &self,
+        on_stdout_line: &mut dyn FnMut(&str) -> Result<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> Result<()>,
+        capture_output: bool,
+        measure: bool,
+    ) -> Result<(Output, Option<u64>)> {
+        let mut peak_rss = None;
         let mut stdout = Vec::new();
         let mut stderr = Vec::new();
 
@@ -402,7 +428,14 @@ impl ProcessBuilder {
                 data.drain(..idx);
                 *pos = 0;
             })?;
-            let status = child.wait();
+            let status = if measure {
+                imp::wait_with_peak_rss(&mut child).map(|(status, rss)| {
+                    peak_rss = rss;
+                    status
+                })
+            } else {
+                child.wait()
+            };
             if let Some(argfile) = argfile {
                 close_tempfile_and_log_error(argfile);
             }
@@ -433,7 +466,7 @@ impl ProcessBuilder {
             }
         }
 
-        Ok(output)
+        Ok((output, peak_rss))
     }
 
     /// Builds the command with an `@<path>` argfile that contains all the
@@ -567,7 +600,8 @@ mod imp {
     use super::{close_tempfile_and_log_error, debug_force_argfile, ProcessBuilder, ProcessError};
     use anyhow::Result;
     use std::io;
-    use std::os::unix::process::CommandExt;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::{Child, ExitStatus};
 
     pub fn exec_replace(process_builder: &ProcessBuilder) -> Result<()> {
         let mut error;
@@ -599,6 +633,33 @@ mod imp {
     pub fn command_line_too_big(err: &io::Error) -> bool {
         err.raw_os_error() == Some(libc::E2BIG)
     }
+
+    /// Waits for `child` with `wait4`, which also reports its peak RSS.
+    pub fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+        let pid = child.id() as libc::pid_t;
+        let mut status = 0;
+        // SAFETY: `rusage` is a plain C struct, for which all zeroes is valid.
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `pid` is our child which hasn't been waited for yet.
+            let ret = unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) };
+            if ret != -1 {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+        // `ru_maxrss` is in kilobytes, except on macOS where it is in bytes.
+        let max_rss = rusage.ru_maxrss as u64;
+        let peak_rss = if cfg!(target_os = "macos") {
+            max_rss
+        } else {
+            max_rss * 1024
+        };
+        Ok((ExitStatus::from_raw(status), Some(peak_rss)))
+    }
 }
 
 #[cfg(windows)]
@@ -606,6 +667,7 @@ mod imp {
     use super::{ProcessBuilder, ProcessError};
     use anyhow::Result;
     use std::io;
+    use std::process::{Child, ExitStatus};
     use windows_sys::Win32::Foundation::{BOOL, FALSE, TRUE};
     use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
 
@@ -629,6 +691,10 @@ mod imp {
         use windows_sys::Win32::Foundation::ERROR_FILENAME_EXCED_RANGE;
         err.raw_os_error() == Some(ERROR_FILENAME_EXCED_RANGE as i32)
     }
+
+    pub fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+        child.wait().map(|status| (status, None))
+    }
 }
 
 #[cfg(test)]
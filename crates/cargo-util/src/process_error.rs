@@ -16,6 +16,12 @@ pub struct ProcessError {
     /// like termination via a signal.
     pub code: Option<i32>,
 
+    /// The signal which terminated the process, on unix.
+    ///
+    /// This is `None` if the process exited normally, failed to launch, or
+    /// on other platforms.
+    pub signal: Option<i32>,
+
     /// The stdout from the process.
     ///
     /// This can be `None` if the process failed to launch, or the output was
@@ -48,13 +54,15 @@ impl ProcessError {
             None => "never executed".to_string(),
         };
 
-        Self::new_raw(
+        let mut error = Self::new_raw(
             msg,
             status.and_then(|s| s.code()),
             &exit,
             output.map(|s| s.stdout.as_slice()),
             output.map(|s| s.stderr.as_slice()),
-        )
+        );
+        error.signal = status.and_then(exit_status_signal);
+        error
     }
 
     /// Creates a new [`ProcessError`] with the raw output data.
@@ -91,6 +99,7 @@ impl ProcessError {
         ProcessError {
             desc,
             code,
+            signal: None,
             stdout: stdout.map(|s| s.to_vec()),
             stderr: stderr.map(|s| s.to_vec()),
         }
@@ -104,6 +113,20 @@ impl ProcessError {
     }
 }
 
+/// Returns the signal which terminated a process, on unix.
+fn exit_status_signal(status: ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
 /// Converts an [`ExitStatus`]  to a human-readable string suitable for
 /// displaying to a user.
 pub fn exit_status_to_string(status: ExitStatus) -> String {
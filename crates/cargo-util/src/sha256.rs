@@ -5,6 +5,7 @@ use std::fs::File;
 use std::io::{self, Read};
 use std::path::Path;
 
+#[derive(Clone)]
 pub struct Sha256(Sha2_sha256);
 
 impl Sha256 {
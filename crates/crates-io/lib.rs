@@ -36,6 +36,9 @@ pub struct Crate {
     pub name: String,
     pub description: Option<String>,
     pub max_version: String,
+    /// The total number of downloads, if the registry reports it.
+    #[serde(default)]
+    pub downloads: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -335,12 +338,18 @@ impl Registry {
     }
 
     pub fn search(&mut self, query: &str, limit: u32) -> Result<(Vec<Crate>, u32)> {
+        self.search_page(query, limit, 1)
+    }
+
+    /// Like [`Registry::search`], returning the given `page` of `limit`
+    /// results, starting at 1.
+    pub fn search_page(&mut self, query: &str, limit: u32, page: u32) -> Result<(Vec<Crate>, u32)> {
         let formatted_query = percent_encode(query.as_bytes(), NON_ALPHANUMERIC);
-        let body = self.req(
-            &format!("/crates?q={}&per_page={}", formatted_query, limit),
-            None,
-            Auth::Unauthorized,
-        )?;
+        let mut path = format!("/crates?q={}&per_page={}", formatted_query, limit);
+        if page > 1 {
+            path.push_str(&format!("&page={}", page));
+        }
+        let body = self.req(&path, None, Auth::Unauthorized)?;
 
         let crates = serde_json::from_str::<Crates>(&body)?;
         Ok((crates.crates, crates.meta.total))
@@ -10,9 +10,7 @@
 //!         but forgot to bump its version.
 //! ```
 
-use std::collections::HashSet;
 use std::fmt::Write;
-use std::fs;
 use std::task;
 
 use cargo::core::dependency::Dependency;
@@ -21,7 +19,7 @@ use cargo::core::Package;
 use cargo::core::QueryKind;
 use cargo::core::Registry;
 use cargo::core::SourceId;
-use cargo::core::Workspace;
+use cargo::ops::cargo_version_check;
 use cargo::util::command_prelude::*;
 use cargo::util::ToSemver;
 use cargo::CargoResult;
@@ -111,7 +109,12 @@ fn bump_check(args: &clap::ArgMatches, config: &mut cargo::util::Config) -> Carg
     let base_commit = get_base_commit(config, args, &repo)?;
     let head_commit = get_head_commit(args, &repo)?;
     let referenced_commit = get_referenced_commit(&repo, &base_commit)?;
-    let changed_members = changed(&ws, &repo, &base_commit, &head_commit)?;
+    let root_pkg_name = ws.current()?.name(); // `cargo` crate.
+    let changed_members =
+        cargo_version_check::changed_members(&ws, &repo, &base_commit, &head_commit)?
+            .into_iter()
+            .filter(|pkg| pkg.name() != root_pkg_name) // Only take care of sub crates here.
+            .collect::<Vec<_>>();
     let status = |msg: &str| config.shell().status(STATUS, msg);
 
     status(&format!("base commit `{}`", base_commit.id()))?;
@@ -123,17 +126,11 @@ fn bump_check(args: &clap::ArgMatches, config: &mut cargo::util::Config) -> Carg
 
     if let Some(referenced_commit) = referenced_commit.as_ref() {
         status(&format!("compare against `{}`", referenced_commit.id()))?;
-        for referenced_member in checkout_ws(&ws, &repo, referenced_commit)?.members() {
-            let Some(changed_member) = changed_members.get(referenced_member) else {
-                let name = referenced_member.name().as_str();
-                tracing::trace!("skipping {name}, may be removed or not published");
-                continue;
-            };
-
-            if changed_member.version() <= referenced_member.version() {
-                needs_bump.push(*changed_member);
-            }
-        }
+        let referenced_ws = cargo_version_check::checkout_ws(&ws, &repo, referenced_commit)?;
+        needs_bump.extend(cargo_version_check::not_bumped(
+            &changed_members,
+            &referenced_ws,
+        ));
     }
 
     if !needs_bump.is_empty() {
@@ -311,51 +308,12 @@ fn beta_and_stable_branch(repo: &git2::Repository) -> CargoResult<[git2::Branch<
     Ok([beta.1, stable.1])
 }
 
-/// Lists all changed workspace members between two commits.
-fn changed<'r, 'ws>(
-    ws: &'ws Workspace<'_>,
-    repo: &'r git2::Repository,
-    base_commit: &git2::Commit<'r>,
-    head: &git2::Commit<'r>,
-) -> CargoResult<HashSet<&'ws Package>> {
-    let root_pkg_name = ws.current()?.name(); // `cargo` crate.
-    let ws_members = ws
-        .members()
-        .filter(|pkg| pkg.name() != root_pkg_name) // Only take care of sub crates here.
-        .filter(|pkg| pkg.publish() != &Some(vec![])) // filter out `publish = false`
-        .map(|pkg| {
-            // Having relative package root path so that we can compare with
-            // paths of changed files to determine which package has changed.
-            let relative_pkg_root = pkg.root().strip_prefix(ws.root()).unwrap();
-            (relative_pkg_root, pkg)
-        })
-        .collect::<Vec<_>>();
-    let base_tree = base_commit.as_object().peel_to_tree()?;
-    let head_tree = head.as_object().peel_to_tree()?;
-    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Default::default())?;
-
-    let mut changed_members = HashSet::new();
-
-    for delta in diff.deltas() {
-        let old = delta.old_file().path().unwrap();
-        let new = delta.new_file().path().unwrap();
-        for (ref pkg_root, pkg) in ws_members.iter() {
-            if old.starts_with(pkg_root) || new.starts_with(pkg_root) {
-                changed_members.insert(*pkg);
-                break;
-            }
-        }
-    }
-
-    Ok(changed_members)
-}
-
 /// Compares version against published crates on crates.io.
 ///
 /// Assumption: We always release a version larger than all existing versions.
 fn check_crates_io<'a>(
     config: &Config,
-    changed_members: &HashSet<&'a Package>,
+    changed_members: &[&'a Package],
     needs_bump: &mut Vec<&'a Package>,
 ) -> CargoResult<()> {
     let source_id = SourceId::crates_io(config)?;
@@ -389,26 +347,6 @@ fn check_crates_io<'a>(
     Ok(())
 }
 
-/// Checkouts a temporary workspace to do further version comparsions.
-fn checkout_ws<'cfg, 'a>(
-    ws: &Workspace<'cfg>,
-    repo: &'a git2::Repository,
-    referenced_commit: &git2::Commit<'a>,
-) -> CargoResult<Workspace<'cfg>> {
-    let repo_path = repo.path().as_os_str().to_str().unwrap();
-    // Put it under `target/cargo-<short-id>`
-    let short_id = &referenced_commit.id().to_string()[..7];
-    let checkout_path = ws.target_dir().join(format!("cargo-{short_id}"));
-    let checkout_path = checkout_path.as_path_unlocked();
-    let _ = fs::remove_dir_all(checkout_path);
-    let new_repo = git2::build::RepoBuilder::new()
-        .clone_local(git2::build::CloneLocal::Local)
-        .clone(repo_path, checkout_path)?;
-    let obj = new_repo.find_object(referenced_commit.id(), None)?;
-    new_repo.reset(&obj, git2::ResetType::Hard, None)?;
-    Workspace::new(&checkout_path.join("Cargo.toml"), ws.config())
-}
-
 #[test]
 fn verify_cli() {
     cli().debug_assert();
@@ -6,6 +6,7 @@ use indexmap::IndexSet;
 use cargo::core::dependency::DepKind;
 use cargo::core::FeatureValue;
 use cargo::ops::cargo_add::add;
+use cargo::ops::cargo_add::estimate;
 use cargo::ops::cargo_add::AddOptions;
 use cargo::ops::cargo_add::DepOp;
 use cargo::ops::resolve_ws;
@@ -76,6 +77,10 @@ Example uses:
                 "ignore-rust-version",
                 "Ignore `rust-version` specification in packages (unstable)"
             ),
+            flag(
+                "estimate",
+                "Report the download size and build time of the new crates (unstable)"
+            ),
         ])
         .arg_manifest_path()
         .arg_package("Package to modify")
@@ -203,6 +208,19 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     }
     let honor_rust_version = !ignore_rust_version;
 
+    let want_estimate = args.flag("estimate");
+    if want_estimate {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--estimate")?;
+    }
+    let manifest_path = spec.manifest_path().to_path_buf();
+    let before = if want_estimate {
+        Some(estimate::snapshot(&ws)?)
+    } else {
+        None
+    };
+
     let options = AddOptions {
         config,
         spec,
@@ -211,8 +229,17 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         dry_run,
         honor_rust_version,
     };
-    add(&ws, &options)?;
-
+    let manifest = add(&ws, &options)?;
+
+    if let Some(before) = before {
+        // With `--dry-run`, the workspace is resolved with the new manifest
+        // in memory, leaving the files alone.
+        let mut ws = args.workspace(config)?;
+        if dry_run {
+            ws.replace_manifest(&manifest_path, &manifest)?;
+        }
+        estimate::report(&ws, &before)?;
+    }
     if !dry_run {
         // Reload the workspace since we've changed dependencies
         let ws = args.workspace(config)?;
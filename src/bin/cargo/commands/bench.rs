@@ -1,5 +1,6 @@
 use crate::command_prelude::*;
 use cargo::ops::{self, TestOptions};
+use std::ffi::OsString;
 
 pub fn cli() -> Command {
     subcommand("bench")
@@ -13,9 +14,11 @@ pub fn cli() -> Command {
         .arg(
             Arg::new("args")
                 .help("Arguments for the bench binary")
+                .value_parser(value_parser!(OsString))
                 .num_args(0..)
                 .last(true),
         )
+        .arg_args_file("Read arguments for the bench binary from a file (unstable)")
         .arg(flag("no-run", "Compile, but don't run benchmarks"))
         .arg(flag(
             "no-fail-fast",
@@ -70,9 +73,11 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         compile_opts,
     };
 
-    let bench_args = args.get_one::<String>("BENCHNAME").into_iter();
-    let bench_args = bench_args.chain(args.get_many::<String>("args").unwrap_or_default());
-    let bench_args = bench_args.map(String::as_str).collect::<Vec<_>>();
+    let bench_args = args.get_one::<String>("BENCHNAME").map(OsString::from);
+    let bench_args = bench_args
+        .into_iter()
+        .chain(args.forwarded_args(config)?)
+        .collect::<Vec<_>>();
 
     ops::run_benches(&ws, &ops, &bench_args)
 }
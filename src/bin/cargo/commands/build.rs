@@ -1,5 +1,6 @@
 use crate::command_prelude::*;
 
+use cargo::core::compiler::{CompileKind, CompileTarget};
 use cargo::ops;
 
 pub fn cli() -> Command {
@@ -33,6 +34,7 @@ pub fn cli() -> Command {
         .arg_profile("Build artifacts with the specified profile")
         .arg_jobs()
         .arg_target_triple("Build for the target triple")
+        .arg_proc_macro_host()
         .arg_target_dir()
         .arg(
             opt(
@@ -42,6 +44,28 @@ pub fn cli() -> Command {
             .value_name("PATH")
             .help_heading(heading::COMPILATION_OPTIONS),
         )
+        .arg(
+            opt(
+                "artifact-dir",
+                "Maintain stable copies of final artifacts here (unstable)",
+            )
+            .value_name("PATH")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+        .arg(
+            flag(
+                "no-deps-rebuild",
+                "Fail instead of rebuilding dependencies (unstable)",
+            )
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+        .arg(
+            flag(
+                "static",
+                "Link the C runtime statically into binaries (unstable)",
+            )
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
         .arg_build_plan()
         .arg_unit_graph()
         .arg_timings()
@@ -69,6 +93,32 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
             .cli_unstable()
             .fail_if_stable_opt("--out-dir", 6790)?;
     }
+    if let Some(artifact_dir) = args.value_of_path("artifact-dir", config) {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--artifact-dir")?;
+        compile_opts.build_config.artifact_dir = Some(artifact_dir);
+    }
+    if args.flag("no-deps-rebuild") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--no-deps-rebuild")?;
+        compile_opts.build_config.no_deps_rebuild = true;
+    }
+    if args.flag("static") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--static")?;
+        // Build for the host explicitly, so that build scripts and
+        // proc-macros are built separately, without `+crt-static`.
+        let host = CompileTarget::new(&config.load_global_rustc(Some(&ws))?.host)?;
+        for kind in &mut compile_opts.build_config.requested_kinds {
+            if kind.is_host() {
+                *kind = CompileKind::Target(host);
+            }
+        }
+        compile_opts.build_config.static_linking = true;
+    }
     ops::compile(&ws, &compile_opts)?;
     Ok(())
 }
@@ -34,6 +34,14 @@ pub fn cli() -> Command {
         .arg_profile("Check artifacts with the specified profile")
         .arg_target_triple("Check for the target triple")
         .arg_target_dir()
+        .arg(
+            flag(
+                "feature-powerset",
+                "Check each package with combinations of its features (unstable)",
+            )
+            .help_heading(heading::FEATURE_SELECTION)
+            .conflicts_with_all(["features", "all-features", "no-default-features"]),
+        )
         .arg_unit_graph()
         .arg_timings()
         .arg_manifest_path()
@@ -51,6 +59,13 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let compile_opts =
         args.compile_options(config, mode, Some(&ws), ProfileChecking::LegacyTestOnly)?;
 
+    if args.flag("feature-powerset") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--feature-powerset")?;
+        return ops::check_feature_powerset(&ws, &compile_opts);
+    }
+
     ops::compile(&ws, &compile_opts)?;
     Ok(())
 }
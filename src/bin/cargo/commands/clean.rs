@@ -1,12 +1,24 @@
 use crate::command_prelude::*;
 
-use cargo::ops::{self, CleanOptions};
-use cargo::util::print_available_packages;
+use cargo::ops::{self, CleanOptions, GcOptions};
+use cargo::util::{parse_size, print_available_packages};
 
 pub fn cli() -> Command {
     subcommand("clean")
         .about("Remove artifacts that cargo has generated in the past")
         .arg_doc("Whether or not to clean just the documentation directory")
+        .arg_dry_run("Display what would be deleted without deleting anything (unstable)")
+        .arg(
+            opt(
+                "keep-days",
+                "Only remove files which weren't modified in the last N days (unstable)",
+            )
+            .value_name("N"),
+        )
+        .arg(flag(
+            "cache-too",
+            "Also purge the workspace from the configured cache backends (unstable)",
+        ))
         .arg_quiet()
         .arg_package_spec_simple("Package to clean artifacts for")
         .arg_release("Whether or not to clean release artifacts")
@@ -14,16 +26,61 @@ pub fn cli() -> Command {
         .arg_target_triple("Target triple to clean output for")
         .arg_target_dir()
         .arg_manifest_path()
+        .subcommand(
+            subcommand("gc")
+                .about("Remove build artifacts which are no longer needed (unstable)")
+                .arg_dry_run("Display what would be deleted without deleting anything")
+                .arg(
+                    opt(
+                        "max-unused-days",
+                        "Also remove the artifacts which weren't used in the last N days",
+                    )
+                    .value_name("N"),
+                )
+                .arg(
+                    opt(
+                        "max-size",
+                        "Also remove the least recently used artifacts down to SIZE per build directory",
+                    )
+                    .value_name("SIZE"),
+                )
+                .arg_quiet()
+                .arg_target_dir()
+                .arg_manifest_path(),
+        )
         .after_help("Run `cargo help clean` for more detailed information.\n")
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    if let Some(("gc", args)) = args.subcommand() {
+        return gc(config, args);
+    }
     let ws = args.workspace(config)?;
 
     if args.is_present_with_zero_values("package") {
         print_available_packages(&ws)?;
     }
 
+    let dry_run = args.dry_run();
+    if dry_run {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--dry-run")?;
+    }
+    let keep_days = args.value_of_u32("keep-days")?;
+    if keep_days.is_some() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--keep-days")?;
+    }
+    let cache_too = args.flag("cache-too");
+    if cache_too && !config.cli_unstable().cache_backends {
+        return Err(anyhow::format_err!(
+            "the `--cache-too` flag is unstable, pass `-Z cache-backends` to enable it"
+        )
+        .into());
+    }
+
     let opts = CleanOptions {
         config,
         spec: values(args, "package"),
@@ -31,7 +88,35 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         requested_profile: args.get_profile_name(config, "dev", ProfileChecking::Custom)?,
         profile_specified: args.contains_id("profile") || args.flag("release"),
         doc: args.flag("doc"),
+        dry_run,
+        keep_days,
+        cache_too,
     };
     ops::clean(&ws, &opts)?;
     Ok(())
 }
+
+fn gc(config: &Config, args: &ArgMatches) -> CliResult {
+    if !config.cli_unstable().target_dir_gc {
+        return Err(anyhow::format_err!(
+            "the `cargo clean gc` command is unstable, pass `-Z target-dir-gc` to enable it"
+        )
+        .into());
+    }
+    let ws = args.workspace(config)?;
+    let mut opts = GcOptions::from_config(config)?;
+    if let Some(days) = args.value_of_u32("max-unused-days")? {
+        opts.max_unused_days = Some(days);
+    }
+    if let Some(size) = args.get_one::<String>("max-size") {
+        opts.max_size = Some(parse_size(size).ok_or_else(|| {
+            anyhow::format_err!(
+                "`--max-size` must be a size such as `20GiB` or `512MiB`, found `{}`",
+                size
+            )
+        })?);
+    }
+    opts.dry_run = args.dry_run();
+    ops::gc(&ws, &opts)?;
+    Ok(())
+}
@@ -28,6 +28,10 @@ pub fn cli() -> Command {
                         .default_value("yes"),
                 ),
         )
+        .subcommand(
+            subcommand("check-mirrors")
+                .about("Check that the sources replacing others can be reached"),
+        )
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
@@ -44,6 +48,9 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
             };
             cargo_config::get(config, &opts)?;
         }
+        Some(("check-mirrors", _)) => {
+            cargo_config::check_mirrors(config)?;
+        }
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
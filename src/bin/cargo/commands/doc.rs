@@ -15,6 +15,13 @@ pub fn cli() -> Command {
             "no-deps",
             "Don't build documentation for dependencies",
         ))
+        .arg(
+            flag(
+                "depgraph-docs",
+                "Document only the public dependencies of the packages (unstable)",
+            )
+            .conflicts_with("no-deps"),
+        )
         .arg(flag("document-private-items", "Document private items"))
         .arg_ignore_rust_version()
         .arg_message_format()
@@ -51,6 +58,12 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let mut compile_opts =
         args.compile_options(config, mode, Some(&ws), ProfileChecking::Custom)?;
     compile_opts.rustdoc_document_private_items = args.flag("document-private-items");
+    if args.flag("depgraph-docs") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--depgraph-docs")?;
+        compile_opts.build_config.depgraph_docs = true;
+    }
 
     let doc_opts = DocOptions {
         open_result: args.flag("open"),
@@ -0,0 +1,26 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("explain-hash")
+        .about("Explain the metadata hash in the name of an artifact (unstable)")
+        .arg(
+            Arg::new("filename")
+                .value_name("FILENAME")
+                .help("Artifact file name or hash to explain, all recorded units if omitted"),
+        )
+        .arg_quiet()
+        .arg_target_dir()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "explain-hash")?;
+    let ws = args.workspace(config)?;
+    let file_name = args.get_one::<String>("filename").map(String::as_str);
+    ops::explain_hash(&ws, file_name)?;
+    Ok(())
+}
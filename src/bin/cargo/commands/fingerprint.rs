@@ -0,0 +1,52 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("fingerprint")
+        .about("Inspect the fingerprints of built units (unstable)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            subcommand("diff")
+                .about("Compare the environment a package was last built in with the current one")
+                .arg(
+                    Arg::new("package")
+                        .value_name("SPEC")
+                        .help("Package to compare the environment of")
+                        .required(true),
+                )
+                .arg_quiet()
+                .arg_release("Compare the environment of release artifacts")
+                .arg_profile("Compare the environment of artifacts of the specified profile")
+                .arg_target_triple("Compare the environment of artifacts for the target triple")
+                .arg_target_dir()
+                .arg_manifest_path(),
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    match args.subcommand() {
+        Some(("diff", args)) => fingerprint_diff(config, args),
+        Some((cmd, _)) => {
+            unreachable!("unexpected command {}", cmd)
+        }
+        None => {
+            unreachable!("unexpected command")
+        }
+    }
+}
+
+fn fingerprint_diff(config: &Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "fingerprint diff")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::FingerprintDiffOptions {
+        spec: args.get_one::<String>("package").cloned().unwrap(),
+        targets: args.targets(),
+        requested_profile: args.get_profile_name(config, "dev", ProfileChecking::Custom)?,
+    };
+    ops::fingerprint_diff(&ws, &opts)?;
+    Ok(())
+}
@@ -65,6 +65,13 @@ pub fn cli() -> Command {
             "list",
             "list all installed packages and their versions",
         ))
+        .arg(
+            flag(
+                "outdated",
+                "With --list, only list packages with a newer version available",
+            )
+            .requires("list"),
+        )
         .arg_ignore_rust_version()
         .arg_message_format()
         .arg_quiet()
@@ -84,6 +91,13 @@ pub fn cli() -> Command {
         .arg_target_triple("Build for the target triple")
         .arg_target_dir()
         .arg_timings()
+        .arg(
+            flag(
+                "ignore-lockfile",
+                "Ignore the published Cargo.lock of the package",
+            )
+            .help_heading(heading::MANIFEST_OPTIONS),
+        )
         .after_help("Run `cargo help install` for more detailed information.\n")
 }
 
@@ -174,7 +188,7 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         args.get_profile_name(config, "release", ProfileChecking::Custom)?;
 
     if args.flag("list") {
-        ops::install_list(root, config)?;
+        ops::install_list(root, args.flag("outdated"), config)?;
     } else {
         ops::install(
             config,
@@ -185,6 +199,7 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
             &compile_opts,
             args.flag("force"),
             args.flag("no-track"),
+            args.flag("ignore-lockfile"),
         )?;
     }
     Ok(())
@@ -0,0 +1,47 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+use std::path::PathBuf;
+
+pub fn cli() -> Command {
+    subcommand("members")
+        .about("List the members of a workspace and the members depending on them (unstable)")
+        .arg(
+            multi_opt(
+                "dependents-of",
+                "SPEC",
+                "List the members depending on the given member",
+            )
+            .conflicts_with("paths-changed"),
+        )
+        .arg(
+            multi_opt(
+                "paths-changed",
+                "PATH",
+                "List the members owning the given files and their dependents",
+            )
+            .num_args(1..)
+            .value_parser(value_parser!(PathBuf)),
+        )
+        .arg_quiet()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "members")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::MembersOptions {
+        dependents_of: args._values_of("dependents-of"),
+        paths_changed: args
+            .get_many::<PathBuf>("paths-changed")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+    };
+    for pkg in ops::members(&ws, &opts)? {
+        cargo::drop_println!(config, "{}", pkg.name());
+    }
+    Ok(())
+}
@@ -18,6 +18,21 @@ pub fn cli() -> Command {
             "Output information only about the workspace members \
              and don't fetch dependencies",
         ))
+        .arg(
+            opt(
+                "depth",
+                "Limit the resolve graph to this depth of dependencies (unstable)",
+            )
+            .value_name("DEPTH")
+            .conflicts_with("no-deps"),
+        )
+        .arg(
+            flag(
+                "no-deps-features",
+                "Skip resolving features and platforms of dependencies (unstable)",
+            )
+            .conflicts_with_all(["no-deps", "filter-platform"]),
+        )
         .arg(
             opt("format-version", "Format version")
                 .value_name("VERSION")
@@ -43,14 +58,29 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         Some(version) => version.parse().unwrap(),
     };
 
+    let max_depth = args.value_of_u32("depth")?;
+    if max_depth.is_some() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--depth")?;
+    }
+    let no_deps_features = args.flag("no-deps-features");
+    if no_deps_features {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--no-deps-features")?;
+    }
+
     let options = OutputMetadataOptions {
-        cli_features: args.cli_features()?,
+        cli_features: args.cli_features(Some(&ws))?,
         no_deps: args.flag("no-deps"),
         filter_platforms: args._values_of("filter-platform"),
+        max_depth,
+        no_deps_features,
         version,
     };
 
-    let result = ops::output_metadata(&ws, &options)?;
-    config.shell().print_json(&result)?;
+    let output = ops::output_metadata_json(&ws, &options)?;
+    cargo::drop_println!(config, "{}", output);
     Ok(())
 }
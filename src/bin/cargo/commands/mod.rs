@@ -9,7 +9,9 @@ pub fn builtin() -> Vec<Command> {
         clean::cli(),
         config::cli(),
         doc::cli(),
+        explain_hash::cli(),
         fetch::cli(),
+        fingerprint::cli(),
         fix::cli(),
         generate_lockfile::cli(),
         git_checkout::cli(),
@@ -19,14 +21,18 @@ pub fn builtin() -> Vec<Command> {
         locate_project::cli(),
         login::cli(),
         logout::cli(),
+        members::cli(),
         metadata::cli(),
         new::cli(),
         owner::cli(),
         package::cli(),
+        patch::cli(),
         pkgid::cli(),
         publish::cli(),
         read_manifest::cli(),
+        release_plan::cli(),
         remove::cli(),
+        remove_member::cli(),
         report::cli(),
         run::cli(),
         rustc::cli(),
@@ -37,8 +43,10 @@ pub fn builtin() -> Vec<Command> {
         uninstall::cli(),
         update::cli(),
         vendor::cli(),
+        verify_lockfile::cli(),
         verify_project::cli(),
         version::cli(),
+        version_check::cli(),
         yank::cli(),
     ]
 }
@@ -54,7 +62,9 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "clean" => clean::exec,
         "config" => config::exec,
         "doc" => doc::exec,
+        "explain-hash" => explain_hash::exec,
         "fetch" => fetch::exec,
+        "fingerprint" => fingerprint::exec,
         "fix" => fix::exec,
         "generate-lockfile" => generate_lockfile::exec,
         "git-checkout" => git_checkout::exec,
@@ -64,14 +74,18 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "locate-project" => locate_project::exec,
         "login" => login::exec,
         "logout" => logout::exec,
+        "members" => members::exec,
         "metadata" => metadata::exec,
         "new" => new::exec,
         "owner" => owner::exec,
         "package" => package::exec,
+        "patch" => patch::exec,
         "pkgid" => pkgid::exec,
         "publish" => publish::exec,
         "read-manifest" => read_manifest::exec,
+        "release-plan" => release_plan::exec,
         "remove" => remove::exec,
+        "remove-member" => remove_member::exec,
         "report" => report::exec,
         "run" => run::exec,
         "rustc" => rustc::exec,
@@ -82,8 +96,10 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "uninstall" => uninstall::exec,
         "update" => update::exec,
         "vendor" => vendor::exec,
+        "verify-lockfile" => verify_lockfile::exec,
         "verify-project" => verify_project::exec,
         "version" => version::exec,
+        "version-check" => version_check::exec,
         "yank" => yank::exec,
         _ => return None,
     };
@@ -97,7 +113,9 @@ pub mod check;
 pub mod clean;
 pub mod config;
 pub mod doc;
+pub mod explain_hash;
 pub mod fetch;
+pub mod fingerprint;
 pub mod fix;
 pub mod generate_lockfile;
 pub mod git_checkout;
@@ -107,14 +125,18 @@ pub mod install;
 pub mod locate_project;
 pub mod login;
 pub mod logout;
+pub mod members;
 pub mod metadata;
 pub mod new;
 pub mod owner;
 pub mod package;
+pub mod patch;
 pub mod pkgid;
 pub mod publish;
 pub mod read_manifest;
+pub mod release_plan;
 pub mod remove;
+pub mod remove_member;
 pub mod report;
 pub mod run;
 pub mod rustc;
@@ -125,6 +147,8 @@ pub mod tree;
 pub mod uninstall;
 pub mod update;
 pub mod vendor;
+pub mod verify_lockfile;
 pub mod verify_project;
 pub mod version;
+pub mod version_check;
 pub mod yank;
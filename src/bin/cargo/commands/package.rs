@@ -1,6 +1,6 @@
 use crate::command_prelude::*;
 
-use cargo::ops::{self, PackageOpts};
+use cargo::ops::{self, CheckFormat, PackageOpts};
 
 pub fn cli() -> Command {
     subcommand("package")
@@ -24,6 +24,19 @@ pub fn cli() -> Command {
             "allow-dirty",
             "Allow dirty working directories to be packaged",
         ))
+        .arg(
+            flag(
+                "check-consistency",
+                "Only report the checks done before packaging (unstable)",
+            )
+            .conflicts_with("list"),
+        )
+        .arg(
+            opt("format", "Format of the checks (unstable)")
+                .value_name("FMT")
+                .value_parser(["text", "json"])
+                .requires("check-consistency"),
+        )
         .arg_quiet()
         .arg_package_spec_no_all(
             "Package(s) to assemble",
@@ -48,6 +61,17 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         .into());
     }
     let specs = args.packages_from_flags()?;
+    let check_consistency = if args.flag("check-consistency") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--check-consistency")?;
+        match args.get_one::<String>("format").map(String::as_str) {
+            Some("json") => Some(CheckFormat::Json),
+            _ => Some(CheckFormat::Text),
+        }
+    } else {
+        None
+    };
 
     ops::package(
         &ws,
@@ -61,7 +85,8 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
             targets: args.targets(),
             jobs: args.jobs()?,
             keep_going: args.keep_going(),
-            cli_features: args.cli_features()?,
+            cli_features: args.cli_features(Some(&ws))?,
+            check_consistency,
         },
     )?;
 
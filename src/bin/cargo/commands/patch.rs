@@ -0,0 +1,35 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("patch")
+        .about("Inspect the `[patch]` table of a workspace (unstable)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            subcommand("status")
+                .about("Report whether each patch is used, and what it replaces")
+                .arg_quiet()
+                .arg_manifest_path(),
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "patch")?;
+    match args.subcommand() {
+        Some(("status", args)) => {
+            let ws = args.workspace(config)?;
+            ops::patch_status(&ws)?;
+        }
+        Some((cmd, _)) => {
+            unreachable!("unexpected command {}", cmd)
+        }
+        None => {
+            unreachable!("unexpected command")
+        }
+    }
+    Ok(())
+}
@@ -7,6 +7,11 @@ pub fn cli() -> Command {
     subcommand("pkgid")
         .about("Print a fully qualified package specification")
         .arg(Arg::new("spec").action(ArgAction::Set))
+        .arg(
+            opt("format", "Output format (unstable)")
+                .value_name("FMT")
+                .value_parser(["text", "json"]),
+        )
         .arg_quiet()
         .arg_package("Argument to get the package ID specifier for")
         .arg_manifest_path()
@@ -29,7 +34,18 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         .get_one::<String>("spec")
         .or_else(|| args.get_one::<String>("package"))
         .map(String::as_str);
-    let spec = ops::pkgid(&ws, spec)?;
-    cargo::drop_println!(config, "{}", spec);
+    let format = args.get_one::<String>("format").map(String::as_str);
+    if format.is_some() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--format")?;
+    }
+    if format == Some("json") {
+        let info = ops::pkgid_info(&ws, spec)?;
+        config.shell().print_json(&info)?;
+    } else {
+        let spec = ops::pkgid(&ws, spec)?;
+        cargo::drop_println!(config, "{}", spec);
+    }
     Ok(())
 }
@@ -18,7 +18,20 @@ pub fn cli() -> Command {
             "Allow dirty working directories to be packaged",
         ))
         .arg_quiet()
-        .arg_package("Package to publish")
+        .arg_package_spec_no_all(
+            "Package(s) to publish",
+            "Publish all packages in the workspace (unstable)",
+            "Don't publish specified packages (unstable)",
+        )
+        .arg(
+            opt(
+                "group",
+                "Publish a group of `[workspace.metadata.publish-groups]` (unstable)",
+            )
+            .value_name("NAME")
+            .conflicts_with_all(["package", "workspace"])
+            .help_heading(heading::PACKAGE_SELECTION),
+        )
         .arg_features()
         .arg_jobs()
         .arg_target_triple("Build for the target triple")
@@ -38,6 +51,13 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         .into());
     }
     let index = args.index()?;
+    let group = args.get_one::<String>("group").cloned();
+    let to_publish = if group.is_some() {
+        // A group selects from all members, like `--workspace`.
+        ops::Packages::from_flags(true, args._values_of("exclude"), Vec::new())?
+    } else {
+        args.packages_from_flags()?
+    };
 
     ops::publish(
         &ws,
@@ -49,13 +69,14 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
             index,
             verify: !args.flag("no-verify"),
             allow_dirty: args.flag("allow-dirty"),
-            to_publish: args.packages_from_flags()?,
+            to_publish,
             targets: args.targets(),
             jobs: args.jobs()?,
             keep_going: args.keep_going(),
             dry_run: args.dry_run(),
             registry,
-            cli_features: args.cli_features()?,
+            cli_features: args.cli_features(Some(&ws))?,
+            group,
         },
     )?;
     Ok(())
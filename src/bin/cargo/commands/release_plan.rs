@@ -0,0 +1,32 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("release-plan")
+        .about("Print the plan for publishing the members of a workspace as JSON (unstable)")
+        .arg(opt("registry", "Registry to plan publishing to").value_name("REGISTRY"))
+        .arg(
+            opt(
+                "base-rev",
+                "Git revision of the previous release, to find members needing a bump",
+            )
+            .value_name("REV"),
+        )
+        .arg_quiet()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "release-plan")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::ReleasePlanOptions {
+        registry: args.registry(config)?,
+        base_rev: args.get_one::<String>("base-rev").cloned(),
+    };
+    let plan = ops::release_plan(&ws, &opts)?;
+    config.shell().print_json(&plan)?;
+    Ok(())
+}
@@ -206,6 +206,10 @@ fn gc_workspace(workspace: &Workspace<'_>) -> CargoResult<()> {
             }
 
             if !is_used {
+                workspace.config().shell().status(
+                    "Removing",
+                    format!("{} from workspace.dependencies", key.get()),
+                )?;
                 *item = toml_edit::Item::None;
                 is_modified = true;
             }
@@ -221,7 +225,7 @@ fn gc_workspace(workspace: &Workspace<'_>) -> CargoResult<()> {
     if let Some(toml_edit::Item::Table(profile_section_table)) = manifest.get_mut("profile") {
         profile_section_table.set_implicit(true);
 
-        for (_, item) in profile_section_table.iter_mut() {
+        for (profile, item) in profile_section_table.iter_mut() {
             if let toml_edit::Item::Table(profile_table) = item {
                 profile_table.set_implicit(true);
 
@@ -236,6 +240,10 @@ fn gc_workspace(workspace: &Workspace<'_>) -> CargoResult<()> {
                             &dependencies,
                             workspace.config(),
                         )? {
+                            workspace.config().shell().status(
+                                "Removing",
+                                format!("{} from profile.{}.package", key.get(), profile.get()),
+                            )?;
                             *item = toml_edit::Item::None;
                             is_modified = true;
                         }
@@ -255,6 +263,10 @@ fn gc_workspace(workspace: &Workspace<'_>) -> CargoResult<()> {
                 &dependencies,
                 workspace.config(),
             )? {
+                workspace
+                    .config()
+                    .shell()
+                    .status("Removing", format!("{} from replace", key.get()))?;
                 *item = toml_edit::Item::None;
                 is_modified = true;
             }
@@ -262,7 +274,10 @@ fn gc_workspace(workspace: &Workspace<'_>) -> CargoResult<()> {
     }
 
     if is_modified {
-        cargo_util::paths::write(workspace.root_manifest(), manifest.to_string().as_bytes())?;
+        cargo_util::paths::write_atomic(
+            workspace.root_manifest(),
+            manifest.to_string().as_bytes(),
+        )?;
     }
 
     Ok(())
@@ -311,7 +326,7 @@ fn gc_unused_patches(workspace: &Workspace<'_>, resolve: &Resolve) -> CargoResul
     if let Some(toml_edit::Item::Table(patch_section_table)) = manifest.get_mut("patch") {
         patch_section_table.set_implicit(true);
 
-        for (_, item) in patch_section_table.iter_mut() {
+        for (source, item) in patch_section_table.iter_mut() {
             if let toml_edit::Item::Table(patch_table) = item {
                 patch_table.set_implicit(true);
 
@@ -330,6 +345,10 @@ fn gc_unused_patches(workspace: &Workspace<'_>, resolve: &Resolve) -> CargoResul
                     if PackageIdSpec::query_str(&url, resolve.unused_patches().iter().cloned())
                         .is_ok()
                     {
+                        workspace.config().shell().status(
+                            "Removing",
+                            format!("{} from patch.{}", key.get(), source.get()),
+                        )?;
                         *item = toml_edit::Item::None;
                         modified = true;
                     }
@@ -339,7 +358,10 @@ fn gc_unused_patches(workspace: &Workspace<'_>, resolve: &Resolve) -> CargoResul
     }
 
     if modified {
-        cargo_util::paths::write(workspace.root_manifest(), manifest.to_string().as_bytes())?;
+        cargo_util::paths::write_atomic(
+            workspace.root_manifest(),
+            manifest.to_string().as_bytes(),
+        )?;
     }
 
     Ok(modified)
@@ -0,0 +1,33 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("remove-member")
+        .about("Remove a member from the workspace (unstable)")
+        .arg(
+            Arg::new("member")
+                .value_name("MEMBER")
+                .help("Name of the member to remove")
+                .required(true),
+        )
+        .arg(flag(
+            "rewrite-deps",
+            "Depend on the member's version from the registry instead of its path",
+        ))
+        .arg_quiet()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "remove-member")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::RemoveMemberOptions {
+        member: args.get_one::<String>("member").cloned().unwrap(),
+        rewrite_deps: args.flag("rewrite-deps"),
+    };
+    ops::remove_member(&ws, &opts)?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::command_prelude::*;
 use cargo::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
 use cargo::drop_println;
+use cargo::ops;
 
 pub fn cli() -> Command {
     subcommand("report")
@@ -21,11 +22,19 @@ pub fn cli() -> Command {
                 )
                 .arg_package("Package to display a report for"),
         )
+        .subcommand(
+            subcommand("features")
+                .about("Explains why each feature of a package is enabled (unstable)")
+                .arg_package("Package to display the features of")
+                .arg_features()
+                .arg_manifest_path(),
+        )
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     match args.subcommand() {
         Some(("future-incompatibilities", args)) => report_future_incompatibilities(config, args),
+        Some(("features", args)) => report_features(config, args),
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
@@ -47,3 +56,16 @@ fn report_future_incompatibilities(config: &Config, args: &ArgMatches) -> CliRes
     drop(config.shell().print_ansi_stdout(report.as_bytes()));
     Ok(())
 }
+
+fn report_features(config: &Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "report features")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::ReportFeaturesOptions {
+        cli_features: args.cli_features(Some(&ws))?,
+        package: args.get_one::<String>("package").cloned(),
+    };
+    ops::report_features(&ws, &opts)?;
+    Ok(())
+}
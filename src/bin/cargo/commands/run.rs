@@ -21,6 +21,7 @@ pub fn cli() -> Command {
                 .num_args(0..)
                 .trailing_var_arg(true),
         )
+        .arg_args_file("Read arguments for the binary or example from a file (unstable)")
         .arg_ignore_rust_version()
         .arg_message_format()
         .arg_quiet()
@@ -82,7 +83,8 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         }
     };
 
-    ops::run(&ws, &compile_opts, &values_os(args, "args")).map_err(|err| to_run_error(config, err))
+    let run_args = args.forwarded_args(config)?;
+    ops::run(&ws, &compile_opts, &run_args).map_err(|err| to_run_error(config, err))
 }
 
 /// See also `util/toml/mod.rs`s `is_embedded`
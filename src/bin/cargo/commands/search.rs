@@ -15,6 +15,12 @@ pub fn cli() -> Command {
             )
             .value_name("LIMIT"),
         )
+        .arg(opt("page", "Page of the results to show (unstable)").value_name("PAGE"))
+        .arg(
+            opt("format", "Format of the results (unstable)")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"]),
+        )
         .arg_index()
         .arg(opt("registry", "Registry to use").value_name("REGISTRY"))
         .arg_quiet()
@@ -32,6 +38,26 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         .map(String::as_str)
         .collect();
     let query: String = query.join("+");
-    ops::search(&query, config, index, limit, registry)?;
+    let page = args.value_of_u32("page")?;
+    if page.is_some() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--page")?;
+    }
+    let page = page.unwrap_or(1).max(1);
+    let format = match args.get_one::<String>("format").map(String::as_str) {
+        Some(format) => {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--format")?;
+            if format == "json" {
+                ops::SearchFormat::Json
+            } else {
+                ops::SearchFormat::Text
+            }
+        }
+        None => ops::SearchFormat::Text,
+    };
+    ops::search(&query, config, index, limit, page, format, registry)?;
     Ok(())
 }
@@ -1,5 +1,7 @@
 use crate::command_prelude::*;
 use cargo::ops;
+use std::ffi::OsString;
+use std::path::PathBuf;
 
 pub fn cli() -> Command {
     subcommand("test")
@@ -14,9 +16,11 @@ pub fn cli() -> Command {
         .arg(
             Arg::new("args")
                 .help("Arguments for the test binary")
+                .value_parser(value_parser!(OsString))
                 .num_args(0..)
                 .last(true),
         )
+        .arg_args_file("Read arguments for the test binary from a file (unstable)")
         .arg(flag("doc", "Test only this library's documentation"))
         .arg(flag("no-run", "Compile, but don't run tests"))
         .arg(flag("no-fail-fast", "Run all tests regardless of failure"))
@@ -35,6 +39,17 @@ pub fn cli() -> Command {
             "Test all packages in the workspace",
             "Exclude packages from the test",
         )
+        .arg(
+            multi_opt(
+                "affected-by",
+                "PATH",
+                "Test only the packages affected by the given files (unstable)",
+            )
+            .num_args(1..)
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with_all(["package", "workspace", "exclude", "all"])
+            .help_heading(heading::PACKAGE_SELECTION),
+        )
         .arg_targets_all(
             "Test only this package's library unit tests",
             "Test only the specified binary",
@@ -52,6 +67,16 @@ pub fn cli() -> Command {
         .arg_release("Build artifacts in release mode, with optimizations")
         .arg_profile("Build artifacts with the specified profile")
         .arg_target_triple("Build for the target triple")
+        .arg_proc_macro_host()
+        .arg(
+            multi_opt(
+                "target-matrix",
+                "TARGET",
+                "Test for each target, given as TRIPLE[=RUNNER] (unstable)",
+            )
+            .conflicts_with("target")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
         .arg_target_dir()
         .arg_unit_graph()
         .arg_timings()
@@ -78,9 +103,10 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     // `TESTNAME` is actually an argument of the test binary, but it's
     // important, so we explicitly mention it and reconfigure.
     let test_name = args.get_one::<String>("TESTNAME");
-    let test_args = args.get_one::<String>("TESTNAME").into_iter();
-    let test_args = test_args.chain(args.get_many::<String>("args").unwrap_or_default());
-    let test_args = test_args.map(String::as_str).collect::<Vec<_>>();
+    let test_args = test_name.map(OsString::from).into_iter();
+    let test_args = test_args
+        .chain(args.forwarded_args(config)?)
+        .collect::<Vec<_>>();
 
     let no_run = args.flag("no-run");
     let doc = args.flag("doc");
@@ -103,11 +129,57 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         compile_opts.filter = ops::CompileFilter::all_test_targets();
     }
 
+    if let Some(changed) = args.get_many::<PathBuf>("affected-by") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--affected-by")?;
+        let changed: Vec<_> = changed.cloned().collect();
+        let affected = ops::affected(&ws, &changed)?;
+        if affected.is_empty() {
+            config
+                .shell()
+                .note("no packages are affected by the given files, nothing to test")?;
+            return Ok(());
+        }
+        for (pkg, reason) in &affected {
+            let why = match reason {
+                ops::AffectedReason::Changed(path) => format!("`{}` changed", path.display()),
+                ops::AffectedReason::DependsOn(id) => format!("depends on `{}`", id.name()),
+            };
+            config
+                .shell()
+                .status("Selected", format!("{} ({})", pkg.name(), why))?;
+        }
+        compile_opts.spec = ops::Packages::Packages(
+            affected
+                .iter()
+                .map(|(pkg, _)| pkg.name().to_string())
+                .collect(),
+        );
+    }
+
+    let matrix = match args.get_many::<String>("target-matrix") {
+        Some(entries) => {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--target-matrix")?;
+            let entries: Vec<_> = entries.map(String::as_str).collect();
+            let matrix = ops::MatrixTarget::parse_all(config, &entries)?;
+            compile_opts.build_config.requested_kinds =
+                matrix.iter().map(|target| target.kind).collect();
+            Some(matrix)
+        }
+        None => None,
+    };
+
     let ops = ops::TestOptions {
         no_run,
         no_fail_fast: args.flag("no-fail-fast"),
         compile_opts,
     };
 
-    ops::run_tests(&ws, &ops, &test_args)
+    match matrix {
+        Some(matrix) => ops::run_test_matrix(&ws, &ops, &matrix, &test_args),
+        None => ops::run_tests(&ws, &ops, &test_args),
+    }
 }
@@ -66,6 +66,15 @@ pub fn cli() -> Command {
             .short('d')
             .alias("duplicate"),
         )
+        .arg(flag(
+            "audit-defaults",
+            "Show what enables default features disabled on dependencies (unstable)",
+        ))
+        .arg(multi_opt(
+            "why-feature",
+            "NAME",
+            "Only show what enables the given feature, as [PKG/]NAME (unstable)",
+        ))
         .arg(
             opt("charset", "Character set to use in output")
                 .value_name("CHARSET")
@@ -140,7 +149,21 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     };
     let target = tree::Target::from_cli(targets);
 
-    let (edge_kinds, no_proc_macro) = parse_edge_kinds(config, args)?;
+    let (mut edge_kinds, no_proc_macro) = parse_edge_kinds(config, args)?;
+    let audit_default_features = args.flag("audit-defaults");
+    if audit_default_features {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--audit-defaults")?;
+        edge_kinds.insert(EdgeKind::Feature);
+    }
+    let why_features = args._values_of("why-feature");
+    if !why_features.is_empty() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--why-feature")?;
+        edge_kinds.insert(EdgeKind::Feature);
+    }
     let graph_features = edge_kinds.contains(&EdgeKind::Feature);
 
     let pkgs_to_prune = args._values_of("prune");
@@ -185,7 +208,7 @@ subtree of the package given to -p.\n\
     let charset = tree::Charset::from_str(args.get_one::<String>("charset").unwrap())
         .map_err(|e| anyhow::anyhow!("{}", e))?;
     let opts = tree::TreeOptions {
-        cli_features: args.cli_features()?,
+        cli_features: args.cli_features(Some(&ws))?,
         packages,
         target,
         edge_kinds,
@@ -199,8 +222,21 @@ subtree of the package given to -p.\n\
         graph_features,
         max_display_depth: args.value_of_u32("depth")?.unwrap_or(u32::MAX),
         no_proc_macro,
+        audit_default_features,
+        why_features,
     };
 
+    if opts.audit_default_features && !opts.why_features.is_empty() {
+        return Err(
+            format_err!("the `--audit-defaults` flag does not support `--why-feature`").into(),
+        );
+    }
+    if opts.audit_default_features && (!opts.invert.is_empty() || opts.duplicates) {
+        return Err(format_err!(
+            "the `--audit-defaults` flag does not support `--invert` or `--duplicates`"
+        )
+        .into());
+    }
     if opts.graph_features && opts.duplicates {
         return Err(format_err!("the `-e features` flag does not support `--duplicates`").into());
     }
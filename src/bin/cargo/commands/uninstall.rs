@@ -7,8 +7,14 @@ pub fn cli() -> Command {
         .about("Remove a Rust binary")
         .arg(Arg::new("spec").num_args(0..))
         .arg(opt("root", "Directory to uninstall packages from").value_name("DIR"))
+        .arg_dry_run("Display what would be removed without removing anything")
         .arg_quiet()
         .arg_package_spec_simple("Package to uninstall")
+        .arg(
+            flag("all", "Uninstall all installed packages")
+                .conflicts_with_all(["spec", "package", "bin"])
+                .help_heading(heading::PACKAGE_SELECTION),
+        )
         .arg(
             multi_opt("bin", "NAME", "Only uninstall the binary NAME")
                 .help_heading(heading::TARGET_SELECTION),
@@ -32,6 +38,13 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         .unwrap_or_else(|| args.get_many::<String>("package").unwrap_or_default())
         .map(String::as_str)
         .collect();
-    ops::uninstall(root, specs, &values(args, "bin"), config)?;
+    ops::uninstall(
+        root,
+        specs,
+        &values(args, "bin"),
+        args.flag("all"),
+        args.dry_run(),
+        config,
+    )?;
     Ok(())
 }
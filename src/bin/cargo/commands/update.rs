@@ -22,6 +22,13 @@ pub fn cli() -> Command {
             .value_name("PRECISE")
             .requires("package"),
         )
+        .arg(
+            flag(
+                "breaking",
+                "Bump requirements on SPEC to its latest version (unstable)",
+            )
+            .conflicts_with_all(["aggressive", "precise"]),
+        )
         .arg_quiet()
         .arg(
             flag("workspace", "Only update the workspace packages")
@@ -40,12 +47,20 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         print_available_packages(&ws)?;
     }
 
+    let breaking = args.flag("breaking");
+    if breaking {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--breaking")?;
+    }
+
     let update_opts = UpdateOptions {
         aggressive: args.flag("aggressive"),
         precise: args.get_one::<String>("precise").map(String::as_str),
         to_update: values(args, "package"),
         dry_run: args.dry_run(),
         workspace: args.flag("workspace"),
+        breaking,
         config,
     };
     ops::update_lockfile(&ws, &update_opts)?;
@@ -32,6 +32,19 @@ pub fn cli() -> Command {
             "versioned-dirs",
             "Always include version in subdir name",
         ))
+        .arg(flag(
+            "no-dev",
+            "Don't vendor packages only needed by dev-dependencies (unstable)",
+        ))
+        .arg(multi_opt(
+            "platform",
+            "TRIPLE",
+            "Only vendor packages needed on the given platforms (unstable)",
+        ))
+        .arg(flag(
+            "thin",
+            "Leave out tests and benchmarks of vendored packages (unstable)",
+        ))
         .arg(flag("no-merge-sources", "Not supported").hide(true))
         .arg(flag("relative-path", "Not supported").hide(true))
         .arg(flag("only-git-deps", "Not supported").hide(true))
@@ -78,6 +91,22 @@ https://github.com/rust-lang/cargo/issues/new
         .into());
     }
 
+    let no_dev = args.flag("no-dev");
+    let platforms = args._values_of("platform");
+    let thin = args.flag("thin");
+    let unstable_flag = if no_dev {
+        Some("--no-dev")
+    } else if !platforms.is_empty() {
+        Some("--platform")
+    } else if thin {
+        Some("--thin")
+    } else {
+        None
+    };
+    if let Some(flag) = unstable_flag {
+        config.cli_unstable().fail_if_stable_opt_untracked(flag)?;
+    }
+
     let ws = args.workspace(config)?;
     let path = args
         .get_one::<PathBuf>("path")
@@ -94,6 +123,9 @@ https://github.com/rust-lang/cargo/issues/new
                 .unwrap_or_default()
                 .cloned()
                 .collect(),
+            no_dev,
+            platforms,
+            thin,
         },
     )?;
     Ok(())
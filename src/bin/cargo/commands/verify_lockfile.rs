@@ -0,0 +1,19 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("verify-lockfile")
+        .about("Verify the sources of dependencies against Cargo.lock without building (unstable)")
+        .arg_quiet()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "verify-lockfile")?;
+    let ws = args.workspace(config)?;
+    ops::verify_lockfile(&ws)?;
+    Ok(())
+}
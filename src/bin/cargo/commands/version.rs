@@ -1,16 +1,93 @@
 use crate::cli;
 use crate::command_prelude::*;
 
+use serde::Serialize;
+
 pub fn cli() -> Command {
     subcommand("version")
         .about("Show version information")
+        .arg(
+            opt(
+                "message-format",
+                "Output representation [possible values: human, json]",
+            )
+            .value_name("FMT"),
+        )
         .arg_quiet()
         .after_help("Run `cargo help version` for more detailed information.\n")
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
-    let verbose = args.verbose() > 0;
-    let version = cli::get_version_string(verbose);
-    cargo::drop_print!(config, "{}", version);
+    let fmt = args
+        .get_one::<String>("message-format")
+        .map(|s| s.to_ascii_lowercase());
+    match fmt.as_deref() {
+        None | Some("human") => {
+            let verbose = args.verbose() > 0;
+            let version = cli::get_version_string(verbose);
+            cargo::drop_print!(config, "{}", version);
+        }
+        Some("json") => {
+            let info = VersionJson::new(config);
+            config.shell().print_json(&info)?;
+        }
+        Some(s) => {
+            return Err(anyhow::format_err!("invalid message format specifier: `{}`", s).into())
+        }
+    }
     Ok(())
 }
+
+/// The `--message-format json` form of `cargo version`.
+///
+/// Fields may be added over time, but existing ones keep their meaning.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct VersionJson {
+    version: String,
+    release_channel: Option<String>,
+    commit_hash: Option<String>,
+    commit_date: Option<String>,
+    host: &'static str,
+    /// Whether `-Z` flags and `cargo-features` are accepted.
+    nightly_features_allowed: bool,
+    /// The `-Z` flags in effect, from the command line or `[unstable]`.
+    unstable_flags: Vec<String>,
+    message_formats: &'static [&'static str],
+    metadata_format_versions: &'static [u32],
+    lockfile_versions: Vec<u32>,
+}
+
+impl VersionJson {
+    fn new(config: &Config) -> VersionJson {
+        let version = cargo::version();
+        let (commit_hash, commit_date) = match version.commit_info {
+            Some(ci) => (Some(ci.commit_hash), Some(ci.commit_date)),
+            None => (None, None),
+        };
+        let unstable = config.cli_unstable();
+        let mut lockfile_versions = vec![1, 2, 3];
+        if config.nightly_features_allowed && unstable.next_lockfile_bump {
+            lockfile_versions.push(4);
+        }
+        VersionJson {
+            version: version.version,
+            release_channel: version.release_channel,
+            commit_hash,
+            commit_date,
+            host: env!("RUST_HOST_TARGET"),
+            nightly_features_allowed: config.nightly_features_allowed,
+            unstable_flags: unstable.enabled(),
+            message_formats: &[
+                "human",
+                "short",
+                "json",
+                "json-diagnostic-short",
+                "json-diagnostic-rendered-ansi",
+                "json-render-diagnostics",
+            ],
+            metadata_format_versions: &[1],
+            lockfile_versions,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::command_prelude::*;
+
+use cargo::ops;
+use std::fmt::Write;
+
+pub fn cli() -> Command {
+    subcommand("version-check")
+        .about("Check that changed workspace members had their version bumped (unstable)")
+        .arg(
+            opt("base-rev", "Git revision the changes are made on top of")
+                .value_name("REV")
+                .required(true),
+        )
+        .arg(opt("head-rev", "Git revision with the changes [default: HEAD]").value_name("REV"))
+        .arg(
+            opt(
+                "baseline-rev",
+                "Git revision to compare versions against [default: base-rev]",
+            )
+            .value_name("REV"),
+        )
+        .arg_quiet()
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command_untracked(config, "version-check")?;
+    let ws = args.workspace(config)?;
+    let opts = ops::VersionCheckOptions {
+        base_rev: args.get_one::<String>("base-rev").unwrap().clone(),
+        head_rev: args.get_one::<String>("head-rev").cloned(),
+        baseline_rev: args.get_one::<String>("baseline-rev").cloned(),
+    };
+    let check = ops::version_check(&ws, &opts)?;
+    for pkg in &check.changed {
+        config
+            .shell()
+            .status("Changed", format!("{} v{}", pkg.name(), pkg.version()))?;
+    }
+    if !check.needs_bump.is_empty() {
+        let mut msg = String::new();
+        msg.push_str("detected changes in these packages but no version bump found:\n");
+        for pkg in &check.needs_bump {
+            writeln!(&mut msg, "  {}@{}", pkg.name(), pkg.version()).unwrap();
+        }
+        msg.push_str("\nbump at least the patch version in each corresponding Cargo.toml");
+        return Err(anyhow::format_err!(msg).into());
+    }
+    config
+        .shell()
+        .status("Finished", "no version bump needed for changed members")?;
+    Ok(())
+}
@@ -12,6 +12,21 @@ pub fn cli() -> Command {
                 .alias("vers")
                 .value_name("VERSION"),
         )
+        .arg(
+            opt(
+                "versions",
+                "Yank all the versions matching the requirement (unstable)",
+            )
+            .value_name("REQ")
+            .conflicts_with("version"),
+        )
+        .arg(
+            flag(
+                "yes",
+                "Skip the confirmation of the versions to yank (unstable)",
+            )
+            .requires("versions"),
+        )
         .arg(flag(
             "undo",
             "Undo a yank, putting a version back into the index",
@@ -30,7 +45,15 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         args.get_one::<String>("crate").map(String::as_str),
         args.get_one::<String>("version").map(String::as_str),
     )?;
-    if version.is_none() {
+    let versions = args.get_one::<String>("versions").cloned();
+    if versions.is_some() {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt_untracked("--versions")?;
+        if let Some(v) = version {
+            return Err(anyhow::format_err!("cannot specify both `@{v}` and `--versions`").into());
+        }
+    } else if version.is_none() {
         return Err(anyhow::format_err!("`--version` is required").into());
     }
 
@@ -38,6 +61,8 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         config,
         krate.map(|s| s.to_string()),
         version.map(|s| s.to_string()),
+        versions,
+        args.flag("yes"),
         args.get_one::<String>("token").cloned().map(Secret::from),
         args.get_one::<String>("index").cloned(),
         args.flag("undo"),
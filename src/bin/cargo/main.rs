@@ -31,9 +31,21 @@ fn main() {
         cli::main(&mut config)
     };
 
-    match result {
-        Err(e) => cargo::exit_with_error(e, &mut config.get_mut().shell()),
-        Ok(()) => {}
+    // The error is reported first, so that `Finished` stays the last event
+    // of the invocation log.
+    let exit_code = match result {
+        Err(e) => cargo::report_error(e, &mut config.get_mut().shell()),
+        Ok(()) => 0,
+    };
+
+    if let Some(log) = config.get().invocation_log() {
+        log.log(&cargo::util::invocation_log::Finished {
+            success: exit_code == 0,
+        });
+    }
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 }
 
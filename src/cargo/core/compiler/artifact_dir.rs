@@ -0,0 +1,73 @@
+//! Maintenance of the `--artifact-dir` directory.
+//!
+//! Unlike `--out-dir`, which receives whatever the last build linked there,
+//! the artifact directory has a stable layout independent of the profile:
+//!
+//! * `bin/` for binaries,
+//! * `lib/` for libraries,
+//! * `examples/` for examples.
+//!
+//! Files are only replaced when they would change, so that their mtime can be
+//! used by tools such as `docker build` to know whether they did. For each
+//! file, a record of the artifact it was copied from is kept in
+//! `.fingerprint/`. A file is replaced when its unit was rebuilt, or when the
+//! record doesn't match the path and mtime of the artifact of the unit, for
+//! example when switching profiles, or after a build without
+//! `--artifact-dir`.
+//!
+//! Files are copied next to their destination first and then renamed, so
+//! that readers never see a partially written file.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+
+use crate::core::Target;
+use crate::util::CargoResult;
+
+/// The directory of the artifact directory holding the artifacts of `target`,
+/// if they are kept there.
+pub fn subdir(target: &Target) -> Option<&'static str> {
+    if target.is_bin() {
+        Some("bin")
+    } else if target.is_lib() {
+        Some("lib")
+    } else if target.is_example() {
+        Some("examples")
+    } else {
+        None
+    }
+}
+
+/// Updates the copy of the artifact `src` in `subdir` of the artifact
+/// directory `dir`, named `file_name`. `rebuilt` is whether the unit of
+/// `src` was just compiled.
+pub fn update(
+    dir: &Path,
+    subdir: &str,
+    src: &Path,
+    file_name: &str,
+    rebuilt: bool,
+) -> CargoResult<()> {
+    let dst = dir.join(subdir).join(file_name);
+    let record_path = dir
+        .join(".fingerprint")
+        .join(format!("{}-{}", subdir, file_name));
+    let record = format!("{}\n{}", src.display(), paths::mtime(src)?);
+    let up_to_date = !rebuilt
+        && dst.exists()
+        && paths::read(&record_path).ok().as_deref() == Some(record.as_str());
+    if up_to_date {
+        return Ok(());
+    }
+
+    paths::create_dir_all(dst.parent().unwrap())?;
+    paths::create_dir_all(record_path.parent().unwrap())?;
+    let tmp = dst.with_file_name(format!(".{}.tmp", file_name));
+    paths::copy(src, &tmp)?;
+    fs::rename(&tmp, &dst)
+        .with_context(|| format!("failed to move `{}` to `{}`", tmp.display(), dst.display()))?;
+    paths::write(&record_path, record)
+}
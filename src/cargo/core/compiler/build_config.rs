@@ -1,8 +1,11 @@
-use crate::core::compiler::CompileKind;
+use crate::core::compiler::flaky::RetryPolicy;
+use crate::core::compiler::tool_wrapper::ToolWrapper;
+use crate::core::compiler::{CompileKind, CompileTarget, MetadataHash};
 use crate::util::config::JobsConfig;
 use crate::util::interning::InternedString;
-use crate::util::{CargoResult, Config, RustfixDiagnosticServer};
+use crate::util::{parse_size, CargoResult, Config, RustfixDiagnosticServer};
 use anyhow::{bail, Context as _};
+use cargo_util::paths::LinkStrategy;
 use cargo_util::ProcessBuilder;
 use serde::ser;
 use std::cell::RefCell;
@@ -29,6 +32,8 @@ pub struct BuildConfig {
     pub force_rebuild: bool,
     /// Output a build plan to stdout instead of actually compiling.
     pub build_plan: bool,
+    /// The schema version of the build plan to output.
+    pub build_plan_version: u32,
     /// Output the unit graph to stdout instead of actually compiling.
     pub unit_graph: bool,
     /// An optional override of the rustc process for primary units
@@ -42,6 +47,45 @@ pub struct BuildConfig {
     // Note that, although the cmd-line flag name is `out-dir`, in code we use
     // `export_dir`, to avoid confusion with out dir at `target/debug/deps`.
     pub export_dir: Option<PathBuf>,
+    /// The directory from `--artifact-dir`, where final artifacts are kept in
+    /// a layout independent of the profile.
+    pub artifact_dir: Option<PathBuf>,
+    /// How final artifacts are placed in `target/{debug,release}` and the
+    /// `--out-dir` directory.
+    pub artifact_link: LinkStrategy,
+    /// The memory budget in bytes for rustc invocations running at the same
+    /// time, from `build.max-memory`.
+    pub max_memory: Option<u64>,
+    /// How the metadata hash of units is computed, from `build.metadata-hash`
+    /// and `build.metadata-hash-length`.
+    pub metadata_hash: MetadataHash,
+    /// Commands run on linked executables and dynamic libraries, from
+    /// `build.post-link-hooks`, each split into a program and its arguments.
+    pub post_link_hooks: Vec<Vec<String>>,
+    /// How units failing for reasons unrelated to their code are run again,
+    /// from `build.retry`.
+    pub retry: RetryPolicy,
+    /// Tools wrapping `rustc` for workspace members, from
+    /// `build.tool-wrappers`, outermost first.
+    pub tool_wrappers: Vec<ToolWrapper>,
+    /// Error out instead of rebuilding units outside of the packages selected
+    /// on the command line, from `--no-deps-rebuild`.
+    pub no_deps_rebuild: bool,
+    /// Only document the dependencies which are part of the public API of the
+    /// workspace members, from `cargo doc --depgraph-docs`.
+    pub depgraph_docs: bool,
+    /// Link the C runtime statically into the units built for the requested
+    /// targets, from `cargo build --static`. Units built for the host, such as
+    /// build scripts and proc-macros, are left alone, so the requested kinds
+    /// should not contain `CompileKind::Host`.
+    pub static_linking: bool,
+    /// The triple to build proc-macros and build scripts for, instead of the
+    /// host of `rustc`, from `--proc-macro-host`. Only set along with
+    /// `--target`, so the requested kinds don't contain `CompileKind::Host`.
+    pub proc_macro_host: Option<CompileTarget>,
+    /// Show short diagnostics for the units of packages which aren't
+    /// workspace members, from `--short-errors` or `term.short-errors`.
+    pub short_dep_errors: bool,
     /// `true` to output a future incompatibility report at the end of the build
     pub future_incompat_report: bool,
     /// Which kinds of build timings to output (empty if none).
@@ -60,6 +104,12 @@ impl BuildConfig {
     ///
     /// * `build.jobs`
     /// * `build.target`
+    /// * `build.artifact-link` (with `-Zartifact-link`)
+    /// * `build.max-memory` (with `-Zmax-memory`)
+    /// * `build.post-link-hooks` (with `-Zpost-link-hooks`)
+    /// * `build.retry` (with `-Zbuild-retry`)
+    /// * `build.tool-wrappers` (with `-Ztool-wrappers`)
+    /// * `term.short-errors` (with `-Zshort-errors`)
     /// * `target.$target.ar`
     /// * `target.$target.linker`
     /// * `target.$target.libfoo.metadata`
@@ -97,6 +147,60 @@ impl BuildConfig {
             },
         };
 
+        let artifact_link = match &cfg.artifact_link {
+            Some(link) if config.cli_unstable().artifact_link => match link.as_str() {
+                "hardlink" => LinkStrategy::Hardlink,
+                "symlink" => LinkStrategy::Symlink,
+                "copy" => LinkStrategy::Copy,
+                _ => bail!(
+                    "`build.artifact-link` must be `hardlink`, `symlink`, or `copy`, found `{}`",
+                    link
+                ),
+            },
+            _ => LinkStrategy::default(),
+        };
+
+        let max_memory = match &cfg.max_memory {
+            Some(size) if config.cli_unstable().max_memory => match parse_size(size) {
+                Some(0) | None => bail!(
+                    "`build.max-memory` must be a size such as `8GiB` or `512MiB`, found `{}`",
+                    size
+                ),
+                Some(bytes) => Some(bytes),
+            },
+            _ => None,
+        };
+
+        let metadata_hash = if config.cli_unstable().metadata_hash {
+            MetadataHash::from_config(cfg.metadata_hash.as_deref(), cfg.metadata_hash_length)?
+        } else {
+            MetadataHash::default()
+        };
+
+        let post_link_hooks = match &cfg.post_link_hooks {
+            Some(hooks) if config.cli_unstable().post_link_hooks => {
+                super::post_link::parse_hooks(hooks)?
+            }
+            _ => Vec::new(),
+        };
+
+        let retry = match &cfg.retry {
+            Some(retry) if config.cli_unstable().build_retry => RetryPolicy::from_config(retry)?,
+            _ => RetryPolicy::default(),
+        };
+
+        let tool_wrappers = match &cfg.tool_wrappers {
+            Some(wrappers) if config.cli_unstable().tool_wrappers => {
+                super::tool_wrapper::from_config(config, wrappers)?
+            }
+            _ => Vec::new(),
+        };
+
+        let short_dep_errors = config.cli_unstable().short_errors
+            && config
+                .get::<Option<bool>>("term.short-errors")?
+                .unwrap_or(false);
+
         if config.cli_unstable().build_std.is_some() && requested_kinds[0].is_host() {
             // TODO: This should eventually be fixed.
             anyhow::bail!("-Zbuild-std requires --target");
@@ -111,10 +215,23 @@ impl BuildConfig {
             message_format: MessageFormat::Human,
             force_rebuild: false,
             build_plan: false,
+            build_plan_version: 1,
             unit_graph: false,
             primary_unit_rustc: None,
             rustfix_diagnostic_server: Arc::new(RefCell::new(None)),
             export_dir: None,
+            artifact_dir: None,
+            artifact_link,
+            max_memory,
+            metadata_hash,
+            post_link_hooks,
+            retry,
+            tool_wrappers,
+            no_deps_rebuild: false,
+            depgraph_docs: false,
+            static_linking: false,
+            proc_macro_host: None,
+            short_dep_errors,
             future_incompat_report: false,
             timing_outputs: Vec::new(),
         })
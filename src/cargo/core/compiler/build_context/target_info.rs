@@ -8,10 +8,11 @@
 //! * [`TargetInfo::rustc_outputs`] to get a list of supported file types.
 
 use crate::core::compiler::apply_env_config;
+use crate::core::compiler::build_probe::run_build_probes;
 use crate::core::compiler::{
-    BuildOutput, CompileKind, CompileMode, CompileTarget, Context, CrateType,
+    BuildConfig, BuildOutput, CompileKind, CompileMode, CompileTarget, Context, CrateType,
 };
-use crate::core::{Dependency, Package, Target, TargetKind, Workspace};
+use crate::core::{Dependency, Package, PackageId, Target, TargetKind, Workspace};
 use crate::util::config::{Config, StringList, TargetConfig};
 use crate::util::interning::InternedString;
 use crate::util::{CargoResult, Rustc};
@@ -150,12 +151,20 @@ impl TargetInfo {
     /// invocation is cached by [`Rustc::cached_output`].
     ///
     /// Search `Tricky` to learn why querying `rustc` several times is needed.
+    ///
+    /// With `static_linking`, `-C target-feature=+crt-static` is added to the
+    /// flags of target units, so that it also shows up in their `cfg`. With
+    /// `proc_macro_host`, the host is queried with `--target`, see
+    /// [`RustcTargetData::rustc_target`].
     pub fn new(
         config: &Config,
         requested_kinds: &[CompileKind],
         rustc: &Rustc,
         kind: CompileKind,
+        static_linking: bool,
+        proc_macro_host: Option<CompileTarget>,
     ) -> CargoResult<TargetInfo> {
+        let static_linking = static_linking && !kind.is_host();
         let mut rustflags = extra_args(
             config,
             requested_kinds,
@@ -164,6 +173,9 @@ impl TargetInfo {
             kind,
             Flags::Rust,
         )?;
+        if static_linking {
+            add_crt_static(&mut rustflags);
+        }
         let mut turn = 0;
         loop {
             let extra_fingerprint = kind.fingerprint_hash();
@@ -191,7 +203,11 @@ impl TargetInfo {
                 process.inherit_jobserver(client);
             }
 
-            if let CompileKind::Target(target) = kind {
+            let target = match kind {
+                CompileKind::Host => proc_macro_host,
+                CompileKind::Target(target) => Some(target),
+            };
+            if let Some(target) = target {
                 process.arg("--target").arg(target.rustc_target());
             }
 
@@ -277,7 +293,7 @@ impl TargetInfo {
 
             // recalculate `rustflags` from above now that we have `cfg`
             // information
-            let new_flags = extra_args(
+            let mut new_flags = extra_args(
                 config,
                 requested_kinds,
                 &rustc.host,
@@ -285,6 +301,9 @@ impl TargetInfo {
                 kind,
                 Flags::Rust,
             )?;
+            if static_linking {
+                add_crt_static(&mut new_flags);
+            }
 
             // Tricky: `RUSTFLAGS` defines the set of active `cfg` flags, active
             // `cfg` flags define which `.cargo/config` sections apply, and they
@@ -751,6 +770,15 @@ fn extra_args(
     }
 }
 
+/// Appends `-C target-feature=+crt-static` to `rustflags`, for
+/// `cargo build --static`.
+///
+/// It is added last so that it wins over a `-crt-static` set by the user.
+fn add_crt_static(rustflags: &mut Vec<String>) {
+    rustflags.push("-C".to_string());
+    rustflags.push("target-feature=+crt-static".to_string());
+}
+
 /// Gets compiler flags from environment variables.
 /// See [`extra_args`] for more.
 fn rustflags_from_env(config: &Config, flags: Flags) -> Option<Vec<String>> {
@@ -864,6 +892,11 @@ pub struct RustcTargetData<'cfg> {
     /// Config
     pub config: &'cfg Config,
     requested_kinds: Vec<CompileKind>,
+    /// Whether target units are linked statically, see [`TargetInfo::new`].
+    static_linking: bool,
+    /// The triple host units are built for, if it isn't the host of `rustc`.
+    /// `rustc.host` is then replaced with it.
+    proc_macro_host: Option<CompileTarget>,
 
     /// Build information for the "host", which is information about when
     /// `rustc` is invoked without a `--target` flag. This is used for
@@ -876,19 +909,61 @@ pub struct RustcTargetData<'cfg> {
     target_config: HashMap<CompileTarget, TargetConfig>,
     /// Information about the target platform that we're building for.
     target_info: HashMap<CompileTarget, TargetInfo>,
+
+    /// The results of the `[build-probe]` tables, see
+    /// [`RustcTargetData::build_probe`].
+    build_probes: RefCell<HashMap<(PackageId, CompileKind), Option<BuildOutput>>>,
 }
 
 impl<'cfg> RustcTargetData<'cfg> {
     pub fn new(
         ws: &Workspace<'cfg>,
         requested_kinds: &[CompileKind],
+    ) -> CargoResult<RustcTargetData<'cfg>> {
+        RustcTargetData::with_options(ws, requested_kinds, false, None)
+    }
+
+    /// Like [`RustcTargetData::new`], with the options of `build_config`
+    /// which change how units are built for each kind:
+    ///
+    /// * `static_linking` links the C runtime statically into target units.
+    /// * `proc_macro_host` builds the host units for another triple.
+    pub fn for_build_config(
+        ws: &Workspace<'cfg>,
+        build_config: &BuildConfig,
+    ) -> CargoResult<RustcTargetData<'cfg>> {
+        RustcTargetData::with_options(
+            ws,
+            &build_config.requested_kinds,
+            build_config.static_linking,
+            build_config.proc_macro_host,
+        )
+    }
+
+    fn with_options(
+        ws: &Workspace<'cfg>,
+        requested_kinds: &[CompileKind],
+        static_linking: bool,
+        proc_macro_host: Option<CompileTarget>,
     ) -> CargoResult<RustcTargetData<'cfg>> {
         let config = ws.config();
-        let rustc = config.load_global_rustc(Some(ws))?;
+        let mut rustc = config.load_global_rustc(Some(ws))?;
+        let proc_macro_host =
+            proc_macro_host.filter(|target| target.short_name() != rustc.host.as_str());
+        if let Some(target) = proc_macro_host {
+            rustc.host = InternedString::new(target.short_name());
+        }
         let mut target_config = HashMap::new();
         let mut target_info = HashMap::new();
         let target_applies_to_host = config.target_applies_to_host()?;
-        let host_info = TargetInfo::new(config, requested_kinds, &rustc, CompileKind::Host)?;
+        let host_info = TargetInfo::new(
+            config,
+            requested_kinds,
+            &rustc,
+            CompileKind::Host,
+            static_linking,
+            proc_macro_host,
+        )?;
         let host_config = if target_applies_to_host {
             config.target_cfg_triple(&rustc.host)?
         } else {
@@ -910,10 +985,13 @@ impl<'cfg> RustcTargetData<'cfg> {
             rustc,
             config,
             requested_kinds: requested_kinds.into(),
+            static_linking,
+            proc_macro_host,
             host_config,
             host_info,
             target_config,
             target_info,
+            build_probes: RefCell::new(HashMap::new()),
         };
 
         // Get all kinds we currently know about.
@@ -957,7 +1035,14 @@ impl<'cfg> RustcTargetData<'cfg> {
             if !self.target_info.contains_key(&target) {
                 self.target_info.insert(
                     target,
-                    TargetInfo::new(self.config, &self.requested_kinds, &self.rustc, kind)?,
+                    TargetInfo::new(
+                        self.config,
+                        &self.requested_kinds,
+                        &self.rustc,
+                        kind,
+                        self.static_linking,
+                        self.proc_macro_host,
+                    )?,
                 );
             }
         }
@@ -973,6 +1058,21 @@ impl<'cfg> RustcTargetData<'cfg> {
         }
     }
 
+    /// The target to pass to `rustc` with `--target` for units of the given
+    /// kind. Host units only get one when built for `--proc-macro-host`.
+    pub fn rustc_target(&self, kind: CompileKind) -> Option<CompileTarget> {
+        match kind {
+            CompileKind::Host => self.proc_macro_host,
+            CompileKind::Target(target) => Some(target),
+        }
+    }
+
+    /// The triple host units are built for, if `--proc-macro-host` overrides
+    /// the host of `rustc`.
+    pub fn proc_macro_host(&self) -> Option<CompileTarget> {
+        self.proc_macro_host
+    }
+
     /// Whether a dependency should be compiled for the host or target platform,
     /// specified by `CompileKind`.
     pub fn dep_platform_activated(&self, dep: &Dependency, kind: CompileKind) -> bool {
@@ -986,6 +1086,28 @@ impl<'cfg> RustcTargetData<'cfg> {
         platform.matches(name, self.cfg(kind))
     }
 
+    /// Whether a dependency is used when building for `requested_kinds`.
+    ///
+    /// Dependencies are evaluated against the requested kinds. Build
+    /// dependencies are only ever built for the host though, so with
+    /// `-Zbuild-dep-host-filtering` their platform is evaluated against the
+    /// host, like the feature resolver and the unit graph do.
+    ///
+    /// This isn't suited to `cargo vendor --platform`, where the platforms
+    /// are the ones the build runs on.
+    pub fn dep_platform_activated_for(
+        &self,
+        dep: &Dependency,
+        requested_kinds: &[CompileKind],
+    ) -> bool {
+        if dep.is_build() && self.config.cli_unstable().build_dep_host_filtering {
+            return self.dep_platform_activated(dep, CompileKind::Host);
+        }
+        requested_kinds
+            .iter()
+            .any(|kind| self.dep_platform_activated(dep, *kind))
+    }
+
     /// Gets the list of `cfg`s printed out from the compiler for the specified kind.
     pub fn cfg(&self, kind: CompileKind) -> &[Cfg] {
         self.info(kind).cfg()
@@ -1026,6 +1148,28 @@ impl<'cfg> RustcTargetData<'cfg> {
     pub fn script_override(&self, lib_name: &str, kind: CompileKind) -> Option<&BuildOutput> {
         self.target_config(kind).links_overrides.get(lib_name)
     }
+
+    /// If the `[build-probe]` table of `pkg` takes the place of its build
+    /// script for `kind`, this returns the `BuildOutput` to use.
+    ///
+    /// The probes of a package are only run once for each platform.
+    pub fn build_probe(&self, pkg: &Package, kind: CompileKind) -> Option<BuildOutput> {
+        if pkg.manifest().build_probes().is_empty() {
+            return None;
+        }
+        // The unit graph uses `CompileKind::Target` for the host until it is
+        // rebuilt to share the host units.
+        let host = self.rustc.host.as_str();
+        let kind = match kind {
+            CompileKind::Target(target) if target.short_name() == host => CompileKind::Host,
+            kind => kind,
+        };
+        self.build_probes
+            .borrow_mut()
+            .entry((pkg.package_id(), kind))
+            .or_insert_with(|| run_build_probes(self.config, pkg, kind, !kind.is_host()))
+            .clone()
+    }
 }
 
 /// Structure used to deal with Rustdoc fingerprinting
@@ -5,6 +5,10 @@
 //! shared with an external build system. Each Invocation in the BuildPlan comprises a single
 //! subprocess and defines the build environment, the outputs produced by the subprocess, and the
 //! dependencies on other Invocations.
+//!
+//! Version 2 of the plan (`--build-plan=2`) additionally records a `version` field and, for every
+//! Invocation, the package ID, the metadata hash used in the output file names, the enabled
+//! features, the named edges to its dependencies and the build script relationship.
 
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
@@ -13,7 +17,8 @@ use serde::Serialize;
 
 use super::context::OutputFile;
 use super::{CompileKind, CompileMode, Context, Unit};
-use crate::core::TargetKind;
+use crate::core::{PackageId, TargetKind};
+use crate::util::interning::InternedString;
 use crate::util::{internal, CargoResult, Config};
 use cargo_util::ProcessBuilder;
 
@@ -31,16 +36,46 @@ struct Invocation {
     args: Vec<String>,
     env: BTreeMap<String, String>,
     cwd: Option<PathBuf>,
+    #[serde(flatten)]
+    v2: Option<InvocationV2>,
+}
+
+/// Additional details about an [`Invocation`] emitted in version 2 of the plan.
+#[derive(Debug, Serialize)]
+struct InvocationV2 {
+    package_id: PackageId,
+    target_name: String,
+    /// The hash mixed into the output file names, which distinguishes
+    /// different builds of the same target.
+    metadata: String,
+    features: Vec<InternedString>,
+    dep_edges: Vec<DepEdge>,
+    /// The index of the Invocation running this unit's build script, if any.
+    build_script: Option<usize>,
+    /// The value of the `links` manifest key of the package.
+    package_links: Option<InternedString>,
+}
+
+/// A named edge to one of the dependencies of an [`Invocation`].
+#[derive(Debug, Serialize)]
+struct DepEdge {
+    index: usize,
+    extern_crate_name: InternedString,
+    public: bool,
+    noprelude: bool,
 }
 
 #[derive(Debug)]
 pub struct BuildPlan {
+    version: u32,
     invocation_map: BTreeMap<String, usize>,
     plan: SerializedBuildPlan,
 }
 
 #[derive(Debug, Serialize)]
 struct SerializedBuildPlan {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
     invocations: Vec<Invocation>,
     inputs: Vec<PathBuf>,
 }
@@ -61,6 +96,7 @@ impl Invocation {
             args: Vec::new(),
             env: BTreeMap::new(),
             cwd: None,
+            v2: None,
         }
     }
 
@@ -103,22 +139,49 @@ impl Invocation {
 }
 
 impl BuildPlan {
-    pub fn new() -> BuildPlan {
+    pub fn new(version: u32) -> BuildPlan {
         BuildPlan {
+            version,
             invocation_map: BTreeMap::new(),
-            plan: SerializedBuildPlan::new(),
+            plan: SerializedBuildPlan::new(version),
         }
     }
 
     pub fn add(&mut self, cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<()> {
         let id = self.plan.invocations.len();
         self.invocation_map.insert(unit.buildkey(), id);
-        let deps = cx
-            .unit_deps(unit)
+        let unit_deps = cx.unit_deps(unit);
+        let deps: Vec<usize> = unit_deps
             .iter()
             .map(|dep| self.invocation_map[&dep.unit.buildkey()])
             .collect();
-        let invocation = Invocation::new(unit, deps);
+        let mut invocation = Invocation::new(unit, deps.clone());
+        if self.version >= 2 {
+            let dep_edges = unit_deps
+                .iter()
+                .zip(&deps)
+                .map(|(dep, &index)| DepEdge {
+                    index,
+                    extern_crate_name: dep.extern_crate_name,
+                    public: dep.public,
+                    noprelude: dep.noprelude,
+                })
+                .collect();
+            let build_script = unit_deps
+                .iter()
+                .zip(&deps)
+                .find(|(dep, _)| dep.unit.mode.is_run_custom_build())
+                .map(|(_, &index)| index);
+            invocation.v2 = Some(InvocationV2 {
+                package_id: unit.pkg.package_id(),
+                target_name: unit.target.name().to_string(),
+                metadata: cx.files().metadata(unit).to_string(),
+                features: unit.features.clone(),
+                dep_edges,
+                build_script,
+                package_links: unit.pkg.manifest().links().map(InternedString::new),
+            });
+        }
         self.plan.invocations.push(invocation);
         Ok(())
     }
@@ -154,8 +217,9 @@ impl BuildPlan {
 }
 
 impl SerializedBuildPlan {
-    pub fn new() -> SerializedBuildPlan {
+    pub fn new(version: u32) -> SerializedBuildPlan {
         SerializedBuildPlan {
+            version: if version >= 2 { Some(version) } else { None },
             invocations: Vec::new(),
             inputs: Vec::new(),
         }
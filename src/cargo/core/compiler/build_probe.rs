@@ -0,0 +1,108 @@
+//! Evaluation of the `[build-probe]` table of a package.
+//!
+//! Each entry of the table looks for a system library with `pkg-config`. When
+//! Cargo can tell the result of all of them by itself, their output takes the
+//! place of the output of the build script, as it does for the
+//! [`links` overrides] of the configuration, and the build script isn't
+//! compiled:
+//!
+//! * the link flags and `cfgs` of the libraries which are found,
+//! * the `fallback-cfgs` of the ones which aren't.
+//!
+//! If a library isn't found and the package has a build script of its own,
+//! the build script is run instead, so that it may build a vendored copy of
+//! the library for example.
+//!
+//! [`links` overrides]: https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#overriding-build-scripts
+
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context as _};
+use cargo_util::ProcessBuilder;
+use tracing::debug;
+
+use crate::core::compiler::{BuildOutput, CompileKind};
+use crate::core::manifest::BuildProbe;
+use crate::core::Package;
+use crate::util::{CargoResult, Config};
+
+/// Runs the probes of `pkg` for `kind`, and returns the output of the build
+/// script they take the place of, if any.
+///
+/// `cross` tells whether `kind` is another platform than the host.
+pub fn run_build_probes(
+    config: &Config,
+    pkg: &Package,
+    kind: CompileKind,
+    cross: bool,
+) -> Option<BuildOutput> {
+    let probes = pkg.manifest().build_probes();
+    if probes.is_empty() {
+        return None;
+    }
+    // The target standing in for a missing build script has no path.
+    let has_script = pkg.manifest().metabuild().is_some()
+        || pkg
+            .targets()
+            .iter()
+            .any(|t| t.is_custom_build() && t.src_path().is_path());
+
+    let mut output = BuildOutput::default();
+    for probe in probes {
+        match find_library(config, probe, cross) {
+            Ok(flags) => {
+                debug!("build probe `{}` of {} found {flags:?}", probe.name, pkg);
+                for flag in flags.split_whitespace() {
+                    if let Some(path) = flag.strip_prefix("-L") {
+                        output
+                            .library_paths
+                            .push(PathBuf::from(format!("native={path}")));
+                    } else if let Some(name) = flag.strip_prefix("-l") {
+                        output.library_links.push(name.to_string());
+                    }
+                }
+                output.cfgs.extend(probe.cfgs.iter().cloned());
+            }
+            Err(e) => {
+                debug!(
+                    "build probe `{}` of {} for {:?} failed: {e:?}",
+                    probe.name, pkg, kind
+                );
+                if has_script {
+                    return None;
+                }
+                output.cfgs.extend(probe.fallback_cfgs.iter().cloned());
+            }
+        }
+    }
+    Some(output)
+}
+
+/// Looks for the library of `probe` with `pkg-config`, and returns its link
+/// flags.
+fn find_library(config: &Config, probe: &BuildProbe, cross: bool) -> CargoResult<String> {
+    // Like the `pkg-config` crate, libraries of the host are never used for
+    // another platform unless asked for.
+    if cross
+        && config.get_env_os("PKG_CONFIG_ALLOW_CROSS").as_deref() != Some(OsStr::new("1"))
+        && config.get_env_os("PKG_CONFIG_SYSROOT_DIR").is_none()
+    {
+        bail!("cross-compiling without `PKG_CONFIG_ALLOW_CROSS=1` or `PKG_CONFIG_SYSROOT_DIR`");
+    }
+    let pkg_config = config
+        .get_env_os("PKG_CONFIG")
+        .unwrap_or_else(|| "pkg-config".into());
+
+    if !probe.version_checks.is_empty() {
+        let mut cmd = ProcessBuilder::new(&pkg_config);
+        cmd.args(&probe.version_checks).arg(&probe.pkg_config);
+        cmd.exec_with_output()?;
+    }
+
+    let mut cmd = ProcessBuilder::new(&pkg_config);
+    cmd.arg("--libs").arg(&probe.pkg_config);
+    let output = cmd.exec_with_output()?;
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("`pkg-config` output for `{}` isn't UTF-8", probe.pkg_config))
+}
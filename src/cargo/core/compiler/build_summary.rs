@@ -0,0 +1,251 @@
+//! The summary printed at the end of a successful build.
+//!
+//! Next to the `Finished` line, Cargo sums up what the build did: how many
+//! units were compiled and how many were fresh, the critical path of the
+//! build, the slowest units, the warnings of each package, the units which
+//! were retried with `build.retry`, and where the artifacts of the root units
+//! are. With `--message-format=json`, the same
+//! information is emitted as a `build-summary` message.
+//!
+//! The summary is opt-in behind `-Zbuild-summary`, and can be turned off
+//! again with `term.summary = false`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::core::compiler::flaky::Flake;
+use crate::core::compiler::job_queue::JobId;
+use crate::core::compiler::{descriptive_pkg_name, Context, FileFlavor, Unit};
+use crate::core::PackageId;
+use crate::util::machine_message::{self, Message};
+use crate::util::{self, CargoResult, Config};
+
+/// How many of the slowest units are listed.
+const SLOWEST_UNITS: usize = 5;
+
+/// Statistics of a build, collected by the job queue.
+pub struct BuildSummary {
+    /// Whether the summary is printed at all, from `-Zbuild-summary` and
+    /// `term.summary`.
+    enabled: bool,
+    /// Number of units which were up to date.
+    fresh: usize,
+    /// Number of units which were compiled.
+    compiled: usize,
+    /// When each running dirty unit started.
+    started: HashMap<JobId, Instant>,
+    /// How long each compiled unit took.
+    durations: HashMap<Unit, Duration>,
+    /// Number of warnings emitted by each package.
+    warnings: BTreeMap<PackageId, usize>,
+    /// Units which were run again, with why, in the order they failed.
+    retries: Vec<(Unit, Flake)>,
+}
+
+impl BuildSummary {
+    pub fn new(config: &Config) -> BuildSummary {
+        BuildSummary {
+            enabled: config.build_summary(),
+            fresh: 0,
+            compiled: 0,
+            started: HashMap::new(),
+            durations: HashMap::new(),
+            warnings: BTreeMap::new(),
+            retries: Vec::new(),
+        }
+    }
+
+    /// Marks that the unit of the job `id` was scheduled.
+    pub fn unit_start(&mut self, id: JobId, fresh: bool) {
+        if fresh {
+            self.fresh += 1;
+        } else {
+            self.compiled += 1;
+            self.started.insert(id, Instant::now());
+        }
+    }
+
+    /// Marks that the unit of the job `id` has finished.
+    pub fn unit_finished(&mut self, id: JobId, unit: &Unit) {
+        if let Some(start) = self.started.remove(&id) {
+            self.durations.insert(unit.clone(), start.elapsed());
+        }
+    }
+
+    /// Records the warnings emitted by a unit of `pkg`.
+    pub fn add_warnings(&mut self, pkg: PackageId, count: usize) {
+        *self.warnings.entry(pkg).or_default() += count;
+    }
+
+    /// Records that `unit` failed because of `flake`, and is run again.
+    pub fn unit_retried(&mut self, unit: &Unit, flake: Flake) {
+        self.retries.push((unit.clone(), flake));
+    }
+
+    /// Emits the summary as a `build-summary` message, for
+    /// `--message-format=json`.
+    pub fn emit_json(&self, cx: &Context<'_, '_>) -> CargoResult<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let config = cx.bcx.config;
+        let artifacts = root_artifacts(cx)?;
+        let msg = machine_message::BuildSummary {
+            compiled: self.compiled,
+            fresh: self.fresh,
+            duration: config.creation_time().elapsed().as_secs_f64(),
+            critical_path: self.critical_path(cx).as_secs_f64(),
+            slowest: self
+                .slowest()
+                .into_iter()
+                .map(|(unit, duration)| machine_message::SummaryUnit {
+                    package_id: unit.pkg.package_id(),
+                    target: &unit.target,
+                    mode: unit.mode,
+                    duration: duration.as_secs_f64(),
+                })
+                .collect(),
+            warnings: self
+                .warnings
+                .iter()
+                .map(|(&package_id, &count)| machine_message::SummaryWarnings { package_id, count })
+                .collect(),
+            retried: self
+                .retries
+                .iter()
+                .map(|(unit, flake)| machine_message::SummaryRetry {
+                    package_id: unit.pkg.package_id(),
+                    target: &unit.target,
+                    mode: unit.mode,
+                    reason: flake.name(),
+                })
+                .collect(),
+            artifacts: &artifacts,
+        }
+        .to_json_string();
+        writeln!(config.shell().out(), "{}", msg)?;
+        Ok(())
+    }
+
+    /// Prints the summary after the `Finished` line.
+    pub fn report(&self, cx: &Context<'_, '_>) -> CargoResult<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let artifacts: Vec<_> = root_artifacts(cx)?
+            .iter()
+            .map(|artifact| cx.bcx.ws.display_path(artifact))
+            .collect();
+        let mut shell = cx.bcx.config.shell();
+        let units = match self.compiled {
+            1 => "unit",
+            _ => "units",
+        };
+        shell.status(
+            "Summary",
+            format!(
+                "{} {units} compiled, {} fresh, critical path {}",
+                self.compiled,
+                self.fresh,
+                util::elapsed(self.critical_path(cx))
+            ),
+        )?;
+        let slowest: Vec<_> = self
+            .slowest()
+            .into_iter()
+            .map(|(unit, duration)| {
+                let name = descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+                format!("{name} {}", util::elapsed(duration))
+            })
+            .collect();
+        if !slowest.is_empty() {
+            shell.status("Slowest", slowest.join(", "))?;
+        }
+        if !self.warnings.is_empty() {
+            let warnings: Vec<_> = self
+                .warnings
+                .iter()
+                .map(|(pkg, count)| format!("`{} v{}` {count}", pkg.name(), pkg.version()))
+                .collect();
+            shell.status("Warnings", warnings.join(", "))?;
+        }
+        if !self.retries.is_empty() {
+            let retries: Vec<_> = self
+                .retries
+                .iter()
+                .map(|(unit, flake)| {
+                    let name = descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+                    format!("{name} {}", flake.name())
+                })
+                .collect();
+            shell.status("Retried", retries.join(", "))?;
+        }
+        for artifact in &artifacts {
+            shell.status("Artifact", artifact)?;
+        }
+        Ok(())
+    }
+
+    /// The units which took the longest to compile, slowest first.
+    fn slowest(&self) -> Vec<(&Unit, Duration)> {
+        let mut slowest: Vec<_> = self
+            .durations
+            .iter()
+            .map(|(unit, &duration)| (unit, duration))
+            .collect();
+        slowest.sort_by(|a, b| b.1.cmp(&a.1));
+        slowest.truncate(SLOWEST_UNITS);
+        slowest
+    }
+
+    /// The longest chain of compilations the build had to wait for, which
+    /// bounds its duration whatever the parallelism.
+    fn critical_path(&self, cx: &Context<'_, '_>) -> Duration {
+        let mut memo = HashMap::new();
+        cx.bcx
+            .roots
+            .iter()
+            .map(|root| self.path_to(cx, root, &mut memo))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn path_to<'a>(
+        &self,
+        cx: &'a Context<'_, '_>,
+        unit: &'a Unit,
+        memo: &mut HashMap<&'a Unit, Duration>,
+    ) -> Duration {
+        if let Some(&duration) = memo.get(unit) {
+            return duration;
+        }
+        let deps = cx
+            .unit_deps(unit)
+            .iter()
+            .map(|dep| self.path_to(cx, &dep.unit, memo))
+            .max()
+            .unwrap_or_default();
+        let duration = deps + self.durations.get(unit).copied().unwrap_or_default();
+        memo.insert(unit, duration);
+        duration
+    }
+}
+
+/// The final artifacts of the root units, as found in the target directory.
+fn root_artifacts(cx: &Context<'_, '_>) -> CargoResult<Vec<PathBuf>> {
+    let mut seen = HashSet::new();
+    let mut artifacts = Vec::new();
+    for unit in &cx.bcx.roots {
+        for output in cx.outputs(unit)?.iter() {
+            if !matches!(output.flavor, FileFlavor::Normal | FileFlavor::Linkable) {
+                continue;
+            }
+            let path = output.bin_dst().clone();
+            if seen.insert(path.clone()) {
+                artifacts.push(path);
+            }
+        }
+    }
+    Ok(artifacts)
+}
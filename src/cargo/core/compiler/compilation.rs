@@ -1,6 +1,6 @@
 //! Type definitions for the result of a compilation.
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
@@ -93,6 +93,10 @@ pub struct Compilation<'cfg> {
     /// The target host triple.
     pub host: String,
 
+    /// The units of this compilation recorded for `cargo clean gc`, by the
+    /// name of their fingerprint directory.
+    pub last_used_units: HashSet<String>,
+
     config: &'cfg Config,
 
     /// Rustc process to be used by default
@@ -140,6 +144,7 @@ impl<'cfg> Compilation<'cfg> {
             to_doc_test: Vec::new(),
             config: bcx.config,
             host: bcx.host_triple().to_string(),
+            last_used_units: HashSet::new(),
             rustc_process: rustc,
             rustc_workspace_wrapper_process,
             primary_rustc_process,
@@ -221,6 +226,11 @@ impl<'cfg> Compilation<'cfg> {
         self.target_runners.get(&kind).and_then(|x| x.as_ref())
     }
 
+    /// Overrides the runner of `kind` found in the configuration.
+    pub fn set_target_runner(&mut self, kind: CompileKind, runner: (PathBuf, Vec<String>)) {
+        self.target_runners.insert(kind, Some(runner));
+    }
+
     /// Returns a [`ProcessBuilder`] appropriate for running a process for the
     /// target platform. This is typically used for `cargo run` and `cargo
     /// test`.
@@ -1,17 +1,20 @@
 //! See [`CompilationFiles`].
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use anyhow::bail;
+use cargo_util::Sha256;
 use lazycell::LazyCell;
 use tracing::debug;
 
 use super::{BuildContext, CompileKind, Context, FileFlavor, Layout};
-use crate::core::compiler::{CompileMode, CompileTarget, CrateType, FileType, Unit};
+use crate::core::compiler::{tool_wrapper, CompileMode, CompileTarget, CrateType, FileType, Unit};
 use crate::core::{Target, TargetKind, Workspace};
+use crate::util::interning::InternedString;
 use crate::util::{self, CargoResult, StableHasher};
 
 /// This is a generic version number that can be changed to make
@@ -68,18 +71,175 @@ const METADATA_VERSION: u8 = 2;
 ///
 /// Note that the `Fingerprint` is in charge of tracking everything needed to determine if a
 /// rebuild is needed.
-#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
-pub struct Metadata(u64);
+///
+/// The hash is 16 hex digits of SipHash by default, `build.metadata-hash` and
+/// `build.metadata-hash-length` select a longer SHA-256 hash instead.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Metadata {
+    /// The hash, most significant byte first, padded with zeros.
+    bytes: [u8; 32],
+    /// The number of hex digits of the hash.
+    len: u8,
+}
+
+impl Metadata {
+    fn from_u64(hash: u64) -> Metadata {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&hash.to_be_bytes());
+        Metadata { bytes, len: 16 }
+    }
+
+    fn from_sha256(bytes: [u8; 32], len: usize) -> Metadata {
+        let mut metadata = Metadata {
+            bytes,
+            len: len as u8,
+        };
+        // Zero the digits past `len`, so that equal hashes compare equal.
+        let full = len / 2;
+        if len % 2 == 1 {
+            metadata.bytes[full] &= 0xf0;
+            metadata.bytes[full + 1..].fill(0);
+        } else {
+            metadata.bytes[full..].fill(0);
+        }
+        metadata
+    }
+}
+
+impl Hash for Metadata {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if self.len == 16 {
+            // Hashed like the `u64` it used to be, to keep the default
+            // metadata hashes of the units depending on this one.
+            let mut hash = [0; 8];
+            hash.copy_from_slice(&self.bytes[..8]);
+            u64::from_be_bytes(hash).hash(state);
+        } else {
+            self.bytes.hash(state);
+            self.len.hash(state);
+        }
+    }
+}
 
 impl fmt::Display for Metadata {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:016x}", self.0)
+        let hex = hex::encode(self.bytes);
+        f.write_str(&hex[..usize::from(self.len)])
     }
 }
 
 impl fmt::Debug for Metadata {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Metadata({:016x})", self.0)
+        write!(f, "Metadata({})", self)
+    }
+}
+
+/// The algorithm computing the [`Metadata`] hash, from `build.metadata-hash`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MetadataHashAlgorithm {
+    /// A 64-bit SipHash, the default.
+    SipHash,
+    /// SHA-256, for longer hashes with `build.metadata-hash-length`.
+    Sha256,
+}
+
+/// How the [`Metadata`] hash of units is computed, with `-Zmetadata-hash`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MetadataHash {
+    pub algorithm: MetadataHashAlgorithm,
+    /// The number of hex digits of the hash, from 16 to 64.
+    pub length: usize,
+}
+
+impl Default for MetadataHash {
+    fn default() -> MetadataHash {
+        MetadataHash {
+            algorithm: MetadataHashAlgorithm::SipHash,
+            length: 16,
+        }
+    }
+}
+
+impl MetadataHash {
+    /// The range of supported hash lengths, in hex digits.
+    const LENGTHS: std::ops::RangeInclusive<usize> = 16..=64;
+
+    /// Returns whether `s` looks like a hash of any supported length, as
+    /// found after the last `-` of the unit directories and file names.
+    pub fn is_hash(s: &str) -> bool {
+        Self::LENGTHS.contains(&s.len()) && s.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Parses `build.metadata-hash` and `build.metadata-hash-length`.
+    pub fn from_config(algorithm: Option<&str>, length: Option<u32>) -> CargoResult<MetadataHash> {
+        let algorithm = match algorithm {
+            None | Some("siphash") => MetadataHashAlgorithm::SipHash,
+            Some("sha256") => MetadataHashAlgorithm::Sha256,
+            Some(other) => bail!(
+                "`build.metadata-hash` must be `siphash` or `sha256`, found `{}`",
+                other
+            ),
+        };
+        let length = length.unwrap_or(16) as usize;
+        if !Self::LENGTHS.contains(&length) {
+            bail!(
+                "`build.metadata-hash-length` must be between 16 and 64, found {}",
+                length
+            );
+        }
+        if length > 16 && algorithm == MetadataHashAlgorithm::SipHash {
+            bail!(
+                "`build.metadata-hash-length` greater than 16 requires \
+                 `build.metadata-hash = \"sha256\"`"
+            );
+        }
+        Ok(MetadataHash { algorithm, length })
+    }
+}
+
+/// The hasher of [`compute_metadata`], for the configured [`MetadataHash`].
+enum MetadataHasher {
+    SipHash(StableHasher),
+    Sha256(Sha256),
+}
+
+impl MetadataHasher {
+    fn new(config: MetadataHash) -> MetadataHasher {
+        match config.algorithm {
+            MetadataHashAlgorithm::SipHash => MetadataHasher::SipHash(StableHasher::new()),
+            MetadataHashAlgorithm::Sha256 => MetadataHasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn finish_metadata(self, config: MetadataHash) -> Metadata {
+        match self {
+            MetadataHasher::SipHash(hasher) => Metadata::from_u64(hasher.finish()),
+            MetadataHasher::Sha256(mut hasher) => {
+                Metadata::from_sha256(hasher.finish(), config.length)
+            }
+        }
+    }
+}
+
+impl Hasher for MetadataHasher {
+    fn finish(&self) -> u64 {
+        match self {
+            MetadataHasher::SipHash(hasher) => hasher.finish(),
+            MetadataHasher::Sha256(hasher) => {
+                let mut hash = [0; 8];
+                hash.copy_from_slice(&hasher.clone().finish()[..8]);
+                u64::from_be_bytes(hash)
+            }
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            MetadataHasher::SipHash(hasher) => hasher.write(bytes),
+            MetadataHasher::Sha256(hasher) => {
+                hasher.update(bytes);
+            }
+        }
     }
 }
 
@@ -101,6 +261,9 @@ pub struct CompilationFiles<'a, 'cfg> {
     pub(super) host: Layout,
     /// The target directory layout for the target (if different from then host).
     pub(super) target: HashMap<CompileTarget, Layout>,
+    /// Layouts for packages whose profile sets `target-dir`, keyed by that
+    /// directory and the kind they are compiled for.
+    pub(super) isolated: BTreeMap<(InternedString, CompileKind), Layout>,
     /// Additional directory to include a copy of the outputs.
     export_dir: Option<PathBuf>,
     /// The root targets requested by the user on the command line (does not
@@ -142,6 +305,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         cx: &Context<'a, 'cfg>,
         host: Layout,
         target: HashMap<CompileTarget, Layout>,
+        isolated: BTreeMap<(InternedString, CompileKind), Layout>,
     ) -> CompilationFiles<'a, 'cfg> {
         let mut metas = HashMap::new();
         for unit in &cx.bcx.roots {
@@ -156,6 +320,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
             ws: cx.bcx.ws,
             host,
             target,
+            isolated,
             export_dir: cx.bcx.build_config.export_dir.clone(),
             roots: cx.bcx.roots.clone(),
             metas,
@@ -171,6 +336,31 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         }
     }
 
+    /// Returns the directory layout for the outputs of the given unit.
+    ///
+    /// This is the layout for `unit.kind`, unless the unit's profile places
+    /// its package in its own `target-dir`.
+    pub fn unit_layout(&self, unit: &Unit) -> &Layout {
+        self.package_layout(unit, unit.kind)
+    }
+
+    /// Returns the layout for `kind` that the package of `unit` uses.
+    fn package_layout(&self, unit: &Unit, kind: CompileKind) -> &Layout {
+        unit.profile
+            .target_dir
+            .and_then(|dir| self.isolated.get(&(dir, kind)))
+            .unwrap_or_else(|| self.layout(kind))
+    }
+
+    /// Returns the `deps` directories of all packages with their own
+    /// `target-dir` which are compiled for `kind`.
+    pub fn isolated_deps_dirs(&self, kind: CompileKind) -> impl Iterator<Item = &Path> {
+        self.isolated
+            .iter()
+            .filter(move |((_, k), _)| *k == kind)
+            .map(|(_, layout)| layout.deps())
+    }
+
     /// Gets the metadata for the given unit.
     ///
     /// See [`Metadata`] and [`fingerprint`] module for more.
@@ -205,7 +395,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         } else if unit.target.is_custom_build() {
             self.build_script_dir(unit)
         } else if unit.target.is_example() {
-            self.layout(unit.kind).examples().to_path_buf()
+            self.unit_layout(unit).examples().to_path_buf()
         } else if unit.artifact.is_true() {
             self.artifact_dir(unit)
         } else {
@@ -250,13 +440,22 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
     /// Returns the directories where Rust crate dependencies are found for the
     /// specified unit.
     pub fn deps_dir(&self, unit: &Unit) -> &Path {
-        self.layout(unit.kind).deps()
+        self.unit_layout(unit).deps()
     }
 
     /// Directory where the fingerprint for the given unit should go.
     pub fn fingerprint_dir(&self, unit: &Unit) -> PathBuf {
         let dir = self.pkg_dir(unit);
-        self.layout(unit.kind).fingerprint().join(dir)
+        self.unit_layout(unit).fingerprint().join(dir)
+    }
+
+    /// Directory given to `rustc` with `-C incremental` for the given unit.
+    ///
+    /// Each unit gets its own, so that its incremental state can be removed
+    /// without touching the one of other units of the same crate.
+    pub fn incremental_dir(&self, unit: &Unit) -> PathBuf {
+        let dir = format!("{}-{}", unit.target.crate_name(), self.metadata(unit));
+        self.unit_layout(unit).incremental().join(dir)
     }
 
     /// Returns the path for a file in the fingerprint directory.
@@ -291,7 +490,9 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         assert!(!unit.mode.is_run_custom_build());
         assert!(self.metas.contains_key(unit));
         let dir = self.pkg_dir(unit);
-        self.layout(CompileKind::Host).build().join(dir)
+        self.package_layout(unit, CompileKind::Host)
+            .build()
+            .join(dir)
     }
 
     /// Returns the directory for compiled artifacts files.
@@ -315,7 +516,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                 invalid
             ),
         };
-        self.layout(unit.kind).artifact().join(dir).join(kind)
+        self.unit_layout(unit).artifact().join(dir).join(kind)
     }
 
     /// Returns the directory where information about running a build script
@@ -325,7 +526,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         assert!(unit.target.is_custom_build());
         assert!(unit.mode.is_run_custom_build());
         let dir = self.pkg_dir(unit);
-        self.layout(unit.kind).build().join(dir)
+        self.unit_layout(unit).build().join(dir)
     }
 
     /// Returns the "OUT_DIR" directory for running a build script.
@@ -411,11 +612,11 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         let filename = file_type.uplift_filename(&unit.target);
         let uplift_path = if unit.target.is_example() {
             // Examples live in their own little world.
-            self.layout(unit.kind).examples().join(filename)
+            self.unit_layout(unit).examples().join(filename)
         } else if unit.target.is_custom_build() {
             self.build_script_dir(unit).join(filename)
         } else {
-            self.layout(unit.kind).dest().join(filename)
+            self.unit_layout(unit).dest().join(filename)
         };
         if from_path == uplift_path {
             // This can happen with things like examples that reside in the
@@ -572,7 +773,8 @@ fn compute_metadata(
     metas: &mut HashMap<Unit, MetaInfo>,
 ) -> MetaInfo {
     let bcx = &cx.bcx;
-    let mut hasher = StableHasher::new();
+    let metadata_hash = bcx.build_config.metadata_hash;
+    let mut hasher = MetadataHasher::new(metadata_hash);
 
     METADATA_VERSION.hash(&mut hasher);
 
@@ -624,6 +826,9 @@ fn compute_metadata(
             path.hash(&mut hasher);
         }
     }
+    if tool_wrapper::applies_to(cx, unit) {
+        bcx.build_config.tool_wrappers.hash(&mut hasher);
+    }
 
     // Seed the contents of `__CARGO_DEFAULT_LIB_METADATA` to the hasher if present.
     // This should be the release channel, to get a different hash for each channel.
@@ -641,13 +846,13 @@ fn compute_metadata(
     unit.is_std.hash(&mut hasher);
 
     MetaInfo {
-        meta_hash: Metadata(hasher.finish()),
+        meta_hash: hasher.finish_metadata(metadata_hash),
         use_extra_filename: should_use_metadata(bcx, unit),
     }
 }
 
 /// Hash the version of rustc being used during the build process.
-fn hash_rustc_version(bcx: &BuildContext<'_, '_>, hasher: &mut StableHasher) {
+fn hash_rustc_version(bcx: &BuildContext<'_, '_>, hasher: &mut impl Hasher) {
     let vers = &bcx.rustc().version;
     if vers.pre.is_empty() || bcx.config.cli_unstable().separate_nightlies {
         // For stable, keep the artifacts separate. This helps if someone is
@@ -1,32 +1,35 @@
 //! [`Context`] is the mutable state used during the build process.
 
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::compiler::compilation::{self, UnitOutput};
 use crate::core::compiler::{self, artifact, Unit};
 use crate::core::PackageId;
+use crate::ops;
 use crate::util::errors::CargoResult;
-use crate::util::profile;
+use crate::util::{profile, Filesystem};
 use anyhow::{bail, Context as _};
 use filetime::FileTime;
 use jobserver::Client;
 
 use super::build_plan::BuildPlan;
 use super::custom_build::{self, BuildDeps, BuildScriptOutputs, BuildScripts};
-use super::fingerprint::Fingerprint;
+use super::fingerprint::{DirtyReason, Fingerprint};
 use super::job_queue::JobQueue;
-use super::layout::Layout;
+use super::layout::{HashedUnit, LastUse, Layout, MetadataHashes, UnitUse};
 use super::lto::Lto;
 use super::unit_graph::UnitDep;
 use super::{
-    BuildContext, Compilation, CompileKind, CompileMode, Executor, FileFlavor, RustDocFingerprint,
+    BuildContext, Compilation, CompileKind, CompileMode, CompileTarget, Executor, FileFlavor,
+    RustDocFingerprint,
 };
 
 mod compilation_files;
 use self::compilation_files::CompilationFiles;
-pub use self::compilation_files::{Metadata, OutputFile};
+pub use self::compilation_files::{Metadata, MetadataHash, MetadataHashAlgorithm, OutputFile};
 
 /// Collection of all the stuff that is needed to perform a build.
 ///
@@ -84,6 +87,14 @@ pub struct Context<'a, 'cfg> {
     /// because the target has a type error. This is in an Arc<Mutex<..>>
     /// because it is continuously updated as the job progresses.
     pub failed_scrape_units: Arc<Mutex<HashSet<Metadata>>>,
+
+    /// Units outside of the primary packages which need to be rebuilt, with
+    /// why, collected with `--no-deps-rebuild`.
+    pub dirty_deps: Vec<(Unit, Option<DirtyReason>)>,
+
+    /// Whether the warning that build scripts can't be sandboxed on this
+    /// system was already emitted, with `-Zbuild-script-sandbox`.
+    pub sandbox_unavailable_warned: bool,
 }
 
 impl<'a, 'cfg> Context<'a, 'cfg> {
@@ -122,6 +133,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             lto: HashMap::new(),
             metadata_for_doc_units: HashMap::new(),
             failed_scrape_units: Arc::new(Mutex::new(HashSet::new())),
+            dirty_deps: Vec::new(),
+            sandbox_unavailable_warned: false,
         })
     }
 
@@ -133,9 +146,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     /// [`ops::cargo_compile`]: ../../../ops/cargo_compile/index.html
     pub fn compile(mut self, exec: &Arc<dyn Executor>) -> CargoResult<Compilation<'cfg>> {
         let mut queue = JobQueue::new(self.bcx);
-        let mut plan = BuildPlan::new();
+        let mut plan = BuildPlan::new(self.bcx.build_config.build_plan_version);
         let build_plan = self.bcx.build_config.build_plan;
-        self.lto = super::lto::generate(self.bcx)?;
         self.prepare_units()?;
         self.prepare()?;
         custom_build::build_map(&mut self)?;
@@ -158,6 +170,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             let force_rebuild = self.bcx.build_config.force_rebuild;
             super::compile(&mut self, &mut queue, &mut plan, unit, exec, force_rebuild)?;
         }
+        if !self.dirty_deps.is_empty() {
+            self.report_dirty_deps()?;
+        }
 
         // Now that we've got the full job queue and we've done all our
         // fingerprint analysis to determine what to run, bust all the memoized
@@ -169,9 +184,17 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             fingerprint.clear_memoized();
         }
 
+        if self.bcx.config.cli_unstable().metadata_hash {
+            self.record_metadata_hashes()?;
+        }
+
         // Now that we've figured out everything that we're going to do, do it!
         queue.execute(&mut self, &mut plan)?;
 
+        if self.bcx.config.cli_unstable().target_dir_gc {
+            self.compilation.last_used_units = self.record_last_use()?;
+        }
+
         if build_plan {
             plan.set_inputs(self.build_plan_inputs()?);
             plan.output_plan(self.bcx.config);
@@ -295,6 +318,81 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         Ok(self.compilation)
     }
 
+    /// Records the use of the artifacts of all units by this build, in the
+    /// [`LastUse`] of their build directory, for `cargo clean gc`.
+    ///
+    /// Returns the names of the units recorded.
+    fn record_last_use(&self) -> CargoResult<HashSet<String>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let lockfile = ops::lockfile_path(self.bcx.ws);
+        let files = self.files();
+        let mut by_dest: BTreeMap<&Path, Vec<(String, &Unit)>> = BTreeMap::new();
+        let mut recorded = HashSet::new();
+        for unit in self.bcx.unit_graph.keys() {
+            let dir = files.fingerprint_dir(unit);
+            let Some(name) = dir.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            by_dest
+                .entry(files.unit_layout(unit).dest())
+                .or_default()
+                .push((name.to_string(), unit));
+        }
+        for (dest, units) in by_dest {
+            let mut last_use = LastUse::load(dest);
+            last_use.lockfiles.insert(lockfile.clone());
+            for (name, unit) in units {
+                let unit_use = UnitUse {
+                    name: unit.pkg.name().to_string(),
+                    version: unit.pkg.version().to_string(),
+                    last_use: now,
+                };
+                last_use.units.insert(name.clone(), unit_use);
+                recorded.insert(name);
+            }
+            last_use.save(dest)?;
+        }
+        Ok(recorded)
+    }
+
+    /// Records the metadata hash of all units in the [`MetadataHashes`] of
+    /// their build directory, for `cargo explain-hash`.
+    fn record_metadata_hashes(&self) -> CargoResult<()> {
+        let files = self.files();
+        let mut by_dest: BTreeMap<&Path, Vec<&Unit>> = BTreeMap::new();
+        for unit in self.bcx.unit_graph.keys() {
+            by_dest
+                .entry(files.unit_layout(unit).dest())
+                .or_default()
+                .push(unit);
+        }
+        for (dest, units) in by_dest {
+            let mut hashes = MetadataHashes::load(dest);
+            for unit in units {
+                let mode = serde_json::to_value(unit.mode)?;
+                let hashed = HashedUnit {
+                    package_id: unit.pkg.package_id().to_string(),
+                    target_name: unit.target.name().to_string(),
+                    target_kind: unit.target.kind().description().to_string(),
+                    mode: mode.as_str().unwrap_or_default().to_string(),
+                    profile: unit.profile.name.to_string(),
+                    features: unit.features.iter().map(|f| f.to_string()).collect(),
+                    kind: match unit.kind {
+                        CompileKind::Host => None,
+                        CompileKind::Target(target) => Some(target.short_name().to_string()),
+                    },
+                };
+                hashes
+                    .units
+                    .insert(files.metadata(unit).to_string(), hashed);
+            }
+            hashes.save(dest)?;
+        }
+        Ok(())
+    }
+
     /// Returns the executable for the specified unit (if any).
     pub fn get_executable(&mut self, unit: &Unit) -> CargoResult<Option<PathBuf>> {
         let is_binary = unit.target.is_executable();
@@ -310,15 +408,52 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     }
 
     pub fn prepare_units(&mut self) -> CargoResult<()> {
+        self.prepare_layouts(true)
+    }
+
+    /// Like [`Context::prepare_units`], but without creating the build
+    /// directories or locking them, to only read what previous builds left.
+    pub fn prepare_units_unlocked(&mut self) -> CargoResult<()> {
+        self.prepare_layouts(false)
+    }
+
+    fn prepare_layouts(&mut self, lock: bool) -> CargoResult<()> {
+        // The metadata of the units computed below hashes their LTO setting.
+        self.lto = super::lto::generate(self.bcx)?;
         let dest = self.bcx.profiles.get_dir_name();
-        let host_layout = Layout::new(self.bcx.ws, None, &dest)?;
+        let ws = self.bcx.ws;
+        let layout = |root: Filesystem, target: Option<CompileTarget>| {
+            if lock {
+                Layout::at(ws, root, target, &dest)
+            } else {
+                Ok(Layout::unlocked(root, target, &dest))
+            }
+        };
+        let host_layout = layout(ws.target_dir(), None)?;
         let mut targets = HashMap::new();
         for kind in self.bcx.all_kinds.iter() {
             if let CompileKind::Target(target) = *kind {
-                let layout = Layout::new(self.bcx.ws, Some(target), &dest)?;
-                targets.insert(target, layout);
+                targets.insert(target, layout(ws.target_dir(), Some(target))?);
             }
         }
+        let mut isolated = BTreeMap::new();
+        let shared_root = ws.target_dir();
+        for unit in self.bcx.unit_graph.keys() {
+            let Some(dir) = unit.profile.target_dir else {
+                continue;
+            };
+            let root = Filesystem::new(ws.root().join(dir.as_str()));
+            if root.as_path_unlocked() == shared_root.as_path_unlocked()
+                || isolated.contains_key(&(dir, unit.kind))
+            {
+                continue;
+            }
+            let target = match unit.kind {
+                CompileKind::Host => None,
+                CompileKind::Target(target) => Some(target),
+            };
+            isolated.insert((dir, unit.kind), layout(root, target)?);
+        }
         self.primary_packages
             .extend(self.bcx.roots.iter().map(|u| u.pkg.package_id()));
         self.compilation
@@ -327,7 +462,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
 
         self.record_units_requiring_metadata();
 
-        let files = CompilationFiles::new(self, host_layout, targets);
+        let files = CompilationFiles::new(self, host_layout, targets, isolated);
         self.files = Some(files);
         Ok(())
     }
@@ -348,6 +483,11 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 .prepare()
                 .with_context(|| "couldn't prepare build directories")?;
         }
+        for layout in self.files.as_mut().unwrap().isolated.values_mut() {
+            layout
+                .prepare()
+                .with_context(|| "couldn't prepare build directories")?;
+        }
 
         let files = self.files.as_ref().unwrap();
         for &kind in self.bcx.all_kinds.iter() {
@@ -411,6 +551,34 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         self.primary_packages.contains(&unit.pkg.package_id())
     }
 
+    /// Explains why each of the [`Context::dirty_deps`] needs to be rebuilt,
+    /// and errors out, for `--no-deps-rebuild`.
+    fn report_dirty_deps(&self) -> CargoResult<()> {
+        let ws_root = self.bcx.ws.root();
+        let mut shell = self.bcx.config.shell();
+        for (unit, reason) in &self.dirty_deps {
+            match reason {
+                Some(reason) => reason.present_to(&mut shell, unit, ws_root)?,
+                None => shell.status(
+                    "Dirty",
+                    format_args!("{}: no previous build was found", unit.pkg),
+                )?,
+            }
+        }
+        let pkgs: BTreeSet<_> = self
+            .dirty_deps
+            .iter()
+            .map(|(unit, _)| unit.pkg.package_id())
+            .collect();
+        bail!(
+            "`--no-deps-rebuild` was passed, but these dependencies need to be rebuilt:\n  {}",
+            pkgs.iter()
+                .map(|pkg| pkg.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        )
+    }
+
     /// Returns the list of filenames read by cargo to generate the [`BuildContext`]
     /// (all `Cargo.toml`, etc.).
     pub fn build_plan_inputs(&self) -> CargoResult<Vec<PathBuf>> {
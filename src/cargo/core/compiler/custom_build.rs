@@ -35,16 +35,23 @@ use super::{fingerprint, Context, Job, Unit, Work};
 use crate::core::compiler::artifact;
 use crate::core::compiler::context::Metadata;
 use crate::core::compiler::job_queue::JobState;
-use crate::core::{profiles::ProfileRoot, PackageId, Target};
+use crate::core::manifest::DeclarativeBuild;
+use crate::core::{profiles::ProfileRoot, OutputCategory, PackageId, Target};
+use crate::sources::PathSource;
 use crate::util::errors::CargoResult;
 use crate::util::machine_message::{self, Message};
-use crate::util::{internal, profile};
+use crate::util::{internal, profile, Config};
 use anyhow::{bail, Context as _};
 use cargo_platform::Cfg;
-use cargo_util::paths;
+use cargo_util::{paths, ProcessBuilder};
+use filetime::FileTime;
 use std::collections::hash_map::{Entry, HashMap};
 use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
 use std::str;
 use std::sync::{Arc, Mutex};
 
@@ -54,6 +61,24 @@ use std::sync::{Arc, Mutex};
 /// [the doc]: https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#cargo-warning
 const CARGO_WARNING: &str = "cargo:warning=";
 
+/// Environment variables inherited by build scripts run in the sandbox of
+/// `-Zbuild-script-sandbox`, on top of the ones set by Cargo.
+const SANDBOX_ENV: &[&str] = &[
+    "CARGO_HOME",
+    "HOME",
+    "LANG",
+    "PATH",
+    "RUSTUP_HOME",
+    "RUSTUP_TOOLCHAIN",
+    "SYSTEMROOT",
+    "TEMP",
+    "TERM",
+    "TMP",
+    "TMPDIR",
+    "USERPROFILE",
+    "WINDIR",
+];
+
 /// Contains the parsed output of a custom build script.
 #[derive(Clone, Debug, Hash, Default)]
 pub struct BuildOutput {
@@ -251,17 +276,25 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     let build_script_unit = dependencies
         .iter()
         .find(|d| !d.unit.mode.is_run_custom_build() && d.unit.target.is_custom_build())
-        .map(|d| &d.unit)
-        .expect("running a script not depending on an actual script");
-    let script_dir = cx.files().build_script_dir(build_script_unit);
+        .map(|d| &d.unit);
+    let declarative_build = unit.pkg.manifest().declarative_build().cloned();
     let script_out_dir = cx.files().build_script_out_dir(unit);
     let script_run_dir = cx.files().build_script_run_dir(unit);
     let build_plan = bcx.build_config.build_plan;
     let invocation_name = unit.buildkey();
 
-    if let Some(deps) = unit.pkg.manifest().metabuild() {
-        prepare_metabuild(cx, build_script_unit, deps)?;
-    }
+    let script_dir = match build_script_unit {
+        Some(build_script_unit) => {
+            if let Some(deps) = unit.pkg.manifest().metabuild() {
+                prepare_metabuild(cx, build_script_unit, deps)?;
+            }
+            cx.files().build_script_dir(build_script_unit)
+        }
+        // Declarative builds are run by Cargo itself, which only needs the
+        // environment of the command below.
+        None if declarative_build.is_some() => script_run_dir.clone(),
+        None => panic!("running a script not depending on an actual script"),
+    };
 
     // Building the command to execute
     let to_exec = script_dir.join(unit.target.name());
@@ -377,7 +410,7 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         })
         .collect::<Vec<_>>();
     let library_name = unit.pkg.library().map(|t| t.crate_name());
-    let pkg_descr = unit.pkg.to_string();
+    let pkg_descr = cx.bcx.ws.display_package_id(unit.pkg.package_id());
     let build_script_outputs = Arc::clone(&cx.build_script_outputs);
     let id = unit.pkg.package_id();
     let output_file = script_run_dir.join("output");
@@ -394,18 +427,49 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     );
     let build_scripts = cx.build_scripts.get(unit).cloned();
     let json_messages = bcx.build_config.emit_json();
-    let extra_verbose = bcx.config.extra_verbose();
+    let show_output = bcx.config.shell().shows(OutputCategory::BuildScriptOutput);
     let (prev_output, prev_script_out_dir) = prev_build_output(cx, unit);
     let metadata_hash = cx.get_run_build_script_metadata(unit);
 
     paths::create_dir_all(&script_dir)?;
     paths::create_dir_all(&script_out_dir)?;
 
+    let mut job = if build_plan {
+        Job::new_dirty(Work::noop(), None)
+    } else {
+        fingerprint::prepare_target(cx, unit, false)?
+    };
+
+    // Listing the files of the package is only worth it when the build
+    // script is going to run.
+    let sandbox = if cx.bcx.config.cli_unstable().build_script_sandbox
+        && declarative_build.is_none()
+        && !build_plan
+        && job.freshness().is_dirty()
+    {
+        let sandbox = Sandbox::new(cx.bcx.config, unit, &script_out_dir)?;
+        if sandbox.wrapper.is_none() && !cx.sandbox_unavailable_warned {
+            cx.sandbox_unavailable_warned = true;
+            cx.bcx.config.shell().warn(
+                "no sandboxing facility was found for `-Zbuild-script-sandbox`, \
+                 build scripts keep network access and can write anywhere\n\
+                 On Linux, install bubblewrap (`bwrap`) to restrict them.",
+            )?;
+        }
+        Some(sandbox)
+    } else {
+        None
+    };
+
     let nightly_features_allowed = cx.bcx.config.nightly_features_allowed;
+    let retry = cx.bcx.build_config.retry.clone();
     let extra_check_cfg = match cx.bcx.config.cli_unstable().check_cfg {
         Some((_, _, _, output)) => output,
         None => false,
     };
+    let pkg_root = unit.pkg.root().to_path_buf();
+    let path_style = cx.bcx.config.path_style();
+    let ws_root = cx.bcx.ws.root().to_path_buf();
     let targets: Vec<Target> = unit.pkg.targets().to_vec();
     // Need a separate copy for the fresh closure.
     let targets_fresh = targets.clone();
@@ -469,53 +533,115 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         }
 
         // And now finally, run the build command itself!
-        state.running(&cmd);
         let timestamp = paths::set_invocation_time(&script_run_dir)?;
         let prefix = format!("[{} {}] ", id.name(), id.version());
         let mut warnings_in_case_of_panic = Vec::new();
-        let output = cmd
-            .exec_with_streaming(
+        let mut run = |cmd: &ProcessBuilder| {
+            state.running(cmd);
+            warnings_in_case_of_panic.clear();
+            cmd.exec_with_streaming(
                 &mut |stdout| {
                     if let Some(warning) = stdout.strip_prefix(CARGO_WARNING) {
                         warnings_in_case_of_panic.push(warning.to_owned());
                     }
-                    if extra_verbose {
+                    if show_output {
                         state.stdout(format!("{}{}", prefix, stdout))?;
                     }
                     Ok(())
                 },
                 &mut |stderr| {
-                    if extra_verbose {
+                    if show_output {
                         state.stderr(format!("{}{}", prefix, stderr))?;
                     }
                     Ok(())
                 },
                 true,
             )
-            .with_context(|| {
-                let mut build_error_context =
-                    format!("failed to run custom build command for `{}`", pkg_descr);
-
-                // If we're opting into backtraces, mention that build dependencies' backtraces can
-                // be improved by requesting debuginfo to be built, if we're not building with
-                // debuginfo already.
-                //
-                // ALLOWED: Other tools like `rustc` might read it directly
-                // through `std::env`. We should make their behavior consistent.
-                #[allow(clippy::disallowed_methods)]
-                if let Ok(show_backtraces) = std::env::var("RUST_BACKTRACE") {
-                    if !built_with_debuginfo && show_backtraces != "0" {
-                        build_error_context.push_str(&format!(
-                            "\n\
-                            note: To improve backtraces for build dependencies, set the \
-                            CARGO_PROFILE_{env_profile_name}_BUILD_OVERRIDE_DEBUG=true environment \
-                            variable to enable debug information generation.",
-                        ));
+        };
+        let output = match (&declarative_build, &sandbox) {
+            (Some(build), _) => run_declarative_build(
+                state,
+                build,
+                &cmd,
+                id.name().as_str(),
+                &pkg_root,
+                &script_out_dir,
+            )
+            .map(|stdout| Output {
+                status: ExitStatus::default(),
+                stdout,
+                stderr: Vec::new(),
+            }),
+            (None, Some(sandbox)) => {
+                let snapshot = sandbox.snapshot();
+                // A failure in the sandbox fails the build, with a hint about
+                // what the sandbox may have blocked.
+                let output = run(&sandbox.wrap(&cmd)).with_context(|| {
+                    format!(
+                        "build script of `{}` failed in the sandbox of `-Zbuild-script-sandbox`\n\
+                         It may need network access, to write outside of `OUT_DIR`, or \
+                         environment variables hidden by the sandbox.",
+                        pkg_descr
+                    )
+                });
+                if output.is_ok() {
+                    for path in sandbox.modified(&snapshot) {
+                        state.warning(format!(
+                            "build script of `{}` modified `{}`, outside of `OUT_DIR`",
+                            pkg_descr,
+                            path_style.display(&path, &ws_root)
+                        ))?;
                     }
                 }
+                output
+            }
+            (None, None) => {
+                let mut retries = 0;
+                loop {
+                    let output = run(&cmd);
+                    let Some(flake) = output
+                        .as_ref()
+                        .err()
+                        .and_then(|e| retry.should_retry(e, retries))
+                    else {
+                        break output;
+                    };
+                    retries += 1;
+                    state.stderr(format!(
+                        "note: build script of `{}` {}, running it again (retry {retries} of {})",
+                        pkg_descr,
+                        flake.describe(),
+                        retry.count(),
+                    ))?;
+                    state.retried(flake);
+                }
+            }
+        };
+        let output = output.with_context(|| {
+            let mut build_error_context =
+                format!("failed to run custom build command for `{}`", pkg_descr);
+
+            // If we're opting into backtraces, mention that build dependencies' backtraces can
+            // be improved by requesting debuginfo to be built, if we're not building with
+            // debuginfo already. Declarative builds run no build script, so there is
+            // nothing to improve.
+            //
+            // ALLOWED: Other tools like `rustc` might read it directly
+            // through `std::env`. We should make their behavior consistent.
+            #[allow(clippy::disallowed_methods)]
+            if let Ok(show_backtraces) = std::env::var("RUST_BACKTRACE") {
+                if !built_with_debuginfo && declarative_build.is_none() && show_backtraces != "0" {
+                    build_error_context.push_str(&format!(
+                        "\n\
+                        note: To improve backtraces for build dependencies, set the \
+                        CARGO_PROFILE_{env_profile_name}_BUILD_OVERRIDE_DEBUG=true environment \
+                        variable to enable debug information generation.",
+                    ));
+                }
+            }
 
-                build_error_context
-            });
+            build_error_context
+        });
 
         if let Err(error) = output {
             insert_warnings_in_build_outputs(
@@ -593,11 +719,6 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         Ok(())
     });
 
-    let mut job = if cx.bcx.build_config.build_plan {
-        Job::new_dirty(Work::noop(), None)
-    } else {
-        fingerprint::prepare_target(cx, unit, false)?
-    };
     if job.freshness().is_dirty() {
         job.before(dirty);
     } else {
@@ -606,6 +727,258 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     Ok(job)
 }
 
+/// Runs the directives of `[package.metadata.build]`, with the environment a
+/// build script would get from `env`.
+///
+/// Returns the instructions a build script doing the same would print, so
+/// that the result is handled like the output of a build script.
+fn run_declarative_build(
+    state: &JobState<'_, '_>,
+    build: &DeclarativeBuild,
+    env: &ProcessBuilder,
+    pkg_name: &str,
+    pkg_root: &Path,
+    out_dir: &Path,
+) -> CargoResult<Vec<u8>> {
+    let env_var = |key: &str| env.get_env(key).map(|v| v.to_string_lossy().into_owned());
+    let mut stdout = String::new();
+    writeln!(stdout, "cargo:rerun-if-changed=Cargo.toml")?;
+
+    let mut include_dirs = Vec::new();
+    for lib in &build.pkg_config {
+        let mut cmd =
+            ProcessBuilder::new(env_var("PKG_CONFIG").unwrap_or_else(|| "pkg-config".to_string()));
+        cmd.cwd(pkg_root).arg("--libs").arg("--cflags").arg(lib);
+        for key in ["PKG_CONFIG_PATH", "PKG_CONFIG_SYSROOT_DIR"] {
+            if let Some(value) = env_var(key) {
+                cmd.env(key, value);
+            }
+        }
+        state.running(&cmd);
+        let output = cmd
+            .exec_with_output()
+            .with_context(|| format!("failed to find system library `{}`", lib))?;
+        let flags = String::from_utf8(output.stdout)
+            .with_context(|| format!("`pkg-config` output for `{}` isn't UTF-8", lib))?;
+        for flag in flags.split_whitespace() {
+            if let Some(path) = flag.strip_prefix("-L") {
+                writeln!(stdout, "cargo:rustc-link-search=native={}", path)?;
+            } else if let Some(name) = flag.strip_prefix("-l") {
+                writeln!(stdout, "cargo:rustc-link-lib={}", name)?;
+            } else if let Some(path) = flag.strip_prefix("-I") {
+                include_dirs.push(path.to_string());
+            }
+        }
+    }
+    if !build.pkg_config.is_empty() {
+        for key in ["PKG_CONFIG", "PKG_CONFIG_PATH", "PKG_CONFIG_SYSROOT_DIR"] {
+            writeln!(stdout, "cargo:rerun-if-env-changed={}", key)?;
+        }
+    }
+
+    if !build.cc.is_empty() {
+        let target = env_var("TARGET").unwrap_or_default();
+        if target.contains("msvc") {
+            bail!("compiling C files with `package.metadata.build` isn't supported for `{target}`");
+        }
+        let cc = env_var("CC").unwrap_or_else(|| "cc".to_string());
+        let cflags = env_var("CFLAGS").unwrap_or_default();
+        let mut objects = Vec::new();
+        for (i, file) in build.cc.iter().enumerate() {
+            let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or("c");
+            let object = out_dir.join(format!("{}-{}.o", i, stem));
+            let mut cmd = ProcessBuilder::new(&cc);
+            cmd.cwd(pkg_root)
+                .arg("-c")
+                .arg(file)
+                .arg("-o")
+                .arg(&object)
+                .arg("-fPIC");
+            for dir in &include_dirs {
+                cmd.arg(format!("-I{}", dir));
+            }
+            for flag in cflags.split_whitespace() {
+                cmd.arg(flag);
+            }
+            state.running(&cmd);
+            cmd.exec_with_output().with_context(|| {
+                format!(
+                    "failed to run `{}` for declarative build of {}",
+                    cc, pkg_name
+                )
+            })?;
+            writeln!(stdout, "cargo:rerun-if-changed={}", file.display())?;
+            objects.push(object);
+        }
+
+        let lib_name = format!("{}_cc", pkg_name.replace('-', "_"));
+        let archive = out_dir.join(format!("lib{}.a", lib_name));
+        // `ar` adds to existing archives, which would keep stale objects.
+        if archive.exists() {
+            paths::remove_file(&archive)?;
+        }
+        let ar = env_var("AR").unwrap_or_else(|| "ar".to_string());
+        let mut cmd = ProcessBuilder::new(&ar);
+        cmd.arg("crs").arg(&archive).args(&objects);
+        state.running(&cmd);
+        cmd.exec_with_output().with_context(|| {
+            format!(
+                "failed to run `{}` for declarative build of {}",
+                ar, pkg_name
+            )
+        })?;
+        writeln!(
+            stdout,
+            "cargo:rustc-link-search=native={}",
+            out_dir.display()
+        )?;
+        writeln!(stdout, "cargo:rustc-link-lib=static={}", lib_name)?;
+        for key in ["CC", "CFLAGS", "AR"] {
+            writeln!(stdout, "cargo:rerun-if-env-changed={}", key)?;
+        }
+    }
+
+    for (cfg, key) in &build.cfg_env {
+        writeln!(stdout, "cargo:rerun-if-env-changed={}", key)?;
+        if env_var(key).map_or(false, |value| !value.is_empty() && value != "0") {
+            writeln!(stdout, "cargo:rustc-cfg={}", cfg)?;
+        }
+    }
+
+    Ok(stdout.into_bytes())
+}
+
+/// The restrictions of a build script run with `-Zbuild-script-sandbox`.
+///
+/// Environment variables are always restricted to the ones set by Cargo and
+/// [`SANDBOX_ENV`]. Where the platform provides a facility for it, network
+/// access is blocked and writes are limited to `OUT_DIR`. Modifications of the
+/// files of the package are detected everywhere.
+struct Sandbox {
+    /// The command enforcing the restrictions of the platform, if any.
+    wrapper: Option<Vec<OsString>>,
+    /// Environment variables of Cargo hidden from the build script.
+    hidden_env: Vec<String>,
+    /// The root of the package.
+    root: PathBuf,
+    /// The files of the package, watched for modifications.
+    files: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    fn new(config: &Config, unit: &Unit, script_out_dir: &Path) -> CargoResult<Sandbox> {
+        let source_id = unit.pkg.package_id().source_id();
+        let mut files =
+            PathSource::new(unit.pkg.root(), source_id, config).list_files(&unit.pkg)?;
+        files.retain(|path| !path.starts_with(script_out_dir));
+        let hidden_env = config
+            .env()
+            .map(|(key, _)| key)
+            .filter(|key| !SANDBOX_ENV.iter().any(|var| var.eq_ignore_ascii_case(key)))
+            .map(str::to_string)
+            .collect();
+        Ok(Sandbox {
+            wrapper: sandbox_wrapper(config, script_out_dir),
+            hidden_env,
+            root: unit.pkg.root().to_path_buf(),
+            files,
+        })
+    }
+
+    /// Returns `cmd` restricted by the sandbox.
+    fn wrap(&self, cmd: &ProcessBuilder) -> ProcessBuilder {
+        let mut cmd = cmd.clone();
+        for key in &self.hidden_env {
+            if !cmd.get_envs().contains_key(key) {
+                cmd.env_remove(key);
+            }
+        }
+        // The outermost wrapper is the last one, so the program comes last.
+        if let Some(wrapper) = &self.wrapper {
+            for arg in wrapper.iter().rev() {
+                cmd = cmd.wrapped(Some(arg));
+            }
+        }
+        cmd
+    }
+
+    /// Records the modification time and size of each file of the package.
+    fn snapshot(&self) -> Vec<Option<(FileTime, u64)>> {
+        self.files.iter().map(|path| file_state(path)).collect()
+    }
+
+    /// Returns the files of the package changed since `snapshot`, relative to
+    /// its root.
+    fn modified<'a>(
+        &'a self,
+        snapshot: &'a [Option<(FileTime, u64)>],
+    ) -> impl Iterator<Item = &'a Path> + 'a {
+        self.files
+            .iter()
+            .zip(snapshot)
+            .filter(|(path, before)| file_state(path) != **before)
+            .map(|(path, _)| path.strip_prefix(&self.root).unwrap_or(path))
+    }
+}
+
+fn file_state(path: &Path) -> Option<(FileTime, u64)> {
+    let metadata = path.metadata().ok()?;
+    Some((
+        FileTime::from_last_modification_time(&metadata),
+        metadata.len(),
+    ))
+}
+
+/// Returns the command running a build script without network access and with
+/// writes limited to `out_dir`, with `bwrap` on Linux and `sandbox-exec` on
+/// macOS.
+fn sandbox_wrapper(config: &Config, out_dir: &Path) -> Option<Vec<OsString>> {
+    if cfg!(target_os = "linux") {
+        let path = config.get_env_os("PATH")?;
+        let bwrap = env::split_paths(&path)
+            .map(|dir| dir.join("bwrap"))
+            .find(|bwrap| bwrap.is_file())?;
+        let mut wrapper: Vec<OsString> = vec![bwrap.into()];
+        wrapper.extend(
+            [
+                "--die-with-parent",
+                "--unshare-net",
+                "--ro-bind",
+                "/",
+                "/",
+                "--dev",
+                "/dev",
+                "--proc",
+                "/proc",
+                "--tmpfs",
+                "/tmp",
+                "--bind",
+            ]
+            .map(OsString::from),
+        );
+        wrapper.push(out_dir.into());
+        wrapper.push(out_dir.into());
+        wrapper.push("--".into());
+        Some(wrapper)
+    } else if cfg!(target_os = "macos") {
+        let sandbox_exec = Path::new("/usr/bin/sandbox-exec");
+        if !sandbox_exec.is_file() {
+            return None;
+        }
+        // Profiles match paths with symlinks resolved.
+        let out_dir = out_dir.canonicalize().ok()?;
+        let profile = format!(
+            "(version 1)(allow default)(deny network*)(deny file-write*)\
+             (allow file-write* (subpath \"{}\") (subpath \"/private/tmp\") \
+             (subpath \"/private/var/folders\") (literal \"/dev/null\"))",
+            out_dir.display()
+        );
+        Some(vec![sandbox_exec.into(), "-p".into(), profile.into()])
+    } else {
+        None
+    }
+}
+
 /// When a build script run fails, store only warnings and nuke other outputs,
 /// as they are likely broken.
 fn insert_warnings_in_build_outputs(
@@ -1041,17 +1414,23 @@ pub fn build_map(cx: &mut Context<'_, '_>) -> CargoResult<()> {
             return Ok(&out[unit]);
         }
 
-        // If there is a build script override, pre-fill the build output.
+        // If there is a build script override, pre-fill the build output. The
+        // probes of `[build-probe]` come after the overrides of the config.
         if unit.mode.is_run_custom_build() {
-            if let Some(links) = unit.pkg.manifest().links() {
-                if let Some(output) = cx.bcx.target_data.script_override(links, unit.kind) {
-                    let metadata = cx.get_run_build_script_metadata(unit);
-                    cx.build_script_outputs.lock().unwrap().insert(
-                        unit.pkg.package_id(),
-                        metadata,
-                        output.clone(),
-                    );
-                }
+            let output = unit
+                .pkg
+                .manifest()
+                .links()
+                .and_then(|links| cx.bcx.target_data.script_override(links, unit.kind))
+                .cloned()
+                .or_else(|| cx.bcx.target_data.build_probe(&unit.pkg, unit.kind));
+            if let Some(output) = output {
+                let metadata = cx.get_run_build_script_metadata(unit);
+                cx.build_script_outputs.lock().unwrap().insert(
+                    unit.pkg.package_id(),
+                    metadata,
+                    output,
+                );
             }
         }
 
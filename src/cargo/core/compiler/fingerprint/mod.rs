@@ -88,7 +88,8 @@
 //!        release channel (bootstrap/stable/beta/nightly) in libstd.
 //!
 //! [^5]: Config settings that are not otherwise captured anywhere else.
-//!       Currently, this is only `doc.extern-map`.
+//!       Currently, this is `doc.extern-map`, and `build.post-link-hooks`
+//!       for the units they apply to.
 //!
 //! [^6]: Via [`Manifest::lint_rustflags`][crate::core::Manifest::lint_rustflags]
 //!
@@ -352,6 +353,7 @@
 //! [`A-rebuild-detection`]: https://github.com/rust-lang/cargo/issues?q=is%3Aissue+is%3Aopen+label%3AA-rebuild-detection
 
 mod dirty_reason;
+pub mod snapshot;
 
 use std::collections::hash_map::{Entry, HashMap};
 
@@ -380,7 +382,7 @@ use crate::util::{internal, path_args, profile, StableHasher};
 use crate::{Config, CARGO_ENV};
 
 use super::custom_build::BuildDeps;
-use super::{BuildContext, Context, FileFlavor, Job, Unit, Work};
+use super::{post_link, tool_wrapper, BuildContext, Context, FileFlavor, Job, Unit, Work};
 
 pub use dirty_reason::DirtyReason;
 
@@ -486,6 +488,21 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
         paths::write(&loc, b"")?;
     }
 
+    let snapshot = if bcx.config.cli_unstable().fingerprint_snapshot {
+        Some(snapshot::EnvSnapshot::new(cx, unit)?)
+    } else {
+        None
+    };
+    let pkg_root = unit.pkg.root().to_path_buf();
+    let target_root = target_root(cx, unit);
+    let write_snapshot = move |loc: &Path, fingerprint: &Fingerprint| match snapshot {
+        Some(snapshot) => {
+            let local = fingerprint.local.lock().unwrap();
+            snapshot.write(loc, &local, &pkg_root, &target_root)
+        }
+        None => Ok(()),
+    };
+
     let write_fingerprint = if unit.mode.is_run_custom_build() {
         // For build scripts the `local` field of the fingerprint may change
         // while we're executing it. For example it could be in the legacy
@@ -515,10 +532,14 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
                 *fingerprint.local.lock().unwrap() = new_local;
             }
 
-            write_fingerprint(&loc, &fingerprint)
+            write_fingerprint(&loc, &fingerprint)?;
+            write_snapshot(&loc, &fingerprint)
         })
     } else {
-        Work::new(move |_| write_fingerprint(&loc, &fingerprint))
+        Work::new(move |_| {
+            write_fingerprint(&loc, &fingerprint)?;
+            write_snapshot(&loc, &fingerprint)
+        })
     };
 
     Ok(Job::new_dirty(write_fingerprint, dirty_reason))
@@ -1341,7 +1362,7 @@ fn calculate(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Arc<Fingerpri
 
     // After we built the initial `Fingerprint` be sure to update the
     // `fs_status` field of it.
-    let target_root = target_root(cx);
+    let target_root = target_root(cx, unit);
     let cargo_exe = cx.bcx.config.cargo_exe()?;
     fingerprint.check_filesystem(
         &mut cx.mtime_cache,
@@ -1380,7 +1401,7 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
     };
 
     // Afterwards calculate our own fingerprint information.
-    let target_root = target_root(cx);
+    let target_root = target_root(cx, unit);
     let local = if unit.mode.is_doc() || unit.mode.is_doc_scrape() {
         // rustdoc does not have dep-info files.
         let fingerprint = pkg_fingerprint(cx.bcx, &unit.pkg).with_context(|| {
@@ -1437,6 +1458,13 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
     if let Some(allow_features) = &cx.bcx.config.cli_unstable().allow_features {
         allow_features.hash(&mut config);
     }
+    let post_link_hooks = &cx.bcx.build_config.post_link_hooks;
+    if !post_link_hooks.is_empty() && post_link::applies_to(unit) {
+        post_link_hooks.hash(&mut config);
+    }
+    if tool_wrapper::applies_to(cx, unit) {
+        cx.bcx.build_config.tool_wrappers.hash(&mut config);
+    }
     let compile_kind = unit.kind.fingerprint_hash();
     Ok(Fingerprint {
         rustc: util::hash_u64(&cx.bcx.rustc().verbose_version),
@@ -1592,7 +1620,7 @@ fn build_script_local_fingerprints(
     // longstanding bug, in Cargo. Recent refactorings just made it painfully
     // obvious.
     let pkg_root = unit.pkg.root().to_path_buf();
-    let target_dir = target_root(cx);
+    let target_dir = target_root(cx, unit);
     let calculate =
         move |deps: &BuildDeps, pkg_fingerprint: Option<&dyn Fn() -> CargoResult<String>>| {
             if deps.rerun_if_changed.is_empty() && deps.rerun_if_env_changed.is_empty() {
@@ -1727,8 +1755,13 @@ pub fn dep_info_loc(cx: &mut Context<'_, '_>, unit: &Unit) -> PathBuf {
 
 /// Returns an absolute path that target directory.
 /// All paths are rewritten to be relative to this.
-fn target_root(cx: &Context<'_, '_>) -> PathBuf {
-    cx.bcx.ws.target_dir().into_path_unlocked()
+///
+/// This is the `target-dir` of the package of `unit` if its profile sets one.
+pub fn target_root(cx: &Context<'_, '_>, unit: &Unit) -> PathBuf {
+    match unit.profile.target_dir {
+        Some(dir) => cx.bcx.ws.root().join(dir.as_str()),
+        None => cx.bcx.ws.target_dir().into_path_unlocked(),
+    }
 }
 
 /// Reads the value from the old fingerprint hash file and compare.
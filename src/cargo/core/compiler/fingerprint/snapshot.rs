@@ -0,0 +1,143 @@
+//! Snapshots of the environment a unit was built in.
+//!
+//! A fingerprint only keeps hashes of most of what it tracks, which is enough
+//! to tell that a unit is dirty, but not what changed. With
+//! `-Zfingerprint-snapshot`, an [`EnvSnapshot`] is written next to the
+//! fingerprint of each unit that is built. It records the values of the
+//! environment variables the unit depends on, and the tools which built it,
+//! so that `cargo fingerprint diff` can compare them with the current
+//! environment.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use serde::{Deserialize, Serialize};
+
+use crate::core::compiler::{Context, Unit};
+use crate::core::PackageId;
+use crate::util::errors::CargoResult;
+use crate::util::Rustc;
+use crate::{Config, CARGO_ENV};
+
+use super::{parse_dep_info, LocalFingerprint};
+
+/// Environment variables which Cargo reads the flags of all units from.
+const FLAGS_ENV: &[&str] = &[
+    "CARGO_ENCODED_RUSTFLAGS",
+    "RUSTFLAGS",
+    "CARGO_ENCODED_RUSTDOCFLAGS",
+    "RUSTDOCFLAGS",
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct EnvSnapshot {
+    pub package_id: PackageId,
+    /// The target of the unit, as in [`Target::description_named`].
+    ///
+    /// [`Target::description_named`]: crate::core::Target::description_named
+    pub target: String,
+    /// The compile mode of the unit, as in the unit graph.
+    pub mode: String,
+    /// Whether the package is a member of the workspace, which decides if
+    /// `build.rustc-workspace-wrapper` applies.
+    pub member: bool,
+    /// The tools building the unit, see [`tools`].
+    pub tools: BTreeMap<String, String>,
+    /// The environment variables the unit depends on, `None` if unset.
+    pub env: BTreeMap<String, Option<String>>,
+}
+
+impl EnvSnapshot {
+    /// Captures the environment `unit` is about to be built in.
+    pub fn new(cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<EnvSnapshot> {
+        let member = cx.bcx.ws.is_member(&unit.pkg);
+        let mode = serde_json::to_value(unit.mode)?;
+        Ok(EnvSnapshot {
+            package_id: unit.pkg.package_id(),
+            target: unit.target.description_named(),
+            mode: mode.as_str().unwrap_or_default().to_string(),
+            member,
+            tools: tools(cx.bcx.rustc(), member),
+            env: FLAGS_ENV
+                .iter()
+                .map(|key| (key.to_string(), current_env(cx.bcx.config, key)))
+                .collect(),
+        })
+    }
+
+    /// Reads the snapshot at `path`.
+    pub fn read(path: &Path) -> CargoResult<EnvSnapshot> {
+        let json = paths::read(path)?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse fingerprint snapshot `{}`", path.display()))
+    }
+
+    /// Adds the environment variables the unit was found to depend on while
+    /// building it, from its `local` fingerprints, and writes the snapshot
+    /// next to the fingerprint at `loc`.
+    pub(super) fn write(
+        mut self,
+        loc: &Path,
+        local: &[LocalFingerprint],
+        pkg_root: &Path,
+        target_root: &Path,
+    ) -> CargoResult<()> {
+        for local in local {
+            match local {
+                LocalFingerprint::RerunIfEnvChanged { var, val } => {
+                    self.env.insert(var.clone(), val.clone());
+                }
+                LocalFingerprint::CheckDepInfo { dep_info } => {
+                    let dep_info = target_root.join(dep_info);
+                    if let Some(info) = parse_dep_info(pkg_root, target_root, &dep_info)? {
+                        self.env.extend(info.env);
+                    }
+                }
+                LocalFingerprint::Precalculated(_) | LocalFingerprint::RerunIfChanged { .. } => {}
+            }
+        }
+        let json = serde_json::to_string(&self)?;
+        paths::write(&path(loc), json.as_bytes())
+    }
+}
+
+/// The path of the snapshot of the fingerprint at `loc`.
+fn path(loc: &Path) -> PathBuf {
+    loc.with_extension("snapshot.json")
+}
+
+/// Whether `path` is the path of a snapshot.
+pub fn is_snapshot(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| name.ends_with(".snapshot.json"))
+}
+
+/// The tools used to build units with `rustc`, by name.
+pub fn tools(rustc: &Rustc, member: bool) -> BTreeMap<String, String> {
+    let mut tools = BTreeMap::new();
+    tools.insert("rustc".to_string(), rustc.verbose_version.clone());
+    if let Some(wrapper) = &rustc.wrapper {
+        tools.insert("rustc-wrapper".to_string(), wrapper.display().to_string());
+    }
+    if let Some(wrapper) = rustc.workspace_wrapper.as_ref().filter(|_| member) {
+        tools.insert(
+            "rustc-workspace-wrapper".to_string(),
+            wrapper.display().to_string(),
+        );
+    }
+    tools
+}
+
+/// The current value of the environment variable `key`, as compared by
+/// [`LocalFingerprint::find_stale_item`].
+pub fn current_env(config: &Config, key: &str) -> Option<String> {
+    if key == CARGO_ENV {
+        let cargo_exe = config.cargo_exe().ok()?;
+        cargo_exe.to_str().map(str::to_string)
+    } else {
+        config.get_env(key).ok()
+    }
+}
@@ -0,0 +1,136 @@
+//! Retrying units which failed for reasons unrelated to their code, with
+//! `build.retry`.
+//!
+//! Some failures are caused by the environment of the build rather than by
+//! the code being built: the OOM killer sending `SIGKILL` to rustc, or a
+//! build script losing its network connection. Such a failure is classified
+//! as a [`Flake`] from how the process failed, and the unit is run again if
+//! the kind of flake is listed in `build.retry.classify`.
+//!
+//! Deterministic failures are never retried. A rustc invocation which
+//! reported an error diagnostic failed because of the code it compiled, so it
+//! isn't retried whatever the classification of its exit.
+
+use anyhow::bail;
+use cargo_util::ProcessError;
+
+use crate::util::config::BuildRetryConfig;
+use crate::util::CargoResult;
+
+/// The signal sent by the OOM killer, and by `kill -9`.
+const SIGKILL: i32 = 9;
+
+/// Messages in the output of a process which point at a network failure.
+const NETWORK_SIGNATURES: &[&str] = &[
+    "Connection refused",
+    "Connection reset by peer",
+    "Connection timed out",
+    "Could not resolve host",
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+    "failed to lookup address information",
+    "operation timed out",
+];
+
+/// A kind of failure which may succeed when run again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flake {
+    /// The process was killed by `SIGKILL`, usually by the OOM killer.
+    Sigkill,
+    /// The process exited with a failure after printing a network error.
+    Network,
+}
+
+impl Flake {
+    /// The name of the flake in `build.retry.classify`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Flake::Sigkill => "sigkill",
+            Flake::Network => "network",
+        }
+    }
+
+    /// Describes the failure, as in "rustc {}".
+    pub fn describe(self) -> &'static str {
+        match self {
+            Flake::Sigkill => "was killed by SIGKILL",
+            Flake::Network => "failed with a network error",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Flake> {
+        match name {
+            "sigkill" => Some(Flake::Sigkill),
+            "network" => Some(Flake::Network),
+            _ => None,
+        }
+    }
+
+    /// Classifies the failure `err` of a process, if it is a flake.
+    fn classify(err: &anyhow::Error) -> Option<Flake> {
+        let perr = err.chain().find_map(|e| e.downcast_ref::<ProcessError>())?;
+        if perr.signal == Some(SIGKILL) {
+            return Some(Flake::Sigkill);
+        }
+        let output_has_network_error = [&perr.stdout, &perr.stderr]
+            .into_iter()
+            .flatten()
+            .map(|output| String::from_utf8_lossy(output))
+            .any(|output| {
+                NETWORK_SIGNATURES
+                    .iter()
+                    .any(|signature| output.contains(signature))
+            });
+        if perr.code.is_some() && output_has_network_error {
+            return Some(Flake::Network);
+        }
+        None
+    }
+}
+
+/// How failing units are retried, from `build.retry`.
+#[derive(Clone, Debug, Default)]
+pub struct RetryPolicy {
+    /// How many times a unit may be run again.
+    count: u32,
+    /// The kinds of failures which are retried.
+    classify: Vec<Flake>,
+}
+
+impl RetryPolicy {
+    /// Parses `build.retry`. The count defaults to 1, and all kinds of flakes
+    /// are retried unless `classify` is given.
+    pub fn from_config(cfg: &BuildRetryConfig) -> CargoResult<RetryPolicy> {
+        let classify = match &cfg.classify {
+            Some(names) => names
+                .iter()
+                .map(|name| match Flake::from_name(name) {
+                    Some(flake) => Ok(flake),
+                    None => bail!(
+                        "`build.retry.classify` must only contain `sigkill` or `network`, \
+                         found `{name}`"
+                    ),
+                })
+                .collect::<CargoResult<_>>()?,
+            None => vec![Flake::Sigkill, Flake::Network],
+        };
+        Ok(RetryPolicy {
+            count: cfg.count.unwrap_or(1),
+            classify,
+        })
+    }
+
+    /// Returns the kind of flake `err` is, if the unit should be run again
+    /// after having already been retried `retries` times.
+    pub fn should_retry(&self, err: &anyhow::Error, retries: u32) -> Option<Flake> {
+        if retries >= self.count {
+            return None;
+        }
+        Flake::classify(err).filter(|flake| self.classify.contains(flake))
+    }
+
+    /// How many times a unit may be run again.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
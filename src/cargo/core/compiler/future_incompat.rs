@@ -248,9 +248,9 @@ impl OnDiskReports {
                 .get(package)
                 .ok_or_else(|| {
                     format_err!(
-                        "could not find package with ID `{}`\n
-                Available packages are: {}\n
-                Omit the `--package` flag to display a report for all packages",
+                        "could not find package with ID `{}`\n\
+                         Available packages are: {}\n\
+                         Omit the `--package` flag to display a report for all packages",
                         package,
                         iter_join(report.per_package.keys(), ", ")
                     )
@@ -0,0 +1,109 @@
+//! Reports of internal compiler errors (ICEs) of `rustc`.
+//!
+//! When `rustc` panics, it prints a backtrace and exits with the code 101,
+//! instead of 1 for regular compilation errors. A bug report for it needs the
+//! exact invocation, which Cargo doesn't show unless `--verbose` is passed,
+//! and the source of the crate. This module saves both into a directory
+//! under `target/ice-reports`, so they can be attached to an issue.
+//!
+//! The environment of the report only has the variables set by Cargo for the
+//! invocation, not the whole environment, which may contain secrets.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use cargo_util::{paths, ProcessBuilder, ProcessError, Sha256};
+use time::OffsetDateTime;
+
+use crate::util::errors::VerboseError;
+use crate::util::CargoResult;
+
+/// The exit code of `rustc` when it panics.
+const ICE_EXIT_CODE: i32 = 101;
+
+/// Where to report ICEs of `rustc`.
+pub const ISSUE_TEMPLATE_URL: &str =
+    "https://github.com/rust-lang/rust/issues/new?labels=C-bug%2C+I-ICE%2C+T-compiler&template=ice.md";
+
+/// Whether `err`, returned by running `rustc`, is an internal compiler error.
+pub fn is_ice(err: &anyhow::Error) -> bool {
+    // Failures with a simple exit code like this one are wrapped in a
+    // `VerboseError`, which hides the `ProcessError` from the chain.
+    err.chain()
+        .filter_map(|e| match e.downcast_ref::<VerboseError>() {
+            Some(verbose) => verbose.inner().downcast_ref::<ProcessError>(),
+            None => e.downcast_ref::<ProcessError>(),
+        })
+        .any(|perr| perr.code == Some(ICE_EXIT_CODE))
+}
+
+/// Saves the report of an ICE of `rustc` running `cmd` on the package at
+/// `pkg_root`, and returns the directory of the report.
+///
+/// The report is made of:
+///
+/// * `command.txt`, the working directory and the command line.
+/// * `env.txt`, the environment variables set for the command.
+/// * `sources.txt`, the SHA-256 and path of each file of the package.
+pub fn save(
+    target_dir: &Path,
+    cmd: &ProcessBuilder,
+    cwd: &Path,
+    pkg_root: &Path,
+) -> CargoResult<PathBuf> {
+    let reports = target_dir.join("ice-reports");
+    let format = time::format_description::parse_borrowed::<2>(
+        "[year][month][day]T[hour][minute][second]Z",
+    )?;
+    let timestamp = OffsetDateTime::now_utc().format(&format)?;
+    // Several units may crash within the same second.
+    let mut dir = reports.join(&timestamp);
+    let mut n = 1;
+    while dir.exists() {
+        n += 1;
+        dir = reports.join(format!("{timestamp}-{n}"));
+    }
+    paths::create_dir_all(&dir)?;
+
+    paths::write(
+        dir.join("command.txt"),
+        format!("cwd: {}\n{cmd}\n", cwd.display()),
+    )?;
+
+    let mut env = String::new();
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => writeln!(env, "{key}={}", value.to_string_lossy())?,
+            None => writeln!(env, "{key} (unset)")?,
+        }
+    }
+    paths::write(dir.join("env.txt"), env)?;
+
+    let mut sources = String::new();
+    for path in source_files(pkg_root) {
+        let hash = Sha256::new().update_path(&path)?.finish_hex();
+        let relative = path.strip_prefix(pkg_root).unwrap_or(&path);
+        writeln!(sources, "{hash}  {}", relative.display())?;
+    }
+    paths::write(dir.join("sources.txt"), sources)?;
+
+    Ok(dir)
+}
+
+/// The files of the package at `pkg_root`, leaving out hidden entries and
+/// the `target` directory.
+fn source_files(pkg_root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<_> = walkdir::WalkDir::new(pkg_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !(entry.file_name().to_string_lossy().starts_with('.')
+                    || entry.depth() == 1 && entry.file_name() == "target")
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(walkdir::DirEntry::into_path)
+        .collect();
+    files.sort();
+    files
+}
@@ -0,0 +1,46 @@
+//! Recovery from corrupted incremental compilation caches.
+//!
+//! `rustc` keeps the incremental compilation state of each unit in the
+//! directory given with `-C incremental`, a `<crate-name>-<metadata>`
+//! directory under the `incremental` directory of the layout. When that state
+//! gets corrupted, for example by a crash in the middle of a session or a bug
+//! of `rustc`, every compilation of the crate fails the same way until the
+//! directory is removed.
+//!
+//! When the output of `rustc` matches one of the known signatures of such a
+//! corruption, Cargo removes the incremental directory of the unit and
+//! compiles it once more, see `rustc` in the parent module.
+
+use std::path::Path;
+
+use cargo_util::paths;
+
+use crate::util::CargoResult;
+
+/// Messages printed by `rustc` when its incremental compilation state is
+/// corrupted.
+const CORRUPTION_SIGNATURES: &[&str] = &[
+    "found unstable fingerprints for",
+    "encountered incremental compilation error",
+    "rustc_incremental/src/persist",
+    "rustc_query_system/src/dep_graph",
+];
+
+/// Whether a line of the stderr of `rustc` reports a corrupted incremental
+/// compilation state.
+pub fn is_corruption(line: &str) -> bool {
+    CORRUPTION_SIGNATURES
+        .iter()
+        .any(|signature| line.contains(signature))
+}
+
+/// Removes the incremental compilation state in `dir`, the directory given
+/// to `rustc` with `-C incremental`. `rustc` may have failed before creating
+/// it.
+pub fn clear(dir: &Path) -> CargoResult<()> {
+    tracing::debug!("removing corrupted incremental state {}", dir.display());
+    if !dir.exists() {
+        return Ok(());
+    }
+    paths::remove_dir_all(dir)
+}
@@ -5,6 +5,7 @@ use std::{cell::Cell, marker, sync::Arc};
 use cargo_util::ProcessBuilder;
 
 use crate::core::compiler::context::OutputFile;
+use crate::core::compiler::flaky::Flake;
 use crate::core::compiler::future_incompat::FutureBreakageItem;
 use crate::util::Queue;
 use crate::CargoResult;
@@ -121,6 +122,11 @@ impl<'a, 'cfg> JobState<'a, 'cfg> {
         Ok(())
     }
 
+    /// See [`Message::JsonWarning`].
+    pub fn json_warning(&self) {
+        self.messages.push(Message::JsonWarning(self.id));
+    }
+
     /// See [`Message::Warning`].
     pub fn warning(&self, warning: String) -> CargoResult<()> {
         self.messages.push_bounded(Message::Warning {
@@ -130,6 +136,12 @@ impl<'a, 'cfg> JobState<'a, 'cfg> {
         Ok(())
     }
 
+    /// See [`Message::Note`].
+    pub fn note(&self, note: String) -> CargoResult<()> {
+        self.messages.push_bounded(Message::Note(note));
+        Ok(())
+    }
+
     /// A method used to signal to the coordinator thread that the rmeta file
     /// for an rlib has been produced. This is only called for some rmeta
     /// builds when required, and can be called at any time before a job ends.
@@ -190,6 +202,18 @@ impl<'a, 'cfg> JobState<'a, 'cfg> {
         }
     }
 
+    /// Reports that the incremental compilation cache of this job was
+    /// corrupted and removed, see [`Message::IncrementalReset`].
+    pub fn incremental_reset(&self) {
+        self.messages.push(Message::IncrementalReset(self.id));
+    }
+
+    /// Reports that this job failed because of `flake`, and is run again,
+    /// see [`Message::Retried`].
+    pub fn retried(&self, flake: Flake) {
+        self.messages.push(Message::Retried(self.id, flake));
+    }
+
     pub fn future_incompat_report(&self, report: Vec<FutureBreakageItem>) {
         self.messages
             .push(Message::FutureIncompatReport(self.id, report));
@@ -0,0 +1,29 @@
+//! Support for `build.max-memory`, which keeps memory-hungry rustc
+//! invocations from running at the same time.
+//!
+//! The peak RSS of each rustc invocation is recorded in the fingerprint
+//! directory of its unit, and used as the estimate for the next build of the
+//! same unit. Since the fingerprint directory depends on the profile, an
+//! estimate is only reused with the same settings, such as `codegen-units`.
+//! Units which were never measured are assumed to use no memory.
+
+use std::path::Path;
+
+use cargo_util::paths;
+
+/// The file in the fingerprint directory holding the peak RSS in bytes.
+const PEAK_RSS_FILE: &str = "peak-rss";
+
+/// Reads the peak RSS recorded by a previous build of a unit.
+pub fn load_peak_rss(fingerprint_dir: &Path) -> Option<u64> {
+    let peak_rss = paths::read(&fingerprint_dir.join(PEAK_RSS_FILE)).ok()?;
+    peak_rss.trim().parse().ok()
+}
+
+/// Records the peak RSS of a unit for the next build.
+pub fn save_peak_rss(fingerprint_dir: &Path, peak_rss: u64) {
+    let path = fingerprint_dir.join(PEAK_RSS_FILE);
+    if let Err(e) = paths::write(&path, peak_rss.to_string()) {
+        tracing::debug!("failed to record peak RSS in `{}`: {e:?}", path.display());
+    }
+}
@@ -70,6 +70,10 @@
 //! data and perform a PGO-like optimization to prioritize jobs, making a build
 //! fully pipelined.
 //!
+//! With `build.max-memory`, the peak RSS measured for each unit by a previous
+//! build is used to hold back jobs which would exceed the memory budget next
+//! to the ones already running. See the [`memory`] module for details.
+//!
 //! ## Message queue
 //!
 //! Each spawned thread running a process uses the message queue [`Queue`] to
@@ -112,6 +116,7 @@
 
 mod job;
 mod job_state;
+pub(crate) mod memory;
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -120,7 +125,7 @@ use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread::{self, Scope};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{format_err, Context as _};
 use cargo_util::ProcessBuilder;
@@ -131,7 +136,9 @@ use tracing::{debug, trace};
 pub use self::job::Freshness::{self, Dirty, Fresh};
 pub use self::job::{Job, Work};
 pub use self::job_state::JobState;
+use super::build_summary::BuildSummary;
 use super::context::OutputFile;
+use super::flaky::Flake;
 use super::timings::Timings;
 use super::{BuildContext, BuildPlan, CompileMode, Context, Unit};
 use crate::core::compiler::descriptive_pkg_name;
@@ -139,9 +146,10 @@ use crate::core::compiler::future_incompat::{
     self, FutureBreakageItem, FutureIncompatReportPackage,
 };
 use crate::core::resolver::ResolveBehavior;
-use crate::core::{PackageId, Shell, TargetKind};
+use crate::core::{OutputCategory, PackageId, Shell, TargetKind};
 use crate::util::diagnostic_server::{self, DiagnosticPrinter};
 use crate::util::errors::AlreadyPrintedError;
+use crate::util::invocation_log;
 use crate::util::machine_message::{self, Message as _};
 use crate::util::CargoResult;
 use crate::util::{self, internal, profile};
@@ -155,6 +163,8 @@ pub struct JobQueue<'cfg> {
     queue: DependencyQueue<Unit, Artifact, Job>,
     counts: HashMap<PackageId, usize>,
     timings: Timings<'cfg>,
+    /// Peak RSS of units measured by a previous build, see [`memory`].
+    memory_estimates: HashMap<Unit, u64>,
 }
 
 /// This structure is backed by the `DependencyQueue` type and manages the
@@ -174,6 +184,9 @@ struct DrainState<'cfg> {
     /// Count of warnings, used to print a summary after the job succeeds
     warning_count: HashMap<JobId, WarningCount>,
     active: HashMap<JobId, Unit>,
+    /// When each active unit started, used to record durations in the
+    /// invocation log (`-Zlog-file`).
+    started: HashMap<JobId, Instant>,
     compiled: HashSet<PackageId>,
     documented: HashSet<PackageId>,
     scraped: HashSet<PackageId>,
@@ -181,6 +194,18 @@ struct DrainState<'cfg> {
     progress: Progress<'cfg>,
     next_id: u32,
     timings: Timings<'cfg>,
+    /// Statistics printed at the end of the build.
+    summary: BuildSummary,
+
+    /// The `build.max-memory` budget for the jobs running at the same time.
+    max_memory: Option<u64>,
+    /// Peak RSS of units measured by a previous build, see [`memory`].
+    memory_estimates: HashMap<Unit, u64>,
+    /// The estimated memory use of each active job.
+    active_memory: HashMap<JobId, u64>,
+    /// Set when a job counted against `max_memory` finishes, so that a
+    /// jobserver token is requested for a job it may have held back.
+    memory_freed: bool,
 
     /// Tokens that are currently owned by this Cargo, and may be "associated"
     /// with a rustc process. They may also be unused, though if so will be
@@ -199,7 +224,11 @@ struct DrainState<'cfg> {
 
     /// How many jobs we've finished
     finished: usize,
+    /// Units which failed, reported at the end of a `--keep-going` build.
+    failed: Vec<Unit>,
     per_package_future_incompat_reports: Vec<FutureIncompatReportPackage>,
+    /// Units whose corrupted incremental compilation cache was removed.
+    incremental_resets: Vec<Unit>,
 }
 
 /// Count of warnings, used to print a summary after the job succeeds
@@ -367,11 +396,23 @@ enum Message {
         id: JobId,
         warning: String,
     },
+    // This is for notes about what Cargo does on behalf of a unit, e.g.
+    // compiling it again after a failure.
+    Note(String),
 
     FixDiagnostic(diagnostic_server::Message),
     Token(io::Result<Acquired>),
     Finish(JobId, Artifact, CargoResult<()>),
     FutureIncompatReport(JobId, Vec<FutureBreakageItem>),
+    // The incremental compilation cache of a unit was corrupted, and removed
+    // before compiling it again. Reported at the end of the build.
+    IncrementalReset(JobId),
+    // A unit failed for a reason unrelated to its code, and is run again,
+    // see `build.retry`. Reported in the build summary.
+    Retried(JobId, Flake),
+    // A warning of a unit passed through as JSON with `--message-format=json`,
+    // which is only counted in the build summary.
+    JsonWarning(JobId),
 }
 
 impl<'cfg> JobQueue<'cfg> {
@@ -380,6 +421,7 @@ impl<'cfg> JobQueue<'cfg> {
             queue: DependencyQueue::new(),
             counts: HashMap::new(),
             timings: Timings::new(bcx, &bcx.roots),
+            memory_estimates: HashMap::new(),
         }
     }
 
@@ -454,6 +496,11 @@ impl<'cfg> JobQueue<'cfg> {
         // a smarter way using timing data from a previous compilation.
         self.queue.queue(unit.clone(), job, queue_deps, 100);
         *self.counts.entry(unit.pkg.package_id()).or_insert(0) += 1;
+        if cx.bcx.build_config.max_memory.is_some() {
+            if let Some(peak_rss) = memory::load_peak_rss(&cx.files().fingerprint_dir(unit)) {
+                self.memory_estimates.insert(unit.clone(), peak_rss);
+            }
+        }
         Ok(())
     }
 
@@ -465,6 +512,13 @@ impl<'cfg> JobQueue<'cfg> {
     pub fn execute(mut self, cx: &mut Context<'_, '_>, plan: &mut BuildPlan) -> CargoResult<()> {
         let _p = profile::start("executing the job graph");
         self.queue.queue_finished();
+        if let Some(seed) = cx.bcx.config.cli_unstable().shuffle_units {
+            cx.bcx.config.shell().note(format!(
+                "shuffling the order of units with seed {seed}, \
+                 pass `-Zshuffle-units={seed}` to reproduce it"
+            ))?;
+            self.queue.shuffle(seed);
+        }
 
         let progress = Progress::with_style("Building", ProgressStyle::Ratio, cx.bcx.config);
         let state = DrainState {
@@ -478,6 +532,7 @@ impl<'cfg> JobQueue<'cfg> {
             diag_dedupe: DiagDedupe::new(cx.bcx.config),
             warning_count: HashMap::new(),
             active: HashMap::new(),
+            started: HashMap::new(),
             compiled: HashSet::new(),
             documented: HashSet::new(),
             scraped: HashSet::new(),
@@ -485,11 +540,18 @@ impl<'cfg> JobQueue<'cfg> {
             progress,
             next_id: 0,
             timings: self.timings,
+            summary: BuildSummary::new(cx.bcx.config),
+            max_memory: cx.bcx.build_config.max_memory,
+            memory_estimates: self.memory_estimates,
+            active_memory: HashMap::new(),
+            memory_freed: false,
             tokens: Vec::new(),
             pending_queue: Vec::new(),
             print: DiagnosticPrinter::new(cx.bcx.config, &cx.bcx.rustc().workspace_wrapper),
             finished: 0,
+            failed: Vec::new(),
             per_package_future_incompat_reports: Vec::new(),
+            incremental_resets: Vec::new(),
         };
 
         // Create a helper thread for acquiring jobserver tokens
@@ -551,14 +613,23 @@ impl<'cfg> DrainState<'cfg> {
             }
         }
 
+        // Tokens of jobs held back by `build.max-memory` may have been
+        // relinquished in the meantime, so ask for one again.
+        if std::mem::take(&mut self.memory_freed) && !self.pending_queue.is_empty() {
+            jobserver_helper.request_token();
+        }
+
         // Now that we've learned of all possible work that we can execute
         // try to spawn it so long as we've got a jobserver token which says
         // we're able to perform some parallel work.
         // The `pending_queue` is sorted in ascending priority order, and we
         // remove items from its end to schedule the highest priority items
         // sooner.
-        while self.has_extra_tokens() && !self.pending_queue.is_empty() {
-            let (unit, job, _) = self.pending_queue.pop().unwrap();
+        while self.has_extra_tokens() {
+            let Some(idx) = self.next_pending_job() else {
+                break;
+            };
+            let (unit, job, _) = self.pending_queue.remove(idx);
             *self.counts.get_mut(&unit.pkg.package_id()).unwrap() -= 1;
             if !cx.bcx.build_config.build_plan {
                 // Print out some nice progress information.
@@ -577,6 +648,31 @@ impl<'cfg> DrainState<'cfg> {
         self.active.len() < self.tokens.len() + 1
     }
 
+    /// Returns the index in `pending_queue` of the job to run next.
+    ///
+    /// This is the highest priority job, unless `build.max-memory` is set,
+    /// in which case it is the highest priority job that fits in the budget
+    /// next to the active jobs. A job is always allowed to run when nothing
+    /// else is counted against the budget, so that the build makes progress.
+    fn next_pending_job(&self) -> Option<usize> {
+        let Some(max_memory) = self.max_memory else {
+            return self.pending_queue.len().checked_sub(1);
+        };
+        let used: u64 = self.active_memory.values().sum();
+        self.pending_queue.iter().rposition(|(unit, job, _)| {
+            used == 0 || used.saturating_add(self.memory_estimate(unit, job)) <= max_memory
+        })
+    }
+
+    /// The estimated memory a job needs while running, from the peak RSS
+    /// measured by a previous build.
+    fn memory_estimate(&self, unit: &Unit, job: &Job) -> u64 {
+        if job.freshness().is_fresh() {
+            return 0;
+        }
+        self.memory_estimates.get(unit).copied().unwrap_or(0)
+    }
+
     fn handle_event(
         &mut self,
         cx: &mut Context<'_, '_>,
@@ -585,10 +681,18 @@ impl<'cfg> DrainState<'cfg> {
     ) -> Result<(), ErrorToHandle> {
         match event {
             Message::Run(id, cmd) => {
+                if let Some(log) = cx.bcx.config.invocation_log() {
+                    let unit = &self.active[&id];
+                    log.log(&invocation_log::ProcessStarted {
+                        package_id: unit.pkg.package_id(),
+                        target: unit.target.name(),
+                        command: &cmd,
+                    });
+                }
                 cx.bcx
                     .config
                     .shell()
-                    .verbose(|c| c.status("Running", &cmd))?;
+                    .category(OutputCategory::ProcessEcho, |c| c.status("Running", &cmd))?;
                 self.timings.unit_start(id, self.active[&id].clone());
             }
             Message::BuildPlanMsg(module_name, cmd, filenames) => {
@@ -622,6 +726,9 @@ impl<'cfg> DrainState<'cfg> {
                 cx.bcx.config.shell().warn(warning)?;
                 self.bump_warning_count(id, true, false);
             }
+            Message::Note(note) => {
+                cx.bcx.config.shell().note(note)?;
+            }
             Message::WarningCount {
                 id,
                 emitted,
@@ -629,6 +736,10 @@ impl<'cfg> DrainState<'cfg> {
             } => {
                 self.bump_warning_count(id, emitted, fixable);
             }
+            Message::JsonWarning(id) => {
+                self.summary
+                    .add_warnings(self.active[&id].pkg.package_id(), 1);
+            }
             Message::FixDiagnostic(msg) => {
                 self.print.print(&msg)?;
             }
@@ -644,6 +755,10 @@ impl<'cfg> DrainState<'cfg> {
                             id,
                             &cx.bcx.rustc().workspace_wrapper,
                         );
+                        self.summary.unit_finished(id, &self.active[&id]);
+                        if self.active_memory.remove(&id).unwrap_or(0) > 0 {
+                            self.memory_freed = true;
+                        }
                         self.active.remove(&id).unwrap()
                     }
                     // ... otherwise if it hasn't finished we leave it
@@ -654,6 +769,18 @@ impl<'cfg> DrainState<'cfg> {
                     }
                 };
                 debug!("end ({:?}): {:?}", unit, result);
+                if let Some(log) = cx.bcx.config.invocation_log() {
+                    if artifact == Artifact::All {
+                        let start = self.started.remove(&id).unwrap();
+                        log.log(&invocation_log::UnitFinished {
+                            package_id: unit.pkg.package_id(),
+                            target: unit.target.name(),
+                            mode: unit.mode,
+                            duration: start.elapsed().as_secs_f64(),
+                            success: result.is_ok(),
+                        });
+                    }
+                }
                 match result {
                     Ok(()) => self.finish(id, &unit, artifact, cx)?,
                     Err(_) if cx.bcx.unit_can_fail_for_docscraping(&unit) => {
@@ -667,6 +794,7 @@ impl<'cfg> DrainState<'cfg> {
                         let msg = "The following warnings were emitted during compilation:";
                         self.emit_warnings(Some(msg), &unit, cx)?;
                         self.back_compat_notice(cx, &unit)?;
+                        self.failed.push(unit);
                         return Err(ErrorToHandle {
                             error,
                             print_always: true,
@@ -679,6 +807,12 @@ impl<'cfg> DrainState<'cfg> {
                 self.per_package_future_incompat_reports
                     .push(FutureIncompatReportPackage { package_id, items });
             }
+            Message::IncrementalReset(id) => {
+                self.incremental_resets.push(self.active[&id].clone());
+            }
+            Message::Retried(id, flake) => {
+                self.summary.unit_retried(&self.active[&id], flake);
+            }
             Message::Token(acquired_token) => {
                 let token = acquired_token.with_context(|| "failed to acquire jobserver token")?;
                 self.tokens.push(token);
@@ -791,6 +925,11 @@ impl<'cfg> DrainState<'cfg> {
             self.handle_error(&mut cx.bcx.config.shell(), &mut errors, e);
         }
         if cx.bcx.build_config.emit_json() {
+            if errors.count == 0 && self.queue.is_empty() && self.pending_queue.is_empty() {
+                if let Err(e) = self.summary.emit_json(cx) {
+                    self.handle_error(&mut cx.bcx.config.shell(), &mut errors, e);
+                }
+            }
             let mut shell = cx.bcx.config.shell();
             let msg = machine_message::BuildFinished {
                 success: errors.count == 0,
@@ -801,7 +940,14 @@ impl<'cfg> DrainState<'cfg> {
             }
         }
 
+        // It doesn't really matter if this fails.
+        let _ = self.report_incremental_resets(cx.bcx.config);
+
         if let Some(error) = errors.to_error() {
+            if cx.bcx.build_config.keep_going {
+                // It doesn't really matter if this fails.
+                let _ = self.report_failed_units(cx.bcx.config);
+            }
             // Any errors up to this point have already been printed via the
             // `display_error` inside `handle_error`.
             Some(anyhow::Error::new(AlreadyPrintedError::new(error)))
@@ -813,6 +959,7 @@ impl<'cfg> DrainState<'cfg> {
             if !cx.bcx.build_config.build_plan {
                 // It doesn't really matter if this fails.
                 let _ = cx.bcx.config.shell().status("Finished", message);
+                let _ = self.summary.report(cx);
                 future_incompat::save_and_display_report(
                     cx.bcx,
                     &self.per_package_future_incompat_reports,
@@ -826,6 +973,44 @@ impl<'cfg> DrainState<'cfg> {
         }
     }
 
+    /// Prints the units which failed, and how many units weren't built
+    /// because of them, to sum up a `--keep-going` build.
+    fn report_failed_units(&self, config: &Config) -> CargoResult<()> {
+        if self.failed.is_empty() {
+            return Ok(());
+        }
+        let mut message = String::from("the following targets failed to compile:");
+        for unit in &self.failed {
+            let name = super::descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+            message.push_str(&format!("\n  {name}"));
+        }
+        match self.queue.len() + self.pending_queue.len() {
+            0 => {}
+            1 => message.push_str("\n1 other unit was not built because it depends on them"),
+            n => message.push_str(&format!(
+                "\n{n} other units were not built because they depend on them"
+            )),
+        }
+        config.shell().note(message)
+    }
+
+    /// Prints the units whose incremental compilation cache was corrupted and
+    /// removed during the build.
+    fn report_incremental_resets(&self, config: &Config) -> CargoResult<()> {
+        if self.incremental_resets.is_empty() {
+            return Ok(());
+        }
+        let mut message = String::from(
+            "the incremental compilation cache of the following targets \
+             was corrupted and removed:",
+        );
+        for unit in &self.incremental_resets {
+            let name = super::descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+            message.push_str(&format!("\n  {name}"));
+        }
+        config.shell().note(message)
+    }
+
     fn handle_error(
         &self,
         shell: &mut Shell,
@@ -910,9 +1095,28 @@ impl<'cfg> DrainState<'cfg> {
         debug!("start {}: {:?}", id, unit);
 
         assert!(self.active.insert(id, unit.clone()).is_none());
+        if self.max_memory.is_some() {
+            let estimate = self.memory_estimate(unit, &job);
+            self.active_memory.insert(id, estimate);
+        }
+        if let Some(log) = cx.bcx.config.invocation_log() {
+            let dirty_reason = match job.freshness() {
+                Dirty(Some(reason)) => Some(format!("{:?}", reason)),
+                _ => None,
+            };
+            log.log(&invocation_log::UnitDecision {
+                package_id: unit.pkg.package_id(),
+                target: unit.target.name(),
+                mode: unit.mode,
+                fresh: job.freshness().is_fresh(),
+                dirty_reason,
+            });
+            self.started.insert(id, Instant::now());
+        }
 
         let messages = self.messages.clone();
         let is_fresh = job.freshness().is_fresh();
+        self.summary.unit_start(id, is_fresh);
         let rmeta_required = cx.rmeta_required(unit);
 
         let doit = move |diag_dedupe| {
@@ -1001,6 +1205,8 @@ impl<'cfg> DrainState<'cfg> {
             None | Some(_) => return,
         };
         let unit = &self.active[&id];
+        self.summary
+            .add_warnings(unit.pkg.package_id(), count.total);
         let mut message = descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
         message.push_str(" generated ");
         match count.total {
@@ -1104,6 +1310,7 @@ impl<'cfg> DrainState<'cfg> {
             return Ok(());
         }
 
+        let pkg = config.display_package_id(unit.pkg.package_id(), ws_root);
         match fresh {
             // Any dirty stage which runs at least one command gets printed as
             // being a compiled package.
@@ -1111,23 +1318,25 @@ impl<'cfg> DrainState<'cfg> {
                 if let Some(reason) = dirty_reason {
                     config
                         .shell()
-                        .verbose(|shell| reason.present_to(shell, unit, ws_root))?;
+                        .category(OutputCategory::FingerprintDebug, |shell| {
+                            reason.present_to(shell, unit, ws_root)
+                        })?;
                 }
 
                 if unit.mode.is_doc() {
                     self.documented.insert(unit.pkg.package_id());
-                    config.shell().status("Documenting", &unit.pkg)?;
+                    config.shell().status("Documenting", &pkg)?;
                 } else if unit.mode.is_doc_test() {
                     // Skip doc test.
                 } else if unit.mode.is_doc_scrape() {
                     self.scraped.insert(unit.pkg.package_id());
-                    config.shell().status("Scraping", &unit.pkg)?;
+                    config.shell().status("Scraping", &pkg)?;
                 } else {
                     self.compiled.insert(unit.pkg.package_id());
                     if unit.mode.is_check() {
-                        config.shell().status("Checking", &unit.pkg)?;
+                        config.shell().status("Checking", &pkg)?;
                     } else {
-                        config.shell().status("Compiling", &unit.pkg)?;
+                        config.shell().status("Compiling", &pkg)?;
                     }
                 }
             }
@@ -1137,7 +1346,11 @@ impl<'cfg> DrainState<'cfg> {
                     && !(unit.mode.is_doc_test() && self.compiled.contains(&unit.pkg.package_id()))
                 {
                     self.compiled.insert(unit.pkg.package_id());
-                    config.shell().verbose(|c| c.status("Fresh", &unit.pkg))?;
+                    config
+                        .shell()
+                        .category(OutputCategory::FingerprintDebug, |c| {
+                            c.status("Fresh", &pkg)
+                        })?;
                 }
             }
         }
@@ -22,6 +22,10 @@
 //!         # from using it at the same time.
 //!         .cargo-lock
 //!
+//!         # The last use of the artifacts of each unit, and the lock files of
+//!         # the workspaces built here, with `-Ztarget-dir-gc`.
+//!         .last-use.json
+//!
 //!         # Hidden directory that holds all of the fingerprint files for all
 //!         # packages
 //!         .fingerprint/
@@ -100,11 +104,18 @@
 //!
 //! When cross-compiling, the layout is the same, except it appears in
 //! `target/$TRIPLE`.
+//!
+//! With `-Zshared-build-cache`, the artifacts of registry dependencies in
+//! `deps/` may be hard links to `$CARGO_HOME/build-cache/$KEY/`, see
+//! [`super::shared_cache`].
 
 use crate::core::compiler::CompileTarget;
 use crate::core::Workspace;
-use crate::util::{CargoResult, FileLock};
+use crate::util::{CargoResult, FileLock, Filesystem};
+use anyhow::bail;
 use cargo_util::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
 /// Contains the paths of all target output locations.
@@ -133,8 +144,8 @@ pub struct Layout {
     /// The directory for temporary data of integration tests and benches: `$dest/tmp`
     tmp: PathBuf,
     /// The lockfile for a build (`.cargo-lock`). Will be unlocked when this
-    /// struct is `drop`ped.
-    _lock: FileLock,
+    /// struct is `drop`ped. `None` for a layout from [`Layout::unlocked`].
+    _lock: Option<FileLock>,
 }
 
 impl Layout {
@@ -149,11 +160,26 @@ impl Layout {
         target: Option<CompileTarget>,
         dest: &str,
     ) -> CargoResult<Layout> {
-        let mut root = ws.target_dir();
+        Layout::at(ws, ws.target_dir(), target, dest)
+    }
+
+    /// Like [`Layout::new`], but rooted at `root` instead of the workspace's
+    /// target directory.
+    ///
+    /// This is used for packages whose profile sets `target-dir`.
+    pub fn at(
+        ws: &Workspace<'_>,
+        mut root: Filesystem,
+        target: Option<CompileTarget>,
+        dest: &str,
+    ) -> CargoResult<Layout> {
         if let Some(target) = target {
             root.push(target.short_name());
         }
         let dest = root.join(dest);
+        if ws.config().cli_unstable().read_only_sources {
+            check_outside_package_roots(ws, root.as_path_unlocked())?;
+        }
         // If the root directory doesn't already exist go ahead and create it
         // here. Use this opportunity to exclude it from backups as well if the
         // system supports it since this is a freshly created folder.
@@ -167,12 +193,29 @@ impl Layout {
         // directory, so just lock the entire thing for the duration of this
         // compile.
         let lock = dest.open_rw(".cargo-lock", ws.config(), "build directory")?;
+        Ok(Layout::with_paths(root, dest, Some(lock)))
+    }
+
+    /// Like [`Layout::at`], but only calculates the paths, without creating
+    /// the directories or locking the build directory.
+    ///
+    /// This is for reading what previous builds left, which doesn't need to
+    /// wait for a build running in the meantime.
+    pub fn unlocked(mut root: Filesystem, target: Option<CompileTarget>, dest: &str) -> Layout {
+        if let Some(target) = target {
+            root.push(target.short_name());
+        }
+        let dest = root.join(dest);
+        Layout::with_paths(root, dest, None)
+    }
+
+    fn with_paths(root: Filesystem, dest: Filesystem, lock: Option<FileLock>) -> Layout {
         let root = root.into_path_unlocked();
         let dest = dest.into_path_unlocked();
         let deps = dest.join("deps");
         let artifact = deps.join("artifact");
 
-        Ok(Layout {
+        Layout {
             deps,
             build: dest.join("build"),
             artifact,
@@ -184,7 +227,7 @@ impl Layout {
             root,
             dest,
             _lock: lock,
-        })
+        }
     }
 
     /// Makes sure all directories stored in the Layout exist on the filesystem.
@@ -240,3 +283,136 @@ impl Layout {
         Ok(&self.tmp)
     }
 }
+
+/// The last use of the artifacts built in the destination directory of a
+/// [`Layout`], stored in `$dest/.last-use.json` with `-Ztarget-dir-gc`.
+///
+/// It is only read and written while holding the `.cargo-lock` of the
+/// directory. `cargo clean gc` uses it to remove the artifacts which are no
+/// longer needed.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LastUse {
+    /// The lock files of the workspaces built in this directory.
+    #[serde(default)]
+    pub lockfiles: BTreeSet<PathBuf>,
+    /// The units built in this directory, by the name `$pkgname-$META` of
+    /// their fingerprint directory.
+    #[serde(default)]
+    pub units: BTreeMap<String, UnitUse>,
+    /// When this directory was last garbage collected, in seconds since the
+    /// Unix epoch.
+    #[serde(default)]
+    pub last_gc: Option<u64>,
+}
+
+/// The package and last use of a unit in [`LastUse`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct UnitUse {
+    pub name: String,
+    pub version: String,
+    /// When the unit was last part of a build, in seconds since the Unix
+    /// epoch.
+    pub last_use: u64,
+}
+
+impl LastUse {
+    const FILE_NAME: &'static str = ".last-use.json";
+
+    /// Loads the last uses recorded in the destination directory `dest`.
+    ///
+    /// A missing or unreadable file is treated as empty, as if tracking
+    /// started with this build.
+    pub fn load(dest: &Path) -> LastUse {
+        paths::read(&dest.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the last uses to the destination directory `dest`.
+    pub fn save(&self, dest: &Path) -> CargoResult<()> {
+        paths::write_atomic(&dest.join(Self::FILE_NAME), serde_json::to_string(self)?)
+    }
+
+    /// Whether `dest` has last uses recorded.
+    pub fn exists(dest: &Path) -> bool {
+        dest.join(Self::FILE_NAME).exists()
+    }
+}
+
+/// The units built in the destination directory of a [`Layout`] by their
+/// metadata hash, stored in `$dest/.metadata-hashes.json` with
+/// `-Zmetadata-hash`.
+///
+/// It is only read and written while holding the `.cargo-lock` of the
+/// directory. `cargo explain-hash` uses it to map the hash in the name of an
+/// artifact back to its unit.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MetadataHashes {
+    /// The units, by their metadata hash.
+    #[serde(default)]
+    pub units: BTreeMap<String, HashedUnit>,
+}
+
+/// A unit recorded in [`MetadataHashes`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HashedUnit {
+    pub package_id: String,
+    pub target_name: String,
+    /// The kind of the target, like `lib` or `build-script`.
+    pub target_kind: String,
+    /// The compile mode, like `build` or `check`.
+    pub mode: String,
+    pub profile: String,
+    pub features: Vec<String>,
+    /// The target triple the unit is compiled for, `None` for the host.
+    pub kind: Option<String>,
+}
+
+impl MetadataHashes {
+    const FILE_NAME: &'static str = ".metadata-hashes.json";
+
+    /// Loads the units recorded in the destination directory `dest`, see
+    /// [`LastUse::load`].
+    pub fn load(dest: &Path) -> MetadataHashes {
+        paths::read(&dest.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the units to the destination directory `dest`.
+    pub fn save(&self, dest: &Path) -> CargoResult<()> {
+        paths::write(&dest.join(Self::FILE_NAME), serde_json::to_string(self)?)
+    }
+
+    /// Whether `dest` has units recorded.
+    pub fn exists(dest: &Path) -> bool {
+        dest.join(Self::FILE_NAME).exists()
+    }
+}
+
+/// Under `-Zread-only-sources`, errors if `dir` would be created inside the
+/// workspace root or the root of a workspace member, since those are assumed
+/// to be read-only.
+fn check_outside_package_roots(ws: &Workspace<'_>, dir: &Path) -> CargoResult<()> {
+    let dir = paths::normalize_path(dir);
+    let roots = std::iter::once(ws.root()).chain(ws.members().map(|pkg| pkg.root()));
+    for root in roots {
+        if dir.starts_with(root) {
+            bail!(
+                "cannot create build directory `{}` inside `{}` \
+                 because `-Zread-only-sources` is enabled\n\
+                 Set `CARGO_TARGET_DIR` or `build.target-dir` to a writable location \
+                 outside of the package sources.",
+                dir.display(),
+                root.display(),
+            );
+        }
+    }
+    Ok(())
+}
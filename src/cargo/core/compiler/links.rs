@@ -49,9 +49,9 @@ pub fn validate_links(resolve: &Resolve, unit_graph: &UnitGraph) -> CargoResult<
                  \n\
                  {}\nalso links to native library `{}`",
                 lib,
-                describe_path(prev_path),
+                describe_path(prev_path, None),
                 lib,
-                describe_path(path),
+                describe_path(path, None),
                 lib
             )
         }
@@ -32,24 +32,33 @@
 //! [`ops::cargo_compile::compile`]: crate::ops::compile
 
 pub mod artifact;
+mod artifact_dir;
 mod build_config;
 pub(crate) mod build_context;
 mod build_plan;
+mod build_probe;
+mod build_summary;
 mod compilation;
 mod compile_kind;
 pub(crate) mod context;
 mod crate_type;
 mod custom_build;
 pub(crate) mod fingerprint;
+mod flaky;
 pub mod future_incompat;
+mod ice_report;
+mod incremental;
 pub(crate) mod job_queue;
 pub(crate) mod layout;
 mod links;
 mod lto;
 mod output_depinfo;
+mod post_link;
 pub mod rustdoc;
+mod shared_cache;
 pub mod standard_lib;
 mod timings;
+mod tool_wrapper;
 mod unit;
 pub mod unit_dependencies;
 pub mod unit_graph;
@@ -63,7 +72,7 @@ use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use anyhow::{Context as _, Error};
+use anyhow::{bail, Context as _, Error};
 use lazycell::LazyCell;
 use tracing::{debug, trace};
 
@@ -74,14 +83,15 @@ pub use self::build_context::{
 use self::build_plan::BuildPlan;
 pub use self::compilation::{Compilation, Doctest, UnitOutput};
 pub use self::compile_kind::{CompileKind, CompileTarget};
-pub use self::context::{Context, Metadata};
+use self::context::OutputFile;
+pub use self::context::{Context, Metadata, MetadataHash, MetadataHashAlgorithm};
 pub use self::crate_type::CrateType;
 pub use self::custom_build::LinkArgTarget;
 pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts};
 pub(crate) use self::fingerprint::DirtyReason;
 pub use self::job_queue::Freshness;
 use self::job_queue::{Job, JobQueue, JobState, Work};
-pub(crate) use self::layout::Layout;
+pub(crate) use self::layout::{HashedUnit, LastUse, Layout, MetadataHashes};
 pub use self::lto::Lto;
 use self::output_depinfo::output_depinfo;
 use self::unit_graph::UnitDep;
@@ -121,6 +131,36 @@ pub trait Executor: Send + Sync + 'static {
         on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
     ) -> CargoResult<()>;
 
+    /// Like [`Executor::exec`], but also returns the peak resident set size of
+    /// the process in bytes, if it is known. Cargo uses it to estimate the
+    /// memory needed by the unit with `build.max-memory`.
+    fn exec_with_peak_rss(
+        &self,
+        cmd: &ProcessBuilder,
+        id: PackageId,
+        target: &Target,
+        mode: CompileMode,
+        on_stdout_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+    ) -> CargoResult<Option<u64>> {
+        self.exec(cmd, id, target, mode, on_stdout_line, on_stderr_line)
+            .map(|()| None)
+    }
+
+    /// Called after rustc produced the executables or dynamic libraries in
+    /// `artifact_paths`, and before they are linked into
+    /// `target/{debug,release}`, so that they can be modified in place.
+    /// This is called after the commands from `build.post-link-hooks`.
+    fn post_link(
+        &self,
+        _id: PackageId,
+        _target: &Target,
+        _mode: CompileMode,
+        _artifact_paths: &[PathBuf],
+    ) -> CargoResult<()> {
+        Ok(())
+    }
+
     /// Queried when queuing each unit of work. If it returns true, then the
     /// unit will always be rebuilt, independent of whether it needs to be.
     fn force_rebuild(&self, _unit: &Unit) -> bool {
@@ -146,6 +186,19 @@ impl Executor for DefaultExecutor {
         cmd.exec_with_streaming(on_stdout_line, on_stderr_line, false)
             .map(drop)
     }
+
+    fn exec_with_peak_rss(
+        &self,
+        cmd: &ProcessBuilder,
+        _id: PackageId,
+        _target: &Target,
+        _mode: CompileMode,
+        on_stdout_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+    ) -> CargoResult<Option<u64>> {
+        cmd.exec_with_streaming_peak_rss(on_stdout_line, on_stderr_line, false)
+            .map(|(_, peak_rss)| peak_rss)
+    }
 }
 
 /// Builds up and enqueue a list of pending jobs onto the `job` queue.
@@ -190,7 +243,11 @@ fn compile<'cfg>(
             let work = if unit.mode.is_doc() || unit.mode.is_doc_scrape() {
                 rustdoc(cx, unit)?
             } else {
-                rustc(cx, unit, exec)?
+                let mut work = rustc(cx, unit, exec)?;
+                if shared_cache::is_cacheable(cx, unit) {
+                    work = shared_cache::wrap(cx, unit, work)?;
+                }
+                work
             };
             work.then(link_targets(cx, unit, false)?)
         } else {
@@ -211,6 +268,11 @@ fn compile<'cfg>(
 
         job
     };
+    if bcx.build_config.no_deps_rebuild && !build_plan && !cx.is_primary_package(unit) {
+        if let Freshness::Dirty(reason) = job.freshness() {
+            cx.dirty_deps.push((unit.clone(), reason.clone()));
+        }
+    }
     jobs.enqueue(cx, unit, job)?;
     drop(p);
 
@@ -257,6 +319,11 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
 
     let outputs = cx.outputs(unit)?;
     let root = cx.files().out_dir(unit);
+    // Extra arguments from `cargo rustc`, like `-Zunpretty=expanded`, may
+    // legitimately stop rustc before it writes any output.
+    let check_outputs = cx.bcx.extra_args_for(unit).is_none();
+    let path_style = cx.bcx.config.path_style();
+    let ws_root = cx.bcx.ws.root().to_path_buf();
 
     // Prepare the native lib state (extra `-L` and `-l` flags).
     let build_script_outputs = Arc::clone(&cx.build_script_outputs);
@@ -290,6 +357,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
 
     let root_output = cx.files().host_dest().to_path_buf();
     let target_dir = cx.bcx.ws.target_dir().into_path_unlocked();
+    let target_root = fingerprint::target_root(cx, unit);
     let pkg_root = unit.pkg.root().to_path_buf();
     let cwd = rustc
         .get_cwd()
@@ -299,6 +367,22 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     let script_metadata = cx.find_build_script_metadata(unit);
     let is_local = unit.is_local();
     let artifact = unit.artifact;
+    let record_peak_rss = cx.bcx.build_config.max_memory.is_some();
+    let post_link_paths: Vec<_> = if post_link::applies_to(unit) {
+        outputs
+            .iter()
+            .filter(|output| output.flavor == FileFlavor::Normal)
+            .map(|output| output.path.clone())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let post_link_hooks = cx.bcx.build_config.post_link_hooks.clone();
+    let retry = cx.bcx.build_config.retry.clone();
+    let incremental_dir = unit
+        .profile
+        .incremental
+        .then(|| cx.files().incremental_dir(unit));
 
     let hide_diagnostics_for_scrape_unit = cx.bcx.unit_can_fail_for_docscraping(unit)
         && !matches!(cx.bcx.config.shell().verbosity(), Verbosity::Verbose);
@@ -394,51 +478,130 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
         if build_plan {
             state.build_plan(buildkey, rustc.clone(), outputs.clone());
         } else {
-            let result = exec
-                .exec(
-                    &rustc,
-                    package_id,
-                    &target,
-                    mode,
-                    &mut |line| on_stdout_line(state, line, package_id, &target),
-                    &mut |line| {
-                        on_stderr_line(
-                            state,
-                            line,
-                            package_id,
-                            &manifest_path,
-                            &target,
-                            &mut output_options,
-                        )
-                    },
-                )
-                .map_err(verbose_if_simple_exit_code)
-                .with_context(|| {
-                    // adapted from rustc_errors/src/lib.rs
-                    let warnings = match output_options.warnings_seen {
-                        0 => String::new(),
-                        1 => "; 1 warning emitted".to_string(),
-                        count => format!("; {} warnings emitted", count),
-                    };
-                    let errors = match output_options.errors_seen {
-                        0 => String::new(),
-                        1 => " due to previous error".to_string(),
-                        count => format!(" due to {} previous errors", count),
-                    };
-                    let name = descriptive_pkg_name(&name, &target, &mode);
-                    format!("could not compile {name}{errors}{warnings}")
-                });
-
-            if let Err(e) = result {
-                if let Some(diagnostic) = failed_scrape_diagnostic {
-                    state.warning(diagnostic)?;
+            let mut retried = false;
+            let mut retries = 0;
+            let result = loop {
+                let mut incremental_corrupted = false;
+                let result = exec
+                    .exec_with_peak_rss(
+                        &rustc,
+                        package_id,
+                        &target,
+                        mode,
+                        &mut |line| on_stdout_line(state, line, package_id, &target),
+                        &mut |line| {
+                            if incremental_dir.is_some() && incremental::is_corruption(line) {
+                                incremental_corrupted = true;
+                            }
+                            on_stderr_line(
+                                state,
+                                line,
+                                package_id,
+                                &manifest_path,
+                                &target,
+                                &mut output_options,
+                            )
+                        },
+                    )
+                    .map_err(verbose_if_simple_exit_code)
+                    .with_context(|| {
+                        // adapted from rustc_errors/src/lib.rs
+                        let warnings = match output_options.warnings_seen {
+                            0 => String::new(),
+                            1 => "; 1 warning emitted".to_string(),
+                            count => format!("; {} warnings emitted", count),
+                        };
+                        let errors = match output_options.errors_seen {
+                            0 => String::new(),
+                            1 => " due to previous error".to_string(),
+                            count => format!(" due to {} previous errors", count),
+                        };
+                        let name = descriptive_pkg_name(&name, &target, &mode);
+                        format!("could not compile {name}{errors}{warnings}")
+                    });
+                // A corrupted incremental state fails every compilation of
+                // the crate, so it's removed and the crate compiled once more.
+                match (&result, &incremental_dir) {
+                    (Err(_), Some(dir)) if incremental_corrupted && !retried => {
+                        state.note(format!(
+                            "the incremental compilation cache of `{name}` is corrupted, \
+                             removing it and compiling again"
+                        ))?;
+                        incremental::clear(dir)?;
+                        output_options.reset();
+                        state.incremental_reset();
+                        retried = true;
+                    }
+                    // Errors reported by rustc come from the code, so only
+                    // failures without any are worth another try.
+                    (Err(e), _) if output_options.errors_seen == 0 => {
+                        let Some(flake) = retry.should_retry(e, retries) else {
+                            break result;
+                        };
+                        retries += 1;
+                        state.stderr(format!(
+                            "note: rustc {} while compiling `{name}`, \
+                             compiling it again (retry {retries} of {})",
+                            flake.describe(),
+                            retry.count(),
+                        ))?;
+                        output_options.reset();
+                        state.retried(flake);
+                    }
+                    _ => break result,
                 }
+            };
 
-                return Err(e);
+            let peak_rss = match result {
+                Ok(peak_rss) => peak_rss,
+                Err(e) => {
+                    if let Some(diagnostic) = failed_scrape_diagnostic {
+                        state.warning(diagnostic)?;
+                    }
+                    if ice_report::is_ice(&e) {
+                        match ice_report::save(&target_dir, &rustc, &cwd, &pkg_root) {
+                            Ok(dir) => state.stderr(format!(
+                                "note: rustc crashed while compiling `{name}`, \
+                                 a report was saved to `{}`\n\
+                                 note: please file an issue at {} with this report",
+                                path_style.display(&dir, &ws_root),
+                                ice_report::ISSUE_TEMPLATE_URL,
+                            ))?,
+                            Err(report_err) => {
+                                debug!("failed to save the report of the ICE: {report_err:?}")
+                            }
+                        }
+                    }
+
+                    return Err(e);
+                }
+            };
+            if let (true, Some(peak_rss)) = (record_peak_rss, peak_rss) {
+                job_queue::memory::save_peak_rss(&fingerprint_dir, peak_rss);
             }
 
             // Exec should never return with success *and* generate an error.
             debug_assert_eq!(output_options.errors_seen, 0);
+
+            if check_outputs {
+                check_outputs_produced(
+                    &outputs,
+                    &output_options.artifacts,
+                    &name,
+                    &target,
+                    &mode,
+                    |path| path_style.display(path, &ws_root),
+                )?;
+            }
+
+            // Hooks run before `link_targets`, so that the linked artifacts
+            // are the modified ones.
+            if !post_link_paths.is_empty() {
+                for path in &post_link_paths {
+                    post_link::run(state, &post_link_hooks, path)?;
+                }
+                exec.post_link(package_id, &target, mode, &post_link_paths)?;
+            }
         }
 
         if rustc_dep_info_loc.exists() {
@@ -447,7 +610,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                 &dep_info_loc,
                 &cwd,
                 &pkg_root,
-                &target_dir,
+                &target_root,
                 &rustc,
                 // Do not track source files in the fingerprint for registry dependencies.
                 is_local,
@@ -516,12 +679,15 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
     let bcx = cx.bcx;
     let outputs = cx.outputs(unit)?;
     let export_dir = cx.files().export_dir();
+    let artifact_dir = bcx.build_config.artifact_dir.clone();
+    let artifact_subdir = artifact_dir::subdir(&unit.target);
     let package_id = unit.pkg.package_id();
     let manifest_path = PathBuf::from(unit.pkg.manifest_path());
     let profile = unit.profile.clone();
     let unit_mode = unit.mode;
     let features = unit.features.iter().map(|s| s.to_string()).collect();
     let json_messages = bcx.build_config.emit_json();
+    let artifact_link = bcx.build_config.artifact_link;
     let executable = cx.get_executable(unit)?;
     let mut target = Target::clone(&unit.target);
     if let TargetSourcePath::Metabuild = target.src_path() {
@@ -551,12 +717,18 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
                 }
             };
             destinations.push(dst.clone());
-            paths::link_or_copy(src, dst)?;
+            paths::link_or_copy_with(src, dst, artifact_link)?;
             if let Some(ref path) = output.export_path {
                 let export_dir = export_dir.as_ref().unwrap();
                 paths::create_dir_all(export_dir)?;
 
-                paths::link_or_copy(src, path)?;
+                paths::link_or_copy_with(src, path, artifact_link)?;
+            }
+            if let (Some(artifact_dir), Some(subdir)) = (&artifact_dir, artifact_subdir) {
+                if !matches!(output.flavor, FileFlavor::Rmeta | FileFlavor::DebugInfo) {
+                    let file_name = dst.file_name().unwrap().to_str().unwrap();
+                    artifact_dir::update(artifact_dir, subdir, src, file_name, !fresh)?;
+                }
             }
         }
 
@@ -675,13 +847,16 @@ fn prepare_rustc(cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<ProcessBuilde
     let mut base = cx
         .compilation
         .rustc_process(unit, is_primary, is_workspace)?;
+    if tool_wrapper::applies_to(cx, unit) {
+        base = tool_wrapper::wrap(base, &cx.bcx.build_config.tool_wrappers);
+    }
 
     if is_primary {
         base.env("CARGO_PRIMARY_PACKAGE", "1");
     }
 
     if unit.target.is_test() || unit.target.is_bench() {
-        let tmp = cx.files().layout(unit.kind).prepare_tmp()?;
+        let tmp = cx.files().unit_layout(unit).prepare_tmp()?;
         base.env("CARGO_TARGET_TMPDIR", tmp.display().to_string());
     }
 
@@ -712,7 +887,7 @@ fn prepare_rustdoc(cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<ProcessBuil
     add_path_args(bcx.ws, unit, &mut rustdoc);
     add_cap_lints(bcx, unit, &mut rustdoc);
 
-    if let CompileKind::Target(target) = unit.kind {
+    if let Some(target) = bcx.target_data.rustc_target(unit.kind) {
         rustdoc.arg("--target").arg(target.rustc_target());
     }
     let doc_dir = cx.files().out_dir(unit);
@@ -720,7 +895,7 @@ fn prepare_rustdoc(cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<ProcessBuil
     rustdoc.args(&features_args(unit));
     rustdoc.args(&check_cfg_args(cx, unit));
 
-    add_error_format_and_color(cx, &mut rustdoc);
+    add_error_format_and_color(cx, &mut rustdoc, unit);
     add_allow_features(cx, &mut rustdoc);
 
     rustdoc.args(unit.pkg.manifest().lint_rustflags());
@@ -928,15 +1103,19 @@ fn add_allow_features(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder) {
 /// which Cargo will extract and display to the user.
 ///
 /// [`--error-format`]: https://doc.rust-lang.org/nightly/rustc/command-line-arguments.html#--error-format-control-how-errors-are-produced
-fn add_error_format_and_color(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder) {
+fn add_error_format_and_color(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder, unit: &Unit) {
     cmd.arg("--error-format=json");
     let mut json = String::from("--json=diagnostic-rendered-ansi,artifacts,future-incompat");
 
-    match cx.bcx.build_config.message_format {
-        MessageFormat::Short | MessageFormat::Json { short: true, .. } => {
-            json.push_str(",diagnostic-short");
-        }
-        _ => {}
+    let build_config = &cx.bcx.build_config;
+    let short = match build_config.message_format {
+        MessageFormat::Short | MessageFormat::Json { short: true, .. } => true,
+        // Keep the full diagnostics of the members, which are the ones being
+        // worked on.
+        _ => build_config.short_dep_errors && !cx.bcx.ws.is_member(&unit.pkg),
+    };
+    if short {
+        json.push_str(",diagnostic-short");
     }
     cmd.arg(json);
 
@@ -974,7 +1153,7 @@ fn build_base_args(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder, unit: &Unit)
     edition.cmd_edition_arg(cmd);
 
     add_path_args(bcx.ws, unit, cmd);
-    add_error_format_and_color(cx, cmd);
+    add_error_format_and_color(cx, cmd, unit);
     add_allow_features(cx, cmd);
 
     let mut contains_dy_lib = false;
@@ -1109,7 +1288,7 @@ fn build_base_args(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder, unit: &Unit)
         }
     }
 
-    if let CompileKind::Target(n) = unit.kind {
+    if let Some(n) = bcx.target_data.rustc_target(unit.kind) {
         cmd.arg("--target").arg(n.rustc_target());
     }
 
@@ -1120,8 +1299,8 @@ fn build_base_args(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder, unit: &Unit)
         bcx.linker(unit.kind).as_ref().map(|s| s.as_ref()),
     );
     if incremental {
-        let dir = cx.files().layout(unit.kind).incremental().as_os_str();
-        opt(cmd, "-C", "incremental=", Some(dir));
+        let dir = cx.files().incremental_dir(unit);
+        opt(cmd, "-C", "incremental=", Some(dir.as_os_str()));
     }
 
     if strip != Strip::None {
@@ -1260,6 +1439,21 @@ fn build_deps_args(cmd: &mut ProcessBuilder, cx: &Context<'_, '_>, unit: &Unit)
         });
     }
 
+    // Packages with their own `target-dir` are not in the shared `deps`
+    // directory, so rustc needs to be told where to find them when they are
+    // transitive dependencies.
+    let mut isolated: Vec<_> = cx.files().isolated_deps_dirs(unit.kind).collect();
+    if !unit.kind.is_host() {
+        isolated.extend(cx.files().isolated_deps_dirs(CompileKind::Host));
+    }
+    for dir in isolated {
+        if dir != cx.files().deps_dir(unit) {
+            let mut deps = OsString::from("dependency=");
+            deps.push(dir);
+            cmd.arg("-L").arg(deps);
+        }
+    }
+
     let deps = cx.unit_deps(unit);
 
     // If there is not one linkable target but should, rustc fails later
@@ -1445,6 +1639,8 @@ struct OutputOptions {
     warnings_seen: usize,
     /// Tracks the number of errors we've seen so far.
     errors_seen: usize,
+    /// The files rustc reported writing, with its artifact notifications.
+    artifacts: Vec<PathBuf>,
 }
 
 impl OutputOptions {
@@ -1461,7 +1657,19 @@ impl OutputOptions {
             show_diagnostics: true,
             warnings_seen: 0,
             errors_seen: 0,
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// Forgets the output of a compilation, before compiling again.
+    fn reset(&mut self) {
+        if let Some((path, cell)) = &mut self.cache_cell {
+            drop(fs::remove_file(&path));
+            *cell = LazyCell::new();
         }
+        self.warnings_seen = 0;
+        self.errors_seen = 0;
+        self.artifacts.clear();
     }
 }
 
@@ -1666,6 +1874,7 @@ fn on_stderr_line_inner(
             debug!("looks like metadata finished early!");
             state.rmeta_produced();
         }
+        options.artifacts.push(PathBuf::from(artifact.artifact));
         return Ok(false);
     }
 
@@ -1680,9 +1889,16 @@ fn on_stderr_line_inner(
     #[derive(serde::Deserialize)]
     struct CompilerMessage {
         level: String,
+        message: String,
     }
     if let Ok(message) = serde_json::from_str::<CompilerMessage>(compiler_message.get()) {
         count_diagnostic(&message.level, options);
+        if message.level == "warning"
+            && !message.message.ends_with("warning emitted")
+            && !message.message.ends_with("warnings emitted")
+        {
+            state.json_warning();
+        }
     }
 
     let msg = machine_message::FromCompiler {
@@ -1720,6 +1936,7 @@ fn replay_output_cache(
         show_diagnostics,
         warnings_seen: 0,
         errors_seen: 0,
+        artifacts: Vec::new(),
     };
     Work::new(move |state| {
         if !path.exists() {
@@ -1768,6 +1985,50 @@ fn descriptive_pkg_name(name: &str, target: &Target, mode: &CompileMode) -> Stri
     format!("`{name}` ({desc_name}{mode})")
 }
 
+/// Errors if rustc succeeded without writing one of the `outputs` of a unit,
+/// for example because a `rustc` wrapper skipped the compilation, instead of
+/// failing later with a "file not found" when the output is linked or used.
+///
+/// `artifacts` are the files rustc reported writing, `display_path` renders
+/// the missing output for the error.
+fn check_outputs_produced(
+    outputs: &[OutputFile],
+    artifacts: &[PathBuf],
+    name: &str,
+    target: &Target,
+    mode: &CompileMode,
+    display_path: impl Fn(&Path) -> String,
+) -> CargoResult<()> {
+    let expected = outputs.iter().filter(|output| {
+        matches!(
+            output.flavor,
+            FileFlavor::Normal | FileFlavor::Linkable | FileFlavor::Rmeta
+        )
+    });
+    for output in expected {
+        if output.path.exists() {
+            continue;
+        }
+        let name = descriptive_pkg_name(name, target, mode);
+        if artifacts.contains(&output.path) {
+            bail!(
+                "rustc wrote `{}` while compiling {}, but it was removed before \
+                 the compilation finished",
+                display_path(&output.path),
+                name
+            );
+        }
+        bail!(
+            "rustc did not produce the expected output `{}` while compiling {}\n\n\
+             note: this can happen when a `rustc` wrapper skips the compilation \
+             or writes its outputs elsewhere",
+            display_path(&output.path),
+            name
+        );
+    }
+    Ok(())
+}
+
 /// Applies environment variables from config `[env]` to [`ProcessBuilder`].
 fn apply_env_config(config: &crate::Config, cmd: &mut ProcessBuilder) -> CargoResult<()> {
     for (key, value) in config.env_config()?.iter() {
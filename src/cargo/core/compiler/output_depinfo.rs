@@ -56,9 +56,11 @@ fn add_deps_for_unit(
     if !unit.mode.is_run_custom_build() {
         // Add dependencies from rustc dep-info output (stored in fingerprint directory)
         let dep_info_loc = fingerprint::dep_info_loc(cx, unit);
-        if let Some(paths) =
-            fingerprint::parse_dep_info(unit.pkg.root(), cx.files().host_root(), &dep_info_loc)?
-        {
+        if let Some(paths) = fingerprint::parse_dep_info(
+            unit.pkg.root(),
+            &fingerprint::target_root(cx, unit),
+            &dep_info_loc,
+        )? {
             for path in paths.files {
                 deps.insert(path);
             }
@@ -0,0 +1,65 @@
+//! Commands run on linked artifacts with `build.post-link-hooks`.
+//!
+//! Each hook is a command line, which is run with the path of the artifact
+//! appended once rustc produced it in `deps/`, and before it is linked into
+//! `target/{debug,release}`. This is meant for tools modifying the artifact
+//! in place, such as `strip`, `objcopy`, or code signing tools.
+//!
+//! Only executables and dynamic libraries are passed to the hooks. Build
+//! scripts and proc-macros are left alone, as they are only used by the
+//! build itself. The hooks are part of the fingerprint of these units, so
+//! changing them rebuilds the artifacts they run on.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use cargo_util::ProcessBuilder;
+
+use super::{CompileMode, CrateType, Unit};
+use crate::core::compiler::job_queue::JobState;
+use crate::util::CargoResult;
+
+/// Parses the entries of `build.post-link-hooks` into a program and its
+/// arguments.
+pub fn parse_hooks(hooks: &[String]) -> CargoResult<Vec<Vec<String>>> {
+    hooks
+        .iter()
+        .map(|hook| {
+            let args: Vec<_> = hook.split_whitespace().map(str::to_string).collect();
+            if args.is_empty() {
+                anyhow::bail!("`build.post-link-hooks` must not contain empty commands");
+            }
+            Ok(args)
+        })
+        .collect()
+}
+
+/// Whether the artifacts of `unit` are passed to the post-link hooks.
+pub fn applies_to(unit: &Unit) -> bool {
+    matches!(
+        unit.mode,
+        CompileMode::Build | CompileMode::Test | CompileMode::Bench
+    ) && !unit.target.is_custom_build()
+        && unit
+            .target
+            .rustc_crate_types()
+            .iter()
+            .any(|ty| matches!(ty, CrateType::Bin | CrateType::Dylib | CrateType::Cdylib))
+}
+
+/// Runs each of `hooks` on the artifact `path`.
+pub fn run(state: &JobState<'_, '_>, hooks: &[Vec<String>], path: &Path) -> CargoResult<()> {
+    for hook in hooks {
+        let mut cmd = ProcessBuilder::new(&hook[0]);
+        cmd.args(&hook[1..]).arg(path);
+        state.running(&cmd);
+        cmd.exec_with_output().with_context(|| {
+            format!(
+                "post-link hook `{}` failed on `{}`",
+                hook.join(" "),
+                path.display()
+            )
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! A build cache shared by all workspaces of a user, enabled with
+//! `-Zshared-build-cache`.
+//!
+//! Registry packages never change once published, so the same dependency
+//! compiled with the same flags produces the same artifacts in every
+//! workspace. Instead of compiling it again for each of them, its artifacts
+//! are stored once in `$CARGO_HOME/build-cache/<key>`, and copied into the
+//! target directories which need them.
+//!
+//! This works as follows:
+//!
+//! * A unit is cacheable if it compiles a library from a registry, and all
+//!   the units it depends on are cacheable too. This rules out packages with
+//!   a build script, or depending on one, as what a build script emits
+//!   depends on its environment.
+//! * The key of an entry is a hash of the metadata hash of the unit, which
+//!   covers the package, its features, its profile and the metadata of its
+//!   dependencies, and of what affects the artifacts without being part of
+//!   the metadata hash, such as the version of rustc and `RUSTFLAGS`.
+//! * The environment variables read by the unit with `env!` and
+//!   `option_env!` are only known once it is compiled, from its dep-info.
+//!   Their names are recorded in `$CARGO_HOME/build-cache/<key>/env`, and
+//!   the artifacts are stored in `$CARGO_HOME/build-cache/<key>/<env>`,
+//!   where `<env>` is a hash of their values.
+//! * A dirty cacheable unit whose entry exists copies its artifacts and its
+//!   dep-info from the entry instead of running rustc. The fingerprint of the
+//!   unit is then written as if it was compiled.
+//! * Otherwise, the unit is compiled and its artifacts and dep-info are
+//!   copied into a temporary directory of the cache, which is renamed to the
+//!   entry once complete. An entry is therefore either complete or missing,
+//!   even with several Cargo processes filling the cache at once.
+//!
+//! Files are hardlinked between the cache and the target directories, and
+//! copied when they are on different filesystems. Neither rustc nor Cargo
+//! write to an existing artifact in place, they replace it, so an entry
+//! isn't modified through a target directory. The modification time is
+//! shared though: restoring an entry marks its files as modified now, which
+//! may make the dependents of the same unit compile again once in the other
+//! target directories linking them.
+//!
+//! Entries are never removed by Cargo, so the cache grows with each version
+//! of a dependency, version of rustc and set of flags it is compiled with.
+//! It can be pruned by deleting `$CARGO_HOME/build-cache`, or some of its
+//! directories, while no build is running.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use filetime::FileTime;
+use tracing::debug;
+
+use crate::core::compiler::fingerprint::{self, snapshot};
+use crate::core::compiler::{CompileMode, Context, Unit, Work};
+use crate::util::{self, CargoResult};
+
+/// Whether the artifacts of `unit` can be shared through the build cache.
+pub fn is_cacheable(cx: &Context<'_, '_>, unit: &Unit) -> bool {
+    if !cx.bcx.config.cli_unstable().shared_build_cache {
+        return false;
+    }
+    let mut visited = HashSet::new();
+    is_cacheable_rec(cx, unit, &mut visited)
+}
+
+fn is_cacheable_rec<'a>(
+    cx: &'a Context<'_, '_>,
+    unit: &'a Unit,
+    visited: &mut HashSet<&'a Unit>,
+) -> bool {
+    if !visited.insert(unit) {
+        return true;
+    }
+    matches!(
+        unit.mode,
+        CompileMode::Build | CompileMode::Check { test: false }
+    ) && unit.target.is_lib()
+        && !unit.is_std
+        && unit.pkg.package_id().source_id().is_registry()
+        && cx
+            .unit_deps(unit)
+            .iter()
+            .all(|dep| is_cacheable_rec(cx, &dep.unit, visited))
+}
+
+/// The name of the dep-info file in an entry of the build cache, next to the
+/// artifacts, which are named after their crate.
+const DEP_INFO: &str = "dep-info";
+
+/// Wraps the compilation of a cacheable unit, to restore its artifacts from
+/// the build cache, or to store them there once compiled.
+pub fn wrap(cx: &mut Context<'_, '_>, unit: &Unit, work: Work) -> CargoResult<Work> {
+    let key_dir = key_dir(cx, unit);
+    // The entry matching the current values of the environment variables the
+    // unit read the last time it was compiled, if it ever was.
+    let entry = read_env_names(&key_dir).map(|names| {
+        let env = names
+            .into_iter()
+            .map(|name| {
+                let value = snapshot::current_env(cx.bcx.config, &name);
+                (name, value)
+            })
+            .collect::<Vec<_>>();
+        entry_path(&key_dir, &env)
+    });
+    let out_dir = cx.files().out_dir(unit);
+    let dep_info = fingerprint::dep_info_loc(cx, unit);
+    let pkg_root = unit.pkg.root().to_path_buf();
+    let target_root = fingerprint::target_root(cx, unit);
+    let outputs: Vec<PathBuf> = cx
+        .outputs(unit)?
+        .iter()
+        .map(|output| output.path.clone())
+        .collect();
+    let name = unit.pkg.to_string();
+
+    Ok(Work::new(move |state| {
+        if let Some(entry) = entry.filter(|entry| entry.is_dir()) {
+            debug!("restoring `{}` from {}", name, entry.display());
+            return restore(&entry, &out_dir, &dep_info)
+                .with_context(|| format!("failed to restore `{}` from the build cache", name));
+        }
+        work.call(state)?;
+        // Failing to fill the cache doesn't fail the build, the unit is
+        // simply compiled again next time.
+        let stored =
+            fingerprint::parse_dep_info(&pkg_root, &target_root, &dep_info).and_then(|info| {
+                match info {
+                    Some(info) => store(&key_dir, info.env, &outputs, &dep_info),
+                    None => anyhow::bail!("no dep-info at {}", dep_info.display()),
+                }
+            });
+        if let Err(e) = stored {
+            debug!("failed to store `{}` in the build cache: {:?}", name, e);
+        }
+        Ok(())
+    }))
+}
+
+/// The directory holding the entries of `unit` in the build cache, one per
+/// set of values of the environment variables it reads.
+fn key_dir(cx: &Context<'_, '_>, unit: &Unit) -> PathBuf {
+    let key = util::hash_u64((
+        &cx.bcx.rustc().verbose_version,
+        cx.files().metadata(unit),
+        unit.mode,
+        unit.target.kind().description(),
+        unit.target.name(),
+        cx.bcx.rustflags_args(unit),
+        cx.bcx.extra_args_for(unit),
+        unit.pkg.manifest().lint_rustflags(),
+    ));
+    cx.bcx
+        .config
+        .home()
+        .join("build-cache")
+        .into_path_unlocked()
+        .join(format!("{:016x}", key))
+}
+
+/// The entry of `key_dir` for the values `env` of the environment variables
+/// read by the unit, sorted by name.
+fn entry_path(key_dir: &Path, env: &[(String, Option<String>)]) -> PathBuf {
+    key_dir.join(format!("{:016x}", util::hash_u64(env)))
+}
+
+/// Reads the names of the environment variables recorded in `key_dir`, or
+/// `None` if no entry was ever stored there.
+fn read_env_names(key_dir: &Path) -> Option<Vec<String>> {
+    let names = fs::read_to_string(key_dir.join("env")).ok()?;
+    Some(names.lines().map(str::to_string).collect())
+}
+
+/// Links the artifacts of `entry` into `out_dir`, and its dep-info to
+/// `dep_info`.
+fn restore(entry: &Path, out_dir: &Path, dep_info: &Path) -> CargoResult<()> {
+    let now = FileTime::now();
+    for file in fs::read_dir(entry)? {
+        let src = file?.path();
+        let dst = if src.file_name() == Some(DEP_INFO.as_ref()) {
+            dep_info.to_path_buf()
+        } else {
+            out_dir.join(src.file_name().unwrap())
+        };
+        paths::link_or_copy(&src, &dst)?;
+        // The artifacts must look newer than the ones of the dependencies,
+        // or the fingerprint would consider the unit stale next time. Links
+        // and clones keep the mtime of the entry.
+        paths::set_file_time_no_err(&dst, now);
+    }
+    Ok(())
+}
+
+/// Stores the `outputs` and the `dep_info` of a compilation in `key_dir`,
+/// for the values `env` of the environment variables it read.
+fn store(
+    key_dir: &Path,
+    mut env: Vec<(String, Option<String>)>,
+    outputs: &[PathBuf],
+    dep_info: &Path,
+) -> CargoResult<()> {
+    env.sort();
+    paths::create_dir_all(key_dir)?;
+    if read_env_names(key_dir).is_none() {
+        let names: String = env.iter().map(|(name, _)| format!("{name}\n")).collect();
+        let tmp = tempfile::Builder::new()
+            .prefix(".tmp")
+            .tempfile_in(key_dir)?;
+        paths::write(tmp.path(), names)?;
+        // Another process may have recorded the names in the meantime, which
+        // are the same as the unit reads the same variables.
+        let _ = tmp.persist_noclobber(key_dir.join("env"));
+    }
+    let entry = entry_path(key_dir, &env);
+    let tmp = tempfile::Builder::new()
+        .prefix(".tmp")
+        .tempdir_in(key_dir)?;
+    for output in outputs.iter().filter(|output| output.exists()) {
+        let dst = tmp.path().join(output.file_name().unwrap());
+        paths::link_or_copy(output, dst)?;
+    }
+    paths::link_or_copy(dep_info, tmp.path().join(DEP_INFO))?;
+    // Another process may have stored the same entry in the meantime, in
+    // which case ours is dropped.
+    if fs::rename(tmp.path(), &entry).is_ok() {
+        let _ = tmp.keep();
+    }
+    Ok(())
+}
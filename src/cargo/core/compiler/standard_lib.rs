@@ -101,6 +101,7 @@ pub fn resolve_std<'cfg>(
         /*exclude*/ &None,
         /*inheritable*/ &None,
         /*custom_metadata*/ &None,
+        /*feature_sets*/ &None,
     ));
     let virtual_manifest = crate::core::VirtualManifest::new(
         /*replace*/ Vec::new(),
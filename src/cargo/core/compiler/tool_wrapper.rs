@@ -0,0 +1,75 @@
+//! Tools wrapping `rustc` for workspace members, with `build.tool-wrappers`.
+//!
+//! A tool wrapper is run in place of `rustc` for the units of workspace
+//! members, with the path of `rustc` and its arguments, like
+//! `RUSTC_WORKSPACE_WRAPPER`. This is meant for linters and analyzers such as
+//! `clippy-driver`. Several wrappers are chained, the first one of the list
+//! running the second one, and so on until `rustc`.
+//!
+//! Unlike `RUSTC_WORKSPACE_WRAPPER`, the version of each tool, as printed by
+//! `<tool> --version`, is part of the fingerprint and of the metadata hash of
+//! the units it wraps. Upgrading a tool thus rebuilds the units it wraps, and
+//! their artifacts are kept apart from the ones built without the tool, so
+//! enabling or disabling it doesn't rebuild the workspace each time.
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use cargo_util::ProcessBuilder;
+
+use super::{Context, Unit};
+use crate::util::config::ConfigRelativePath;
+use crate::util::{CargoResult, Config};
+
+/// A tool from `build.tool-wrappers`.
+#[derive(Debug, Clone, Hash)]
+pub struct ToolWrapper {
+    /// The program to run in place of `rustc`.
+    pub path: PathBuf,
+    /// The output of `<path> --version`.
+    pub version: String,
+}
+
+/// Resolves the entries of `build.tool-wrappers`, querying the version of
+/// each tool.
+pub fn from_config(
+    config: &Config,
+    wrappers: &[ConfigRelativePath],
+) -> CargoResult<Vec<ToolWrapper>> {
+    wrappers
+        .iter()
+        .map(|wrapper| {
+            let path = wrapper.resolve_program(config);
+            let output = ProcessBuilder::new(&path)
+                .arg("--version")
+                .exec_with_output()
+                .with_context(|| {
+                    format!(
+                        "failed to get the version of the tool wrapper `{}` \
+                         from `build.tool-wrappers`",
+                        wrapper.raw_value()
+                    )
+                })?;
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Ok(ToolWrapper { path, version })
+        })
+        .collect()
+}
+
+/// Whether `unit` is compiled through the tool wrappers.
+pub fn applies_to(cx: &Context<'_, '_>, unit: &Unit) -> bool {
+    !cx.bcx.build_config.tool_wrappers.is_empty()
+        && cx.bcx.ws.is_member(&unit.pkg)
+        && !unit.mode.is_doc()
+        && !unit.mode.is_doc_test()
+        && !unit.mode.is_run_custom_build()
+}
+
+/// Wraps the `rustc` invocation `cmd` in the tools of `wrappers`.
+pub fn wrap(cmd: ProcessBuilder, wrappers: &[ToolWrapper]) -> ProcessBuilder {
+    // The last wrapper added is the one run first.
+    wrappers
+        .iter()
+        .rev()
+        .fold(cmd, |cmd, wrapper| cmd.wrapped(Some(&wrapper.path)))
+}
@@ -143,10 +143,75 @@ pub fn build_unit_dependencies<'a, 'cfg>(
         list.sort();
     }
     trace!("ALL UNIT DEPENDENCIES {:#?}", state.unit_dependencies);
+    check_cycles(&state)?;
 
     Ok(state.unit_dependencies)
 }
 
+/// Errors if the unit graph has a cycle, naming each of its edges.
+///
+/// The resolver rejects cycles between packages unless they go through a
+/// dev-dependency, and those normally end at a different unit, like the test
+/// of a proc-macro depending on a sibling which uses the proc-macro. Anything
+/// else would otherwise only be noticed once the units are queued.
+fn check_cycles(state: &State<'_, '_>) -> CargoResult<()> {
+    let mut units: Vec<&Unit> = state.unit_dependencies.keys().collect();
+    units.sort();
+    let mut checked = HashSet::new();
+    let mut path = Vec::new();
+    for unit in units {
+        visit(state, unit, &mut path, &mut checked)?;
+    }
+    return Ok(());
+
+    fn visit<'u>(
+        state: &'u State<'_, '_>,
+        unit: &'u Unit,
+        path: &mut Vec<&'u Unit>,
+        checked: &mut HashSet<&'u Unit>,
+    ) -> CargoResult<()> {
+        if checked.contains(unit) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|parent| *parent == unit) {
+            let cycle = &path[start..];
+            let mut msg = "cyclic dependency between the units of the build:".to_string();
+            for (i, &parent) in cycle.iter().enumerate() {
+                let child = cycle.get(i + 1).copied().unwrap_or(unit);
+                let edge = (parent.clone(), child.clone());
+                let dev = if state.dev_dependency_edges.contains(&edge) {
+                    ", as a dev-dependency"
+                } else {
+                    ""
+                };
+                msg.push_str(&format!(
+                    "\n  {} depends on {}{}",
+                    describe(parent),
+                    describe(child),
+                    dev
+                ));
+            }
+            anyhow::bail!(msg);
+        }
+        path.push(unit);
+        for dep in &state.unit_dependencies[unit] {
+            visit(state, &dep.unit, path, checked)?;
+        }
+        path.pop();
+        checked.insert(unit);
+        Ok(())
+    }
+
+    fn describe(unit: &Unit) -> String {
+        let name = super::descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+        if unit.mode.is_run_custom_build() {
+            format!("the run of {name}")
+        } else {
+            name
+        }
+    }
+}
+
 /// Compute all the dependencies for the standard library.
 fn calc_deps_of_std(
     state: &mut State<'_, '_>,
@@ -469,6 +534,19 @@ fn compute_deps_custom_build(
             return Ok(Vec::new());
         }
     }
+    if state
+        .target_data
+        .build_probe(&unit.pkg, unit.kind)
+        .is_some()
+    {
+        // Neither do build scripts replaced by `[build-probe]`.
+        return Ok(Vec::new());
+    }
+    if unit.pkg.manifest().declarative_build().is_some() {
+        // Declarative builds are run by Cargo itself, there is no script to
+        // compile.
+        return Ok(Vec::new());
+    }
     // All dependencies of this unit should use profiles for custom builds.
     // If this is a build script of a proc macro, make sure it uses host
     // features.
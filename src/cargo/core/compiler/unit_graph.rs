@@ -1,6 +1,10 @@
 //! Serialization of [`UnitGraph`] for unstable option [`--unit-graph`].
 //!
+//! The serialized form is versioned by [`VERSION`]. Library users can get it
+//! without going through stdout with [`ops::unit_graph`].
+//!
 //! [`--unit-graph`]: https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#unit-graph
+//! [`ops::unit_graph`]: crate::ops::unit_graph
 
 use crate::core::compiler::Unit;
 use crate::core::compiler::{CompileKind, CompileMode};
@@ -37,38 +41,50 @@ pub struct UnitDep {
     pub noprelude: bool,
 }
 
-const VERSION: u32 = 1;
+/// The version of the serialized unit graph schema. It is increased if any
+/// backwards incompatible changes are made.
+pub const VERSION: u32 = 1;
 
+/// The serialized form of a [`UnitGraph`].
 #[derive(serde::Serialize)]
-struct SerializedUnitGraph<'a> {
-    version: u32,
-    units: Vec<SerializedUnit<'a>>,
-    roots: Vec<usize>,
+pub struct SerializedUnitGraph {
+    /// The [`VERSION`] of the schema.
+    pub version: u32,
+    /// All units, sorted.
+    pub units: Vec<SerializedUnit>,
+    /// Indices into `units` of the roots of the graph.
+    pub roots: Vec<usize>,
 }
 
+/// A serialized [`Unit`].
 #[derive(serde::Serialize)]
-struct SerializedUnit<'a> {
-    pkg_id: PackageId,
-    target: &'a Target,
-    profile: &'a Profile,
-    platform: CompileKind,
-    mode: CompileMode,
-    features: &'a Vec<InternedString>,
+pub struct SerializedUnit {
+    pub pkg_id: PackageId,
+    pub target: Target,
+    /// The profile settings of this unit, which may differ from the ones in
+    /// the manifest. For example `panic` is always `unwind` for tests.
+    pub profile: Profile,
+    /// The platform the unit is built for.
+    pub platform: CompileKind,
+    pub mode: CompileMode,
+    pub features: Vec<InternedString>,
     #[serde(skip_serializing_if = "std::ops::Not::not")] // hide for unstable build-std
-    is_std: bool,
-    dependencies: Vec<SerializedUnitDep>,
+    pub is_std: bool,
+    pub dependencies: Vec<SerializedUnitDep>,
 }
 
+/// A serialized [`UnitDep`].
 #[derive(serde::Serialize)]
-struct SerializedUnitDep {
-    index: usize,
-    extern_crate_name: InternedString,
+pub struct SerializedUnitDep {
+    /// Index into [`SerializedUnitGraph::units`] of the dependency.
+    pub index: usize,
+    pub extern_crate_name: InternedString,
     // This is only set on nightly since it is unstable.
     #[serde(skip_serializing_if = "Option::is_none")]
-    public: Option<bool>,
+    pub public: Option<bool>,
     // This is only set on nightly since it is unstable.
     #[serde(skip_serializing_if = "Option::is_none")]
-    noprelude: Option<bool>,
+    pub noprelude: Option<bool>,
     // Intentionally not including `unit_for` because it is a low-level
     // internal detail that is mostly used for building the graph.
 }
@@ -80,6 +96,21 @@ pub fn emit_serialized_unit_graph(
     unit_graph: &UnitGraph,
     config: &Config,
 ) -> CargoResult<()> {
+    let s = serialize_unit_graph(root_units, unit_graph, config);
+
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+    serde_json::to_writer(&mut lock, &s)?;
+    drop(writeln!(lock));
+    Ok(())
+}
+
+/// Converts [`UnitGraph`] to its serialized form for given `root_units`.
+pub fn serialize_unit_graph(
+    root_units: &[Unit],
+    unit_graph: &UnitGraph,
+    config: &Config,
+) -> SerializedUnitGraph {
     let mut units: Vec<(&Unit, &Vec<UnitDep>)> = unit_graph.iter().collect();
     units.sort_unstable();
     // Create a map for quick lookup for dependencies.
@@ -111,25 +142,19 @@ pub fn emit_serialized_unit_graph(
                 .collect();
             SerializedUnit {
                 pkg_id: unit.pkg.package_id(),
-                target: &unit.target,
-                profile: &unit.profile,
+                target: unit.target.clone(),
+                profile: unit.profile.clone(),
                 platform: unit.kind,
                 mode: unit.mode,
-                features: &unit.features,
+                features: unit.features.clone(),
                 is_std: unit.is_std,
                 dependencies,
             }
         })
         .collect();
-    let s = SerializedUnitGraph {
+    SerializedUnitGraph {
         version: VERSION,
         units: ser_units,
         roots,
-    };
-
-    let stdout = std::io::stdout();
-    let mut lock = stdout.lock();
-    serde_json::to_writer(&mut lock, &s)?;
-    drop(writeln!(lock));
-    Ok(())
+    }
 }
@@ -140,6 +140,7 @@
 use std::collections::BTreeSet;
 use std::env;
 use std::fmt::{self, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::{bail, Error};
@@ -483,6 +484,18 @@ features! {
 
     // Allow specifying rustflags directly in a profile
     (stable, workspace_inheritance, "1.64", "reference/unstable.html#workspace-inheritance"),
+
+    // Allow placing the outputs of specific packages in their own target directory
+    (unstable, package_target_dir, "", "reference/unstable.html#package-target-dir"),
+
+    // Allow targets to be left out unless they are selected by name
+    (unstable, requires_explicit, "", "reference/unstable.html#requires-explicit"),
+
+    // Allow workspaces to define named sets of features of their members
+    (unstable, feature_sets, "", "reference/unstable.html#feature-sets"),
+
+    // Allow probing for system libraries without a build script
+    (unstable, build_probe, "", "reference/unstable.html#build-probe"),
 }
 
 pub struct Feature {
@@ -686,6 +699,19 @@ macro_rules! unstable_cli_options {
                 let fields = vec![$((stringify!($element), $help)),*];
                 fields
             }
+
+            /// Returns the names of the flags that are set to something other
+            /// than their default, in the `kebab-case` form used on the
+            /// command line.
+            pub fn enabled(&self) -> Vec<String> {
+                let mut enabled = Vec::new();
+                $(
+                    if format!("{:?}", self.$element) != format!("{:?}", <$ty>::default()) {
+                        enabled.push(stringify!($element).replace('_', "-"));
+                    }
+                )*
+                enabled
+            }
         }
 
         #[cfg(test)]
@@ -718,39 +744,67 @@ unstable_cli_options!(
     // All other unstable features.
     // Please keep this list lexicographically ordered.
     advanced_env: bool = (HIDDEN),
+    artifact_link: bool = ("Enable the `build.artifact-link` key in the .cargo/config.toml file"),
     avoid_dev_deps: bool = ("Avoid installing dev-dependencies if possible"),
     binary_dep_depinfo: bool = ("Track changes to dependency artifacts"),
     bindeps: bool = ("Allow Cargo packages to depend on bin, cdylib, and staticlib crates, and use the artifacts built by those crates"),
+    build_dep_host_filtering: bool = ("Evaluate the platform of build dependencies against the host when filtering dependencies"),
+    build_retry: bool = ("Enable the `build.retry` key in the .cargo/config.toml file"),
+    build_script_sandbox: bool = ("Run build scripts in a sandbox and report what it blocks"),
     #[serde(deserialize_with = "deserialize_build_std")]
     build_std: Option<Vec<String>>  = ("Enable Cargo to compile the standard library itself as part of a crate graph compilation"),
     build_std_features: Option<Vec<String>>  = ("Configure features enabled for the standard library itself when building the standard library"),
+    build_summary: bool = ("Print a summary of the build after the `Finished` line"),
+    cache_backends: bool = ("Enable the `cache-backends` table in the .cargo/config.toml file, and `cargo clean --cache-too`"),
     #[serde(deserialize_with = "deserialize_check_cfg")]
     check_cfg: Option<(/*features:*/ bool, /*well_known_names:*/ bool, /*well_known_values:*/ bool, /*output:*/ bool)> = ("Specify scope of compile-time checking of `cfg` names/values"),
     codegen_backend: bool = ("Enable the `codegen-backend` option in profiles in .cargo/config.toml file"),
     config_include: bool = ("Enable the `include` key in config files"),
+    content_hashes: Option<ContentHashes> = ("Record hashes of the contents of git and path dependencies in Cargo.lock"),
     credential_process: bool = ("Add a config setting to fetch registry authentication tokens by calling an external process"),
     direct_minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum (direct dependencies only)"),
     doctest_xcompile: bool = ("Compile and run doctests for non-host target using runner config"),
     dual_proc_macros: bool = ("Build proc-macros for both the host and the target"),
     features: Option<Vec<String>>  = (HIDDEN),
+    fingerprint_snapshot: bool = ("Record the environment of each built unit for `cargo fingerprint diff`"),
     gitoxide: Option<GitoxideFeatures> = ("Use gitoxide for the given git interactions, or all of them if no argument is given"),
     host_config: bool = ("Enable the [host] section in the .cargo/config.toml file"),
     lints: bool = ("Pass `[lints]` to the linting tools"),
+    log_file: Option<PathBuf> = ("Write a structured log of the invocation to the given file"),
+    max_concurrent_downloads: bool = ("Enable the `http.max-concurrent-downloads` key in the .cargo/config.toml file"),
+    max_memory: bool = ("Enable the `build.max-memory` key in the .cargo/config.toml file"),
+    metadata_cache: bool = ("Reuse the output of `cargo metadata` until the workspace, `Cargo.lock` or the configuration change"),
+    metadata_hash: bool = ("Enable the `build.metadata-hash` keys in the .cargo/config.toml file"),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
+    mirror_fallback: bool = ("Enable the `source.<name>.fallback` key in the .cargo/config.toml file"),
     msrv_policy: bool = ("Enable rust-version aware policy within cargo"),
     mtime_on_use: bool = ("Configure Cargo to update the mtime of used files"),
+    nested_workspaces: bool = ("Allow workspace members to be the roots of workspaces of their own"),
     next_lockfile_bump: bool = (HIDDEN),
     no_index_update: bool = ("Do not update the registry index even if the cache is outdated"),
+    package_target_dir: bool = ("Enable the `target-dir` option in package profile overrides in .cargo/config.toml file"),
     panic_abort_tests: bool = ("Enable support to run tests with -Cpanic=abort"),
+    post_link_hooks: bool = ("Enable the `build.post-link-hooks` key in the .cargo/config.toml file"),
     profile_rustflags: bool = ("Enable the `rustflags` option in profiles in .cargo/config.toml file"),
     publish_timeout: bool = ("Enable the `publish.timeout` key in .cargo/config.toml file"),
+    read_only_sources: bool = ("Never write to package roots, and allow a read-only `CARGO_HOME` with caches in `CARGO_CACHE_HOME`"),
     registry_auth: bool = ("Authentication for alternative registries, and generate registry authentication tokens using asymmetric cryptography"),
+    resolver_precheck: bool = ("Report conflicting version requirements of workspace members before resolving"),
+    resolver_prefetch: bool = ("Query the registry for dependencies the resolver is likely to need next ahead of time"),
     rustdoc_map: bool = ("Allow passing external documentation mappings to rustdoc"),
     rustdoc_scrape_examples: bool = ("Allows Rustdoc to scrape code examples from reverse-dependencies"),
     script: bool = ("Enable support for single-file, `.rs` packages"),
     separate_nightlies: bool = (HIDDEN),
+    shared_build_cache: bool = ("Share the artifacts of registry dependencies between workspaces through `$CARGO_HOME/build-cache`"),
+    short_errors: bool = ("Enable the `term.short-errors` key in the .cargo/config.toml file"),
+    shuffle_units: Option<u64> = ("Shuffle the order units are built in, with the given seed or a random one"),
     skip_rustdoc_fingerprint: bool = (HIDDEN),
+    source_override: bool = ("Enable the `source-override.process` key in the .cargo/config.toml file"),
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
+    target_dir_gc: bool = ("Track the last use of build artifacts, and enable `cargo clean gc`"),
+    term_paths: bool = ("Enable the `term.paths` key in the .cargo/config.toml file"),
+    term_verbosity: bool = ("Enable the `term.verbosity` key in the .cargo/config.toml file"),
+    tool_wrappers: bool = ("Enable the `build.tool-wrappers` key in the .cargo/config.toml file"),
     unstable_options: bool = ("Allow the usage of unstable options"),
 );
 
@@ -915,6 +969,30 @@ fn parse_gitoxide(
     Ok(Some(out))
 }
 
+/// The kinds of sources whose contents are hashed in `Cargo.lock` with
+/// `-Zcontent-hashes`.
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+pub struct ContentHashes {
+    /// Hash the checkouts of git dependencies.
+    pub git: bool,
+    /// Hash path dependencies which are not members of the workspace.
+    pub path: bool,
+}
+
+fn parse_content_hashes(
+    it: impl Iterator<Item = impl AsRef<str>>,
+) -> CargoResult<Option<ContentHashes>> {
+    let mut out = ContentHashes::default();
+    for e in it {
+        match e.as_ref() {
+            "git" => out.git = true,
+            "path" => out.path = true,
+            _ => bail!("unstable 'content-hashes' only takes `git` and `path` as valid inputs"),
+        }
+    }
+    Ok(Some(out))
+}
+
 fn parse_check_cfg(
     it: impl Iterator<Item = impl AsRef<str>>,
 ) -> CargoResult<Option<(bool, bool, bool, bool)>> {
@@ -1087,22 +1165,40 @@ impl CliUnstable {
             // Unstable features
             // Sorted alphabetically:
             "advanced-env" => self.advanced_env = parse_empty(k, v)?,
+            "artifact-link" => self.artifact_link = parse_empty(k, v)?,
             "avoid-dev-deps" => self.avoid_dev_deps = parse_empty(k, v)?,
             "binary-dep-depinfo" => self.binary_dep_depinfo = parse_empty(k, v)?,
             "bindeps" => self.bindeps = parse_empty(k, v)?,
+            "build-dep-host-filtering" => self.build_dep_host_filtering = parse_empty(k, v)?,
+            "build-retry" => self.build_retry = parse_empty(k, v)?,
+            "build-script-sandbox" => self.build_script_sandbox = parse_empty(k, v)?,
             "build-std" => {
                 self.build_std = Some(crate::core::compiler::standard_lib::parse_unstable_flag(v))
             }
             "build-std-features" => self.build_std_features = Some(parse_features(v)),
+            "build-summary" => self.build_summary = parse_empty(k, v)?,
+            "cache-backends" => self.cache_backends = parse_empty(k, v)?,
             "check-cfg" => {
                 self.check_cfg = v.map_or(Ok(None), |v| parse_check_cfg(v.split(',')))?
             }
             "codegen-backend" => self.codegen_backend = parse_empty(k, v)?,
             "config-include" => self.config_include = parse_empty(k, v)?,
+            "content-hashes" => {
+                self.content_hashes = v.map_or_else(
+                    || {
+                        Ok(Some(ContentHashes {
+                            git: true,
+                            path: false,
+                        }))
+                    },
+                    |v| parse_content_hashes(v.split(',')),
+                )?
+            }
             "credential-process" => self.credential_process = parse_empty(k, v)?,
             "direct-minimal-versions" => self.direct_minimal_versions = parse_empty(k, v)?,
             "doctest-xcompile" => self.doctest_xcompile = parse_empty(k, v)?,
             "dual-proc-macros" => self.dual_proc_macros = parse_empty(k, v)?,
+            "fingerprint-snapshot" => self.fingerprint_snapshot = parse_empty(k, v)?,
             "gitoxide" => {
                 self.gitoxide = v.map_or_else(
                     || Ok(Some(GitoxideFeatures::all())),
@@ -1111,22 +1207,57 @@ impl CliUnstable {
             }
             "host-config" => self.host_config = parse_empty(k, v)?,
             "lints" => self.lints = parse_empty(k, v)?,
+            "log-file" => {
+                let Some(v) = v else {
+                    bail!("`-Zlog-file` requires a path, for example `-Zlog-file=cargo-log.json`")
+                };
+                self.log_file = Some(PathBuf::from(v))
+            }
+            "max-concurrent-downloads" => self.max_concurrent_downloads = parse_empty(k, v)?,
+            "max-memory" => self.max_memory = parse_empty(k, v)?,
+            "metadata-cache" => self.metadata_cache = parse_empty(k, v)?,
+            "metadata-hash" => self.metadata_hash = parse_empty(k, v)?,
+            "nested-workspaces" => self.nested_workspaces = parse_empty(k, v)?,
             "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
+            "mirror-fallback" => self.mirror_fallback = parse_empty(k, v)?,
             "msrv-policy" => self.msrv_policy = parse_empty(k, v)?,
             // can also be set in .cargo/config or with and ENV
             "mtime-on-use" => self.mtime_on_use = parse_empty(k, v)?,
             "no-index-update" => self.no_index_update = parse_empty(k, v)?,
+            "package-target-dir" => self.package_target_dir = parse_empty(k, v)?,
             "panic-abort-tests" => self.panic_abort_tests = parse_empty(k, v)?,
+            "post-link-hooks" => self.post_link_hooks = parse_empty(k, v)?,
             "profile-rustflags" => self.profile_rustflags = parse_empty(k, v)?,
             "publish-timeout" => self.publish_timeout = parse_empty(k, v)?,
+            "read-only-sources" => self.read_only_sources = parse_empty(k, v)?,
             "registry-auth" => self.registry_auth = parse_empty(k, v)?,
+            "resolver-precheck" => self.resolver_precheck = parse_empty(k, v)?,
+            "resolver-prefetch" => self.resolver_prefetch = parse_empty(k, v)?,
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
             "rustdoc-scrape-examples" => self.rustdoc_scrape_examples = parse_empty(k, v)?,
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
+            "shared-build-cache" => self.shared_build_cache = parse_empty(k, v)?,
+            "short-errors" => self.short_errors = parse_empty(k, v)?,
+            "shuffle-units" => {
+                self.shuffle_units = Some(match v {
+                    Some(v) => v.parse().map_err(|_| {
+                        anyhow::format_err!(
+                            "`-Zshuffle-units` expects a number as the seed, found `{}`",
+                            v
+                        )
+                    })?,
+                    None => rand::random(),
+                })
+            }
             "skip-rustdoc-fingerprint" => self.skip_rustdoc_fingerprint = parse_empty(k, v)?,
+            "source-override" => self.source_override = parse_empty(k, v)?,
             "script" => self.script = parse_empty(k, v)?,
             "target-applies-to-host" => self.target_applies_to_host = parse_empty(k, v)?,
+            "target-dir-gc" => self.target_dir_gc = parse_empty(k, v)?,
+            "term-paths" => self.term_paths = parse_empty(k, v)?,
+            "term-verbosity" => self.term_verbosity = parse_empty(k, v)?,
+            "tool-wrappers" => self.tool_wrappers = parse_empty(k, v)?,
             "unstable-options" => self.unstable_options = parse_empty(k, v)?,
             _ => bail!("unknown `-Z` flag specified: {}", k),
         }
@@ -1137,23 +1268,33 @@ impl CliUnstable {
     /// Generates an error if `-Z unstable-options` was not used for a new,
     /// unstable command-line flag.
     pub fn fail_if_stable_opt(&self, flag: &str, issue: u32) -> CargoResult<()> {
+        let see = format!(
+            "\nSee https://github.com/rust-lang/cargo/issues/{issue} for more \
+             information about the `{flag}` flag."
+        );
+        self.fail_if_stable_opt_inner(flag, &see)
+    }
+
+    /// Like [`CliUnstable::fail_if_stable_opt`], for flags which don't have a
+    /// tracking issue yet.
+    pub fn fail_if_stable_opt_untracked(&self, flag: &str) -> CargoResult<()> {
+        self.fail_if_stable_opt_inner(flag, "")
+    }
+
+    fn fail_if_stable_opt_inner(&self, flag: &str, see: &str) -> CargoResult<()> {
         if !self.unstable_options {
-            let see = format!(
-                "See https://github.com/rust-lang/cargo/issues/{issue} for more \
-                 information about the `{flag}` flag."
-            );
             // NOTE: a `config` isn't available here, check the channel directly
             let channel = channel();
             if channel == "nightly" || channel == "dev" {
                 bail!(
-                    "the `{flag}` flag is unstable, pass `-Z unstable-options` to enable it\n\
+                    "the `{flag}` flag is unstable, pass `-Z unstable-options` to enable it\
                      {see}"
                 );
             } else {
                 bail!(
                     "the `{flag}` flag is unstable, and only available on the nightly channel \
                      of Cargo, but this is the `{channel}` channel\n\
-                     {SEE_CHANNELS}\n\
+                     {SEE_CHANNELS}\
                      {see}"
                 );
             }
@@ -1169,17 +1310,36 @@ impl CliUnstable {
         command: &str,
         issue: u32,
     ) -> CargoResult<()> {
-        if self.unstable_options {
-            return Ok(());
-        }
         let see = format!(
-            "See https://github.com/rust-lang/cargo/issues/{} for more \
+            "\nSee https://github.com/rust-lang/cargo/issues/{} for more \
             information about the `cargo {}` command.",
             issue, command
         );
+        self.fail_if_stable_command_inner(config, command, &see)
+    }
+
+    /// Like [`CliUnstable::fail_if_stable_command`], for subcommands which
+    /// don't have a tracking issue yet.
+    pub fn fail_if_stable_command_untracked(
+        &self,
+        config: &Config,
+        command: &str,
+    ) -> CargoResult<()> {
+        self.fail_if_stable_command_inner(config, command, "")
+    }
+
+    fn fail_if_stable_command_inner(
+        &self,
+        config: &Config,
+        command: &str,
+        see: &str,
+    ) -> CargoResult<()> {
+        if self.unstable_options {
+            return Ok(());
+        }
         if config.nightly_features_allowed {
             bail!(
-                "the `cargo {}` command is unstable, pass `-Z unstable-options` to enable it\n\
+                "the `cargo {}` command is unstable, pass `-Z unstable-options` to enable it\
                  {}",
                 command,
                 see
@@ -1188,7 +1348,7 @@ impl CliUnstable {
             bail!(
                 "the `cargo {}` command is unstable, and only available on the \
                  nightly channel of Cargo, but this is the `{}` channel\n\
-                 {}\n\
+                 {}\
                  {}",
                 command,
                 channel(),
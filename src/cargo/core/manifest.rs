@@ -8,7 +8,7 @@ use std::sync::Arc;
 use anyhow::Context as _;
 use semver::Version;
 use serde::ser;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::core::compiler::rustdoc::RustdocScrapeExamples;
@@ -62,11 +62,47 @@ pub struct Manifest {
     im_a_teapot: Option<bool>,
     default_run: Option<String>,
     metabuild: Option<Vec<String>>,
+    declarative_build: Option<DeclarativeBuild>,
+    build_probes: Vec<BuildProbe>,
     resolve_behavior: Option<ResolveBehavior>,
     lint_rustflags: Vec<String>,
     embedded: bool,
 }
 
+/// The directives of `[package.metadata.build]`, which Cargo runs itself in
+/// place of a build script when the `metabuild` feature is enabled.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DeclarativeBuild {
+    /// System libraries to link, as found by `pkg-config`.
+    #[serde(default)]
+    pub pkg_config: Vec<String>,
+    /// C files to compile into a static library, relative to the package
+    /// root.
+    #[serde(default)]
+    pub cc: Vec<PathBuf>,
+    /// Cfgs to set, each one when the environment variable it maps to is set
+    /// to a value other than an empty string or `0`.
+    #[serde(default)]
+    pub cfg_env: BTreeMap<String, String>,
+}
+
+/// An entry of the `[build-probe]` table, which Cargo evaluates itself in
+/// place of a build script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildProbe {
+    /// The key of the entry in the table.
+    pub name: String,
+    /// The name of the library for `pkg-config`.
+    pub pkg_config: String,
+    /// The version checks of `pkg-config`, like `--atleast-version=1.2`.
+    pub version_checks: Vec<String>,
+    /// Cfgs to set when the library is found.
+    pub cfgs: Vec<String>,
+    /// Cfgs to set when the library isn't found.
+    pub fallback_cfgs: Vec<String>,
+}
+
 /// When parsing `Cargo.toml`, some warnings should silenced
 /// if the manifest comes from a dependency. `ManifestWarning`
 /// allows this delayed emission of warnings.
@@ -224,6 +260,7 @@ struct TargetInner {
     proc_macro: bool,
     edition: Edition,
     doc_scrape_examples: RustdocScrapeExamples,
+    requires_explicit: bool,
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -378,6 +415,7 @@ compact_debug! {
                 proc_macro
                 edition
                 doc_scrape_examples
+                requires_explicit
             )]
         }
     }
@@ -406,6 +444,8 @@ impl Manifest {
         default_run: Option<String>,
         original: Rc<TomlManifest>,
         metabuild: Option<Vec<String>>,
+        declarative_build: Option<DeclarativeBuild>,
+        build_probes: Vec<BuildProbe>,
         resolve_behavior: Option<ResolveBehavior>,
         lint_rustflags: Vec<String>,
         embedded: bool,
@@ -433,6 +473,8 @@ impl Manifest {
             im_a_teapot,
             default_run,
             metabuild,
+            declarative_build,
+            build_probes,
             resolve_behavior,
             lint_rustflags,
             embedded,
@@ -586,6 +628,14 @@ impl Manifest {
         self.metabuild.as_ref()
     }
 
+    pub fn declarative_build(&self) -> Option<&DeclarativeBuild> {
+        self.declarative_build.as_ref()
+    }
+
+    pub fn build_probes(&self) -> &[BuildProbe] {
+        &self.build_probes
+    }
+
     pub fn metabuild_path(&self, target_dir: Filesystem) -> PathBuf {
         let hash = short_hash(&self.package_id());
         target_dir
@@ -669,6 +719,7 @@ impl Target {
                 edition,
                 tested: true,
                 benched: true,
+                requires_explicit: false,
             }),
         }
     }
@@ -833,6 +884,11 @@ impl Target {
     pub fn doctested(&self) -> bool {
         self.inner.doctest
     }
+    /// Whether the target is only built when selected by name, and not with
+    /// the default targets or flags like `--all-targets` and `--benches`.
+    pub fn requires_explicit(&self) -> bool {
+        self.inner.requires_explicit
+    }
 
     pub fn doctestable(&self) -> bool {
         match self.kind() {
@@ -948,6 +1004,10 @@ impl Target {
         Arc::make_mut(&mut self.inner).doc_scrape_examples = doc_scrape_examples;
         self
     }
+    pub fn set_requires_explicit(&mut self, requires_explicit: bool) -> &mut Target {
+        Arc::make_mut(&mut self.inner).requires_explicit = requires_explicit;
+        self
+    }
     pub fn set_harness(&mut self, harness: bool) -> &mut Target {
         Arc::make_mut(&mut self.inner).harness = harness;
         self
@@ -7,7 +7,7 @@ pub use self::package_id::PackageId;
 pub use self::package_id_spec::PackageIdSpec;
 pub use self::registry::Registry;
 pub use self::resolver::{Resolve, ResolveVersion};
-pub use self::shell::{Shell, Verbosity};
+pub use self::shell::{OutputCategory, Shell, Verbosity};
 pub use self::source::{GitReference, QueryKind, Source, SourceId, SourceMap};
 pub use self::summary::{FeatureMap, FeatureValue, Summary};
 pub use self::workspace::{
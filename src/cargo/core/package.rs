@@ -1,6 +1,6 @@
 use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::hash;
 use std::mem;
@@ -25,7 +25,7 @@ use crate::core::source::MaybePackage;
 use crate::core::{Dependency, Manifest, PackageId, SourceId, Target};
 use crate::core::{SourceMap, Summary, Workspace};
 use crate::util::config::PackageCacheLock;
-use crate::util::errors::{CargoResult, HttpNotSuccessful};
+use crate::util::errors::{CargoResult, HttpNotSuccessful, OfflineError};
 use crate::util::interning::InternedString;
 use crate::util::network::http::http_handle_and_timeout;
 use crate::util::network::http::HttpTimeout;
@@ -309,6 +309,9 @@ pub struct PackageSet<'cfg> {
     downloading: Cell<bool>,
     /// Whether or not to use curl HTTP/2 multiplexing.
     multiplexing: bool,
+    /// The maximum number of transfers at the same time, from
+    /// `http.max-concurrent-downloads`.
+    max_concurrent_downloads: Option<usize>,
 }
 
 /// Helper for downloading crates.
@@ -323,6 +326,9 @@ pub struct Downloads<'a, 'cfg> {
     pending_ids: HashSet<PackageId>,
     /// Downloads that have failed and are waiting to retry again later.
     sleeping: SleepTracker<(Download<'cfg>, Easy)>,
+    /// Downloads waiting for another one to finish, as there are already
+    /// `http.max-concurrent-downloads` of them in `pending`.
+    queued: VecDeque<(Download<'cfg>, Easy)>,
     /// The final result of each download. A pair `(token, result)`. This is a
     /// temporary holding area, needed because curl can report multiple
     /// downloads at once, but the main loop (`wait`) is written to only
@@ -411,7 +417,8 @@ impl<'cfg> PackageSet<'cfg> {
         // We've enabled the `http2` feature of `curl` in Cargo, so treat
         // failures here as fatal as it would indicate a build-time problem.
         let mut multi = Multi::new();
-        let multiplexing = config.http_config()?.multiplexing.unwrap_or(true);
+        let http = config.http_config()?;
+        let multiplexing = http.multiplexing.unwrap_or(true);
         multi
             .pipelining(false, multiplexing)
             .with_context(|| "failed to enable multiplexing/pipelining in curl")?;
@@ -419,6 +426,14 @@ impl<'cfg> PackageSet<'cfg> {
         // let's not flood crates.io with connections
         multi.set_max_host_connections(2)?;
 
+        let max_concurrent_downloads = match http.max_concurrent_downloads {
+            Some(0) if config.cli_unstable().max_concurrent_downloads => {
+                anyhow::bail!("`http.max-concurrent-downloads` must be at least 1")
+            }
+            Some(max) if config.cli_unstable().max_concurrent_downloads => Some(max as usize),
+            _ => None,
+        };
+
         Ok(PackageSet {
             packages: package_ids
                 .iter()
@@ -429,6 +444,7 @@ impl<'cfg> PackageSet<'cfg> {
             multi,
             downloading: Cell::new(false),
             multiplexing,
+            max_concurrent_downloads,
         })
     }
 
@@ -450,6 +466,7 @@ impl<'cfg> PackageSet<'cfg> {
             pending: HashMap::new(),
             pending_ids: HashSet::new(),
             sleeping: SleepTracker::new(),
+            queued: VecDeque::new(),
             results: Vec::new(),
             progress: RefCell::new(Some(Progress::with_style(
                 "Downloading",
@@ -548,7 +565,18 @@ impl<'cfg> PackageSet<'cfg> {
                 force_all_targets,
             )?;
         }
-        self.get_many(to_download.into_iter())?;
+        self.get_many(to_download.into_iter()).map_err(|mut e| {
+            if let Some(offline) = e.downcast_mut::<OfflineError>() {
+                let mut fetch = "cargo fetch".to_string();
+                for kind in requested_kinds {
+                    if let CompileKind::Target(target) = kind {
+                        fetch.push_str(&format!(" --target {}", target.rustc_target()));
+                    }
+                }
+                offline.fetch_command = Some(fetch);
+            }
+            e
+        })?;
         Ok(())
     }
 
@@ -624,10 +652,11 @@ impl<'cfg> PackageSet<'cfg> {
                         return false;
                     }
                     if force_all_targets == ForceAllTargets::No {
-                        let activated = requested_kinds
-                            .iter()
-                            .chain(Some(&CompileKind::Host))
-                            .any(|kind| target_data.dep_platform_activated(dep, *kind));
+                        // Normal and dev dependencies are also built for the
+                        // host when used by build scripts or proc-macros.
+                        let activated = target_data
+                            .dep_platform_activated_for(dep, requested_kinds)
+                            || target_data.dep_platform_activated(dep, CompileKind::Host);
                         if !activated {
                             return false;
                         }
@@ -712,8 +741,10 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
         let token = self.next;
         self.next += 1;
         debug!("downloading {} as {}", id, token);
-        assert!(self.pending_ids.insert(id));
 
+        if self.set.config.offline() {
+            return Err(OfflineError::new(format!("`{}`", id), url).into());
+        }
         let (mut handle, _timeout) = http_handle_and_timeout(self.set.config)?;
         handle.get(true)?;
         handle.url(&url)?;
@@ -796,7 +827,7 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
 
     /// Returns the number of crates that are still downloading.
     pub fn remaining(&self) -> usize {
-        self.pending.len() + self.sleeping.len()
+        self.pending.len() + self.queued.len() + self.sleeping.len()
     }
 
     /// Blocks the current thread waiting for a package to finish downloading.
@@ -927,7 +958,15 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
         Ok(slot.borrow().unwrap())
     }
 
+    /// Hands the download over to curl, or queues it if there are already
+    /// `http.max-concurrent-downloads` transfers.
     fn enqueue(&mut self, dl: Download<'cfg>, handle: Easy) -> CargoResult<()> {
+        if self.is_full() {
+            debug!("queueing {} until another download finishes", dl.id);
+            self.queued.push_back((dl, handle));
+            return Ok(());
+        }
+        assert!(self.pending_ids.insert(dl.id));
         let mut handle = self.set.multi.add(handle)?;
         let now = Instant::now();
         handle.set_token(dl.token)?;
@@ -962,6 +1001,7 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
         // `wait` method on `multi`.
         loop {
             self.add_sleepers()?;
+            self.start_queued()?;
             let n = tls::set(self, || {
                 self.set
                     .multi
@@ -1003,12 +1043,29 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
 
     fn add_sleepers(&mut self) -> CargoResult<()> {
         for (dl, handle) in self.sleeping.to_retry() {
-            self.pending_ids.insert(dl.id);
             self.enqueue(dl, handle)?;
         }
         Ok(())
     }
 
+    /// Starts the queued downloads, as long as there is room for them.
+    fn start_queued(&mut self) -> CargoResult<()> {
+        while !self.is_full() {
+            let Some((dl, handle)) = self.queued.pop_front() else {
+                break;
+            };
+            self.enqueue(dl, handle)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `http.max-concurrent-downloads` transfers are running.
+    fn is_full(&self) -> bool {
+        self.set
+            .max_concurrent_downloads
+            .map_or(false, |max| self.pending.len() >= max)
+    }
+
     fn progress(&self, token: usize, total: u64, cur: u64) -> bool {
         let dl = &self.pending[&token].0;
         dl.total.set(total);
@@ -1099,6 +1156,19 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
                 if remaining > 0 && dur > Duration::from_millis(500) {
                     msg.push_str(&format!(", remaining bytes: {}", ByteSize(remaining)));
                 }
+                // The rate over all the downloads, including the finished
+                // ones, as they run at the same time.
+                let received = self.downloaded_bytes
+                    + self
+                        .pending
+                        .values()
+                        .map(|(dl, _)| dl.current.get())
+                        .sum::<u64>();
+                let elapsed = self.start.elapsed();
+                if received > 0 && elapsed > Duration::from_millis(500) {
+                    let rate = (received as f64 / elapsed.as_secs_f64()) as u64;
+                    msg.push_str(&format!(", {}/s", ByteSize(rate)));
+                }
             }
         }
         progress.print_now(&msg)
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
 use anyhow::{bail, Context as _};
 use semver::Version;
@@ -104,6 +105,30 @@ impl PackageIdSpec {
         }
     }
 
+    /// Returns the shortest specification matching `package_id`, and none of
+    /// the other packages in `ids`.
+    ///
+    /// This is the name alone if no other package has the same name, the
+    /// name and the version if no other package also has the same version,
+    /// and the fully qualified specification otherwise.
+    pub fn minimal<I>(package_id: PackageId, ids: I) -> PackageIdSpec
+    where
+        I: IntoIterator<Item = PackageId>,
+    {
+        let others: Vec<_> = ids
+            .into_iter()
+            .filter(|&id| id != package_id && id.name() == package_id.name())
+            .collect();
+        let mut spec = PackageIdSpec::from_package_id(package_id);
+        if others.is_empty() {
+            spec.version = None;
+        }
+        if others.iter().all(|id| id.version() != package_id.version()) {
+            spec.url = None;
+        }
+        spec
+    }
+
     /// Tries to convert a valid `Url` to a `PackageIdSpec`.
     fn from_url(mut url: Url) -> CargoResult<PackageIdSpec> {
         if url.query().is_some() {
@@ -302,6 +327,14 @@ impl fmt::Display for PackageIdSpec {
     }
 }
 
+impl FromStr for PackageIdSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> CargoResult<PackageIdSpec> {
+        PackageIdSpec::parse(spec)
+    }
+}
+
 impl ser::Serialize for PackageIdSpec {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
@@ -429,4 +462,28 @@ mod tests {
         assert!(PackageIdSpec::parse("foo@1.2.3").unwrap().matches(foo));
         assert!(!PackageIdSpec::parse("foo@1.2.2").unwrap().matches(foo));
     }
+
+    #[test]
+    fn minimal() {
+        let url = Url::parse("https://example.com").unwrap();
+        let sid = SourceId::for_registry(&url).unwrap();
+        let other_url = Url::parse("https://example.org").unwrap();
+        let other_sid = SourceId::for_registry(&other_url).unwrap();
+        let foo = PackageId::new("foo", "1.2.3", sid).unwrap();
+        let foo_old = PackageId::new("foo", "1.0.0", sid).unwrap();
+        let foo_other = PackageId::new("foo", "1.2.3", other_sid).unwrap();
+        let bar = PackageId::new("bar", "1.2.3", sid).unwrap();
+
+        let minimal = |ids: &[PackageId]| PackageIdSpec::minimal(foo, ids.iter().copied());
+        assert_eq!(minimal(&[foo, bar]).to_string(), "foo");
+        assert_eq!(minimal(&[foo, foo_old, bar]).to_string(), "foo@1.2.3");
+        assert_eq!(
+            minimal(&[foo, foo_old, foo_other]).to_string(),
+            "https://example.com/#foo@1.2.3"
+        );
+        assert_eq!(
+            "foo@1.2.3".parse::<PackageIdSpec>().unwrap(),
+            minimal(&[foo, foo_old])
+        );
+    }
 }
@@ -555,6 +555,9 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(flags) = &toml.rustflags {
         profile.rustflags = flags.clone();
     }
+    if let Some(target_dir) = &toml.target_dir {
+        profile.target_dir = Some(InternedString::new(target_dir));
+    }
     profile.strip = match toml.strip {
         Some(StringOrBool::Bool(true)) => Strip::Named(InternedString::new("symbols")),
         None | Some(StringOrBool::Bool(false)) => Strip::None,
@@ -598,6 +601,9 @@ pub struct Profile {
     #[serde(skip_serializing_if = "Vec::is_empty")] // remove when `rustflags` is stablized
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Vec<InternedString>,
+    // Note that `target_dir` is used for the cargo-feature `package_target_dir`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<InternedString>,
 }
 
 impl Default for Profile {
@@ -618,6 +624,7 @@ impl Default for Profile {
             panic: PanicStrategy::Unwind,
             strip: Strip::None,
             rustflags: vec![],
+            target_dir: None,
         }
     }
 }
@@ -646,6 +653,7 @@ compact_debug! {
                 panic
                 strip
                 rustflags
+                target_dir
             )]
         }
     }
@@ -712,6 +720,7 @@ impl Profile {
             self.rpath,
             (self.incremental, self.panic, self.strip),
             &self.rustflags,
+            self.target_dir,
         )
     }
 }
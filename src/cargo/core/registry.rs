@@ -4,6 +4,7 @@ use std::task::{ready, Poll};
 use crate::core::PackageSet;
 use crate::core::{Dependency, PackageId, QueryKind, Source, SourceId, SourceMap, Summary};
 use crate::sources::config::SourceConfigMap;
+use crate::sources::{ProcessSourceOverride, SourceOverride};
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
 use crate::util::{CanonicalUrl, Config};
@@ -80,6 +81,12 @@ pub struct PackageRegistry<'cfg> {
     patches: HashMap<CanonicalUrl, Vec<Summary>>,
     patches_locked: bool,
     patches_available: HashMap<CanonicalUrl, Vec<PackageId>>,
+
+    // Consulted before querying the source of a dependency, see
+    // `set_source_override`. Its answers are cached in `redirects`, keyed by
+    // the name, source and version requirement of the dependency.
+    source_override: Option<Box<dyn SourceOverride + 'cfg>>,
+    redirects: HashMap<(InternedString, SourceId, String), Option<SourceId>>,
 }
 
 /// A map of all "locked packages" which is filled in when parsing a lock file
@@ -127,6 +134,8 @@ pub struct LockedPatchDependency {
 impl<'cfg> PackageRegistry<'cfg> {
     pub fn new(config: &'cfg Config) -> CargoResult<PackageRegistry<'cfg>> {
         let source_config = SourceConfigMap::new(config)?;
+        let source_override = ProcessSourceOverride::from_config(config)?
+            .map(|o| Box::new(o) as Box<dyn SourceOverride + 'cfg>);
         Ok(PackageRegistry {
             config,
             sources: SourceMap::new(),
@@ -138,9 +147,20 @@ impl<'cfg> PackageRegistry<'cfg> {
             patches: HashMap::new(),
             patches_locked: false,
             patches_available: HashMap::new(),
+            source_override,
+            redirects: HashMap::new(),
         })
     }
 
+    /// Sets the [`SourceOverride`] consulted before querying the source of
+    /// a dependency, replacing the one from `source-override.process`.
+    ///
+    /// Dependencies on path sources are never redirected.
+    pub fn set_source_override(&mut self, source_override: Box<dyn SourceOverride + 'cfg>) {
+        self.source_override = Some(source_override);
+        self.redirects.clear();
+    }
+
     pub fn get(self, package_ids: &[PackageId]) -> CargoResult<PackageSet<'cfg>> {
         trace!("getting packages; sources={}", self.sources.len());
         PackageSet::new(package_ids, self.sources, self.config)
@@ -490,6 +510,77 @@ impl<'cfg> PackageRegistry<'cfg> {
         Poll::Ready(Ok(None))
     }
 
+    /// Asks the [`SourceOverride`] whether `dep` should be loaded from a
+    /// local directory instead, returning the path source for it.
+    fn query_redirect(&mut self, dep: &Dependency) -> CargoResult<Option<SourceId>> {
+        let Some(source_override) = &mut self.source_override else {
+            return Ok(None);
+        };
+        if dep.source_id().is_path() {
+            return Ok(None);
+        }
+        let key = (
+            dep.package_name(),
+            dep.source_id(),
+            dep.version_req().to_string(),
+        );
+        if let Some(redirect) = self.redirects.get(&key) {
+            return Ok(*redirect);
+        }
+        let redirect = match source_override.redirect(dep)? {
+            Some(path) => {
+                if !path.join("Cargo.toml").is_file() {
+                    bail!(
+                        "dependency `{}` was redirected to `{}` by the source override, \
+                         but there is no `Cargo.toml` in that directory",
+                        dep.package_name(),
+                        path.display()
+                    );
+                }
+                debug!("redirecting {} to {}", dep.package_name(), path.display());
+                Some(SourceId::for_path(&path)?)
+            }
+            None => None,
+        };
+        self.redirects.insert(key, redirect);
+        Ok(redirect)
+    }
+
+    /// Queries the directory `dep` was redirected to by the
+    /// [`SourceOverride`], checking that it contains a matching package.
+    fn query_redirected(
+        &mut self,
+        dep: &Dependency,
+        path_id: SourceId,
+        f: &mut dyn FnMut(Summary),
+    ) -> Poll<CargoResult<()>> {
+        self.ensure_loaded(path_id, Kind::Locked)?;
+        let source = self.sources.get_mut(path_id).unwrap();
+        let any_version = Dependency::new_override(dep.package_name(), path_id);
+        let mut results = ready!(source.query_vec(&any_version, QueryKind::Exact))?;
+        let Some(summary) = results.pop() else {
+            return Poll::Ready(Err(anyhow::anyhow!(
+                "dependency `{}` was redirected to {} by the source override, \
+                 but the package there is not named `{}`",
+                dep.package_name(),
+                path_id,
+                dep.package_name()
+            )));
+        };
+        if !dep.version_req().matches(summary.version()) {
+            return Poll::Ready(Err(anyhow::anyhow!(
+                "dependency `{}` was redirected to {} by the source override, \
+                 but its version {} does not match the requirement `{}`",
+                dep.package_name(),
+                path_id,
+                summary.version(),
+                dep.version_req()
+            )));
+        }
+        f(self.lock(summary));
+        Poll::Ready(Ok(()))
+    }
+
     /// This function is used to transform a summary to another locked summary
     /// if possible. This is where the concept of a lock file comes into play.
     ///
@@ -575,6 +666,11 @@ impl<'cfg> Registry for PackageRegistry<'cfg> {
         f: &mut dyn FnMut(Summary),
     ) -> Poll<CargoResult<()>> {
         assert!(self.patches_locked);
+        // A redirect from the source override takes precedence over
+        // everything else, the original source isn't even loaded.
+        if let Some(path_id) = self.query_redirect(dep)? {
+            return self.query_redirected(dep, path_id, f);
+        }
         let (override_summary, n, to_warn) = {
             // Look for an override and get ready to query the real source.
             let override_summary = ready!(self.query_overrides(dep))?;
@@ -28,10 +28,14 @@ use std::rc::Rc;
 use std::task::Poll;
 use tracing::debug;
 
+/// The number of speculative queries issued between two calls to
+/// [`RegistryQueryer::reset_pending`], see [`RegistryQueryer::prefetch`].
+const PREFETCH_LIMIT: usize = 64;
+
 pub struct RegistryQueryer<'a> {
     pub registry: &'a mut (dyn Registry + 'a),
     replacements: &'a [(PackageIdSpec, Dependency)],
-    version_prefs: &'a VersionPreferences,
+    pub version_prefs: &'a VersionPreferences,
     /// If set the list of dependency candidates will be sorted by minimal
     /// versions first. That allows `cargo update -Z minimal-versions` which will
     /// specify minimum dependency versions to be used.
@@ -49,6 +53,13 @@ pub struct RegistryQueryer<'a> {
     >,
     /// all the cases we ended up using a supplied replacement
     used_replacements: HashMap<PackageId, Summary>,
+    /// If set the dependencies of the candidates found by `build_deps` are
+    /// queried ahead of time, see `prefetch`.
+    prefetch: bool,
+    /// Results of speculative queries which were ready, waiting to be used by `query`.
+    prefetched: HashMap<Dependency, Vec<Summary>>,
+    /// Speculative queries issued since the last `reset_pending`.
+    prefetch_issued: HashSet<Dependency>,
 }
 
 impl<'a> RegistryQueryer<'a> {
@@ -57,6 +68,7 @@ impl<'a> RegistryQueryer<'a> {
         replacements: &'a [(PackageIdSpec, Dependency)],
         version_prefs: &'a VersionPreferences,
         minimal_versions: bool,
+        prefetch: bool,
     ) -> Self {
         RegistryQueryer {
             registry,
@@ -66,6 +78,9 @@ impl<'a> RegistryQueryer<'a> {
             registry_cache: HashMap::new(),
             summary_cache: HashMap::new(),
             used_replacements: HashMap::new(),
+            prefetch,
+            prefetched: HashMap::new(),
+            prefetch_issued: HashSet::new(),
         }
     }
 
@@ -83,6 +98,7 @@ impl<'a> RegistryQueryer<'a> {
             }
             *r
         });
+        self.prefetch_issued.clear();
         all_ready
     }
 
@@ -110,15 +126,21 @@ impl<'a> RegistryQueryer<'a> {
             return out.map(Result::Ok);
         }
 
-        let mut ret = Vec::new();
-        let ready = self.registry.query(dep, QueryKind::Exact, &mut |s| {
-            ret.push(s);
-        })?;
-        if ready.is_pending() {
-            self.registry_cache
-                .insert((dep.clone(), first_minimal_version), Poll::Pending);
-            return Poll::Pending;
-        }
+        let mut ret = match self.prefetched.remove(dep) {
+            Some(ret) => ret,
+            None => {
+                let mut ret = Vec::new();
+                let ready = self.registry.query(dep, QueryKind::Exact, &mut |s| {
+                    ret.push(s);
+                })?;
+                if ready.is_pending() {
+                    self.registry_cache
+                        .insert((dep.clone(), first_minimal_version), Poll::Pending);
+                    return Poll::Pending;
+                }
+                ret
+            }
+        };
         for summary in ret.iter() {
             let mut potential_matches = self
                 .replacements
@@ -201,6 +223,12 @@ impl<'a> RegistryQueryer<'a> {
             }
         }
 
+        // Versions requiring a newer rustc are skipped with `resolver.msrv-policy`, so that an
+        // older version is selected instead. A version locked in `Cargo.lock` stays locked.
+        if !dep.version_req().is_locked() {
+            ret.retain(|s| self.version_prefs.is_rust_version_compatible(s));
+        }
+
         // When we attempt versions for a package we'll want to do so in a sorted fashion to pick
         // the "best candidates" first. VersionPreferences implements this notion.
         let ordering = if first_minimal_version || self.minimal_versions {
@@ -262,7 +290,7 @@ impl<'a> RegistryQueryer<'a> {
                         format!(
                             "failed to get `{}` as a dependency of {}",
                             dep.package_name(),
-                            describe_path_in_context(cx, &candidate.package_id()),
+                            describe_path_in_context(cx, &candidate.package_id(), None),
                         )
                     })),
                 },
@@ -275,6 +303,10 @@ impl<'a> RegistryQueryer<'a> {
         // work before we figure that out.
         deps.sort_by_key(|&(_, ref a, _)| a.len());
 
+        if self.prefetch {
+            self.prefetch(&deps);
+        }
+
         let out = Rc::new((used_features, Rc::new(deps)));
 
         // If we succeed we add the result to the cache so we can use it again next time.
@@ -286,6 +318,45 @@ impl<'a> RegistryQueryer<'a> {
 
         Ok(out)
     }
+
+    /// Speculatively queries the registry for the dependencies of the most
+    /// preferred candidate of each of `deps`, which are likely to be the next
+    /// ones the resolver asks for.
+    ///
+    /// Queries which aren't ready are left to the registry, so that the next
+    /// `block_until_ready` loads them together with the ones the resolver is
+    /// waiting on, instead of in a later round. Results which are ready are
+    /// kept for `query`. Errors are ignored here, they are reported if the
+    /// dependency is actually queried.
+    ///
+    /// Optional dependencies are skipped as their features aren't known yet,
+    /// and at most `PREFETCH_LIMIT` queries are issued per round.
+    fn prefetch(&mut self, deps: &[DepInfo]) {
+        let next = deps
+            .iter()
+            .filter_map(|(_, candidates, _)| candidates.first())
+            .flat_map(|candidate| candidate.dependencies())
+            .filter(|dep| dep.is_transitive() && !dep.is_optional());
+        for dep in next {
+            if self.prefetch_issued.len() >= PREFETCH_LIMIT {
+                break;
+            }
+            if self.prefetched.contains_key(dep)
+                || self.registry_cache.contains_key(&(dep.clone(), false))
+                || self.registry_cache.contains_key(&(dep.clone(), true))
+                || !self.prefetch_issued.insert(dep.clone())
+            {
+                continue;
+            }
+            debug!("prefetching `{}` {}", dep.package_name(), dep.version_req());
+            let mut ret = Vec::new();
+            if let Poll::Ready(Ok(())) = self.registry.query(dep, QueryKind::Exact, &mut |s| {
+                ret.push(s);
+            }) {
+                self.prefetched.insert(dep.clone(), ret);
+            }
+        }
+    }
 }
 
 /// Returns the features we ended up using and
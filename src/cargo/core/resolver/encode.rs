@@ -157,6 +157,7 @@ impl EncodableResolve {
         let unstable_lockfile_version_allowed = ws.config().cli_unstable().next_lockfile_bump;
         let path_deps = build_path_deps(ws)?;
         let mut checksums = HashMap::new();
+        let mut content_hashes = HashMap::new();
 
         let mut version = match self.version {
             Some(4) if ws.config().nightly_features_allowed => {
@@ -219,6 +220,9 @@ impl EncodableResolve {
                     version = version.max(ResolveVersion::V2);
                     checksums.insert(id, Some(cksum.clone()));
                 }
+                if let Some(hash) = &pkg.content_hash {
+                    content_hashes.insert(id, hash.clone());
+                }
 
                 assert!(live_pkgs.insert(enc_id, (id, pkg)).is_none())
             }
@@ -397,7 +401,7 @@ impl EncodableResolve {
             version = ResolveVersion::V2;
         }
 
-        Ok(Resolve::new(
+        let mut resolve = Resolve::new(
             g,
             replacements,
             HashMap::new(),
@@ -406,7 +410,11 @@ impl EncodableResolve {
             unused_patches,
             version,
             HashMap::new(),
-        ))
+        );
+        for (id, hash) in content_hashes {
+            resolve.set_content_hash(id, hash);
+        }
+        Ok(resolve)
     }
 }
 
@@ -490,6 +498,10 @@ pub struct EncodableDependency {
     version: String,
     source: Option<EncodableSourceId>,
     checksum: Option<String>,
+    /// The hash of the contents of a git or path package, with
+    /// `-Zcontent-hashes`.
+    #[serde(rename = "content-hash")]
+    content_hash: Option<String>,
     dependencies: Option<Vec<EncodablePackageId>>,
     replace: Option<EncodablePackageId>,
 }
@@ -690,6 +702,7 @@ impl ser::Serialize for Resolve {
                     } else {
                         None
                     },
+                    content_hash: None,
                 })
                 .collect(),
         };
@@ -763,6 +776,7 @@ fn encodable_resolve_node(
         } else {
             None
         },
+        content_hash: resolve.content_hashes().get(&id).cloned(),
     }
 }
 
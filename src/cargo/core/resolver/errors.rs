@@ -3,11 +3,12 @@ use std::task::Poll;
 
 use crate::core::{Dependency, PackageId, QueryKind, Registry, Summary};
 use crate::util::edit_distance::edit_distance;
-use crate::util::{Config, VersionExt};
+use crate::util::{CargoResult, Config, VersionExt};
 use anyhow::Error;
 
 use super::context::Context;
 use super::types::{ConflictMap, ConflictReason};
+use super::VersionPreferences;
 
 /// Error during resolution providing a path of `PackageId`s.
 pub struct ResolveError {
@@ -75,6 +76,7 @@ pub(super) fn activation_error(
     dep: &Dependency,
     conflicting_activations: &ConflictMap,
     candidates: &[Summary],
+    version_prefs: &VersionPreferences,
     config: Option<&Config>,
 ) -> ResolveError {
     let to_resolve_err = |err| {
@@ -89,10 +91,32 @@ pub(super) fn activation_error(
         )
     };
 
+    // The versions skipped by `resolver.msrv-policy` never made it to the
+    // candidates, so they are looked up again to explain why.
+    let skipped = match rust_version_skipped(registry, dep, version_prefs) {
+        Ok(skipped) => skipped,
+        Err(e) => return to_resolve_err(e),
+    };
+    let describe_skipped = |msg: &mut String| {
+        if skipped.is_empty() {
+            return;
+        }
+        msg.push_str(
+            "\n\nversions skipped because they require a newer rustc than the active one, \
+             as `resolver.msrv-policy` is `compatible`:",
+        );
+        for summary in &skipped {
+            msg.push_str("\n\n  `");
+            msg.push_str(&summary.package_id().to_string());
+            msg.push_str("` requires rustc ");
+            msg.push_str(summary.rust_version().as_deref().unwrap_or_default());
+        }
+    };
+
     if !candidates.is_empty() {
         let mut msg = format!("failed to select a version for `{}`.", dep.package_name());
         msg.push_str("\n    ... required by ");
-        msg.push_str(&describe_path_in_context(cx, &parent.package_id()));
+        msg.push_str(&describe_path_in_context(cx, &parent.package_id(), config));
 
         msg.push_str("\nversions that meet the requirements `");
         msg.push_str(&dep.version_req().to_string());
@@ -136,7 +160,7 @@ pub(super) fn activation_error(
                     msg.push_str("`, but it conflicts with a previous package which links to `");
                     msg.push_str(link);
                     msg.push_str("` as well:\n");
-                    msg.push_str(&describe_path_in_context(cx, p));
+                    msg.push_str(&describe_path_in_context(cx, p, config));
                     msg.push_str("\nOnly one package in the dependency graph may specify the same links value. This helps ensure that only one copy of a native library is linked in the final binary. ");
                     msg.push_str("Try to adjust your dependencies so that only one package uses the links ='");
                     msg.push_str(&*dep.package_name());
@@ -205,11 +229,13 @@ pub(super) fn activation_error(
             for (p, r) in &conflicting_activations {
                 if let ConflictReason::Semver = r {
                     msg.push_str("\n\n  previously selected ");
-                    msg.push_str(&describe_path_in_context(cx, p));
+                    msg.push_str(&describe_path_in_context(cx, p, config));
                 }
             }
         }
 
+        describe_skipped(&mut msg);
+
         msg.push_str("\n\nfailed to select a version for `");
         msg.push_str(&*dep.package_name());
         msg.push_str("` which could resolve this conflict");
@@ -270,10 +296,14 @@ pub(super) fn activation_error(
                 dep.version_req(),
                 locked_version,
                 versions,
-                registry.describe_source(dep.source_id()),
+                match (config, dep.source_id().local_path()) {
+                    (Some(config), Some(path)) => config.display_path(&path, config.cwd()),
+                    _ => registry.describe_source(dep.source_id()),
+                },
             );
             msg.push_str("required by ");
-            msg.push_str(&describe_path_in_context(cx, &parent.package_id()));
+            msg.push_str(&describe_path_in_context(cx, &parent.package_id(), config));
+            describe_skipped(&mut msg);
 
             // If we have a path dependency with a locked version, then this may
             // indicate that we updated a sub-package and forgot to run `cargo
@@ -355,9 +385,13 @@ pub(super) fn activation_error(
                 }
                 msg.push('\n');
             }
-            msg.push_str(&format!("location searched: {}\n", dep.source_id()));
+            let location = match (config, dep.source_id().local_path()) {
+                (Some(config), Some(path)) => config.display_path(&path, config.cwd()),
+                _ => dep.source_id().to_string(),
+            };
+            msg.push_str(&format!("location searched: {}\n", location));
             msg.push_str("required by ");
-            msg.push_str(&describe_path_in_context(cx, &parent.package_id()));
+            msg.push_str(&describe_path_in_context(cx, &parent.package_id(), config));
 
             msg
         };
@@ -376,15 +410,40 @@ pub(super) fn activation_error(
     to_resolve_err(anyhow::format_err!("{}", msg))
 }
 
+/// Returns the versions matching `dep` which `version_prefs` skips because
+/// they require a newer rustc, newest first.
+fn rust_version_skipped(
+    registry: &mut dyn Registry,
+    dep: &Dependency,
+    version_prefs: &VersionPreferences,
+) -> CargoResult<Vec<Summary>> {
+    if dep.version_req().is_locked() {
+        return Ok(Vec::new());
+    }
+    let mut skipped = loop {
+        match registry.query_vec(dep, QueryKind::Exact) {
+            Poll::Ready(candidates) => break candidates?,
+            Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    skipped.retain(|s| !version_prefs.is_rust_version_compatible(s));
+    skipped.sort_unstable_by(|a, b| b.version().cmp(a.version()));
+    Ok(skipped)
+}
+
 /// Returns String representation of dependency chain for a particular `pkgid`
 /// within given context.
-pub(super) fn describe_path_in_context(cx: &Context, id: &PackageId) -> String {
+pub(super) fn describe_path_in_context(
+    cx: &Context,
+    id: &PackageId,
+    config: Option<&Config>,
+) -> String {
     let iter = cx
         .parents
         .path_to_bottom(id)
         .into_iter()
         .map(|(p, d)| (p, d.and_then(|d| d.iter().next())));
-    describe_path(iter)
+    describe_path(iter, config)
 }
 
 /// Returns String representation of dependency chain for a particular `pkgid`.
@@ -396,13 +455,21 @@ pub(super) fn describe_path_in_context(cx: &Context, id: &PackageId) -> String {
 /// -> (pkg1, dep from pkg1 satisfied by pkg0)
 /// -> (pkg2, dep from pkg2 satisfied by pkg1)
 /// -> ...
+///
+/// With a `config`, the paths of path packages are rendered as with
+/// [`Config::display_path`].
 pub(crate) fn describe_path<'a>(
     mut path: impl Iterator<Item = (&'a PackageId, Option<&'a Dependency>)>,
+    config: Option<&Config>,
 ) -> String {
     use std::fmt::Write;
 
+    let display_id = |id: &PackageId| match config {
+        Some(config) => config.display_package_id(*id, config.cwd()),
+        None => id.to_string(),
+    };
     if let Some(p) = path.next() {
-        let mut dep_path_desc = format!("package `{}`", p.0);
+        let mut dep_path_desc = format!("package `{}`", display_id(p.0));
         for (pkg, dep) in path {
             let dep = dep.unwrap();
             let source_kind = if dep.source_id().is_path() {
@@ -426,7 +493,10 @@ pub(crate) fn describe_path<'a>(
             write!(
                 dep_path_desc,
                 "\n    ... which satisfies {}dependency `{}` {}of package `{}`",
-                source_kind, requirement, locked_version, pkg
+                source_kind,
+                requirement,
+                locked_version,
+                display_id(pkg)
             )
             .unwrap();
         }
@@ -58,13 +58,15 @@
 //! that we're implementing something that probably shouldn't be allocating all
 //! over the place.
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::mem;
 use std::rc::Rc;
+use std::task::Poll;
 use std::time::{Duration, Instant};
 
 use tracing::{debug, trace};
 
+use crate::core::dependency::DepKind;
 use crate::core::PackageIdSpec;
 use crate::core::{Dependency, PackageId, Registry, Summary};
 use crate::util::config::Config;
@@ -72,7 +74,7 @@ use crate::util::errors::CargoResult;
 use crate::util::network::PollExt;
 use crate::util::profile;
 
-use self::context::Context;
+use self::context::{ActivationsKey, Context};
 use self::dep_cache::RegistryQueryer;
 use self::features::RequestedFeatures;
 use self::types::{ConflictMap, ConflictReason, DepsFrame};
@@ -148,9 +150,25 @@ pub fn resolve(
         Some(config) => config.cli_unstable().direct_minimal_versions,
         None => false,
     };
-    let mut registry =
-        RegistryQueryer::new(registry, replacements, version_prefs, minimal_versions);
+    let precheck = match config {
+        Some(config) => config.cli_unstable().resolver_precheck,
+        None => false,
+    };
+    let prefetch = match config {
+        Some(config) => config.cli_unstable().resolver_prefetch,
+        None => false,
+    };
+    let mut registry = RegistryQueryer::new(
+        registry,
+        replacements,
+        version_prefs,
+        minimal_versions,
+        prefetch,
+    );
     let cx = loop {
+        if precheck {
+            check_root_requirements(&mut registry, summaries, direct_minimal_versions)?;
+        }
         let cx = Context::new(check_public_visible_dependencies);
         let cx = activate_deps_loop(
             cx,
@@ -376,6 +394,7 @@ fn activate_deps_loop(
                             &dep,
                             &conflicting_activations,
                             &candidates,
+                            registry.version_prefs,
                             config,
                         ))
                     }
@@ -1060,6 +1079,93 @@ fn find_candidate(
     None
 }
 
+/// Looks for requirements reachable from the root summaries which can never be
+/// satisfied together, before any activation is attempted.
+///
+/// Only one semver-compatible version of a package may be activated, so two
+/// requirements whose candidates all fall within the same semver-compatible
+/// family, but which have no candidate in common, are a conflict no amount of
+/// backtracking can fix. The non-optional dependencies of the roots are
+/// checked, and so are the ones of every package which is the only candidate
+/// of such a dependency, since it gets activated whatever else is chosen.
+/// Anything else depends on which versions get activated. Queries that are
+/// still pending are skipped here and checked on the next pass through
+/// `resolve`.
+fn check_root_requirements(
+    registry: &mut RegistryQueryer<'_>,
+    summaries: &[(Summary, ResolveOpts)],
+    direct_minimal_versions: bool,
+) -> CargoResult<()> {
+    let mut by_key: BTreeMap<ActivationsKey, Vec<_>> = BTreeMap::new();
+    let mut visited: HashSet<PackageId> = summaries.iter().map(|(s, _)| s.package_id()).collect();
+    // The summaries to check, with the options of the roots. Only the
+    // dev-dependencies of roots are ever activated.
+    let mut queue: VecDeque<(Summary, Option<&ResolveOpts>)> = summaries
+        .iter()
+        .map(|(summary, opts)| (summary.clone(), Some(opts)))
+        .collect();
+    while let Some((summary, opts)) = queue.pop_front() {
+        let dev_deps = opts.map_or(false, |opts| opts.dev_deps);
+        let first_minimal_version = opts.is_some() && direct_minimal_versions;
+        for dep in summary.dependencies() {
+            if dep.is_optional() || (!dev_deps && dep.kind() == DepKind::Development) {
+                continue;
+            }
+            let candidates = match registry.query(dep, first_minimal_version) {
+                Poll::Ready(Ok(candidates)) => candidates,
+                // Errors are reported with more context by the main loop.
+                Poll::Ready(Err(_)) | Poll::Pending => continue,
+            };
+            let mut keys = candidates
+                .iter()
+                .map(|s| s.package_id().as_activations_key());
+            let Some(key) = keys.next() else {
+                continue;
+            };
+            if keys.any(|k| k != key) {
+                continue;
+            }
+            if let [only] = candidates.as_slice() {
+                if visited.insert(only.package_id()) {
+                    queue.push_back((only.clone(), None));
+                }
+            }
+            let ids: HashSet<_> = candidates.iter().map(|s| s.package_id()).collect();
+            by_key
+                .entry(key)
+                .or_default()
+                .push((summary.clone(), dep.clone(), ids));
+        }
+    }
+
+    for requirements in by_key.values() {
+        for (i, (a, a_dep, a_ids)) in requirements.iter().enumerate() {
+            for (b, b_dep, b_ids) in &requirements[i + 1..] {
+                if !a_ids.is_disjoint(b_ids) {
+                    continue;
+                }
+                let name = b_dep.package_name();
+                let msg = format!(
+                    "failed to select a version for the requirement `{name} = \"{}\"`\n  \
+                     required by package `{}`\n\
+                     which conflicts with the requirement `{name} = \"{}\"`\n  \
+                     required by package `{}`\n\
+                     only one semver-compatible version of `{name}` can be selected, \
+                     and no version of `{name}` matches both requirements",
+                    b_dep.version_req(),
+                    b.package_id(),
+                    a_dep.version_req(),
+                    a.package_id(),
+                );
+                return Err(
+                    ResolveError::new(anyhow::format_err!(msg), vec![b.package_id()]).into(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 fn check_cycles(resolve: &Resolve) -> CargoResult<()> {
     // Create a simple graph representation alternative of `resolve` which has
     // only the edges we care about. Note that `BTree*` is used to produce
@@ -1113,7 +1219,7 @@ fn check_cycles(resolve: &Resolve) -> CargoResult<()> {
             anyhow::bail!(
                 "cyclic package dependency: package `{}` depends on itself. Cycle:\n{}",
                 id,
-                errors::describe_path(iter),
+                errors::describe_path(iter, None),
             );
         }
 
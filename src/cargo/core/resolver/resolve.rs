@@ -28,6 +28,10 @@ pub struct Resolve {
     /// validate the correct crate file is used. This is `None` for sources
     /// that do not use `.crate` files, like path or git dependencies.
     checksums: HashMap<PackageId, Option<String>>,
+    /// SHA256 hash of the contents of git and path packages, recorded with
+    /// `-Zcontent-hashes` to detect changes to their sources. See
+    /// [`crate::ops::check_content_hashes`].
+    content_hashes: HashMap<PackageId, String>,
     /// "Unknown" metadata. This is a collection of extra, unrecognized data
     /// found in the `[metadata]` section of `Cargo.lock`, preserved for
     /// forwards compatibility.
@@ -121,6 +125,7 @@ impl Resolve {
             replacements,
             features,
             checksums,
+            content_hashes: HashMap::new(),
             metadata,
             unused_patches,
             reverse_replacements,
@@ -233,6 +238,17 @@ unable to verify that `{0}` is the same as when the lockfile was generated
             }
         }
 
+        // Keep the content hashes of the packages still locked to the same
+        // revision. Package IDs don't tell git revisions apart.
+        let mine: HashSet<_> = self.iter().collect();
+        for (id, hash) in previous.content_hashes.iter() {
+            if let Some(mine) = mine.get(id) {
+                if mine.source_id().precise() == id.source_id().precise() {
+                    self.content_hashes.insert(*mine, hash.clone());
+                }
+            }
+        }
+
         // Be sure to just copy over any unknown metadata.
         self.metadata = previous.metadata.clone();
 
@@ -311,6 +327,14 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         &self.checksums
     }
 
+    pub fn content_hashes(&self) -> &HashMap<PackageId, String> {
+        &self.content_hashes
+    }
+
+    pub fn set_content_hash(&mut self, pkg_id: PackageId, hash: String) {
+        self.content_hashes.insert(pkg_id, hash);
+    }
+
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
@@ -393,7 +417,8 @@ impl PartialEq for Resolve {
         compare! {
             // fields to compare
             graph replacements reverse_replacements features
-            checksums metadata unused_patches public_dependencies summaries
+            checksums content_hashes metadata unused_patches public_dependencies
+            summaries
             |
             // fields to ignore
             version
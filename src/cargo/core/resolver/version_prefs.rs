@@ -14,10 +14,14 @@ use crate::util::interning::InternedString;
 ///
 /// As written, a version is either "preferred" or "not preferred".  Later extensions may
 /// introduce more granular preferences.
+///
+/// With [`Self::max_rust_version`], the versions requiring a newer rustc are not
+/// candidates at all, see [`Self::is_rust_version_compatible`].
 #[derive(Default)]
 pub struct VersionPreferences {
     try_to_use: HashSet<PackageId>,
     prefer_patch_deps: HashMap<InternedString, HashSet<Dependency>>,
+    max_rust_version: Option<semver::Version>,
 }
 
 pub enum VersionOrdering {
@@ -39,6 +43,27 @@ impl VersionPreferences {
             .insert(dep);
     }
 
+    /// Limit the candidates to the versions compatible with the given rustc,
+    /// as with `resolver.msrv-policy = "compatible"`.
+    pub fn max_rust_version(&mut self, rust_version: Option<semver::Version>) {
+        self.max_rust_version = rust_version;
+    }
+
+    /// Whether `summary` can be built by the rustc set with
+    /// [`Self::max_rust_version`], if any.
+    ///
+    /// Invalid `rust-version` values, which older registries may hold, are
+    /// considered compatible here and reported when building.
+    pub fn is_rust_version_compatible(&self, summary: &Summary) -> bool {
+        let (Some(current), Some(required)) = (&self.max_rust_version, summary.rust_version())
+        else {
+            return true;
+        };
+        semver::VersionReq::parse(&required)
+            .map(|req| req.matches(current))
+            .unwrap_or(true)
+    }
+
     /// Sort the given vector of summaries in-place, with all summaries presumed to be for
     /// the same package.  Preferred versions appear first in the result, sorted by
     /// `version_ordering`, followed by non-preferred versions sorted the same way.
@@ -191,4 +216,29 @@ mod test {
             "foo/1.1.0, foo/1.2.3, foo/1.0.9, foo/1.2.4".to_string()
         );
     }
+
+    #[test]
+    fn test_max_rust_version() {
+        let mut vp = VersionPreferences::default();
+        let summary = |rust_version: Option<&str>| {
+            let features = BTreeMap::new();
+            Summary::new(
+                pkgid("foo", "1.0.0"),
+                Vec::new(),
+                &features,
+                None::<&String>,
+                rust_version,
+            )
+            .unwrap()
+        };
+
+        assert!(vp.is_rust_version_compatible(&summary(Some("1.70"))));
+
+        vp.max_rust_version(Some(semver::Version::new(1, 65, 0)));
+        assert!(vp.is_rust_version_compatible(&summary(None)));
+        assert!(vp.is_rust_version_compatible(&summary(Some("1.60"))));
+        assert!(vp.is_rust_version_compatible(&summary(Some("1.65.0"))));
+        assert!(!vp.is_rust_version_compatible(&summary(Some("1.70"))));
+        assert!(vp.is_rust_version_compatible(&summary(Some("invalid"))));
+    }
 }
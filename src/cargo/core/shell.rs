@@ -1,11 +1,14 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::io::prelude::*;
 use std::io::IsTerminal;
+use std::rc::Rc;
 
 use termcolor::Color::{Cyan, Green, Red, Yellow};
 use termcolor::{self, Color, ColorSpec, StandardStream, WriteColor};
 
 use crate::util::errors::CargoResult;
+use crate::util::invocation_log::InvocationLog;
 
 pub enum TtyWidth {
     NoTty,
@@ -45,6 +48,41 @@ pub enum Verbosity {
     Quiet,
 }
 
+/// Kinds of messages which can be shown or hidden on their own, regardless of
+/// the [`Verbosity`], with `term.verbosity` (`-Zterm-verbosity`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputCategory {
+    /// Right-aligned statuses like `Compiling`, shown unless `--quiet`.
+    Status,
+    /// The `Running` statuses echoing the commands run, shown with
+    /// `--verbose`.
+    ProcessEcho,
+    /// The output of build scripts, shown with `-vv`.
+    BuildScriptOutput,
+    /// The `Fresh` and `Dirty` statuses explaining why units are rebuilt,
+    /// shown with `--verbose`.
+    FingerprintDebug,
+}
+
+impl OutputCategory {
+    pub const ALL: [OutputCategory; 4] = [
+        OutputCategory::Status,
+        OutputCategory::ProcessEcho,
+        OutputCategory::BuildScriptOutput,
+        OutputCategory::FingerprintDebug,
+    ];
+
+    /// The name of the category in `term.verbosity`.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputCategory::Status => "status",
+            OutputCategory::ProcessEcho => "process-echo",
+            OutputCategory::BuildScriptOutput => "build-script-output",
+            OutputCategory::FingerprintDebug => "fingerprint-debug",
+        }
+    }
+}
+
 /// An abstraction around console output that remembers preferences for output
 /// verbosity and color.
 pub struct Shell {
@@ -53,9 +91,19 @@ pub struct Shell {
     output: ShellOut,
     /// How verbose messages should be.
     verbosity: Verbosity,
+    /// Whether `-vv` was passed.
+    extra_verbose: bool,
+    /// Categories of messages shown or hidden regardless of `verbosity`.
+    categories: HashMap<OutputCategory, bool>,
+    /// Set while running the callback of [`Shell::category`], so that its
+    /// statuses are not hidden with [`OutputCategory::Status`].
+    in_category: bool,
     /// Flag that indicates the current line needs to be cleared before
     /// printing. Used when a progress bar is currently displayed.
     needs_clear: bool,
+    /// Where warnings and errors are additionally recorded, regardless of
+    /// verbosity (`-Zlog-file`).
+    invocation_log: Option<Rc<InvocationLog>>,
 }
 
 impl fmt::Debug for Shell {
@@ -111,7 +159,11 @@ impl Shell {
                 stderr_tty: std::io::stderr().is_terminal(),
             },
             verbosity: Verbosity::Verbose,
+            extra_verbose: false,
+            categories: HashMap::new(),
+            in_category: false,
             needs_clear: false,
+            invocation_log: None,
         }
     }
 
@@ -120,7 +172,11 @@ impl Shell {
         Shell {
             output: ShellOut::Write(out),
             verbosity: Verbosity::Verbose,
+            extra_verbose: false,
+            categories: HashMap::new(),
+            in_category: false,
             needs_clear: false,
+            invocation_log: None,
         }
     }
 
@@ -133,16 +189,23 @@ impl Shell {
         color: Color,
         justified: bool,
     ) -> CargoResult<()> {
-        match self.verbosity {
-            Verbosity::Quiet => Ok(()),
-            _ => {
-                if self.needs_clear {
-                    self.err_erase_line();
-                }
-                self.output
-                    .message_stderr(status, message, color, justified)
-            }
+        // The category of a callback of `category` was already checked, and
+        // may be shown even with `--quiet`.
+        let shown = if self.in_category {
+            true
+        } else if justified {
+            self.shows(OutputCategory::Status)
+        } else {
+            self.verbosity != Verbosity::Quiet
+        };
+        if !shown {
+            return Ok(());
         }
+        if self.needs_clear {
+            self.err_erase_line();
+        }
+        self.output
+            .message_stderr(status, message, color, justified)
     }
 
     /// Sets whether the next print should clear the current line.
@@ -238,6 +301,43 @@ impl Shell {
         }
     }
 
+    /// Runs the callback only if messages of `category` are shown.
+    ///
+    /// The statuses it prints are shown even if [`OutputCategory::Status`]
+    /// is hidden.
+    pub fn category<F>(&mut self, category: OutputCategory, mut callback: F) -> CargoResult<()>
+    where
+        F: FnMut(&mut Shell) -> CargoResult<()>,
+    {
+        if !self.shows(category) {
+            return Ok(());
+        }
+        let in_category = std::mem::replace(&mut self.in_category, true);
+        let result = callback(self);
+        self.in_category = in_category;
+        result
+    }
+
+    /// Returns whether messages of `category` are shown, either because it
+    /// was set in `term.verbosity`, or because of the verbosity.
+    pub fn shows(&self, category: OutputCategory) -> bool {
+        if let Some(&shown) = self.categories.get(&category) {
+            return shown;
+        }
+        match category {
+            OutputCategory::Status => self.verbosity != Verbosity::Quiet,
+            OutputCategory::ProcessEcho | OutputCategory::FingerprintDebug => {
+                self.verbosity == Verbosity::Verbose
+            }
+            OutputCategory::BuildScriptOutput => self.extra_verbose,
+        }
+    }
+
+    /// Shows or hides the messages of `category` regardless of the verbosity.
+    pub fn set_category_shown(&mut self, category: OutputCategory, shown: bool) {
+        self.categories.insert(category, shown);
+    }
+
     /// Runs the callback if we are not in verbose mode.
     pub fn concise<F>(&mut self, mut callback: F) -> CargoResult<()>
     where
@@ -251,6 +351,9 @@ impl Shell {
 
     /// Prints a red 'error' message.
     pub fn error<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
+        if let Some(log) = &self.invocation_log {
+            log.diagnostic("error", &message);
+        }
         if self.needs_clear {
             self.err_erase_line();
         }
@@ -260,6 +363,9 @@ impl Shell {
 
     /// Prints an amber 'warning' message.
     pub fn warn<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
+        if let Some(log) = &self.invocation_log {
+            log.diagnostic("warning", &message);
+        }
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
             _ => self.print(&"warning", Some(&message), Yellow, false),
@@ -271,11 +377,21 @@ impl Shell {
         self.print(&"note", Some(&message), Cyan, false)
     }
 
+    /// Records warnings and errors to `log` in addition to printing them.
+    pub fn set_invocation_log(&mut self, log: Rc<InvocationLog>) {
+        self.invocation_log = Some(log);
+    }
+
     /// Updates the verbosity of the shell.
     pub fn set_verbosity(&mut self, verbosity: Verbosity) {
         self.verbosity = verbosity;
     }
 
+    /// Updates whether `-vv` was passed.
+    pub fn set_extra_verbose(&mut self, extra_verbose: bool) {
+        self.extra_verbose = extra_verbose;
+    }
+
     /// Gets the verbosity of the shell.
     pub fn verbosity(&self) -> Verbosity {
         self.verbosity
@@ -22,7 +22,10 @@ use crate::sources::{PathSource, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
 use crate::util::edit_distance;
 use crate::util::errors::{CargoResult, ManifestError};
 use crate::util::interning::InternedString;
-use crate::util::toml::{read_manifest, InheritableFields, TomlDependency, TomlProfiles};
+use crate::util::restricted_names::is_glob_pattern;
+use crate::util::toml::{
+    read_manifest, read_manifest_from_str, InheritableFields, TomlDependency, TomlProfiles,
+};
 use crate::util::{config::ConfigRelativePath, Config, Filesystem, IntoUrl};
 use cargo_util::paths;
 use cargo_util::paths::normalize_path;
@@ -97,6 +100,10 @@ pub struct Workspace<'cfg> {
 
     /// Workspace-level custom metadata
     custom_metadata: Option<toml::Value>,
+
+    /// The `[workspace.feature-sets]` table, mapping the name of each set to
+    /// the features it enables in each member.
+    feature_sets: BTreeMap<String, BTreeMap<String, Vec<String>>>,
 }
 
 // Separate structure for tracking loaded packages (to avoid loading anything
@@ -174,6 +181,7 @@ pub struct WorkspaceRootConfig {
     exclude: Vec<String>,
     inheritable_fields: InheritableFields,
     custom_metadata: Option<toml::Value>,
+    feature_sets: BTreeMap<String, BTreeMap<String, Vec<String>>>,
 }
 
 impl<'cfg> Workspace<'cfg> {
@@ -196,9 +204,10 @@ impl<'cfg> Workspace<'cfg> {
             ws.root_manifest = ws.find_root(manifest_path)?;
         }
 
-        ws.custom_metadata = ws
-            .load_workspace_config()?
-            .and_then(|cfg| cfg.custom_metadata);
+        if let Some(ws_config) = ws.load_workspace_config()? {
+            ws.custom_metadata = ws_config.custom_metadata;
+            ws.feature_sets = ws_config.feature_sets;
+        }
         ws.find_members()?;
         ws.set_resolve_behavior();
         ws.validate()?;
@@ -224,6 +233,7 @@ impl<'cfg> Workspace<'cfg> {
             ignore_lock: false,
             resolve_behavior: ResolveBehavior::V1,
             custom_metadata: None,
+            feature_sets: BTreeMap::new(),
         }
     }
 
@@ -305,20 +315,20 @@ impl<'cfg> Workspace<'cfg> {
                 "manifest path `{}` is a virtual manifest, but this \
                  command requires running against an actual package in \
                  this workspace",
-                self.current_manifest.display()
+                self.display_path(&self.current_manifest)
             )
         })?;
         Ok(pkg)
     }
 
     pub fn current_mut(&mut self) -> CargoResult<&mut Package> {
-        let cm = self.current_manifest.clone();
+        let cm = self.display_path(&self.current_manifest);
         let pkg = self.current_opt_mut().ok_or_else(|| {
             anyhow::format_err!(
                 "manifest path `{}` is a virtual manifest, but this \
                  command requires running against an actual package in \
                  this workspace",
-                cm.display()
+                cm
             )
         })?;
         Ok(pkg)
@@ -365,6 +375,19 @@ impl<'cfg> Workspace<'cfg> {
         self.root_manifest().parent().unwrap()
     }
 
+    /// Renders `path` for a message, relative to the workspace root with
+    /// `-Zterm-paths`, see [`Config::display_path`].
+    pub fn display_path(&self, path: &Path) -> String {
+        self.config.display_path(path, self.root())
+    }
+
+    /// Renders the package `id` for a message, with the path of a path
+    /// package relative to the workspace root with `-Zterm-paths`, see
+    /// [`Config::display_package_id`].
+    pub fn display_package_id(&self, id: PackageId) -> String {
+        self.config.display_package_id(id, self.root())
+    }
+
     /// Returns the path of the `Cargo.toml` which is the root of this
     /// workspace.
     pub fn root_manifest(&self) -> &Path {
@@ -595,10 +618,72 @@ impl<'cfg> Workspace<'cfg> {
         self
     }
 
+    /// Replaces the manifest of the member at `manifest_path` with one parsed
+    /// from `contents`, without writing it.
+    ///
+    /// This resolves the workspace as it would be after an edit, as with
+    /// `cargo add --dry-run --estimate`.
+    pub fn replace_manifest(&mut self, manifest_path: &Path, contents: &str) -> CargoResult<()> {
+        let source_id = SourceId::for_path(manifest_path.parent().unwrap())?;
+        let (manifest, _nested_paths) =
+            read_manifest_from_str(contents, manifest_path, false, source_id, self.config)
+                .with_context(|| {
+                    format!(
+                        "failed to parse manifest at `{}`",
+                        self.display_path(manifest_path)
+                    )
+                })?;
+        let EitherManifest::Real(manifest) = manifest else {
+            bail!(
+                "`{}` is not a package manifest",
+                self.display_path(manifest_path)
+            );
+        };
+        let Some(package) = self.packages.maybe_get_mut(manifest_path) else {
+            bail!(
+                "`{}` is not a member of the workspace",
+                self.display_path(manifest_path)
+            );
+        };
+        *package = MaybePackage::Package(Package::new(manifest, manifest_path));
+        Ok(())
+    }
+
     pub fn custom_metadata(&self) -> Option<&toml::Value> {
         self.custom_metadata.as_ref()
     }
 
+    /// Expands the feature set `name` of `[workspace.feature-sets]` into
+    /// `member/feature` values, as passed to `--features`.
+    pub fn feature_set(&self, name: &str) -> CargoResult<Vec<String>> {
+        let Some(set) = self.feature_sets.get(name) else {
+            let mut msg =
+                format!("feature set `{name}` is not defined in `[workspace.feature-sets]`");
+            if self.feature_sets.is_empty() {
+                msg.push_str(", which has no feature sets");
+            } else {
+                let names: Vec<_> = self.feature_sets.keys().map(String::as_str).collect();
+                msg.push_str(&format!("\n\navailable feature sets: {}", names.join(", ")));
+            }
+            bail!(msg);
+        };
+        let mut features = Vec::new();
+        for (member, member_features) in set {
+            if !self.members().any(|pkg| pkg.name() == member.as_str()) {
+                bail!(
+                    "feature set `{name}` enables features of `{member}`, \
+                     which is not a member of the workspace"
+                );
+            }
+            features.extend(
+                member_features
+                    .iter()
+                    .map(|feature| format!("{member}/{feature}")),
+            );
+        }
+        Ok(features)
+    }
+
     pub fn load_workspace_config(&mut self) -> CargoResult<Option<WorkspaceRootConfig>> {
         // If we didn't find a root, it must mean there is no [workspace] section, and thus no
         // metadata.
@@ -611,7 +696,7 @@ impl<'cfg> Workspace<'cfg> {
 
                 _ => bail!(
                     "root of a workspace inferred but wasn't a root: {}",
-                    root_path.display()
+                    self.display_path(root_path)
                 ),
             }
         }
@@ -673,8 +758,9 @@ impl<'cfg> Workspace<'cfg> {
         // self.root_manifest must be Some to have retrieved workspace_config
         let root_manifest_path = self.root_manifest.clone().unwrap();
 
-        let members_paths =
-            workspace_config.members_paths(workspace_config.members.as_ref().unwrap_or(&vec![]))?;
+        let no_members = Vec::new();
+        let members_paths = workspace_config
+            .members_paths(workspace_config.members.as_ref().unwrap_or(&no_members))?;
         let default_members_paths = if root_manifest_path == self.current_manifest {
             if let Some(ref default) = workspace_config.default_members {
                 Some(workspace_config.members_paths(default)?)
@@ -685,20 +771,30 @@ impl<'cfg> Workspace<'cfg> {
             None
         };
 
-        for path in &members_paths {
+        for (path, glob) in &members_paths {
             self.find_path_deps(&path.join("Cargo.toml"), &root_manifest_path, false)
                 .with_context(|| {
+                    let referenced = match glob {
+                        Some(glob) => format!("referenced via `{glob}`"),
+                        None => "referenced".to_string(),
+                    };
                     format!(
-                        "failed to load manifest for workspace member `{}`",
-                        path.display()
+                        "failed to load manifest for workspace member `{}`\n\
+                         {referenced} by workspace at `{}`",
+                        self.display_path(path),
+                        self.display_path(&root_manifest_path)
                     )
                 })?;
         }
 
         self.find_path_deps(&root_manifest_path, &root_manifest_path, false)?;
 
+        if self.config.cli_unstable().nested_workspaces {
+            self.find_nested_members()?;
+        }
+
         if let Some(default) = default_members_paths {
-            for path in default {
+            for (path, glob) in default {
                 let normalized_path = paths::normalize_path(&path);
                 let manifest_path = normalized_path.join("Cargo.toml");
                 if !self.members.contains(&manifest_path) {
@@ -708,16 +804,29 @@ impl<'cfg> Workspace<'cfg> {
                     // manifest path, both because `members_paths` doesn't
                     // include `/Cargo.toml`, and because excluded paths may not
                     // be crates.
-                    let exclude = members_paths.contains(&normalized_path)
-                        && workspace_config.is_excluded(&normalized_path);
-                    if exclude {
+                    let listed = members_paths
+                        .iter()
+                        .any(|(member, _)| *member == normalized_path);
+                    if listed && workspace_config.is_excluded(&normalized_path) {
                         continue;
                     }
-                    bail!(
+                    let mut msg = format!(
                         "package `{}` is listed in workspace’s default-members \
                          but is not a member.",
-                        path.display()
-                    )
+                        self.display_path(&path)
+                    );
+                    if let Some(glob) = glob {
+                        msg.push_str(&format!(
+                            "\nIt was matched by `{glob}` in `workspace.default-members`."
+                        ));
+                    }
+                    if let Some(exclude) = workspace_config.excluded_by(&normalized_path) {
+                        msg.push_str(&format!(
+                            "\nIt is excluded by `{exclude}` in `workspace.exclude`, \
+                             which only applies to paths also listed in `workspace.members`."
+                        ));
+                    }
+                    bail!(msg)
                 }
                 self.default_members.push(manifest_path)
             }
@@ -730,6 +839,49 @@ impl<'cfg> Workspace<'cfg> {
         Ok(())
     }
 
+    /// Adds the members of the workspaces nested in this one, with
+    /// `-Znested-workspaces`.
+    ///
+    /// A member which is the root of a workspace of its own brings in the
+    /// members of that workspace, picked with its own `members` and `exclude`
+    /// lists, as if this workspace listed them. They are then built and
+    /// resolved as part of this workspace, with its lock file, target
+    /// directory, profiles and patches. Working from within the nested
+    /// workspace still uses the nested one on its own.
+    fn find_nested_members(&mut self) -> CargoResult<()> {
+        let mut i = 0;
+        while i < self.members.len() {
+            let nested_root = self.members[i].clone();
+            i += 1;
+            if Some(&nested_root) == self.root_manifest.as_ref() {
+                continue;
+            }
+            let nested_config = match self.packages.load(&nested_root)?.workspace_config() {
+                WorkspaceConfig::Root(config) => config.clone(),
+                WorkspaceConfig::Member { .. } => continue,
+            };
+            debug!("find_nested_members - {}", nested_root.display());
+            let members_paths =
+                nested_config.members_paths(nested_config.members.as_deref().unwrap_or(&[]))?;
+            for (path, glob) in &members_paths {
+                self.find_path_deps(&path.join("Cargo.toml"), &nested_root, false)
+                    .with_context(|| {
+                        let referenced = match glob {
+                            Some(glob) => format!("referenced via `{glob}`"),
+                            None => "referenced".to_string(),
+                        };
+                        format!(
+                            "failed to load manifest for workspace member `{}`\n\
+                             {referenced} by nested workspace at `{}`",
+                            self.display_path(path),
+                            self.display_path(&nested_root)
+                        )
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
     fn find_path_deps(
         &mut self,
         manifest_path: &Path,
@@ -845,8 +997,8 @@ impl<'cfg> Workspace<'cfg> {
                          - {}\n\
                          - {}",
                     name,
-                    prev.display(),
-                    member.display()
+                    self.display_path(prev),
+                    self.display_path(member)
                 );
             }
         }
@@ -854,10 +1006,16 @@ impl<'cfg> Workspace<'cfg> {
     }
 
     fn validate_workspace_roots(&self) -> CargoResult<()> {
+        let nested_workspaces = self.config.cli_unstable().nested_workspaces;
         let roots: Vec<PathBuf> = self
             .members
             .iter()
             .filter(|&member| {
+                // The roots of nested workspaces are members themselves, see
+                // `find_nested_members`.
+                if nested_workspaces && Some(member) != self.root_manifest.as_ref() {
+                    return false;
+                }
                 let config = self.packages.get(member).workspace_config();
                 matches!(config, WorkspaceConfig::Root(_))
             })
@@ -870,15 +1028,15 @@ impl<'cfg> Workspace<'cfg> {
                  which is not configured with [workspace]: \n\
                  configuration at: {}\n\
                  points to: {}",
-                self.current_manifest.display(),
-                self.root_manifest.as_ref().unwrap().display()
+                self.display_path(&self.current_manifest),
+                self.display_path(self.root_manifest.as_ref().unwrap())
             ),
             _ => {
                 bail!(
                     "multiple workspace roots found in the same workspace:\n{}",
                     roots
                         .iter()
-                        .map(|r| format!("  {}", r.display()))
+                        .map(|r| format!("  {}", self.display_path(r)))
                         .collect::<Vec<_>>()
                         .join("\n")
                 );
@@ -892,6 +1050,15 @@ impl<'cfg> Workspace<'cfg> {
             if root == self.root_manifest {
                 continue;
             }
+            // The roots of nested workspaces are members themselves, see
+            // `find_nested_members`.
+            let nested = match &root {
+                Some(root) => self.members.contains(root),
+                None => false,
+            };
+            if nested && self.config.cli_unstable().nested_workspaces {
+                continue;
+            }
 
             match root {
                 Some(root) => {
@@ -899,17 +1066,17 @@ impl<'cfg> Workspace<'cfg> {
                         "package `{}` is a member of the wrong workspace\n\
                          expected: {}\n\
                          actual:   {}",
-                        member.display(),
-                        self.root_manifest.as_ref().unwrap().display(),
-                        root.display()
+                        self.display_path(&member),
+                        self.display_path(self.root_manifest.as_ref().unwrap()),
+                        self.display_path(&root)
                     );
                 }
                 None => {
                     bail!(
                         "workspace member `{}` is not hierarchically below \
                          the workspace root `{}`",
-                        member.display(),
-                        self.root_manifest.as_ref().unwrap().display()
+                        self.display_path(&member),
+                        self.display_path(self.root_manifest.as_ref().unwrap())
                     );
                 }
             }
@@ -934,13 +1101,13 @@ impl<'cfg> Workspace<'cfg> {
                      `workspace.members` array of the manifest \
                      located at: {}",
                 rel.display(),
-                root.display()
+                self.display_path(root)
             ),
             Err(_) => format!(
                 "this may be fixable by adding a member to \
                      the `workspace.members` array of the \
                      manifest located at: {}",
-                root.display()
+                self.display_path(root)
             ),
         };
         let extra = match *root_pkg {
@@ -955,7 +1122,7 @@ impl<'cfg> Workspace<'cfg> {
                         "this may be fixable by ensuring that this \
                              crate is depended on by the workspace \
                              root: {}",
-                        root.display()
+                        self.display_path(root)
                     )
                 } else {
                     members_msg
@@ -969,8 +1136,8 @@ impl<'cfg> Workspace<'cfg> {
                  Alternatively, to keep it out of the workspace, add the package \
                  to the `workspace.exclude` array, or add an empty `[workspace]` \
                  table to the package's manifest.",
-            self.current_manifest.display(),
-            root.display(),
+            self.display_path(&self.current_manifest),
+            self.display_path(root),
             extra
         );
     }
@@ -990,8 +1157,8 @@ impl<'cfg> Workspace<'cfg> {
                          workspace: {}",
                         what,
                         what,
-                        pkg.manifest_path().display(),
-                        root_manifest.display(),
+                        self.display_path(pkg.manifest_path()),
+                        self.display_path(root_manifest),
                     );
                     self.config.shell().warn(&msg)
                 };
@@ -1087,8 +1254,10 @@ impl<'cfg> Workspace<'cfg> {
             for warning in warnings {
                 if warning.is_critical {
                     let err = anyhow::format_err!("{}", warning.message);
-                    let cx =
-                        anyhow::format_err!("failed to parse manifest at `{}`", path.display());
+                    let cx = anyhow::format_err!(
+                        "failed to parse manifest at `{}`",
+                        self.display_path(&path)
+                    );
                     return Err(err.context(cx));
                 } else {
                     let msg = if self.root_manifest.is_none() {
@@ -1096,7 +1265,7 @@ impl<'cfg> Workspace<'cfg> {
                     } else {
                         // In a workspace, it can be confusing where a warning
                         // originated, so include the path.
-                        format!("{}: {}", path.display(), warning.message)
+                        format!("{}: {}", self.display_path(&path), warning.message)
                     };
                     self.config.shell().warn(msg)?
                 }
@@ -1617,6 +1786,7 @@ impl WorkspaceRootConfig {
         exclude: &Option<Vec<String>>,
         inheritable: &Option<InheritableFields>,
         custom_metadata: &Option<toml::Value>,
+        feature_sets: &Option<BTreeMap<String, BTreeMap<String, Vec<String>>>>,
     ) -> WorkspaceRootConfig {
         WorkspaceRootConfig {
             root_dir: root_dir.to_path_buf(),
@@ -1625,6 +1795,7 @@ impl WorkspaceRootConfig {
             exclude: exclude.clone().unwrap_or_default(),
             inheritable_fields: inheritable.clone().unwrap_or_default(),
             custom_metadata: custom_metadata.clone(),
+            feature_sets: feature_sets.clone().unwrap_or_default(),
         }
     }
     /// Checks the path against the `excluded` list.
@@ -1646,11 +1817,24 @@ impl WorkspaceRootConfig {
         !explicit_member && excluded
     }
 
+    /// Returns the entry of the `excluded` list matching the path, if any.
+    fn excluded_by(&self, manifest_path: &Path) -> Option<&str> {
+        self.exclude
+            .iter()
+            .find(|ex| manifest_path.starts_with(self.root_dir.join(ex)))
+            .map(String::as_str)
+    }
+
     fn has_members_list(&self) -> bool {
         self.members.is_some()
     }
 
-    fn members_paths(&self, globs: &[String]) -> CargoResult<Vec<PathBuf>> {
+    /// Expands the entries of `members` or `default-members` into paths, each
+    /// with the pattern it was matched by if the entry is a glob.
+    fn members_paths<'g>(
+        &self,
+        globs: &'g [String],
+    ) -> CargoResult<Vec<(PathBuf, Option<&'g str>)>> {
         let mut expanded_list = Vec::new();
 
         for glob in globs {
@@ -1660,8 +1844,9 @@ impl WorkspaceRootConfig {
             // If glob does not find any valid paths, then put the original
             // path in the expanded list to maintain backwards compatibility.
             if expanded_paths.is_empty() {
-                expanded_list.push(pathbuf);
+                expanded_list.push((pathbuf, None));
             } else {
+                let pattern = is_glob_pattern(glob).then_some(glob.as_str());
                 // Some OS can create system support files anywhere.
                 // (e.g. macOS creates `.DS_Store` file if you visit a directory using Finder.)
                 // Such files can be reported as a member path unexpectedly.
@@ -1669,7 +1854,7 @@ impl WorkspaceRootConfig {
                 // as a member.
                 for expanded_path in expanded_paths {
                     if expanded_path.is_dir() {
-                        expanded_list.push(expanded_path);
+                        expanded_list.push((expanded_path, pattern));
                     }
                 }
             }
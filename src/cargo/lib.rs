@@ -165,13 +165,18 @@ pub mod util;
 mod version;
 
 pub fn exit_with_error(err: CliError, shell: &mut Shell) -> ! {
-    debug!("exit_with_error; err={:?}", err);
+    std::process::exit(report_error(err, shell))
+}
+
+/// Displays `err` like [`exit_with_error`] without exiting, and returns the
+/// exit code to use.
+pub fn report_error(err: CliError, shell: &mut Shell) -> i32 {
+    debug!("report_error; err={:?}", err);
 
     if let Some(ref err) = err.error {
         if let Some(clap_err) = err.downcast_ref::<clap::Error>() {
-            let exit_code = if clap_err.use_stderr() { 1 } else { 0 };
             let _ = clap_err.print();
-            std::process::exit(exit_code)
+            return if clap_err.use_stderr() { 1 } else { 0 };
         }
     }
 
@@ -180,7 +185,7 @@ pub fn exit_with_error(err: CliError, shell: &mut Shell) -> ! {
         display_error(&error, shell);
     }
 
-    std::process::exit(exit_code)
+    exit_code
 }
 
 /// Displays an error, and all its causes, to stderr.
@@ -0,0 +1,179 @@
+//! Estimates the cost of adding dependencies, for `cargo add --estimate`.
+//!
+//! The workspace is resolved before and after the dependencies are added, and
+//! the packages only found in the second resolve are the new crates. They are
+//! downloaded, as a build would do, to measure the size of their `.crate`
+//! files. Their build time is taken from the `--timings` reports of the
+//! workspace, if they were ever built in it.
+//!
+//! Neither resolve writes the lock file, and with `--dry-run` the second one
+//! uses the manifest with the dependencies in memory, see
+//! [`Workspace::replace_manifest`].
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cargo_util::paths;
+use serde::Deserialize;
+
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::features::{CliFeatures, HasDevUnits};
+use crate::core::{Package, PackageId, PackageSet, Resolve, Workspace};
+use crate::ops;
+use crate::sources::registry::cached_crate_path;
+use crate::util::human_readable_bytes;
+use crate::{drop_eprintln, CargoResult, Config};
+
+/// The packages of the dependency graph of `ws`, to compare with the graph
+/// once the dependencies are added.
+pub fn snapshot(ws: &Workspace<'_>) -> CargoResult<HashSet<PackageId>> {
+    let (_, resolve) = resolve(ws)?;
+    Ok(resolve.iter().collect())
+}
+
+/// Prints the crates of the dependency graph of `ws` which are not in
+/// `before`, with their download size and build time.
+pub fn report(ws: &Workspace<'_>, before: &HashSet<PackageId>) -> CargoResult<()> {
+    let config = ws.config();
+    let (pkg_set, resolve) = resolve(ws)?;
+    let mut new: Vec<_> = resolve.iter().filter(|id| !before.contains(id)).collect();
+    new.sort();
+    if new.is_empty() {
+        return config
+            .shell()
+            .status("Estimate", "no new crates in the dependency graph");
+    }
+
+    let mut packages = pkg_set.get_many(new.iter().copied())?;
+    packages.sort_by_key(|pkg| pkg.package_id());
+    let timings = load_timings(ws);
+    config.shell().status(
+        "Estimate",
+        format!(
+            "{} new crate{} in the dependency graph",
+            new.len(),
+            if new.len() == 1 { "" } else { "s" }
+        ),
+    )?;
+
+    let mut sizes = Vec::new();
+    let mut durations = Vec::new();
+    for pkg in packages {
+        let id = pkg.package_id();
+        let size = crate_size(config, pkg);
+        let duration = timings
+            .get(&(id.name().to_string(), id.version().to_string()))
+            .copied();
+        let size_desc = match size {
+            Some(size) => format_size(size),
+            None => String::from("size unknown"),
+        };
+        let duration_desc = match duration {
+            Some(duration) => format!("built in {duration:.1}s"),
+            None => String::from("no timings"),
+        };
+        drop_eprintln!(config, "{:13}{id}: {size_desc}, {duration_desc}", "");
+        sizes.extend(size);
+        durations.extend(duration);
+    }
+
+    let mut summary = format!("{} to download", format_size(sizes.iter().sum()));
+    if sizes.len() < new.len() {
+        summary.push_str(&format!(" for {} of {} crates", sizes.len(), new.len()));
+    }
+    if durations.is_empty() {
+        summary.push_str(", no build timings of these crates (see `cargo build --timings`)");
+    } else {
+        let total: f64 = durations.iter().sum();
+        summary.push_str(&format!(", about {total:.1}s of build time"));
+        if durations.len() < new.len() {
+            summary.push_str(&format!(" for {} of {} crates", durations.len(), new.len()));
+        }
+    }
+    config.shell().status("Estimate", summary)
+}
+
+/// Resolves the dependencies of `ws` like [`ops::resolve_ws`], but without
+/// writing the lock file.
+fn resolve<'cfg>(ws: &Workspace<'cfg>) -> CargoResult<(PackageSet<'cfg>, Resolve)> {
+    let mut registry = PackageRegistry::new(ws.config())?;
+    let prev = ops::load_pkg_lockfile(ws)?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        &CliFeatures::new_all(true),
+        HasDevUnits::Yes,
+        prev.as_ref(),
+        None,
+        &[],
+        true,
+    )?;
+    let pkg_set = ops::get_resolved_packages(&resolve, registry)?;
+    Ok((pkg_set, resolve))
+}
+
+fn format_size(size: u64) -> String {
+    let (size, unit) = human_readable_bytes(size);
+    format!("{size:.1}{unit}")
+}
+
+/// The size of the `.crate` file of `pkg`, if it comes from a registry.
+fn crate_size(config: &Config, pkg: &Package) -> Option<u64> {
+    let path = cached_crate_path(config, pkg)?;
+    fs::metadata(path).ok().map(|meta| meta.len())
+}
+
+/// A unit in the `UNIT_DATA` of a `--timings` report.
+#[derive(Deserialize)]
+struct TimingUnit {
+    name: String,
+    version: String,
+    duration: f64,
+}
+
+/// The build time of each crate, by name and version, from the `--timings`
+/// reports of `ws`. The latest report wins, and the units of a crate, such as
+/// its build script, are added up.
+fn load_timings(ws: &Workspace<'_>) -> HashMap<(String, String), f64> {
+    let dir = ws.target_dir().into_path_unlocked().join("cargo-timings");
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return HashMap::new();
+    };
+    // The reports are named after their timestamp, so sorting them puts the
+    // latest one last.
+    let mut reports: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| {
+                    name.starts_with("cargo-timing-") && name.ends_with(".html")
+                })
+        })
+        .collect();
+    reports.sort();
+
+    let mut timings = HashMap::new();
+    for report in reports {
+        let Some(units) = read_timing_units(&report) else {
+            tracing::debug!("no unit data in `{}`", report.display());
+            continue;
+        };
+        let mut crates = HashMap::new();
+        for unit in units {
+            *crates.entry((unit.name, unit.version)).or_insert(0.0) += unit.duration;
+        }
+        timings.extend(crates);
+    }
+    timings
+}
+
+fn read_timing_units(report: &Path) -> Option<Vec<TimingUnit>> {
+    let html = paths::read(report).ok()?;
+    let (_, data) = html.split_once("const UNIT_DATA = ")?;
+    serde_json::Deserializer::from_str(data)
+        .into_iter::<Vec<TimingUnit>>()
+        .next()?
+        .ok()
+}
@@ -1,6 +1,7 @@
 //! Core of cargo-add command
 
 mod crate_spec;
+pub mod estimate;
 
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
@@ -26,6 +27,8 @@ use crate::core::Registry;
 use crate::core::Shell;
 use crate::core::Summary;
 use crate::core::Workspace;
+use crate::drop_print;
+use crate::util::diff::unified_diff;
 use crate::util::toml_mut::dependency::Dependency;
 use crate::util::toml_mut::dependency::GitSource;
 use crate::util::toml_mut::dependency::MaybeWorkspace;
@@ -56,7 +59,10 @@ pub struct AddOptions<'a> {
 }
 
 /// Add dependencies to a manifest
-pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<()> {
+///
+/// Returns the contents of the manifest with the dependencies, which is only
+/// written without `dry_run`.
+pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<String> {
     let dep_table = options
         .section
         .to_table()
@@ -217,12 +223,18 @@ pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<(
     }
 
     if options.dry_run {
+        let diff = unified_diff(
+            &original_raw_manifest,
+            &manifest.to_string(),
+            &manifest.path.display().to_string(),
+        );
+        drop_print!(options.config, "{}", diff);
         options.config.shell().warn("aborting add due to dry run")?;
     } else {
         manifest.write()?;
     }
 
-    Ok(())
+    Ok(manifest.to_string())
 }
 
 /// Dependency entry operation
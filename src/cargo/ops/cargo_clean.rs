@@ -1,16 +1,23 @@
-use crate::core::compiler::{CompileKind, CompileMode, Layout, RustcTargetData};
+use crate::core::compiler::{CompileKind, CompileMode, LastUse, Layout, RustcTargetData};
 use crate::core::profiles::Profiles;
-use crate::core::{PackageIdSpec, TargetKind, Workspace};
+use crate::core::resolver::Resolve;
+use crate::core::{PackageId, PackageIdSpec, TargetKind, Workspace};
 use crate::ops;
+use crate::ops::cargo_compile::build_glob;
+use crate::util::cache_backend::{CacheBackend, CleanEvent, ProcessCacheBackend};
 use crate::util::edit_distance;
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
-use crate::util::{Config, Progress, ProgressStyle};
+use crate::util::restricted_names::is_glob_pattern;
+use crate::util::{human_readable_bytes, parse_size, Config, Filesystem, Progress, ProgressStyle};
 
 use anyhow::Context as _;
 use cargo_util::paths;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub struct CleanOptions<'a> {
     pub config: &'a Config,
@@ -24,17 +31,178 @@ pub struct CleanOptions<'a> {
     pub requested_profile: InternedString,
     /// Whether to just clean the doc directory
     pub doc: bool,
+    /// Whether to only report what would be removed, without removing it
+    pub dry_run: bool,
+    /// Only remove files which weren't modified in this many days
+    pub keep_days: Option<u32>,
+    /// Whether to also purge the entries of the workspace from the
+    /// configured cache backends
+    pub cache_too: bool,
+}
+
+/// The state of a run of `cargo clean`.
+struct CleanContext<'cfg> {
+    config: &'cfg Config,
+    dry_run: bool,
+    /// Files modified after this time are kept, with `--keep-days`.
+    keep_after: Option<SystemTime>,
+    num_files_removed: u64,
+    total_bytes_removed: u64,
+}
+
+impl<'cfg> CleanContext<'cfg> {
+    fn new(config: &'cfg Config, dry_run: bool) -> Self {
+        CleanContext {
+            config,
+            dry_run,
+            keep_after: None,
+            num_files_removed: 0,
+            total_bytes_removed: 0,
+        }
+    }
+
+    /// Displays the number and size of the files removed, or which would be
+    /// removed with `--dry-run`.
+    fn display_summary(&self) -> CargoResult<()> {
+        let (size, unit) = human_readable_bytes(self.total_bytes_removed);
+        let summary = format!(
+            "{} file{}, {size:.1}{unit} total",
+            self.num_files_removed,
+            if self.num_files_removed == 1 { "" } else { "s" }
+        );
+        if self.dry_run {
+            self.config.shell().status("Summary", summary)?;
+            self.config
+                .shell()
+                .warn("no files deleted due to --dry-run")?;
+        } else {
+            self.config.shell().status("Removed", summary)?;
+        }
+        Ok(())
+    }
 }
 
 /// Cleans the package's build artifacts.
 pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
+    let config = ws.config();
+    let keep_after = opts
+        .keep_days
+        .map(|days| SystemTime::now() - Duration::from_secs(u64::from(days) * SECONDS_PER_DAY));
+    let mut ctx = CleanContext::new(config, opts.dry_run);
+    ctx.keep_after = keep_after;
+    clean_artifacts(ws, opts, &mut ctx)?;
+
+    if opts.dry_run || opts.keep_days.is_some() {
+        ctx.display_summary()?;
+    }
+    if !opts.dry_run {
+        notify_cache_backends(ws, opts)?;
+    }
+    Ok(())
+}
+
+/// Tells the configured cache backends about what was cleaned.
+///
+/// A backend failing to process the event is only a warning, as the
+/// artifacts are already removed, unless it was asked to purge its entries
+/// with `--cache-too`.
+fn notify_cache_backends(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
+    let config = ws.config();
+    let mut backends = ProcessCacheBackend::from_config(config)?;
+    if opts.cache_too && backends.is_empty() {
+        anyhow::bail!(
+            "`--cache-too` requires a cache backend to be configured in `cache-backends`"
+        );
+    }
+    let target_dir = ws.target_dir().into_path_unlocked();
+    let event = CleanEvent {
+        workspace_root: ws.root(),
+        target_dir: &target_dir,
+        packages: &opts.spec,
+        profile: opts
+            .profile_specified
+            .then(|| opts.requested_profile.as_str()),
+        targets: &opts.targets,
+        doc: opts.doc,
+        purge: opts.cache_too,
+    };
+    for backend in &mut backends {
+        match backend.on_clean(&event) {
+            Ok(removed) => {
+                if opts.cache_too {
+                    let removed = removed
+                        .map(|n| format!("{n} {}", if n == 1 { "entry" } else { "entries" }))
+                        .unwrap_or_else(|| "entries".to_string());
+                    config
+                        .shell()
+                        .status("Purged", format!("{removed} from `{}`", backend.name()))?;
+                }
+            }
+            Err(e) if opts.cache_too => return Err(e),
+            Err(e) => {
+                crate::display_warning_with_error(
+                    &format!(
+                        "failed to notify cache backend `{}` of `cargo clean`",
+                        backend.name()
+                    ),
+                    &e,
+                    &mut config.shell(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn clean_artifacts(
+    ws: &Workspace<'_>,
+    opts: &CleanOptions<'_>,
+    ctx: &mut CleanContext<'_>,
+) -> CargoResult<()> {
     let mut target_dir = ws.target_dir();
     let config = ws.config();
 
-    // If the doc option is set, we just want to delete the doc directory.
+    // If the doc option is set, we just want to delete the doc directories,
+    // or the docs of the given packages.
     if opts.doc {
-        target_dir = target_dir.join("doc");
-        return clean_entire_folder(&target_dir.into_path_unlocked(), config);
+        let doc_dirs = if opts.targets.is_empty() {
+            vec![target_dir.join("doc").into_path_unlocked()]
+        } else {
+            CompileKind::from_requested_targets(config, &opts.targets)?
+                .into_iter()
+                .filter_map(|kind| match kind {
+                    CompileKind::Target(target) => Some(
+                        target_dir
+                            .join(target.short_name())
+                            .join("doc")
+                            .into_path_unlocked(),
+                    ),
+                    CompileKind::Host => None,
+                })
+                .collect()
+        };
+        if opts.spec.is_empty() {
+            for doc_dir in &doc_dirs {
+                clean_entire_folder(doc_dir, ctx)?;
+            }
+            return Ok(());
+        }
+        // The search index still lists the removed crates until the docs are
+        // built again.
+        let (pkg_set, resolve) = ops::resolve_ws(ws)?;
+        let packages = pkg_set.get_many(matching_packages(config, &opts.spec, &resolve)?)?;
+        let mut progress = CleaningPackagesBar::new(config, packages.len());
+        for pkg in packages {
+            progress.on_cleaning_package(&pkg.name())?;
+            for target in pkg.targets().iter().filter(|t| t.documented()) {
+                let crate_name = target.crate_name();
+                for doc_dir in &doc_dirs {
+                    rm_rf(&doc_dir.join(&crate_name), ctx, &mut progress)?;
+                    rm_rf(&doc_dir.join("src").join(&crate_name), ctx, &mut progress)?;
+                }
+            }
+        }
+        return Ok(());
     }
 
     let profiles = Profiles::new(ws, opts.requested_profile)?;
@@ -53,7 +221,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     // Note that we don't bother grabbing a lock here as we're just going to
     // blow it all away anyway.
     if opts.spec.is_empty() {
-        return clean_entire_folder(&target_dir.into_path_unlocked(), config);
+        return clean_entire_folder(&target_dir.into_path_unlocked(), ctx);
     }
 
     // Clean specific packages.
@@ -94,44 +262,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     // remove it (otherwise you're left with lots of broken links).
     // Doc tests produce no output.
 
-    // Get Packages for the specified specs.
-    let mut pkg_ids = Vec::new();
-    for spec_str in opts.spec.iter() {
-        // Translate the spec to a Package.
-        let spec = PackageIdSpec::parse(spec_str)?;
-        if spec.version().is_some() {
-            config.shell().warn(&format!(
-                "version qualifier in `-p {}` is ignored, \
-                cleaning all versions of `{}` found",
-                spec_str,
-                spec.name()
-            ))?;
-        }
-        if spec.url().is_some() {
-            config.shell().warn(&format!(
-                "url qualifier in `-p {}` ignored, \
-                cleaning all versions of `{}` found",
-                spec_str,
-                spec.name()
-            ))?;
-        }
-        let matches: Vec<_> = resolve.iter().filter(|id| spec.matches(*id)).collect();
-        if matches.is_empty() {
-            let mut suggestion = String::new();
-            suggestion.push_str(&edit_distance::closest_msg(
-                &spec.name(),
-                resolve.iter(),
-                |id| id.name().as_str(),
-            ));
-            anyhow::bail!(
-                "package ID specification `{}` did not match any packages{}",
-                spec,
-                suggestion
-            );
-        }
-        pkg_ids.extend(matches);
-    }
-    let packages = pkg_set.get_many(pkg_ids)?;
+    let packages = pkg_set.get_many(matching_packages(config, &opts.spec, &resolve)?)?;
 
     let mut progress = CleaningPackagesBar::new(config, packages.len());
     for pkg in packages {
@@ -144,7 +275,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
             rm_rf_package_glob_containing_hash(
                 &pkg.name(),
                 &Path::new(&dir).join(&pkg_dir),
-                config,
+                ctx,
                 &mut progress,
             )?;
         }
@@ -157,7 +288,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
                     rm_rf_package_glob_containing_hash(
                         &pkg.name(),
                         &Path::new(&dir).join(&pkg_dir),
-                        config,
+                        ctx,
                         &mut progress,
                     )?;
                 }
@@ -190,35 +321,35 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
                         let dir_glob = escape_glob_path(dir)?;
                         let dir_glob = Path::new(&dir_glob);
 
-                        rm_rf_glob(&dir_glob.join(&hashed_name), config, &mut progress)?;
-                        rm_rf(&dir.join(&unhashed_name), config, &mut progress)?;
+                        rm_rf_glob(&dir_glob.join(&hashed_name), ctx, &mut progress)?;
+                        rm_rf(&dir.join(&unhashed_name), ctx, &mut progress)?;
                         // Remove dep-info file generated by rustc. It is not tracked in
                         // file_types. It does not have a prefix.
                         let hashed_dep_info = dir_glob.join(format!("{}-*.d", crate_name));
-                        rm_rf_glob(&hashed_dep_info, config, &mut progress)?;
+                        rm_rf_glob(&hashed_dep_info, ctx, &mut progress)?;
                         let unhashed_dep_info = dir.join(format!("{}.d", crate_name));
-                        rm_rf(&unhashed_dep_info, config, &mut progress)?;
+                        rm_rf(&unhashed_dep_info, ctx, &mut progress)?;
                         // Remove split-debuginfo files generated by rustc.
                         let split_debuginfo_obj = dir_glob.join(format!("{}.*.o", crate_name));
-                        rm_rf_glob(&split_debuginfo_obj, config, &mut progress)?;
+                        rm_rf_glob(&split_debuginfo_obj, ctx, &mut progress)?;
                         let split_debuginfo_dwo = dir_glob.join(format!("{}.*.dwo", crate_name));
-                        rm_rf_glob(&split_debuginfo_dwo, config, &mut progress)?;
+                        rm_rf_glob(&split_debuginfo_dwo, ctx, &mut progress)?;
                         let split_debuginfo_dwp = dir_glob.join(format!("{}.*.dwp", crate_name));
-                        rm_rf_glob(&split_debuginfo_dwp, config, &mut progress)?;
+                        rm_rf_glob(&split_debuginfo_dwp, ctx, &mut progress)?;
 
                         // Remove the uplifted copy.
                         if let Some(uplift_dir) = uplift_dir {
                             let uplifted_path = uplift_dir.join(file_type.uplift_filename(target));
-                            rm_rf(&uplifted_path, config, &mut progress)?;
+                            rm_rf(&uplifted_path, ctx, &mut progress)?;
                             // Dep-info generated by Cargo itself.
                             let dep_info = uplifted_path.with_extension("d");
-                            rm_rf(&dep_info, config, &mut progress)?;
+                            rm_rf(&dep_info, ctx, &mut progress)?;
                         }
                     }
                     // TODO: what to do about build_script_build?
                     let dir = escape_glob_path(layout.incremental())?;
                     let incremental = Path::new(&dir).join(format!("{}-*", crate_name));
-                    rm_rf_glob(&incremental, config, &mut progress)?;
+                    rm_rf_glob(&incremental, ctx, &mut progress)?;
                 }
             }
         }
@@ -227,6 +358,323 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     Ok(())
 }
 
+/// Returns the packages of `resolve` matching the `-p` specs, which may be
+/// glob patterns of package names.
+fn matching_packages(
+    config: &Config,
+    specs: &[String],
+    resolve: &Resolve,
+) -> CargoResult<Vec<PackageId>> {
+    let mut pkg_ids = Vec::new();
+    for spec_str in specs.iter() {
+        if is_glob_pattern(spec_str) {
+            let pattern = build_glob(spec_str)?;
+            let matches: Vec<_> = resolve
+                .iter()
+                .filter(|id| pattern.matches(id.name().as_str()))
+                .collect();
+            if matches.is_empty() {
+                anyhow::bail!("package pattern `{}` did not match any packages", spec_str);
+            }
+            pkg_ids.extend(matches);
+            continue;
+        }
+        // Translate the spec to a Package.
+        let spec = PackageIdSpec::parse(spec_str)?;
+        if spec.version().is_some() {
+            config.shell().warn(&format!(
+                "version qualifier in `-p {}` is ignored, \
+                cleaning all versions of `{}` found",
+                spec_str,
+                spec.name()
+            ))?;
+        }
+        if spec.url().is_some() {
+            config.shell().warn(&format!(
+                "url qualifier in `-p {}` ignored, \
+                cleaning all versions of `{}` found",
+                spec_str,
+                spec.name()
+            ))?;
+        }
+        let matches: Vec<_> = resolve.iter().filter(|id| spec.matches(*id)).collect();
+        if matches.is_empty() {
+            let mut suggestion = String::new();
+            suggestion.push_str(&edit_distance::closest_msg(
+                &spec.name(),
+                resolve.iter(),
+                |id| id.name().as_str(),
+            ));
+            anyhow::bail!(
+                "package ID specification `{}` did not match any packages{}",
+                spec,
+                suggestion
+            );
+        }
+        pkg_ids.extend(matches);
+    }
+    pkg_ids.sort();
+    pkg_ids.dedup();
+    Ok(pkg_ids)
+}
+
+/// Options for `cargo clean gc`.
+pub struct GcOptions {
+    /// Remove the units which weren't used in this many days
+    pub max_unused_days: Option<u32>,
+    /// Remove the least recently used units until each build directory is at
+    /// most this many bytes
+    pub max_size: Option<u64>,
+    /// Whether to only report what would be removed, without removing it
+    pub dry_run: bool,
+}
+
+/// The `[gc.target-dir]` table of the configuration.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TargetDirGcConfig {
+    max_unused_days: Option<u32>,
+    max_size: Option<String>,
+}
+
+impl GcOptions {
+    /// Reads the limits of the `[gc.target-dir]` configuration.
+    pub fn from_config(config: &Config) -> CargoResult<GcOptions> {
+        let cfg = config
+            .get::<Option<TargetDirGcConfig>>("gc.target-dir")?
+            .unwrap_or_default();
+        let max_size = match &cfg.max_size {
+            Some(size) => match parse_size(size) {
+                Some(bytes) => Some(bytes),
+                None => anyhow::bail!(
+                    "`gc.target-dir.max-size` must be a size such as `20GiB` or `512MiB`, found `{}`",
+                    size
+                ),
+            },
+            None => None,
+        };
+        Ok(GcOptions {
+            max_unused_days: cfg.max_unused_days,
+            max_size,
+            dry_run: false,
+        })
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Removes the artifacts of the target directory of `ws` which are no longer
+/// needed.
+///
+/// Only the build directories with a [`LastUse`] are collected, that is the
+/// ones built with `-Ztarget-dir-gc`, and only the units it records. In each
+/// directory, the units removed are:
+///
+/// * the units of packages which aren't in the lock file of any workspace
+///   built there anymore,
+/// * the units which weren't used in `max_unused_days`,
+/// * the least recently used units, until the directory is at most
+///   `max_size`.
+pub fn gc(ws: &Workspace<'_>, opts: &GcOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let mut ctx = CleanContext::new(config, opts.dry_run);
+    for dest in build_dests(&ws.target_dir().into_path_unlocked(), LastUse::exists) {
+        let _lock =
+            Filesystem::new(dest.clone()).open_rw(".cargo-lock", config, "build directory")?;
+        let mut last_use = LastUse::load(&dest);
+        gc_dest(&dest, &mut last_use, opts, &HashSet::new(), &mut ctx)?;
+    }
+    ctx.display_summary()
+}
+
+/// Collects the target directory of `ws` after a build, with the limits of
+/// the `[gc.target-dir]` configuration if there are any.
+///
+/// Each build directory is collected at most once a day. The units `in_use`
+/// by the build which just finished are never removed.
+pub fn auto_gc(ws: &Workspace<'_>, in_use: &HashSet<String>) -> CargoResult<()> {
+    let config = ws.config();
+    if !config.cli_unstable().target_dir_gc {
+        return Ok(());
+    }
+    let opts = GcOptions::from_config(config)?;
+    if opts.max_unused_days.is_none() && opts.max_size.is_none() {
+        return Ok(());
+    }
+    let now = unix_now();
+    let mut ctx = CleanContext::new(config, false);
+    for dest in build_dests(&ws.target_dir().into_path_unlocked(), LastUse::exists) {
+        let _lock =
+            Filesystem::new(dest.clone()).open_rw(".cargo-lock", config, "build directory")?;
+        let mut last_use = LastUse::load(&dest);
+        let collected_recently = last_use.last_gc.map_or(false, |last_gc| {
+            now.saturating_sub(last_gc) < SECONDS_PER_DAY
+        });
+        if collected_recently {
+            continue;
+        }
+        gc_dest(&dest, &mut last_use, &opts, in_use, &mut ctx)?;
+    }
+    if ctx.num_files_removed > 0 {
+        ctx.display_summary()?;
+    }
+    Ok(())
+}
+
+/// Returns the build directories under the target directory `root` for which
+/// `pred` holds, like `target/debug` or `target/$TRIPLE/release`.
+pub(crate) fn build_dests(root: &Path, pred: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir() && pred(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Removes the units of `last_use` which are no longer needed from the build
+/// directory `dest`, see [`gc`]. The units `in_use` are kept.
+fn gc_dest(
+    dest: &Path,
+    last_use: &mut LastUse,
+    opts: &GcOptions,
+    in_use: &HashSet<String>,
+    ctx: &mut CleanContext<'_>,
+) -> CargoResult<()> {
+    let now = unix_now();
+    last_use.lockfiles.retain(|path| path.exists());
+    let locked = locked_packages(&last_use.lockfiles);
+
+    let mut stale = Vec::new();
+    let mut kept = Vec::new();
+    for (name, unit) in &last_use.units {
+        if in_use.contains(name) {
+            continue;
+        }
+        let unlocked = locked.as_ref().map_or(false, |locked| {
+            !locked.contains(&(unit.name.clone(), unit.version.clone()))
+        });
+        let unused = opts.max_unused_days.map_or(false, |days| {
+            now.saturating_sub(unit.last_use) > u64::from(days) * SECONDS_PER_DAY
+        });
+        if unlocked || unused {
+            stale.push(name.clone());
+        } else {
+            kept.push((unit.last_use, name.clone()));
+        }
+    }
+    if let Some(max_size) = opts.max_size {
+        // Only the files of the recorded units count towards the size, as
+        // they are the only ones which can be removed.
+        let mut size = 0;
+        for name in last_use.units.keys() {
+            if !stale.contains(name) {
+                size += paths_size(&unit_paths(dest, name)?);
+            }
+        }
+        kept.sort();
+        for (_, name) in kept {
+            if size <= max_size {
+                break;
+            }
+            size = size.saturating_sub(paths_size(&unit_paths(dest, &name)?));
+            stale.push(name);
+        }
+    }
+
+    let mut progress = CleaningPackagesBar::new(ctx.config, stale.len());
+    for name in &stale {
+        progress.on_cleaning_package(name)?;
+        for path in unit_paths(dest, name)? {
+            rm_rf(&path, ctx, &mut progress)?;
+        }
+    }
+    if !ctx.dry_run {
+        for name in &stale {
+            last_use.units.remove(name);
+        }
+        last_use.last_gc = Some(now);
+        last_use.save(dest)?;
+    }
+    Ok(())
+}
+
+/// Returns the `(name, version)` of the packages in `lockfiles`, or `None` if
+/// they aren't all known.
+fn locked_packages(lockfiles: &BTreeSet<PathBuf>) -> Option<HashSet<(String, String)>> {
+    #[derive(Deserialize)]
+    struct Lockfile {
+        #[serde(default)]
+        package: Vec<LockedPackage>,
+    }
+    #[derive(Deserialize)]
+    struct LockedPackage {
+        name: String,
+        version: String,
+    }
+
+    if lockfiles.is_empty() {
+        return None;
+    }
+    let mut locked = HashSet::new();
+    for path in lockfiles {
+        let lockfile: Lockfile = toml::from_str(&paths::read(path).ok()?).ok()?;
+        locked.extend(lockfile.package.into_iter().map(|p| (p.name, p.version)));
+    }
+    Some(locked)
+}
+
+/// Returns the paths in the build directory `dest` of the unit whose
+/// fingerprint directory is `name`.
+fn unit_paths(dest: &Path, name: &str) -> CargoResult<Vec<PathBuf>> {
+    let mut unit_paths = vec![
+        dest.join(".fingerprint").join(name),
+        dest.join("build").join(name),
+        dest.join("deps").join("artifact").join(name),
+    ];
+    // The files of the unit in `deps` and `examples` have the same metadata
+    // hash as its fingerprint directory.
+    let Some((_, meta)) = name.rsplit_once('-') else {
+        return Ok(unit_paths);
+    };
+    for dir in ["deps", "examples"] {
+        let dir_glob = escape_glob_path(&dest.join(dir))?;
+        for pattern in [format!("*-{meta}"), format!("*-{meta}.*")] {
+            let pattern = Path::new(&dir_glob).join(pattern);
+            let pattern = pattern
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("expected utf-8 path"))?;
+            for path in glob::glob(pattern)? {
+                unit_paths.push(path?);
+            }
+        }
+    }
+    Ok(unit_paths)
+}
+
+fn paths_size(paths: &[PathBuf]) -> u64 {
+    paths.iter().map(|path| dir_size(path)).sum()
+}
+
+/// Returns the total size of the files under `path`.
+fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
 fn escape_glob_path(pattern: &Path) -> CargoResult<String> {
     let pattern = pattern
         .to_str()
@@ -242,7 +690,7 @@ fn escape_glob_path(pattern: &Path) -> CargoResult<String> {
 fn rm_rf_package_glob_containing_hash(
     package: &str,
     pattern: &Path,
-    config: &Config,
+    ctx: &mut CleanContext<'_>,
     progress: &mut dyn CleaningProgressBar,
 ) -> CargoResult<()> {
     // TODO: Display utf8 warning to user?  Or switch to globset?
@@ -263,14 +711,14 @@ fn rm_rf_package_glob_containing_hash(
             continue;
         }
 
-        rm_rf(&path, config, progress)?;
+        rm_rf(&path, ctx, progress)?;
     }
     Ok(())
 }
 
 fn rm_rf_glob(
     pattern: &Path,
-    config: &Config,
+    ctx: &mut CleanContext<'_>,
     progress: &mut dyn CleaningProgressBar,
 ) -> CargoResult<()> {
     // TODO: Display utf8 warning to user?  Or switch to globset?
@@ -278,17 +726,21 @@ fn rm_rf_glob(
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("expected utf-8 path"))?;
     for path in glob::glob(pattern)? {
-        rm_rf(&path?, config, progress)?;
+        rm_rf(&path?, ctx, progress)?;
     }
     Ok(())
 }
 
-fn rm_rf(path: &Path, config: &Config, progress: &mut dyn CleaningProgressBar) -> CargoResult<()> {
+fn rm_rf(
+    path: &Path,
+    ctx: &mut CleanContext<'_>,
+    progress: &mut dyn CleaningProgressBar,
+) -> CargoResult<()> {
     if fs::symlink_metadata(path).is_err() {
         return Ok(());
     }
 
-    config
+    ctx.config
         .shell()
         .verbose(|shell| shell.status("Removing", path.display()))?;
     progress.display_now()?;
@@ -297,6 +749,17 @@ fn rm_rf(path: &Path, config: &Config, progress: &mut dyn CleaningProgressBar) -
         let entry = entry?;
         progress.on_clean()?;
         if entry.file_type().is_dir() {
+            if ctx.dry_run {
+                continue;
+            }
+            if ctx.keep_after.is_some() {
+                // Directories still holding recent files are kept.
+                if fs::read_dir(entry.path())?.next().is_none() {
+                    paths::remove_dir(entry.path())
+                        .with_context(|| "could not remove build directory")?;
+                }
+                continue;
+            }
             // The contents should have been removed by now, but sometimes a race condition is hit
             // where other files have been added by the OS. `paths::remove_dir_all` also falls back
             // to `std::fs::remove_dir_all`, which may be more reliable than a simple walk in
@@ -304,17 +767,28 @@ fn rm_rf(path: &Path, config: &Config, progress: &mut dyn CleaningProgressBar) -
             paths::remove_dir_all(entry.path())
                 .with_context(|| "could not remove build directory")?;
         } else {
-            paths::remove_file(entry.path()).with_context(|| "failed to remove build artifact")?;
+            let meta = entry.metadata()?;
+            if let Some(keep_after) = ctx.keep_after {
+                if meta.modified()? > keep_after {
+                    continue;
+                }
+            }
+            ctx.num_files_removed += 1;
+            ctx.total_bytes_removed += meta.len();
+            if !ctx.dry_run {
+                paths::remove_file(entry.path())
+                    .with_context(|| "failed to remove build artifact")?;
+            }
         }
     }
 
     Ok(())
 }
 
-fn clean_entire_folder(path: &Path, config: &Config) -> CargoResult<()> {
+fn clean_entire_folder(path: &Path, ctx: &mut CleanContext<'_>) -> CargoResult<()> {
     let num_paths = walkdir::WalkDir::new(path).into_iter().count();
-    let mut progress = CleaningFolderBar::new(config, num_paths);
-    rm_rf(path, config, &mut progress)
+    let mut progress = CleaningFolderBar::new(ctx.config, num_paths);
+    rm_rf(path, ctx, &mut progress)
 }
 
 trait CleaningProgressBar {
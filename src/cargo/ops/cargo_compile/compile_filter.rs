@@ -243,7 +243,7 @@ impl CompileFilter {
     /// [`filter_default_targets`]: super::UnitGenerator::filter_default_targets
     pub fn target_run(&self, target: &Target) -> bool {
         match *self {
-            CompileFilter::Default { .. } => true,
+            CompileFilter::Default { .. } => !target.requires_explicit(),
             CompileFilter::Only {
                 ref lib,
                 ref bins,
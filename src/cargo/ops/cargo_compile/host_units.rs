@@ -0,0 +1,78 @@
+//! Checks that the units built for the host can be built, before the build
+//! starts.
+//!
+//! Build scripts and proc-macros are built for the host, even when cross
+//! compiling. If the standard library or the linker of the host is missing,
+//! `rustc` would only fail once it reaches the first of them, with an error
+//! from the linker which doesn't point to the host at all. This is mostly the
+//! case with `--proc-macro-host`, which builds them for another triple than
+//! the host of `rustc`.
+
+use anyhow::bail;
+
+use crate::core::compiler::{BuildContext, CompileKind, Unit};
+use crate::util::CargoResult;
+
+/// Fails if the host units of `bcx` cannot be built, when cross compiling.
+pub fn validate(bcx: &BuildContext<'_, '_>) -> CargoResult<()> {
+    if bcx
+        .build_config
+        .requested_kinds
+        .iter()
+        .any(CompileKind::is_host)
+    {
+        // Everything is built for the host, `rustc` errors are clear enough.
+        return Ok(());
+    }
+    let Some(unit) = bcx
+        .unit_graph
+        .keys()
+        .filter(|unit| unit.kind.is_host() && !unit.mode.is_run_custom_build())
+        .min()
+    else {
+        return Ok(());
+    };
+    let host = bcx.target_data.short_name(&CompileKind::Host);
+    let proc_macro_host = bcx.target_data.proc_macro_host().is_some();
+
+    let libdir = &bcx
+        .target_data
+        .info(CompileKind::Host)
+        .sysroot_target_libdir;
+    if !libdir.is_dir() {
+        let hint = if proc_macro_host {
+            format!(
+                "install it with `rustup target add {host}`, or pass another `--proc-macro-host`"
+            )
+        } else {
+            String::from("the installation of `rustc` looks incomplete")
+        };
+        bail!(
+            "cannot build {} for the host `{host}`\n\n\
+             The standard library for `{host}` is not installed at `{}`, {hint}.",
+            describe(unit),
+            libdir.display(),
+        );
+    }
+
+    if proc_macro_host && bcx.linker(CompileKind::Host).is_none() {
+        bail!(
+            "cannot build {} for the host `{host}`\n\n\
+             No linker is configured for `{host}`, set `target.{host}.linker` \
+             in the Cargo configuration.",
+            describe(unit),
+        );
+    }
+    Ok(())
+}
+
+fn describe(unit: &Unit) -> String {
+    let pkg = unit.pkg.package_id();
+    if unit.target.proc_macro() {
+        format!("proc-macro `{pkg}`")
+    } else if unit.target.is_custom_build() {
+        format!("the build script of `{pkg}`")
+    } else {
+        format!("`{}` of `{pkg}`", unit.target.name())
+    }
+}
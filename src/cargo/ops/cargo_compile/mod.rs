@@ -35,10 +35,12 @@
 //! [`drain_the_queue`]: crate::core::compiler::job_queue
 //! ["Cargo Target"]: https://doc.rust-lang.org/nightly/cargo/reference/cargo-targets.html
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::core::compiler::fingerprint;
 use crate::core::compiler::unit_dependencies::build_unit_dependencies;
 use crate::core::compiler::unit_graph::{self, UnitDep, UnitGraph};
 use crate::core::compiler::{standard_lib, CrateType, TargetInfo};
@@ -64,8 +66,12 @@ use unit_generator::UnitGenerator;
 
 mod packages;
 
+pub(crate) use packages::build_glob;
 pub use packages::Packages;
 
+mod host_units;
+mod static_linking;
+
 /// Contains information about how a package should be compiled.
 ///
 /// Note on distinction between `CompileOptions` and [`BuildConfig`]:
@@ -153,9 +159,97 @@ pub fn compile_ws<'a>(
         unit_graph::emit_serialized_unit_graph(&bcx.roots, &bcx.unit_graph, ws.config())?;
         return Compilation::new(&bcx);
     }
+    host_units::validate(&bcx)?;
+    if options.build_config.static_linking {
+        static_linking::hints(&bcx)?;
+    }
     let _p = profile::start("compiling");
     let cx = Context::new(&bcx)?;
-    cx.compile(exec)
+    let compilation = cx.compile(exec)?;
+    if options.build_config.static_linking {
+        static_linking::report_shared_libs(&bcx, &compilation)?;
+    }
+    ops::auto_gc(ws, &compilation.last_used_units)?;
+    Ok(compilation)
+}
+
+/// Computes the unit graph for `options` without building anything.
+///
+/// This returns the same information as `--unit-graph`, for library users
+/// who would rather not parse it back from stdout.
+pub fn unit_graph(
+    ws: &Workspace<'_>,
+    options: &CompileOptions,
+) -> CargoResult<unit_graph::SerializedUnitGraph> {
+    let interner = UnitInterner::new();
+    let bcx = create_bcx(ws, options, &interner)?;
+    Ok(unit_graph::serialize_unit_graph(
+        &bcx.roots,
+        &bcx.unit_graph,
+        ws.config(),
+    ))
+}
+
+/// Source files mapped to the units which depend on them, see [`build_closure`].
+#[derive(serde::Serialize)]
+pub struct BuildClosure {
+    /// The unit graph of the compile options, as returned by [`unit_graph`].
+    pub unit_graph: unit_graph::SerializedUnitGraph,
+    /// Every file listed in the dep-info of a unit by the last build, with
+    /// the indices into `unit_graph.units` of the units listing it.
+    pub files: BTreeMap<PathBuf, Vec<usize>>,
+}
+
+impl BuildClosure {
+    /// The units whose dep-info lists `path`, which are rebuilt when it
+    /// changes.
+    ///
+    /// The units depending on them are rebuilt too, but aren't returned: they
+    /// can be found from the `dependencies` of the units of `unit_graph`.
+    ///
+    /// `path` must be absolute, like the paths in the dep-info.
+    pub fn units_for(&self, path: &Path) -> impl Iterator<Item = &unit_graph::SerializedUnit> {
+        self.files
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.unit_graph.units[index])
+    }
+}
+
+/// Maps the source files of the last build with `options` to the units whose
+/// dep-info lists them, without building anything.
+///
+/// This answers which units saving a file invalidates, for editors and other
+/// tools. Units which were never built list no files.
+pub fn build_closure(ws: &Workspace<'_>, options: &CompileOptions) -> CargoResult<BuildClosure> {
+    let interner = UnitInterner::new();
+    let bcx = create_bcx(ws, options, &interner)?;
+    let mut cx = Context::new(&bcx)?;
+    // Only the paths of the dep-info files are needed, so this neither
+    // creates the build directory nor waits for a build holding its lock.
+    cx.prepare_units_unlocked()?;
+
+    // Sorted like the units of the serialized unit graph.
+    let mut units: Vec<&Unit> = bcx.unit_graph.keys().collect();
+    units.sort_unstable();
+    let mut files: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+    for (index, unit) in units.into_iter().enumerate() {
+        let dep_info = fingerprint::dep_info_loc(&mut cx, unit);
+        let target_root = fingerprint::target_root(&cx, unit);
+        let Some(info) = fingerprint::parse_dep_info(unit.pkg.root(), &target_root, &dep_info)?
+        else {
+            continue;
+        };
+        for file in info.files {
+            files.entry(file).or_default().push(index);
+        }
+    }
+
+    Ok(BuildClosure {
+        unit_graph: unit_graph::serialize_unit_graph(&bcx.roots, &bcx.unit_graph, ws.config()),
+        files,
+    })
 }
 
 /// Executes `rustc --print <VALUE>`.
@@ -177,7 +271,14 @@ pub fn print<'a>(
         if index != 0 {
             drop_println!(config);
         }
-        let target_info = TargetInfo::new(config, &build_config.requested_kinds, &rustc, *kind)?;
+        let target_info = TargetInfo::new(
+            config,
+            &build_config.requested_kinds,
+            &rustc,
+            *kind,
+            build_config.static_linking,
+            build_config.proc_macro_host,
+        )?;
         let mut process = rustc.process();
         process.args(&target_info.rustflags);
         if let Some(args) = target_rustc_args {
@@ -237,7 +338,7 @@ pub fn create_bcx<'a, 'cfg>(
     }
     config.validate_term_config()?;
 
-    let target_data = RustcTargetData::new(ws, &build_config.requested_kinds)?;
+    let target_data = RustcTargetData::for_build_config(ws, build_config)?;
 
     let specs = spec.to_package_id_specs(ws)?;
     let has_dev_units = {
@@ -419,6 +520,9 @@ pub fn create_bcx<'a, 'cfg>(
     // what heuristics to use in that case.
     if build_config.mode == (CompileMode::Doc { deps: true }) {
         remove_duplicate_doc(build_config, &units, &mut unit_graph);
+        if build_config.depgraph_docs {
+            remove_private_dep_docs(ws, &resolve, &units, &mut unit_graph);
+        }
     }
 
     let host_kind_requested = build_config
@@ -835,6 +939,58 @@ fn remove_duplicate_doc(
     unit_graph.retain(|unit, _| visited.contains(unit));
 }
 
+/// Removes the documentation of dependencies which are not part of the public
+/// API of the workspace members, for `cargo doc --depgraph-docs`.
+///
+/// A dependency is documented when it is a workspace member, or a public
+/// dependency of a package which is itself documented. Its items can then
+/// show up in the documentation of the workspace members, through
+/// re-exports or in the signatures of public items. The `Check` units of the
+/// other dependencies are kept, since rustdoc still needs their metadata.
+fn remove_private_dep_docs(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    root_units: &[Unit],
+    unit_graph: &mut UnitGraph,
+) {
+    for (unit, deps) in unit_graph.iter_mut() {
+        // The standard library is resolved separately, leave it alone.
+        if !unit.mode.is_doc() || unit.is_std {
+            continue;
+        }
+        let pkg_id = unit.pkg.package_id();
+        deps.retain(|dep| {
+            let dep_id = dep.unit.pkg.package_id();
+            let keep = !dep.unit.mode.is_doc()
+                || dep.unit.is_std
+                || ws.is_member(&dep.unit.pkg)
+                || resolve.is_public_dep(pkg_id, dep_id);
+            if !keep {
+                tracing::debug!(
+                    "not documenting {} since it is a private dependency of {}",
+                    dep_id,
+                    pkg_id
+                );
+            }
+            keep
+        });
+    }
+    // Remove the units which are no longer reachable from the roots.
+    let mut visited = HashSet::new();
+    fn visit(unit: &Unit, graph: &UnitGraph, visited: &mut HashSet<Unit>) {
+        if !visited.insert(unit.clone()) {
+            return;
+        }
+        for dep in &graph[unit] {
+            visit(&dep.unit, graph, visited);
+        }
+    }
+    for unit in root_units {
+        visit(unit, unit_graph, &mut visited);
+    }
+    unit_graph.retain(|unit, _| visited.contains(unit));
+}
+
 /// Override crate types for given units.
 ///
 /// This is primarily used by `cargo rustc --crate-type`.
@@ -0,0 +1,299 @@
+//! Helpers for `cargo build --static`.
+//!
+//! The C runtime itself is linked statically by [`RustcTargetData`] adding
+//! `-C target-feature=+crt-static` to the flags of the units built for the
+//! requested targets. This module covers what `rustc` cannot do on its own:
+//!
+//! * [`hints`] points out targets which cannot produce fully static
+//!   executables, and the features making `-sys` crates bundle their native
+//!   library.
+//! * [`report_shared_libs`] reads the executables once built, and warns about
+//!   the shared libraries they still depend on.
+//!
+//! [`RustcTargetData`]: crate::core::compiler::RustcTargetData
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use cargo_util::paths;
+
+use crate::core::compiler::{BuildContext, Compilation};
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+
+/// Prefixes of the features commonly used by `-sys` crates to build and link
+/// their native library statically, like `vendored` for `openssl-sys` or
+/// `bundled` for `libsqlite3-sys`.
+const BUNDLING_FEATURE_PREFIXES: &[&str] = &["vendored", "bundled", "static"];
+
+/// Prints notes about the requested targets and the packages linking to
+/// native libraries, before the build starts.
+pub fn hints(bcx: &BuildContext<'_, '_>) -> CargoResult<()> {
+    let mut shell = bcx.config.shell();
+    for kind in &bcx.build_config.requested_kinds {
+        let triple = bcx.target_data.short_name(kind);
+        if triple.contains("-linux-gnu") {
+            shell.note(format!(
+                "glibc does not fully support static linking, consider building for \
+                 `--target {}` instead of `{triple}`",
+                triple.replacen("-linux-gnu", "-linux-musl", 1)
+            ))?;
+        } else if triple.contains("-apple-") {
+            shell.warn(format!(
+                "`{triple}` does not support linking the C runtime statically, \
+                 executables will still depend on the system libraries"
+            ))?;
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+    for unit in bcx.unit_graph.keys() {
+        let Some(links) = unit.pkg.manifest().links() else {
+            continue;
+        };
+        if unit.kind.is_host() || !seen.insert(unit.pkg.package_id()) {
+            continue;
+        }
+        let candidates: Vec<InternedString> = unit
+            .pkg
+            .summary()
+            .features()
+            .keys()
+            .copied()
+            .filter(|feature| {
+                BUNDLING_FEATURE_PREFIXES
+                    .iter()
+                    .any(|prefix| feature.starts_with(*prefix))
+            })
+            .collect();
+        if candidates.is_empty() || candidates.iter().any(|f| unit.features.contains(f)) {
+            continue;
+        }
+        let candidates = candidates
+            .iter()
+            .map(|feature| format!("`{feature}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        suggestions.push((
+            unit.pkg.package_id(),
+            format!(
+                "`{} v{}` links to the native library `{links}`, enabling one of its \
+                 features {candidates} may link it statically",
+                unit.pkg.name(),
+                unit.pkg.version()
+            ),
+        ));
+    }
+    suggestions.sort();
+    for (_, suggestion) in suggestions {
+        shell.note(suggestion)?;
+    }
+    Ok(())
+}
+
+/// Warns about each executable of `compilation` which still depends on
+/// shared libraries.
+///
+/// Only ELF executables are inspected, other formats are skipped.
+pub fn report_shared_libs(
+    bcx: &BuildContext<'_, '_>,
+    compilation: &Compilation<'_>,
+) -> CargoResult<()> {
+    for output in &compilation.binaries {
+        if output.unit.kind.is_host() {
+            continue;
+        }
+        let Some(needed) = needed_libraries(&output.path)? else {
+            tracing::debug!("not inspecting `{}`, it is not ELF", output.path.display());
+            continue;
+        };
+        if needed.is_empty() {
+            continue;
+        }
+        let needed = needed
+            .iter()
+            .map(|lib| format!("`{lib}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        bcx.config.shell().warn(format!(
+            "`{}` is not fully static, it depends on the shared libraries {needed}",
+            output.path.display()
+        ))?;
+    }
+    Ok(())
+}
+
+/// Reads the `DT_NEEDED` entries of the ELF file at `path`, that is the
+/// shared libraries it is linked against.
+///
+/// Returns `None` if `path` is not an ELF file, or if it is malformed.
+fn needed_libraries(path: &Path) -> CargoResult<Option<Vec<String>>> {
+    let data = paths::read_bytes(path)?;
+    Ok(Elf::parse(&data).and_then(|elf| elf.needed()))
+}
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+
+/// Just enough of an ELF reader to find the `DT_NEEDED` entries.
+struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    is_le: bool,
+}
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Option<Elf<'a>> {
+        if data.get(..4)? != b"\x7fELF" {
+            return None;
+        }
+        let is_64 = match data.get(4)? {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let is_le = match data.get(5)? {
+            1 => true,
+            2 => false,
+            _ => return None,
+        };
+        Some(Elf { data, is_64, is_le })
+    }
+
+    fn bytes<const N: usize>(&self, offset: u64) -> Option<[u8; N]> {
+        let offset = usize::try_from(offset).ok()?;
+        self.data
+            .get(offset..offset.checked_add(N)?)?
+            .try_into()
+            .ok()
+    }
+
+    fn u16(&self, offset: u64) -> Option<u64> {
+        let b = self.bytes(offset)?;
+        Some(u64::from(if self.is_le {
+            u16::from_le_bytes(b)
+        } else {
+            u16::from_be_bytes(b)
+        }))
+    }
+
+    fn u32(&self, offset: u64) -> Option<u64> {
+        let b = self.bytes(offset)?;
+        Some(u64::from(if self.is_le {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        }))
+    }
+
+    fn u64(&self, offset: u64) -> Option<u64> {
+        let b = self.bytes(offset)?;
+        Some(if self.is_le {
+            u64::from_le_bytes(b)
+        } else {
+            u64::from_be_bytes(b)
+        })
+    }
+
+    /// Reads an address or offset, whose size depends on the class.
+    fn word(&self, offset: u64) -> Option<u64> {
+        if self.is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset)
+        }
+    }
+
+    /// The `(p_type, p_offset, p_vaddr, p_filesz)` of each program header.
+    fn segments(&self) -> Option<Vec<(u32, u64, u64, u64)>> {
+        let (phoff, phentsize, phnum) = if self.is_64 {
+            (self.u64(0x20)?, self.u16(0x36)?, self.u16(0x38)?)
+        } else {
+            (self.u32(0x1c)?, self.u16(0x2a)?, self.u16(0x2c)?)
+        };
+        (0..phnum)
+            .map(|i| {
+                let ph = phoff.checked_add(i * phentsize)?;
+                let p_type = self.u32(ph)? as u32;
+                Some(if self.is_64 {
+                    (
+                        p_type,
+                        self.u64(ph + 8)?,
+                        self.u64(ph + 16)?,
+                        self.u64(ph + 32)?,
+                    )
+                } else {
+                    (
+                        p_type,
+                        self.u32(ph + 4)?,
+                        self.u32(ph + 8)?,
+                        self.u32(ph + 16)?,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn needed(&self) -> Option<Vec<String>> {
+        let segments = self.segments()?;
+        let Some(&(_, dynamic, _, dynamic_size)) =
+            segments.iter().find(|(p_type, ..)| *p_type == PT_DYNAMIC)
+        else {
+            // Not dynamically linked at all.
+            return Some(Vec::new());
+        };
+
+        let entry_size = if self.is_64 { 16 } else { 8 };
+        let mut strtab = None;
+        let mut needed = Vec::new();
+        for i in 0..dynamic_size / entry_size {
+            let entry = dynamic.checked_add(i * entry_size)?;
+            let tag = self.word(entry)?;
+            let value = self.word(entry + entry_size / 2)?;
+            match tag {
+                DT_NULL => break,
+                DT_NEEDED => needed.push(value),
+                DT_STRTAB => strtab = Some(value),
+                _ => {}
+            }
+        }
+        if needed.is_empty() {
+            return Some(Vec::new());
+        }
+
+        // `DT_STRTAB` is an address, find where it is loaded from.
+        let strtab = strtab?;
+        let &(_, offset, vaddr, _) = segments.iter().find(|&&(p_type, _, vaddr, size)| {
+            p_type == PT_LOAD && vaddr <= strtab && strtab < vaddr.saturating_add(size)
+        })?;
+        let strtab = usize::try_from(strtab - vaddr + offset).ok()?;
+        needed
+            .into_iter()
+            .map(|name| {
+                let start = strtab.checked_add(usize::try_from(name).ok()?)?;
+                let name = self.data.get(start..)?;
+                let end = name.iter().position(|&b| b == 0)?;
+                Some(String::from_utf8_lossy(&name[..end]).into_owned())
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, target_os = "linux", target_env = "gnu"))]
+mod tests {
+    use super::needed_libraries;
+
+    #[test]
+    fn needed_libraries_of_current_exe() {
+        let exe = std::env::current_exe().unwrap();
+        let needed = needed_libraries(&exe).unwrap().unwrap();
+        assert!(
+            needed.iter().any(|lib| lib.starts_with("libc.so")),
+            "{needed:?}"
+        );
+    }
+}
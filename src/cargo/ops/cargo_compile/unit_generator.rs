@@ -180,21 +180,21 @@ impl<'a> UnitGenerator<'a, '_> {
 
     /// Given a list of all targets for a package, filters out only the targets
     /// that are automatically included when the user doesn't specify any targets.
+    ///
+    /// Targets with `requires-explicit` are never included.
     fn filter_default_targets<'b>(&self, targets: &'b [Target]) -> Vec<&'b Target> {
+        let candidates = targets.iter().filter(|t| !t.requires_explicit());
         match self.mode {
-            CompileMode::Bench => targets.iter().filter(|t| t.benched()).collect(),
-            CompileMode::Test => targets
-                .iter()
+            CompileMode::Bench => candidates.filter(|t| t.benched()).collect(),
+            CompileMode::Test => candidates
                 .filter(|t| t.tested() || t.is_example())
                 .collect(),
-            CompileMode::Build | CompileMode::Check { .. } => targets
-                .iter()
-                .filter(|t| t.is_bin() || t.is_lib())
-                .collect(),
+            CompileMode::Build | CompileMode::Check { .. } => {
+                candidates.filter(|t| t.is_bin() || t.is_lib()).collect()
+            }
             CompileMode::Doc { .. } => {
                 // `doc` does lib and bins (bin with same name as lib is skipped).
-                targets
-                    .iter()
+                candidates
                     .filter(|t| {
                         t.documented()
                             && (!t.is_bin()
@@ -299,7 +299,11 @@ impl<'a> UnitGenerator<'a, '_> {
     ) -> CargoResult<Vec<Proposal<'a>>> {
         let mut proposals = Vec::new();
         match rule {
-            FilterRule::All => proposals.extend(self.filter_targets(is_expected_kind, false, mode)),
+            FilterRule::All => {
+                // Targets with `requires-explicit` are only built when named.
+                let filter = |t: &Target| is_expected_kind(t) && !t.requires_explicit();
+                proposals.extend(self.filter_targets(filter, false, mode))
+            }
             FilterRule::Just(names) => {
                 for name in names {
                     proposals.extend(self.find_named_targets(
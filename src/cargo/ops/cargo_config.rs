@@ -1,5 +1,6 @@
 //! Implementation of `cargo config` subcommand.
 
+use crate::sources::{mirror, SourceConfigMap};
 use crate::util::config::{Config, ConfigKey, ConfigValue as CV, Definition};
 use crate::util::errors::CargoResult;
 use crate::{drop_eprintln, drop_println};
@@ -62,9 +63,10 @@ pub fn get(config: &Config, opts: &GetOptions<'_>) -> CargoResult<()> {
         None => ConfigKey::new(),
     };
     if opts.merged {
-        let cv = config
+        let mut cv = config
             .get_cv_with_env(&key)?
             .ok_or_else(|| format_err!("config value `{}` is not set", key))?;
+        merge_env(config, &key, &mut cv)?;
         match opts.format {
             ConfigFormat::Toml => print_toml(config, opts, &key, &cv),
             ConfigFormat::Json => print_json(config, &key, &cv, true),
@@ -88,6 +90,28 @@ pub fn get(config: &Config, opts: &GetOptions<'_>) -> CargoResult<()> {
     Ok(())
 }
 
+/// Replaces the values in the table `cv` which are overridden by environment
+/// variables, like `CARGO_PROFILE_RELEASE_LTO` for `profile.release.lto`.
+///
+/// Environment variables for keys which aren't in a config file can't be
+/// mapped back to a key, since `-` and `.` both become `_`. Those are only
+/// listed by [`maybe_env`].
+fn merge_env(config: &Config, key: &ConfigKey, cv: &mut CV) -> CargoResult<()> {
+    let CV::Table(map, _def) = cv else {
+        return Ok(());
+    };
+    for (table_key, val) in map.iter_mut() {
+        let mut subkey = key.clone();
+        subkey.push(table_key);
+        if let CV::Table(..) = val {
+            merge_env(config, &subkey, val)?;
+        } else if let Some(env_cv) = config.get_cv_with_env(&subkey)? {
+            *val = env_cv;
+        }
+    }
+    Ok(())
+}
+
 /// Checks for environment variables that might be used.
 fn maybe_env<'config>(
     config: &'config Config,
@@ -308,3 +332,34 @@ fn print_toml_unmerged(config: &Config, opts: &GetOptions<'_>, key: &ConfigKey)
     }
     Ok(())
 }
+
+/// Checks that each source replacement of the `[source]` table can be
+/// reached, for `cargo config check-mirrors`.
+pub fn check_mirrors(config: &Config) -> CargoResult<()> {
+    let map = SourceConfigMap::new(config)?;
+    let replacements = map.replacements()?;
+    if replacements.is_empty() {
+        config
+            .shell()
+            .note("no sources are replaced in the `[source]` table")?;
+        return Ok(());
+    }
+    let mut unreachable = 0;
+    for (orig_name, name, id) in &replacements {
+        let url = id.url();
+        match mirror::check(config, *id) {
+            Ok(()) => drop_println!(config, "{orig_name} -> {name} ({url}): ok"),
+            Err(e) => {
+                unreachable += 1;
+                drop_println!(config, "{orig_name} -> {name} ({url}): unreachable: {e:#}");
+            }
+        }
+    }
+    if unreachable > 0 {
+        bail!(
+            "{unreachable} of {} source replacements are unreachable",
+            replacements.len()
+        );
+    }
+    Ok(())
+}
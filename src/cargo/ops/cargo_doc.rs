@@ -36,7 +36,7 @@ pub fn doc(ws: &Workspace<'_>, options: &DocOptions) -> CargoResult<()> {
             };
 
             let mut shell = ws.config().shell();
-            shell.status("Opening", path.display())?;
+            shell.status("Opening", ws.display_path(&path))?;
             open_docs(&path, &mut shell, config_browser, ws.config())?;
         }
     }
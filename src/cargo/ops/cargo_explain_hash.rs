@@ -0,0 +1,81 @@
+//! Implementation of `cargo explain-hash`, which maps the metadata hash in the
+//! name of an artifact back to the unit it was built for.
+//!
+//! The hashes are recorded in the build directories of the target directory
+//! by builds with `-Zmetadata-hash`, see [`MetadataHashes`].
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::bail;
+
+use crate::core::compiler::{HashedUnit, MetadataHash, MetadataHashes};
+use crate::core::Workspace;
+use crate::drop_print;
+use crate::ops::cargo_clean::build_dests;
+use crate::util::errors::CargoResult;
+
+/// Prints the unit whose metadata hash is in `file_name`, or all the recorded
+/// units without a file name.
+pub fn explain_hash(ws: &Workspace<'_>, file_name: Option<&str>) -> CargoResult<()> {
+    let config = ws.config();
+    let target_dir = ws.target_dir().into_path_unlocked();
+    let mut units = BTreeMap::new();
+    for dest in build_dests(&target_dir, MetadataHashes::exists) {
+        for (hash, unit) in MetadataHashes::load(&dest).units {
+            units.insert(hash, (unit, dest.clone()));
+        }
+    }
+
+    let mut out = String::new();
+    match file_name {
+        Some(file_name) => {
+            let Some(hash) = hash_of(file_name) else {
+                bail!("`{}` doesn't contain a metadata hash", file_name);
+            };
+            let Some((unit, dest)) = units.get(hash) else {
+                bail!(
+                    "no unit with the metadata hash `{}` was found in `{}`, \
+                     hashes are only recorded by builds with `-Zmetadata-hash`",
+                    hash,
+                    ws.display_path(&target_dir)
+                );
+            };
+            describe(&mut out, hash, unit, &ws.display_path(dest));
+        }
+        None => {
+            for (hash, (unit, dest)) in &units {
+                describe(&mut out, hash, unit, &ws.display_path(dest));
+            }
+        }
+    }
+    drop_print!(config, "{}", out);
+    Ok(())
+}
+
+/// Extracts the metadata hash of a file name such as
+/// `libfoo-0123456789abcdef.rlib` or `foo-0123456789abcdef`, which may also
+/// be a path or the bare hash.
+fn hash_of(file_name: &str) -> Option<&str> {
+    let name = Path::new(file_name).file_name()?.to_str()?;
+    let stem = name.split('.').next()?;
+    let hash = stem.rsplit('-').next()?;
+    MetadataHash::is_hash(hash).then_some(hash)
+}
+
+fn describe(out: &mut String, hash: &str, unit: &HashedUnit, dest: &str) {
+    let features = if unit.features.is_empty() {
+        "none".to_string()
+    } else {
+        unit.features.join(", ")
+    };
+    let _ = writeln!(out, "{}:", hash);
+    let _ = writeln!(out, "  package: {}", unit.package_id);
+    let _ = writeln!(out, "  target: {} `{}`", unit.target_kind, unit.target_name);
+    let _ = writeln!(out, "  mode: {}", unit.mode);
+    let _ = writeln!(out, "  profile: {}", unit.profile);
+    let _ = writeln!(out, "  features: {}", features);
+    let _ = writeln!(out, "  kind: {}", unit.kind.as_deref().unwrap_or("host"));
+    let _ = writeln!(out, "  directory: {}", dest);
+}
@@ -0,0 +1,184 @@
+//! Checks packages under combinations of their features, for
+//! `cargo check --feature-powerset`.
+//!
+//! Each combination is a separate compilation, with `--no-default-features`
+//! and the features of the combination. They all share the target
+//! directory, so a dependency is only checked again when the combination
+//! changes its own features.
+
+use std::collections::BTreeSet;
+
+use anyhow::format_err;
+use serde::Deserialize;
+
+use crate::core::resolver::CliFeatures;
+use crate::core::{Package, Workspace};
+use crate::drop_eprintln;
+use crate::ops::{self, CompileOptions, Packages};
+use crate::util::{CargoResult, CliResult};
+
+/// The maximum number of combinations checked for a single package, to avoid
+/// starting a build which would never end by accident.
+const MAX_COMBINATIONS: usize = 1024;
+
+/// The `[feature-powerset]` config table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct FeaturePowersetConfig {
+    /// The maximum number of features in a combination.
+    depth: Option<u32>,
+    /// Features left out of the combinations.
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Explicit feature sets to check instead of the powerset, each as a
+    /// list of features separated by commas or spaces.
+    sets: Option<Vec<String>>,
+}
+
+/// Checks each package selected by `options` with each combination of its
+/// features, then prints a summary of the outcome per package.
+///
+/// The combinations of a package are checked until one of them fails, and
+/// that one is reported.
+pub fn check_feature_powerset(ws: &Workspace<'_>, options: &CompileOptions) -> CliResult {
+    let config = ws.config();
+    let powerset = config
+        .get::<Option<FeaturePowersetConfig>>("feature-powerset")?
+        .unwrap_or_default();
+
+    let mut summary = Vec::new();
+    for pkg in options.spec.get_packages(ws)? {
+        let combinations = combinations(pkg, &powerset)?;
+        if combinations.is_empty() {
+            config.shell().warn(format!(
+                "none of the sets in `feature-powerset.sets` apply to `{}`",
+                pkg.name()
+            ))?;
+        }
+        let mut options = options.clone();
+        options.spec = Packages::Packages(vec![format!("{}@{}", pkg.name(), pkg.version())]);
+        let mut failed = None;
+        for features in &combinations {
+            let args = cli_args(features);
+            config
+                .shell()
+                .status("Features", format!("{} ({args})", pkg.package_id()))?;
+            options.cli_features = CliFeatures::from_command_line(features, false, false)?;
+            if let Err(e) = ops::compile(ws, &options) {
+                crate::display_error(&e, &mut config.shell());
+                failed = Some(args);
+                break;
+            }
+        }
+        summary.push((pkg.package_id().to_string(), combinations.len(), failed));
+    }
+
+    let failed: Vec<_> = summary
+        .iter()
+        .filter(|(_, _, failed)| failed.is_some())
+        .map(|(pkg, _, _)| pkg.as_str())
+        .collect();
+    config.shell().status(
+        "Summary",
+        format!(
+            "{} of {} packages passed",
+            summary.len() - failed.len(),
+            summary.len()
+        ),
+    )?;
+    let width = summary
+        .iter()
+        .map(|(pkg, _, _)| pkg.len())
+        .max()
+        .unwrap_or(0);
+    for (pkg, count, failed) in &summary {
+        match failed {
+            Some(args) => drop_eprintln!(config, "  {pkg:<width$}  FAILED (`{args}`)"),
+            None if *count == 1 => drop_eprintln!(config, "  {pkg:<width$}  ok (1 combination)"),
+            None => drop_eprintln!(config, "  {pkg:<width$}  ok ({count} combinations)"),
+        }
+    }
+
+    match failed.len() {
+        0 => Ok(()),
+        1 => Err(format_err!("a feature combination failed for `{}`", failed[0]).into()),
+        _ => Err(format_err!(
+            "feature combinations failed for {}",
+            failed
+                .iter()
+                .map(|pkg| format!("`{pkg}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into()),
+    }
+}
+
+/// The feature combinations to check for `pkg`, from the smallest to the
+/// largest.
+///
+/// These are either the sets of `feature-powerset.sets` which only contain
+/// features of `pkg`, or the combinations of its features with at most
+/// `feature-powerset.depth` of them, leaving out `feature-powerset.exclude`.
+fn combinations(pkg: &Package, powerset: &FeaturePowersetConfig) -> CargoResult<Vec<Vec<String>>> {
+    let features = pkg.summary().features();
+    if let Some(sets) = &powerset.sets {
+        return Ok(sets
+            .iter()
+            .map(|set| {
+                set.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|f| !f.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|set| set.iter().all(|f| features.contains_key(f.as_str())))
+            .collect());
+    }
+
+    let features: Vec<&str> = features
+        .keys()
+        .map(|f| f.as_str())
+        .filter(|f| !powerset.exclude.iter().any(|e| e == f))
+        .collect();
+    let depth = powerset
+        .depth
+        .map_or(features.len(), |depth| depth as usize)
+        .min(features.len());
+    let mut combinations: Vec<BTreeSet<&str>> = vec![BTreeSet::new()];
+    let mut previous = combinations.clone();
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for combination in &previous {
+            let last = combination.iter().next_back();
+            for &feature in &features {
+                if last.map_or(true, |&last| feature > last) {
+                    let mut combination = combination.clone();
+                    combination.insert(feature);
+                    next.push(combination);
+                }
+            }
+        }
+        combinations.extend(next.iter().cloned());
+        if combinations.len() > MAX_COMBINATIONS {
+            anyhow::bail!(
+                "`{}` has more than {MAX_COMBINATIONS} feature combinations, \
+                 limit them with `feature-powerset.depth` or `feature-powerset.exclude`",
+                pkg.name()
+            );
+        }
+        previous = next;
+    }
+    Ok(combinations
+        .into_iter()
+        .map(|c| c.into_iter().map(str::to_string).collect())
+        .collect())
+}
+
+/// The command-line arguments selecting `features`.
+fn cli_args(features: &[String]) -> String {
+    if features.is_empty() {
+        "--no-default-features".to_string()
+    } else {
+        format!("--no-default-features --features {}", features.join(","))
+    }
+}
@@ -57,10 +57,7 @@ pub fn fetch<'a>(
                     // that this is a bit lossy because not all dependencies are
                     // always compiled for all platforms, but it should be
                     // "close enough" for now.
-                    build_config
-                        .requested_kinds
-                        .iter()
-                        .any(|kind| data.dep_platform_activated(d, *kind))
+                    data.dep_platform_activated_for(d, &build_config.requested_kinds)
                 })
             })
             .map(|(id, _deps)| id);
@@ -0,0 +1,143 @@
+//! Implementation of `cargo fingerprint diff`, which compares the environment
+//! units were last built in with the current one.
+//!
+//! The snapshots of the environment are written next to the fingerprints by
+//! builds with `-Zfingerprint-snapshot`, see [`snapshot`]. They are looked up
+//! in the fingerprint directories of the host and of the requested targets,
+//! for the selected profile.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+
+use crate::core::compiler::fingerprint::snapshot::{self, EnvSnapshot};
+use crate::core::compiler::CompileKind;
+use crate::core::profiles::Profiles;
+use crate::core::{PackageIdSpec, Workspace};
+use crate::drop_print;
+use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
+
+pub struct FingerprintDiffOptions {
+    /// The package to compare the snapshots of.
+    pub spec: String,
+    /// The target arch triples to look for snapshots of, in addition to the
+    /// host.
+    pub targets: Vec<String>,
+    pub requested_profile: InternedString,
+}
+
+/// Prints what changed in the environment of the units of a package since
+/// they were last built.
+pub fn fingerprint_diff(ws: &Workspace<'_>, opts: &FingerprintDiffOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let spec = PackageIdSpec::parse(&opts.spec)?;
+    let profiles = Profiles::new(ws, opts.requested_profile)?;
+    let dir_name = profiles.get_dir_name();
+    let target_dir = ws.target_dir().into_path_unlocked();
+    let mut dirs = vec![target_dir.join(&dir_name)];
+    for kind in CompileKind::from_requested_targets(config, &opts.targets)? {
+        if let CompileKind::Target(target) = kind {
+            dirs.push(target_dir.join(target.short_name()).join(&dir_name));
+        }
+    }
+
+    let mut snapshots = Vec::new();
+    for dir in &dirs {
+        for path in snapshot_paths(&dir.join(".fingerprint"))? {
+            let snapshot = EnvSnapshot::read(&path)?;
+            if spec.matches(snapshot.package_id) {
+                snapshots.push(snapshot);
+            }
+        }
+    }
+    if snapshots.is_empty() {
+        bail!(
+            "no fingerprint snapshot of `{}` was found in `{}`, \
+             snapshots are only recorded by builds with `-Zfingerprint-snapshot`",
+            opts.spec,
+            ws.display_path(&target_dir.join(&dir_name))
+        );
+    }
+    snapshots.sort_by(|a, b| {
+        (a.package_id, &a.target, &a.mode).cmp(&(b.package_id, &b.target, &b.mode))
+    });
+
+    let rustc = config.load_global_rustc(Some(ws))?;
+    let members: HashSet<_> = ws.members().map(|pkg| pkg.package_id()).collect();
+    let mut out = String::new();
+    for snapshot in snapshots {
+        let _ = writeln!(
+            out,
+            "{}, {} ({}):",
+            snapshot.package_id, snapshot.target, snapshot.mode
+        );
+        let mut changes = Vec::new();
+        for (key, old) in &snapshot.env {
+            let new = snapshot::current_env(config, key);
+            match (old, &new) {
+                (Some(old), Some(new)) if old != new => {
+                    changes.push(format!("`{}` changed from `{}` to `{}`", key, old, new));
+                }
+                (Some(old), None) => {
+                    changes.push(format!("`{}` is no longer set, it was `{}`", key, old));
+                }
+                (None, Some(new)) => {
+                    changes.push(format!("`{}` is now set to `{}`, it was unset", key, new));
+                }
+                _ => {}
+            }
+        }
+        let member = members.contains(&snapshot.package_id);
+        let tools = snapshot::tools(&rustc, member);
+        let names: BTreeSet<_> = snapshot.tools.keys().chain(tools.keys()).collect();
+        for name in names {
+            match (snapshot.tools.get(name), tools.get(name)) {
+                (Some(old), Some(new)) if old != new => {
+                    let (old, new) = first_difference(old, new);
+                    changes.push(format!("{} changed from `{}` to `{}`", name, old, new));
+                }
+                (Some(old), None) => {
+                    changes.push(format!("{} is no longer used, it was `{}`", name, old));
+                }
+                (None, Some(new)) => changes.push(format!("{} `{}` is now used", name, new)),
+                _ => {}
+            }
+        }
+        if changes.is_empty() {
+            changes.push("no change found".to_string());
+        }
+        for change in changes {
+            let _ = writeln!(out, "  {}", change);
+        }
+    }
+    drop_print!(config, "{}", out);
+    Ok(())
+}
+
+/// The snapshots in the fingerprint directory `dir`, which has a
+/// subdirectory for each unit.
+fn snapshot_paths(dir: &Path) -> CargoResult<Vec<PathBuf>> {
+    let mut snapshots = Vec::new();
+    if !dir.exists() {
+        return Ok(snapshots);
+    }
+    for entry in walkdir::WalkDir::new(dir).min_depth(2).max_depth(2) {
+        let entry = entry?;
+        if snapshot::is_snapshot(entry.path()) {
+            snapshots.push(entry.into_path());
+        }
+    }
+    Ok(snapshots)
+}
+
+/// The first line which differs between the versions `old` and `new` of a
+/// tool, such as the output of `rustc -vV`.
+fn first_difference<'a>(old: &'a str, new: &'a str) -> (&'a str, &'a str) {
+    old.lines()
+        .zip(new.lines())
+        .find(|(old, new)| old != new)
+        .unwrap_or((old, new))
+}
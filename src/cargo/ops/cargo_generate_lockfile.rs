@@ -1,12 +1,15 @@
 use crate::core::registry::PackageRegistry;
 use crate::core::resolver::features::{CliFeatures, HasDevUnits};
-use crate::core::{PackageId, PackageIdSpec};
+use crate::core::{Dependency, PackageId, PackageIdSpec, QueryKind, Registry};
 use crate::core::{Resolve, SourceId, Workspace};
 use crate::ops;
 use crate::util::config::Config;
+use crate::util::toml_mut::manifest::LocalManifest;
 use crate::util::CargoResult;
 use anyhow::Context;
-use std::collections::{BTreeMap, HashSet};
+use semver::{Op, Version, VersionReq};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::task::Poll;
 use termcolor::Color::{self, Cyan, Green, Red, Yellow};
 use tracing::debug;
 
@@ -17,6 +20,9 @@ pub struct UpdateOptions<'a> {
     pub aggressive: bool,
     pub dry_run: bool,
     pub workspace: bool,
+    /// Bump the version requirements of the dependencies to update in the
+    /// manifests, when their latest version is incompatible with them.
+    pub breaking: bool,
 }
 
 pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
@@ -44,6 +50,26 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         anyhow::bail!("you can't generate a lockfile for an empty workspace.")
     }
 
+    if opts.breaking {
+        let _lock = ws.config().acquire_package_cache_lock()?;
+        if upgrade_manifests(ws, opts)? && !opts.dry_run {
+            // Load the new requirements before resolving them.
+            let ws = Workspace::new(ws.root_manifest(), opts.config)?;
+            return update_lockfile(
+                &ws,
+                &UpdateOptions {
+                    config: opts.config,
+                    to_update: opts.to_update.clone(),
+                    precise: opts.precise,
+                    aggressive: opts.aggressive,
+                    dry_run: opts.dry_run,
+                    workspace: opts.workspace,
+                    breaking: false,
+                },
+            );
+        }
+    }
+
     // Updates often require a lot of modifications to the registry, so ensure
     // that we're synchronized against other Cargos.
     let _lock = ws.config().acquire_package_cache_lock()?;
@@ -255,3 +281,199 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         changes.into_iter().map(|(_, v)| v).collect()
     }
 }
+
+/// Bumps the version requirements on the registry dependencies selected by
+/// `opts.to_update`, or on all of them, in the manifests of the workspace,
+/// when the latest version of the dependency is incompatible with them.
+///
+/// Returns whether a requirement was bumped.
+fn upgrade_manifests(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoResult<bool> {
+    let config = opts.config;
+    let specs = opts
+        .to_update
+        .iter()
+        .map(|spec| PackageIdSpec::parse(spec))
+        .collect::<CargoResult<Vec<_>>>()?;
+    let mut registry = PackageRegistry::new(config)?;
+    registry.lock_patches();
+    let mut latest_versions = HashMap::new();
+
+    let mut paths = vec![ws.root_manifest().to_path_buf()];
+    for member in ws.members() {
+        if !paths.iter().any(|path| path == member.manifest_path()) {
+            paths.push(member.manifest_path().to_path_buf());
+        }
+    }
+
+    let mut upgraded = false;
+    for path in paths {
+        let mut manifest = LocalManifest::try_new(&path)?;
+        let mut tables: Vec<Vec<String>> = manifest
+            .get_sections()
+            .into_iter()
+            .map(|(table, _)| table.to_table().into_iter().map(String::from).collect())
+            .collect();
+        let ws_deps = vec!["workspace".to_string(), "dependencies".to_string()];
+        if manifest.get_table(&ws_deps).is_ok() {
+            tables.push(ws_deps);
+        }
+
+        let mut changed = false;
+        for table_path in tables {
+            let Some(table) = manifest.get_table_mut(&table_path)?.as_table_like_mut() else {
+                continue;
+            };
+            for (key, item) in table.iter_mut() {
+                let Some((name, registry_name, version)) = registry_dependency(key.get(), item)
+                else {
+                    continue;
+                };
+                let Some(req) = version.as_str() else {
+                    continue;
+                };
+                let selected = specs.is_empty()
+                    || specs.iter().any(|spec| {
+                        spec.name() == name.as_str()
+                            && spec.version().map_or(true, |v| {
+                                VersionReq::parse(req).map_or(false, |req| req.matches(v))
+                            })
+                    });
+                if !selected {
+                    continue;
+                }
+                let source_id = match &registry_name {
+                    Some(registry_name) => SourceId::alt_registry(config, registry_name)?,
+                    None => SourceId::crates_io(config)?,
+                };
+                let cache_key = (name.clone(), source_id);
+                if !latest_versions.contains_key(&cache_key) {
+                    let latest = latest_version(&mut registry, &name, source_id)?;
+                    latest_versions.insert(cache_key.clone(), latest);
+                }
+                let latest = &latest_versions[&cache_key];
+                let Some(new_req) = latest
+                    .as_ref()
+                    .and_then(|latest| upgrade_requirement(req, latest))
+                else {
+                    continue;
+                };
+                config.shell().status(
+                    "Upgrading",
+                    format!(
+                        "{} {} -> {} in `{}`",
+                        name,
+                        req,
+                        new_req,
+                        table_path.join(".")
+                    ),
+                )?;
+                let decor = version.decor().clone();
+                *version = new_req.into();
+                *version.decor_mut() = decor;
+                changed = true;
+            }
+        }
+
+        if changed && !opts.dry_run {
+            let data = &manifest.data;
+            if data.contains_key("package") || data.contains_key("project") {
+                manifest.write()?;
+            } else {
+                manifest.write_workspace()?;
+            }
+        }
+        upgraded |= changed;
+    }
+    if upgraded && opts.dry_run {
+        config
+            .shell()
+            .warn("not updating the manifests due to dry run")?;
+    }
+    Ok(upgraded)
+}
+
+/// Returns the name of the package, the name of the registry (unless it is
+/// crates.io) and the version requirement of the dependency `item` named
+/// `key`, if it is a dependency on a registry.
+fn registry_dependency<'a>(
+    key: &str,
+    item: &'a mut toml_edit::Item,
+) -> Option<(String, Option<String>, &'a mut toml_edit::Value)> {
+    if item.is_str() {
+        return Some((key.to_string(), None, item.as_value_mut()?));
+    }
+    let dep = item.as_table_like_mut()?;
+    if ["path", "git", "workspace"]
+        .iter()
+        .any(|key| dep.contains_key(key))
+    {
+        return None;
+    }
+    let name = dep
+        .get("package")
+        .and_then(|name| name.as_str())
+        .unwrap_or(key)
+        .to_string();
+    let registry_name = dep
+        .get("registry")
+        .and_then(|registry| registry.as_str())
+        .map(String::from);
+    let version = dep.get_mut("version")?.as_value_mut()?;
+    Some((name, registry_name, version))
+}
+
+/// The latest version of the package `name`, other than pre-releases.
+fn latest_version(
+    registry: &mut PackageRegistry<'_>,
+    name: &str,
+    source_id: SourceId,
+) -> CargoResult<Option<Version>> {
+    let dep = Dependency::parse(name, None, source_id)?;
+    let summaries = loop {
+        match registry.query_vec(&dep, QueryKind::Exact) {
+            Poll::Ready(summaries) => break summaries?,
+            Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    Ok(summaries
+        .iter()
+        .map(|summary| summary.version())
+        .filter(|version| version.pre.is_empty())
+        .max()
+        .cloned())
+}
+
+/// Returns a requirement like `req` which matches `latest`, if `req` is a
+/// caret requirement, like `1.2` or `^1.2`, to an older version.
+///
+/// The new requirement has as many components as `req`, so `1.2` becomes
+/// `2.0` rather than `2.0.3`. Other kinds of requirements are left alone, as
+/// they were likely chosen on purpose.
+fn upgrade_requirement(req: &str, latest: &Version) -> Option<String> {
+    let parsed = VersionReq::parse(req).ok()?;
+    let [comparator] = parsed.comparators.as_slice() else {
+        return None;
+    };
+    if comparator.op != Op::Caret || !comparator.pre.is_empty() || parsed.matches(latest) {
+        return None;
+    }
+    let lowest = Version::new(
+        comparator.major,
+        comparator.minor.unwrap_or(0),
+        comparator.patch.unwrap_or(0),
+    );
+    if *latest < lowest {
+        return None;
+    }
+    let mut new_req = latest.major.to_string();
+    if comparator.minor.is_some() {
+        new_req.push_str(&format!(".{}", latest.minor));
+    }
+    if comparator.patch.is_some() {
+        new_req.push_str(&format!(".{}", latest.patch));
+    }
+    if req.trim_start().starts_with('^') {
+        new_req.insert(0, '^');
+    }
+    Some(new_req)
+}
@@ -1,11 +1,13 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::task::Poll;
 use std::{env, fs};
 
 use crate::core::compiler::{CompileKind, DefaultExecutor, Executor, UnitOutput};
 use crate::core::{
-    Dependency, Edition, Package, PackageId, PackageIdSpec, Source, SourceId, Target, Workspace,
+    Dependency, Edition, Package, PackageId, PackageIdSpec, QueryKind, Source, SourceId, Target,
+    Workspace,
 };
 use crate::ops::{common_for_install_and_uninstall::*, FilterRule};
 use crate::ops::{CompileFilter, Packages};
@@ -38,6 +40,66 @@ impl Drop for Transaction {
     }
 }
 
+/// How the `Cargo.lock` of an installed package is used.
+#[derive(Clone, Copy, PartialEq)]
+enum LockfilePolicy {
+    /// With `--locked` or `--frozen`, the dependencies must be exactly the
+    /// ones of the lock file.
+    Locked,
+    /// The lock file published with the package is used, and only updated
+    /// where it doesn't match the manifest anymore.
+    Respected,
+    /// With `--ignore-lockfile`, the dependencies are resolved to their latest
+    /// compatible versions, despite the published lock file.
+    Ignored,
+    /// There is no lock file, or it's the one of a local package, which is
+    /// only used with `--locked`.
+    Unused,
+}
+
+impl LockfilePolicy {
+    fn new(config: &Config, ws: &Workspace<'_>, source_id: SourceId, ignore: bool) -> Self {
+        if !ws.root().join("Cargo.lock").exists() {
+            LockfilePolicy::Unused
+        } else if !config.lock_update_allowed() {
+            LockfilePolicy::Locked
+        } else if source_id.is_path() {
+            LockfilePolicy::Unused
+        } else if ignore {
+            LockfilePolicy::Ignored
+        } else {
+            LockfilePolicy::Respected
+        }
+    }
+
+    /// Whether the resolver ignores the lock file, see
+    /// [`Workspace::ignore_lock`].
+    fn ignore_lock(self, config: &Config) -> bool {
+        match self {
+            LockfilePolicy::Locked | LockfilePolicy::Respected => false,
+            LockfilePolicy::Ignored => true,
+            LockfilePolicy::Unused => config.lock_update_allowed(),
+        }
+    }
+
+    /// Describes the policy once the package is installed.
+    fn summary(self) -> Option<&'static str> {
+        match self {
+            LockfilePolicy::Locked => {
+                Some("the dependencies were pinned by the Cargo.lock of the package (--locked)")
+            }
+            LockfilePolicy::Respected => Some(
+                "the dependencies were pinned by the published Cargo.lock of the package, \
+                 pass --ignore-lockfile to use their latest compatible versions",
+            ),
+            LockfilePolicy::Ignored => {
+                Some("the published Cargo.lock of the package was ignored (--ignore-lockfile)")
+            }
+            LockfilePolicy::Unused => None,
+        }
+    }
+}
+
 struct InstallablePackage<'cfg, 'a> {
     config: &'cfg Config,
     opts: ops::CompileOptions,
@@ -46,6 +108,7 @@ struct InstallablePackage<'cfg, 'a> {
     vers: Option<&'a str>,
     force: bool,
     no_track: bool,
+    lockfile_policy: LockfilePolicy,
 
     pkg: Package,
     ws: Workspace<'cfg>,
@@ -66,6 +129,7 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
         original_opts: &'a ops::CompileOptions,
         force: bool,
         no_track: bool,
+        ignore_lockfile: bool,
         needs_update_if_source_is_index: bool,
     ) -> CargoResult<Option<InstallablePackage<'cfg, 'a>>> {
         if let Some(name) = krate {
@@ -111,7 +175,7 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
                     bail!(
                         "`{}` is not a directory. \
                      --path must point to a directory containing a Cargo.toml file.",
-                        src.path().display()
+                        config.display_path(src.path(), config.cwd())
                     )
                 }
                 if !src.path().join("Cargo.toml").exists() {
@@ -120,19 +184,19 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
                             "`{}` is not a crate root; specify a crate to \
                          install from crates.io, or use --path or --git to \
                          specify an alternate source",
-                            src.path().display()
+                            config.display_path(src.path(), config.cwd())
                         );
                     } else if src.path().join("cargo.toml").exists() {
                         bail!(
                             "`{}` does not contain a Cargo.toml file, but found cargo.toml please try to rename it to Cargo.toml. \
                      --path must point to a directory containing a Cargo.toml file.",
-                            src.path().display()
+                            config.display_path(src.path(), config.cwd())
                         )
                     } else {
                         bail!(
                             "`{}` does not contain a Cargo.toml file. \
                      --path must point to a directory containing a Cargo.toml file.",
-                            src.path().display()
+                            config.display_path(src.path(), config.cwd())
                         )
                     }
                 }
@@ -170,8 +234,10 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
             }
         };
 
-        let (ws, rustc, target) =
+        let (mut ws, rustc, target) =
             make_ws_rustc_target(config, &original_opts, &source_id, pkg.clone())?;
+        let lockfile_policy = LockfilePolicy::new(config, &ws, source_id, ignore_lockfile);
+        ws.set_ignore_lock(lockfile_policy.ignore_lock(config));
         // If we're installing in --locked mode and there's no `Cargo.lock` published
         // ie. the bin was published before https://github.com/rust-lang/cargo/pull/7026
         if config.locked() && !ws.root().join("Cargo.lock").exists() {
@@ -237,6 +303,7 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
             vers,
             force,
             no_track,
+            lockfile_policy,
 
             pkg,
             ws,
@@ -289,7 +356,10 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
     }
 
     fn install_one(mut self) -> CargoResult<bool> {
-        self.config.shell().status("Installing", &self.pkg)?;
+        let pkg = self
+            .config
+            .display_package_id(self.pkg.package_id(), self.config.cwd());
+        self.config.shell().status("Installing", pkg)?;
 
         let dst = self.root.join("bin").into_path_unlocked();
 
@@ -315,7 +385,7 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
         let compile = ops::compile_ws(&self.ws, &self.opts, &exec).with_context(|| {
             if let Some(td) = td_opt.take() {
                 // preserve the temporary directory, so the user can inspect it
-                drop(td.into_path());
+                drop(td.keep());
             }
 
             format!(
@@ -515,6 +585,7 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
                     executables(successful_bins.iter())
                 ),
             )?;
+            self.report_lockfile_policy()?;
             Ok(true)
         } else {
             if !to_install.is_empty() {
@@ -541,24 +612,32 @@ impl<'cfg, 'a> InstallablePackage<'cfg, 'a> {
                     ),
                 )?;
             }
+            self.report_lockfile_policy()?;
             Ok(true)
         }
     }
 
+    fn report_lockfile_policy(&self) -> CargoResult<()> {
+        match self.lockfile_policy.summary() {
+            Some(summary) => self.config.shell().note(summary),
+            None => Ok(()),
+        }
+    }
+
     fn check_yanked_install(&self) -> CargoResult<()> {
         if self.ws.ignore_lock() || !self.ws.root().join("Cargo.lock").exists() {
             return Ok(());
         }
+        let hint = if self.lockfile_policy == LockfilePolicy::Respected {
+            "consider running with --ignore-lockfile"
+        } else {
+            "consider running without --locked"
+        };
         // It would be best if `source` could be passed in here to avoid a
         // duplicate "Updating", but since `source` is taken by value, then it
         // wouldn't be available for `compile_ws`.
         let (pkg_set, resolve) = ops::resolve_ws(&self.ws)?;
-        ops::check_yanked(
-            self.ws.config(),
-            &pkg_set,
-            &resolve,
-            "consider running without --locked",
-        )
+        ops::check_yanked(self.ws.config(), &pkg_set, &resolve, hint)
     }
 }
 
@@ -610,7 +689,11 @@ pub fn install(
     opts: &ops::CompileOptions,
     force: bool,
     no_track: bool,
+    ignore_lockfile: bool,
 ) -> CargoResult<()> {
+    if ignore_lockfile && !config.lock_update_allowed() {
+        bail!("cannot use `--ignore-lockfile` with `--locked` or `--frozen`");
+    }
     let root = resolve_root(root, config)?;
     let dst = root.join("bin").into_path_unlocked();
     let map = SourceConfigMap::new(config)?;
@@ -622,7 +705,18 @@ pub fn install(
             .map(|(k, v)| (Some(k), v))
             .unwrap_or((None, None));
         let installable_pkg = InstallablePackage::new(
-            config, root, map, krate, source_id, from_cwd, vers, opts, force, no_track, true,
+            config,
+            root,
+            map,
+            krate,
+            source_id,
+            from_cwd,
+            vers,
+            opts,
+            force,
+            no_track,
+            ignore_lockfile,
+            true,
         )?;
         let mut installed_anything = true;
         if let Some(installable_pkg) = installable_pkg {
@@ -652,6 +746,7 @@ pub fn install(
                     opts,
                     force,
                     no_track,
+                    ignore_lockfile,
                     !did_update,
                 ) {
                     Ok(Some(installable_pkg)) => {
@@ -793,7 +888,6 @@ fn make_ws_rustc_target<'cfg>(
     } else {
         Workspace::ephemeral(pkg, config, None, false)?
     };
-    ws.set_ignore_lock(config.lock_update_allowed());
     ws.set_require_optional_deps(false);
 
     let rustc = config.load_global_rustc(Some(&ws))?;
@@ -854,11 +948,25 @@ fn parse_semver_flag(v: &str) -> CargoResult<VersionReq> {
 }
 
 /// Display a list of installed binaries.
-pub fn install_list(dst: Option<&str>, config: &Config) -> CargoResult<()> {
+pub fn install_list(dst: Option<&str>, outdated: bool, config: &Config) -> CargoResult<()> {
     let root = resolve_root(dst, config)?;
     let tracker = InstallTracker::load(config, &root)?;
+    let latest = if outdated {
+        latest_versions(&tracker, config)?
+    } else {
+        HashMap::new()
+    };
     for (k, v) in tracker.all_installed_bins() {
-        drop_println!(config, "{}:", k);
+        if outdated {
+            match latest.get(k) {
+                Some(version) if *version > *k.version() => {
+                    drop_println!(config, "{} (latest: v{}):", k, version)
+                }
+                _ => continue,
+            }
+        } else {
+            drop_println!(config, "{}:", k);
+        }
         for bin in v {
             drop_println!(config, "    {}", bin);
         }
@@ -866,6 +974,53 @@ pub fn install_list(dst: Option<&str>, config: &Config) -> CargoResult<()> {
     Ok(())
 }
 
+/// Queries the latest version of each package installed from a registry.
+///
+/// Packages installed from git or a path have no versions to compare with,
+/// and are left out.
+fn latest_versions(
+    tracker: &InstallTracker,
+    config: &Config,
+) -> CargoResult<HashMap<PackageId, semver::Version>> {
+    let mut by_source: HashMap<SourceId, Vec<PackageId>> = HashMap::new();
+    for (pkg_id, _) in tracker.all_installed_bins() {
+        if pkg_id.source_id().is_registry() {
+            by_source
+                .entry(pkg_id.source_id())
+                .or_default()
+                .push(*pkg_id);
+        }
+    }
+
+    let map = SourceConfigMap::new(config)?;
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut latest = HashMap::new();
+    for (source_id, pkg_ids) in by_source {
+        let mut source = map.load(source_id, &HashSet::new())?;
+        source.invalidate_cache();
+        for pkg_id in pkg_ids {
+            // Like `cargo install`, only consider pre-releases if one is
+            // installed.
+            let req = if pkg_id.version().pre.is_empty() {
+                "*".to_string()
+            } else {
+                format!(">={}", pkg_id.version())
+            };
+            let dep = Dependency::parse(pkg_id.name(), Some(&req), source_id)?;
+            let summaries = loop {
+                match source.query_vec(&dep, QueryKind::Exact)? {
+                    Poll::Ready(summaries) => break summaries,
+                    Poll::Pending => source.block_until_ready()?,
+                }
+            };
+            if let Some(version) = summaries.iter().map(|s| s.version()).max() {
+                latest.insert(pkg_id, version.clone());
+            }
+        }
+    }
+    Ok(latest)
+}
+
 /// Removes executables that are no longer part of a package that was
 /// previously installed.
 fn remove_orphaned_bins(
@@ -0,0 +1,215 @@
+//! Implementation of `cargo members`, which answers questions about the
+//! dependency graph between the members of a workspace.
+//!
+//! Only the dependencies declared in the manifests of the members are
+//! considered, so no resolution or network access is needed. All kinds of
+//! dependencies form edges, since a change to a dev-dependency may break the
+//! tests of its dependents.
+//!
+//! The same graph backs `cargo test --affected-by`, see [`affected`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::compiler::{fingerprint, MetadataHash};
+use crate::core::{Package, PackageId, PackageIdSpec, Workspace};
+use crate::util::CargoResult;
+use cargo_util::paths;
+
+pub struct MembersOptions {
+    /// Package specs of the members whose dependents are listed.
+    pub dependents_of: Vec<String>,
+    /// Changed files, whose owning members are listed along with their
+    /// dependents.
+    pub paths_changed: Vec<PathBuf>,
+}
+
+/// Why a member is affected by a set of changed files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AffectedReason {
+    /// The member owns the changed file, or a previous build of the member
+    /// read it according to its dep-info.
+    Changed(PathBuf),
+    /// The member depends on another affected member.
+    DependsOn(PackageId),
+}
+
+/// Returns the workspace members selected by `opts`, sorted by package ID.
+///
+/// Without any options all members are returned.
+pub fn members<'a>(ws: &'a Workspace<'_>, opts: &MembersOptions) -> CargoResult<Vec<&'a Package>> {
+    let members = member_map(ws);
+
+    if opts.dependents_of.is_empty() && opts.paths_changed.is_empty() {
+        return Ok(sorted(&members, members.keys().copied()));
+    }
+
+    let mut selected = BTreeMap::new();
+    for spec in &opts.dependents_of {
+        let spec = PackageIdSpec::parse(spec)?;
+        let id = spec.query(members.keys().copied())?;
+        selected.extend(dependents(&members, id));
+    }
+    if !opts.paths_changed.is_empty() {
+        selected.extend(
+            affected(ws, &opts.paths_changed)?
+                .into_iter()
+                .map(|(pkg, reason)| (pkg.package_id(), reason)),
+        );
+    }
+
+    Ok(sorted(&members, selected.into_keys()))
+}
+
+/// Returns the workspace members affected by changes to the given files,
+/// sorted by package ID, along with the reason each one was selected.
+///
+/// A file affects the member owning it, any member whose previous builds in
+/// the target directory recorded it in their dep-info, and every member
+/// depending on those. Files which affect no member are ignored.
+pub fn affected<'a>(
+    ws: &'a Workspace<'_>,
+    changed: &[PathBuf],
+) -> CargoResult<Vec<(&'a Package, AffectedReason)>> {
+    let members = member_map(ws);
+    let dep_info = dep_info_files(ws, &members)?;
+
+    let cwd = ws.config().cwd();
+    let mut direct = BTreeMap::new();
+    for path in changed {
+        let abs = paths::normalize_path(&cwd.join(path));
+        let readers = dep_info
+            .iter()
+            .filter(|(_, files)| files.iter().any(|file| *file == abs))
+            .map(|(&id, _)| id);
+        for id in owner(&members, &abs).into_iter().chain(readers) {
+            direct
+                .entry(id)
+                .or_insert_with(|| AffectedReason::Changed(path.clone()));
+        }
+    }
+
+    let mut selected = BTreeMap::new();
+    for &id in direct.keys() {
+        for (dependent, reason) in dependents(&members, id) {
+            if !direct.contains_key(&dependent) {
+                selected.entry(dependent).or_insert(reason);
+            }
+        }
+    }
+    selected.extend(direct);
+
+    Ok(selected
+        .into_iter()
+        .map(|(id, reason)| (members[&id], reason))
+        .collect())
+}
+
+fn member_map<'a>(ws: &'a Workspace<'_>) -> HashMap<PackageId, &'a Package> {
+    ws.members().map(|pkg| (pkg.package_id(), pkg)).collect()
+}
+
+/// Returns the members which depend on `id`, directly or transitively, along
+/// with the member through which each one depends on it.
+fn dependents(
+    members: &HashMap<PackageId, &Package>,
+    id: PackageId,
+) -> BTreeMap<PackageId, AffectedReason> {
+    let mut found = BTreeMap::new();
+    let mut queue = vec![id];
+    while let Some(dependency) = queue.pop() {
+        for (&member_id, member) in members {
+            let depends = member
+                .dependencies()
+                .iter()
+                .any(|dep| dep.matches_id(dependency));
+            if depends && member_id != id && !found.contains_key(&member_id) {
+                found.insert(member_id, AffectedReason::DependsOn(dependency));
+                queue.push(member_id);
+            }
+        }
+    }
+    found
+}
+
+/// Returns the member whose package root is the innermost directory
+/// containing `path`.
+fn owner(members: &HashMap<PackageId, &Package>, path: &Path) -> Option<PackageId> {
+    members
+        .values()
+        .filter(|pkg| path.starts_with(pkg.root()))
+        .max_by_key(|pkg| pkg.root().components().count())
+        .map(|pkg| pkg.package_id())
+}
+
+/// Collects the files recorded in the dep-info of previous builds of each
+/// member, for every profile and target in the target directory.
+///
+/// This catches files outside of a member's package root, for example ones
+/// pulled in with `include_str!`.
+fn dep_info_files(
+    ws: &Workspace<'_>,
+    members: &HashMap<PackageId, &Package>,
+) -> CargoResult<HashMap<PackageId, Vec<PathBuf>>> {
+    let target_root = ws.target_dir().into_path_unlocked();
+    // The layout is `target/<profile>/.fingerprint` on the host, and
+    // `target/<triple>/<profile>/.fingerprint` with `--target`.
+    let mut fingerprint_dirs = Vec::new();
+    for dir in read_dirs(&target_root) {
+        fingerprint_dirs.push(dir.join(".fingerprint"));
+        fingerprint_dirs.extend(read_dirs(&dir).map(|dir| dir.join(".fingerprint")));
+    }
+
+    let mut files: HashMap<PackageId, Vec<PathBuf>> = HashMap::new();
+    for unit_dir in fingerprint_dirs.iter().flat_map(|dir| read_dirs(dir)) {
+        let Some(dir_name) = unit_dir.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // Unit directories are named `<package name>-<metadata hash>`.
+        let Some(pkg) = members.values().find(|pkg| {
+            dir_name
+                .strip_prefix(pkg.name().as_str())
+                .and_then(|rest| rest.strip_prefix('-'))
+                .map_or(false, MetadataHash::is_hash)
+        }) else {
+            continue;
+        };
+        let Ok(entries) = fs::read_dir(&unit_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with("dep-") {
+                continue;
+            }
+            if let Some(info) =
+                fingerprint::parse_dep_info(pkg.root(), &target_root, &entry.path())?
+            {
+                files
+                    .entry(pkg.package_id())
+                    .or_default()
+                    .extend(info.files.iter().map(|file| paths::normalize_path(file)));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Returns the subdirectories of `dir`, or nothing if it can't be read.
+fn read_dirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().map_or(false, |ty| ty.is_dir()))
+        .map(|entry| entry.path())
+}
+
+fn sorted<'a>(
+    members: &HashMap<PackageId, &'a Package>,
+    ids: impl Iterator<Item = PackageId>,
+) -> Vec<&'a Package> {
+    let mut pkgs: Vec<_> = ids.map(|id| members[&id]).collect();
+    pkgs.sort_by_key(|pkg| pkg.package_id());
+    pkgs
+}
@@ -1,6 +1,7 @@
-use crate::core::{Edition, Shell, Workspace};
+use crate::core::{find_workspace_root, Edition, Shell, Workspace};
 use crate::util::errors::CargoResult;
 use crate::util::important_paths::find_root_manifest_for_wd;
+use crate::util::toml_mut::manifest::LocalManifest;
 use crate::util::{existing_vcs_repo, FossilRepo, GitRepo, HgRepo, PijulRepo};
 use crate::util::{restricted_names, Config};
 use anyhow::{anyhow, Context as _};
@@ -58,6 +59,8 @@ pub struct NewOptions {
     pub name: Option<String>,
     pub edition: Option<String>,
     pub registry: Option<String>,
+    /// Add the package to the members of the enclosing workspace.
+    pub add_to_workspace: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -96,6 +99,7 @@ struct MkOptions<'a> {
     bin: bool,
     edition: Option<&'a str>,
     registry: Option<&'a str>,
+    add_to_workspace: bool,
 }
 
 impl NewOptions {
@@ -124,6 +128,7 @@ impl NewOptions {
             name,
             edition,
             registry,
+            add_to_workspace: false,
         };
         Ok(opts)
     }
@@ -451,6 +456,7 @@ pub fn new(opts: &NewOptions, config: &Config) -> CargoResult<()> {
         bin: is_bin,
         edition: opts.edition.as_deref(),
         registry: opts.registry.as_deref(),
+        add_to_workspace: opts.add_to_workspace,
     };
 
     mk(config, &mkopts).with_context(|| {
@@ -557,6 +563,7 @@ pub fn init(opts: &NewOptions, config: &Config) -> CargoResult<NewProjectKind> {
         source_files: src_paths_types,
         edition: opts.edition.as_deref(),
         registry: opts.registry.as_deref(),
+        add_to_workspace: opts.add_to_workspace,
     };
 
     mk(config, &mkopts).with_context(|| {
@@ -884,6 +891,10 @@ mod tests {
         }
     }
 
+    if opts.add_to_workspace {
+        add_to_workspace(config, &manifest_path)?;
+    }
+
     if let Err(e) = Workspace::new(&path.join("Cargo.toml"), config) {
         crate::display_warning_with_error(
             "compiling this new package may not work due to invalid \
@@ -896,6 +907,40 @@ mod tests {
     Ok(())
 }
 
+/// Adds the package at `manifest_path` to the members of the enclosing
+/// workspace, unless it is already one of them.
+fn add_to_workspace(config: &Config, manifest_path: &Path) -> CargoResult<()> {
+    let Some(root_manifest_path) = find_workspace_root(manifest_path, config)? else {
+        return Ok(());
+    };
+    if Workspace::new(manifest_path, config).is_ok() {
+        return Ok(());
+    }
+    let root = root_manifest_path.parent().unwrap();
+    let member = manifest_path
+        .parent()
+        .unwrap()
+        .strip_prefix(root)
+        .expect("the workspace root is an ancestor")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut root_manifest = LocalManifest::try_new(&root_manifest_path)?;
+    if root_manifest.add_workspace_member(&member)? {
+        root_manifest.write_workspace()?;
+        config.shell().status(
+            "Adding",
+            format!(
+                "`{}` to the members of the workspace at `{}`",
+                member,
+                root_manifest_path.display()
+            ),
+        )?;
+    }
+    Ok(())
+}
+
 // Update the manifest with the inherited workspace package keys.
 // If the option is not set, the key is removed from the manifest.
 // If the option is set, keep the value from the manifest.
@@ -4,12 +4,13 @@ use crate::core::dependency::DepKind;
 use crate::core::package::SerializedPackage;
 use crate::core::resolver::{features::CliFeatures, HasDevUnits, Resolve};
 use crate::core::{Package, PackageId, Workspace};
+use crate::ops::metadata_cache::MetadataCache;
 use crate::ops::{self, Packages};
 use crate::util::interning::InternedString;
 use crate::util::CargoResult;
 use cargo_platform::Platform;
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::path::PathBuf;
 
 const VERSION: u32 = 1;
@@ -19,6 +20,12 @@ pub struct OutputMetadataOptions {
     pub no_deps: bool,
     pub version: u32,
     pub filter_platforms: Vec<String>,
+    /// Only include the dependencies up to this many levels away from the
+    /// workspace members in the resolve graph, from `--depth`.
+    pub max_depth: Option<u32>,
+    /// Skip the feature resolution and the platform information it needs,
+    /// from `--no-deps-features`.
+    pub no_deps_features: bool,
 }
 
 /// Loads the manifest, resolves the dependencies of the package to the concrete
@@ -52,6 +59,43 @@ pub fn output_metadata(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> Cargo
     })
 }
 
+/// Like [`output_metadata`], but serialized to JSON, and reused from the
+/// metadata cache of the workspace with `-Zmetadata-cache`.
+pub fn output_metadata_json(
+    ws: &Workspace<'_>,
+    opt: &OutputMetadataOptions,
+) -> CargoResult<String> {
+    // `--no-deps` doesn't resolve anything, so there is nothing to save.
+    if !ws.config().cli_unstable().metadata_cache || opt.no_deps {
+        return Ok(serde_json::to_string(&output_metadata(ws, opt)?)?);
+    }
+    let rustc = ws.config().load_global_rustc(Some(ws))?;
+    let cache = MetadataCache::new(
+        ws,
+        (
+            &rustc.verbose_version,
+            &opt.cli_features,
+            opt.version,
+            &opt.filter_platforms,
+            opt.max_depth,
+            opt.no_deps_features,
+        ),
+    );
+    if let Some(output) = cache.load(ws)? {
+        return Ok(output);
+    }
+    let info = output_metadata(ws, opt)?;
+    let output = serde_json::to_string(&info)?;
+    let path_deps_outside_ws =
+        info.resolve.iter().flat_map(|r| &r.nodes).any(|node| {
+            node.id.source_id().is_path() && !info.workspace_members.contains(&node.id)
+        });
+    if !path_deps_outside_ws {
+        cache.save(ws, &output)?;
+    }
+    Ok(output)
+}
+
 /// This is the structure that is serialized and displayed to the user.
 ///
 /// See cargo-metadata.adoc for detailed documentation of the format.
@@ -122,50 +166,88 @@ fn build_resolve_graph(
     ws: &Workspace<'_>,
     metadata_opts: &OutputMetadataOptions,
 ) -> CargoResult<(Vec<SerializedPackage>, MetadataResolve)> {
-    // TODO: Without --filter-platform, features are being resolved for `host` only.
-    // How should this work?
-    let requested_kinds =
-        CompileKind::from_requested_targets(ws.config(), &metadata_opts.filter_platforms)?;
-    let target_data = RustcTargetData::new(ws, &requested_kinds)?;
-    // Resolve entire workspace.
-    let specs = Packages::All.to_package_id_specs(ws)?;
-    let force_all = if metadata_opts.filter_platforms.is_empty() {
-        crate::core::resolver::features::ForceAllTargets::Yes
+    let (resolve, package_map, target_data, requested_kinds) = if metadata_opts.no_deps_features {
+        // Only resolve the dependency graph, skipping the feature resolver
+        // and the calls to rustc needed to learn about the target platforms.
+        // Only the packages which end up in the output are downloaded.
+        let specs = Packages::All.to_package_id_specs(ws)?;
+        let (pkg_set, _, resolve) = ops::resolve_ws_with_features(
+            ws,
+            &metadata_opts.cli_features,
+            &specs,
+            HasDevUnits::Yes,
+        )?;
+        // The nodes at the last level list the targets of their dependencies.
+        let depth = metadata_opts.max_depth.map(|max| max + 1);
+        let package_ids = reachable_packages(ws, &resolve, depth);
+        let package_map = clone_packages(pkg_set.get_many(package_ids)?);
+        (resolve, package_map, None, vec![CompileKind::Host])
     } else {
-        crate::core::resolver::features::ForceAllTargets::No
-    };
-
-    // Note that even with --filter-platform we end up downloading host dependencies as well,
-    // as that is the behavior of download_accessible.
-    let ws_resolve = ops::resolve_ws_with_opts(
-        ws,
-        &target_data,
-        &requested_kinds,
-        &metadata_opts.cli_features,
-        &specs,
-        HasDevUnits::Yes,
-        force_all,
-    )?;
+        // TODO: Without --filter-platform, features are being resolved for `host` only.
+        // How should this work?
+        let requested_kinds =
+            CompileKind::from_requested_targets(ws.config(), &metadata_opts.filter_platforms)?;
+        let target_data = RustcTargetData::new(ws, &requested_kinds)?;
+        // Resolve entire workspace.
+        let specs = Packages::All.to_package_id_specs(ws)?;
+        let force_all = if metadata_opts.filter_platforms.is_empty() {
+            crate::core::resolver::features::ForceAllTargets::Yes
+        } else {
+            crate::core::resolver::features::ForceAllTargets::No
+        };
 
-    let package_map: BTreeMap<PackageId, Package> = ws_resolve
-        .pkg_set
-        .packages()
-        // This is a little lazy, but serde doesn't handle Rc fields very well.
-        .map(|pkg| (pkg.package_id(), Package::clone(pkg)))
-        .collect();
+        // Note that even with --filter-platform we end up downloading host dependencies as well,
+        // as that is the behavior of download_accessible.
+        let ws_resolve = ops::resolve_ws_with_opts(
+            ws,
+            &target_data,
+            &requested_kinds,
+            &metadata_opts.cli_features,
+            &specs,
+            HasDevUnits::Yes,
+            force_all,
+        )?;
+        let package_map = clone_packages(ws_resolve.pkg_set.packages());
+        (
+            ws_resolve.targeted_resolve,
+            package_map,
+            Some(target_data),
+            requested_kinds,
+        )
+    };
 
-    // Start from the workspace roots, and recurse through filling out the
-    // map, filtering targets as necessary.
+    // Start from the workspace roots, and go through the graph breadth first
+    // filling out the map, filtering targets as necessary. Going breadth
+    // first means every package is reached at its lowest depth.
     let mut node_map = BTreeMap::new();
-    for member_pkg in ws.members() {
-        build_resolve_graph_r(
-            &mut node_map,
-            member_pkg.package_id(),
-            &ws_resolve.targeted_resolve,
+    let mut queue: VecDeque<(PackageId, u32)> =
+        ws.members().map(|pkg| (pkg.package_id(), 0)).collect();
+    while let Some((pkg_id, depth)) = queue.pop_front() {
+        if node_map.contains_key(&pkg_id) {
+            continue;
+        }
+        let node = build_resolve_node(
+            pkg_id,
+            &resolve,
             &package_map,
-            &target_data,
+            target_data.as_ref(),
             &requested_kinds,
+            metadata_opts.no_deps_features,
         )?;
+        if metadata_opts.max_depth.map_or(true, |max| depth < max) {
+            queue.extend(node.dependencies.iter().map(|&dep_id| (dep_id, depth + 1)));
+        }
+        node_map.insert(pkg_id, node);
+    }
+    // With `--depth`, the nodes at the last level depend on packages which
+    // have no node, and aren't in `packages`. Drop them so that every package
+    // ID in the output can be looked up.
+    if metadata_opts.max_depth.is_some() {
+        let included: HashSet<PackageId> = node_map.keys().copied().collect();
+        for node in node_map.values_mut() {
+            node.dependencies.retain(|dep_id| included.contains(dep_id));
+            node.deps.retain(|dep| included.contains(&dep.pkg));
+        }
     }
     // Get a Vec of Packages.
     let actual_packages = package_map
@@ -181,17 +263,49 @@ fn build_resolve_graph(
     Ok((actual_packages, mr))
 }
 
-fn build_resolve_graph_r(
-    node_map: &mut BTreeMap<PackageId, MetadataResolveNode>,
+/// Returns the packages at most `max_depth` dependencies away from the
+/// workspace members in `resolve`.
+fn reachable_packages(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    max_depth: Option<u32>,
+) -> Vec<PackageId> {
+    let mut reached = BTreeMap::new();
+    let mut queue: VecDeque<(PackageId, u32)> =
+        ws.members().map(|pkg| (pkg.package_id(), 0)).collect();
+    while let Some((pkg_id, depth)) = queue.pop_front() {
+        if reached.insert(pkg_id, depth).is_some() {
+            continue;
+        }
+        if max_depth.map_or(true, |max| depth < max) {
+            queue.extend(resolve.deps(pkg_id).map(|(dep_id, _)| (dep_id, depth + 1)));
+        }
+    }
+    reached.into_keys().collect()
+}
+
+fn clone_packages<'a>(
+    packages: impl IntoIterator<Item = &'a Package>,
+) -> BTreeMap<PackageId, Package> {
+    packages
+        .into_iter()
+        // This is a little lazy, but serde doesn't handle Rc fields very well.
+        .map(|pkg| (pkg.package_id(), Package::clone(pkg)))
+        .collect()
+}
+
+/// Builds the node of `pkg_id` in the resolve graph.
+///
+/// `target_data` is only `None` with `--no-deps-features`, in which case
+/// `requested_kinds` is only the host, and no features are listed.
+fn build_resolve_node(
     pkg_id: PackageId,
     resolve: &Resolve,
     package_map: &BTreeMap<PackageId, Package>,
-    target_data: &RustcTargetData<'_>,
+    target_data: Option<&RustcTargetData<'_>>,
     requested_kinds: &[CompileKind],
-) -> CargoResult<()> {
-    if node_map.contains_key(&pkg_id) {
-        return Ok(());
-    }
+    no_deps_features: bool,
+) -> CargoResult<MetadataResolveNode> {
     // This normalizes the IDs so that they are consistent between the
     // `packages` array and the `resolve` map. This is a bit of a hack to
     // compensate for the fact that
@@ -207,7 +321,11 @@ fn build_resolve_graph_r(
     // are deserialized from Cargo.lock. Cargo.lock may have been generated by
     // an older (or newer!) version of Cargo which uses a different style.
     let normalize_id = |id| -> PackageId { *package_map.get_key_value(&id).unwrap().0 };
-    let features = resolve.features(pkg_id).to_vec();
+    let features = if no_deps_features {
+        Vec::new()
+    } else {
+        resolve.features(pkg_id).to_vec()
+    };
 
     let deps = {
         let mut dep_metadatas = Vec::new();
@@ -215,10 +333,10 @@ fn build_resolve_graph_r(
             if requested_kinds == [CompileKind::Host] {
                 true
             } else {
-                requested_kinds.iter().any(|kind| {
-                    deps.iter()
-                        .any(|dep| target_data.dep_platform_activated(dep, *kind))
-                })
+                let target_data =
+                    target_data.expect("platforms are only filtered with target data");
+                deps.iter()
+                    .any(|dep| target_data.dep_platform_activated_for(dep, requested_kinds))
             }
         });
         for (dep_id, deps) in iter {
@@ -323,24 +441,10 @@ fn build_resolve_graph_r(
     };
 
     let dumb_deps: Vec<PackageId> = deps.iter().map(|dep| dep.pkg).collect();
-    let to_visit = dumb_deps.clone();
-    let node = MetadataResolveNode {
+    Ok(MetadataResolveNode {
         id: normalize_id(pkg_id),
         dependencies: dumb_deps,
         deps,
         features,
-    };
-    node_map.insert(pkg_id, node);
-    for dep_id in to_visit {
-        build_resolve_graph_r(
-            node_map,
-            dep_id,
-            resolve,
-            package_map,
-            target_data,
-            requested_kinds,
-        )?;
-    }
-
-    Ok(())
+    })
 }
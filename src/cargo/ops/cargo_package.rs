@@ -15,6 +15,7 @@ use crate::core::{Package, PackageId, PackageSet, Resolve, SourceId};
 use crate::sources::PathSource;
 use crate::util::config::JobsConfig;
 use crate::util::errors::CargoResult;
+use crate::util::machine_message::{self, Message};
 use crate::util::toml::TomlManifest;
 use crate::util::{self, human_readable_bytes, restricted_names, Config, FileLock};
 use crate::{drop_println, ops};
@@ -38,6 +39,43 @@ pub struct PackageOpts<'cfg> {
     pub to_package: ops::Packages,
     pub targets: Vec<String>,
     pub cli_features: CliFeatures,
+    /// Only runs the checks done before packaging, and reports them in the
+    /// given format, see [`check_consistency`].
+    pub check_consistency: Option<CheckFormat>,
+}
+
+/// How `cargo package --check-consistency` reports the outcome of its checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckFormat {
+    /// A summary per package.
+    Text,
+    /// A JSON message per check.
+    Json,
+}
+
+/// The outcome of one of the checks of `cargo package --check-consistency`.
+enum CheckOutcome {
+    Passed,
+    Failed(String),
+    Skipped(&'static str),
+}
+
+impl CheckOutcome {
+    fn status(&self) -> &'static str {
+        match self {
+            CheckOutcome::Passed => "passed",
+            CheckOutcome::Failed(_) => "failed",
+            CheckOutcome::Skipped(_) => "skipped",
+        }
+    }
+
+    fn message(&self) -> Option<&str> {
+        match self {
+            CheckOutcome::Passed => None,
+            CheckOutcome::Failed(message) => Some(message),
+            CheckOutcome::Skipped(reason) => Some(reason),
+        }
+    }
 }
 
 const ORIGINAL_MANIFEST_FILE: &str = "Cargo.toml.orig";
@@ -100,7 +138,23 @@ pub fn package_one(
              the exclude list will be ignored",
         )?;
     }
-    let src_files = src.list_files(pkg)?;
+    let (src_files, reported) = src.list_files_with_reported(pkg)?;
+    for reported in reported {
+        let path = reported
+            .path
+            .strip_prefix(pkg.root())
+            .unwrap_or(&reported.path);
+        let action = if reported.reason.is_skipped() {
+            "skipped"
+        } else {
+            "including"
+        };
+        config.shell().note(format!(
+            "{action} `{}`: {}",
+            path.display(),
+            reported.reason
+        ))?;
+    }
 
     // Check (git) repository state, getting the current commit hash if not
     // dirty.
@@ -141,7 +195,7 @@ pub fn package_one(
     // it exists.
     config
         .shell()
-        .status("Packaging", pkg.package_id().to_string())?;
+        .status("Packaging", ws.display_package_id(pkg.package_id()))?;
     dst.file().set_len(0)?;
     let uncompressed_size = tar(ws, pkg, ar_files, dst.file(), &filename)
         .with_context(|| "failed to prepare local package for uploading")?;
@@ -156,10 +210,12 @@ pub fn package_one(
     fs::rename(&src_path, &dst_path)
         .with_context(|| "failed to move temporary tarball into final location")?;
 
-    let dst_metadata = dst
-        .file()
-        .metadata()
-        .with_context(|| format!("could not learn metadata for: `{}`", dst_path.display()))?;
+    let dst_metadata = dst.file().metadata().with_context(|| {
+        format!(
+            "could not learn metadata for: `{}`",
+            ws.display_path(&dst_path)
+        )
+    })?;
     let compressed_size = dst_metadata.len();
 
     let uncompressed = human_readable_bytes(uncompressed_size);
@@ -182,6 +238,7 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     )?;
 
     let mut dsts = Vec::with_capacity(pkgs.len());
+    let mut failed = Vec::new();
 
     if ws.root().join("Cargo.lock").exists() {
         // Make sure the Cargo.lock is up-to-date and valid.
@@ -191,29 +248,38 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     }
 
     for (pkg, cli_features) in pkgs {
-        let result = package_one(
-            ws,
-            pkg,
-            &PackageOpts {
-                config: opts.config,
-                list: opts.list,
-                check_metadata: opts.check_metadata,
-                allow_dirty: opts.allow_dirty,
-                verify: opts.verify,
-                jobs: opts.jobs.clone(),
-                keep_going: opts.keep_going,
-                to_package: ops::Packages::Default,
-                targets: opts.targets.clone(),
-                cli_features: cli_features,
-            },
-        )?;
+        let pkg_opts = PackageOpts {
+            config: opts.config,
+            list: opts.list,
+            check_metadata: opts.check_metadata,
+            allow_dirty: opts.allow_dirty,
+            verify: opts.verify,
+            jobs: opts.jobs.clone(),
+            keep_going: opts.keep_going,
+            to_package: ops::Packages::Default,
+            targets: opts.targets.clone(),
+            cli_features: cli_features,
+            check_consistency: opts.check_consistency,
+        };
+        if let Some(format) = opts.check_consistency {
+            if !check_consistency(ws, pkg, &pkg_opts, format)? {
+                failed.push(format!("`{}`", pkg.package_id()));
+            }
+            continue;
+        }
+
+        let result = package_one(ws, pkg, &pkg_opts)?;
 
         if !opts.list {
             dsts.push(result.unwrap());
         }
     }
 
-    if opts.list {
+    if !failed.is_empty() {
+        anyhow::bail!("consistency checks failed for {}", failed.join(", "));
+    }
+
+    if opts.list || opts.check_consistency.is_some() {
         // We're just listing, so there's no file output
         Ok(None)
     } else {
@@ -221,6 +287,149 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     }
 }
 
+/// Runs the checks done before packaging `pkg` without stopping at the first
+/// failing one, and reports each of them on stdout:
+///
+/// * `path-dependency-versions`: path and git dependencies have a version.
+/// * `included-files`: the source files of all targets are in the package,
+///   given `package.include` and `package.exclude`.
+/// * `vcs-clean`: the files of the package are committed, unless
+///   `--allow-dirty` is passed.
+/// * `verify`: the package builds from its `.crate` file on its own, with
+///   dependencies from the registry, unless `--no-verify` is passed. The
+///   `.crate` file is left in the scratch space.
+///
+/// Returns whether all the checks passed.
+fn check_consistency(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    opts: &PackageOpts<'_>,
+    format: CheckFormat,
+) -> CargoResult<bool> {
+    let config = ws.config();
+    let mut src = PathSource::new(pkg.root(), pkg.package_id().source_id(), config);
+    src.update()?;
+    let src_files = src.list_files(pkg)?;
+
+    let mut checks = Vec::new();
+
+    let errors: Vec<String> = pkg
+        .dependencies()
+        .iter()
+        .filter_map(|dep| super::check_dep_has_version(dep, false).err())
+        .map(|e| e.to_string())
+        .collect();
+    checks.push((
+        "path-dependency-versions",
+        if errors.is_empty() {
+            CheckOutcome::Passed
+        } else {
+            CheckOutcome::Failed(errors.join("\n"))
+        },
+    ));
+
+    let missing: Vec<String> = pkg
+        .targets()
+        .iter()
+        .filter_map(|target| target.src_path().path())
+        .filter(|path| !src_files.iter().any(|file| file == path))
+        .map(|path| {
+            let path = path.strip_prefix(pkg.root()).unwrap_or(path);
+            format!("`{}`", path.display())
+        })
+        .collect();
+    checks.push((
+        "included-files",
+        if missing.is_empty() {
+            CheckOutcome::Passed
+        } else {
+            CheckOutcome::Failed(format!(
+                "source files of targets are not included in the package: {}",
+                missing.join(", ")
+            ))
+        },
+    ));
+
+    checks.push((
+        "vcs-clean",
+        if opts.allow_dirty {
+            CheckOutcome::Skipped("`--allow-dirty` was passed")
+        } else {
+            match check_repo_state(pkg, &src_files, config) {
+                Ok(_) => CheckOutcome::Passed,
+                Err(e) => CheckOutcome::Failed(format!("{e:#}")),
+            }
+        },
+    ));
+
+    checks.push((
+        "verify",
+        if opts.verify {
+            match tar_and_verify(ws, pkg, src_files, opts) {
+                Ok(()) => CheckOutcome::Passed,
+                Err(e) => CheckOutcome::Failed(format!("{e:#}")),
+            }
+        } else {
+            CheckOutcome::Skipped("`--no-verify` was passed")
+        },
+    ));
+
+    match format {
+        CheckFormat::Json => {
+            for (check, outcome) in &checks {
+                let msg = machine_message::PackageCheck {
+                    package_id: pkg.package_id(),
+                    check,
+                    status: outcome.status(),
+                    message: outcome.message(),
+                };
+                drop_println!(config, "{}", msg.to_json_string());
+            }
+        }
+        CheckFormat::Text => {
+            drop_println!(config, "{}", pkg.package_id());
+            for (check, outcome) in &checks {
+                let status = outcome.status();
+                match outcome {
+                    CheckOutcome::Passed => drop_println!(config, "  {check:<24}  {status}"),
+                    CheckOutcome::Skipped(reason) => {
+                        drop_println!(config, "  {check:<24}  {status}, {reason}")
+                    }
+                    CheckOutcome::Failed(message) => {
+                        drop_println!(config, "  {check:<24}  {status}");
+                        for line in message.lines() {
+                            drop_println!(config, "    {line}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(checks
+        .iter()
+        .all(|(_, outcome)| !matches!(outcome, CheckOutcome::Failed(_))))
+}
+
+/// Builds the `.crate` file of `pkg` in the scratch space, and verifies it.
+fn tar_and_verify(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    src_files: Vec<PathBuf>,
+    opts: &PackageOpts<'_>,
+) -> CargoResult<()> {
+    let config = ws.config();
+    let ar_files = build_ar_list(ws, pkg, src_files, None)?;
+    let filename = pkg.package_id().tarball_name();
+    let dir = ws.target_dir().join("package");
+    let mut dst = dir.open_rw(format!(".{}", filename), config, "package scratch space")?;
+    dst.file().set_len(0)?;
+    tar(ws, pkg, ar_files, dst.file(), &filename)
+        .with_context(|| "failed to prepare local package for uploading")?;
+    dst.seek(SeekFrom::Start(0))?;
+    run_verify(ws, pkg, &dst, opts).with_context(|| "failed to verify package tarball")
+}
+
 /// Builds list of files to archive.
 fn build_ar_list(
     ws: &Workspace<'_>,
@@ -393,7 +602,7 @@ fn warn_on_nonexistent_file(
     let rel_msg = if path.is_absolute() {
         "".to_string()
     } else {
-        format!(" (relative to `{}`)", pkg.root().display())
+        format!(" (relative to `{}`)", ws.display_path(pkg.root()))
     };
     ws.config().shell().warn(&format!(
         "{manifest_key_name} `{}` does not appear to exist{}.\n\
@@ -401,7 +610,7 @@ fn warn_on_nonexistent_file(
                 This may become a hard error in the future.",
         path.display(),
         rel_msg,
-        pkg.manifest_path().display()
+        ws.display_path(pkg.manifest_path())
     ))
 }
 
@@ -527,13 +736,16 @@ fn check_repo_state(
                 shell.warn(format!(
                     "No (git) Cargo.toml found at `{}` in workdir `{}`",
                     path.display(),
-                    workdir.display()
+                    config.display_path(workdir, config.cwd())
                 ))
             })?;
         }
     } else {
         config.shell().verbose(|shell| {
-            shell.warn(format!("No (git) VCS found for `{}`", p.root().display()))
+            shell.warn(format!(
+                "No (git) VCS found for `{}`",
+                config.display_path(p.root(), config.cwd())
+            ))
         })?;
     }
 
@@ -671,16 +883,25 @@ fn tar(
         match contents {
             FileContents::OnDisk(disk_path) => {
                 let mut file = File::open(&disk_path).with_context(|| {
-                    format!("failed to open for archiving: `{}`", disk_path.display())
+                    format!(
+                        "failed to open for archiving: `{}`",
+                        ws.display_path(&disk_path)
+                    )
                 })?;
                 let metadata = file.metadata().with_context(|| {
-                    format!("could not learn metadata for: `{}`", disk_path.display())
+                    format!(
+                        "could not learn metadata for: `{}`",
+                        ws.display_path(&disk_path)
+                    )
                 })?;
                 header.set_metadata_in_mode(&metadata, HeaderMode::Deterministic);
                 header.set_cksum();
                 ar.append_data(&mut header, &ar_path, &mut file)
                     .with_context(|| {
-                        format!("could not archive source file `{}`", disk_path.display())
+                        format!(
+                            "could not archive source file `{}`",
+                            ws.display_path(&disk_path)
+                        )
                     })?;
                 uncompressed_size += metadata.len() as u64;
             }
@@ -847,7 +1068,9 @@ fn run_verify(
 ) -> CargoResult<()> {
     let config = ws.config();
 
-    config.shell().status("Verifying", pkg)?;
+    config
+        .shell()
+        .status("Verifying", ws.display_package_id(pkg.package_id()))?;
 
     let f = GzDecoder::new(tar.file());
     let dst = tar
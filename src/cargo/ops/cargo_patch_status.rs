@@ -0,0 +1,276 @@
+//! Implementation of `cargo patch status`, which reports the state of each
+//! entry of the `[patch]` table, and the diagnostics of unused patches shared
+//! with the warnings of [`ops::resolve`](super::resolve).
+//!
+//! A patch is unused when the resolver picked something else for all the
+//! dependencies on the package it patches. The usual reasons are, in the
+//! order they are checked:
+//!
+//! * another patch of the same package for the same source was picked,
+//! * the patched source is misspelled,
+//! * nothing depends on the package any more,
+//! * the version of the patch doesn't match the requirement of a dependency,
+//! * the lock file pins the upstream version.
+//!
+//! A used patch may also have become unneeded, once upstream released a
+//! version which is at least as recent as the patch. Checking it requires a
+//! query of the upstream source, so it is only done by `cargo patch status`.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::task::Poll;
+
+use anyhow::Context as _;
+use semver::Version;
+
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::Resolve;
+use crate::core::{Dependency, PackageId, QueryKind, Registry, SourceId, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::sources::CRATES_IO_INDEX;
+use crate::util::interning::InternedString;
+use crate::util::{CanonicalUrl, CargoResult, OptVersionReq};
+
+/// Why a patch was not used in the crate graph.
+pub enum UnusedPatch {
+    /// Another patch of the same package for the same source is used.
+    Superseded(PackageId),
+    /// The package is only in the crate graph from other sources.
+    OtherSources(Vec<SourceId>),
+    /// Nothing in the crate graph depends on the package.
+    NoDependents(InternedString),
+    /// A dependency requires a version the patch doesn't have.
+    VersionMismatch {
+        dependent: PackageId,
+        req: OptVersionReq,
+    },
+    /// The lock file pins the upstream package.
+    Locked(PackageId),
+}
+
+impl fmt::Display for UnusedPatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnusedPatch::Superseded(other) => write!(
+                f,
+                "Another patch of the same package, `{other}`, is used instead.\n\
+                 Remove one of them from `[patch]`."
+            ),
+            UnusedPatch::OtherSources(ids) => {
+                write!(
+                    f,
+                    "Perhaps you misspelled the source URL being patched.\n\
+                     Possible URLs for `[patch.<URL>]`:"
+                )?;
+                for id in ids {
+                    write!(f, "\n    {}", id.display_registry_name())?;
+                }
+                Ok(())
+            }
+            UnusedPatch::NoDependents(name) => write!(
+                f,
+                "No package in the crate graph depends on `{name}`.\n\
+                 Remove the patch, unless it is for an optional dependency which is not enabled."
+            ),
+            UnusedPatch::VersionMismatch { dependent, req } => write!(
+                f,
+                "Its version doesn't match the requirement `{req}` of `{dependent}`.\n\
+                 Change the version of the patch, or the requirement of the dependency."
+            ),
+            UnusedPatch::Locked(locked) => write!(
+                f,
+                "`{locked}` is locked in Cargo.lock instead.\n\
+                 Run `cargo update -p {}@{}` to use the patch.",
+                locked.name(),
+                locked.version()
+            ),
+        }
+    }
+}
+
+/// Tells why the patch `unused` of the source `url` was not used in
+/// `resolve`, if it can be found out. `patches` are all the patches of `url`.
+pub fn diagnose_unused_patch(
+    resolve: &Resolve,
+    unused: PackageId,
+    url: &CanonicalUrl,
+    patches: &[PackageId],
+) -> Option<UnusedPatch> {
+    let in_graph: HashSet<_> = resolve.iter().collect();
+    if let Some(other) = patches
+        .iter()
+        .find(|id| **id != unused && id.name() == unused.name() && in_graph.contains(id))
+    {
+        return Some(UnusedPatch::Superseded(*other));
+    }
+
+    let dependents = dependents(resolve, unused.name(), url);
+    if dependents.is_empty() {
+        let mut ids: Vec<_> = in_graph
+            .iter()
+            .filter(|id| id.name() == unused.name())
+            .map(|id| id.source_id())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if ids.is_empty() {
+            return Some(UnusedPatch::NoDependents(unused.name()));
+        }
+        ids.sort();
+        return Some(UnusedPatch::OtherSources(ids));
+    }
+
+    // The dependencies of the lock file only match the locked version, so the
+    // patch is compared to the requirement of the manifest.
+    let mismatch = dependents.iter().find_map(|(dependent, dep)| {
+        let req = match dep.version_req() {
+            OptVersionReq::Locked(_, req) => OptVersionReq::Req(req.clone()),
+            req => req.clone(),
+        };
+        (!req.matches(unused.version())).then_some((*dependent, req))
+    });
+    if let Some((dependent, req)) = mismatch {
+        return Some(UnusedPatch::VersionMismatch { dependent, req });
+    }
+
+    in_graph
+        .iter()
+        .filter(|id| id.name() == unused.name() && id.source_id().canonical_url() == url)
+        .max()
+        .map(|locked| UnusedPatch::Locked(*locked))
+}
+
+/// The dependencies in `resolve` on the package `name` of the source `url`,
+/// with the package declaring them.
+fn dependents<'a>(
+    resolve: &'a Resolve,
+    name: InternedString,
+    url: &CanonicalUrl,
+) -> Vec<(PackageId, &'a Dependency)> {
+    let mut dependents: Vec<_> = resolve
+        .iter()
+        .flat_map(|pkg| resolve.deps(pkg).map(move |(_, deps)| (pkg, deps)))
+        .flat_map(|(pkg, deps)| deps.iter().map(move |dep| (pkg, dep)))
+        .filter(|(_, dep)| dep.package_name() == name && dep.source_id().canonical_url() == url)
+        .collect();
+    dependents.sort_by_key(|(pkg, _)| *pkg);
+    dependents
+}
+
+/// Prints the state of each entry of the `[patch]` table of `ws`.
+pub fn patch_status(ws: &Workspace<'_>) -> CargoResult<()> {
+    let config = ws.config();
+    let root_patch = ws.root_patch()?;
+    if root_patch.values().all(|patches| patches.is_empty()) {
+        return config
+            .shell()
+            .note("no packages are patched in the `[patch]` table");
+    }
+
+    let (_, resolve) = ops::resolve_ws(ws)?;
+    let unused: HashSet<_> = resolve.unused_patches().iter().copied().collect();
+    let candidates: Vec<_> = resolve.iter().chain(unused.iter().copied()).collect();
+
+    // No patches are added to this registry, to query the upstream sources.
+    let mut registry = PackageRegistry::new(config)?;
+    let _lock = config.acquire_package_cache_lock()?;
+    registry.lock_patches();
+
+    let mut urls: Vec<_> = root_patch.keys().collect();
+    urls.sort();
+    for url in urls {
+        let canonical = CanonicalUrl::new(url)?;
+        let patches: Vec<PackageId> = root_patch[url]
+            .iter()
+            .filter_map(|dep| candidates.iter().copied().find(|id| dep.matches_id(*id)))
+            .collect();
+        for &patch in &patches {
+            let dependents = dependents(&resolve, patch.name(), &canonical);
+            let upstream = dependents.first().map(|(_, dep)| dep.source_id());
+            let source = match upstream {
+                Some(id) => id.display_registry_name(),
+                None if url.as_str() == CRATES_IO_INDEX => String::from("crates-io"),
+                None => url.to_string(),
+            };
+
+            if unused.contains(&patch) {
+                drop_println!(config, "{patch} patches {source}: unused");
+                if let Some(reason) = diagnose_unused_patch(&resolve, patch, &canonical, &patches) {
+                    for line in reason.to_string().lines() {
+                        drop_println!(config, "    {line}");
+                    }
+                }
+                continue;
+            }
+
+            drop_println!(config, "{patch} patches {source}: used");
+            for (dependent, dep) in &dependents {
+                drop_println!(
+                    config,
+                    "    required as `{}` by {dependent}",
+                    dep.version_req()
+                );
+            }
+            let Some(upstream) = upstream else {
+                continue;
+            };
+            let reqs: Vec<_> = dependents
+                .iter()
+                .map(|(_, dep)| dep.version_req())
+                .collect();
+            let versions = upstream_versions(&mut registry, patch.name(), upstream)?;
+            let matching = versions
+                .iter()
+                .filter(|version| reqs.iter().all(|req| req.matches(version)));
+            match matching.clone().max() {
+                Some(replaced) => {
+                    drop_println!(config, "    replaces {} v{replaced}", patch.name())
+                }
+                None => drop_println!(
+                    config,
+                    "    no version of {} in {source} matches its dependents",
+                    patch.name()
+                ),
+            }
+            if let Some(released) = matching
+                .filter(|version| *version >= patch.version() && version.pre.is_empty())
+                .max()
+            {
+                drop_println!(
+                    config,
+                    "    {} v{released} was released in {source}, \
+                     the patch may no longer be needed",
+                    patch.name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The versions of the package `name` in the source `source_id`, ignoring
+/// patches.
+fn upstream_versions(
+    registry: &mut PackageRegistry<'_>,
+    name: InternedString,
+    source_id: SourceId,
+) -> CargoResult<Vec<Version>> {
+    let dep = Dependency::parse(name, None, source_id)?;
+    let summaries = loop {
+        match registry.query_vec(&dep, QueryKind::Exact) {
+            Poll::Ready(summaries) => break summaries,
+            Poll::Pending => registry.block_until_ready()?,
+        }
+    }
+    .with_context(|| {
+        format!(
+            "failed to query `{name}` in {}",
+            source_id.display_registry_name()
+        )
+    })?;
+    Ok(summaries
+        .iter()
+        .map(|summary| summary.version().clone())
+        .collect())
+}
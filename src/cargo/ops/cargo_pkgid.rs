@@ -1,8 +1,51 @@
-use crate::core::{PackageIdSpec, Workspace};
+use serde::Serialize;
+
+use crate::core::{PackageId, PackageIdSpec, Workspace};
 use crate::ops;
+use crate::util::interning::InternedString;
 use crate::util::CargoResult;
 
+/// Information about a package printed by `cargo pkgid --format json`.
+#[derive(Serialize)]
+pub struct PkgidInfo {
+    /// The fully qualified specification of the package.
+    pub pkgid: PackageIdSpec,
+    /// The shortest specification which only matches this package, to be
+    /// passed to `-p`.
+    pub spec: PackageIdSpec,
+    pub name: InternedString,
+    pub version: String,
+    /// The URL of the source of the package.
+    pub source: String,
+    /// Whether other packages in `Cargo.lock` have the same name, so that
+    /// the name alone is not enough to select this one.
+    pub ambiguous: bool,
+}
+
 pub fn pkgid(ws: &Workspace<'_>, spec: Option<&str>) -> CargoResult<PackageIdSpec> {
+    let (pkgid, _) = query(ws, spec)?;
+    Ok(PackageIdSpec::from_package_id(pkgid))
+}
+
+/// Like [`pkgid`], with more information about the package and how to
+/// select it.
+pub fn pkgid_info(ws: &Workspace<'_>, spec: Option<&str>) -> CargoResult<PkgidInfo> {
+    let (pkgid, ids) = query(ws, spec)?;
+    Ok(PkgidInfo {
+        pkgid: PackageIdSpec::from_package_id(pkgid),
+        spec: PackageIdSpec::minimal(pkgid, ids.iter().copied()),
+        name: pkgid.name(),
+        version: pkgid.version().to_string(),
+        source: pkgid.source_id().url().to_string(),
+        ambiguous: ids
+            .iter()
+            .any(|&id| id != pkgid && id.name() == pkgid.name()),
+    })
+}
+
+/// Finds the package matching `spec`, returning it and all the packages of
+/// the lock file.
+fn query(ws: &Workspace<'_>, spec: Option<&str>) -> CargoResult<(PackageId, Vec<PackageId>)> {
     let resolve = match ops::load_pkg_lockfile(ws)? {
         Some(resolve) => resolve,
         None => anyhow::bail!("a Cargo.lock must exist for this command"),
@@ -12,5 +55,5 @@ pub fn pkgid(ws: &Workspace<'_>, spec: Option<&str>) -> CargoResult<PackageIdSpe
         Some(spec) => PackageIdSpec::query_str(spec, resolve.iter())?,
         None => ws.current()?.package_id(),
     };
-    Ok(PackageIdSpec::from_package_id(pkgid))
+    Ok((pkgid, resolve.iter().collect()))
 }
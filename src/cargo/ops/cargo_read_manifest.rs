@@ -27,7 +27,7 @@ pub fn read_package(
         EitherManifest::Virtual(..) => anyhow::bail!(
             "found a virtual manifest at `{}` instead of a package \
              manifest",
-            path.display()
+            config.display_path(path, config.cwd())
         ),
     };
 
@@ -92,12 +92,12 @@ pub fn read_packages(
                 if find_project_manifest_exact(path, "cargo.toml").is_ok() {
                     Err(anyhow::format_err!(
                 "Could not find Cargo.toml in `{}`, but found cargo.toml please try to rename it to Cargo.toml",
-                path.display()
+                config.display_path(path, config.cwd())
             ))
                 } else {
                     Err(anyhow::format_err!(
                         "Could not find Cargo.toml in `{}`",
-                        path.display()
+                        config.display_path(path, config.cwd())
                     ))
                 }
             }
@@ -190,7 +190,7 @@ fn read_nested_packages(
                 let _ = config.shell().warn(format!(
                     "skipping duplicate package `{}` found at `{}`",
                     pkg.name(),
-                    path.display()
+                    config.display_path(path, config.cwd())
                 ));
             }
         }
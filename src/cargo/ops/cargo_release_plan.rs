@@ -0,0 +1,375 @@
+//! Implementation of `cargo release-plan`, which computes how the members of a
+//! workspace would be released without publishing, tagging, or committing
+//! anything.
+//!
+//! The plan is built from the workspace graph and the versions already
+//! available in the registry:
+//!
+//! * Members whose manifest version isn't in the registry yet are published.
+//! * With a base revision, members changed since it without a version bump get
+//!   a patch bump, see [`cargo_version_check`].
+//! * Members depending on a member released with a semver-incompatible version
+//!   get a patch bump, so that a release using the new version exists.
+//!
+//! [`cargo_version_check`]: super::cargo_version_check
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::task::Poll;
+
+use anyhow::Context as _;
+use serde::Serialize;
+
+use crate::core::dependency::DepKind;
+use crate::core::{Dependency, Package, QueryKind, SourceId, Workspace};
+use crate::ops::cargo_version_check;
+use crate::sources::{SourceConfigMap, CRATES_IO_REGISTRY};
+use crate::util::CargoResult;
+
+/// The version of the JSON output of `cargo release-plan`.
+const VERSION: u32 = 1;
+
+pub struct ReleasePlanOptions {
+    /// Registry the members are published to, crates.io if unset.
+    pub registry: Option<String>,
+    /// Git revision of the previous release. Members changed since then
+    /// without a version bump are planned to be bumped.
+    pub base_rev: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReleasePlan {
+    pub version: u32,
+    pub registry: String,
+    /// The members to publish, in the order they must be published in.
+    pub packages: Vec<PlannedPackage>,
+    /// The members which are not published, sorted by name.
+    pub skipped: Vec<SkippedPackage>,
+    /// The tags to create once the members are published, one per member.
+    pub tags: Vec<String>,
+    /// The commits to create before publishing.
+    pub commits: Vec<PlannedCommit>,
+}
+
+#[derive(Serialize)]
+pub struct PlannedPackage {
+    pub name: String,
+    /// The version to publish.
+    pub version: String,
+    /// The version in the manifest, which differs from `version` if the
+    /// member needs a bump.
+    pub manifest_version: String,
+    /// The greatest version in the registry, if any.
+    pub registry_version: Option<String>,
+    pub reason: PublishReason,
+    /// Members which must be published before this one.
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishReason {
+    /// No version of the member is in the registry.
+    Unpublished,
+    /// The manifest version is greater than any version in the registry.
+    NewVersion,
+    /// The member changed since the base revision without a version bump.
+    Changed,
+    /// A dependency of the member is released with a semver-incompatible
+    /// version.
+    Dependency,
+}
+
+#[derive(Serialize)]
+pub struct SkippedPackage {
+    pub name: String,
+    pub version: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// `package.publish` doesn't allow the registry.
+    NotPublishable,
+    /// The manifest version is already in the registry.
+    UpToDate,
+}
+
+#[derive(Serialize)]
+pub struct PlannedCommit {
+    pub message: String,
+    pub bumps: Vec<VersionBump>,
+}
+
+#[derive(Serialize)]
+pub struct VersionBump {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Computes the release plan of the members of `ws`.
+///
+/// This only reads the registry index and, with a base revision, the git
+/// repository containing the workspace.
+pub fn release_plan(ws: &Workspace<'_>, opts: &ReleasePlanOptions) -> CargoResult<ReleasePlan> {
+    let config = ws.config();
+    let reg_name = opts.registry.as_deref().unwrap_or(CRATES_IO_REGISTRY);
+    let source_id = match &opts.registry {
+        Some(registry) => SourceId::alt_registry(config, registry)?,
+        None => SourceId::crates_io(config)?,
+    };
+
+    let mut skipped = Vec::new();
+    let mut members = Vec::new();
+    for pkg in ws.members() {
+        let publishable = pkg
+            .publish()
+            .as_ref()
+            .map_or(true, |regs| regs.iter().any(|r| r == reg_name));
+        if publishable {
+            members.push(pkg);
+        } else {
+            skipped.push(SkippedPackage {
+                name: pkg.name().to_string(),
+                version: pkg.version().to_string(),
+                reason: SkipReason::NotPublishable,
+            });
+        }
+    }
+
+    let registry_versions = registry_versions(ws, source_id, &members)?;
+    let changed: HashSet<_> = match &opts.base_rev {
+        Some(base_rev) => {
+            let repo = git2::Repository::discover(ws.root())
+                .with_context(|| format!("`{}` is not in a git repository", ws.root().display()))?;
+            let base = repo
+                .revparse_single(base_rev)
+                .and_then(|obj| obj.peel_to_commit())
+                .with_context(|| format!("failed to find git revision `{base_rev}`"))?;
+            let head = repo.head()?.peel_to_commit()?;
+            cargo_version_check::changed_members(ws, &repo, &base, &head)?
+                .into_iter()
+                .map(|pkg| pkg.package_id())
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+
+    let mut planned: BTreeMap<&str, (semver::Version, PublishReason)> = BTreeMap::new();
+    for pkg in &members {
+        let latest = registry_versions.get(pkg.name().as_str());
+        let reason = match latest {
+            None => Some(PublishReason::Unpublished),
+            Some(latest) if pkg.version() > latest => Some(PublishReason::NewVersion),
+            Some(_) if changed.contains(&pkg.package_id()) => Some(PublishReason::Changed),
+            Some(_) => None,
+        };
+        if let Some(reason) = reason {
+            planned.insert(
+                pkg.name().as_str(),
+                (next_version(pkg, latest, reason), reason),
+            );
+        }
+    }
+
+    // A patch bump is always compatible, so bumps due to dependencies don't
+    // need to be propagated further.
+    let mut dependency_bumps = Vec::new();
+    for pkg in &members {
+        let name = pkg.name().as_str();
+        let Some(latest) = registry_versions.get(name) else {
+            continue;
+        };
+        if planned.contains_key(name) {
+            continue;
+        }
+        let breaking = ws_deps(pkg, &members).any(|dep| {
+            let dep_name = dep.name().as_str();
+            match (planned.get(dep_name), registry_versions.get(dep_name)) {
+                (Some((new, _)), Some(old)) => !semver_compatible(new, old),
+                _ => false,
+            }
+        });
+        if breaking {
+            let reason = PublishReason::Dependency;
+            dependency_bumps.push((name, next_version(pkg, Some(latest), reason)));
+        }
+    }
+    for (name, version) in dependency_bumps {
+        planned.insert(name, (version, PublishReason::Dependency));
+    }
+
+    let to_publish: Vec<&Package> = members
+        .iter()
+        .copied()
+        .filter(|pkg| planned.contains_key(pkg.name().as_str()))
+        .collect();
+    let mut packages = Vec::new();
+    let mut bumps = Vec::new();
+    for pkg in publish_order(&to_publish)? {
+        let name = pkg.name().as_str();
+        let (version, reason) = &planned[name];
+        if version != pkg.version() {
+            bumps.push(VersionBump {
+                name: name.to_string(),
+                from: pkg.version().to_string(),
+                to: version.to_string(),
+            });
+        }
+        packages.push(PlannedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            manifest_version: pkg.version().to_string(),
+            registry_version: registry_versions.get(name).map(|v| v.to_string()),
+            reason: *reason,
+            dependencies: ws_deps(pkg, &members)
+                .filter(|dep| planned.contains_key(dep.name().as_str()))
+                .map(|dep| dep.name().to_string())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        });
+    }
+
+    for pkg in &members {
+        if !planned.contains_key(pkg.name().as_str()) {
+            skipped.push(SkippedPackage {
+                name: pkg.name().to_string(),
+                version: pkg.version().to_string(),
+                reason: SkipReason::UpToDate,
+            });
+        }
+    }
+    skipped.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let tags = packages
+        .iter()
+        .map(|pkg| format!("{}-v{}", pkg.name, pkg.version))
+        .collect();
+    let mut commits = Vec::new();
+    if !bumps.is_empty() {
+        let names: Vec<_> = bumps.iter().map(|b| b.name.as_str()).collect();
+        commits.push(PlannedCommit {
+            message: format!("Bump versions of {}", names.join(", ")),
+            bumps,
+        });
+    }
+
+    Ok(ReleasePlan {
+        version: VERSION,
+        registry: reg_name.to_string(),
+        packages,
+        skipped,
+        tags,
+        commits,
+    })
+}
+
+/// Queries the greatest version of each member in the registry.
+fn registry_versions(
+    ws: &Workspace<'_>,
+    source_id: SourceId,
+    members: &[&Package],
+) -> CargoResult<HashMap<String, semver::Version>> {
+    let config = ws.config();
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut source = SourceConfigMap::new(config)?.load(source_id, &HashSet::new())?;
+    let mut versions = HashMap::new();
+    for pkg in members {
+        let query = Dependency::parse(pkg.name(), None, source_id)?;
+        let summaries = loop {
+            // Exact to avoid returning all for path/git
+            match source.query_vec(&query, QueryKind::Exact) {
+                Poll::Ready(res) => break res?,
+                Poll::Pending => source.block_until_ready()?,
+            }
+        };
+        if let Some(latest) = summaries.iter().map(|s| s.version()).max() {
+            versions.insert(pkg.name().to_string(), latest.clone());
+        }
+    }
+    Ok(versions)
+}
+
+/// Returns the members `pkg` needs to be published after.
+///
+/// Dev-dependencies are left out, as they are stripped from path
+/// dependencies when publishing.
+pub(crate) fn ws_deps<'a>(
+    pkg: &'a Package,
+    members: &'a [&'a Package],
+) -> impl Iterator<Item = &'a Package> + 'a {
+    members.iter().copied().filter(move |member| {
+        pkg.dependencies().iter().any(|dep| {
+            dep.kind() != DepKind::Development
+                && dep.package_name() == member.name()
+                && dep.source_id() == member.package_id().source_id()
+        })
+    })
+}
+
+/// Sorts `pkgs` so that each one comes after the packages of `pkgs` it
+/// depends on, and by name otherwise.
+///
+/// Fails if some of `pkgs` depend on each other in a cycle, so that none of
+/// them can be published first.
+pub(crate) fn publish_order<'a>(pkgs: &[&'a Package]) -> CargoResult<Vec<&'a Package>> {
+    let mut pending: BTreeMap<&str, (&'a Package, BTreeSet<&str>)> = pkgs
+        .iter()
+        .map(|&pkg| {
+            let deps = ws_deps(pkg, pkgs).map(|dep| dep.name().as_str()).collect();
+            (pkg.name().as_str(), (pkg, deps))
+        })
+        .collect();
+    let mut order = Vec::new();
+    loop {
+        let Some(next) = pending
+            .iter()
+            .find(|(_, (_, deps))| deps.is_empty())
+            .map(|(&name, _)| name)
+        else {
+            break;
+        };
+        let (pkg, _) = pending.remove(next).unwrap();
+        for (_, deps) in pending.values_mut() {
+            deps.remove(next);
+        }
+        order.push(pkg);
+    }
+    if !pending.is_empty() {
+        let names: Vec<_> = pending.keys().map(|name| format!("`{name}`")).collect();
+        anyhow::bail!(
+            "cannot publish {} as they depend on each other in a cycle",
+            names.join(", ")
+        );
+    }
+    Ok(order)
+}
+
+/// The version `pkg` is published with.
+fn next_version(
+    pkg: &Package,
+    latest: Option<&semver::Version>,
+    reason: PublishReason,
+) -> semver::Version {
+    match reason {
+        PublishReason::Unpublished | PublishReason::NewVersion => pkg.version().clone(),
+        PublishReason::Changed | PublishReason::Dependency => {
+            let current = pkg.version().max(latest.unwrap_or(pkg.version()));
+            semver::Version::new(current.major, current.minor, current.patch + 1)
+        }
+    }
+}
+
+/// Whether `a` and `b` have the same left-most non-zero component, as with
+/// caret requirements.
+fn semver_compatible(a: &semver::Version, b: &semver::Version) -> bool {
+    match (a.major, b.major) {
+        (0, 0) => match (a.minor, b.minor) {
+            (0, 0) => a.patch == b.patch,
+            (x, y) => x == y,
+        },
+        (x, y) => x == y,
+    }
+}
@@ -0,0 +1,180 @@
+//! Implementation of `cargo remove-member`, which detaches a package from its
+//! workspace.
+//!
+//! The package is removed from `workspace.members`, or added to
+//! `workspace.exclude` if it is only matched by a glob. Path dependencies of
+//! the other members on the package would pull it back into the workspace,
+//! so they are either rewritten to depend on its version from the registry,
+//! or the package is excluded from the workspace as well.
+
+use std::path::Path;
+
+use anyhow::bail;
+use cargo_util::paths;
+
+use crate::core::Workspace;
+use crate::util::toml_mut::manifest::LocalManifest;
+use crate::util::CargoResult;
+
+pub struct RemoveMemberOptions {
+    /// Name of the member to remove.
+    pub member: String,
+    /// Replace the path dependencies of the other members on the removed one
+    /// with a dependency on its version from the registry.
+    pub rewrite_deps: bool,
+}
+
+/// Removes a member from the workspace `ws`.
+pub fn remove_member(ws: &Workspace<'_>, opts: &RemoveMemberOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let Some(member) = ws.members().find(|pkg| pkg.name().as_str() == opts.member) else {
+        bail!(
+            "package `{}` is not a member of the workspace at `{}`",
+            opts.member,
+            ws.root_manifest().display()
+        );
+    };
+    if member.manifest_path() == ws.root_manifest() {
+        bail!(
+            "package `{}` is the root package of the workspace and cannot be removed",
+            opts.member
+        );
+    }
+    let member_root = member.root();
+    let version = member.version().to_string();
+    let relative = pathdiff::diff_paths(member_root, ws.root())
+        .expect("both paths are absolute")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut root_manifest = LocalManifest::try_new(ws.root_manifest())?;
+    let mut remaining = Vec::new();
+
+    // Dependencies inherited with `workspace = true` are declared in the
+    // root manifest.
+    let ws_deps_path = ["workspace".to_string(), "dependencies".to_string()];
+    if root_manifest.get_table(&ws_deps_path).is_ok() {
+        let table = root_manifest.get_table_mut(&ws_deps_path)?;
+        for name in detach_path_deps(table, ws.root(), member_root, &version, opts.rewrite_deps) {
+            if opts.rewrite_deps {
+                config.shell().status(
+                    "Updating",
+                    format!("`workspace.dependencies.{}` to version `{}`", name, version),
+                )?;
+            } else {
+                remaining.push(format!("`workspace.dependencies.{}`", name));
+            }
+        }
+    }
+
+    for pkg in ws.members() {
+        if pkg.package_id() == member.package_id() {
+            continue;
+        }
+        let mut manifest = if pkg.manifest_path() == ws.root_manifest() {
+            // Edit the root manifest in place, as it is written once below.
+            None
+        } else {
+            Some(LocalManifest::try_new(pkg.manifest_path())?)
+        };
+        let mut detached = Vec::new();
+        {
+            let manifest = manifest.as_mut().unwrap_or(&mut root_manifest);
+            for (table, _) in manifest.get_sections() {
+                let table_path: Vec<_> = table.to_table().into_iter().map(String::from).collect();
+                let item = manifest.get_table_mut(&table_path)?;
+                for name in
+                    detach_path_deps(item, pkg.root(), member_root, &version, opts.rewrite_deps)
+                {
+                    detached.push((table_path.join("."), name));
+                }
+            }
+        }
+        for (table, name) in detached {
+            if opts.rewrite_deps {
+                config.shell().status(
+                    "Updating",
+                    format!(
+                        "`{}.{}` of `{}` to version `{}`",
+                        table,
+                        name,
+                        pkg.name(),
+                        version
+                    ),
+                )?;
+            } else {
+                remaining.push(format!("`{}.{}` of `{}`", table, name, pkg.name()));
+            }
+        }
+        if let Some(manifest) = manifest {
+            if opts.rewrite_deps {
+                manifest.write()?;
+            }
+        }
+    }
+
+    let listed = root_manifest.remove_workspace_member(&relative)?;
+    if !listed || !remaining.is_empty() {
+        root_manifest.add_workspace_exclude(&relative)?;
+    }
+    root_manifest.write_workspace()?;
+    config.shell().status(
+        "Removing",
+        format!(
+            "`{}` from the members of the workspace at `{}`",
+            relative,
+            ws.root_manifest().display()
+        ),
+    )?;
+    if !remaining.is_empty() {
+        config.shell().warn(format!(
+            "`{}` is still a path dependency of:\n  {}\n\
+             it was added to `workspace.exclude`, \
+             pass `--rewrite-deps` to depend on its version from the registry instead",
+            opts.member,
+            remaining.join("\n  ")
+        ))?;
+    }
+    Ok(())
+}
+
+/// Finds the dependencies in the dependency table `table`, of a manifest in
+/// `crate_root`, on the package in `member_root` through a path, and returns
+/// their names. If `rewrite` is set, they are changed to depend on `version`
+/// instead.
+fn detach_path_deps(
+    table: &mut toml_edit::Item,
+    crate_root: &Path,
+    member_root: &Path,
+    version: &str,
+    rewrite: bool,
+) -> Vec<String> {
+    let mut names = Vec::new();
+    let Some(table) = table.as_table_like_mut() else {
+        return names;
+    };
+    for (name, dep_item) in table.iter_mut() {
+        let Some(dep) = dep_item.as_table_like_mut() else {
+            continue;
+        };
+        let Some(path) = dep.get("path").and_then(|path| path.as_str()) else {
+            continue;
+        };
+        if paths::normalize_path(&crate_root.join(path)) != member_root {
+            continue;
+        }
+        names.push(name.get().to_string());
+        if rewrite {
+            dep.remove("path");
+            if !dep.contains_key("version") {
+                dep.insert("version", toml_edit::value(version));
+            }
+            if let Some(dep) = dep_item.as_inline_table_mut() {
+                dep.fmt();
+            }
+        }
+    }
+    names
+}
@@ -0,0 +1,305 @@
+//! Implementation of `cargo report features`, which explains why each feature
+//! of a package is enabled.
+//!
+//! The features of a package are unified across the whole dependency graph,
+//! so a feature may be enabled by a crate far away from the package. For each
+//! feature enabled in the [`ResolvedFeatures`] of the build, the report walks
+//! back from the feature to what requested it, until it reaches:
+//!
+//! * the command line, for the features of the selected packages,
+//! * or a dependency declaration, with `features = [..]` or default features.
+//!
+//! Along the way, a feature may be enabled by another feature of the same
+//! package, or by a `dep/feature` value in a feature of a dependent. The
+//! shortest such chain is reported.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+
+use crate::core::compiler::{CompileKind, RustcTargetData};
+use crate::core::dependency::DepKind;
+use crate::core::resolver::features::{CliFeatures, FeaturesFor, ResolvedFeatures};
+use crate::core::resolver::{ForceAllTargets, HasDevUnits, Resolve};
+use crate::core::{FeatureValue, PackageId, PackageIdSpec, Workspace};
+use crate::drop_print;
+use crate::ops::{self, Packages};
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+
+pub struct ReportFeaturesOptions {
+    pub cli_features: CliFeatures,
+    /// The package to report the features of, the current package if unset.
+    pub package: Option<String>,
+}
+
+/// A feature of a package.
+type Node = (PackageId, InternedString);
+
+/// What enabled a feature.
+enum Enabler {
+    /// The features selected on the command line.
+    CommandLine,
+    /// The default features of a package selected on the command line.
+    CommandLineDefault,
+    /// A dependency declaration of a package, with `features = [..]` or
+    /// default features.
+    Dependency {
+        pkg: PackageId,
+        name: InternedString,
+        kind: DepKind,
+    },
+    /// Another feature of the same package, or a feature of a dependent
+    /// with the given value.
+    Feature {
+        node: Node,
+        value: Option<FeatureValue>,
+    },
+}
+
+/// Prints which features of a package are enabled and why.
+pub fn report_features(ws: &Workspace<'_>, opts: &ReportFeaturesOptions) -> CargoResult<()> {
+    let requested_kinds = [CompileKind::Host];
+    let target_data = RustcTargetData::new(ws, &requested_kinds)?;
+    let specs = Packages::Default.to_package_id_specs(ws)?;
+    let ws_resolve = ops::resolve_ws_with_opts(
+        ws,
+        &target_data,
+        &requested_kinds,
+        &opts.cli_features,
+        &specs,
+        HasDevUnits::No,
+        ForceAllTargets::No,
+    )?;
+    let resolve = &ws_resolve.targeted_resolve;
+    let roots: HashSet<_> = resolve.specs_to_ids(&specs)?.into_iter().collect();
+
+    let pkg_id = match &opts.package {
+        Some(spec) => PackageIdSpec::query_str(spec, resolve.iter())?,
+        None => ws.current()?.package_id(),
+    };
+    let explainer = Explainer {
+        resolve,
+        features: &ws_resolve.resolved_features,
+        cli_features: &opts.cli_features,
+        roots: &roots,
+        dependents: dependents(resolve),
+    };
+
+    let mut features: Vec<_> = explainer.active_features(pkg_id).into_iter().collect();
+    features.sort();
+    let mut out = format!("features of {}:\n", pkg_id);
+    if features.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for feature in features {
+        let _ = write!(out, "  {}", feature);
+        let chain = explainer.explain((pkg_id, feature));
+        for (i, enabler) in chain.iter().enumerate() {
+            let prefix = if i == 0 { ": " } else { "    which is " };
+            let _ = writeln!(out, "{}enabled {}", prefix, explainer.describe(enabler));
+        }
+        if chain.is_empty() {
+            out.push('\n');
+        }
+    }
+    drop_print!(ws.config(), "{}", out);
+    Ok(())
+}
+
+/// Maps each package to the packages depending on it.
+fn dependents(resolve: &Resolve) -> HashMap<PackageId, Vec<PackageId>> {
+    let mut dependents: HashMap<_, Vec<_>> = HashMap::new();
+    for pkg_id in resolve.iter() {
+        for (dep_id, _) in resolve.deps(pkg_id) {
+            dependents.entry(dep_id).or_default().push(pkg_id);
+        }
+    }
+    for parents in dependents.values_mut() {
+        parents.sort();
+    }
+    dependents
+}
+
+struct Explainer<'a> {
+    resolve: &'a Resolve,
+    features: &'a ResolvedFeatures,
+    cli_features: &'a CliFeatures,
+    roots: &'a HashSet<PackageId>,
+    dependents: HashMap<PackageId, Vec<PackageId>>,
+}
+
+impl Explainer<'_> {
+    /// The features of `pkg_id` enabled for the target or the host.
+    fn active_features(&self, pkg_id: PackageId) -> HashSet<InternedString> {
+        [FeaturesFor::NormalOrDev, FeaturesFor::HostDep]
+            .into_iter()
+            .filter_map(|ff| self.features.activated_features_unverified(pkg_id, ff))
+            .flatten()
+            .collect()
+    }
+
+    /// Whether the optional dependency `name` of `pkg_id` is enabled, the
+    /// feature resolver doesn't track the other dependencies.
+    fn is_dep_active(&self, pkg_id: PackageId, name: InternedString) -> bool {
+        [FeaturesFor::NormalOrDev, FeaturesFor::HostDep]
+            .into_iter()
+            .any(|ff| self.features.is_dep_activated(pkg_id, ff, name))
+    }
+
+    /// Returns the chain of enablers from `node` to the command line or a
+    /// dependency declaration, found with a breadth-first search.
+    fn explain(&self, node: Node) -> Vec<Enabler> {
+        let mut queue = VecDeque::from([node]);
+        let mut visited = HashSet::from([node]);
+        // How each visited node was reached: the node it enables, and how.
+        let mut reached_by: HashMap<Node, (Node, Enabler)> = HashMap::new();
+        while let Some(current) = queue.pop_front() {
+            for enabler in self.enablers(current) {
+                let next = match &enabler {
+                    Enabler::Feature { node, .. } => *node,
+                    _ => {
+                        let mut chain = vec![enabler];
+                        let mut at = current;
+                        while let Some((to, enabler)) = reached_by.remove(&at) {
+                            chain.push(enabler);
+                            at = to;
+                        }
+                        chain.reverse();
+                        return chain;
+                    }
+                };
+                if visited.insert(next) {
+                    reached_by.insert(next, (current, enabler));
+                    queue.push_back(next);
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    /// Everything which directly enables the feature `node`.
+    fn enablers(&self, (pkg_id, feature): Node) -> Vec<Enabler> {
+        let mut enablers = Vec::new();
+        let summary = self.resolve.summary(pkg_id);
+        let feature_map = summary.features();
+
+        if self.roots.contains(&pkg_id) {
+            let cli = &self.cli_features;
+            if cli.all_features || cli.features.contains(&FeatureValue::Feature(feature)) {
+                enablers.push(Enabler::CommandLine);
+            } else if feature == "default" && cli.uses_default_features {
+                enablers.push(Enabler::CommandLineDefault);
+            }
+        }
+
+        let active = self.active_features(pkg_id);
+        let mut own: Vec<_> = active
+            .iter()
+            .filter(|f| **f != feature)
+            .filter(|f| {
+                feature_map.get(*f).map_or(false, |values| {
+                    values.iter().any(|value| match value {
+                        FeatureValue::Feature(f) => *f == feature,
+                        // `dep/feat` also enables the implicit feature of an
+                        // optional dependency.
+                        FeatureValue::DepFeature {
+                            dep_name,
+                            weak: false,
+                            ..
+                        } => *dep_name == feature,
+                        FeatureValue::Dep { .. } | FeatureValue::DepFeature { .. } => false,
+                    })
+                })
+            })
+            .collect();
+        own.sort();
+        enablers.extend(own.into_iter().map(|f| Enabler::Feature {
+            node: (pkg_id, *f),
+            value: None,
+        }));
+
+        for &parent in self.dependents.get(&pkg_id).into_iter().flatten() {
+            let parent_summary = self.resolve.summary(parent);
+            let parent_active = self.active_features(parent);
+            let deps = self
+                .resolve
+                .deps(parent)
+                .filter(|(id, _)| *id == pkg_id)
+                .flat_map(|(_, deps)| deps);
+            for dep in deps {
+                let name = dep.name_in_toml();
+                if dep.kind() == DepKind::Development
+                    || (dep.is_optional() && !self.is_dep_active(parent, name))
+                {
+                    continue;
+                }
+                let declared = dep.features().contains(&feature)
+                    || (feature == "default" && dep.uses_default_features());
+                if declared {
+                    enablers.push(Enabler::Dependency {
+                        pkg: parent,
+                        name,
+                        kind: dep.kind(),
+                    });
+                }
+                let value = FeatureValue::DepFeature {
+                    dep_name: name,
+                    dep_feature: feature,
+                    weak: false,
+                };
+                let weak_value = FeatureValue::DepFeature {
+                    dep_name: name,
+                    dep_feature: feature,
+                    weak: true,
+                };
+                if self.roots.contains(&parent)
+                    && (self.cli_features.features.contains(&value)
+                        || self.cli_features.features.contains(&weak_value))
+                {
+                    enablers.push(Enabler::CommandLine);
+                }
+                let mut from_features: Vec<_> = parent_active
+                    .iter()
+                    .filter_map(|f| {
+                        let values = parent_summary.features().get(f)?;
+                        values
+                            .iter()
+                            .find(|v| **v == value || **v == weak_value)
+                            .map(|v| (*f, v.clone()))
+                    })
+                    .collect();
+                from_features.sort_by_key(|(f, _)| *f);
+                enablers.extend(from_features.into_iter().map(|(f, v)| Enabler::Feature {
+                    node: (parent, f),
+                    value: Some(v),
+                }));
+            }
+        }
+        enablers
+    }
+
+    fn describe(&self, enabler: &Enabler) -> String {
+        match enabler {
+            Enabler::CommandLine => "on the command line".to_string(),
+            Enabler::CommandLineDefault => {
+                "as a default feature of a package selected on the command line".to_string()
+            }
+            Enabler::Dependency { pkg, name, kind } => {
+                let kind = match kind {
+                    DepKind::Normal => "dependency",
+                    DepKind::Development => "dev-dependency",
+                    DepKind::Build => "build-dependency",
+                };
+                format!("by the `{}` {} of {}", name, kind, pkg)
+            }
+            Enabler::Feature {
+                node: (pkg, feature),
+                value: None,
+            } => format!("by feature `{}` of {}", feature, pkg),
+            Enabler::Feature {
+                node: (pkg, feature),
+                value: Some(value),
+            } => format!("by feature `{}` of {} (`{}`)", feature, pkg, value),
+        }
+    }
+}
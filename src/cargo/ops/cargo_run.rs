@@ -28,7 +28,7 @@ pub fn run(
                 !target.is_lib()
                     && !target.is_custom_build()
                     && if !options.filter.is_specific() {
-                        target.is_bin()
+                        target.is_bin() && !target.requires_explicit()
                     } else {
                         options.filter.target_run(target)
                     }
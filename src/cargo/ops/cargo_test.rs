@@ -1,11 +1,16 @@
-use crate::core::compiler::{Compilation, CompileKind, Doctest, Metadata, Unit, UnitOutput};
-use crate::core::shell::Verbosity;
+use crate::core::compiler::{
+    Compilation, CompileKind, CompileTarget, Doctest, Metadata, Unit, UnitOutput,
+};
+use crate::core::shell::{OutputCategory, Verbosity};
 use crate::core::{TargetKind, Workspace};
 use crate::ops;
+use crate::util::config::{ConfigRelativePath, Definition, Value};
 use crate::util::errors::CargoResult;
+use crate::util::machine_message::{self, Message};
 use crate::util::{add_path_args, CliError, CliResult, Config};
-use anyhow::format_err;
-use cargo_util::{ProcessBuilder, ProcessError};
+use crate::{drop_eprintln, drop_println};
+use anyhow::{bail, format_err};
+use cargo_util::{paths, ProcessBuilder, ProcessError};
 use std::ffi::OsString;
 use std::fmt::Write;
 use std::path::{Path, PathBuf};
@@ -16,6 +21,59 @@ pub struct TestOptions {
     pub no_fail_fast: bool,
 }
 
+/// A target of `cargo test --target-matrix`.
+pub struct MatrixTarget {
+    pub kind: CompileKind,
+    /// The runner overriding `target.<triple>.runner` for this target.
+    pub runner: Option<(PathBuf, Vec<String>)>,
+}
+
+impl MatrixTarget {
+    /// Parses the entries of `--target-matrix`, which must all be for
+    /// different targets.
+    pub fn parse_all(config: &Config, entries: &[&str]) -> CargoResult<Vec<MatrixTarget>> {
+        let mut matrix: Vec<MatrixTarget> = Vec::new();
+        for entry in entries {
+            let target = MatrixTarget::parse(config, entry)?;
+            if matrix.iter().any(|t| t.kind == target.kind) {
+                bail!(
+                    "target `{}` is given more than once to `--target-matrix`",
+                    entry.split('=').next().unwrap()
+                );
+            }
+            matrix.push(target);
+        }
+        Ok(matrix)
+    }
+
+    /// Parses a `TRIPLE[=RUNNER]` entry of `--target-matrix`, where `RUNNER`
+    /// is a program followed by its arguments, separated by spaces.
+    fn parse(config: &Config, entry: &str) -> CargoResult<MatrixTarget> {
+        let (triple, runner) = match entry.split_once('=') {
+            Some((triple, runner)) => (triple, Some(runner)),
+            None => (entry, None),
+        };
+        let runner = match runner.map(|runner| runner.split_whitespace().collect::<Vec<_>>()) {
+            Some(args) if args.is_empty() => {
+                bail!("the runner of `{triple}` in `--target-matrix` is empty")
+            }
+            Some(args) => {
+                let path = ConfigRelativePath::new(Value {
+                    val: args[0].to_string(),
+                    definition: Definition::Cli(None),
+                });
+                let args = args[1..].iter().map(|arg| arg.to_string()).collect();
+                Some((path.resolve_program(config), args))
+            }
+            None => None,
+        };
+        Ok(MatrixTarget {
+            kind: CompileKind::Target(CompileTarget::new(triple)?),
+            runner,
+        })
+    }
+}
+
 /// The kind of test.
 ///
 /// This is needed because `Unit` does not track whether or not something is a
@@ -62,40 +120,154 @@ impl UnitTestError {
 ///
 /// On error, the returned [`CliError`] will have the appropriate process exit
 /// code that Cargo should use.
-pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str]) -> CliResult {
+pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[OsString]) -> CliResult {
     let compilation = compile_tests(ws, options)?;
 
     if options.no_run {
-        if !options.compile_opts.build_config.emit_json() {
-            display_no_run_information(ws, test_args, &compilation, "unittests")?;
-        }
+        report_no_run(ws, options, test_args, &compilation, TestKind::Test)?;
         return Ok(());
     }
-    let mut errors = run_unit_tests(ws, options, test_args, &compilation, TestKind::Test)?;
+    let mut errors = run_unit_tests(ws, options, test_args, &compilation, TestKind::Test, None)?;
 
-    let doctest_errors = run_doc_tests(ws, options, test_args, &compilation)?;
+    let doctest_errors = run_doc_tests(ws, options, test_args, &compilation, None)?;
     errors.extend(doctest_errors);
     no_fail_fast_err(ws, &options.compile_opts, &errors)
 }
 
+/// Compiles and runs tests for each target of `matrix`, then prints a summary
+/// of the outcome per target.
+///
+/// The tests of every target are run even if the ones of another target fail.
+/// `--no-fail-fast` only applies to the tests of a single target.
+///
+/// The compile options are expected to request exactly the targets of
+/// `matrix`.
+pub fn run_test_matrix(
+    ws: &Workspace<'_>,
+    options: &TestOptions,
+    matrix: &[MatrixTarget],
+    test_args: &[OsString],
+) -> CliResult {
+    let config = ws.config();
+    let mut compilation = compile_tests(ws, options)?;
+    for target in matrix {
+        if let Some(runner) = &target.runner {
+            compilation.set_target_runner(target.kind, runner.clone());
+        }
+    }
+
+    if options.no_run {
+        report_no_run(ws, options, test_args, &compilation, TestKind::Test)?;
+        return Ok(());
+    }
+
+    // Units built for the host, such as the tests of proc-macros, run along
+    // with the host target if it's in the matrix, or on their own otherwise.
+    let host = CompileKind::Target(CompileTarget::new(&compilation.host)?);
+    let mut rows: Vec<(String, Vec<CompileKind>)> = matrix
+        .iter()
+        .map(|target| {
+            let mut kinds = vec![target.kind];
+            if target.kind == host {
+                kinds.push(CompileKind::Host);
+            }
+            (matrix_triple(target.kind, &compilation), kinds)
+        })
+        .collect();
+    let has_host_units = compilation.tests.iter().any(|t| t.unit.kind.is_host())
+        || compilation
+            .to_doc_test
+            .iter()
+            .any(|d| d.unit.kind.is_host());
+    if has_host_units && !matrix.iter().any(|target| target.kind == host) {
+        rows.push((compilation.host.to_string(), vec![CompileKind::Host]));
+    }
+
+    let mut summary = Vec::new();
+    for (triple, kinds) in &rows {
+        config.shell().status("Testing", triple)?;
+        let kinds = Some(kinds.as_slice());
+        let outcome = run_unit_tests(ws, options, test_args, &compilation, TestKind::Test, kinds)
+            .and_then(|mut errors| {
+                errors.extend(run_doc_tests(ws, options, test_args, &compilation, kinds)?);
+                Ok(errors)
+            });
+        let outcome = match outcome {
+            Ok(errors) if errors.is_empty() => None,
+            Ok(errors) => {
+                let args: Vec<_> = errors
+                    .iter()
+                    .map(|unit_err| format!("`{}`", unit_err.cli_args(ws, &options.compile_opts)))
+                    .collect();
+                Some(format!("FAILED ({})", args.join(", ")))
+            }
+            Err(_) => Some("FAILED".to_string()),
+        };
+        summary.push((triple, outcome));
+    }
+
+    let failed: Vec<_> = summary
+        .iter()
+        .filter(|(_, outcome)| outcome.is_some())
+        .map(|(triple, _)| triple.as_str())
+        .collect();
+    config.shell().status(
+        "Summary",
+        format!(
+            "{} of {} targets passed",
+            summary.len() - failed.len(),
+            summary.len()
+        ),
+    )?;
+    let width = summary
+        .iter()
+        .map(|(triple, _)| triple.len())
+        .max()
+        .unwrap_or(0);
+    for (triple, outcome) in &summary {
+        let outcome = outcome.as_deref().unwrap_or("ok");
+        drop_eprintln!(config, "  {triple:<width$}  {outcome}");
+    }
+
+    match failed.len() {
+        0 => Ok(()),
+        1 => Err(format_err!("tests failed for target `{}`", failed[0]).into()),
+        _ => Err(format_err!(
+            "tests failed for targets {}",
+            failed
+                .iter()
+                .map(|triple| format!("`{triple}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into()),
+    }
+}
+
+/// The triple shown for the tests built for `kind`.
+fn matrix_triple(kind: CompileKind, compilation: &Compilation<'_>) -> String {
+    match kind {
+        CompileKind::Host => compilation.host.to_string(),
+        CompileKind::Target(target) => target.short_name().to_string(),
+    }
+}
+
 /// Compiles and runs benchmarks.
 ///
 /// On error, the returned [`CliError`] will have the appropriate process exit
 /// code that Cargo should use.
-pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[&str]) -> CliResult {
+pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[OsString]) -> CliResult {
     let compilation = compile_tests(ws, options)?;
 
     if options.no_run {
-        if !options.compile_opts.build_config.emit_json() {
-            display_no_run_information(ws, args, &compilation, "benches")?;
-        }
+        report_no_run(ws, options, args, &compilation, TestKind::Bench)?;
         return Ok(());
     }
 
     let mut args = args.to_vec();
-    args.push("--bench");
+    args.push("--bench".into());
 
-    let errors = run_unit_tests(ws, options, &args, &compilation, TestKind::Bench)?;
+    let errors = run_unit_tests(ws, options, &args, &compilation, TestKind::Bench, None)?;
     no_fail_fast_err(ws, &options.compile_opts, &errors)
 }
 
@@ -107,14 +279,17 @@ fn compile_tests<'a>(ws: &Workspace<'a>, options: &TestOptions) -> CargoResult<C
 
 /// Runs the unit and integration tests of a package.
 ///
+/// Only the tests built for `kinds` are run, if set.
+///
 /// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
 /// If `--no-fail-fast` is *not* used, then this returns an `Err`.
 fn run_unit_tests(
     ws: &Workspace<'_>,
     options: &TestOptions,
-    test_args: &[&str],
+    test_args: &[OsString],
     compilation: &Compilation<'_>,
     test_kind: TestKind,
+    kinds: Option<&[CompileKind]>,
 ) -> Result<Vec<UnitTestError>, CliError> {
     let config = ws.config();
     let cwd = config.cwd();
@@ -126,6 +301,9 @@ fn run_unit_tests(
         script_meta,
     } in compilation.tests.iter()
     {
+        if kinds.map_or(false, |kinds| !kinds.contains(&unit.kind)) {
+            continue;
+        }
         let (exe_display, cmd) = cmd_builds(
             config,
             cwd,
@@ -136,12 +314,16 @@ fn run_unit_tests(
             compilation,
             "unittests",
         )?;
-        config
-            .shell()
-            .concise(|shell| shell.status("Running", &exe_display))?;
-        config
-            .shell()
-            .verbose(|shell| shell.status("Running", &cmd))?;
+        {
+            let mut shell = config.shell();
+            if shell.shows(OutputCategory::ProcessEcho) {
+                shell.category(OutputCategory::ProcessEcho, |shell| {
+                    shell.status("Running", &cmd)
+                })?;
+            } else {
+                shell.status("Running", &exe_display)?;
+            }
+        }
 
         if let Err(e) = cmd.exec() {
             let code = fail_fast_code(&e);
@@ -161,13 +343,16 @@ fn run_unit_tests(
 
 /// Runs doc tests.
 ///
+/// Only the doc tests built for `kinds` are run, if set.
+///
 /// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
 /// If `--no-fail-fast` is *not* used, then this returns an `Err`.
 fn run_doc_tests(
     ws: &Workspace<'_>,
     options: &TestOptions,
-    test_args: &[&str],
+    test_args: &[OsString],
     compilation: &Compilation<'_>,
+    kinds: Option<&[CompileKind]>,
 ) -> Result<Vec<UnitTestError>, CliError> {
     let config = ws.config();
     let mut errors = Vec::new();
@@ -183,6 +368,10 @@ fn run_doc_tests(
             env,
         } = doctest_info;
 
+        if kinds.map_or(false, |kinds| !kinds.contains(&unit.kind)) {
+            continue;
+        }
+
         if !doctest_xcompile {
             match unit.kind {
                 CompileKind::Host => {}
@@ -268,7 +457,9 @@ fn run_doc_tests(
 
         config
             .shell()
-            .verbose(|shell| shell.status("Running", p.to_string()))?;
+            .category(OutputCategory::ProcessEcho, |shell| {
+                shell.status("Running", p.to_string())
+            })?;
         if let Err(e) = p.exec() {
             let code = fail_fast_code(&e);
             let unit_err = UnitTestError {
@@ -285,24 +476,35 @@ fn run_doc_tests(
     Ok(errors)
 }
 
-/// Displays human-readable descriptions of the test executables.
+/// Describes the test executables built with `--no-run`.
 ///
-/// This is used when `cargo test --no-run` is used.
-fn display_no_run_information(
+/// They are displayed, or emitted as `test-executable` messages with
+/// `--message-format=json`. They are also recorded in an index under the
+/// target directory, `test-executables.json` or `bench-executables.json`, so
+/// that tools can find and run them later without parsing the build output.
+fn report_no_run(
     ws: &Workspace<'_>,
-    test_args: &[&str],
+    options: &TestOptions,
+    test_args: &[OsString],
     compilation: &Compilation<'_>,
-    exec_type: &str,
+    test_kind: TestKind,
 ) -> CargoResult<()> {
     let config = ws.config();
     let cwd = config.cwd();
+    let emit_json = options.compile_opts.build_config.emit_json();
+    let (exec_type, index_name) = match test_kind {
+        TestKind::Bench => ("benches", "bench-executables.json"),
+        _ => ("unittests", "test-executables.json"),
+    };
+
+    let mut executables = Vec::new();
     for UnitOutput {
         unit,
         path,
         script_meta,
     } in compilation.tests.iter()
     {
-        let (exe_display, cmd) = cmd_builds(
+        let (exe_display, mut cmd) = cmd_builds(
             config,
             cwd,
             unit,
@@ -312,15 +514,59 @@ fn display_no_run_information(
             compilation,
             exec_type,
         )?;
-        config
-            .shell()
-            .concise(|shell| shell.status("Executable", &exe_display))?;
-        config
-            .shell()
-            .verbose(|shell| shell.status("Executable", &cmd))?;
+        if !emit_json {
+            config
+                .shell()
+                .concise(|shell| shell.status("Executable", &exe_display))?;
+            config
+                .shell()
+                .verbose(|shell| shell.status("Executable", &cmd))?;
+        }
+        // Benchmarks are run with `--bench`, which isn't part of the
+        // displayed command.
+        if let TestKind::Bench = test_kind {
+            cmd.arg("--bench");
+        }
+        executables.push((unit, path, cmd));
+    }
+
+    let messages: Vec<_> = executables
+        .iter()
+        .map(|(unit, path, cmd)| machine_message::TestExecutable {
+            package_id: unit.pkg.package_id(),
+            manifest_path: unit.pkg.manifest_path(),
+            target: &unit.target,
+            features: unit.features.iter().map(|s| s.to_string()).collect(),
+            executable: path,
+            program: cmd.get_program().to_string_lossy().into_owned(),
+            args: cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            cwd: cmd.get_cwd(),
+            env: cmd
+                .get_envs()
+                .iter()
+                .filter_map(|(key, value)| {
+                    let value = value.as_ref()?.to_string_lossy().into_owned();
+                    Some((key.clone(), value))
+                })
+                .collect(),
+        })
+        .collect();
+    if emit_json {
+        for msg in &messages {
+            drop_println!(config, "{}", msg.to_json_string());
+        }
     }
 
-    return Ok(());
+    let index = serde_json::json!({
+        "version": 1,
+        "executables": messages,
+    });
+    let index_path = ws.target_dir().into_path_unlocked().join(index_name);
+    paths::write(&index_path, serde_json::to_string_pretty(&index)?)?;
+    Ok(())
 }
 
 /// Creates a [`ProcessBuilder`] for executing a single test.
@@ -334,7 +580,7 @@ fn cmd_builds(
     unit: &Unit,
     path: &PathBuf,
     script_meta: &Option<Metadata>,
-    test_args: &[&str],
+    test_args: &[OsString],
     compilation: &Compilation<'_>,
     exec_type: &str,
 ) -> CargoResult<(String, ProcessBuilder)> {
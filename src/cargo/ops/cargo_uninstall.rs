@@ -1,38 +1,50 @@
 use crate::core::PackageId;
 use crate::core::{PackageIdSpec, SourceId};
+use crate::ops::cargo_compile::build_glob;
 use crate::ops::common_for_install_and_uninstall::*;
 use crate::sources::PathSource;
 use crate::util::errors::CargoResult;
+use crate::util::restricted_names::is_glob_pattern;
 use crate::util::Config;
 use crate::util::Filesystem;
 use anyhow::bail;
 use cargo_util::paths;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 use std::env;
 
+/// Uninstalls the packages of `specs`, which may be glob patterns matching
+/// the names of installed packages, or every installed package with `all`.
 pub fn uninstall(
     root: Option<&str>,
     specs: Vec<&str>,
     bins: &[String],
+    all: bool,
+    dry_run: bool,
     config: &Config,
 ) -> CargoResult<()> {
+    let root = resolve_root(root, config)?;
+    let specs = expand_specs(&root, specs, all, config)?;
     if specs.len() > 1 && !bins.is_empty() {
         bail!("A binary can only be associated with a single installed package, specifying multiple specs with --bin is redundant.");
     }
 
-    let root = resolve_root(root, config)?;
     let scheduled_error = if specs.len() == 1 {
-        uninstall_one(&root, specs[0], bins, config)?;
+        uninstall_one(&root, &specs[0], bins, dry_run, config)?;
+        false
+    } else if specs.is_empty() && all {
+        config
+            .shell()
+            .status("Skipping", "no packages are installed")?;
         false
     } else if specs.is_empty() {
-        uninstall_cwd(&root, bins, config)?;
+        uninstall_cwd(&root, bins, dry_run, config)?;
         false
     } else {
         let mut succeeded = vec![];
         let mut failed = vec![];
-        for spec in specs {
+        for spec in specs.iter().map(String::as_str) {
             let root = root.clone();
-            match uninstall_one(&root, spec, bins, config) {
+            match uninstall_one(&root, spec, bins, dry_run, config) {
                 Ok(()) => succeeded.push(spec),
                 Err(e) => {
                     crate::display_error(&e, &mut config.shell());
@@ -62,6 +74,10 @@ pub fn uninstall(
         !failed.is_empty()
     };
 
+    if dry_run {
+        config.shell().warn("no files deleted due to --dry-run")?;
+    }
+
     if scheduled_error {
         bail!("some packages failed to uninstall");
     }
@@ -69,19 +85,87 @@ pub fn uninstall(
     Ok(())
 }
 
+/// Replaces the glob patterns of `specs` with the installed packages whose
+/// name matches, or returns all the installed packages with `all`.
+fn expand_specs(
+    root: &Filesystem,
+    specs: Vec<&str>,
+    all: bool,
+    config: &Config,
+) -> CargoResult<Vec<String>> {
+    if !all && !specs.iter().any(is_glob_pattern) {
+        return Ok(specs.into_iter().map(str::to_string).collect());
+    }
+    let installed: Vec<PackageId> = InstallTracker::load(config, root)?
+        .all_installed_bins()
+        .map(|(pkg_id, _)| *pkg_id)
+        .collect();
+    if all {
+        return Ok(installed
+            .iter()
+            .map(|pkg_id| unique_spec(*pkg_id, &installed))
+            .collect());
+    }
+
+    let mut seen = HashSet::new();
+    let mut expanded = Vec::new();
+    for spec in specs {
+        if !is_glob_pattern(spec) {
+            if seen.insert(spec.to_string()) {
+                expanded.push(spec.to_string());
+            }
+            continue;
+        }
+        let pattern = build_glob(spec)?;
+        let mut matched = installed
+            .iter()
+            .filter(|pkg_id| pattern.matches(&pkg_id.name()))
+            .peekable();
+        if matched.peek().is_none() {
+            bail!("no installed package matches the pattern `{}`", spec);
+        }
+        for pkg_id in matched {
+            let spec = unique_spec(*pkg_id, &installed);
+            if seen.insert(spec.clone()) {
+                expanded.push(spec);
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// The shortest spec matching only `pkg_id` among the `installed` packages.
+fn unique_spec(pkg_id: PackageId, installed: &[PackageId]) -> String {
+    let same_name = installed
+        .iter()
+        .filter(|other| other.name() == pkg_id.name())
+        .count();
+    if same_name == 1 {
+        pkg_id.name().to_string()
+    } else {
+        PackageIdSpec::from_package_id(pkg_id).to_string()
+    }
+}
+
 pub fn uninstall_one(
     root: &Filesystem,
     spec: &str,
     bins: &[String],
+    dry_run: bool,
     config: &Config,
 ) -> CargoResult<()> {
     let tracker = InstallTracker::load(config, root)?;
     let all_pkgs = tracker.all_installed_bins().map(|(pkg_id, _set)| *pkg_id);
     let pkgid = PackageIdSpec::query_str(spec, all_pkgs)?;
-    uninstall_pkgid(root, tracker, pkgid, bins, config)
+    uninstall_pkgid(root, tracker, pkgid, bins, dry_run, config)
 }
 
-fn uninstall_cwd(root: &Filesystem, bins: &[String], config: &Config) -> CargoResult<()> {
+fn uninstall_cwd(
+    root: &Filesystem,
+    bins: &[String],
+    dry_run: bool,
+    config: &Config,
+) -> CargoResult<()> {
     let tracker = InstallTracker::load(config, root)?;
     let source_id = SourceId::for_path(config.cwd())?;
     let mut src = path_source(source_id, config)?;
@@ -92,7 +176,7 @@ fn uninstall_cwd(root: &Filesystem, bins: &[String], config: &Config) -> CargoRe
         config,
     )?;
     let pkgid = pkg.package_id();
-    uninstall_pkgid(root, tracker, pkgid, bins, config)
+    uninstall_pkgid(root, tracker, pkgid, bins, dry_run, config)
 }
 
 fn uninstall_pkgid(
@@ -100,6 +184,7 @@ fn uninstall_pkgid(
     mut tracker: InstallTracker,
     pkgid: PackageId,
     bins: &[String],
+    dry_run: bool,
     config: &Config,
 ) -> CargoResult<()> {
     let mut to_remove = Vec::new();
@@ -109,15 +194,6 @@ fn uninstall_pkgid(
     };
 
     let dst = root.join("bin").into_path_unlocked();
-    for bin in &installed {
-        let bin = dst.join(bin);
-        if !bin.exists() {
-            bail!(
-                "corrupt metadata, `{}` does not exist when it should",
-                bin.display()
-            )
-        }
-    }
 
     let bins = bins
         .iter()
@@ -145,10 +221,24 @@ fn uninstall_pkgid(
         }
         tracker.remove(pkgid, &bins);
     }
+    if dry_run {
+        for bin in to_remove {
+            config.shell().status("Removing", bin.display())?;
+        }
+        return Ok(());
+    }
     tracker.save()?;
     for bin in to_remove {
-        config.shell().status("Removing", bin.display())?;
-        paths::remove_file(bin)?;
+        if bin.exists() {
+            config.shell().status("Removing", bin.display())?;
+            paths::remove_file(bin)?;
+        } else {
+            // The binary was removed by hand, only its tracking is left.
+            config.shell().warn(format!(
+                "`{}` was already removed, forgetting it",
+                bin.display()
+            ))?;
+        }
     }
 
     Ok(())
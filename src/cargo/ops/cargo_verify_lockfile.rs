@@ -0,0 +1,195 @@
+//! Content hashes of git and path packages in `Cargo.lock`, with
+//! `-Zcontent-hashes`, and `cargo verify-lockfile`.
+//!
+//! Registry packages are verified against the `checksum` of their `.crate`
+//! file. Git checkouts and path dependencies have no such file, so their
+//! contents are hashed instead: the `content-hash` of a package is the
+//! SHA-256 of the list of its files, with the SHA-256 of each file, as
+//! `cargo package` would list them.
+//!
+//! The hashes are recorded as packages are resolved. A git checkout whose
+//! contents changed for the same revision has been tampered with, which is an
+//! error with `--locked` and a warning otherwise. Path dependencies change as
+//! they are edited, so their hash is only enforced with `--locked`.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::bail;
+use cargo_util::Sha256;
+
+use crate::core::registry::{PackageRegistry, Registry};
+use crate::core::resolver::Resolve;
+use crate::core::{Package, PackageId, PackageSet, Workspace};
+use crate::ops;
+use crate::sources::registry::cached_crate_path;
+use crate::sources::PathSource;
+use crate::util::{CargoResult, Config};
+
+/// Computes the hash of the contents of `pkg`.
+pub fn content_hash(config: &Config, pkg: &Package) -> CargoResult<String> {
+    let source = PathSource::new(pkg.root(), pkg.package_id().source_id(), config);
+    let mut files: Vec<_> = source
+        .list_files(pkg)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(pkg.root()).ok()?;
+            // Git checkouts are marked as complete with a `.cargo-ok` file,
+            // which isn't part of the package.
+            if relative == Path::new(".cargo-ok") {
+                return None;
+            }
+            let name = relative.to_str()?.replace('\\', "/");
+            Some((name, path))
+        })
+        .collect();
+    files.sort();
+
+    let mut hash = Sha256::new();
+    for (name, path) in files {
+        let file_hash = Sha256::new().update_path(&path)?.finish_hex();
+        hash.update(format!("{name}\n{file_hash}\n").as_bytes());
+    }
+    Ok(hash.finish_hex())
+}
+
+/// Whether the contents of `id` are hashed with the current
+/// `-Zcontent-hashes`.
+fn is_hashed(ws: &Workspace<'_>, members: &HashSet<PackageId>, id: PackageId) -> bool {
+    let Some(kinds) = ws.config().cli_unstable().content_hashes else {
+        return false;
+    };
+    let source_id = id.source_id();
+    (kinds.git && source_id.is_git())
+        || (kinds.path && source_id.is_path() && !members.contains(&id))
+}
+
+/// Checks the content hashes of the git and path packages of `resolve`
+/// against the lock file, and records the missing ones in it.
+///
+/// With `--locked`, a mismatch is an error and missing hashes are left out,
+/// since the lock file can't be updated.
+pub fn check_content_hashes(
+    ws: &Workspace<'_>,
+    pkg_set: &PackageSet<'_>,
+    resolve: &mut Resolve,
+) -> CargoResult<()> {
+    let config = ws.config();
+    if config.cli_unstable().content_hashes.is_none()
+        || ws.ignore_lock()
+        || ws.is_ephemeral()
+        || !ws.require_optional_deps()
+    {
+        return Ok(());
+    }
+
+    let members: HashSet<_> = ws.members().map(|pkg| pkg.package_id()).collect();
+    let mut ids: Vec<_> = pkg_set
+        .package_ids()
+        .filter(|id| resolve.contains(id) && is_hashed(ws, &members, *id))
+        .collect();
+    ids.sort();
+
+    let mut changed = false;
+    for pkg in pkg_set.get_many(ids)? {
+        let id = pkg.package_id();
+        let actual = content_hash(config, pkg)?;
+        match resolve.content_hashes().get(&id) {
+            Some(expected) if *expected == actual => continue,
+            Some(_) if config.locked() => bail!(
+                "the contents of `{id}` don't match the `content-hash` in Cargo.lock\n\
+                 The files at `{}` may have been tampered with.",
+                ws.display_path(pkg.root())
+            ),
+            Some(_) => {
+                if id.source_id().is_git() {
+                    config.shell().warn(format!(
+                        "the contents of `{id}` changed since its `content-hash` \
+                         was recorded in Cargo.lock, updating it"
+                    ))?;
+                }
+            }
+            None if config.locked() => continue,
+            None => {}
+        }
+        resolve.set_content_hash(id, actual);
+        changed = true;
+    }
+
+    if changed {
+        ops::write_pkg_lockfile(ws, resolve)?;
+    }
+    Ok(())
+}
+
+/// Verifies the packages of the lock file of `ws` without building them.
+///
+/// Registry packages are checked against the `checksum` of their downloaded
+/// `.crate` file, git and path packages against their `content-hash`.
+/// Packages which have neither are counted as unverified.
+pub fn verify_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
+    let config = ws.config();
+    let Some(resolve) = ops::load_pkg_lockfile(ws)? else {
+        bail!(
+            "no Cargo.lock found for `{}`\n\
+             Run `cargo generate-lockfile` to create one.",
+            ws.root().display()
+        );
+    };
+
+    let members: HashSet<_> = ws.members().map(|pkg| pkg.package_id()).collect();
+    let mut ids: Vec<_> = resolve.iter().filter(|id| !members.contains(id)).collect();
+    ids.sort();
+
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut registry = PackageRegistry::new(config)?;
+    registry.add_sources(ids.iter().map(|id| id.source_id()))?;
+    registry.block_until_ready()?;
+    let pkg_set = registry.get(&ids)?;
+
+    let mut verified = 0;
+    let mut unverified = 0;
+    let mut mismatches = Vec::new();
+    for pkg in pkg_set.get_many(ids.iter().copied())? {
+        let id = pkg.package_id();
+        let (expected, actual) = if let Some(Some(checksum)) = resolve.checksums().get(&id) {
+            let Some(path) = cached_crate_path(config, pkg).filter(|path| path.exists()) else {
+                unverified += 1;
+                continue;
+            };
+            let actual = Sha256::new().update_path(&path)?.finish_hex();
+            (checksum.clone(), actual)
+        } else if let Some(hash) = resolve.content_hashes().get(&id) {
+            (hash.clone(), content_hash(config, pkg)?)
+        } else {
+            unverified += 1;
+            continue;
+        };
+        if expected == actual {
+            verified += 1;
+        } else {
+            mismatches.push(format!("  {id} ({})", ws.display_path(pkg.root())));
+        }
+    }
+
+    if !mismatches.is_empty() {
+        bail!(
+            "the contents of {} package{} don't match Cargo.lock:\n{}",
+            mismatches.len(),
+            if mismatches.len() == 1 { "" } else { "s" },
+            mismatches.join("\n")
+        );
+    }
+
+    let mut status = format!(
+        "{verified} package{} against Cargo.lock",
+        if verified == 1 { "" } else { "s" }
+    );
+    if unverified > 0 {
+        status.push_str(&format!(
+            ", {unverified} without a checksum or content hash"
+        ));
+    }
+    config.shell().status("Verified", status)?;
+    Ok(())
+}
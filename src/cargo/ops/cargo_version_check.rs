@@ -0,0 +1,169 @@
+//! Implementation of `cargo version-check`, which finds the workspace members
+//! changed since a git revision without having their version bumped.
+//!
+//! Changes are found by diffing the trees of two commits, so uncommitted
+//! changes are not considered. Versions are compared against the members of
+//! the workspace as it was at a baseline commit, which is checked out into the
+//! target directory.
+//!
+//! Assumption: versions only ever increase, there are no point releases of
+//! older versions.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::core::{Package, Workspace};
+use crate::util::CargoResult;
+
+pub struct VersionCheckOptions {
+    /// Git revision the changes are made on top of.
+    pub base_rev: String,
+    /// Git revision with the changes. Defaults to `HEAD`.
+    pub head_rev: Option<String>,
+    /// Git revision whose member versions are compared against. Defaults to
+    /// `base_rev`, but may for example be the last release branch.
+    pub baseline_rev: Option<String>,
+}
+
+/// The result of [`version_check`].
+pub struct VersionCheck<'a> {
+    /// Publishable members with changes between the base and head revisions,
+    /// sorted by package ID.
+    pub changed: Vec<&'a Package>,
+    /// Changed members whose version isn't greater than their version at the
+    /// baseline revision, sorted by package ID.
+    pub needs_bump: Vec<&'a Package>,
+}
+
+/// Checks which members of `ws` changed between two git revisions without a
+/// version bump.
+pub fn version_check<'a>(
+    ws: &'a Workspace<'_>,
+    opts: &VersionCheckOptions,
+) -> CargoResult<VersionCheck<'a>> {
+    let repo = git2::Repository::discover(ws.root())
+        .with_context(|| format!("`{}` is not in a git repository", ws.root().display()))?;
+    let base = find_commit(&repo, &opts.base_rev)?;
+    let head = match &opts.head_rev {
+        Some(rev) => find_commit(&repo, rev)?,
+        None => repo.head()?.peel_to_commit()?,
+    };
+    let baseline = match &opts.baseline_rev {
+        Some(rev) => find_commit(&repo, rev)?,
+        None => base.clone(),
+    };
+
+    let changed = changed_members(ws, &repo, &base, &head)?;
+    let baseline_ws = checkout_ws(ws, &repo, &baseline)?;
+    let needs_bump = not_bumped(&changed, &baseline_ws);
+    Ok(VersionCheck {
+        changed,
+        needs_bump,
+    })
+}
+
+/// Lists the publishable workspace members with changed files between two
+/// commits, sorted by package ID.
+pub fn changed_members<'ws, 'r>(
+    ws: &'ws Workspace<'_>,
+    repo: &'r git2::Repository,
+    base: &git2::Commit<'r>,
+    head: &git2::Commit<'r>,
+) -> CargoResult<Vec<&'ws Package>> {
+    let mut members = Vec::new();
+    for pkg in ws.members() {
+        // `publish = false`
+        if pkg.publish() == &Some(vec![]) {
+            continue;
+        }
+        // Paths in the diff are relative to the root of the repository.
+        let Some(relative_root) = relative_to_workdir(repo, pkg.root())? else {
+            continue;
+        };
+        members.push((relative_root, pkg));
+    }
+    // Nested packages must be matched before the packages containing them.
+    members.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+
+    let base_tree = base.tree()?;
+    let head_tree = head.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?;
+
+    let mut changed = Vec::new();
+    for delta in diff.deltas() {
+        let paths = [delta.old_file().path(), delta.new_file().path()];
+        let owner = members
+            .iter()
+            .find(|(root, _)| paths.iter().flatten().any(|path| path.starts_with(root)));
+        if let Some((_, pkg)) = owner {
+            changed.push(*pkg);
+        }
+    }
+    changed.sort_by_key(|pkg| pkg.package_id());
+    changed.dedup_by_key(|pkg| pkg.package_id());
+    Ok(changed)
+}
+
+/// Returns the members of `changed` whose version isn't greater than the
+/// version of the same-named member of `baseline`.
+///
+/// Members which don't exist in `baseline` are new, and never need a bump.
+pub fn not_bumped<'a>(changed: &[&'a Package], baseline: &Workspace<'_>) -> Vec<&'a Package> {
+    changed
+        .iter()
+        .copied()
+        .filter(|pkg| {
+            baseline
+                .members()
+                .find(|old| old.name() == pkg.name())
+                .map_or(false, |old| pkg.version() <= old.version())
+        })
+        .collect()
+}
+
+/// Checks out `commit` into `target/version-check-<short-id>` and loads the
+/// workspace corresponding to `ws` from it.
+pub fn checkout_ws<'cfg, 'r>(
+    ws: &Workspace<'cfg>,
+    repo: &'r git2::Repository,
+    commit: &git2::Commit<'r>,
+) -> CargoResult<Workspace<'cfg>> {
+    let short_id = &commit.id().to_string()[..7];
+    let checkout_path = ws.target_dir().join(format!("version-check-{short_id}"));
+    let checkout_path = checkout_path.as_path_unlocked();
+    let _ = fs::remove_dir_all(checkout_path);
+    let new_repo = git2::build::RepoBuilder::new()
+        .clone_local(git2::build::CloneLocal::Local)
+        .clone(&repo.path().to_string_lossy(), checkout_path)
+        .with_context(|| format!("failed to check out `{}`", commit.id()))?;
+    let obj = new_repo.find_object(commit.id(), None)?;
+    new_repo.reset(&obj, git2::ResetType::Hard, None)?;
+
+    let manifest = relative_to_workdir(repo, ws.root_manifest())?
+        .unwrap_or_else(|| PathBuf::from("Cargo.toml"));
+    Workspace::new(&checkout_path.join(manifest), ws.config())
+        .with_context(|| format!("failed to load the workspace at `{}`", commit.id()))
+}
+
+/// Returns `path` relative to the working directory of `repo`, or `None` if
+/// it's outside of it.
+fn relative_to_workdir(repo: &git2::Repository, path: &Path) -> CargoResult<Option<PathBuf>> {
+    let workdir = repo
+        .workdir()
+        .context("cannot check versions in a bare git repository")?;
+    // Either side may go through a symlink, for example on macOS where
+    // temporary directories live under `/private`.
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    Ok(canonical(path)
+        .strip_prefix(canonical(workdir))
+        .ok()
+        .map(Path::to_path_buf))
+}
+
+fn find_commit<'r>(repo: &'r git2::Repository, rev: &str) -> CargoResult<git2::Commit<'r>> {
+    repo.revparse_single(rev)
+        .and_then(|obj| obj.peel_to_commit())
+        .with_context(|| format!("failed to find git revision `{rev}`"))
+}
@@ -1,4 +1,5 @@
 use std::io::prelude::*;
+use std::path::PathBuf;
 
 use crate::core::{resolver, Resolve, ResolveVersion, Workspace};
 use crate::util::errors::CargoResult;
@@ -6,6 +7,7 @@ use crate::util::toml as cargo_toml;
 use crate::util::Filesystem;
 
 use anyhow::Context as _;
+use cargo_util::paths;
 
 pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
     let lock_root = lock_root(ws);
@@ -17,14 +19,19 @@ pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
 
     let mut s = String::new();
     f.read_to_string(&mut s)
-        .with_context(|| format!("failed to read file: {}", f.path().display()))?;
+        .with_context(|| format!("failed to read file: {}", ws.display_path(f.path())))?;
 
     let resolve = (|| -> CargoResult<Option<Resolve>> {
         let resolve: toml::Table = cargo_toml::parse_document(&s, f.path(), ws.config())?;
         let v: resolver::EncodableResolve = resolve.try_into()?;
         Ok(Some(v.into_resolve(&s, ws)?))
     })()
-    .with_context(|| format!("failed to parse lock file at: {}", f.path().display()))?;
+    .with_context(|| {
+        format!(
+            "failed to parse lock file at: {}",
+            ws.display_path(f.path())
+        )
+    })?;
     Ok(resolve)
 }
 
@@ -55,12 +62,21 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
             "the lock file {} needs to be updated but {} was passed to prevent this\n\
              If you want to try to generate the lock file without accessing the network, \
              remove the {} flag and use --offline instead.",
-            lock_root.as_path_unlocked().join("Cargo.lock").display(),
+            ws.display_path(&lock_root.as_path_unlocked().join("Cargo.lock")),
             flag,
             flag
         );
     }
 
+    if ws.config().cli_unstable().read_only_sources {
+        anyhow::bail!(
+            "the lock file {} needs to be updated but `-Zread-only-sources` forbids \
+             writing to package roots\n\
+             Update the lock file in a writable checkout and commit it.",
+            ws.display_path(&lock_root.as_path_unlocked().join("Cargo.lock")),
+        );
+    }
+
     // While we're updating the lock file anyway go ahead and update its
     // encoding to whatever the latest default is. That way we can slowly roll
     // out lock file updates as they're otherwise already updated, and changes
@@ -79,18 +95,16 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
         )
     }
 
-    // Ok, if that didn't work just write it out
+    // Ok, if that didn't work just write it out. The lock is held while the
+    // new contents replace the file, so that an interrupted write never
+    // leaves a truncated lock file behind.
     lock_root
-        .open_rw("Cargo.lock", ws.config(), "Cargo.lock file")
-        .and_then(|mut f| {
-            f.file().set_len(0)?;
-            f.write_all(out.as_bytes())?;
-            Ok(())
-        })
+        .open_rw_replaceable("Cargo.lock", ws.config(), "Cargo.lock file")
+        .and_then(|f| paths::write_atomic(f.path(), out.as_bytes()))
         .with_context(|| {
             format!(
                 "failed to write {}",
-                lock_root.as_path_unlocked().join("Cargo.lock").display()
+                ws.display_path(&lock_root.as_path_unlocked().join("Cargo.lock"))
             )
         })?;
     Ok(())
@@ -222,6 +236,9 @@ fn emit_package(dep: &toml::Table, out: &mut String) {
     if dep.contains_key("checksum") {
         out.push_str(&format!("checksum = {}\n", &dep["checksum"]));
     }
+    if dep.contains_key("content-hash") {
+        out.push_str(&format!("content-hash = {}\n", &dep["content-hash"]));
+    }
 
     if let Some(s) = dep.get("dependencies") {
         let slice = s.as_array().unwrap();
@@ -241,6 +258,11 @@ fn emit_package(dep: &toml::Table, out: &mut String) {
     }
 }
 
+/// Returns the path of the lock file of `ws`, which may not exist yet.
+pub fn lockfile_path(ws: &Workspace<'_>) -> PathBuf {
+    lock_root(ws).as_path_unlocked().join("Cargo.lock")
+}
+
 fn lock_root(ws: &Workspace<'_>) -> Filesystem {
     if ws.root_maybe().is_embedded() {
         ws.target_dir()
@@ -0,0 +1,152 @@
+//! A cache of the output of `cargo metadata`, with `-Zmetadata-cache`.
+//!
+//! Resolving dependencies and features, and asking rustc about the targets,
+//! is most of the time spent by `cargo metadata`, which tools such as editors
+//! run again and again while nothing changed. With `-Zmetadata-cache`, the
+//! output is saved in `target/.cargo-metadata-cache`, along with a
+//! fingerprint of what it was computed from, and reused as long as the
+//! fingerprint is the same.
+//!
+//! Only `cargo metadata` is cached for now. `cargo check` and `cargo tree`
+//! build a unit graph or a dependency graph, neither of which is
+//! serializable, and print their output as they go. Caching these graphs,
+//! under the same fingerprint, is left for a follow-up.
+//!
+//! The fingerprint covers:
+//!
+//! * the versions of Cargo and rustc, and the command-line options;
+//! * the root manifest and the workspace members, as loaded by this
+//!   invocation, which includes the targets discovered in their directories;
+//! * the contents of `Cargo.lock`;
+//! * the configuration, from config files, `--config`, and the `CARGO_*` and
+//!   `RUST*` environment variables.
+//!
+//! Path dependencies which aren't workspace members are only loaded while
+//! resolving, so they can't be checked without doing the work the cache
+//! saves. Outputs including any of them are not cached.
+
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use cargo_util::paths;
+
+use crate::core::Workspace;
+use crate::ops;
+use crate::util::config::ConfigValue;
+use crate::util::{to_hex, CargoResult, StableHasher};
+
+/// The directory of the cache in the target directory.
+const CACHE_DIR: &str = ".cargo-metadata-cache";
+
+/// The cached output of `cargo metadata` for a workspace.
+pub struct MetadataCache {
+    /// The file holding the fingerprint on its first line, followed by the
+    /// output.
+    path: PathBuf,
+    /// The hash of the command-line options, and of anything else the caller
+    /// knows the output depends on.
+    options: u64,
+}
+
+impl MetadataCache {
+    /// Creates the cache of `cargo metadata` run with `options`.
+    pub fn new(ws: &Workspace<'_>, options: impl Hash) -> MetadataCache {
+        let mut hasher = StableHasher::new();
+        options.hash(&mut hasher);
+        MetadataCache {
+            path: ws
+                .target_dir()
+                .as_path_unlocked()
+                .join(CACHE_DIR)
+                .join("metadata.json"),
+            options: hasher.finish(),
+        }
+    }
+
+    /// Returns the cached output, if it was computed from the same inputs.
+    pub fn load(&self, ws: &Workspace<'_>) -> CargoResult<Option<String>> {
+        let Ok(cached) = paths::read(&self.path) else {
+            return Ok(None);
+        };
+        let Some((fingerprint, output)) = cached.split_once('\n') else {
+            return Ok(None);
+        };
+        if fingerprint != self.fingerprint(ws)? {
+            tracing::debug!("metadata cache `{}` is stale", self.path.display());
+            return Ok(None);
+        }
+        Ok(Some(output.to_string()))
+    }
+
+    /// Saves `output` for the next invocations.
+    ///
+    /// This is called after computing the output, so that changes it made,
+    /// such as writing `Cargo.lock`, are part of the fingerprint.
+    pub fn save(&self, ws: &Workspace<'_>, output: &str) -> CargoResult<()> {
+        let fingerprint = self.fingerprint(ws)?;
+        // The cache is only an optimization, so a read-only target directory
+        // doesn't fail the command. It is replaced atomically, so that
+        // concurrent invocations never read a partially written file.
+        let result = paths::create_dir_all(self.path.parent().unwrap())
+            .and_then(|()| paths::write_atomic(&self.path, format!("{fingerprint}\n{output}")));
+        if let Err(e) = result {
+            tracing::debug!("failed to save metadata cache: {e:?}");
+        }
+        Ok(())
+    }
+
+    fn fingerprint(&self, ws: &Workspace<'_>) -> CargoResult<String> {
+        let mut hasher = StableHasher::new();
+        crate::version().to_string().hash(&mut hasher);
+        self.options.hash(&mut hasher);
+
+        paths::read_bytes(ws.root_manifest())?.hash(&mut hasher);
+        for pkg in ws.members() {
+            paths::read_bytes(pkg.manifest_path())?.hash(&mut hasher);
+            serde_json::to_string(&pkg.serialized())?.hash(&mut hasher);
+        }
+        paths::read_bytes(&ops::lockfile_path(ws))
+            .ok()
+            .hash(&mut hasher);
+
+        let config = ws.config();
+        let mut values: Vec<_> = config.values()?.iter().collect();
+        values.sort_by_key(|(key, _)| *key);
+        for (key, value) in values {
+            key.hash(&mut hasher);
+            hash_config_value(value, &mut hasher);
+        }
+        let mut env: Vec<_> = config
+            .env()
+            .filter(|(key, _)| key.starts_with("CARGO") || key.starts_with("RUST"))
+            .collect();
+        env.sort();
+        env.hash(&mut hasher);
+
+        Ok(to_hex(hasher.finish()))
+    }
+}
+
+/// Hashes a config value regardless of where it was defined, and of the
+/// order of the keys of tables.
+fn hash_config_value(value: &ConfigValue, hasher: &mut StableHasher) {
+    std::mem::discriminant(value).hash(hasher);
+    match value {
+        ConfigValue::Integer(i, _) => i.hash(hasher),
+        ConfigValue::String(s, _) => s.hash(hasher),
+        ConfigValue::Boolean(b, _) => b.hash(hasher),
+        ConfigValue::List(list, _) => {
+            for (s, _) in list {
+                s.hash(hasher);
+            }
+        }
+        ConfigValue::Table(table, _) => {
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, value) in entries {
+                key.hash(hasher);
+                hash_config_value(value, hasher);
+            }
+        }
+    }
+}
@@ -1,26 +1,39 @@
 use crate::sources::CRATES_IO_DOMAIN;
 
-pub use self::cargo_clean::{clean, CleanOptions};
+pub use self::cargo_clean::{auto_gc, clean, gc, CleanOptions, GcOptions};
 pub use self::cargo_compile::{
-    compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features, CompileOptions,
+    build_closure, compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features,
+    unit_graph, BuildClosure, CompileOptions,
 };
 pub use self::cargo_compile::{CompileFilter, FilterRule, LibRule, Packages};
 pub use self::cargo_doc::{doc, DocOptions};
+pub use self::cargo_explain_hash::explain_hash;
+pub use self::cargo_feature_powerset::check_feature_powerset;
 pub use self::cargo_fetch::{fetch, FetchOptions};
+pub use self::cargo_fingerprint_diff::{fingerprint_diff, FingerprintDiffOptions};
 pub use self::cargo_generate_lockfile::generate_lockfile;
 pub use self::cargo_generate_lockfile::update_lockfile;
 pub use self::cargo_generate_lockfile::UpdateOptions;
 pub use self::cargo_install::{install, install_list};
+pub use self::cargo_members::{affected, members, AffectedReason, MembersOptions};
 pub use self::cargo_new::{init, new, NewOptions, NewProjectKind, VersionControl};
-pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
-pub use self::cargo_package::{check_yanked, package, package_one, PackageOpts};
-pub use self::cargo_pkgid::pkgid;
+pub use self::cargo_output_metadata::{
+    output_metadata, output_metadata_json, ExportInfo, OutputMetadataOptions,
+};
+pub use self::cargo_package::{check_yanked, package, package_one, CheckFormat, PackageOpts};
+pub use self::cargo_patch_status::{diagnose_unused_patch, patch_status, UnusedPatch};
+pub use self::cargo_pkgid::{pkgid, pkgid_info, PkgidInfo};
 pub use self::cargo_read_manifest::{read_package, read_packages};
+pub use self::cargo_release_plan::{release_plan, ReleasePlan, ReleasePlanOptions};
+pub use self::cargo_remove_member::{remove_member, RemoveMemberOptions};
+pub use self::cargo_report_features::{report_features, ReportFeaturesOptions};
 pub use self::cargo_run::run;
-pub use self::cargo_test::{run_benches, run_tests, TestOptions};
+pub use self::cargo_test::{run_benches, run_test_matrix, run_tests, MatrixTarget, TestOptions};
 pub use self::cargo_uninstall::uninstall;
+pub use self::cargo_verify_lockfile::{check_content_hashes, verify_lockfile};
+pub use self::cargo_version_check::{version_check, VersionCheck, VersionCheckOptions};
 pub use self::fix::{fix, fix_exec_rustc, fix_get_proxy_lock_addr, FixOptions};
-pub use self::lockfile::{load_pkg_lockfile, resolve_to_string, write_pkg_lockfile};
+pub use self::lockfile::{load_pkg_lockfile, lockfile_path, resolve_to_string, write_pkg_lockfile};
 pub use self::registry::modify_owners;
 pub use self::registry::publish;
 pub use self::registry::registry_login;
@@ -30,9 +43,10 @@ pub use self::registry::yank;
 pub use self::registry::OwnersOptions;
 pub use self::registry::PublishOpts;
 pub use self::registry::RegistryCredentialConfig;
+pub use self::registry::SearchFormat;
 pub use self::resolve::{
-    add_overrides, get_resolved_packages, resolve_with_previous, resolve_ws, resolve_ws_with_opts,
-    WorkspaceResolve,
+    add_overrides, get_resolved_packages, resolve_with_previous, resolve_ws,
+    resolve_ws_with_features, resolve_ws_with_opts, WorkspaceResolve,
 };
 pub use self::vendor::{vendor, VendorOptions};
 
@@ -41,21 +55,32 @@ mod cargo_clean;
 pub(crate) mod cargo_compile;
 pub mod cargo_config;
 mod cargo_doc;
+mod cargo_explain_hash;
+mod cargo_feature_powerset;
 mod cargo_fetch;
+mod cargo_fingerprint_diff;
 mod cargo_generate_lockfile;
 mod cargo_install;
+mod cargo_members;
 mod cargo_new;
 mod cargo_output_metadata;
 mod cargo_package;
+mod cargo_patch_status;
 mod cargo_pkgid;
 mod cargo_read_manifest;
+mod cargo_release_plan;
 pub mod cargo_remove;
+mod cargo_remove_member;
+mod cargo_report_features;
 mod cargo_run;
 mod cargo_test;
 mod cargo_uninstall;
+mod cargo_verify_lockfile;
+pub mod cargo_version_check;
 mod common_for_install_and_uninstall;
 mod fix;
 pub(crate) mod lockfile;
+mod metadata_cache;
 pub(crate) mod registry;
 pub(crate) mod resolve;
 pub mod tree;
@@ -32,7 +32,7 @@ pub use self::owner::modify_owners;
 pub use self::owner::OwnersOptions;
 pub use self::publish::publish;
 pub use self::publish::PublishOpts;
-pub use self::search::search;
+pub use self::search::{search, SearchFormat};
 pub use self::yank::yank;
 
 /// Registry settings loaded from config files.
@@ -3,6 +3,7 @@
 //! [1]: https://doc.rust-lang.org/nightly/cargo/reference/registry-web-api.html#publish
 
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs::File;
 use std::time::Duration;
@@ -25,6 +26,8 @@ use crate::core::QueryKind;
 use crate::core::SourceId;
 use crate::core::Workspace;
 use crate::ops;
+use crate::ops::cargo_release_plan::publish_order;
+use crate::ops::cargo_release_plan::ws_deps;
 use crate::ops::PackageOpts;
 use crate::ops::Packages;
 use crate::sources::SourceConfigMap;
@@ -51,19 +54,35 @@ pub struct PublishOpts<'cfg> {
     pub dry_run: bool,
     pub registry: Option<String>,
     pub cli_features: CliFeatures,
+    /// The group of `[workspace.metadata.publish-groups]` to publish, which
+    /// `to_publish` is narrowed down to.
+    pub group: Option<String>,
 }
 
 pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
-    let specs = opts.to_publish.to_package_id_specs(ws)?;
-    if specs.len() > 1 {
+    let mut specs = opts.to_publish.to_package_id_specs(ws)?;
+    // Publishing several packages at once, or selecting them with
+    // `--workspace`, `--exclude` or `--group`, is unstable.
+    let several = specs.len() > 1
+        || opts.group.is_some()
+        || !matches!(opts.to_publish, Packages::Default | Packages::Packages(_));
+    if several && !opts.config.cli_unstable().unstable_options {
         bail!("the `-p` argument must be specified to select a single package to publish")
     }
+    if let Some(group) = &opts.group {
+        let members = publish_group(ws, group)?;
+        specs.retain(|spec| members.iter().any(|pkg| spec.matches(pkg.package_id())));
+        if specs.is_empty() {
+            bail!("all members of the publish group `{}` are excluded", group);
+        }
+    }
     if Packages::Default == opts.to_publish && ws.is_virtual() {
         bail!("the `-p` argument must be specified in the root of a virtual workspace")
     }
-    let member_ids = ws.members().map(|p| p.package_id());
-    // Check that the spec matches exactly one member.
-    specs[0].query(member_ids)?;
+    // Check that each spec matches exactly one member.
+    for spec in &specs {
+        spec.query(ws.members().map(|p| p.package_id()))?;
+    }
     let mut pkgs = ws.members_with_features(&specs, &opts.cli_features)?;
     // In `members_with_features_old`, it will add "current" package (determined by the cwd)
     // So we need filter
@@ -72,10 +91,201 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         .filter(|(m, _)| specs.iter().any(|spec| spec.matches(m.package_id())))
         .collect();
     // Double check. It is safe theoretically, unless logic has updated.
-    assert_eq!(pkgs.len(), 1);
+    assert!(!pkgs.is_empty());
 
+    if pkgs.len() > 1 || opts.group.is_some() {
+        return publish_many(ws, pkgs, opts);
+    }
     let (pkg, cli_features) = pkgs.pop().unwrap();
+    publish_one(ws, pkg, cli_features, opts.verify, opts)?;
+    Ok(())
+}
+
+/// Publishes several members of the workspace, each one after the members it
+/// depends on is available in the registry.
+///
+/// Members with `package.publish = false` are skipped.
+fn publish_many(
+    ws: &Workspace<'_>,
+    pkgs: Vec<(&Package, CliFeatures)>,
+    opts: &PublishOpts<'_>,
+) -> CargoResult<()> {
+    let config = opts.config;
+    let mut selected = Vec::new();
+    let mut features = HashMap::new();
+    for (pkg, cli_features) in pkgs {
+        if pkg.publish().as_ref().map_or(false, |regs| regs.is_empty()) {
+            config.shell().note(format!(
+                "skipping `{}`, `package.publish` is set to `false`",
+                pkg.name()
+            ))?;
+            continue;
+        }
+        features.insert(pkg.package_id(), cli_features);
+        selected.push(pkg);
+    }
+
+    if selected.is_empty() {
+        config
+            .shell()
+            .warn("none of the selected packages can be published")?;
+        return Ok(());
+    }
+    check_path_deps(ws, &selected)?;
+
+    let order = publish_order(&selected)?;
+    config.shell().status(
+        "Publishing",
+        format!(
+            "{} in order: {}",
+            if order.len() == 1 {
+                "1 package".to_string()
+            } else {
+                format!("{} packages", order.len())
+            },
+            order
+                .iter()
+                .map(|pkg| pkg.name().as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    )?;
+    for (i, &pkg) in order.iter().enumerate() {
+        let cli_features = features.remove(&pkg.package_id()).unwrap();
+        let mut verify = opts.verify;
+        if verify && opts.dry_run {
+            // The members it depends on are not uploaded by a dry run, so
+            // the package cannot be built from the registry.
+            if let Some(dep) = ws_deps(pkg, &order[..i]).next() {
+                config.shell().note(format!(
+                    "skipping verification of `{}`, it depends on `{}` which is not \
+                     uploaded by a dry run",
+                    pkg.name(),
+                    dep.name()
+                ))?;
+                verify = false;
+            }
+        }
+        let available = publish_one(ws, pkg, cli_features, verify, opts)?;
+        let dependents: Vec<_> = order[i + 1..]
+            .iter()
+            .filter(|dependent| ws_deps(dependent, &[pkg]).next().is_some())
+            .map(|dependent| format!("`{}`", dependent.name()))
+            .collect();
+        // Nothing is uploaded by a dry run, so the dependents aren't either.
+        if !available && !opts.dry_run && !dependents.is_empty() {
+            bail!(
+                "`{} v{}` is not available in the registry yet, which {} depend on\n\
+                 Publish the remaining packages once it is available.",
+                pkg.name(),
+                pkg.version(),
+                dependents.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the members of the group `name` of
+/// `[workspace.metadata.publish-groups]`.
+fn publish_group<'a>(ws: &'a Workspace<'_>, name: &str) -> CargoResult<Vec<&'a Package>> {
+    let groups = ws
+        .custom_metadata()
+        .and_then(|metadata| metadata.get("publish-groups"))
+        .and_then(|groups| groups.as_table());
+    let Some(group) = groups.and_then(|groups| groups.get(name)) else {
+        let mut msg = format!(
+            "publish group `{name}` is not defined in `[workspace.metadata.publish-groups]`"
+        );
+        match groups.filter(|groups| !groups.is_empty()) {
+            Some(groups) => {
+                let names: Vec<_> = groups.keys().map(String::as_str).collect();
+                msg.push_str(&format!(
+                    "\n\navailable publish groups: {}",
+                    names.join(", ")
+                ));
+            }
+            None => msg.push_str(", which has no publish groups"),
+        }
+        bail!(msg);
+    };
+    let Some(names) = group.as_array().and_then(|names| {
+        names
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Option<Vec<_>>>()
+    }) else {
+        bail!("`workspace.metadata.publish-groups.{name}` must be a list of package names");
+    };
+    names
+        .into_iter()
+        .map(|member| {
+            ws.members()
+                .find(|pkg| pkg.name() == member)
+                .ok_or_else(|| {
+                    anyhow::format_err!(
+                        "publish group `{name}` contains `{member}`, \
+                         which is not a member of the workspace"
+                    )
+                })
+        })
+        .collect()
+}
 
+/// Checks that the members in `selected` don't depend on members which can't
+/// be published along with them, before anything is uploaded.
+///
+/// Such a dependency either has no version, or is a member which is not
+/// selected and has `package.publish = false`.
+fn check_path_deps(ws: &Workspace<'_>, selected: &[&Package]) -> CargoResult<()> {
+    for pkg in selected {
+        for dep in pkg.dependencies() {
+            if !dep.is_transitive() || !dep.source_id().is_path() {
+                continue;
+            }
+            let Some(member) = ws.members().find(|member| {
+                dep.package_name() == member.name()
+                    && dep.source_id() == member.package_id().source_id()
+            }) else {
+                continue;
+            };
+            if !dep.specified_req() {
+                bail!(
+                    "`{}` depends on `{}` by path only, which cannot be published\n\
+                     Add a `version` to the dependency on `{}`.",
+                    pkg.name(),
+                    member.name(),
+                    member.name()
+                );
+            }
+            let unpublishable = member
+                .publish()
+                .as_ref()
+                .map_or(false, |regs| regs.is_empty());
+            if unpublishable && !selected.contains(&member) {
+                bail!(
+                    "`{}` depends on `{}`, which has `package.publish` set to `false`",
+                    pkg.name(),
+                    member.name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Packages and uploads `pkg`, then waits for it to be available in the
+/// registry, unless this is a dry run.
+///
+/// Returns whether the package is known to be available, which it isn't
+/// after a dry run or when `publish.timeout` is 0.
+fn publish_one(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    cli_features: CliFeatures,
+    verify: bool,
+    opts: &PublishOpts<'_>,
+) -> CargoResult<bool> {
     let mut publish_registry = opts.registry.clone();
     if let Some(ref allowed_registries) = *pkg.publish() {
         if publish_registry.is_none() && allowed_registries.len() == 1 {
@@ -133,7 +343,7 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         pkg,
         &PackageOpts {
             config: opts.config,
-            verify: opts.verify,
+            verify,
             list: false,
             check_metadata: true,
             allow_dirty: opts.allow_dirty,
@@ -142,6 +352,7 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
             jobs: opts.jobs.clone(),
             keep_going: opts.keep_going,
             cli_features,
+            check_consistency: None,
         },
     )?
     .unwrap();
@@ -166,7 +377,7 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
 
     opts.config
         .shell()
-        .status("Uploading", pkg.package_id().to_string())?;
+        .status("Uploading", ws.display_package_id(pkg.package_id()))?;
     transmit(
         opts.config,
         pkg,
@@ -185,19 +396,22 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         };
         if 0 < timeout {
             let timeout = Duration::from_secs(timeout);
-            wait_for_publish(opts.config, reg_ids.original, pkg, timeout)?;
+            return wait_for_publish(opts.config, reg_ids.original, pkg, timeout);
         }
     }
 
-    Ok(())
+    Ok(false)
 }
 
+/// Waits until `pkg` is available in the registry, or until `timeout`.
+///
+/// Returns whether it is available.
 fn wait_for_publish(
     config: &Config,
     registry_src: SourceId,
     pkg: &Package,
     timeout: Duration,
-) -> CargoResult<()> {
+) -> CargoResult<bool> {
     let version_req = format!("={}", pkg.version());
     let mut source = SourceConfigMap::empty(config)?.load(registry_src, &HashSet::new())?;
     // Disable the source's built-in progress bars. Repeatedly showing a bunch
@@ -270,7 +484,7 @@ fn wait_for_publish(
         )?;
     }
 
-    Ok(())
+    Ok(is_available)
 }
 
 fn verify_dependencies(
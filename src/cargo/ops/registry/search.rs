@@ -6,6 +6,7 @@ use std::cmp;
 use std::iter::repeat;
 
 use anyhow::Context as _;
+use serde::Serialize;
 use termcolor::Color;
 use termcolor::ColorSpec;
 use url::Url;
@@ -14,22 +15,66 @@ use crate::util::truncate_with_ellipsis;
 use crate::CargoResult;
 use crate::Config;
 
+/// How `cargo search` prints the results.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SearchFormat {
+    /// Lines to paste in a manifest, with the descriptions as comments.
+    Text,
+    /// A single JSON object, for tools such as editor plugins.
+    Json,
+}
+
+/// The JSON output of `cargo search --format json`.
+#[derive(Serialize)]
+struct SearchOutput<'a> {
+    crates: Vec<SearchResult<'a>>,
+    total: u32,
+    page: u32,
+}
+
+#[derive(Serialize)]
+struct SearchResult<'a> {
+    name: &'a str,
+    max_version: &'a str,
+    description: Option<&'a str>,
+    downloads: Option<u64>,
+}
+
 pub fn search(
     query: &str,
     config: &Config,
     index: Option<String>,
     limit: u32,
+    page: u32,
+    format: SearchFormat,
     reg: Option<String>,
 ) -> CargoResult<()> {
     let (mut registry, source_ids) =
         super::registry(config, None, index.as_deref(), reg.as_deref(), false, None)?;
-    let (crates, total_crates) = registry.search(query, limit).with_context(|| {
+    let (crates, total_crates) = registry.search_page(query, limit, page).with_context(|| {
         format!(
             "failed to retrieve search results from the registry at {}",
             registry.host()
         )
     })?;
 
+    if format == SearchFormat::Json {
+        let crates = crates
+            .iter()
+            .map(|krate| SearchResult {
+                name: &krate.name,
+                max_version: &krate.max_version,
+                description: krate.description.as_deref(),
+                downloads: krate.downloads,
+            })
+            .collect();
+        return config.shell().print_json(&SearchOutput {
+            crates,
+            total: total_crates,
+            page,
+        });
+    }
+
     let names = crates
         .iter()
         .map(|krate| format!("{} = \"{}\"", krate.name, krate.max_version))
@@ -69,8 +114,19 @@ pub fn search(
         let _ = config.shell().write_stdout("\n", &ColorSpec::new());
     }
 
+    // The crates on this page and the ones before it.
+    let shown = page.saturating_mul(limit);
     let search_max_limit = 100;
-    if total_crates > limit && limit < search_max_limit {
+    if total_crates > shown && page > 1 {
+        let _ = config.shell().write_stdout(
+            format_args!(
+                "... and {} crates more (use --page {} to see more)\n",
+                total_crates - shown,
+                page + 1
+            ),
+            &ColorSpec::new(),
+        );
+    } else if total_crates > limit && limit < search_max_limit {
         let _ = config.shell().write_stdout(
             format_args!(
                 "... and {} crates more (use --limit N to see more)\n",
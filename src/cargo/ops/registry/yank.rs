@@ -3,20 +3,29 @@
 //! [yank]: https://doc.rust-lang.org/nightly/cargo/reference/registry-web-api.html#yank
 //! [unyank]: https://doc.rust-lang.org/nightly/cargo/reference/registry-web-api.html#unyank
 
+use std::collections::HashSet;
+use std::io::{BufRead, IsTerminal};
+use std::task::Poll;
+
 use anyhow::bail;
 use anyhow::Context as _;
 use cargo_credential::Operation;
 use cargo_credential::Secret;
 
-use crate::core::Workspace;
+use crate::core::{Source, SourceId, Workspace};
+use crate::sources::RegistrySource;
 use crate::util::config::Config;
 use crate::util::errors::CargoResult;
 use crate::util::important_paths::find_root_manifest_for_wd;
+use crate::util::OptVersionReq;
+use crate::{drop_eprint, drop_eprintln};
 
 pub fn yank(
     config: &Config,
     krate: Option<String>,
     version: Option<String>,
+    versions: Option<String>,
+    yes: bool,
     token: Option<Secret<String>>,
     index: Option<String>,
     undo: bool,
@@ -30,6 +39,9 @@ pub fn yank(
             ws.current()?.package_id().name().to_string()
         }
     };
+    if let Some(versions) = versions {
+        return yank_many(config, &name, &versions, yes, token, index, undo, reg);
+    }
     let version = match version {
         Some(v) => v,
         None => bail!("a version must be specified to yank"),
@@ -74,3 +86,165 @@ pub fn yank(
 
     Ok(())
 }
+
+/// Yanks, or unyanks with `undo`, the versions of `name` matching the
+/// `versions` requirement, once confirmed.
+fn yank_many(
+    config: &Config,
+    name: &str,
+    versions: &str,
+    yes: bool,
+    token: Option<Secret<String>>,
+    index: Option<String>,
+    undo: bool,
+    reg: Option<String>,
+) -> CargoResult<()> {
+    let req = semver::VersionReq::parse(versions)
+        .with_context(|| format!("invalid version requirement `{versions}`"))?;
+    let action = if undo { "unyank" } else { "yank" };
+    let source_ids = super::get_source_id(config, index.as_deref(), reg.as_deref())?;
+    let matching = matching_versions(config, source_ids.replacement, name, &req.into(), undo)?;
+    if matching.is_empty() {
+        bail!("no versions of `{name}` matching `{versions}` to {action}");
+    }
+
+    let list = matching
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    config.shell().status(
+        "Matched",
+        format!(
+            "{} version{} of `{name}` to {action}: {list}",
+            matching.len(),
+            if matching.len() == 1 { "" } else { "s" }
+        ),
+    )?;
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            bail!("pass `--yes` to {action} these versions without confirming them");
+        }
+        drop_eprint!(config, "Continue? [y/N] ");
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            bail!("aborted, no versions were {action}ed");
+        }
+    }
+
+    let mut results = Vec::new();
+    for version in &matching {
+        let version = version.to_string();
+        let result = yank_one(config, name, &version, &token, &index, undo, &reg);
+        if let Err(e) = &result {
+            crate::display_error(e, &mut config.shell());
+        }
+        results.push((format!("{name}@{version}"), result.is_ok()));
+    }
+
+    let failed = results.iter().filter(|(_, ok)| !ok).count();
+    config.shell().status(
+        "Summary",
+        format!(
+            "{} of {} versions {action}ed",
+            results.len() - failed,
+            results.len()
+        ),
+    )?;
+    let width = results
+        .iter()
+        .map(|(spec, _)| spec.len())
+        .max()
+        .unwrap_or(0);
+    for (spec, ok) in &results {
+        let status = if *ok { "ok" } else { "FAILED" };
+        drop_eprintln!(config, "  {spec:<width$}  {status}");
+    }
+    if failed > 0 {
+        bail!(
+            "failed to {action} {failed} of {} versions of `{name}`",
+            results.len()
+        );
+    }
+    Ok(())
+}
+
+fn yank_one(
+    config: &Config,
+    name: &str,
+    version: &str,
+    token: &Option<Secret<String>>,
+    index: &Option<String>,
+    undo: bool,
+    reg: &Option<String>,
+) -> CargoResult<()> {
+    let message = if undo {
+        Operation::Unyank {
+            name,
+            vers: version,
+        }
+    } else {
+        Operation::Yank {
+            name,
+            vers: version,
+        }
+    };
+    let (mut registry, _) = super::registry(
+        config,
+        token.as_ref().map(Secret::as_deref),
+        index.as_deref(),
+        reg.as_deref(),
+        true,
+        Some(message),
+    )?;
+
+    let package_spec = format!("{name}@{version}");
+    if undo {
+        config.shell().status("Unyank", package_spec)?;
+        registry.unyank(name, version).with_context(|| {
+            format!(
+                "failed to undo a yank of `{name}@{version}` from the registry at {}",
+                registry.host()
+            )
+        })?;
+    } else {
+        config.shell().status("Yank", package_spec)?;
+        registry.yank(name, version).with_context(|| {
+            format!(
+                "failed to yank `{name}@{version}` from the registry at {}",
+                registry.host()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Finds the published versions of `name` matching `req` in the index, which
+/// are not yanked yet, or which are yanked with `undo`.
+fn matching_versions(
+    config: &Config,
+    source_id: SourceId,
+    name: &str,
+    req: &OptVersionReq,
+    undo: bool,
+) -> CargoResult<Vec<semver::Version>> {
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut source = RegistrySource::remote(source_id, &HashSet::new(), config)?;
+    // The yanked status has to be up to date.
+    source.invalidate_cache();
+    let mut versions = loop {
+        match source.versions(name, req)? {
+            Poll::Pending => source
+                .block_until_ready()
+                .with_context(|| format!("failed to update {source_id}"))?,
+            Poll::Ready(versions) => break versions,
+        }
+    };
+    versions.sort();
+    Ok(versions
+        .into_iter()
+        .filter(|(_, yanked)| *yanked == undo)
+        .map(|(version, _)| version)
+        .collect())
+}
@@ -69,9 +69,10 @@ use crate::core::{GitReference, PackageId, PackageIdSpec, PackageSet, SourceId,
 use crate::ops;
 use crate::sources::PathSource;
 use crate::util::errors::CargoResult;
-use crate::util::{profile, CanonicalUrl};
+use crate::util::{invocation_log, profile, CanonicalUrl};
 use anyhow::Context as _;
-use std::collections::{HashMap, HashSet};
+use serde::Deserialize;
+use std::collections::HashSet;
 use tracing::{debug, trace};
 
 /// Result for `resolve_ws_with_opts`.
@@ -107,8 +108,9 @@ version. This may also occur with an optional dependency that is not enabled.";
 /// `package`, which don't specify any options or features.
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
     let mut registry = PackageRegistry::new(ws.config())?;
-    let resolve = resolve_with_registry(ws, &mut registry)?;
+    let mut resolve = resolve_with_registry(ws, &mut registry)?;
     let packages = get_resolved_packages(&resolve, registry)?;
+    ops::check_content_hashes(ws, &packages, &mut resolve)?;
     Ok((packages, resolve))
 }
 
@@ -131,9 +133,74 @@ pub fn resolve_ws_with_opts<'cfg>(
     has_dev_units: HasDevUnits,
     force_all_targets: ForceAllTargets,
 ) -> CargoResult<WorkspaceResolve<'cfg>> {
+    let (pkg_set, resolve, resolved_with_overrides) =
+        resolve_ws_with_features(ws, cli_features, specs, has_dev_units)?;
+
+    let member_ids = ws
+        .members_with_features(specs, cli_features)?
+        .into_iter()
+        .map(|(p, _fts)| p.package_id())
+        .collect::<Vec<_>>();
+    pkg_set.download_accessible(
+        &resolved_with_overrides,
+        &member_ids,
+        has_dev_units,
+        requested_targets,
+        target_data,
+        force_all_targets,
+    )?;
+
+    let feature_opts = FeatureOpts::new(ws, has_dev_units, force_all_targets)?;
+    let resolved_features = FeatureResolver::resolve(
+        ws,
+        target_data,
+        &resolved_with_overrides,
+        &pkg_set,
+        cli_features,
+        specs,
+        requested_targets,
+        feature_opts,
+    )?;
+
+    pkg_set.warn_no_lib_packages_and_artifact_libs_overlapping_deps(
+        ws,
+        &resolved_with_overrides,
+        &member_ids,
+        has_dev_units,
+        requested_targets,
+        target_data,
+        force_all_targets,
+    )?;
+
+    if let Some(log) = ws.config().invocation_log() {
+        let mut packages: Vec<_> = resolved_with_overrides.iter().collect();
+        packages.sort();
+        log.log(&invocation_log::Resolved { packages });
+    }
+
+    Ok(WorkspaceResolve {
+        pkg_set,
+        workspace_resolve: resolve,
+        targeted_resolve: resolved_with_overrides,
+        resolved_features,
+    })
+}
+
+/// Resolves the dependency graph of some packages of the workspace with
+/// `cli_features`, like [`resolve_ws_with_opts`], but without downloading the
+/// packages or resolving the features of the dependencies.
+///
+/// Returns the packages, to download the ones needed, the resolve of the
+/// lock file, and the resolve of `specs`.
+pub fn resolve_ws_with_features<'cfg>(
+    ws: &Workspace<'cfg>,
+    cli_features: &CliFeatures,
+    specs: &[PackageIdSpec],
+    has_dev_units: HasDevUnits,
+) -> CargoResult<(PackageSet<'cfg>, Option<Resolve>, Resolve)> {
     let mut registry = PackageRegistry::new(ws.config())?;
     let mut add_patches = true;
-    let resolve = if ws.ignore_lock() {
+    let mut resolve = if ws.ignore_lock() {
         None
     } else if ws.require_optional_deps() {
         // First, resolve the root_package's *listed* dependencies, as well as
@@ -187,49 +254,11 @@ pub fn resolve_ws_with_opts<'cfg>(
     )?;
 
     let pkg_set = get_resolved_packages(&resolved_with_overrides, registry)?;
+    if let Some(resolve) = &mut resolve {
+        ops::check_content_hashes(ws, &pkg_set, resolve)?;
+    }
 
-    let member_ids = ws
-        .members_with_features(specs, cli_features)?
-        .into_iter()
-        .map(|(p, _fts)| p.package_id())
-        .collect::<Vec<_>>();
-    pkg_set.download_accessible(
-        &resolved_with_overrides,
-        &member_ids,
-        has_dev_units,
-        requested_targets,
-        target_data,
-        force_all_targets,
-    )?;
-
-    let feature_opts = FeatureOpts::new(ws, has_dev_units, force_all_targets)?;
-    let resolved_features = FeatureResolver::resolve(
-        ws,
-        target_data,
-        &resolved_with_overrides,
-        &pkg_set,
-        cli_features,
-        specs,
-        requested_targets,
-        feature_opts,
-    )?;
-
-    pkg_set.warn_no_lib_packages_and_artifact_libs_overlapping_deps(
-        ws,
-        &resolved_with_overrides,
-        &member_ids,
-        has_dev_units,
-        requested_targets,
-        target_data,
-        force_all_targets,
-    )?;
-
-    Ok(WorkspaceResolve {
-        pkg_set,
-        workspace_resolve: resolve,
-        targeted_resolve: resolved_with_overrides,
-        resolved_features,
-    })
+    Ok((pkg_set, resolve, resolved_with_overrides))
 }
 
 fn resolve_with_registry<'cfg>(
@@ -496,6 +525,7 @@ pub fn resolve_with_previous<'cfg>(
     };
 
     ws.preload(registry);
+    version_prefs.max_rust_version(msrv_policy_rust_version(ws)?);
     let mut resolved = resolver::resolve(
         &summaries,
         &replace,
@@ -523,6 +553,39 @@ pub fn resolve_with_previous<'cfg>(
     Ok(resolved)
 }
 
+/// The `[resolver]` configuration table.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct ResolverConfig {
+    msrv_policy: Option<String>,
+}
+
+/// Returns the version of the active rustc if `resolver.msrv-policy` asks
+/// the resolver to skip the versions of packages requiring a newer one.
+fn msrv_policy_rust_version(ws: &Workspace<'_>) -> CargoResult<Option<semver::Version>> {
+    let config = ws.config();
+    if !config.cli_unstable().msrv_policy {
+        return Ok(None);
+    }
+    let cfg = config.get::<Option<ResolverConfig>>("resolver")?;
+    match cfg.unwrap_or_default().msrv_policy.as_deref() {
+        None | Some("ignore") => Ok(None),
+        Some("compatible") => {
+            // Remove any pre-release identifiers, as when checking
+            // `rust-version` before building.
+            let version = config.load_global_rustc(Some(ws))?.version;
+            Ok(Some(semver::Version::new(
+                version.major,
+                version.minor,
+                version.patch,
+            )))
+        }
+        Some(policy) => anyhow::bail!(
+            "`resolver.msrv-policy` must be `compatible` or `ignore`, found `{policy}`"
+        ),
+    }
+}
+
 /// Read the `paths` configuration variable to discover all path overrides that
 /// have been configured.
 pub fn add_overrides<'a>(
@@ -549,7 +612,7 @@ pub fn add_overrides<'a>(
             format!(
                 "failed to update path override `{}` \
                  (defined in `{}`)",
-                path.display(),
+                ws.display_path(&path),
                 definition
             )
         })?;
@@ -792,8 +855,9 @@ fn master_branch_git_source(id: PackageId, resolve: &Resolve) -> Option<PackageI
 /// Emits warnings of unused patches case by case.
 ///
 /// This function does its best to provide more targeted and helpful
-/// (such as showing close candidates that failed to match). However, that's
-/// not terribly easy to do, so just show a general help message if we cannot.
+/// messages, such as the version requirement the patch doesn't match, see
+/// [`ops::diagnose_unused_patch`]. However, that's not always possible, so
+/// just show a general help message if we cannot.
 fn emit_warnings_of_unused_patches(
     ws: &Workspace<'_>,
     resolve: &Resolve,
@@ -801,53 +865,25 @@ fn emit_warnings_of_unused_patches(
 ) -> CargoResult<()> {
     const MESSAGE: &str = "was not used in the crate graph.";
 
-    // Patch package with the source URLs being patch
-    let mut patch_pkgid_to_urls = HashMap::new();
-    for (url, summaries) in registry.patches().iter() {
-        for summary in summaries.iter() {
-            patch_pkgid_to_urls
-                .entry(summary.package_id())
-                .or_insert_with(HashSet::new)
-                .insert(url);
-        }
-    }
-
-    // pkg name -> all source IDs of under the same pkg name
-    let mut source_ids_grouped_by_pkg_name = HashMap::new();
-    for pkgid in resolve.iter() {
-        source_ids_grouped_by_pkg_name
-            .entry(pkgid.name())
-            .or_insert_with(HashSet::new)
-            .insert(pkgid.source_id());
-    }
-
+    let mut unused_patches = resolve.unused_patches().to_vec();
+    unused_patches.sort();
     let mut unemitted_unused_patches = Vec::new();
-    for unused in resolve.unused_patches().iter() {
-        // Show alternative source URLs if the source URLs being patch
-        // cannot not be found in the crate graph.
-        match (
-            source_ids_grouped_by_pkg_name.get(&unused.name()),
-            patch_pkgid_to_urls.get(unused),
-        ) {
-            (Some(ids), Some(patched_urls))
-                if ids
-                    .iter()
-                    .all(|id| !patched_urls.contains(id.canonical_url())) =>
-            {
-                use std::fmt::Write;
-                let mut msg = String::new();
-                writeln!(msg, "Patch `{}` {}", unused, MESSAGE)?;
-                write!(
-                    msg,
-                    "Perhaps you misspelled the source URL being patched.\n\
-                    Possible URLs for `[patch.<URL>]`:",
-                )?;
-                for id in ids.iter() {
-                    write!(msg, "\n    {}", id.display_registry_name())?;
-                }
-                ws.config().shell().warn(msg)?;
+    for unused in unused_patches.iter() {
+        let mut emitted = false;
+        for (url, summaries) in registry.patches().iter() {
+            if !summaries.iter().any(|s| s.package_id() == *unused) {
+                continue;
             }
-            _ => unemitted_unused_patches.push(unused),
+            let patches: Vec<_> = summaries.iter().map(|s| s.package_id()).collect();
+            if let Some(reason) = ops::diagnose_unused_patch(resolve, *unused, url, &patches) {
+                ws.config()
+                    .shell()
+                    .warn(format!("Patch `{}` {}\n{}", unused, MESSAGE, reason))?;
+                emitted = true;
+            }
+        }
+        if !emitted {
+            unemitted_unused_patches.push(unused);
         }
     }
 
@@ -58,6 +58,29 @@ impl Edges {
     }
 }
 
+/// A dependency declared with `default-features = false`, see
+/// [`Graph::default_features_audit`].
+pub struct DisabledDefaults {
+    /// Index of the package node declaring the dependency.
+    pub from: usize,
+    /// Index of the package node of the dependency.
+    pub to: usize,
+    /// What enables the default features of the dependency anyway.
+    pub enabled_by: Vec<DefaultsEnabler>,
+}
+
+/// Something enabling the `default` feature of a package.
+pub enum DefaultsEnabler {
+    /// A dependency without `default-features = false`, from the package node
+    /// with the given index.
+    Dep { from: usize, kind: DepKind },
+    /// The feature node with the given index.
+    Feature(usize),
+    /// The package is selected on the command-line, with its default
+    /// features.
+    CommandLine,
+}
+
 /// A graph of dependencies.
 pub struct Graph<'a> {
     nodes: Vec<Node>,
@@ -148,7 +171,7 @@ impl<'a> Graph<'a> {
         self.package_map[&id]
     }
 
-    fn package_id_for_index(&self, index: usize) -> PackageId {
+    pub fn package_id_for_index(&self, index: usize) -> PackageId {
         match self.nodes[index] {
             Node::Package { package_id, .. } => package_id,
             Node::Feature { .. } => panic!("unexpected feature node"),
@@ -201,6 +224,82 @@ impl<'a> Graph<'a> {
         new_graph
     }
 
+    /// Removes the edges which do not lead to a feature matching one of
+    /// `features`, given as `[PKG/]NAME`, except for the ones below the
+    /// matching features. Returns the `roots` which still lead to one of
+    /// them.
+    ///
+    /// The graph must be built with features, and not be inverted.
+    pub fn retain_paths_to_features(
+        &mut self,
+        roots: &[usize],
+        features: &[String],
+    ) -> CargoResult<Vec<usize>> {
+        let mut matches = Vec::new();
+        for feature in features {
+            let (pkg, name) = match feature.split_once('/') {
+                Some((pkg, name)) => (Some(pkg), name),
+                None => (None, feature.as_str()),
+            };
+            let len = matches.len();
+            matches.extend(self.nodes.iter().enumerate().filter_map(|(i, node)| {
+                match node {
+                    Node::Feature {
+                        node_index,
+                        name: n,
+                    } if n.as_str() == name => pkg
+                        .map_or(true, |pkg| {
+                            self.package_id_for_index(*node_index).name() == pkg
+                        })
+                        .then_some(i),
+                    _ => None,
+                }
+            }));
+            if matches.len() == len {
+                anyhow::bail!("feature `{feature}` is not enabled in the dependency graph");
+            }
+        }
+
+        // Everything below the matching features is kept.
+        let mut below = HashSet::new();
+        let mut stack = matches.clone();
+        while let Some(index) = stack.pop() {
+            if below.insert(index) {
+                stack.extend(self.edges[index].0.values().flatten());
+            }
+        }
+        // And so are the edges leading to them, but not the other edges of
+        // the nodes on the way, even to nodes below the matching features.
+        let mut parents: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (from, edges) in self.edges.iter().enumerate() {
+            for to in edges.0.values().flatten() {
+                parents.entry(*to).or_default().push(from);
+            }
+        }
+        let mut stack = matches;
+        let mut above = HashSet::new();
+        while let Some(index) = stack.pop() {
+            if above.insert(index) {
+                stack.extend(parents.get(&index).into_iter().flatten());
+            }
+        }
+
+        for (from, edges) in self.edges.iter_mut().enumerate() {
+            if below.contains(&from) {
+                continue;
+            }
+            for indexes in edges.0.values_mut() {
+                indexes.retain(|index| above.contains(index));
+            }
+            edges.0.retain(|_, indexes| !indexes.is_empty());
+        }
+        Ok(roots
+            .iter()
+            .copied()
+            .filter(|root| above.contains(root))
+            .collect())
+    }
+
     /// Inverts the direction of all edges.
     pub fn invert(&mut self) {
         let mut new_edges = vec![Edges::new(); self.edges.len()];
@@ -214,6 +313,84 @@ impl<'a> Graph<'a> {
         self.edges = new_edges;
     }
 
+    /// For each dependency of the `roots` package nodes declared with
+    /// `default-features = false`, finds what enables its default features
+    /// anyway.
+    ///
+    /// The graph must be built with features, and not be inverted.
+    pub fn default_features_audit(
+        &self,
+        resolve: &Resolve,
+        roots: &[usize],
+    ) -> Vec<DisabledDefaults> {
+        assert!(!self.dep_name_map.is_empty());
+        let mut incoming: HashMap<usize, Vec<(usize, EdgeKind)>> = HashMap::new();
+        for (from, node_edges) in self.edges.iter().enumerate() {
+            for (kind, edges) in &node_edges.0 {
+                for to in edges {
+                    incoming.entry(*to).or_default().push((from, *kind));
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+        for &root in roots {
+            let pkg = self.package_for_id(self.package_id_for_index(root));
+            // Dependencies with features are connected through feature nodes.
+            let mut dep_indexes: Vec<usize> = self.edges[root]
+                .0
+                .iter()
+                .filter(|(kind, _)| matches!(kind, EdgeKind::Dep(_)))
+                .flat_map(|(_, edges)| edges)
+                .map(|&index| match self.nodes[index] {
+                    Node::Package { .. } => index,
+                    Node::Feature { node_index, .. } => node_index,
+                })
+                .collect();
+            dep_indexes.sort_unstable_by(|a, b| self.nodes[*a].cmp(&self.nodes[*b]));
+            dep_indexes.dedup();
+
+            for to in dep_indexes {
+                let dep_id = self.package_id_for_index(to);
+                let disabled = pkg
+                    .dependencies()
+                    .iter()
+                    .any(|dep| dep.matches_id(dep_id) && !dep.uses_default_features());
+                let has_defaults = resolve.summary(dep_id).features().contains_key("default");
+                if !disabled || !has_defaults {
+                    continue;
+                }
+
+                let default_node = Node::Feature {
+                    node_index: to,
+                    name: InternedString::new("default"),
+                };
+                let mut enabled_by = Vec::new();
+                if let Some(&default_index) = self.index.get(&default_node) {
+                    let mut enablers = incoming.get(&default_index).cloned().unwrap_or_default();
+                    enablers.sort_unstable_by(|a, b| self.nodes[a.0].cmp(&self.nodes[b.0]));
+                    for (from, kind) in enablers {
+                        enabled_by.push(match (&self.nodes[from], kind) {
+                            (Node::Package { .. }, EdgeKind::Dep(kind)) => {
+                                DefaultsEnabler::Dep { from, kind }
+                            }
+                            _ => DefaultsEnabler::Feature(from),
+                        });
+                    }
+                    if self.is_cli_feature(default_index) {
+                        enabled_by.push(DefaultsEnabler::CommandLine);
+                    }
+                }
+                result.push(DisabledDefaults {
+                    from: root,
+                    to,
+                    enabled_by,
+                });
+            }
+        }
+        result
+    }
+
     /// Returns a list of nodes that are considered "duplicates" (same package
     /// name, with different versions/features/source/etc.).
     pub fn find_duplicates(&self) -> Vec<usize> {
@@ -3,13 +3,13 @@
 use self::format::Pattern;
 use crate::core::compiler::{CompileKind, RustcTargetData};
 use crate::core::dependency::DepKind;
-use crate::core::resolver::{features::CliFeatures, ForceAllTargets, HasDevUnits};
+use crate::core::resolver::{features::CliFeatures, ForceAllTargets, HasDevUnits, Resolve};
 use crate::core::{Package, PackageId, PackageIdSpec, Workspace};
 use crate::ops::{self, Packages};
 use crate::util::{CargoResult, Config};
 use crate::{drop_print, drop_println};
 use anyhow::Context;
-use graph::Graph;
+use graph::{DefaultsEnabler, Graph};
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
@@ -49,6 +49,13 @@ pub struct TreeOptions {
     pub max_display_depth: u32,
     /// Excludes proc-macro dependencies.
     pub no_proc_macro: bool,
+    /// If `true`, report what enables the default features of the
+    /// dependencies declared with `default-features = false` instead of
+    /// printing the tree. Requires `graph_features`.
+    pub audit_default_features: bool,
+    /// Only show the paths to these features, given as `[PKG/]NAME`, and
+    /// what they enable. Requires `graph_features`.
+    pub why_features: Vec<String>,
 }
 
 #[derive(PartialEq)]
@@ -189,6 +196,21 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
     let root_ids = ws_resolve.targeted_resolve.specs_to_ids(&root_specs)?;
     let root_indexes = graph.indexes_from_ids(&root_ids);
 
+    if opts.audit_default_features {
+        return print_default_features_audit(
+            ws.config(),
+            &ws_resolve.targeted_resolve,
+            &root_indexes,
+            &graph,
+        );
+    }
+
+    let root_indexes = if opts.why_features.is_empty() {
+        root_indexes
+    } else {
+        graph.retain_paths_to_features(&root_indexes, &opts.why_features)?
+    };
+
     let root_indexes = if opts.duplicates {
         // `-d -p foo` will only show duplicates within foo's subtree
         graph = graph.from_reachable(root_indexes.as_slice());
@@ -225,6 +247,54 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
     Ok(())
 }
 
+/// Prints what enables the default features of the dependencies of `roots`
+/// declared with `default-features = false`.
+fn print_default_features_audit(
+    config: &Config,
+    resolve: &Resolve,
+    roots: &[usize],
+    graph: &Graph<'_>,
+) -> CargoResult<()> {
+    let audit = graph.default_features_audit(resolve, roots);
+    if audit.is_empty() {
+        return config
+            .shell()
+            .note("no dependency is declared with `default-features = false`");
+    }
+    for disabled in audit {
+        let from = graph.package_id_for_index(disabled.from);
+        let to = graph.package_id_for_index(disabled.to);
+        if disabled.enabled_by.is_empty() {
+            drop_println!(config, "{} -> {}: default features disabled", from, to);
+            continue;
+        }
+        drop_println!(config, "{} -> {}: default features enabled by", from, to);
+        for enabler in disabled.enabled_by {
+            match enabler {
+                DefaultsEnabler::Dep { from, kind } => {
+                    let kind = match kind {
+                        DepKind::Normal => "dependency",
+                        DepKind::Development => "dev-dependency",
+                        DepKind::Build => "build-dependency",
+                    };
+                    drop_println!(config, "  {} ({})", graph.package_id_for_index(from), kind);
+                }
+                DefaultsEnabler::Feature(index) => match graph.node(index) {
+                    Node::Feature { node_index, name } => drop_println!(
+                        config,
+                        "  {} (feature `{}`)",
+                        graph.package_id_for_index(*node_index),
+                        name
+                    ),
+                    Node::Package { .. } => unreachable!(),
+                },
+                DefaultsEnabler::CommandLine => drop_println!(config, "  the command-line"),
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Prints a tree for each given root.
 fn print(
     config: &Config,
@@ -1,13 +1,16 @@
+use crate::core::compiler::{CompileKind, RustcTargetData};
+use crate::core::dependency::DepKind;
 use crate::core::package::MANIFEST_PREAMBLE;
+use crate::core::resolver::Resolve;
 use crate::core::shell::Verbosity;
-use crate::core::{GitReference, Package, Workspace};
+use crate::core::{GitReference, Package, PackageId, Workspace};
 use crate::ops;
 use crate::sources::path::PathSource;
 use crate::sources::CRATES_IO_REGISTRY;
 use crate::util::{try_canonicalize, CargoResult, Config};
 use anyhow::{bail, Context as _};
 use cargo_util::{paths, Sha256};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ffi::OsStr;
@@ -20,6 +23,13 @@ pub struct VendorOptions<'a> {
     pub versioned_dirs: bool,
     pub destination: &'a Path,
     pub extra: Vec<PathBuf>,
+    /// Skip packages only needed by dev-dependencies of workspace members.
+    pub no_dev: bool,
+    /// Skip packages not needed on any of these platforms. Empty means all
+    /// platforms.
+    pub platforms: Vec<String>,
+    /// Leave out the `tests` and `benches` directories of vendored packages.
+    pub thin: bool,
 }
 
 pub fn vendor(ws: &Workspace<'_>, opts: &VendorOptions<'_>) -> CargoResult<()> {
@@ -142,12 +152,14 @@ fn sync(
 
     let mut checksums = HashMap::new();
     let mut ids = BTreeMap::new();
+    let mut needed = HashSet::new();
 
     // Next up let's actually download all crates and start storing internal
     // tables about them.
     for ws in workspaces {
         let (packages, resolve) =
             ops::resolve_ws(ws).with_context(|| "failed to load pkg lockfile")?;
+        needed.extend(needed_packages(ws, &resolve, opts)?);
 
         packages
             .get_many(resolve.iter())
@@ -214,7 +226,15 @@ fn sync(
         let cksum = dst.join(".cargo-checksum.json");
         if dir_has_version_suffix && cksum.exists() {
             // Always re-copy directory without version suffix in case the version changed
-            continue;
+            if matches_lockfile(&cksum, checksums[id].as_ref().and_then(|c| c.as_deref())) {
+                continue;
+            }
+            config.shell().warn(format!(
+                "the vendored sources of `{}` at {} don't match the checksum \
+                 in the lock file, vendoring them again",
+                id,
+                dst.display()
+            ))?;
         }
 
         config.shell().status(
@@ -225,9 +245,20 @@ fn sync(
         let _ = fs::remove_dir_all(&dst);
         let pathsource = PathSource::new(src, id.source_id(), config);
         let paths = pathsource.list_files(pkg)?;
+        let stub = !needed.contains(id);
+        let paths = if stub {
+            vec![pkg.manifest_path().to_path_buf()]
+        } else if opts.thin {
+            thin_paths(pkg, src, paths)
+        } else {
+            paths
+        };
         let mut map = BTreeMap::new();
         cp_sources(pkg, src, &paths, &dst, &mut map, &mut tmp_buf)
             .with_context(|| format!("failed to copy over vendored sources for: {}", id))?;
+        if stub {
+            write_stub_targets(pkg, src, &dst, &mut map)?;
+        }
 
         // Finally, emit the metadata about this package
         let json = serde_json::json!({
@@ -315,6 +346,130 @@ fn sync(
     Ok(VendorConfig { source: config })
 }
 
+/// Returns the packages in `resolve` which are needed to build the
+/// workspace members.
+///
+/// Without `--no-dev` or `--platform` this is every package in `resolve`.
+/// Otherwise the dependency graph is walked from the workspace members,
+/// skipping dev-dependency edges and edges for platforms that weren't asked
+/// for. The packages left out are still vendored as stubs, since Cargo needs
+/// their manifests to load the lock file.
+fn needed_packages(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    opts: &VendorOptions<'_>,
+) -> CargoResult<HashSet<PackageId>> {
+    if !opts.no_dev && opts.platforms.is_empty() {
+        return Ok(resolve.iter().collect());
+    }
+
+    let (requested_kinds, target_data) = if opts.platforms.is_empty() {
+        (Vec::new(), None)
+    } else {
+        let requested_kinds = CompileKind::from_requested_targets(ws.config(), &opts.platforms)?;
+        let target_data = RustcTargetData::new(ws, &requested_kinds)?;
+        (requested_kinds, Some(target_data))
+    };
+
+    let mut needed = HashSet::new();
+    let mut queue: Vec<_> = ws.members().map(|pkg| pkg.package_id()).collect();
+    while let Some(id) = queue.pop() {
+        if !needed.insert(id) {
+            continue;
+        }
+        for (dep_id, deps) in resolve.deps(id) {
+            let used = deps.iter().any(|dep| {
+                if opts.no_dev && dep.kind() == DepKind::Development {
+                    return false;
+                }
+                match &target_data {
+                    // Build scripts and proc-macros run on the host, which
+                    // is expected to be among the requested platforms.
+                    Some(target_data) => requested_kinds
+                        .iter()
+                        .any(|kind| target_data.dep_platform_activated(dep, *kind)),
+                    None => true,
+                }
+            });
+            if used {
+                queue.push(dep_id);
+            }
+        }
+    }
+    Ok(needed)
+}
+
+/// Whether the vendored sources with the `.cargo-checksum.json` at `cksum`
+/// were copied from the package with the `checksum` of the lock file.
+fn matches_lockfile(cksum: &Path, checksum: Option<&str>) -> bool {
+    #[derive(Deserialize)]
+    struct VendoredChecksum {
+        package: Option<String>,
+    }
+    paths::read(cksum)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<VendoredChecksum>(&contents).ok())
+        .map_or(false, |vendored| vendored.package.as_deref() == checksum)
+}
+
+/// Writes empty sources for the library and build script of a package which
+/// is vendored as a stub, so that its manifest still loads.
+fn write_stub_targets(
+    pkg: &Package,
+    src: &Path,
+    dst: &Path,
+    cksums: &mut BTreeMap<String, String>,
+) -> CargoResult<()> {
+    for target in pkg.targets() {
+        let contents = if target.is_lib() {
+            ""
+        } else if target.is_custom_build() {
+            "fn main() {}\n"
+        } else {
+            continue;
+        };
+        let Some(relative) = target
+            .src_path()
+            .path()
+            .and_then(|p| p.strip_prefix(src).ok())
+        else {
+            continue;
+        };
+        let path = relative
+            .iter()
+            .fold(dst.to_owned(), |acc, component| acc.join(&component));
+        paths::create_dir_all(path.parent().unwrap())?;
+        paths::write(&path, contents)?;
+        let mut cksum = Sha256::new();
+        cksum.update(contents.as_bytes());
+        cksums.insert(
+            relative.to_str().unwrap().replace("\\", "/"),
+            cksum.finish_hex(),
+        );
+    }
+    Ok(())
+}
+
+/// Removes the `tests` and `benches` directories from the files of `pkg`.
+///
+/// Cargo never builds the tests or benchmarks of dependencies. A directory is
+/// kept if the manifest declares a target of that kind without a `path`,
+/// since Cargo would then fail to load the vendored package.
+fn thin_paths(pkg: &Package, src: &Path, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let original = pkg.manifest().original();
+    let mut skip = Vec::new();
+    if !original.has_test_without_path() {
+        skip.push(src.join("tests"));
+    }
+    if !original.has_bench_without_path() {
+        skip.push(src.join("benches"));
+    }
+    paths
+        .into_iter()
+        .filter(|p| !skip.iter().any(|dir| p.starts_with(dir)))
+        .collect()
+}
+
 fn cp_sources(
     pkg: &Package,
     src: &Path,
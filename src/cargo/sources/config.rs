@@ -5,7 +5,7 @@
 //! sources to one another via the `replace-with` key in `.cargo/config`.
 
 use crate::core::{GitReference, PackageId, Source, SourceId};
-use crate::sources::{ReplacedSource, CRATES_IO_REGISTRY};
+use crate::sources::{mirror, ReplacedSource, CRATES_IO_REGISTRY};
 use crate::util::config::{self, ConfigRelativePath, OptValue};
 use crate::util::errors::CargoResult;
 use crate::util::{Config, IntoUrl};
@@ -46,6 +46,8 @@ struct SourceConfigDef {
     tag: OptValue<String>,
     /// The git revision.
     rev: OptValue<String>,
+    /// Whether to use this source when its replacement is unreachable.
+    fallback: OptValue<bool>,
 }
 
 /// Configuration for a particular source, found in TOML looking like:
@@ -67,6 +69,10 @@ struct SourceConfig {
     /// this configuration key was defined (such as the `.cargo/config` path
     /// or the environment variable name).
     replace_with: Option<(String, String)>,
+
+    /// Whether this source is used instead of its replacement when the
+    /// replacement is unreachable, with `-Zmirror-fallback`.
+    fallback: bool,
 }
 
 impl<'cfg> SourceConfigMap<'cfg> {
@@ -96,6 +102,7 @@ impl<'cfg> SourceConfigMap<'cfg> {
             SourceConfig {
                 id: SourceId::crates_io(config)?,
                 replace_with: None,
+                fallback: false,
             },
         )?;
         if SourceId::crates_io_is_sparse(config)? {
@@ -104,6 +111,7 @@ impl<'cfg> SourceConfigMap<'cfg> {
                 SourceConfig {
                     id: SourceId::crates_io_maybe_sparse_http(config)?,
                     replace_with: None,
+                    fallback: false,
                 },
             )?;
         }
@@ -113,6 +121,7 @@ impl<'cfg> SourceConfigMap<'cfg> {
                 SourceConfig {
                     id: SourceId::for_alt_registry(&url.parse()?, CRATES_IO_REGISTRY)?,
                     replace_with: None,
+                    fallback: false,
                 },
             )?;
         }
@@ -134,20 +143,93 @@ impl<'cfg> SourceConfigMap<'cfg> {
     ) -> CargoResult<Box<dyn Source + 'cfg>> {
         debug!("loading: {}", id);
 
+        let Some((orig_name, name, new_id)) = self.replacement(id)? else {
+            return id.load(self.config, yanked_whitelist);
+        };
+        if self.cfgs[orig_name].fallback
+            && self.config.cli_unstable().mirror_fallback
+            && !self.config.offline()
+            && !self.is_reachable(orig_name, name, new_id)?
+        {
+            return id.load(self.config, yanked_whitelist);
+        }
+
+        let new_src = new_id.load(
+            self.config,
+            &yanked_whitelist
+                .iter()
+                .map(|p| p.map_source(id, new_id))
+                .collect(),
+        )?;
+        let old_src = id.load(self.config, yanked_whitelist)?;
+        if !new_src.supports_checksums() && old_src.supports_checksums() {
+            bail!(
+                "\
+cannot replace `{orig}` with `{name}`, the source `{orig}` supports \
+checksums, but `{name}` does not
+
+a lock file compatible with `{orig}` cannot be generated in this situation
+",
+                orig = orig_name,
+                name = name
+            );
+        }
+
+        if old_src.requires_precise() && id.precise().is_none() {
+            bail!(
+                "\
+the source {orig} requires a lock file to be present first before it can be
+used against vendored source code
+
+remove the source replacement configuration, generate a lock file, and then
+restore the source replacement configuration to continue the build
+",
+                orig = orig_name
+            );
+        }
+
+        Ok(Box::new(ReplacedSource::new(id, new_id, new_src)))
+    }
+
+    /// Every source replaced with another one, as its name, the name of its
+    /// replacement, and the [`SourceId`] of the replacement.
+    pub fn replacements(&self) -> CargoResult<Vec<(&str, &str, SourceId)>> {
+        let mut replacements = Vec::new();
+        for cfg in self.cfgs.values() {
+            if cfg.replace_with.is_none() {
+                continue;
+            }
+            if let Some(replacement) = self.replacement(cfg.id)? {
+                replacements.push(replacement);
+            }
+        }
+        replacements.sort();
+        Ok(replacements)
+    }
+
+    /// Follows the `replace-with` keys from the source `id`, to the source
+    /// it is eventually replaced with. Returns the name of `id`, the name of
+    /// the replacement and its [`SourceId`], or `None` if `id` isn't
+    /// replaced.
+    fn replacement(&self, id: SourceId) -> CargoResult<Option<(&str, &str, SourceId)>> {
         let mut name = match self.id2name.get(&id) {
             Some(name) => name,
-            None => return id.load(self.config, yanked_whitelist),
+            None => return Ok(None),
         };
         let mut cfg_loc = "";
         let orig_name = name;
-        let new_id = loop {
+        loop {
             let cfg = match self.cfgs.get(name) {
                 Some(cfg) => cfg,
                 None => {
                     // Attempt to interpret the source name as an alt registry name
                     if let Ok(alt_id) = SourceId::alt_registry(self.config, name) {
                         debug!("following pointer to registry {}", name);
-                        break alt_id.with_precise(id.precise().map(str::to_string));
+                        return Ok(Some((
+                            orig_name.as_str(),
+                            name.as_str(),
+                            alt_id.with_precise(id.precise().map(str::to_string)),
+                        )));
                     }
                     bail!(
                         "could not find a configured source with the \
@@ -164,9 +246,13 @@ impl<'cfg> SourceConfigMap<'cfg> {
                     name = s;
                     cfg_loc = c;
                 }
-                None if id == cfg.id => return id.load(self.config, yanked_whitelist),
+                None if id == cfg.id => return Ok(None),
                 None => {
-                    break cfg.id.with_precise(id.precise().map(|s| s.to_string()));
+                    return Ok(Some((
+                        orig_name.as_str(),
+                        name.as_str(),
+                        cfg.id.with_precise(id.precise().map(|s| s.to_string())),
+                    )));
                 }
             }
             debug!("following pointer to {}", name);
@@ -179,43 +265,28 @@ impl<'cfg> SourceConfigMap<'cfg> {
                     cfg_loc
                 )
             }
-        };
-
-        let new_src = new_id.load(
-            self.config,
-            &yanked_whitelist
-                .iter()
-                .map(|p| p.map_source(id, new_id))
-                .collect(),
-        )?;
-        let old_src = id.load(self.config, yanked_whitelist)?;
-        if !new_src.supports_checksums() && old_src.supports_checksums() {
-            bail!(
-                "\
-cannot replace `{orig}` with `{name}`, the source `{orig}` supports \
-checksums, but `{name}` does not
-
-a lock file compatible with `{orig}` cannot be generated in this situation
-",
-                orig = orig_name,
-                name = name
-            );
         }
+    }
 
-        if old_src.requires_precise() && id.precise().is_none() {
-            bail!(
-                "\
-the source {orig} requires a lock file to be present first before it can be
-used against vendored source code
-
-remove the source replacement configuration, generate a lock file, and then
-restore the source replacement configuration to continue the build
-",
-                orig = orig_name
-            );
+    /// Checks whether `new_id`, named `name`, which replaces the source
+    /// named `orig_name`, can be reached, warning that `orig_name` is used
+    /// instead if not.
+    fn is_reachable(&self, orig_name: &str, name: &str, new_id: SourceId) -> CargoResult<bool> {
+        if let Some(reachable) = self.config.mirror_status().get(&new_id) {
+            return Ok(*reachable);
         }
-
-        Ok(Box::new(ReplacedSource::new(id, new_id, new_src)))
+        let reachable = match mirror::check(self.config, new_id) {
+            Ok(()) => true,
+            Err(e) => {
+                self.config.shell().warn(format!(
+                    "source `{name}` replacing `{orig_name}` is unreachable, \
+                     falling back to `{orig_name}`: {e:#}"
+                ))?;
+                false
+            }
+        };
+        self.config.mirror_status().insert(new_id, reachable);
+        Ok(reachable)
     }
 
     /// Adds a source config with an associated name.
@@ -304,11 +375,14 @@ restore the source replacement configuration to continue the build
             .replace_with
             .map(|val| (val.val, val.definition.to_string()));
 
+        let fallback = def.fallback.map_or(false, |val| val.val);
+
         self.add(
             &name,
             SourceConfig {
                 id: src,
                 replace_with,
+                fallback,
             },
         )?;
 
@@ -5,7 +5,7 @@ use crate::core::GitReference;
 use crate::core::{Dependency, Package, PackageId, Summary};
 use crate::sources::git::utils::GitRemote;
 use crate::sources::PathSource;
-use crate::util::errors::CargoResult;
+use crate::util::errors::{CargoResult, OfflineError};
 use crate::util::hex::short_hash;
 use crate::util::Config;
 use anyhow::Context;
@@ -246,10 +246,11 @@ impl<'cfg> Source for GitSource<'cfg> {
             // doesn't have it.
             (locked_rev, db) => {
                 if self.config.offline() {
-                    anyhow::bail!(
-                        "can't checkout from '{}': you are in the offline mode (--offline)",
-                        self.remote.url()
-                    );
+                    let what = match locked_rev {
+                        Some(rev) => format!("revision `{}` of the git repository", rev),
+                        None => "the git repository".to_string(),
+                    };
+                    return Err(OfflineError::new(what, self.remote.url().as_str()).into());
                 }
                 if !self.quiet {
                     self.config.shell().status(
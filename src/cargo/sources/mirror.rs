@@ -0,0 +1,77 @@
+//! Health checks of the sources replacing others, such as mirrors of
+//! crates.io.
+//!
+//! A source defined with `fallback = true` in its `[source]` table is used
+//! instead of its replacement when the replacement can't be reached, with
+//! `-Zmirror-fallback`. `cargo config check-mirrors` runs the same check on
+//! every replacement.
+//!
+//! The check is a single request to the replacement, which only tells that
+//! it answers:
+//!
+//! * Sparse registries are asked for their `config.json`.
+//! * Git registries and repositories over HTTP are asked for their refs, like
+//!   the first request of a fetch.
+//! * Local registries, directories, and `file://` URLs must exist.
+//!
+//! Other URLs, such as `ssh://`, can't be checked without a full fetch, and
+//! are assumed to be reachable.
+
+use anyhow::{bail, Context as _};
+use tracing::debug;
+
+use crate::core::SourceId;
+use crate::util::network::http::http_handle;
+use crate::util::{CargoResult, Config};
+
+/// Checks that the source `id` can be reached.
+pub fn check(config: &Config, id: SourceId) -> CargoResult<()> {
+    let url = id.url();
+    let url = url.as_str().strip_prefix("sparse+").unwrap_or(url.as_str());
+    let url = url::Url::parse(url)?;
+    match url.scheme() {
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|()| anyhow::format_err!("invalid path in `{url}`"))?;
+            if !path.exists() {
+                bail!("`{}` does not exist", path.display());
+            }
+            return Ok(());
+        }
+        "http" | "https" => {}
+        scheme => {
+            debug!("cannot check `{scheme}` source {id}, assuming it is reachable");
+            return Ok(());
+        }
+    }
+
+    let probe = if id.is_sparse() {
+        let mut url = url.to_string();
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        url + "config.json"
+    } else {
+        format!(
+            "{}/info/refs?service=git-upload-pack",
+            url.as_str().trim_end_matches('/')
+        )
+    };
+
+    let mut handle = http_handle(config)?;
+    handle.get(true)?;
+    handle.url(&probe)?;
+    handle.follow_location(true)?;
+    // Only the status is needed.
+    handle.write_function(|buf| Ok(buf.len()))?;
+    handle
+        .perform()
+        .with_context(|| format!("failed to reach `{probe}`"))?;
+    let code = handle.response_code()?;
+    // Registries requiring authentication answer, but turn us away.
+    if !(200..300).contains(&code) && code != 401 {
+        bail!("`{probe}` answered with status {code}");
+    }
+    Ok(())
+}
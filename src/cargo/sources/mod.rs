@@ -14,7 +14,9 @@
 //!   redirecting operations on the original source to the replacement.
 //!
 //! This module also contains [`SourceConfigMap`], which is effectively the
-//! representation of the `[source.*]` value in Cargo configuration.
+//! representation of the `[source.*]` value in Cargo configuration, and
+//! [`SourceOverride`], which redirects single dependencies to a local
+//! directory before their source is loaded.
 //!
 //! [`Source`]: crate::core::Source
 //! [source replacement]: https://doc.rust-lang.org/nightly/cargo/reference/source-replacement.html
@@ -25,10 +27,13 @@ pub use self::git::GitSource;
 pub use self::path::PathSource;
 pub use self::registry::{RegistrySource, CRATES_IO_DOMAIN, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
 pub use self::replaced::ReplacedSource;
+pub use self::source_override::{ProcessSourceOverride, SourceOverride};
 
 pub mod config;
 pub mod directory;
 pub mod git;
+pub mod mirror;
 pub mod path;
 pub mod registry;
 pub mod replaced;
+pub mod source_override;
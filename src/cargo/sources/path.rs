@@ -119,16 +119,38 @@ impl<'cfg> PathSource<'cfg> {
     /// use other methods like `.gitignore`, `package.include`, or
     /// `package.exclude` to filter the list of files.
     pub fn list_files(&self, pkg: &Package) -> CargoResult<Vec<PathBuf>> {
-        self._list_files(pkg).with_context(|| {
+        self.list_files_with_reported(pkg).map(|(files, _)| files)
+    }
+
+    /// Like [`PathSource::list_files`], but also returns the paths worth
+    /// reporting to the user, along with why.
+    ///
+    /// Those are the paths left out despite matching the `include` and
+    /// `exclude` rules, and the symbolic links to directories outside of the
+    /// package, which are followed like any other directory, see
+    /// [`ReportReason::is_skipped`]. Symbolic links are followed as long as
+    /// they don't form a loop. Git submodules are listed like the rest of the
+    /// package, as long as they are checked out.
+    pub fn list_files_with_reported(
+        &self,
+        pkg: &Package,
+    ) -> CargoResult<(Vec<PathBuf>, Vec<ReportedPath>)> {
+        let mut reported = Vec::new();
+        let files = self._list_files(pkg, &mut reported).with_context(|| {
             format!(
                 "failed to determine list of files in {}",
                 pkg.root().display()
             )
-        })
+        })?;
+        Ok((files, reported))
     }
 
     /// See [`PathSource::list_files`].
-    fn _list_files(&self, pkg: &Package) -> CargoResult<Vec<PathBuf>> {
+    fn _list_files(
+        &self,
+        pkg: &Package,
+        reported: &mut Vec<ReportedPath>,
+    ) -> CargoResult<Vec<PathBuf>> {
         let root = pkg.root();
         let no_include_option = pkg.manifest().include().is_empty();
         let git_repo = if no_include_option {
@@ -191,10 +213,10 @@ impl<'cfg> PathSource<'cfg> {
         // Attempt Git-prepopulate only if no `include` (see rust-lang/cargo#4135).
         if no_include_option {
             if let Some(repo) = git_repo {
-                return self.list_files_git(pkg, &repo, &filter);
+                return self.list_files_git(pkg, &repo, &filter, reported);
             }
         }
-        self.list_files_walk(pkg, &filter)
+        self.list_files_walk(pkg, &filter, reported)
     }
 
     /// Returns `Some(git2::Repository)` if found sibling `Cargo.toml` and `.git`
@@ -250,6 +272,7 @@ impl<'cfg> PathSource<'cfg> {
         pkg: &Package,
         repo: &git2::Repository,
         filter: &dyn Fn(&Path, bool) -> bool,
+        reported: &mut Vec<ReportedPath>,
     ) -> CargoResult<Vec<PathBuf>> {
         warn!("list_files_git {}", pkg.package_id());
         let index = repo.index()?;
@@ -354,6 +377,12 @@ impl<'cfg> PathSource<'cfg> {
             // symlink points to a directory.
             let is_dir = is_dir.unwrap_or_else(|| file_path.is_dir());
             if is_dir {
+                if is_symlink_outside(&file_path, pkg_path) && filter(&file_path, is_dir) {
+                    reported.push(ReportedPath {
+                        path: file_path.clone(),
+                        reason: ReportReason::SymlinkOutsidePackage,
+                    });
+                }
                 warn!("  found submodule {}", file_path.display());
                 let rel = file_path.strip_prefix(root)?;
                 let rel = rel.to_str().ok_or_else(|| {
@@ -362,13 +391,25 @@ impl<'cfg> PathSource<'cfg> {
                 // Git submodules are currently only named through `/` path
                 // separators, explicitly not `\` which windows uses. Who knew?
                 let rel = rel.replace(r"\", "/");
-                match repo.find_submodule(&rel).and_then(|s| s.open()) {
-                    Ok(repo) => {
-                        let files = self.list_files_git(pkg, &repo, filter)?;
+                match repo.find_submodule(&rel).map(|s| s.open()) {
+                    Ok(Ok(repo)) => {
+                        let files = self.list_files_git(pkg, &repo, filter, reported)?;
                         ret.extend(files.into_iter());
                     }
+                    // The submodule isn't checked out, its directory is
+                    // usually empty.
+                    Ok(Err(..)) => {
+                        let found = ret.len();
+                        self.walk(pkg_path, &file_path, &mut ret, filter, reported)?;
+                        if ret.len() == found && filter(&file_path, is_dir) {
+                            reported.push(ReportedPath {
+                                path: file_path,
+                                reason: ReportReason::UninitializedSubmodule,
+                            });
+                        }
+                    }
                     Err(..) => {
-                        self.walk(&file_path, &mut ret, false, filter)?;
+                        self.walk(pkg_path, &file_path, &mut ret, filter, reported)?;
                     }
                 }
             } else if filter(&file_path, is_dir) {
@@ -409,20 +450,27 @@ impl<'cfg> PathSource<'cfg> {
         &self,
         pkg: &Package,
         filter: &dyn Fn(&Path, bool) -> bool,
+        reported: &mut Vec<ReportedPath>,
     ) -> CargoResult<Vec<PathBuf>> {
         let mut ret = Vec::new();
-        self.walk(pkg.root(), &mut ret, true, filter)?;
+        self.walk(pkg.root(), pkg.root(), &mut ret, filter, reported)?;
         Ok(ret)
     }
 
     /// Helper recursive function for [`PathSource::list_files_walk`].
+    ///
+    /// Walks `path`, which is either the root of the package `pkg_root` or a
+    /// directory inside of it.
     fn walk(
         &self,
+        pkg_root: &Path,
         path: &Path,
         ret: &mut Vec<PathBuf>,
-        is_root: bool,
         filter: &dyn Fn(&Path, bool) -> bool,
+        reported: &mut Vec<ReportedPath>,
     ) -> CargoResult<()> {
+        let is_root = path == pkg_root;
+        let mut outside = Vec::new();
         let walkdir = WalkDir::new(path)
             .follow_links(true)
             .into_iter()
@@ -439,6 +487,13 @@ impl<'cfg> PathSource<'cfg> {
                     return true;
                 }
 
+                if entry.path_is_symlink() && is_symlink_outside(path, pkg_root) {
+                    outside.push(ReportedPath {
+                        path: path.to_path_buf(),
+                        reason: ReportReason::SymlinkOutsidePackage,
+                    });
+                }
+
                 // Don't recurse into any sub-packages that we have.
                 if !at_root && path.join("Cargo.toml").exists() {
                     return false;
@@ -462,6 +517,12 @@ impl<'cfg> PathSource<'cfg> {
                     }
                 }
                 Err(err) if err.loop_ancestor().is_some() => {
+                    if let Some(path) = err.path() {
+                        reported.push(ReportedPath {
+                            path: path.to_path_buf(),
+                            reason: ReportReason::FilesystemLoop,
+                        });
+                    }
                     self.config.shell().warn(err)?;
                 }
                 Err(err) => match err.path() {
@@ -477,6 +538,7 @@ impl<'cfg> PathSource<'cfg> {
                 },
             }
         }
+        reported.extend(outside);
 
         Ok(())
     }
@@ -530,6 +592,60 @@ impl<'cfg> PathSource<'cfg> {
     }
 }
 
+/// A path reported by [`PathSource::list_files_with_reported`].
+#[derive(Debug)]
+pub struct ReportedPath {
+    pub path: PathBuf,
+    pub reason: ReportReason,
+}
+
+/// Why a [`ReportedPath`] was reported, which tells whether it was left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportReason {
+    /// A symbolic link to a directory outside of the package. Unlike the
+    /// other reasons, the files of the directory are still listed.
+    SymlinkOutsidePackage,
+    /// A symbolic link to one of its parent directories.
+    FilesystemLoop,
+    /// A git submodule which isn't checked out.
+    UninitializedSubmodule,
+}
+
+impl ReportReason {
+    /// Whether the path was actually left out, rather than only reported.
+    pub fn is_skipped(self) -> bool {
+        self != ReportReason::SymlinkOutsidePackage
+    }
+}
+
+impl fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportReason::SymlinkOutsidePackage => {
+                f.write_str("symbolic link to a directory outside of the package")
+            }
+            ReportReason::FilesystemLoop => {
+                f.write_str("symbolic link to one of its parent directories")
+            }
+            ReportReason::UninitializedSubmodule => f.write_str("git submodule is not checked out"),
+        }
+    }
+}
+
+/// Whether `path` is a symbolic link to a directory outside of `pkg_root`.
+///
+/// Symbolic links to files are not reported, as these are commonly used to
+/// share files like licenses between the packages of a workspace.
+fn is_symlink_outside(path: &Path, pkg_root: &Path) -> bool {
+    if !path.is_symlink() {
+        return false;
+    }
+    match (path.canonicalize(), pkg_root.canonicalize()) {
+        (Ok(target), Ok(root)) => target.is_dir() && !target.starts_with(root),
+        _ => false,
+    }
+}
+
 impl<'cfg> Debug for PathSource<'cfg> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "the paths source")
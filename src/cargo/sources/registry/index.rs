@@ -97,11 +97,13 @@ use cargo_util::{paths, registry::make_dep_path};
 use semver::Version;
 use serde::Deserialize;
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::BTreeMap;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::ErrorKind;
 use std::path::Path;
+use std::rc::Rc;
 use std::str;
 use std::task::{ready, Poll};
 use tracing::{debug, info};
@@ -143,6 +145,9 @@ pub struct RegistryIndex<'cfg> {
     summaries_cache: HashMap<InternedString, Summaries>,
     /// [`Config`] reference for convenience.
     config: &'cfg Config,
+    /// Whether we've already warned about index entries with a schema
+    /// version newer than [`INDEX_V_MAX`], so it is only shown once.
+    warned_newer_schema: Rc<Cell<bool>>,
 }
 
 /// An internal cache of summaries for a particular package.
@@ -309,7 +314,7 @@ pub struct IndexPackage<'a> {
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version `1`. Entries with unknown
-    /// versions are ignored.
+    /// versions are ignored, with a single warning per registry.
     ///
     /// Version `2` schema adds the `features2` field.
     ///
@@ -370,6 +375,7 @@ impl<'cfg> RegistryIndex<'cfg> {
             path: path.clone(),
             summaries_cache: HashMap::new(),
             config,
+            warned_newer_schema: Rc::new(Cell::new(false)),
         }
     }
 
@@ -408,6 +414,8 @@ impl<'cfg> RegistryIndex<'cfg> {
         'a: 'b,
     {
         let source_id = self.source_id;
+        let config = self.config;
+        let warned_newer_schema = Rc::clone(&self.warned_newer_schema);
 
         // First up parse what summaries we have available.
         let name = InternedString::new(name);
@@ -439,6 +447,17 @@ impl<'cfg> RegistryIndex<'cfg> {
                         is.summary.name(),
                         is.summary.version()
                     );
+                    if !warned_newer_schema.replace(true) {
+                        let _ = config.shell().warn(format!(
+                            "some index entries of {} use a newer schema than this \
+                             version of Cargo understands and were ignored, \
+                             such as `{} v{}`\n\
+                             Updating Cargo may make more versions available.",
+                            source_id,
+                            is.summary.name(),
+                            is.summary.version()
+                        ));
+                    }
                     false
                 } else {
                     true
@@ -448,6 +448,27 @@ fn short_name(id: SourceId, is_shallow: bool) -> String {
     name
 }
 
+/// The path of the downloaded `.crate` file of `pkg`, if it comes from a
+/// remote registry.
+///
+/// Registry packages are unpacked in `registry/src/<registry>/`, and their
+/// `.crate` file is kept in `registry/cache/<registry>/`. The file may have
+/// been removed since the package was unpacked.
+pub fn cached_crate_path(config: &Config, pkg: &Package) -> Option<PathBuf> {
+    let id = pkg.package_id();
+    if !id.source_id().is_remote_registry() {
+        return None;
+    }
+    let registry = pkg.root().parent()?.file_name()?;
+    Some(
+        config
+            .registry_cache_path()
+            .join(registry)
+            .into_path_unlocked()
+            .join(id.tarball_name()),
+    )
+}
+
 impl<'cfg> RegistrySource<'cfg> {
     /// Creates a [`Source`] of a "remote" registry.
     /// It could be either an HTTP-based [`http_remote::HttpRegistry`] or
@@ -528,6 +549,22 @@ impl<'cfg> RegistrySource<'cfg> {
         self.ops.config()
     }
 
+    /// Lists the versions of `name` matching `req` in the index, including
+    /// the yanked ones, along with whether each of them is yanked.
+    pub fn versions(
+        &mut self,
+        name: &str,
+        req: &OptVersionReq,
+    ) -> Poll<CargoResult<Vec<(semver::Version, bool)>>> {
+        self.index
+            .summaries(name, req, &mut *self.ops)
+            .map_ok(|summaries| {
+                summaries
+                    .map(|s| (s.summary.version().clone(), s.yanked))
+                    .collect()
+            })
+    }
+
     /// Unpacks a downloaded package into a location where it's ready to be
     /// compiled.
     ///
@@ -0,0 +1,128 @@
+//! Redirecting dependencies to local directories, for `-Zsource-override`.
+//!
+//! [`SourceOverride`] is the extension point consulted by the
+//! [`PackageRegistry`] before loading the source of a dependency, and
+//! [`ProcessSourceOverride`] implements it by asking the external program
+//! configured in `source-override.process`.
+//!
+//! [`PackageRegistry`]: crate::core::registry::PackageRegistry
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context as _};
+use cargo_util::ProcessBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::core::Dependency;
+use crate::util::config::PathAndArgs;
+use crate::util::errors::CargoResult;
+use crate::util::Config;
+
+/// The version of the protocol spoken with `source-override.process`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Decides where to load a dependency from instead of its own source.
+///
+/// The directory returned for a dependency is loaded as a path source. The
+/// [`PackageRegistry`] then checks that it contains a package with the name
+/// of the dependency, in a version matching its requirement, and fails
+/// otherwise.
+///
+/// [`PackageRegistry`]: crate::core::registry::PackageRegistry
+pub trait SourceOverride {
+    /// Returns the directory to load `dep` from, or `None` to load it from
+    /// its own source as usual.
+    fn redirect(&mut self, dep: &Dependency) -> CargoResult<Option<PathBuf>>;
+}
+
+/// A [`SourceOverride`] asking the program configured in
+/// `source-override.process`.
+///
+/// The program is run once for each dependency. It gets a [`Request`] as a
+/// single line of JSON on stdin, and must print a [`Response`] as JSON on
+/// stdout.
+pub struct ProcessSourceOverride {
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+/// The dependency the program is asked about.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Request<'a> {
+    v: u32,
+    name: &'a str,
+    source: String,
+    version_req: String,
+}
+
+/// The answer of the program. A missing `path` leaves the dependency alone.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Response {
+    path: Option<PathBuf>,
+}
+
+impl ProcessSourceOverride {
+    /// Creates the override from the `source-override.process` config,
+    /// if it is set and `-Zsource-override` is enabled.
+    pub fn from_config(config: &Config) -> CargoResult<Option<ProcessSourceOverride>> {
+        if !config.cli_unstable().source_override {
+            return Ok(None);
+        }
+        let Some(process) = config.get::<Option<PathAndArgs>>("source-override.process")? else {
+            return Ok(None);
+        };
+        Ok(Some(ProcessSourceOverride {
+            program: process.path.resolve_program(config),
+            args: process.args,
+        }))
+    }
+}
+
+impl SourceOverride for ProcessSourceOverride {
+    fn redirect(&mut self, dep: &Dependency) -> CargoResult<Option<PathBuf>> {
+        let request = serde_json::to_string(&Request {
+            v: PROTOCOL_VERSION,
+            name: &dep.package_name(),
+            source: dep.source_id().as_url().to_string(),
+            version_req: dep.version_req().to_string(),
+        })?;
+        tracing::debug!("source-override < {request}");
+        let output = ProcessBuilder::new(&self.program)
+            .args(&self.args)
+            .stdin(format!("{request}\n"))
+            .exec_with_output()
+            .with_context(|| {
+                format!(
+                    "source override process `{}` failed for dependency `{}`",
+                    self.program.display(),
+                    dep.package_name()
+                )
+            })?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        tracing::debug!("source-override > {}", stdout.trim_end());
+        let response: Response = serde_json::from_str(&stdout).with_context(|| {
+            format!(
+                "source override process `{}` returned an invalid response \
+                 for dependency `{}`: `{}`",
+                self.program.display(),
+                dep.package_name(),
+                stdout.trim_end()
+            )
+        })?;
+        let Some(path) = response.path else {
+            return Ok(None);
+        };
+        if !path.is_absolute() {
+            bail!(
+                "source override process `{}` redirected dependency `{}` to `{}`, \
+                 but the path must be absolute",
+                self.program.display(),
+                dep.package_name(),
+                path.display()
+            );
+        }
+        Ok(Some(path))
+    }
+}
@@ -0,0 +1,138 @@
+//! Notifying shared build caches of `cargo clean`, for `-Zcache-backends`.
+//!
+//! Artifact caches shared between workspaces, or compiler wrappers like
+//! `sccache`, keep entries which `cargo clean` doesn't know about. A cache
+//! backend configured in `cache-backends.<name>.process` is told about each
+//! `cargo clean`, so that it can invalidate or verify the entries of the
+//! workspace, and purge them with `cargo clean --cache-too`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::ProcessBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::util::config::PathAndArgs;
+use crate::util::errors::CargoResult;
+use crate::util::Config;
+
+/// The version of the protocol spoken with `cache-backends.<name>.process`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// What `cargo clean` removed.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CleanEvent<'a> {
+    pub workspace_root: &'a Path,
+    pub target_dir: &'a Path,
+    /// The `--package` specs, or nothing if everything was removed.
+    pub packages: &'a [String],
+    /// The profile whose artifacts were removed, or `None` for all of them.
+    pub profile: Option<&'a str>,
+    pub targets: &'a [String],
+    /// Whether only documentation was removed.
+    pub doc: bool,
+    /// Whether the entries of the workspace should be purged from the cache,
+    /// with `--cache-too`.
+    pub purge: bool,
+}
+
+/// A build cache which is told about `cargo clean`.
+pub trait CacheBackend {
+    /// The name of the backend in the configuration.
+    fn name(&self) -> &str;
+
+    /// Notifies the backend that artifacts were cleaned, and returns the
+    /// number of entries it invalidated or purged, if it knows.
+    fn on_clean(&mut self, event: &CleanEvent<'_>) -> CargoResult<Option<u64>>;
+}
+
+/// The `cache-backends.<name>` table of the configuration.
+#[derive(Deserialize)]
+struct CacheBackendConfig {
+    process: PathAndArgs,
+}
+
+/// A [`CacheBackend`] running the program configured in
+/// `cache-backends.<name>.process`.
+///
+/// The program is run once for each event. It gets a [`Request`] as a single
+/// line of JSON on stdin, and must print a [`Response`] as JSON on stdout.
+pub struct ProcessCacheBackend {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+/// The event sent to the program.
+#[derive(Serialize)]
+struct Request<'a> {
+    v: u32,
+    event: &'static str,
+    #[serde(flatten)]
+    clean: &'a CleanEvent<'a>,
+}
+
+/// The answer of the program. Unknown fields are ignored, so that backends
+/// can answer more than Cargo knows about.
+#[derive(Deserialize)]
+struct Response {
+    removed: Option<u64>,
+}
+
+impl ProcessCacheBackend {
+    /// Creates the backends of the `cache-backends` config, if
+    /// `-Zcache-backends` is enabled.
+    pub fn from_config(config: &Config) -> CargoResult<Vec<ProcessCacheBackend>> {
+        if !config.cli_unstable().cache_backends {
+            return Ok(Vec::new());
+        }
+        let backends = config
+            .get::<Option<BTreeMap<String, CacheBackendConfig>>>("cache-backends")?
+            .unwrap_or_default();
+        Ok(backends
+            .into_iter()
+            .map(|(name, backend)| ProcessCacheBackend {
+                name,
+                program: backend.process.path.resolve_program(config),
+                args: backend.process.args,
+            })
+            .collect())
+    }
+}
+
+impl CacheBackend for ProcessCacheBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_clean(&mut self, event: &CleanEvent<'_>) -> CargoResult<Option<u64>> {
+        let request = serde_json::to_string(&Request {
+            v: PROTOCOL_VERSION,
+            event: "clean",
+            clean: event,
+        })?;
+        tracing::debug!("cache-backends.{} < {request}", self.name);
+        let output = ProcessBuilder::new(&self.program)
+            .args(&self.args)
+            .stdin(format!("{request}\n"))
+            .exec_with_output()
+            .with_context(|| {
+                format!(
+                    "cache backend `{}` failed to process `cargo clean`",
+                    self.name
+                )
+            })?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        tracing::debug!("cache-backends.{} > {}", self.name, stdout.trim_end());
+        let response: Response = serde_json::from_str(&stdout).with_context(|| {
+            format!(
+                "cache backend `{}` returned an invalid response: `{}`",
+                self.name,
+                stdout.trim_end()
+            )
+        })?;
+        Ok(response.removed)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::compiler::{BuildConfig, MessageFormat, TimingOutput};
+use crate::core::compiler::{BuildConfig, CompileKind, CompileTarget, MessageFormat, TimingOutput};
 use crate::core::resolver::CliFeatures;
 use crate::core::{Edition, Workspace};
 use crate::ops::{CompileFilter, CompileOptions, NewOptions, Packages, VersionControl};
@@ -185,6 +185,14 @@ pub trait CommandExt: Sized {
             )
             .help_heading(heading::FEATURE_SELECTION),
         )
+        ._arg(
+            opt(
+                "feature-set",
+                "Activate a feature set of `[workspace.feature-sets]` (unstable)",
+            )
+            .value_name("NAME")
+            .help_heading(heading::FEATURE_SELECTION),
+        )
     }
 
     fn arg_release(self, release: &'static str) -> Self {
@@ -211,6 +219,17 @@ pub trait CommandExt: Sized {
         self._arg(multi_opt("target", "TRIPLE", target).help_heading(heading::COMPILATION_OPTIONS))
     }
 
+    fn arg_proc_macro_host(self) -> Self {
+        self._arg(
+            opt(
+                "proc-macro-host",
+                "Build proc-macros and build scripts for the triple (unstable)",
+            )
+            .value_name("TRIPLE")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
     fn arg_target_dir(self) -> Self {
         self._arg(
             opt("target-dir", "Directory for all generated artifacts")
@@ -229,11 +248,17 @@ pub trait CommandExt: Sized {
 
     fn arg_message_format(self) -> Self {
         self._arg(multi_opt("message-format", "FMT", "Error format"))
+            ._arg(flag(
+                "short-errors",
+                "Show short errors for non-member dependencies (unstable)",
+            ))
     }
 
     fn arg_build_plan(self) -> Self {
         self._arg(
-            flag("build-plan", "Output the build plan in JSON (unstable)")
+            optional_opt("build-plan", "Output the build plan in JSON (unstable)")
+                .value_name("VERSION")
+                .require_equals(true)
                 .help_heading(heading::COMPILATION_OPTIONS),
         )
     }
@@ -271,6 +296,10 @@ pub trait CommandExt: Sized {
             )
             .value_name("NAME"),
         )
+        ._arg(flag(
+            "add-to-workspace",
+            "Add the package to the members of the enclosing workspace (unstable)",
+        ))
     }
 
     fn arg_index(self) -> Self {
@@ -295,6 +324,10 @@ pub trait CommandExt: Sized {
         ))
     }
 
+    fn arg_args_file(self, help: &'static str) -> Self {
+        self._arg(opt("args-file", help).value_name("PATH"))
+    }
+
     fn arg_quiet(self) -> Self {
         self._arg(flag("quiet", "Do not print cargo log messages").short('q'))
     }
@@ -586,7 +619,33 @@ pub trait ArgMatchesExt {
         )?;
         build_config.message_format = message_format.unwrap_or(MessageFormat::Human);
         build_config.requested_profile = self.get_profile_name(config, "dev", profile_checking)?;
-        build_config.build_plan = self.flag("build-plan");
+        if self._contains("build-plan") {
+            build_config.build_plan = true;
+            build_config.build_plan_version = match self._value_of("build-plan") {
+                None | Some("1") => 1,
+                Some("2") => 2,
+                Some(s) => bail!("invalid build plan version `{}`, expected `1` or `2`", s),
+            };
+        }
+        if let Some(host) = self._value_of("proc-macro-host") {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--proc-macro-host")?;
+            if build_config
+                .requested_kinds
+                .iter()
+                .any(CompileKind::is_host)
+            {
+                bail!("`--proc-macro-host` requires `--target`");
+            }
+            build_config.proc_macro_host = Some(CompileTarget::new(host)?);
+        }
+        if self.flag("short-errors") {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--short-errors")?;
+            build_config.short_dep_errors = true;
+        }
         build_config.unit_graph = self.flag("unit-graph");
         build_config.future_incompat_report = self.flag("future-incompat-report");
 
@@ -635,7 +694,7 @@ pub trait ArgMatchesExt {
 
         let opts = CompileOptions {
             build_config,
-            cli_features: self.cli_features()?,
+            cli_features: self.cli_features(workspace)?,
             spec,
             filter: CompileFilter::from_raw_arguments(
                 self.flag("lib"),
@@ -671,9 +730,21 @@ pub trait ArgMatchesExt {
         Ok(opts)
     }
 
-    fn cli_features(&self) -> CargoResult<CliFeatures> {
+    /// The features selected on the command line, with the ones of
+    /// `--feature-set` looked up in the workspace `ws`.
+    fn cli_features(&self, ws: Option<&Workspace<'_>>) -> CargoResult<CliFeatures> {
+        let mut features = self._values_of("features");
+        if let Some(name) = self._value_of("feature-set") {
+            let Some(ws) = ws else {
+                bail!("`--feature-set` can only be used in a workspace");
+            };
+            ws.config()
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--feature-set")?;
+            features.extend(ws.feature_set(name)?);
+        }
         CliFeatures::from_command_line(
-            &self._values_of("features"),
+            &features,
             self.flag("all-features"),
             !self.flag("no-default-features"),
         )
@@ -704,7 +775,7 @@ pub trait ArgMatchesExt {
             "none" => VersionControl::NoVcs,
             vcs => panic!("Impossible vcs: {:?}", vcs),
         });
-        NewOptions::new(
+        let mut opts = NewOptions::new(
             vcs,
             self.flag("bin"),
             self.flag("lib"),
@@ -712,7 +783,14 @@ pub trait ArgMatchesExt {
             self._value_of("name").map(|s| s.to_string()),
             self._value_of("edition").map(|s| s.to_string()),
             self.registry(config)?,
-        )
+        )?;
+        if self.flag("add-to-workspace") {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--add-to-workspace")?;
+            opts.add_to_workspace = true;
+        }
+        Ok(opts)
     }
 
     fn registry(&self, config: &Config) -> CargoResult<Option<String>> {
@@ -768,6 +846,22 @@ pub trait ArgMatchesExt {
         Ok(())
     }
 
+    /// Returns the arguments forwarded to the binary run by `cargo run`,
+    /// `cargo test`, or `cargo bench`: the lines of `--args-file`, one
+    /// argument per line, followed by the arguments given after `--`.
+    fn forwarded_args(&self, config: &Config) -> CargoResult<Vec<OsString>> {
+        let mut forwarded = Vec::new();
+        if let Some(path) = self.value_of_path("args-file", config) {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt_untracked("--args-file")?;
+            let contents = paths::read(&path)?;
+            forwarded.extend(contents.lines().map(OsString::from));
+        }
+        forwarded.extend(self._values_of_os("args"));
+        Ok(forwarded)
+    }
+
     fn is_present_with_zero_values(&self, name: &str) -> bool {
         self._contains(name) && self._value_of(name).is_none()
     }
@@ -57,20 +57,24 @@ use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::fs::{self, File};
+use std::io;
 use std::io::prelude::*;
-use std::io::{self, SeekFrom};
 use std::mem;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::Once;
 use std::time::Instant;
 
 use self::ConfigValue as CV;
 use crate::core::compiler::rustdoc::RustdocExternMap;
-use crate::core::shell::Verbosity;
-use crate::core::{features, CliUnstable, Shell, SourceId, Workspace, WorkspaceRootConfig};
+use crate::core::shell::{OutputCategory, Verbosity};
+use crate::core::{
+    features, CliUnstable, PackageId, Shell, SourceId, Workspace, WorkspaceRootConfig,
+};
 use crate::ops::RegistryCredentialConfig;
 use crate::util::errors::CargoResult;
+use crate::util::invocation_log::{self, InvocationLog};
 use crate::util::network::http::configure_http_handle;
 use crate::util::network::http::http_handle;
 use crate::util::toml as cargo_toml;
@@ -160,6 +164,9 @@ pub struct CredentialCacheValue {
 pub struct Config {
     /// The location of the user's Cargo home directory. OS-dependent.
     home_path: Filesystem,
+    /// A writable overlay for the caches normally kept in `home_path`, used
+    /// when `CARGO_HOME` is read-only (`-Zread-only-sources`).
+    cache_home_path: Option<Filesystem>,
     /// Information about how to write messages to the shell
     shell: RefCell<Shell>,
     /// A collection of configuration options
@@ -201,12 +208,17 @@ pub struct Config {
     cache_rustc_info: bool,
     /// Creation time of this config, used to output the total build time
     creation_time: Instant,
+    /// Structured log of this invocation, enabled with `-Zlog-file`.
+    invocation_log: Option<Rc<InvocationLog>>,
     /// Target Directory via resolved Cli parameter
     target_dir: Option<Filesystem>,
     /// Environment variable snapshot.
     env: Env,
     /// Tracks which sources have been updated to avoid multiple updates.
     updated_sources: LazyCell<RefCell<HashSet<SourceId>>>,
+    /// Whether the replacements of sources with a fallback were reachable,
+    /// to only check them once.
+    mirror_status: LazyCell<RefCell<HashMap<SourceId, bool>>>,
     /// Cache of credentials from configuration or credential providers.
     /// Maps from url to credential value.
     credential_cache: LazyCell<RefCell<HashMap<CanonicalUrl, CredentialCacheValue>>>,
@@ -223,6 +235,12 @@ pub struct Config {
     target_cfgs: LazyCell<Vec<(String, TargetCfgConfig)>>,
     doc_extern_map: LazyCell<RustdocExternMap>,
     progress_config: ProgressConfig,
+    /// Whether to print a summary at the end of builds, from `-Zbuild-summary`
+    /// and `term.summary`.
+    build_summary: bool,
+    /// How paths are rendered in messages, from `-Zterm-paths`, `term.paths`
+    /// and `--verbose`.
+    path_style: PathStyle,
     env_config: LazyCell<EnvConfig>,
     /// This should be false if:
     /// - this is an artifact of the rustc distribution process for "stable" or for "beta"
@@ -274,6 +292,7 @@ impl Config {
 
         Config {
             home_path: Filesystem::new(homedir),
+            cache_home_path: None,
             shell: RefCell::new(shell),
             cwd,
             search_stop_path: None,
@@ -299,9 +318,11 @@ impl Config {
             crates_io_source_id: LazyCell::new(),
             cache_rustc_info,
             creation_time: Instant::now(),
+            invocation_log: None,
             target_dir: None,
             env,
             updated_sources: LazyCell::new(),
+            mirror_status: LazyCell::new(),
             credential_cache: LazyCell::new(),
             registry_config: LazyCell::new(),
             package_cache_lock: RefCell::new(None),
@@ -312,6 +333,8 @@ impl Config {
             target_cfgs: LazyCell::new(),
             doc_extern_map: LazyCell::new(),
             progress_config: ProgressConfig::default(),
+            build_summary: false,
+            path_style: PathStyle::Absolute,
             env_config: LazyCell::new(),
             nightly_features_allowed: matches!(&*features::channel(), "nightly" | "dev"),
             ws_roots: RefCell::new(HashMap::new()),
@@ -340,6 +363,15 @@ impl Config {
         &self.home_path
     }
 
+    /// Gets the directory holding Cargo's caches (git checkouts, registry
+    /// index and downloads).
+    ///
+    /// This is the Cargo home directory unless a writable overlay was set
+    /// with `CARGO_CACHE_HOME` under `-Zread-only-sources`.
+    pub fn cache_home(&self) -> &Filesystem {
+        self.cache_home_path.as_ref().unwrap_or(&self.home_path)
+    }
+
     /// Returns a path to display to the user with the location of their home
     /// config file (to only be used for displaying a diagnostics suggestion,
     /// such as recommending where to add a config value).
@@ -354,12 +386,12 @@ impl Config {
 
     /// Gets the Cargo Git directory (`<cargo_home>/git`).
     pub fn git_path(&self) -> Filesystem {
-        self.home_path.join("git")
+        self.cache_home().join("git")
     }
 
     /// Gets the Cargo base directory for all registry information (`<cargo_home>/registry`).
     pub fn registry_base_path(&self) -> Filesystem {
-        self.home_path.join("registry")
+        self.cache_home().join("registry")
     }
 
     /// Gets the Cargo registry index directory (`<cargo_home>/registry/index`).
@@ -486,6 +518,13 @@ impl Config {
             .borrow_mut()
     }
 
+    /// Whether the replacements of sources with a fallback were reachable.
+    pub fn mirror_status(&self) -> RefMut<'_, HashMap<SourceId, bool>> {
+        self.mirror_status
+            .borrow_with(|| RefCell::new(HashMap::new()))
+            .borrow_mut()
+    }
+
     /// Cached credentials from credential providers or configuration.
     pub fn credential_cache(&self) -> RefMut<'_, HashMap<CanonicalUrl, CredentialCacheValue>> {
         self.credential_cache
@@ -1031,6 +1070,7 @@ impl Config {
         let cli_target_dir = target_dir.as_ref().map(|dir| Filesystem::new(dir.clone()));
 
         self.shell().set_verbosity(verbosity);
+        self.shell().set_extra_verbose(extra_verbose);
         self.shell().set_color_choice(color)?;
         self.progress_config = term.progress.unwrap_or_default();
         self.extra_verbose = extra_verbose;
@@ -1046,9 +1086,74 @@ impl Config {
 
         self.load_unstable_flags_from_config()?;
 
+        if self.unstable_flags.read_only_sources {
+            if let Some(dir) = self.get_env_os("CARGO_CACHE_HOME") {
+                if dir.is_empty() {
+                    bail!("the `CARGO_CACHE_HOME` environment variable is set to an empty string")
+                }
+                self.cache_home_path = Some(Filesystem::new(self.cwd.join(dir)));
+            }
+        }
+
+        if let Some(path) = &self.unstable_flags.log_file {
+            let path = self.cwd.join(path);
+            self.open_invocation_log(&path)?;
+        }
+
+        if self.unstable_flags.term_verbosity {
+            if let Some(categories) = &term.verbosity {
+                self.load_term_verbosity(categories)?;
+            }
+        }
+
+        self.build_summary = self.unstable_flags.build_summary && term.summary.unwrap_or(true);
+        self.path_style = match (verbosity, self.unstable_flags.term_paths) {
+            (Verbosity::Normal | Verbosity::Quiet, true) => term.paths.unwrap_or_default(),
+            _ => PathStyle::Absolute,
+        };
+
+        Ok(())
+    }
+
+    /// Shows or hides the categories of messages set in `term.verbosity`
+    /// (`-Zterm-verbosity`), regardless of `--quiet` and `--verbose`.
+    fn load_term_verbosity(&mut self, categories: &HashMap<String, bool>) -> CargoResult<()> {
+        for (name, &shown) in categories {
+            let Some(category) = OutputCategory::ALL.into_iter().find(|c| c.name() == name) else {
+                let expected = OutputCategory::ALL
+                    .iter()
+                    .map(|c| format!("`{}`", c.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bail!("unknown category `{name}` in `term.verbosity`, expected one of {expected}");
+            };
+            self.shell().set_category_shown(category, shown);
+        }
         Ok(())
     }
 
+    /// Starts logging this invocation to `path` (`-Zlog-file`), recording the
+    /// command line and a snapshot of the configuration.
+    fn open_invocation_log(&mut self, path: &Path) -> CargoResult<()> {
+        let log = Rc::new(InvocationLog::create(path)?);
+        log.log(&invocation_log::Invocation {
+            version: crate::version().to_string(),
+            args: env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            cwd: &self.cwd,
+        });
+        log.log(&invocation_log::ConfigSnapshot::new(self.values()?));
+        self.shell().set_invocation_log(Rc::clone(&log));
+        self.invocation_log = Some(log);
+        Ok(())
+    }
+
+    /// The structured log of this invocation, if `-Zlog-file` was passed.
+    pub fn invocation_log(&self) -> Option<&InvocationLog> {
+        self.invocation_log.as_deref()
+    }
+
     fn load_unstable_flags_from_config(&mut self) -> CargoResult<()> {
         // If nightly features are enabled, allow setting Z-flags from config
         // using the `unstable` table. Ignore that block otherwise.
@@ -1765,6 +1870,36 @@ impl Config {
         &self.progress_config
     }
 
+    /// Whether builds end with a summary, see `-Zbuild-summary`.
+    pub fn build_summary(&self) -> bool {
+        self.build_summary
+    }
+
+    /// How paths are rendered in messages, see [`Config::display_path`].
+    pub fn path_style(&self) -> PathStyle {
+        self.path_style
+    }
+
+    /// Renders `path` for a message. With `-Zterm-paths`, it is relative to
+    /// `base` unless `--verbose` is passed or `term.paths` is `absolute`.
+    pub fn display_path(&self, path: &Path, base: &Path) -> String {
+        self.path_style.display(path, base)
+    }
+
+    /// Renders the package `id` for a message, with the path of a path
+    /// package rendered by [`Config::display_path`].
+    pub fn display_package_id(&self, id: PackageId, base: &Path) -> String {
+        match id.source_id().local_path() {
+            Some(path) => format!(
+                "{} v{} ({})",
+                id.name(),
+                id.version(),
+                self.display_path(&path, base)
+            ),
+            None => id.to_string(),
+        }
+    }
+
     pub fn env_config(&self) -> CargoResult<&EnvConfig> {
         let env_config = self
             .env_config
@@ -1879,7 +2014,7 @@ impl Config {
             "package cache lock is not currently held, Cargo forgot to call \
              `acquire_package_cache_lock` before we got to this stack frame",
         );
-        assert!(ret.starts_with(self.home_path.as_path_unlocked()));
+        assert!(ret.starts_with(self.cache_home().as_path_unlocked()));
         ret
     }
 
@@ -1916,11 +2051,11 @@ impl Config {
                 // someone else on the system we should synchronize with them,
                 // but if we can't even do that then we did our best and we just
                 // keep on chugging elsewhere.
-                match self.home_path.open_rw(path, self, desc) {
+                match self.cache_home().open_rw(path, self, desc) {
                     Ok(lock) => *slot = Some((Some(lock), 1)),
                     Err(e) => {
                         if maybe_readonly(&e) {
-                            let lock = self.home_path.open_ro(path, self, desc).ok();
+                            let lock = self.cache_home().open_ro(path, self, desc).ok();
                             *slot = Some((lock, 1));
                             return Ok(PackageCacheLock(self));
                         }
@@ -2261,7 +2396,7 @@ pub fn save_credentials(
     let mut file = {
         cfg.home_path.create_dir()?;
         cfg.home_path
-            .open_rw(filename, cfg, "credentials' config file")?
+            .open_rw_replaceable(filename, cfg, "credentials' config file")?
     };
 
     let mut contents = String::new();
@@ -2353,13 +2488,11 @@ pub fn save_credentials(
         }
     }
 
-    let contents = toml.to_string();
-    file.seek(SeekFrom::Start(0))?;
-    file.write_all(contents.as_bytes())
-        .with_context(|| format!("failed to write to `{}`", file.path().display()))?;
-    file.file().set_len(contents.len() as u64)?;
+    // Restrict the permissions before writing, as the new contents inherit
+    // them when they atomically replace the file.
     set_permissions(file.file(), 0o600)
         .with_context(|| format!("failed to set permissions of `{}`", file.path().display()))?;
+    paths::write_atomic(file.path(), toml.to_string())?;
 
     return Ok(());
 
@@ -2404,6 +2537,7 @@ pub struct CargoHttpConfig {
     pub user_agent: Option<String>,
     pub debug: Option<bool>,
     pub multiplexing: Option<bool>,
+    pub max_concurrent_downloads: Option<u32>,
     pub ssl_version: Option<SslVersionConfig>,
 }
 
@@ -2510,6 +2644,25 @@ pub struct CargoBuildConfig {
     pub rustc: Option<ConfigRelativePath>,
     pub rustdoc: Option<ConfigRelativePath>,
     pub out_dir: Option<ConfigRelativePath>,
+    pub artifact_link: Option<String>,
+    pub max_memory: Option<String>,
+    pub metadata_hash: Option<String>,
+    pub metadata_hash_length: Option<u32>,
+    pub post_link_hooks: Option<Vec<String>>,
+    pub retry: Option<BuildRetryConfig>,
+    pub tool_wrappers: Option<Vec<ConfigRelativePath>>,
+}
+
+/// Configuration for `build.retry`.
+///
+/// ```toml
+/// [build]
+/// retry = { count = 1, classify = ["sigkill", "network"] }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct BuildRetryConfig {
+    pub count: Option<u32>,
+    pub classify: Option<Vec<String>>,
 }
 
 /// Configuration for `build.target`.
@@ -2569,6 +2722,34 @@ struct TermConfig {
     #[serde(default)]
     #[serde(deserialize_with = "progress_or_string")]
     progress: Option<ProgressConfig>,
+    summary: Option<bool>,
+    paths: Option<PathStyle>,
+    verbosity: Option<HashMap<String, bool>>,
+}
+
+/// How paths are rendered in messages, see [`Config::display_path`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PathStyle {
+    /// Relative to the workspace root.
+    #[default]
+    Relative,
+    Absolute,
+}
+
+impl PathStyle {
+    /// Renders `path` in this style. Paths outside of `base` are always
+    /// rendered as they are.
+    pub fn display(self, path: &Path, base: &Path) -> String {
+        if self == PathStyle::Absolute {
+            return path.display().to_string();
+        }
+        match path.strip_prefix(base) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => rel.display().to_string(),
+            Err(_) => path.display().to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
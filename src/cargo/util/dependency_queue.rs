@@ -12,6 +12,10 @@
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
 #[derive(Debug)]
 pub struct DependencyQueue<N: Hash + Eq, E: Hash + Eq, V> {
     /// A list of all known keys to build.
@@ -145,6 +149,26 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         }
     }
 
+    /// Replaces the priorities computed by [`DependencyQueue::queue_finished`]
+    /// with a random order chosen from `seed`, so that packages which are
+    /// ready at the same time are dequeued in a different order.
+    ///
+    /// The order only depends on `seed` and the ordering of the keys, which
+    /// makes it reproducible.
+    pub fn shuffle(&mut self, seed: u64)
+    where
+        N: Ord,
+    {
+        let mut keys: Vec<_> = self.dep_map.keys().cloned().collect();
+        keys.sort();
+        keys.shuffle(&mut StdRng::seed_from_u64(seed));
+        self.priority = keys
+            .into_iter()
+            .enumerate()
+            .map(|(priority, key)| (key, priority))
+            .collect();
+    }
+
     /// Dequeues a package that is ready to be built.
     ///
     /// A package is ready to be built when it has 0 un-built dependencies. If
@@ -252,4 +276,28 @@ mod test {
         q.finish(&4, &());
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn shuffle_is_reproducible() {
+        let order = |seed| {
+            let mut q = DependencyQueue::new();
+            for i in 0..10 {
+                q.queue(i, (), vec![], 1);
+            }
+            q.queue(10, (), (0..10).map(|i| (i, ())), 1);
+            q.queue_finished();
+            q.shuffle(seed);
+            let mut order = Vec::new();
+            while let Some((key, (), _)) = q.dequeue() {
+                order.push(key);
+            }
+            order
+        };
+
+        let first = order(1);
+        assert_eq!(first, order(1));
+        // The dependent is not ready yet.
+        assert_eq!(first.len(), 10);
+        assert!((0..100).any(|seed| order(seed) != first));
+    }
 }
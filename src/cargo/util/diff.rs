@@ -0,0 +1,165 @@
+//! A minimal line-based diff, to preview changes to small files like
+//! manifests.
+
+use std::fmt::Write as _;
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+enum Op {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Returns the changes from `old` to `new` in the unified diff format, or an
+/// empty string if they are the same. `name` is the name of the file in the
+/// header of the diff.
+pub fn unified_diff(old: &str, new: &str, name: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    let ops = diff_lines(&old, &new);
+
+    let mut out = String::new();
+    let changes: Vec<_> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return out;
+    }
+    let _ = writeln!(out, "--- {}", name);
+    let _ = writeln!(out, "+++ {}", name);
+
+    // Group the changes which are close enough to share their context.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for i in changes {
+        match hunks.last_mut() {
+            Some((_, end)) if i - *end <= 2 * CONTEXT => *end = i,
+            _ => hunks.push((i, i)),
+        }
+    }
+
+    // The number of lines of `old` and `new` before each operation.
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut i, mut j) = (0, 0);
+    for op in &ops {
+        positions.push((i, j));
+        match op {
+            Op::Equal(..) => (i, j) = (i + 1, j + 1),
+            Op::Delete(_) => i += 1,
+            Op::Insert(_) => j += 1,
+        }
+    }
+
+    for (start, end) in hunks {
+        let start = start.saturating_sub(CONTEXT);
+        let end = (end + CONTEXT + 1).min(ops.len());
+        let (old_start, new_start) = positions[start];
+        let ops = &ops[start..end];
+        let old_len = ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+        let new_len = ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old_start, old_len),
+            range(new_start, new_len)
+        );
+        for op in ops {
+            let _ = match *op {
+                Op::Equal(i) => writeln!(out, " {}", old[i]),
+                Op::Delete(i) => writeln!(out, "-{}", old[i]),
+                Op::Insert(j) => writeln!(out, "+{}", new[j]),
+            };
+        }
+    }
+    out
+}
+
+/// Formats a range of lines of a hunk header, where `start` is 0-based.
+fn range(start: usize, len: usize) -> String {
+    // An empty range refers to the line before it.
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, len)
+    }
+}
+
+/// Computes the changes from `old` to `new` with a longest common
+/// subsequence of lines.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    // `lcs[i][j]` is the length of the longest common subsequence of
+    // `old[i..]` and `new[j..]`.
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(i));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(Op::Delete));
+    ops.extend((j..m).map(Op::Insert));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified_diff;
+
+    #[test]
+    fn same() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "f"), "");
+    }
+
+    #[test]
+    fn append() {
+        assert_eq!(
+            unified_diff("a\nb\nc\nd\ne\n", "a\nb\nc\nd\ne\nf\n", "f"),
+            "--- f\n+++ f\n@@ -3,3 +3,4 @@\n c\n d\n e\n+f\n"
+        );
+    }
+
+    #[test]
+    fn separate_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "0\n1\n2\n3\n4\n5\n6\n7\n8\nnine\n10\n";
+        assert_eq!(
+            unified_diff(old, new, "f"),
+            "--- f\n+++ f\n\
+             @@ -1,3 +1,4 @@\n+0\n 1\n 2\n 3\n\
+             @@ -6,5 +7,5 @@\n 6\n 7\n 8\n-9\n+nine\n 10\n"
+        );
+    }
+
+    #[test]
+    fn from_empty() {
+        assert_eq!(
+            unified_diff("", "a\n", "f"),
+            "--- f\n+++ f\n@@ -0,0 +1 @@\n+a\n"
+        );
+    }
+}
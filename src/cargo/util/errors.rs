@@ -113,6 +113,48 @@ impl fmt::Display for HttpNotSuccessful {
 
 impl std::error::Error for HttpNotSuccessful {}
 
+// =============================================================================
+// Offline error
+
+/// Error when something which isn't cached locally has to be downloaded, but
+/// the network isn't available because of `--offline`.
+#[derive(Debug)]
+pub struct OfflineError {
+    /// What is missing, like "`bar v0.1.0`".
+    pub what: String,
+    /// Where it would be downloaded from.
+    pub url: String,
+    /// The command which downloads it ahead of time, `cargo fetch` unless
+    /// more is known about the build, like its `--target`.
+    pub fetch_command: Option<String>,
+}
+
+impl OfflineError {
+    pub fn new(what: impl Into<String>, url: impl Into<String>) -> OfflineError {
+        OfflineError {
+            what: what.into(),
+            url: url.into(),
+            fetch_command: None,
+        }
+    }
+}
+
+impl fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is not available offline, and can't be downloaded from `{}` \
+             in offline mode (--offline)\n\n\
+             help: run `{}` without `--offline` to download it beforehand",
+            self.what,
+            self.url,
+            self.fetch_command.as_deref().unwrap_or("cargo fetch")
+        )
+    }
+}
+
+impl std::error::Error for OfflineError {}
+
 // =============================================================================
 // Verbose error
 
@@ -130,6 +172,11 @@ impl VerboseError {
     pub fn new(inner: Error) -> VerboseError {
         VerboseError { inner }
     }
+
+    /// The wrapped error, which isn't part of the chain of sources.
+    pub fn inner(&self) -> &Error {
+        &self.inner
+    }
 }
 
 impl std::error::Error for VerboseError {
@@ -180,6 +180,35 @@ impl Filesystem {
         )
     }
 
+    /// Like [`Filesystem::open_rw`], for files which are replaced by renaming
+    /// another file over them, as [`paths::write_atomic`] does.
+    ///
+    /// A process waiting for the lock of such a file would otherwise end up
+    /// holding the lock of the replaced file, while other processes lock the
+    /// new one. So once the lock is acquired, the locked file is checked to
+    /// still be the one at `path`, and the one found there is locked instead
+    /// if not.
+    pub fn open_rw_replaceable<P>(
+        &self,
+        path: P,
+        config: &Config,
+        msg: &str,
+    ) -> CargoResult<FileLock>
+    where
+        P: AsRef<Path>,
+    {
+        loop {
+            let lock = self.open_rw(path.as_ref(), config, msg)?;
+            let locked = same_file::Handle::from_file(lock.file().try_clone()?)?;
+            if let Ok(current) = same_file::Handle::from_path(lock.path()) {
+                if current == locked {
+                    return Ok(lock);
+                }
+            }
+            // Replaced or removed while waiting for the lock, try again.
+        }
+    }
+
     /// Opens shared access to a file, returning the locked version of a file.
     ///
     /// This function will fail if `path` doesn't already exist, but if it does
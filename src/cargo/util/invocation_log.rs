@@ -0,0 +1,235 @@
+//! A structured log of a single Cargo invocation, enabled with `-Zlog-file`.
+//!
+//! The log is written as one JSON object per line, each tagged with a
+//! `reason` field like the messages of `--message-format=json`, plus the
+//! number of seconds elapsed since the log was opened. Every event is flushed
+//! as soon as it is recorded so a log is still useful when Cargo crashes or
+//! is killed part-way through, which makes it suitable for attaching to bug
+//! reports or keeping around as a CI artifact.
+//!
+//! Events are recorded regardless of the verbosity of the terminal output.
+//! Failures to write the log are deliberately ignored, since the log should
+//! never be the reason a build fails.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::Context as _;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::core::compiler::CompileMode;
+use crate::core::PackageId;
+use crate::util::config::ConfigValue as CV;
+use crate::util::machine_message::Message;
+use crate::CargoResult;
+
+/// Config keys whose values are never written to the log.
+const REDACTED_KEYS: &[&str] = &["token", "secret-key", "secret-key-subject"];
+
+/// A handle to the file an invocation is being logged to.
+pub struct InvocationLog {
+    path: PathBuf,
+    file: RefCell<File>,
+    start: Instant,
+}
+
+impl std::fmt::Debug for InvocationLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InvocationLog")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl InvocationLog {
+    /// Creates (or truncates) the log file at `path`.
+    pub fn create(path: &Path) -> CargoResult<InvocationLog> {
+        if let Some(parent) = path.parent() {
+            cargo_util::paths::create_dir_all(parent)?;
+        }
+        let file = File::create(path)
+            .with_context(|| format!("failed to create log file `{}`", path.display()))?;
+        Ok(InvocationLog {
+            path: path.to_path_buf(),
+            file: RefCell::new(file),
+            start: Instant::now(),
+        })
+    }
+
+    /// The path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a single event.
+    pub fn log<T: Message>(&self, event: &T) {
+        // Same shape as `Message::to_json_string`, with the timestamp spliced
+        // in after the `reason`.
+        let json = serde_json::to_string(event).unwrap();
+        assert!(json.starts_with("{\""));
+        let line = format!(
+            "{{\"reason\":{},\"elapsed\":{:.3},{}",
+            json!(event.reason()),
+            self.start.elapsed().as_secs_f64(),
+            &json[1..]
+        );
+        let mut file = self.file.borrow_mut();
+        let _ = writeln!(file, "{}", line);
+        let _ = file.flush();
+    }
+
+    /// Records a warning or error which was shown (or would have been shown)
+    /// to the user.
+    pub fn diagnostic(&self, level: &str, message: &dyn std::fmt::Display) {
+        self.log(&Diagnostic {
+            level,
+            message: message.to_string(),
+        });
+    }
+}
+
+/// The first event of every log, describing how Cargo was invoked.
+#[derive(Serialize)]
+pub struct Invocation<'a> {
+    pub version: String,
+    pub args: Vec<String>,
+    pub cwd: &'a Path,
+}
+
+impl<'a> Message for Invocation<'a> {
+    fn reason(&self) -> &str {
+        "invocation"
+    }
+}
+
+/// A snapshot of the merged configuration, with credentials redacted.
+#[derive(Serialize)]
+pub struct ConfigSnapshot {
+    pub values: serde_json::Value,
+}
+
+impl ConfigSnapshot {
+    pub fn new(values: &HashMap<String, CV>) -> ConfigSnapshot {
+        let mut table = json!({});
+        for (key, cv) in values {
+            table[key] = cv_to_json(key, cv);
+        }
+        return ConfigSnapshot { values: table };
+
+        fn cv_to_json(key: &str, cv: &CV) -> serde_json::Value {
+            if REDACTED_KEYS.contains(&key) {
+                return json!("<redacted>");
+            }
+            match cv {
+                CV::Boolean(val, _def) => json!(val),
+                CV::Integer(val, _def) => json!(val),
+                CV::String(val, _def) => json!(val),
+                CV::List(vals, _def) => {
+                    let jvals: Vec<_> = vals.iter().map(|(val, _def)| json!(val)).collect();
+                    json!(jvals)
+                }
+                CV::Table(map, _def) => {
+                    let mut table = json!({});
+                    for (key, val) in map {
+                        table[key] = cv_to_json(key, val);
+                    }
+                    table
+                }
+            }
+        }
+    }
+}
+
+impl Message for ConfigSnapshot {
+    fn reason(&self) -> &str {
+        "config"
+    }
+}
+
+/// The set of packages selected by the resolver.
+#[derive(Serialize)]
+pub struct Resolved {
+    pub packages: Vec<PackageId>,
+}
+
+impl Message for Resolved {
+    fn reason(&self) -> &str {
+        "resolve"
+    }
+}
+
+/// The decision of whether a unit needs to be rebuilt.
+#[derive(Serialize)]
+pub struct UnitDecision<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub mode: CompileMode,
+    pub fresh: bool,
+    pub dirty_reason: Option<String>,
+}
+
+impl<'a> Message for UnitDecision<'a> {
+    fn reason(&self) -> &str {
+        "unit-decision"
+    }
+}
+
+/// A process spawned on behalf of a unit, such as `rustc` or a build script.
+#[derive(Serialize)]
+pub struct ProcessStarted<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub command: &'a str,
+}
+
+impl<'a> Message for ProcessStarted<'a> {
+    fn reason(&self) -> &str {
+        "process-started"
+    }
+}
+
+/// The completion of a unit's work, with how long it took.
+#[derive(Serialize)]
+pub struct UnitFinished<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub mode: CompileMode,
+    pub duration: f64,
+    pub success: bool,
+}
+
+impl<'a> Message for UnitFinished<'a> {
+    fn reason(&self) -> &str {
+        "unit-finished"
+    }
+}
+
+/// A warning or error message.
+#[derive(Serialize)]
+pub struct Diagnostic<'a> {
+    pub level: &'a str,
+    pub message: String,
+}
+
+impl<'a> Message for Diagnostic<'a> {
+    fn reason(&self) -> &str {
+        "diagnostic"
+    }
+}
+
+/// The last event of a log, written when Cargo exits normally.
+#[derive(Serialize)]
+pub struct Finished {
+    pub success: bool,
+}
+
+impl Message for Finished {
+    fn reason(&self) -> &str {
+        "finished"
+    }
+}
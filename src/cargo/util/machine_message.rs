@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::ser;
@@ -111,3 +112,83 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+/// The summary of a successful build, see `core::compiler::build_summary`.
+#[derive(Serialize)]
+pub struct BuildSummary<'a> {
+    pub compiled: usize,
+    pub fresh: usize,
+    pub duration: f64,
+    pub critical_path: f64,
+    pub slowest: Vec<SummaryUnit<'a>>,
+    pub warnings: Vec<SummaryWarnings>,
+    /// Units run again with `build.retry`, only present if there are any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retried: Vec<SummaryRetry<'a>>,
+    pub artifacts: &'a [PathBuf],
+}
+
+#[derive(Serialize)]
+pub struct SummaryUnit<'a> {
+    pub package_id: PackageId,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub duration: f64,
+}
+
+#[derive(Serialize)]
+pub struct SummaryWarnings {
+    pub package_id: PackageId,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct SummaryRetry<'a> {
+    pub package_id: PackageId,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub reason: &'static str,
+}
+
+impl<'a> Message for BuildSummary<'a> {
+    fn reason(&self) -> &str {
+        "build-summary"
+    }
+}
+
+/// A test or benchmark executable built by `cargo test --no-run` or
+/// `cargo bench --no-run`, with what's needed to run it as Cargo would.
+#[derive(Serialize)]
+pub struct TestExecutable<'a> {
+    pub package_id: PackageId,
+    pub manifest_path: &'a Path,
+    pub target: &'a Target,
+    pub features: Vec<String>,
+    pub executable: &'a Path,
+    /// The program to run, which is either the executable or its runner.
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<&'a Path>,
+    /// The environment variables set by Cargo.
+    pub env: BTreeMap<String, String>,
+}
+
+impl<'a> Message for TestExecutable<'a> {
+    fn reason(&self) -> &str {
+        "test-executable"
+    }
+}
+
+#[derive(Serialize)]
+pub struct PackageCheck<'a> {
+    pub package_id: PackageId,
+    pub check: &'a str,
+    pub status: &'a str,
+    pub message: Option<&'a str>,
+}
+
+impl<'a> Message for PackageCheck<'a> {
+    fn reason(&self) -> &str {
+        "package-check"
+    }
+}
@@ -31,6 +31,7 @@ pub use self::workspace::{
 };
 
 pub mod auth;
+pub mod cache_backend;
 mod canonical_url;
 pub mod command_prelude;
 pub mod config;
@@ -39,6 +40,7 @@ pub mod cpu;
 pub mod credential;
 mod dependency_queue;
 pub mod diagnostic_server;
+pub mod diff;
 pub mod edit_distance;
 pub mod errors;
 mod flock;
@@ -49,6 +51,7 @@ pub mod important_paths;
 pub mod interning;
 pub mod into_url;
 mod into_url_with_base;
+pub mod invocation_log;
 mod io;
 pub mod job;
 mod lockserver;
@@ -85,6 +88,25 @@ pub fn human_readable_bytes(bytes: u64) -> (f32, &'static str) {
     (bytes / 1024_f32.powi(i as i32), UNITS[i])
 }
 
+/// Parses a size in bytes with an optional binary unit suffix, such as
+/// `512MiB` or `8GiB`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let scale: u64 = match unit.trim_start() {
+        "" | "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(scale)
+}
+
 pub fn iter_join_onto<W, I, T>(mut w: W, iter: I, delim: &str) -> fmt::Result
 where
     W: fmt::Write,
@@ -21,7 +21,9 @@ use url::Url;
 
 use crate::core::compiler::{CompileKind, CompileTarget};
 use crate::core::dependency::{Artifact, ArtifactTarget, DepKind};
-use crate::core::manifest::{ManifestMetadata, TargetSourcePath, Warnings};
+use crate::core::manifest::{
+    BuildProbe, DeclarativeBuild, ManifestMetadata, TargetSourcePath, Warnings,
+};
 use crate::core::resolver::ResolveBehavior;
 use crate::core::{find_workspace_root, resolve_relative_path, CliUnstable};
 use crate::core::{Dependency, Manifest, PackageId, Summary, Target};
@@ -61,7 +63,10 @@ pub fn read_manifest(
     if embedded {
         if !config.cli_unstable().script {
             return Err(ManifestError::new(
-                anyhow::anyhow!("parsing `{}` requires `-Zscript`", path.display()),
+                anyhow::anyhow!(
+                    "parsing `{}` requires `-Zscript`",
+                    config.display_path(path, config.cwd())
+                ),
                 path.into(),
             ));
         }
@@ -70,7 +75,12 @@ pub fn read_manifest(
     }
 
     read_manifest_from_str(&contents, path, embedded, source_id, config)
-        .with_context(|| format!("failed to parse manifest at `{}`", path.display()))
+        .with_context(|| {
+            format!(
+                "failed to parse manifest at `{}`",
+                config.display_path(path, config.cwd())
+            )
+        })
         .map_err(|err| ManifestError::new(err, path.into()))
 }
 
@@ -90,7 +100,7 @@ pub fn is_embedded(path: &Path) -> bool {
 /// within the manifest. For virtual manifests, these paths can only
 /// come from patched or replaced dependencies. These paths are not
 /// canonicalized.
-fn read_manifest_from_str(
+pub fn read_manifest_from_str(
     contents: &str,
     manifest_file: &Path,
     embedded: bool,
@@ -378,6 +388,61 @@ pub struct TomlManifest {
     workspace: Option<TomlWorkspace>,
     badges: Option<MaybeWorkspaceBtreeMap>,
     lints: Option<toml::Value>,
+    build_probe: Option<BTreeMap<String, TomlBuildProbe>>,
+}
+
+/// An entry of the `[build-probe]` table.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlBuildProbe {
+    pkg_config: Option<String>,
+    version: Option<String>,
+    cfgs: Option<Vec<String>>,
+    fallback_cfgs: Option<Vec<String>>,
+}
+
+impl TomlBuildProbe {
+    fn to_build_probe(&self, name: &str) -> CargoResult<BuildProbe> {
+        let pkg_config = self.pkg_config.clone().unwrap_or_else(|| name.to_string());
+        let version_checks = match &self.version {
+            Some(req) => parse_probe_version(req)
+                .with_context(|| format!("invalid `version` of `build-probe.{name}`: `{req}`"))?,
+            None => Vec::new(),
+        };
+        Ok(BuildProbe {
+            name: name.to_string(),
+            pkg_config,
+            version_checks,
+            cfgs: self.cfgs.clone().unwrap_or_default(),
+            fallback_cfgs: self.fallback_cfgs.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// Turns the comma-separated comparisons of a `[build-probe]` version into
+/// `pkg-config` arguments. A version without an operator is a minimum.
+fn parse_probe_version(req: &str) -> CargoResult<Vec<String>> {
+    req.split(',')
+        .map(|cmp| {
+            let cmp = cmp.trim();
+            let (flag, version) = if let Some(version) = cmp.strip_prefix(">=") {
+                ("--atleast-version", version)
+            } else if let Some(version) = cmp.strip_prefix("<=") {
+                ("--max-version", version)
+            } else if let Some(version) = cmp.strip_prefix('=') {
+                ("--exact-version", version)
+            } else if cmp.starts_with(['<', '>', '^', '~']) {
+                bail!("only `>=`, `<=` and `=` comparisons are supported by `pkg-config`")
+            } else {
+                ("--atleast-version", cmp)
+            };
+            let version = version.trim();
+            if version.is_empty() {
+                bail!("expected a version after `{cmp}`");
+            }
+            Ok(format!("{flag}={version}"))
+        })
+        .collect()
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
@@ -580,6 +645,8 @@ pub struct TomlProfile {
     pub strip: Option<StringOrBool>,
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Option<Vec<InternedString>>,
+    // Note that `target-dir` is used for the cargo-feature `package_target_dir`
+    pub target_dir: Option<String>,
     // These two fields must be last because they are sub-tables, and TOML
     // requires all non-tables to be listed first.
     pub package: Option<BTreeMap<ProfilePackageSpec, TomlProfile>>,
@@ -636,6 +703,12 @@ impl TomlProfile {
         warnings: &mut Vec<String>,
     ) -> CargoResult<()> {
         self.validate_profile(name, cli_unstable, features)?;
+        if self.target_dir.is_some() {
+            bail!(
+                "`target-dir` may only be specified in a package override, \
+                 such as `[profile.{name}.package.<name>]`"
+            );
+        }
         if let Some(ref profile) = self.build_override {
             profile.validate_override("build-override")?;
             profile.validate_profile(&format!("{name}.build-override"), cli_unstable, features)?;
@@ -824,6 +897,29 @@ impl TomlProfile {
                 _ => {}
             }
         }
+        if let Some(target_dir) = &self.target_dir {
+            match (
+                features.require(Feature::package_target_dir()),
+                cli_unstable.package_target_dir,
+            ) {
+                (Err(e), false) => return Err(e),
+                _ => {}
+            }
+
+            if target_dir.is_empty() {
+                bail!("`profile.{}.target-dir` may not be an empty string", name);
+            }
+        }
+        if let Some(split_debuginfo) = &self.split_debuginfo {
+            if !matches!(split_debuginfo.as_str(), "off" | "packed" | "unpacked") {
+                bail!(
+                    "`profile.{}.split-debuginfo` setting of `{}` is not a valid setting, \
+                     must be `off`, `packed` or `unpacked`",
+                    name,
+                    split_debuginfo,
+                );
+            }
+        }
         Ok(())
     }
 
@@ -844,6 +940,9 @@ impl TomlProfile {
         if self.rpath.is_some() {
             bail!("`rpath` may not be specified in a `{}` profile", which)
         }
+        if which == "build-override" && self.target_dir.is_some() {
+            bail!("`target-dir` may not be specified in a `{}` profile", which)
+        }
         Ok(())
     }
 
@@ -897,6 +996,10 @@ impl TomlProfile {
             self.rustflags = Some(v.clone());
         }
 
+        if let Some(v) = &profile.target_dir {
+            self.target_dir = Some(v.clone());
+        }
+
         if let Some(other_package) = &profile.package {
             match &mut self.package {
                 Some(self_package) => {
@@ -1554,6 +1657,9 @@ pub struct TomlWorkspace {
     package: Option<InheritableFields>,
     dependencies: Option<BTreeMap<String, TomlDependency>>,
     lints: Option<toml::Value>,
+    /// Named sets of features of the members, as `set -> member -> features`.
+    #[serde(rename = "feature-sets")]
+    feature_sets: Option<BTreeMap<String, BTreeMap<String, Vec<String>>>>,
 
     // Note that this field must come last due to the way toml serialization
     // works which requires tables to be emitted after all values.
@@ -1850,6 +1956,7 @@ impl TomlManifest {
             badges: self.badges.clone(),
             cargo_features: self.cargo_features.clone(),
             lints: self.lints.clone(),
+            build_probe: self.build_probe.clone(),
         });
 
         fn map_deps(
@@ -1955,7 +2062,7 @@ impl TomlManifest {
                     config.shell().warn(format!(
                         "manifest at `{}` contains both `project` and `package`, \
                     this could become a hard error in the future",
-                        package_root.display()
+                        config.display_path(package_root, config.cwd())
                     ))?;
                 }
                 project.clone()
@@ -1966,7 +2073,7 @@ impl TomlManifest {
                     config.shell().warn(format!(
                         "manifest at `{}` contains `[project]` instead of `[package]`, \
                                 this could become a hard error in the future",
-                        package_root.display()
+                        config.display_path(package_root, config.cwd())
                     ))?;
                 }
                 project.clone()
@@ -1992,6 +2099,9 @@ impl TomlManifest {
                         );
                     }
                 }
+                if toml_config.feature_sets.is_some() {
+                    features.require(Feature::feature_sets())?;
+                }
                 let ws_root_config = WorkspaceRootConfig::new(
                     package_root,
                     &toml_config.members,
@@ -1999,6 +2109,7 @@ impl TomlManifest {
                     &toml_config.exclude,
                     &Some(inheritable),
                     &toml_config.metadata,
+                    &toml_config.feature_sets,
                 );
                 config
                     .ws_roots
@@ -2091,6 +2202,30 @@ impl TomlManifest {
         if package.metabuild.is_some() {
             features.require(Feature::metabuild())?;
         }
+        // `package.metadata` is otherwise ignored by Cargo, so the directives
+        // are only read with the feature enabled.
+        let declarative_build = match package.metadata.as_ref().and_then(|m| m.get("build")) {
+            Some(build) if features.is_enabled(Feature::metabuild()) => Some(
+                build
+                    .clone()
+                    .try_into::<DeclarativeBuild>()
+                    .context("failed to parse `package.metadata.build`")?,
+            ),
+            _ => None,
+        };
+        let build_probes = match &me.build_probe {
+            Some(probes) => {
+                features.require(Feature::build_probe())?;
+                if declarative_build.is_some() {
+                    bail!("cannot specify both `package.metadata.build` and `[build-probe]`");
+                }
+                probes
+                    .iter()
+                    .map(|(name, probe)| probe.to_build_probe(name))
+                    .collect::<CargoResult<Vec<_>>>()?
+            }
+            None => Vec::new(),
+        };
 
         let resolve_behavior = match (
             package.resolver.as_ref(),
@@ -2106,7 +2241,7 @@ impl TomlManifest {
         // If we have no lib at all, use the inferred lib, if available.
         // If we have a lib with a path, we're done.
         // If we have a lib with no path, use the inferred lib or else the package name.
-        let targets = targets(
+        let mut targets = targets(
             &features,
             me,
             package_name,
@@ -2117,6 +2252,23 @@ impl TomlManifest {
             &mut warnings,
             &mut errors,
         )?;
+        if declarative_build.is_some() {
+            if targets.iter().any(|t| t.is_custom_build()) {
+                bail!("cannot specify both `package.metadata.build` and a build script");
+            }
+            targets.push(Target::metabuild_target(&format!(
+                "declarative-build-{}",
+                package_name
+            )));
+        }
+        // The probes take the place of the build script when there is none,
+        // see `RustcTargetData::build_probe`.
+        if !build_probes.is_empty() && !targets.iter().any(|t| t.is_custom_build()) {
+            targets.push(Target::metabuild_target(&format!(
+                "build-probe-{}",
+                package_name
+            )));
+        }
 
         if targets.is_empty() {
             debug!("manifest has no build targets");
@@ -2552,6 +2704,7 @@ impl TomlManifest {
                 .map(|_| MaybeWorkspace::Defined(metadata.badges.clone())),
             lints: lints
                 .map(|lints| toml::Value::try_from(MaybeWorkspaceLints::Defined(lints)).unwrap()),
+            build_probe: me.build_probe.clone(),
         };
         let mut manifest = Manifest::new(
             summary,
@@ -2575,6 +2728,8 @@ impl TomlManifest {
             package.default_run.clone(),
             Rc::new(resolved_toml),
             package.metabuild.clone().map(|sov| sov.0),
+            declarative_build,
+            build_probes,
             resolve_behavior,
             rustflags,
             embedded,
@@ -2647,6 +2802,9 @@ impl TomlManifest {
         if me.badges.is_some() {
             bail!("this virtual manifest specifies a [badges] section, which is not allowed");
         }
+        if me.build_probe.is_some() {
+            bail!("this virtual manifest specifies a [build-probe] section, which is not allowed");
+        }
 
         let mut nested_paths = Vec::new();
         let mut warnings = Vec::new();
@@ -2686,6 +2844,9 @@ impl TomlManifest {
                 let lints = parse_unstable_lints(toml_config.lints.clone(), config, &mut warnings)?;
                 let lints = verify_lints(lints)?;
                 inheritable.update_lints(lints);
+                if toml_config.feature_sets.is_some() {
+                    features.require(Feature::feature_sets())?;
+                }
                 let ws_root_config = WorkspaceRootConfig::new(
                     root,
                     &toml_config.members,
@@ -2693,6 +2854,7 @@ impl TomlManifest {
                     &toml_config.exclude,
                     &Some(inheritable),
                     &toml_config.metadata,
+                    &toml_config.feature_sets,
                 );
                 config
                     .ws_roots
@@ -2828,6 +2990,16 @@ impl TomlManifest {
     pub fn features(&self) -> Option<&BTreeMap<InternedString, Vec<InternedString>>> {
         self.features.as_ref()
     }
+
+    /// Whether any `[[test]]` target is declared without an explicit `path`.
+    pub fn has_test_without_path(&self) -> bool {
+        self.test.iter().flatten().any(|t| t.path.is_none())
+    }
+
+    /// Whether any `[[bench]]` target is declared without an explicit `path`.
+    pub fn has_bench_without_path(&self) -> bool {
+        self.bench.iter().flatten().any(|t| t.path.is_none())
+    }
 }
 
 fn parse_unstable_lints<T: Deserialize<'static>>(
@@ -2972,7 +3144,7 @@ fn inheritable_from_path(
         }
         _ => bail!(
             "root of a workspace inferred but wasn't a root: {}",
-            workspace_path.display()
+            config.display_path(&workspace_path, config.cwd())
         ),
     }
 }
@@ -3398,6 +3570,8 @@ struct TomlTarget {
     harness: Option<bool>,
     required_features: Option<Vec<String>>,
     edition: Option<String>,
+    // Note that `requires-explicit` is used for the cargo-feature `requires_explicit`
+    requires_explicit: Option<bool>,
 }
 
 #[derive(Clone)]
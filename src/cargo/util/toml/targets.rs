@@ -44,6 +44,8 @@ pub fn targets(
 ) -> CargoResult<Vec<Target>> {
     let mut targets = Vec::new();
 
+    validate_requires_explicit(features, manifest)?;
+
     let has_lib;
 
     if let Some(target) = clean_lib(
@@ -800,6 +802,31 @@ fn validate_unique_names(targets: &[TomlTarget], target_kind: &str) -> CargoResu
     Ok(())
 }
 
+/// Checks that `requires-explicit` is only used with its cargo-feature, and
+/// not on the library, which the other targets of the package depend on.
+fn validate_requires_explicit(features: &Features, manifest: &TomlManifest) -> CargoResult<()> {
+    if manifest
+        .lib
+        .as_ref()
+        .map_or(false, |lib| lib.requires_explicit.is_some())
+    {
+        anyhow::bail!("the `requires-explicit` key cannot be set on the library target");
+    }
+    let mut targets = [
+        &manifest.bin,
+        &manifest.example,
+        &manifest.test,
+        &manifest.bench,
+    ]
+    .into_iter()
+    .flatten()
+    .flatten();
+    if targets.any(|target| target.requires_explicit.is_some()) {
+        features.require(Feature::requires_explicit())?;
+    }
+    Ok(())
+}
+
 fn configure(toml: &TomlTarget, target: &mut Target) -> CargoResult<()> {
     let t2 = target.clone();
     target
@@ -809,6 +836,7 @@ fn configure(toml: &TomlTarget, target: &mut Target) -> CargoResult<()> {
         .set_benched(toml.bench.unwrap_or_else(|| t2.benched()))
         .set_harness(toml.harness.unwrap_or_else(|| t2.harness()))
         .set_proc_macro(toml.proc_macro().unwrap_or_else(|| t2.proc_macro()))
+        .set_requires_explicit(toml.requires_explicit.unwrap_or(false))
         .set_doc_scrape_examples(match toml.doc_scrape_examples {
             None => RustdocScrapeExamples::Unset,
             Some(false) => RustdocScrapeExamples::Disabled,
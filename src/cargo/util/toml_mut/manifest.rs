@@ -218,6 +218,105 @@ impl Manifest {
         }
         result
     }
+
+    /// Adds `member` to `workspace.members`, creating the array if needed.
+    ///
+    /// Returns `false` if `member` is already listed.
+    pub fn add_workspace_member(&mut self, member: &str) -> CargoResult<bool> {
+        Ok(add_to_array(
+            self.get_workspace_array_mut("members")?,
+            member,
+        ))
+    }
+
+    /// Adds `member` to `workspace.exclude`, creating the array if needed.
+    ///
+    /// Returns `false` if `member` is already listed.
+    pub fn add_workspace_exclude(&mut self, member: &str) -> CargoResult<bool> {
+        Ok(add_to_array(
+            self.get_workspace_array_mut("exclude")?,
+            member,
+        ))
+    }
+
+    /// Removes `member` from `workspace.members`.
+    ///
+    /// Returns `false` if `member` isn't listed, for example if it is only
+    /// matched by a glob.
+    pub fn remove_workspace_member(&mut self, member: &str) -> CargoResult<bool> {
+        let has_members = self
+            .data
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .is_some();
+        if !has_members {
+            return Ok(false);
+        }
+        let members = self.get_workspace_array_mut("members")?;
+        let Some(index) = members
+            .iter()
+            .position(|value| is_same_member(value, member))
+        else {
+            return Ok(false);
+        };
+        let removed = members.remove(index);
+        // The first element isn't preceded by a space in `["a", "b"]`.
+        if let (0, Some(first)) = (index, members.get_mut(0)) {
+            if let Some(prefix) = removed.decor().prefix() {
+                first.decor_mut().set_prefix(prefix.clone());
+            }
+        }
+        Ok(true)
+    }
+
+    fn get_workspace_array_mut(&mut self, key: &str) -> CargoResult<&mut toml_edit::Array> {
+        let workspace = self
+            .data
+            .as_table_mut()
+            .get_mut("workspace")
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .ok_or_else(|| anyhow::format_err!("the manifest has no `[workspace]` table"))?;
+        workspace
+            .entry(key)
+            .or_insert(toml_edit::value(toml_edit::Array::new()))
+            .as_array_mut()
+            .ok_or_else(|| anyhow::format_err!("`workspace.{}` is not an array", key))
+    }
+}
+
+/// Appends `member` to `array`, indented like its last element.
+fn add_to_array(array: &mut toml_edit::Array, member: &str) -> bool {
+    if array.iter().any(|value| is_same_member(value, member)) {
+        return false;
+    }
+    // Only keep the indentation, not the comments above the last element.
+    let prefix = array
+        .iter()
+        .last()
+        .and_then(|value| value.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .map(|prefix| match prefix.rfind('\n') {
+            Some(i) => prefix[i..].to_owned(),
+            None => prefix.to_owned(),
+        });
+    array.push(member);
+    if let (Some(prefix), Some(last)) = (prefix, array.iter_mut().last()) {
+        last.decor_mut().set_prefix(prefix);
+    }
+    true
+}
+
+/// Whether the entry `value` of `workspace.members` or `workspace.exclude`
+/// is the path `member`.
+fn is_same_member(value: &toml_edit::Value, member: &str) -> bool {
+    fn normalize(path: &str) -> String {
+        let path = path.replace('\\', "/");
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        path.to_string()
+    }
+    value
+        .as_str()
+        .map_or(false, |value| normalize(value) == normalize(member))
 }
 
 impl str::FromStr for Manifest {
@@ -296,7 +395,19 @@ impl LocalManifest {
         let s = self.manifest.data.to_string();
         let new_contents_bytes = s.as_bytes();
 
-        cargo_util::paths::write(&self.path, new_contents_bytes)
+        cargo_util::paths::write_atomic(&self.path, new_contents_bytes)
+    }
+
+    /// Write changes back to the file, which may be the virtual manifest of a
+    /// workspace.
+    pub fn write_workspace(&self) -> CargoResult<()> {
+        if !self.manifest.data.contains_key("workspace") {
+            anyhow::bail!(
+                "missing expected `workspace` field in {}",
+                self.path.display()
+            );
+        }
+        cargo_util::paths::write_atomic(&self.path, self.manifest.data.to_string().as_bytes())
     }
 
     /// Lookup a dependency.
@@ -0,0 +1,131 @@
+//! Tests for the `--artifact-dir` flag.
+
+use cargo_test_support::{project, Project};
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+use std::path::PathBuf;
+
+const MAIN: &str = r#"
+    fn main() {
+        if cfg!(debug_assertions) {
+            println!("debug");
+        } else {
+            println!("release");
+        }
+    }
+"#;
+
+fn artifact(p: &Project, name: &str) -> PathBuf {
+    p.root().join(format!("out/bin/{name}{EXE_SUFFIX}"))
+}
+
+fn build(p: &Project, args: &str) {
+    p.cargo(&format!(
+        "build -Zunstable-options --artifact-dir out {args}"
+    ))
+    .masquerade_as_nightly_cargo(&["artifact-dir"])
+    .run();
+}
+
+fn check_output(p: &Project, expected: &str) {
+    p.process(artifact(p, "foo"))
+        .with_stdout(&format!("{expected}\n"))
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+    p.cargo("build --artifact-dir out")
+        .masquerade_as_nightly_cargo(&["artifact-dir"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--artifact-dir` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn layout() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("examples/ex.rs", "fn main() {}")
+        .file("tests/t.rs", "")
+        .build();
+
+    build(&p, "--all-targets");
+    let mut files: Vec<_> = ["bin", "lib", "examples"]
+        .iter()
+        .flat_map(|dir| fs::read_dir(p.root().join("out").join(dir)).unwrap())
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let dir = path.parent().unwrap().file_name().unwrap().to_owned();
+            format!(
+                "{}/{}",
+                dir.to_str().unwrap(),
+                path.file_name().unwrap().to_str().unwrap()
+            )
+        })
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            format!("bin/foo{EXE_SUFFIX}"),
+            format!("examples/ex{EXE_SUFFIX}"),
+            "lib/libfoo.rlib".to_string(),
+        ]
+    );
+}
+
+#[cargo_test]
+fn unchanged_artifacts_are_kept() {
+    let p = project().file("src/main.rs", MAIN).build();
+    build(&p, "");
+    check_output(&p, "debug");
+    let mtime = fs::metadata(artifact(&p, "foo"))
+        .unwrap()
+        .modified()
+        .unwrap();
+
+    build(&p, "");
+    let new_mtime = fs::metadata(artifact(&p, "foo"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    assert_eq!(mtime, new_mtime);
+
+    p.change_file("src/main.rs", r#"fn main() { println!("changed"); }"#);
+    build(&p, "");
+    check_output(&p, "changed");
+}
+
+#[cargo_test]
+fn profile_switch() {
+    let p = project().file("src/main.rs", MAIN).build();
+    build(&p, "");
+    check_output(&p, "debug");
+
+    build(&p, "--release");
+    check_output(&p, "release");
+
+    // Nothing is rebuilt, but the artifact comes from the other profile.
+    build(&p, "");
+    check_output(&p, "debug");
+}
+
+#[cargo_test]
+fn rebuilt_without_artifact_dir() {
+    let p = project().file("src/main.rs", MAIN).build();
+    build(&p, "");
+    check_output(&p, "debug");
+
+    p.change_file("src/main.rs", r#"fn main() { println!("changed"); }"#);
+    p.cargo("build").run();
+    check_output(&p, "debug");
+
+    build(&p, "");
+    check_output(&p, "changed");
+}
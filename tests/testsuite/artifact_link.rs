@@ -0,0 +1,76 @@
+//! Tests for `-Zartifact-link` and the `build.artifact-link` config.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config.toml", "[build]\nartifact-link = \"bogus\"\n")
+        .build();
+
+    p.cargo("build").run();
+    assert!(p.bin("foo").is_file());
+}
+
+#[cargo_test]
+fn invalid_value() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config.toml", "[build]\nartifact-link = \"bogus\"\n")
+        .build();
+
+    p.cargo("build -Zartifact-link")
+        .masquerade_as_nightly_cargo(&["artifact-link"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.artifact-link` must be `hardlink`, `symlink`, or `copy`, found `bogus`",
+        )
+        .run();
+}
+
+#[cfg(target_os = "linux")]
+#[cargo_test]
+fn link_strategies() {
+    use std::os::unix::fs::MetadataExt;
+
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("build -Zartifact-link")
+        .masquerade_as_nightly_cargo(&["artifact-link"])
+        .env("CARGO_BUILD_ARTIFACT_LINK", "hardlink")
+        .run();
+    assert_eq!(p.bin("foo").metadata().unwrap().nlink(), 2);
+
+    p.cargo("build -Zartifact-link")
+        .masquerade_as_nightly_cargo(&["artifact-link"])
+        .env("CARGO_BUILD_ARTIFACT_LINK", "copy")
+        .run();
+    let meta = p.bin("foo").symlink_metadata().unwrap();
+    assert!(meta.is_file());
+    assert_eq!(meta.nlink(), 1);
+
+    p.cargo("build -Zartifact-link")
+        .masquerade_as_nightly_cargo(&["artifact-link"])
+        .env("CARGO_BUILD_ARTIFACT_LINK", "symlink")
+        .run();
+    assert!(p.bin("foo").symlink_metadata().unwrap().is_symlink());
+    p.process(&p.bin("foo")).run();
+}
+
+#[cfg(target_os = "linux")]
+#[cargo_test]
+fn out_dir_uses_strategy() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("build -Z unstable-options -Zartifact-link --out-dir out")
+        .masquerade_as_nightly_cargo(&["out-dir", "artifact-link"])
+        .env("CARGO_BUILD_ARTIFACT_LINK", "symlink")
+        .run();
+    assert!(p
+        .root()
+        .join("out/foo")
+        .symlink_metadata()
+        .unwrap()
+        .is_symlink());
+}
@@ -1666,7 +1666,56 @@ fn json_artifact_includes_executable_for_benchmark() {
                 }
 
                 {"reason": "build-finished", "success": true}
+
+                {
+                    "reason": "test-executable",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "manifest_path": "[..]/foo/Cargo.toml",
+                    "target": "{...}",
+                    "features": [],
+                    "executable": "[..]/foo/target/release/deps/benchmark-[..][EXE]",
+                    "program": "[..]/foo/target/release/deps/benchmark-[..][EXE]",
+                    "args": ["--bench"],
+                    "cwd": "[..]/foo",
+                    "env": "{...}"
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn pass_through_args_file() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [[bench]]
+                name = "b"
+                harness = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "benches/b.rs",
+            r#"
+                fn main() {
+                    let args: Vec<_> = std::env::args().skip(1).collect();
+                    assert_eq!(args, ["hello world", "--flag", "", "last", "--bench"]);
+                }
             "#,
         )
+        .file("args.txt", "hello world\n--flag\n\n")
+        .build();
+
+    p.cargo("bench --bench b -Zunstable-options --args-file args.txt -v -- last")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains(
+            "[RUNNING] `[CWD]/target/release/deps/b-[..][EXE] [..]hello world[..] --flag [..] last --bench`",
+        )
         .run();
 }
@@ -0,0 +1,57 @@
+//! Tests for `cargo::ops::build_closure`.
+
+use cargo::{
+    core::compiler::CompileMode,
+    core::{Shell, Workspace},
+    ops::{self, CompileOptions},
+    Config,
+};
+use cargo_test_support::{basic_manifest, paths, project};
+use std::env;
+
+#[cargo_test]
+fn files_map_to_units() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "mod a;")
+        .file("src/a.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    let shell = Shell::from_write(Box::new(Vec::new()));
+    let config = Config::new(shell, env::current_dir().unwrap(), paths::home());
+    let ws = Workspace::new(&p.root().join("Cargo.toml"), &config).unwrap();
+    let compile_options = CompileOptions::new(ws.config(), CompileMode::Build).unwrap();
+
+    // Nothing is known before the first build.
+    let closure = ops::build_closure(&ws, &compile_options).unwrap();
+    assert_eq!(closure.unit_graph.units.len(), 2);
+    assert!(closure.files.is_empty());
+    // Nothing is written either.
+    assert!(!p.root().join("target/debug").exists());
+
+    ops::compile(&ws, &compile_options).unwrap();
+    let closure = ops::build_closure(&ws, &compile_options).unwrap();
+
+    let names = |path: &str| -> Vec<String> {
+        closure
+            .units_for(&p.root().join(path))
+            .map(|unit| unit.target.name().to_string())
+            .collect()
+    };
+    assert_eq!(names("src/lib.rs"), ["foo"]);
+    assert_eq!(names("src/a.rs"), ["foo"]);
+    assert_eq!(names("bar/src/lib.rs"), ["bar"]);
+    assert!(names("src/unknown.rs").is_empty());
+}
@@ -220,3 +220,126 @@ fn build_plan_with_dev_dep() {
         .masquerade_as_nightly_cargo(&["build-plan"])
         .run();
 }
+
+#[cargo_test]
+fn cargo_build_plan_v2_build_script() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                authors = []
+                links = "foo"
+                build = "build.rs"
+            "#,
+        )
+        .file("src/main.rs", r#"fn main() {}"#)
+        .file("build.rs", r#"fn main() {}"#)
+        .build();
+
+    p.cargo("build --build-plan=2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_json(
+            r#"
+            {
+                "version": 2,
+                "inputs": [
+                    "[..]/foo/Cargo.toml"
+                ],
+                "invocations": [
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": "{...}",
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "rustc",
+                        "target_kind": ["custom-build"],
+                        "compile_mode": "build",
+                        "package_id": "foo 0.5.0 [..]",
+                        "target_name": "build-script-build",
+                        "metadata": "[..]",
+                        "features": [],
+                        "dep_edges": [],
+                        "build_script": null,
+                        "package_links": "foo"
+                    },
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [0],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": [],
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "[..]/build-script-build",
+                        "target_kind": ["custom-build"],
+                        "compile_mode": "run-custom-build",
+                        "package_id": "foo 0.5.0 [..]",
+                        "target_name": "build-script-build",
+                        "metadata": "[..]",
+                        "features": [],
+                        "dep_edges": [
+                            {
+                                "index": 0,
+                                "extern_crate_name": "build_script_build",
+                                "public": false,
+                                "noprelude": false
+                            }
+                        ],
+                        "build_script": null,
+                        "package_links": "foo"
+                    },
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [1],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": "{...}",
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "rustc",
+                        "target_kind": ["bin"],
+                        "compile_mode": "build",
+                        "package_id": "foo 0.5.0 [..]",
+                        "target_name": "foo",
+                        "metadata": "[..]",
+                        "features": [],
+                        "dep_edges": [
+                            {
+                                "index": 1,
+                                "extern_crate_name": "build_script_build",
+                                "public": false,
+                                "noprelude": false
+                            }
+                        ],
+                        "build_script": 1,
+                        "package_links": "foo"
+                    }
+                ]
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn cargo_build_plan_invalid_version() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build --build-plan=3 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_status(101)
+        .with_stderr("[ERROR] invalid build plan version `3`, expected `1` or `2`")
+        .run();
+}
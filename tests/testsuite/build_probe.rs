@@ -0,0 +1,263 @@
+//! Tests for the `[build-probe]` table.
+
+use cargo_test_support::project;
+
+/// A fake `pkg-config` knowing of the library `foo`, at version 1.2.
+const PKG_CONFIG: &str = r#"#!/bin/sh
+case "$1" in
+    --atleast-version=1.*) [ "$2" = foo ] ;;
+    --atleast-version=*) exit 1 ;;
+    --libs) [ "$2" = foo ] && echo "-L/opt/foo/lib -lfoo" ;;
+    *) exit 1 ;;
+esac
+"#;
+
+#[cargo_test]
+fn feature_required() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.foo]
+                cfgs = ["has_foo"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  feature `build-probe` is required
+
+  The package requires the Cargo feature called `build-probe`, but that feature is \
+  not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = [\"build-probe\"]` to the top of Cargo.toml \
+  (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#build-probe \
+  for more information about the status of this feature.
+",
+        )
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn found() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.foo]
+                version = ">=1.0"
+                cfgs = ["has_foo"]
+                fallback-cfgs = ["no_foo"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"#[cfg(not(has_foo))] compile_error!("has_foo is not set");"#,
+        )
+        .executable("pkg-config", PKG_CONFIG)
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .env("PKG_CONFIG", p.root().join("pkg-config"))
+        .with_stderr_does_not_contain("[..]--crate-name build_probe_foo[..]")
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-L native=/opt/foo/lib[..]--cfg has_foo[..]`",
+        )
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn not_found_fallback_cfgs() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.bar]
+                cfgs = ["has_bar"]
+                fallback-cfgs = ["no_bar"]
+
+                [build-probe.old-foo]
+                pkg-config = "foo"
+                version = "2.0"
+                fallback-cfgs = ["old_foo"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(not(all(no_bar, old_foo)))] compile_error!("fallback cfgs are not set");
+                #[cfg(has_bar)] compile_error!("has_bar is set");
+            "#,
+        )
+        .executable("pkg-config", PKG_CONFIG)
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .env("PKG_CONFIG", p.root().join("pkg-config"))
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn not_found_runs_build_script() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.bar]
+                cfgs = ["has_bar"]
+                fallback-cfgs = ["no_bar"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(not(from_script))] compile_error!("the build script didn't run");
+                #[cfg(any(has_bar, no_bar))] compile_error!("the probe was used");
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rustc-cfg=from_script"); }"#,
+        )
+        .executable("pkg-config", PKG_CONFIG)
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .env("PKG_CONFIG", p.root().join("pkg-config"))
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn found_skips_build_script() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.foo]
+                cfgs = ["has_foo"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { panic!("the build script ran"); }"#,
+        )
+        .executable("pkg-config", PKG_CONFIG)
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .env("PKG_CONFIG", p.root().join("pkg-config"))
+        .with_stderr_does_not_contain("[..]--crate-name build_script_build[..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]--cfg has_foo[..]`")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_version() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-probe.foo]
+                version = "^1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  invalid `version` of `build-probe.foo`: `^1.0`
+
+Caused by:
+  only `>=`, `<=` and `=` comparisons are supported by `pkg-config`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn virtual_manifest() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-probe"]
+
+                [workspace]
+                members = []
+
+                [build-probe.foo]
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-probe"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  this virtual manifest specifies a [build-probe] section, which is not allowed
+",
+        )
+        .run();
+}
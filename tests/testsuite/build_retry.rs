@@ -0,0 +1,176 @@
+//! Tests for retrying units which failed for reasons unrelated to their code
+//! (`build.retry`).
+
+use cargo_test_support::{basic_manifest, project};
+
+/// A build script which fails the first time it runs, after running `FAIL`.
+const FLAKY_BUILD_RS: &str = r#"
+    use std::path::Path;
+
+    fn main() {
+        let marker = Path::new(env!("CARGO_MANIFEST_DIR")).join("attempted");
+        if !marker.exists() {
+            std::fs::write(&marker, "").unwrap();
+            FAIL
+        }
+    }
+"#;
+
+const NETWORK_FAILURE: &str = r#"
+    eprintln!("error: Connection refused (os error 111)");
+    std::process::exit(1);
+"#;
+
+#[cargo_test]
+fn retry_network_failure_of_build_script() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("build.rs", &FLAKY_BUILD_RS.replace("FAIL", NETWORK_FAILURE))
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                retry = { count = 1, classify = ["network"] }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zbuild-retry -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-retry", "build-summary"])
+        .with_stderr_contains(
+            "note: build script of `foo v0.1.0 ([CWD])` failed with a network error, \
+             running it again (retry 1 of 1)",
+        )
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .with_stderr_contains("[..]Retried `foo` (build script) network")
+        .run();
+}
+
+#[cargo_test]
+fn retry_in_json_summary() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("build.rs", &FLAKY_BUILD_RS.replace("FAIL", NETWORK_FAILURE))
+        .file(".cargo/config.toml", "[build]\nretry = {}")
+        .build();
+
+    p.cargo("build -Zbuild-retry -Zbuild-summary --message-format=json")
+        .masquerade_as_nightly_cargo(&["build-retry", "build-summary"])
+        .with_stdout_contains(
+            r#"{"reason":"build-summary",[..]"retried":[{"package_id":"foo 0.1.0 [..]",[..]"mode":"run-custom-build","reason":"network"}],[..]}"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+#[cfg(unix)]
+fn retry_sigkill() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            &FLAKY_BUILD_RS.replace(
+                "FAIL",
+                r#"
+                    std::process::Command::new("kill")
+                        .args(["-9", &std::process::id().to_string()])
+                        .status()
+                        .unwrap();
+                    std::thread::sleep(std::time::Duration::from_secs(60));
+                "#,
+            ),
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                retry = { classify = ["sigkill"] }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zbuild-retry")
+        .masquerade_as_nightly_cargo(&["build-retry"])
+        .with_stderr_contains(
+            "note: build script of `foo v0.1.0 ([CWD])` was killed by SIGKILL, \
+             running it again (retry 1 of 1)",
+        )
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .run();
+}
+
+#[cargo_test]
+fn no_retry_of_unclassified_failure() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("build.rs", &FLAKY_BUILD_RS.replace("FAIL", NETWORK_FAILURE))
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                retry = { count = 3, classify = ["sigkill"] }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zbuild-retry")
+        .masquerade_as_nightly_cargo(&["build-retry"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] failed to run custom build command for `foo v0.1.0 ([CWD])`")
+        .with_stderr_does_not_contain("note: build script[..]")
+        .run();
+}
+
+#[cargo_test]
+fn no_retry_of_compile_errors() {
+    let p = project()
+        .file("src/lib.rs", "compile_error!(\"Connection refused\");")
+        .file(".cargo/config.toml", "[build]\nretry = { count = 3 }")
+        .build();
+
+    p.cargo("build -Zbuild-retry")
+        .masquerade_as_nightly_cargo(&["build-retry"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib) due to previous error")
+        .with_stderr_does_not_contain("note: rustc[..]")
+        .run();
+}
+
+#[cargo_test]
+fn retry_requires_flag() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("build.rs", &FLAKY_BUILD_RS.replace("FAIL", NETWORK_FAILURE))
+        .file(".cargo/config.toml", "[build]\nretry = { count = 1 }")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] failed to run custom build command for `foo v0.1.0 ([CWD])`")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_classification() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            "[build]\nretry = { classify = [\"timeout\"] }",
+        )
+        .build();
+
+    p.cargo("build -Zbuild-retry")
+        .masquerade_as_nightly_cargo(&["build-retry"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.retry.classify` must only contain `sigkill` or `network`, \
+             found `timeout`",
+        )
+        .run();
+}
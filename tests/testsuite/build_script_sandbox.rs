@@ -0,0 +1,163 @@
+//! Tests for `-Zbuild-script-sandbox`.
+
+use std::env;
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check -Zbuild-script-sandbox")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `-Z` flag is only accepted on the nightly channel of Cargo[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn well_behaved_build_script() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::fs;
+                use std::path::Path;
+
+                fn main() {
+                    assert!(env::var("SANDBOX_SECRET").is_err());
+                    let out_dir = env::var("OUT_DIR").unwrap();
+                    fs::write(Path::new(&out_dir).join("generated.rs"), "").unwrap();
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-sandbox")
+        .masquerade_as_nightly_cargo(&["build-script-sandbox"])
+        .env("SANDBOX_SECRET", "1")
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .with_stderr_does_not_contain("[..]build script of `foo[..]")
+        .run();
+}
+
+#[cargo_test]
+fn warns_once_without_sandboxing_facility() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file("a/build.rs", "fn main() {}")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .file("b/build.rs", "fn main() {}")
+        .build();
+
+    let output = p
+        .cargo("check -Zbuild-script-sandbox")
+        .masquerade_as_nightly_cargo(&["build-script-sandbox"])
+        .exec_with_output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let warnings = stderr
+        .matches("no sandboxing facility was found for `-Zbuild-script-sandbox`")
+        .count();
+    let has_bwrap = env::var_os("PATH").map_or(false, |path| {
+        env::split_paths(&path).any(|dir| dir.join("bwrap").is_file())
+    });
+    let expected = if cfg!(target_os = "macos") || (cfg!(target_os = "linux") && has_bwrap) {
+        0
+    } else {
+        1
+    };
+    assert_eq!(warnings, expected, "{stderr}");
+}
+
+#[cargo_test]
+fn hidden_env_fails_in_sandbox() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rerun-if-env-changed=SANDBOX_SECRET");
+                    std::env::var("SANDBOX_SECRET").unwrap();
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-sandbox")
+        .masquerade_as_nightly_cargo(&["build-script-sandbox"])
+        .env("SANDBOX_SECRET", "1")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+
+Caused by:
+  build script of `foo v0.0.1 ([CWD])` failed in the sandbox of `-Zbuild-script-sandbox`
+  It may need network access, to write outside of `OUT_DIR`, or environment variables hidden by the sandbox.
+
+Caused by:
+  process didn't exit successfully: [..]
+",
+        )
+        .run();
+
+    p.cargo("check")
+        .env("SANDBOX_SECRET", "1")
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .run();
+}
+
+#[cargo_test]
+fn modified_package_files() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("data.txt", "original")
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::fs;
+                use std::path::Path;
+
+                fn main() {
+                    let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+                    let data = Path::new(&root).join("data.txt");
+                    // The write is blocked where the platform can sandbox
+                    // the build script.
+                    let _ = fs::write(&data, "modified by the build script");
+                }
+            "#,
+        )
+        .build();
+
+    let output = p
+        .cargo("check -Zbuild-script-sandbox")
+        .masquerade_as_nightly_cargo(&["build-script-sandbox"])
+        .exec_with_output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let modified = p.read_file("data.txt") != "original";
+    assert_eq!(
+        stderr.contains("modified `data.txt`, outside of `OUT_DIR`"),
+        modified,
+        "{stderr}"
+    );
+}
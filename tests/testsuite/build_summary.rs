@@ -0,0 +1,176 @@
+//! Tests for the summary printed at the end of builds (`-Zbuild-summary`).
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn summary_after_finished() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .build();
+
+    p.cargo("build -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .with_stderr_contains("[..]Summary 2 units compiled, 0 fresh, critical path [..]s")
+        .with_stderr_contains("[..]Slowest `[..]` ([..]) [..]s, `[..]` ([..]) [..]s")
+        .with_stderr_contains("[..]Warnings `bar v0.1.0` 1")
+        .with_stderr_contains("[..]Artifact [CWD]/target/debug/foo[EXE]")
+        .run();
+
+    p.cargo("build -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_stderr_contains("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]")
+        .with_stderr_contains("[..]Summary 0 units compiled, 2 fresh, critical path 0.00s")
+        .with_stderr_contains("[..]Warnings `bar v0.1.0` 1")
+        .with_stderr_contains("[..]Artifact [CWD]/target/debug/foo[EXE]")
+        .run();
+}
+
+#[cargo_test]
+fn summary_requires_flag() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .build();
+
+    p.cargo("build")
+        .with_stderr_does_not_contain("[..]Summary[..]")
+        .run();
+
+    p.cargo("build --message-format=json")
+        .with_stdout_does_not_contain("[..]\"build-summary\"[..]")
+        .run();
+}
+
+#[cargo_test]
+fn summary_disabled() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .file(".cargo/config.toml", "[term]\nsummary = false")
+        .build();
+
+    p.cargo("build -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_stderr_does_not_contain("[..]Summary[..]")
+        .run();
+}
+
+#[cargo_test]
+fn summary_quiet() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .build();
+
+    p.cargo("build -q -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_stderr_does_not_contain("[..]Summary[..]")
+        .run();
+}
+
+#[cargo_test]
+fn summary_json() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .build();
+
+    p.cargo("build --message-format=json -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_json_contains_unordered(
+            r#"
+                {
+                    "reason": "build-summary",
+                    "compiled": 2,
+                    "fresh": 0,
+                    "duration": "{...}",
+                    "critical_path": "{...}",
+                    "slowest": "{...}",
+                    "warnings": [
+                        {
+                            "package_id": "bar 0.1.0 (path+file:///[..]/foo/bar)",
+                            "count": 1
+                        }
+                    ],
+                    "artifacts": ["[..]/foo/target/debug/foo[..]"]
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn summary_not_printed_on_failure() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "fn main() { error }")
+        .build();
+
+    p.cargo("build -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]Summary[..]")
+        .run();
+}
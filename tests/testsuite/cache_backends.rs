@@ -0,0 +1,254 @@
+//! Tests for `-Zcache-backends` and `cargo clean --cache-too`.
+
+use std::path::PathBuf;
+
+use cargo_test_support::{basic_manifest, paths, project};
+
+/// Builds a cache backend which appends its requests to `requests.jsonl` in
+/// the directory it is given, and answers with `response.json` of that
+/// directory, or fails if there's a `fail` file.
+fn backend_process() -> PathBuf {
+    let p = project()
+        .at(paths::root().join("backend"))
+        .file("Cargo.toml", &basic_manifest("backend", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::io::{BufRead, Write};
+                fn main() {
+                    let dir = std::path::PathBuf::from(std::env::args().nth(1).unwrap());
+                    let mut request = String::new();
+                    std::io::stdin().lock().read_line(&mut request).unwrap();
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(dir.join("requests.jsonl"))
+                        .unwrap()
+                        .write_all(request.as_bytes())
+                        .unwrap();
+                    if dir.join("fail").exists() {
+                        eprintln!("cache is unavailable");
+                        std::process::exit(1);
+                    }
+                    match std::fs::read_to_string(dir.join("response.json")) {
+                        Ok(response) => print!("{response}"),
+                        Err(_) => print!("{{}}"),
+                    }
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build").run();
+    p.bin("backend")
+}
+
+fn requests() -> Vec<serde_json::Value> {
+    std::fs::read_to_string(paths::root().join("state/requests.jsonl"))
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    p.cargo("build").run();
+    p.cargo("clean").run();
+    assert!(requests().is_empty());
+}
+
+#[cargo_test]
+fn cache_too_requires_flag() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    p.cargo("clean --cache-too")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--cache-too` flag is unstable, pass `-Z cache-backends` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn notified_on_clean() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    p.cargo("build").run();
+    p.cargo("clean -p foo --release -Zcache-backends")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .with_stderr("")
+        .run();
+
+    let requests = requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request["v"], 1);
+    assert_eq!(request["event"], "clean");
+    assert_eq!(request["packages"], serde_json::json!(["foo"]));
+    assert_eq!(request["profile"], "release");
+    assert_eq!(request["doc"], false);
+    assert_eq!(request["purge"], false);
+    assert_eq!(
+        request["target-dir"].as_str().unwrap(),
+        p.root().join("target").to_str().unwrap()
+    );
+}
+
+#[cargo_test]
+fn not_notified_on_dry_run() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    p.cargo("build").run();
+    p.cargo("clean --dry-run -Zcache-backends -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .run();
+    assert!(requests().is_empty());
+}
+
+#[cargo_test]
+fn failure_is_a_warning() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    std::fs::write(paths::root().join("state/fail"), "").unwrap();
+    p.cargo("build").run();
+    p.cargo("clean -Zcache-backends")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .with_stderr_contains("[WARNING] failed to notify cache backend `shared` of `cargo clean`")
+        .with_stderr_contains("[..]cache is unavailable")
+        .run();
+    assert!(!p.root().join("target").exists());
+}
+
+#[cargo_test]
+fn cache_too() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    std::fs::write(
+        paths::root().join("state/response.json"),
+        r#"{"removed":3}"#,
+    )
+    .unwrap();
+    p.cargo("build").run();
+    p.cargo("clean --cache-too -Zcache-backends")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .with_stderr("[..]Purged 3 entries from `shared`")
+        .run();
+    assert_eq!(requests()[0]["purge"], true);
+}
+
+#[cargo_test]
+fn cache_too_fails_with_backend() {
+    let process = backend_process();
+    let state = paths::root().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[cache-backends.shared]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                state.display()
+            ),
+        )
+        .build();
+    std::fs::write(paths::root().join("state/fail"), "").unwrap();
+    p.cargo("clean --cache-too -Zcache-backends")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] cache backend `shared` failed to process `cargo clean`")
+        .run();
+}
+
+#[cargo_test]
+fn cache_too_without_backend() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("clean --cache-too -Zcache-backends")
+        .masquerade_as_nightly_cargo(&["cache-backends"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `--cache-too` requires a cache backend to be configured in `cache-backends`",
+        )
+        .run();
+}
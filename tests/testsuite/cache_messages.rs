@@ -153,6 +153,47 @@ fn cached_as_json() {
     assert_eq!(fix_fresh, orig_cargo_out);
 }
 
+#[cargo_test]
+fn cached_as_json_with_mixed_changes() {
+    // Diagnostics of fresh units are replayed next to the ones of rebuilt
+    // units, so that JSON consumers always see all of them.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "fn unused_in_a() {}")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "fn unused_in_b() {}")
+        .build();
+
+    p.cargo("check --message-format=json").run();
+
+    p.change_file(
+        "b/src/lib.rs",
+        "fn unused_in_b() {}\nfn also_unused_in_b() {}",
+    );
+    let output = p
+        .cargo("check --message-format=json")
+        .exec_with_output()
+        .expect("cargo to run");
+    assert!(output.status.success());
+    let stdout = as_str(&output.stdout);
+    let messages: Vec<_> = stdout
+        .lines()
+        .filter(|line| line.contains("\"reason\":\"compiler-message\""))
+        .collect();
+    assert!(messages.iter().any(|m| m.contains("unused_in_a")));
+    assert!(messages.iter().any(|m| m.contains("also_unused_in_b")));
+    assert!(stdout
+        .lines()
+        .any(|line| line.contains("\"name\":\"a\"") && line.contains("\"fresh\":true")));
+}
+
 #[cargo_test]
 fn clears_cache_after_fix() {
     // Make sure the cache is invalidated when there is no output.
@@ -17,6 +17,8 @@ mod dev_build_conflict;
 mod dev_prefer_existing_version;
 mod dry_run;
 mod empty_dep_table;
+mod estimate_dry_run;
+mod estimate_unstable;
 mod features;
 mod features_empty;
 mod features_multiple_occurrences;
@@ -71,7 +71,7 @@ fn get_toml() {
     // - The "extra-table" is shown without a warning. I'm not sure how that
     //   should be handled, since displaying warnings could cause problems
     //   with ingesting the output.
-    // - Environment variables aren't loaded. :(
+    // - Environment variables are only loaded for keys in a config file.
     let sub_folder = common_setup();
     cargo_process("config get -Zunstable-options")
         .cwd(&sub_folder.parent().unwrap())
@@ -83,7 +83,7 @@ fn get_toml() {
             "\
 alias.foo = \"abc --xyz\"
 alias.sub-example = [\"sub\", \"example\"]
-build.jobs = 99
+build.jobs = 100
 build.rustflags = [\"--flag-directory\", \"--flag-global\"]
 extra-table.somekey = \"somevalue\"
 profile.dev.opt-level = 3
@@ -158,7 +158,7 @@ profile.dev.package.foo.opt-level = 1
 #[cargo_test]
 fn get_json() {
     // Notes:
-    // - This does not show env vars at all. :(
+    // - Environment variables are only loaded for keys in a config file.
     let all_json = r#"
             {
               "alias": {
@@ -201,7 +201,7 @@ fn get_json() {
         .masquerade_as_nightly_cargo(&["cargo-config"])
         .env("CARGO_ALIAS_BAR", "cat dog")
         .env("CARGO_BUILD_JOBS", "100")
-        .with_json(all_json)
+        .with_json(&all_json.replace("\"jobs\": 99", "\"jobs\": 100"))
         .with_stderr(
             "\
 note: The following environment variables may affect the loaded values.
@@ -291,6 +291,42 @@ build.rustflags = [
         .run();
 }
 
+#[cargo_test]
+fn show_origin_toml_env() {
+    let sub_folder = common_setup();
+    cargo_process("config get --show-origin profile -Zunstable-options")
+        .cwd(&sub_folder.parent().unwrap())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .env("CARGO_PROFILE_DEV_OPT_LEVEL", "2")
+        .with_stdout(
+            "\
+profile.dev.opt-level = 2 # environment variable `CARGO_PROFILE_DEV_OPT_LEVEL`
+profile.dev.package.foo.opt-level = 1 # [ROOT]/home/.cargo/config.toml
+# The following environment variables may affect the loaded values.
+# CARGO_PROFILE_DEV_OPT_LEVEL=2
+",
+        )
+        .with_stderr("")
+        .run();
+
+    // `--config` still takes precedence.
+    cargo_process("config get --show-origin profile -Zunstable-options")
+        .arg("--config=profile.dev.opt-level=1")
+        .cwd(&sub_folder.parent().unwrap())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .env("CARGO_PROFILE_DEV_OPT_LEVEL", "2")
+        .with_stdout(
+            "\
+profile.dev.opt-level = 1 # --config cli option
+profile.dev.package.foo.opt-level = 1 # [ROOT]/home/.cargo/config.toml
+# The following environment variables may affect the loaded values.
+# CARGO_PROFILE_DEV_OPT_LEVEL=2
+",
+        )
+        .with_stderr("")
+        .run();
+}
+
 #[cargo_test]
 fn show_origin_toml_cli() {
     let sub_folder = common_setup();
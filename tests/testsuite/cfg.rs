@@ -212,6 +212,55 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn unknown_predicate() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+                authors = []
+
+                [target.'cfg(accessible(::std::foo))'.dependencies]
+                b = { path = 'b' }
+                [target.'cfg(any(unix, windows, version("1.0")))'.dependencies]
+                c = { path = 'c' }
+                [target.'cfg(not(accessible(::std::foo)))'.dependencies]
+                d = { path = 'd' }
+                [target.'cfg(any(unix, windows))'.dependencies]
+                e = { path = 'e' }
+            "#,
+        )
+        .file("src/lib.rs", "extern crate e;")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.0.1"))
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_manifest("c", "0.0.1"))
+        .file("c/src/lib.rs", "")
+        .file("d/Cargo.toml", &basic_manifest("d", "0.0.1"))
+        .file("d/src/lib.rs", "")
+        .file("e/Cargo.toml", &basic_manifest("e", "0.0.1"))
+        .file("e/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_stderr_unordered(
+            "\
+[WARNING] Found `accessible(::std::foo)` in `target.'cfg(...)'.dependencies`. \
+This predicate is not supported by this version of Cargo, the table using it will never match.
+[WARNING] Found `version(\"1.0\")` in `target.'cfg(...)'.dependencies`. \
+This predicate is not supported by this version of Cargo, the table using it will never match.
+[WARNING] Found `accessible(::std::foo)` in `target.'cfg(...)'.dependencies`. \
+This predicate is not supported by this version of Cargo, the table using it will never match.
+[CHECKING] e v0.0.1 ([..])
+[CHECKING] a v0.0.1 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn multiple_match_ok() {
     let p = project()
@@ -869,6 +869,55 @@ fn check_keep_going() {
         .run();
 }
 
+#[cargo_test]
+fn check_keep_going_summary() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bad", "good", "user"]
+            "#,
+        )
+        .file("bad/Cargo.toml", &basic_manifest("bad", "0.1.0"))
+        .file("bad/src/lib.rs", "compile_error!(\"BAD\");")
+        .file("good/Cargo.toml", &basic_manifest("good", "0.1.0"))
+        .file("good/src/lib.rs", "")
+        .file(
+            "user/Cargo.toml",
+            r#"
+                [package]
+                name = "user"
+                version = "0.1.0"
+
+                [dependencies]
+                bad = { path = "../bad" }
+            "#,
+        )
+        .file("user/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -j1 --keep-going -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["keep-going"])
+        .with_status(101)
+        .with_stderr_contains("[CHECKING] good v0.1.0 ([CWD]/good)")
+        .with_stderr_contains(
+            "\
+[NOTE] the following targets failed to compile:
+  `bad` (lib)
+1 other unit was not built because it depends on them
+",
+        )
+        .with_stderr_does_not_contain("[CHECKING] user [..]")
+        .run();
+
+    // Without `--keep-going`, the build stops at the first failure.
+    p.cargo("check -j1")
+        .with_status(101)
+        .with_stderr_does_not_contain("[NOTE] the following targets failed to compile:")
+        .run();
+}
+
 #[cargo_test]
 fn does_not_use_empty_rustc_wrapper() {
     // An empty RUSTC_WRAPPER environment variable won't be used.
@@ -1026,6 +1075,109 @@ fn rustc_workspace_wrapper_excludes_published_deps() {
         .run();
 }
 
+#[cargo_test]
+fn rustc_workspace_wrapper_skipping_compilation() {
+    // Skips the compilation of `foo`, but not the queries of the target info.
+    let wrapper = project()
+        .at("wrapper")
+        .file("Cargo.toml", &basic_manifest("wrapper", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let args: Vec<String> = std::env::args().skip(1).collect();
+                    if args.windows(2).any(|w| w[0] == "--crate-name" && w[1] == "foo") {
+                        return;
+                    }
+                    let status = std::process::Command::new(&args[0])
+                        .args(&args[1..])
+                        .status()
+                        .unwrap();
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            "#,
+        )
+        .build();
+    wrapper.cargo("build").run();
+
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("wrapper"))
+        .with_status(101)
+        .with_stderr(
+            "\
+[CHECKING] foo v0.0.1 ([CWD])
+[ERROR] rustc did not produce the expected output `[CWD]/target/debug/deps/libfoo-[..].rmeta` \
+while compiling `foo` (lib)
+
+[NOTE] this can happen when a `rustc` wrapper skips the compilation or writes its outputs elsewhere
+",
+        )
+        .run();
+
+    // Extra arguments of `cargo rustc` may stop rustc before it writes
+    // anything.
+    p.cargo("rustc -- -Zunpretty=expanded")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("wrapper"))
+        .run();
+}
+
+#[cargo_test]
+fn rustc_wrappers_from_config() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                baz = "1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[build]\nrustc-workspace-wrapper = '{}'\n",
+                tools::echo_wrapper().display()
+            ),
+        )
+        .build();
+
+    Package::new("baz", "1.0.0").publish();
+
+    p.cargo("check -v")
+        .with_stderr_contains("WRAPPER CALLED: rustc --crate-name foo [..]")
+        .with_stderr_contains("[CHECKING] baz [..]")
+        .with_stderr_does_not_contain("WRAPPER CALLED: rustc --crate-name baz [..]")
+        .run();
+
+    // With both wrappers, every unit goes through `rustc-wrapper`, and
+    // workspace members additionally get `rustc-workspace-wrapper` inside it.
+    p.change_file(
+        ".cargo/config.toml",
+        &format!(
+            "[build]\nrustc-wrapper = '{0}'\nrustc-workspace-wrapper = '{0}'\n",
+            tools::echo_wrapper().display()
+        ),
+    );
+    p.cargo("clean").run();
+    p.cargo("check -v")
+        .with_stderr_contains(
+            "WRAPPER CALLED: [..]rustc-echo-wrapper[EXE] rustc --crate-name foo [..]",
+        )
+        .with_stderr_contains("WRAPPER CALLED: rustc --crate-name foo [..]")
+        .with_stderr_contains("WRAPPER CALLED: rustc --crate-name baz [..]")
+        .with_stderr_does_not_contain(
+            "WRAPPER CALLED: [..]rustc-echo-wrapper[EXE] rustc --crate-name baz [..]",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn warn_manifest_package_and_project() {
     let p = project()
@@ -673,3 +673,139 @@ fn clean_spec_reserved() {
         )
         .run();
 }
+
+#[cargo_test]
+fn clean_spec_glob() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                dep1 = { path = "dep1" }
+                dep2 = { path = "dep2" }
+                other = { path = "other" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("dep1/Cargo.toml", &basic_manifest("dep1", "0.0.1"))
+        .file("dep1/src/lib.rs", "")
+        .file("dep2/Cargo.toml", &basic_manifest("dep2", "0.0.1"))
+        .file("dep2/src/lib.rs", "")
+        .file("other/Cargo.toml", &basic_manifest("other", "0.0.1"))
+        .file("other/src/lib.rs", "")
+        .build();
+
+    p.cargo("build").run();
+
+    p.cargo("clean -p 'nope*'")
+        .with_status(101)
+        .with_stderr("[ERROR] package pattern `nope*` did not match any packages")
+        .run();
+
+    p.cargo("clean -p 'dep*'").run();
+    assert!(p.glob("target/debug/deps/libdep*").next().is_none());
+    assert!(p.glob("target/debug/deps/libother-*").next().is_some());
+    assert!(p.glob("target/debug/deps/libfoo-*").next().is_some());
+}
+
+#[cargo_test]
+fn clean_doc_spec() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.0.1"))
+        .file("a/src/lib.rs", "")
+        .build();
+
+    p.cargo("doc").run();
+    let doc_path = p.build_dir().join("doc");
+    assert!(doc_path.join("a").is_dir());
+
+    p.cargo("clean --doc -p a").run();
+
+    assert!(!doc_path.join("a").exists());
+    assert!(!doc_path.join("src/a").exists());
+    assert!(doc_path.join("foo").is_dir());
+    assert!(p.glob("target/debug/deps/liba-*").next().is_some());
+}
+
+#[cargo_test]
+fn dry_run_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("clean --dry-run")
+        .masquerade_as_nightly_cargo(&["clean --dry-run"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--dry-run` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dry_run() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build").run();
+    p.cargo("clean --dry-run -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["clean --dry-run"])
+        .with_stderr(
+            "\
+[SUMMARY] [..] files, [..] total
+[WARNING] no files deleted due to --dry-run
+",
+        )
+        .run();
+    assert!(p.glob("target/debug/deps/libfoo-*").next().is_some());
+
+    p.cargo("clean --dry-run -p foo -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["clean --dry-run"])
+        .with_stderr_contains("[REMOVING] [CWD]/target/debug/deps/libfoo-[..].rlib")
+        .with_stderr_contains("[SUMMARY] [..] files, [..] total")
+        .run();
+    assert!(p.glob("target/debug/deps/libfoo-*").next().is_some());
+}
+
+#[cargo_test]
+fn keep_days() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build").run();
+    let old = p
+        .glob("target/debug/deps/libfoo-*.rmeta")
+        .next()
+        .unwrap()
+        .unwrap();
+    let old_time = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_mtime(&old, old_time).unwrap();
+
+    p.cargo("clean --keep-days 7 --dry-run -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["clean --keep-days"])
+        .with_stderr_contains("[SUMMARY] 1 file, [..] total")
+        .run();
+    assert!(old.exists());
+
+    p.cargo("clean --keep-days 7 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["clean --keep-days"])
+        .with_stderr("[..]Removed 1 file, [..] total")
+        .run();
+    assert!(!old.exists());
+    assert!(p.bin("foo").exists());
+}
@@ -1524,6 +1524,7 @@ fn all_profile_options() {
         package: None,
         build_override: None,
         rustflags: None,
+        target_dir: None,
     };
     let mut overrides = BTreeMap::new();
     let key = cargo_toml::ProfilePackageSpec::Spec(PackageIdSpec::parse("foo").unwrap());
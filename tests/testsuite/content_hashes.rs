@@ -0,0 +1,298 @@
+//! Tests for `-Zcontent-hashes` and `cargo verify-lockfile`.
+
+use std::fs;
+
+use cargo_test_support::paths;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, git, project, t};
+
+/// Modifies the source of `bar` in its git checkout.
+fn tamper_with_checkout() {
+    let mut checkouts = t!(glob::glob(
+        paths::home()
+            .join(".cargo/git/checkouts/bar-*/*")
+            .to_str()
+            .unwrap()
+    ));
+    let checkout = checkouts.next().unwrap().unwrap();
+    t!(fs::write(
+        checkout.join("src/lib.rs"),
+        "pub fn bar() { println!(\"hi\") }"
+    ));
+}
+
+#[cargo_test]
+fn git_hash_recorded() {
+    let git = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "pub fn bar() {}")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    bar = {{ git = '{}' }}
+                "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("fetch -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("content-hash = \""), "{lock}");
+
+    // Without the flag the hash is kept as is.
+    p.cargo("check").run();
+    assert_eq!(p.read_lockfile(), lock);
+}
+
+#[cargo_test]
+fn git_hash_not_recorded_without_flag() {
+    let git = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "pub fn bar() {}")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    bar = {{ git = '{}' }}
+                "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("fetch").run();
+    assert!(!p.read_lockfile().contains("content-hash"));
+}
+
+#[cargo_test]
+fn git_tampering_locked() {
+    let git = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "pub fn bar() {}")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    bar = {{ git = '{}' }}
+                "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+    tamper_with_checkout();
+
+    p.cargo("check --locked -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the contents of `bar v0.1.0 ([..])` don't match the `content-hash` in Cargo.lock",
+        )
+        .with_stderr_contains("The files at `[..]` may have been tampered with.")
+        .run();
+}
+
+#[cargo_test]
+fn git_tampering_unlocked() {
+    let git = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "pub fn bar() {}")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    bar = {{ git = '{}' }}
+                "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+    let lock = p.read_lockfile();
+    tamper_with_checkout();
+
+    p.cargo("check -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .with_stderr_contains(
+            "[WARNING] the contents of `bar v0.1.0 ([..])` changed since its `content-hash` \
+             was recorded in Cargo.lock, updating it",
+        )
+        .run();
+    assert_ne!(p.read_lockfile(), lock);
+}
+
+#[cargo_test]
+fn path_hashes_are_opt_in() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "vendor/bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("vendor/bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("vendor/bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("fetch -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+    assert!(!p.read_lockfile().contains("content-hash"));
+
+    p.cargo("fetch -Zcontent-hashes=git,path")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+    let lock = p.read_lockfile();
+    assert_eq!(lock.matches("content-hash").count(), 1, "{lock}");
+
+    // Edits of path dependencies update the hash quietly...
+    p.change_file("vendor/bar/src/lib.rs", "pub fn bar() {}");
+    p.cargo("check -Zcontent-hashes=path")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .with_stderr_does_not_contain("[WARNING][..]")
+        .run();
+    assert_ne!(p.read_lockfile(), lock);
+
+    // ...but are rejected with `--locked`.
+    p.change_file("vendor/bar/src/lib.rs", "");
+    p.cargo("check --locked -Zcontent-hashes=path")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the contents of `bar v0.1.0 ([CWD]/vendor/bar)` \
+             don't match the `content-hash` in Cargo.lock",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_kind() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check -Zcontent-hashes=registry")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] unstable 'content-hashes' only takes `git` and `path` as valid inputs",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn verify_lockfile_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("verify-lockfile")
+        .masquerade_as_nightly_cargo(&["verify-lockfile"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo verify-lockfile` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn verify_lockfile_no_lockfile() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["verify-lockfile"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] no Cargo.lock found for `[CWD]`
+Run `cargo generate-lockfile` to create one.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn verify_lockfile() {
+    Package::new("baz", "0.1.0").publish();
+    let git = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "pub fn bar() {}")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    bar = {{ git = '{}' }}
+                    baz = "0.1"
+                "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("fetch -Zcontent-hashes")
+        .masquerade_as_nightly_cargo(&["content-hashes"])
+        .run();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["verify-lockfile"])
+        .with_stderr_contains("[..]Verified 2 packages against Cargo.lock")
+        .run();
+
+    tamper_with_checkout();
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["verify-lockfile"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the contents of 1 package don't match Cargo.lock:")
+        .with_stderr_contains("  bar v0.1.0 ([..]) ([..])")
+        .run();
+}
@@ -160,6 +160,109 @@ fn doc_no_deps() {
     assert!(!p.root().join("target/doc/bar/index.html").is_file());
 }
 
+#[cargo_test]
+fn depgraph_docs_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("doc --depgraph-docs")
+        .masquerade_as_nightly_cargo(&["depgraph-docs"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--depgraph-docs` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test(nightly, reason = "private dependencies need `--extern priv:`")]
+fn depgraph_docs() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["public-dependency"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                pub_dep = { path = "pub_dep", public = true }
+                priv_dep = { path = "priv_dep" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "pub use pub_dep::Pub; pub fn foo() { priv_dep::private(); }",
+        )
+        .file(
+            "pub_dep/Cargo.toml",
+            r#"
+                cargo-features = ["public-dependency"]
+
+                [package]
+                name = "pub_dep"
+                version = "0.0.1"
+
+                [dependencies]
+                inner_pub = { path = "../inner_pub", public = true }
+                inner_priv = { path = "../inner_priv" }
+            "#,
+        )
+        .file(
+            "pub_dep/src/lib.rs",
+            "pub use inner_pub::InnerPub; pub struct Pub; pub fn f() { inner_priv::f(); }",
+        )
+        .file(
+            "inner_pub/Cargo.toml",
+            &basic_manifest("inner_pub", "0.0.1"),
+        )
+        .file("inner_pub/src/lib.rs", "pub struct InnerPub;")
+        .file(
+            "inner_priv/Cargo.toml",
+            &basic_manifest("inner_priv", "0.0.1"),
+        )
+        .file("inner_priv/src/lib.rs", "pub fn f() {}")
+        .file("priv_dep/Cargo.toml", &basic_manifest("priv_dep", "0.0.1"))
+        .file("priv_dep/src/lib.rs", "pub fn private() {}")
+        .build();
+
+    p.cargo("doc --depgraph-docs -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["public-dependency", "depgraph-docs"])
+        .with_stderr_unordered(
+            "\
+[CHECKING] inner_pub v0.0.1 ([CWD]/inner_pub)
+[CHECKING] inner_priv v0.0.1 ([CWD]/inner_priv)
+[CHECKING] pub_dep v0.0.1 ([CWD]/pub_dep)
+[CHECKING] priv_dep v0.0.1 ([CWD]/priv_dep)
+[DOCUMENTING] inner_pub v0.0.1 ([CWD]/inner_pub)
+[DOCUMENTING] pub_dep v0.0.1 ([CWD]/pub_dep)
+[DOCUMENTING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    assert!(p.root().join("target/doc/foo/index.html").is_file());
+    assert!(p.root().join("target/doc/pub_dep/index.html").is_file());
+    assert!(p.root().join("target/doc/inner_pub/index.html").is_file());
+    assert!(!p.root().join("target/doc/priv_dep/index.html").is_file());
+    assert!(!p.root().join("target/doc/inner_priv/index.html").is_file());
+}
+
+#[cargo_test]
+fn depgraph_docs_conflicts_with_no_deps() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("doc --depgraph-docs --no-deps -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["depgraph-docs"])
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] the argument '--depgraph-docs' cannot be used with '--no-deps'",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn doc_only_bin() {
     let p = project()
@@ -0,0 +1,230 @@
+//! Tests for `cargo check --feature-powerset`.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--feature-powerset` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn depth_and_exclude() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset -Zunstable-options")
+        .arg("--config=feature-powerset.depth=1")
+        .arg("--config=feature-powerset.exclude=['default']")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_stderr(
+            "\
+[..]Features foo v0.1.0 ([CWD]) (--no-default-features)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+    Features foo v0.1.0 ([CWD]) (--no-default-features --features a)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+    Features foo v0.1.0 ([CWD]) (--no-default-features --features b)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+     Summary 1 of 1 packages passed
+  foo v0.1.0 ([CWD])  ok (3 combinations)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn full_powerset() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_stderr_contains(
+            "    Features foo v0.1.0 ([CWD]) (--no-default-features --features a,b,default)",
+        )
+        .with_stderr_contains("  foo v0.1.0 ([CWD])  ok (8 combinations)")
+        .run();
+}
+
+#[cargo_test]
+fn reports_first_failure() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"#[cfg(all(feature = "a", feature = "b"))] compile_error!("a and b");"#,
+        )
+        .build();
+    p.cargo("check --feature-powerset -Zunstable-options")
+        .arg("--config=feature-powerset.exclude=['default']")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_status(101)
+        .with_stderr_contains(
+            "    Features foo v0.1.0 ([CWD]) (--no-default-features --features a,b)",
+        )
+        .with_stderr_contains("[ERROR] a and b")
+        .with_stderr_contains("     Summary 0 of 1 packages passed")
+        .with_stderr_contains(
+            "  foo v0.1.0 ([CWD])  FAILED (`--no-default-features --features a,b`)",
+        )
+        .with_stderr_contains("[ERROR] a feature combination failed for `foo v0.1.0 ([CWD])`")
+        .run();
+}
+
+#[cargo_test]
+fn explicit_sets() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset -Zunstable-options")
+        .arg("--config=feature-powerset.sets=['a', 'a,b', 'c']")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_stderr(
+            "\
+[..]Features foo v0.1.0 ([CWD]) (--no-default-features --features a)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+    Features foo v0.1.0 ([CWD]) (--no-default-features --features a,b)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+     Summary 1 of 1 packages passed
+  foo v0.1.0 ([CWD])  ok (2 combinations)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn too_many_combinations() {
+    let features: String = (0..11).map(|i| format!("f{i} = []\n")).collect();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [features]
+                    {features}
+                "#
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `foo` has more than 1024 feature combinations, \
+limit them with `feature-powerset.depth` or `feature-powerset.exclude`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn conflicts_with_features() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check --feature-powerset --features a -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["feature-powerset"])
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] the argument '--feature-powerset' cannot be used with '--features <FEATURES>'",
+        )
+        .run();
+}
@@ -0,0 +1,386 @@
+//! Tests for `[workspace.feature-sets]` and `--feature-set`.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn feature_sets_gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a"]
+
+                [workspace.feature-sets.wasm]
+                a = ["wasm"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `feature-sets` is required
+
+  The package requires the Cargo feature called `feature-sets`, but that feature is \
+  not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = [\"feature-sets\"]` to the top of Cargo.toml \
+  (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#feature-sets \
+  for more information about the status of this feature.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_set_requires_unstable_options() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+
+                [workspace.feature-sets.wasm]
+                a = ["wasm"]
+                b = ["web"]
+
+                [workspace.feature-sets.full]
+                a = ["wasm", "std"]
+                b = ["web"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check --feature-set wasm")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--feature-set` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_set() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+
+                [workspace.feature-sets.wasm]
+                a = ["wasm"]
+                b = ["web"]
+
+                [workspace.feature-sets.full]
+                a = ["wasm", "std"]
+                b = ["web"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v -Zunstable-options --feature-set wasm")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name a [..]--cfg 'feature=\"wasm\"'[..]")
+        .with_stderr_does_not_contain("[..]feature=\"std\"[..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name b [..]--cfg 'feature=\"web\"'[..]")
+        .run();
+
+    p.cargo("check -v -Zunstable-options --feature-set full")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name a [..]--cfg 'feature=\"std\"' --cfg 'feature=\"wasm\"'[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_set_with_features() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+
+                [workspace.feature-sets.wasm]
+                a = ["wasm"]
+                b = ["web"]
+
+                [workspace.feature-sets.full]
+                a = ["wasm", "std"]
+                b = ["web"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v -Zunstable-options --feature-set wasm --features a/std")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name a [..]--cfg 'feature=\"std\"' --cfg 'feature=\"wasm\"'[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn unknown_feature_set() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+
+                [workspace.feature-sets.wasm]
+                a = ["wasm"]
+                b = ["web"]
+
+                [workspace.feature-sets.full]
+                a = ["wasm", "std"]
+                b = ["web"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zunstable-options --feature-set minimal")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] feature set `minimal` is not defined in `[workspace.feature-sets]`
+
+available feature sets: full, wasm
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_feature_sets() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zunstable-options --feature-set wasm")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] feature set `wasm` is not defined in `[workspace.feature-sets]`, \
+which has no feature sets
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_a_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-sets"]
+
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+
+                [workspace.feature-sets.wasm]
+                c = ["wasm"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [features]
+                wasm = []
+                std = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [features]
+                web = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zunstable-options --feature-set wasm")
+        .masquerade_as_nightly_cargo(&["feature-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] feature set `wasm` enables features of `c`, which is not a member of the workspace
+",
+        )
+        .run();
+}
@@ -115,6 +115,106 @@ fn fetch_platform_specific_dependencies() {
         .run();
 }
 
+#[cargo_test]
+fn fetch_platform_specific_build_dependencies() {
+    if cross_compile::disabled() {
+        return;
+    }
+
+    Package::new("d1", "1.2.3")
+        .file("Cargo.toml", &basic_manifest("d1", "1.2.3"))
+        .file("src/lib.rs", "")
+        .publish();
+
+    Package::new("d2", "0.1.2")
+        .file("Cargo.toml", &basic_manifest("d2", "0.1.2"))
+        .file("src/lib.rs", "")
+        .publish();
+
+    let target = cross_compile::alternate();
+    let host = rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = []
+
+                    [target.{host}.build-dependencies]
+                    d1 = "1.2.3"
+
+                    [target.{target}.build-dependencies]
+                    d2 = "0.1.2"
+                "#,
+                host = host,
+                target = target
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    // Build scripts run on the host, whatever the target.
+    p.cargo("fetch -Zbuild-dep-host-filtering --target")
+        .arg(&target)
+        .masquerade_as_nightly_cargo(&["build-dep-host-filtering"])
+        .with_stderr_contains("[DOWNLOADED] d1 v1.2.3 [..]")
+        .with_stderr_does_not_contain("[DOWNLOADED] d2 v0.1.2 [..]")
+        .run();
+}
+
+#[cargo_test]
+fn fetch_platform_specific_build_dependencies_by_target() {
+    if cross_compile::disabled() {
+        return;
+    }
+
+    Package::new("d1", "1.2.3")
+        .file("Cargo.toml", &basic_manifest("d1", "1.2.3"))
+        .file("src/lib.rs", "")
+        .publish();
+
+    Package::new("d2", "0.1.2")
+        .file("Cargo.toml", &basic_manifest("d2", "0.1.2"))
+        .file("src/lib.rs", "")
+        .publish();
+
+    let target = cross_compile::alternate();
+    let host = rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = []
+
+                    [target.{host}.build-dependencies]
+                    d1 = "1.2.3"
+
+                    [target.{target}.build-dependencies]
+                    d2 = "0.1.2"
+                "#,
+                host = host,
+                target = target
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("fetch --target")
+        .arg(&target)
+        .with_stderr_contains("[DOWNLOADED] d2 v0.1.2 [..]")
+        .with_stderr_does_not_contain("[DOWNLOADED] d1 v1.2.3 [..]")
+        .run();
+}
+
 #[cargo_test]
 fn fetch_warning() {
     let p = project()
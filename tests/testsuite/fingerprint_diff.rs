@@ -0,0 +1,107 @@
+//! Tests for `-Zfingerprint-snapshot` and `cargo fingerprint diff`.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"pub const FOO: Option<&str> = option_env!("FOO");"#,
+        )
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rerun-if-env-changed=BAR"); }"#,
+        )
+        .build();
+    p.cargo("fingerprint diff foo")
+        .masquerade_as_nightly_cargo(&["fingerprint diff"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo fingerprint diff` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_snapshot() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"pub const FOO: Option<&str> = option_env!("FOO");"#,
+        )
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rerun-if-env-changed=BAR"); }"#,
+        )
+        .build();
+    p.cargo("build").run();
+    p.cargo("fingerprint diff foo -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["fingerprint diff"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] no fingerprint snapshot of `foo` was found in `[CWD]/target/debug`, \
+             snapshots are only recorded by builds with `-Zfingerprint-snapshot`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn env_changes() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"pub const FOO: Option<&str> = option_env!("FOO");"#,
+        )
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rerun-if-env-changed=BAR"); }"#,
+        )
+        .build();
+    p.cargo("build -Zfingerprint-snapshot")
+        .masquerade_as_nightly_cargo(&["fingerprint-snapshot"])
+        .env("FOO", "1")
+        .run();
+
+    p.cargo("fingerprint diff foo -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["fingerprint diff"])
+        .env("FOO", "1")
+        .with_stdout(
+            "\
+foo v0.0.1 ([CWD]), build script (build):
+  no change found
+foo v0.0.1 ([CWD]), build script (run-custom-build):
+  no change found
+foo v0.0.1 ([CWD]), lib (build):
+  no change found
+",
+        )
+        .run();
+
+    p.cargo("fingerprint diff foo -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["fingerprint diff"])
+        .env("BAR", "x")
+        .with_stdout(
+            "\
+foo v0.0.1 ([CWD]), build script (build):
+  no change found
+foo v0.0.1 ([CWD]), build script (run-custom-build):
+  `BAR` is now set to `x`, it was unset
+foo v0.0.1 ([CWD]), lib (build):
+  `FOO` is no longer set, it was `1`
+",
+        )
+        .run();
+
+    // The snapshot is updated when the unit is rebuilt.
+    p.cargo("build -Zfingerprint-snapshot")
+        .masquerade_as_nightly_cargo(&["fingerprint-snapshot"])
+        .env("FOO", "2")
+        .run();
+    p.cargo("fingerprint diff foo -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["fingerprint diff"])
+        .env("FOO", "3")
+        .with_stdout_contains("  `FOO` changed from `2` to `3`")
+        .run();
+}
@@ -310,6 +310,17 @@ fn bad_ids() {
             "\
 error: could not find report with ID 7
 Available IDs are: 1
+",
+        )
+        .run();
+
+    p.cargo("report future-incompatibilities --package bar")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: could not find package with ID `bar`
+Available packages are: foo@0.0.0
+Omit the `--package` flag to display a report for all packages
 ",
         )
         .run();
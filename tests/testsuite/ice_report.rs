@@ -0,0 +1,85 @@
+//! Tests for the reports saved when rustc crashes.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn ice_saves_report() {
+    // A `RUSTC_WORKSPACE_WRAPPER` which crashes like an ICE of `rustc` when
+    // compiling `foo`, and runs `rustc` otherwise.
+    let wrapper = project()
+        .at("rustc-crash")
+        .file("Cargo.toml", &basic_manifest("rustc-crash", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::process::{exit, Command};
+
+                fn main() {
+                    let args: Vec<String> = std::env::args().skip(1).collect();
+                    if !args.windows(2).any(|w| w[0] == "--crate-name" && w[1] == "foo") {
+                        let status = Command::new(&args[0]).args(&args[1..]).status().unwrap();
+                        exit(status.code().unwrap_or(1));
+                    }
+                    eprintln!("thread 'rustc' panicked at 'boom'");
+                    eprintln!("error: the compiler unexpectedly panicked. this is a bug.");
+                    exit(101);
+                }
+            "#,
+        )
+        .build();
+    wrapper.cargo("build").run();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("rustc-crash"))
+        .env("FOO_SECRET", "hunter2")
+        .with_status(101)
+        .with_stderr_contains("thread 'rustc' panicked at 'boom'")
+        .with_stderr_contains(
+            "[NOTE] rustc crashed while compiling `foo`, \
+             a report was saved to `[CWD]/target/ice-reports/[..]`",
+        )
+        .with_stderr_contains(
+            "[NOTE] please file an issue at \
+             https://github.com/rust-lang/rust/issues/new?[..]template=ice.md with this report",
+        )
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib)")
+        .run();
+
+    let reports: Vec<_> = p
+        .root()
+        .join("target/ice-reports")
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+
+    let command = std::fs::read_to_string(report.join("command.txt")).unwrap();
+    assert!(command.contains("--crate-name foo"), "{command}");
+    let env = std::fs::read_to_string(report.join("env.txt")).unwrap();
+    assert!(env.contains("CARGO_PKG_NAME=foo"), "{env}");
+    assert!(!env.contains("FOO_SECRET"), "{env}");
+    let sources = std::fs::read_to_string(report.join("sources.txt")).unwrap();
+    assert!(sources.contains("  Cargo.toml\n"), "{sources}");
+    assert!(sources.contains("  src/lib.rs\n"), "{sources}");
+    assert!(!sources.contains("target"), "{sources}");
+}
+
+#[cargo_test]
+fn compile_error_saves_no_report() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "fn f() -> u8 { \"\" }")
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]ice-reports[..]")
+        .run();
+    assert!(!p.root().join("target/ice-reports").exists());
+}
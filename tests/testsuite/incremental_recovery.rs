@@ -0,0 +1,147 @@
+//! Tests for the recovery from corrupted incremental compilation caches.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn corrupted_cache_is_removed() {
+    // A `RUSTC_WORKSPACE_WRAPPER` which fails like `rustc` with a corrupted
+    // incremental state as long as its incremental directory contains a
+    // `corrupted` file, and runs `rustc` otherwise.
+    let wrapper = project()
+        .at("rustc-corrupted")
+        .file("Cargo.toml", &basic_manifest("rustc-corrupted", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let corrupted = std::env::args()
+                        .filter_map(|arg| arg.strip_prefix("incremental=").map(String::from))
+                        .any(|dir| std::path::Path::new(&dir).join("corrupted").exists());
+                    if corrupted {
+                        eprintln!("thread 'rustc' panicked at 'found unstable fingerprints for foo'");
+                        std::process::exit(101);
+                    }
+                    let args: Vec<_> = std::env::args().skip(1).collect();
+                    let status = std::process::Command::new(&args[0])
+                        .args(&args[1..])
+                        .status()
+                        .unwrap();
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            "#,
+        )
+        .build();
+    wrapper.cargo("build").run();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build")
+        .env("CARGO_INCREMENTAL", "1")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("rustc-corrupted"))
+        .run();
+    let incremental: Vec<_> = p
+        .glob("target/debug/incremental/foo-*")
+        .map(|e| e.unwrap())
+        .collect();
+    assert_eq!(incremental.len(), 1);
+    let state = incremental[0].join("corrupted");
+    std::fs::write(&state, "").unwrap();
+    // The state of other units of the same crate is left alone.
+    let other = p.root().join("target/debug/incremental/foo-other");
+    std::fs::create_dir_all(&other).unwrap();
+    p.change_file("src/lib.rs", "pub fn f() {}");
+
+    p.cargo("build")
+        .env("CARGO_INCREMENTAL", "1")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("rustc-corrupted"))
+        .with_stderr_contains("thread 'rustc' panicked at 'found unstable fingerprints for foo'")
+        .with_stderr_contains(
+            "[NOTE] the incremental compilation cache of `foo` is corrupted, \
+             removing it and compiling again",
+        )
+        .with_stderr_contains(
+            "[NOTE] the incremental compilation cache of the following targets \
+             was corrupted and removed:\n  `foo` (lib)",
+        )
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+    assert!(!state.exists());
+    assert!(other.exists());
+}
+
+#[cargo_test]
+fn retried_only_once() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    let wrapper = project()
+        .at("rustc-corrupted")
+        .file("Cargo.toml", &basic_manifest("rustc-corrupted", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::process::{exit, Command};
+
+                fn main() {
+                    let args: Vec<String> = std::env::args().skip(1).collect();
+                    if !args.windows(2).any(|w| w[0] == "--crate-name" && w[1] == "foo") {
+                        let status = Command::new(&args[0]).args(&args[1..]).status().unwrap();
+                        exit(status.code().unwrap_or(1));
+                    }
+                    eprintln!("error: internal compiler error: encountered incremental compilation error");
+                    exit(101);
+                }
+            "#,
+        )
+        .build();
+    wrapper.cargo("build").run();
+
+    p.cargo("build")
+        .env("CARGO_INCREMENTAL", "1")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("rustc-corrupted"))
+        .with_status(101)
+        .with_stderr_contains(
+            "[NOTE] the incremental compilation cache of `foo` is corrupted, \
+             removing it and compiling again",
+        )
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib)")
+        .run();
+}
+
+#[cargo_test]
+fn not_retried_without_incremental() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    let wrapper = project()
+        .at("rustc-corrupted")
+        .file("Cargo.toml", &basic_manifest("rustc-corrupted", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::process::{exit, Command};
+
+                fn main() {
+                    let args: Vec<String> = std::env::args().skip(1).collect();
+                    if !args.windows(2).any(|w| w[0] == "--crate-name" && w[1] == "foo") {
+                        let status = Command::new(&args[0]).args(&args[1..]).status().unwrap();
+                        exit(status.code().unwrap_or(1));
+                    }
+                    eprintln!("error: internal compiler error: encountered incremental compilation error");
+                    exit(101);
+                }
+            "#,
+        )
+        .build();
+    wrapper.cargo("build").run();
+
+    p.cargo("build")
+        .env("CARGO_INCREMENTAL", "0")
+        .env("RUSTC_WORKSPACE_WRAPPER", wrapper.bin("rustc-corrupted"))
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]incremental compilation cache[..]")
+        .run();
+}
@@ -791,6 +791,86 @@ repository = "https://gitlab.com/rust-lang/rust"
     );
 }
 
+#[cargo_test]
+fn inherit_workspace_fields_in_metadata() {
+    // Tools reading `cargo metadata` see the values a member inherits, not
+    // the `field.workspace = true` placeholders.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            authors = ["Rustaceans"]
+            edition = "2018"
+            license = "MIT"
+            repository = "https://github.com/example/example"
+            rust-version = "1.60"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version.workspace = true
+            authors.workspace = true
+            edition.workspace = true
+            license.workspace = true
+            repository.workspace = true
+            rust-version.workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 --no-deps")
+        .with_json(
+            r#"
+    {
+        "packages": [
+            {
+                "authors": ["Rustaceans"],
+                "categories": [],
+                "default_run": null,
+                "name": "bar",
+                "version": "1.2.3",
+                "id": "bar 1.2.3 (path+file:[..]bar)",
+                "keywords": [],
+                "source": null,
+                "dependencies": [],
+                "edition": "2018",
+                "license": "MIT",
+                "license_file": null,
+                "links": null,
+                "description": null,
+                "readme": null,
+                "repository": "https://github.com/example/example",
+                "rust_version": "1.60",
+                "homepage": null,
+                "documentation": null,
+                "targets": "{...}",
+                "features": {},
+                "manifest_path": "[..]bar/Cargo.toml",
+                "metadata": null,
+                "publish": null
+            }
+        ],
+        "workspace_members": ["bar 1.2.3 (path+file:[..]bar)"],
+        "workspace_default_members": ["bar 1.2.3 (path+file:[..]bar)"],
+        "resolve": null,
+        "target_directory": "[..]foo/target",
+        "version": 1,
+        "workspace_root": "[..]/foo",
+        "metadata": null
+    }"#,
+        )
+        .run();
+}
+
 #[cargo_test]
 fn inherit_dependencies() {
     let registry = RegistryBuilder::new().http_api().http_index().build();
@@ -14,7 +14,7 @@ use cargo_test_support::{
 use cargo_util::ProcessError;
 
 use cargo_test_support::install::{
-    assert_has_installed_exe, assert_has_not_installed_exe, cargo_home,
+    assert_has_installed_exe, assert_has_not_installed_exe, cargo_home, exe,
 };
 use cargo_test_support::paths::{self, CargoPathExt};
 use std::env;
@@ -1054,6 +1054,37 @@ foo v0.0.1:
         .run();
 }
 
+#[cargo_test]
+fn list_outdated() {
+    pkg("foo", "0.0.1");
+    pkg("bar", "0.2.1");
+    pkg("bar", "0.2.2");
+
+    cargo_process("install bar --version =0.2.1").run();
+    cargo_process("install foo").run();
+    cargo_process("install --list --outdated")
+        .with_stdout(
+            "\
+bar v0.2.1 (latest: v0.2.2):
+    bar[..]
+",
+        )
+        .run();
+
+    cargo_process("install bar").run();
+    cargo_process("install --list --outdated")
+        .with_stdout("")
+        .run();
+}
+
+#[cargo_test]
+fn outdated_requires_list() {
+    cargo_process("install --outdated")
+        .with_status(1)
+        .with_stderr_contains("[..]the following required arguments were not provided:[..]")
+        .run();
+}
+
 #[cargo_test]
 fn list_error() {
     pkg("foo", "0.0.1");
@@ -1686,6 +1717,107 @@ error: some packages failed to uninstall
     assert_has_not_installed_exe(cargo_home(), "bar");
 }
 
+#[cargo_test]
+fn uninstall_glob() {
+    pkg("foo", "0.0.1");
+    pkg("foobar", "0.0.1");
+    pkg("bar", "0.0.1");
+
+    cargo_process("install foo foobar bar").run();
+
+    cargo_process("uninstall f*")
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+[REMOVING] [CWD]/home/.cargo/bin/foobar[EXE]
+[SUMMARY] Successfully uninstalled foo, foobar!
+",
+        )
+        .run();
+
+    assert_has_not_installed_exe(cargo_home(), "foo");
+    assert_has_not_installed_exe(cargo_home(), "foobar");
+    assert_has_installed_exe(cargo_home(), "bar");
+
+    cargo_process("uninstall baz*")
+        .with_status(101)
+        .with_stderr("[ERROR] no installed package matches the pattern `baz*`")
+        .run();
+}
+
+#[cargo_test]
+fn uninstall_all() {
+    pkg("foo", "0.0.1");
+    pkg("bar", "0.0.1");
+
+    cargo_process("install foo bar").run();
+
+    cargo_process("uninstall --all")
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/bar[EXE]
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+[SUMMARY] Successfully uninstalled bar, foo!
+",
+        )
+        .run();
+
+    assert_has_not_installed_exe(cargo_home(), "foo");
+    assert_has_not_installed_exe(cargo_home(), "bar");
+
+    cargo_process("uninstall --all")
+        .with_stderr("[SKIPPING] no packages are installed")
+        .run();
+
+    cargo_process("uninstall --all foo")
+        .with_status(1)
+        .with_stderr_contains("[..]the argument '--all' cannot be used with '[spec]...'")
+        .run();
+}
+
+#[cargo_test]
+fn uninstall_dry_run() {
+    pkg("foo", "0.0.1");
+
+    cargo_process("install foo").run();
+
+    cargo_process("uninstall foo --dry-run")
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+[WARNING] no files deleted due to --dry-run
+",
+        )
+        .run();
+
+    assert_has_installed_exe(cargo_home(), "foo");
+    cargo_process("install --list")
+        .with_stdout(
+            "\
+foo v0.0.1:
+    foo[EXE]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn uninstall_manually_removed_bin() {
+    pkg("foo", "0.0.1");
+
+    cargo_process("install foo").run();
+    let bin = cargo_home().join("bin").join(exe("foo"));
+    fs::remove_file(&bin).unwrap();
+
+    cargo_process("uninstall foo")
+        .with_stderr(
+            "[WARNING] `[CWD]/home/.cargo/bin/foo[EXE]` was already removed, forgetting it",
+        )
+        .run();
+
+    cargo_process("install --list").with_stdout("").run();
+}
+
 #[cargo_test]
 fn custom_target_dir_for_git_source() {
     let p = git::repo(&paths::root().join("foo"))
@@ -1707,7 +1839,8 @@ fn custom_target_dir_for_git_source() {
 
 #[cargo_test]
 fn install_respects_lock_file() {
-    // `cargo install` now requires --locked to use a Cargo.lock.
+    // `cargo install` uses the published Cargo.lock, unless --ignore-lockfile
+    // is passed.
     Package::new("bar", "0.1.0").publish();
     Package::new("bar", "0.1.1")
         .file("src/lib.rs", "not rust")
@@ -1738,10 +1871,32 @@ dependencies = [
         .publish();
 
     cargo_process("install foo")
+        .with_stderr_contains(
+            "[NOTE] the dependencies were pinned by the published Cargo.lock of the package, \
+             pass --ignore-lockfile to use their latest compatible versions",
+        )
+        .run();
+    cargo_process("install --force --ignore-lockfile foo")
         .with_stderr_contains("[..]not rust[..]")
         .with_status(101)
         .run();
-    cargo_process("install --locked foo").run();
+    cargo_process("install --force --locked foo")
+        .with_stderr_contains(
+            "[NOTE] the dependencies were pinned by the Cargo.lock of the package (--locked)",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn install_ignore_lockfile_conflicts_with_locked() {
+    Package::new("foo", "0.1.0")
+        .file("src/main.rs", "fn main() {}")
+        .publish();
+
+    cargo_process("install --locked --ignore-lockfile foo")
+        .with_status(101)
+        .with_stderr("[ERROR] cannot use `--ignore-lockfile` with `--locked` or `--frozen`")
+        .run();
 }
 
 #[cargo_test]
@@ -0,0 +1,180 @@
+//! Tests for `-Zlog-file`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project, Project};
+
+fn read_log(p: &Project, path: &str) -> Vec<serde_json::Value> {
+    p.read_file(path)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn reasons(log: &[serde_json::Value]) -> Vec<&str> {
+    log.iter().map(|e| e["reason"].as_str().unwrap()).collect()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zlog-file=cargo-log.json")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `-Z` flag is only accepted on the nightly channel of Cargo[..]",
+        )
+        .run();
+    assert!(!p.root().join("cargo-log.json").exists());
+}
+
+#[cargo_test]
+fn requires_path() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zlog-file")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `-Zlog-file` requires a path, for example `-Zlog-file=cargo-log.json`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn logs_build() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", "[registry]\ntoken = \"sekrit\"\n")
+        .build();
+
+    p.cargo("check -Zlog-file=logs/cargo-log.json")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .run();
+
+    let log = read_log(&p, "logs/cargo-log.json");
+    let seen = reasons(&log);
+    assert_eq!(seen[0], "invocation");
+    assert_eq!(seen[1], "config");
+    assert_eq!(*seen.last().unwrap(), "finished");
+    assert!(seen.contains(&"resolve"));
+    assert!(seen.contains(&"process-started"));
+
+    assert_eq!(log[1]["values"]["registry"]["token"], "<redacted>");
+    assert!(!p.read_file("logs/cargo-log.json").contains("sekrit"));
+
+    let resolved: Vec<_> = log.iter().find(|e| e["reason"] == "resolve").unwrap()["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|id| id.as_str().unwrap().split(' ').next().unwrap())
+        .collect();
+    assert_eq!(resolved, ["bar", "foo"]);
+
+    let decisions: Vec<_> = log
+        .iter()
+        .filter(|e| e["reason"] == "unit-decision")
+        .collect();
+    assert_eq!(decisions.len(), 2);
+    assert!(decisions.iter().all(|e| e["fresh"] == false));
+    let finished: Vec<_> = log
+        .iter()
+        .filter(|e| e["reason"] == "unit-finished")
+        .collect();
+    assert_eq!(finished.len(), 2);
+    assert!(finished
+        .iter()
+        .all(|e| e["success"] == true && e["duration"].is_f64()));
+    assert!(log.iter().all(|e| e["elapsed"].is_f64()));
+
+    // A second, fresh run truncates the log and records the fresh decisions.
+    p.cargo("check -Zlog-file=logs/cargo-log.json")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .run();
+    let log = read_log(&p, "logs/cargo-log.json");
+    assert_eq!(
+        reasons(&log).iter().filter(|r| **r == "invocation").count(),
+        1
+    );
+    assert!(log
+        .iter()
+        .filter(|e| e["reason"] == "unit-decision")
+        .all(|e| e["fresh"] == true));
+}
+
+#[cargo_test]
+fn logs_diagnostics_when_quiet() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                bulid = "oops"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -q -Zlog-file=cargo-log.json")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .with_stderr("")
+        .run();
+
+    let log = read_log(&p, "cargo-log.json");
+    assert!(log.iter().any(|e| e["reason"] == "diagnostic"
+        && e["level"] == "warning"
+        && e["message"] == "unused manifest key: package.bulid"));
+}
+
+#[cargo_test]
+fn logs_errors() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "not rust")
+        .build();
+
+    p.cargo("check -Zlog-file=cargo-log.json")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo` [..]")
+        .run();
+
+    let log = read_log(&p, "cargo-log.json");
+    let finished = log.iter().find(|e| e["reason"] == "unit-finished").unwrap();
+    assert_eq!(finished["success"], false);
+    let last_finished = log.iter().find(|e| e["reason"] == "finished").unwrap();
+    assert_eq!(last_finished["success"], false);
+    assert!(log.iter().any(|e| e["reason"] == "diagnostic"
+        && e["level"] == "error"
+        && e["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("could not compile `foo`")));
+
+    // An error reported when Cargo exits is logged before the final event.
+    p.cargo("check -p bar -Zlog-file=cargo-log.json")
+        .masquerade_as_nightly_cargo(&["log-file"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] package ID specification `bar` did not match any packages")
+        .run();
+
+    let log = read_log(&p, "cargo-log.json");
+    let n = log.len();
+    assert_eq!(log[n - 2]["reason"], "diagnostic");
+    assert_eq!(log[n - 2]["level"], "error");
+    assert_eq!(log[n - 1]["reason"], "finished");
+    assert_eq!(log[n - 1]["success"], false);
+}
@@ -8,15 +8,23 @@ extern crate cargo_test_macro;
 mod advanced_env;
 mod alt_registry;
 mod artifact_dep;
+mod artifact_dir;
+mod artifact_link;
 mod bad_config;
 mod bad_manifest_path;
 mod bench;
 mod binary_name;
 mod build;
+mod build_closure;
 mod build_plan;
+mod build_probe;
+mod build_retry;
 mod build_script;
 mod build_script_env;
 mod build_script_extra_link_arg;
+mod build_script_sandbox;
+mod build_summary;
+mod cache_backends;
 mod cache_messages;
 mod cargo;
 mod cargo_add;
@@ -29,8 +37,10 @@ mod cargo_command;
 mod cargo_config;
 mod cargo_doc;
 mod cargo_env_config;
+mod cargo_explain_hash;
 mod cargo_features;
 mod cargo_fetch;
+mod cargo_fingerprint;
 mod cargo_fix;
 mod cargo_generate_lockfile;
 mod cargo_git_checkout;
@@ -40,14 +50,18 @@ mod cargo_install;
 mod cargo_locate_project;
 mod cargo_login;
 mod cargo_logout;
+mod cargo_members;
 mod cargo_metadata;
 mod cargo_new;
 mod cargo_owner;
 mod cargo_package;
+mod cargo_patch;
 mod cargo_pkgid;
 mod cargo_publish;
 mod cargo_read_manifest;
+mod cargo_release_plan;
 mod cargo_remove;
+mod cargo_remove_member;
 mod cargo_report;
 mod cargo_run;
 mod cargo_rustc;
@@ -59,8 +73,10 @@ mod cargo_tree;
 mod cargo_uninstall;
 mod cargo_update;
 mod cargo_vendor;
+mod cargo_verify_lockfile;
 mod cargo_verify_project;
 mod cargo_version;
+mod cargo_version_check;
 mod cargo_yank;
 mod cfg;
 mod check;
@@ -71,6 +87,7 @@ mod concurrent;
 mod config;
 mod config_cli;
 mod config_include;
+mod content_hashes;
 mod corrupt_git;
 mod credential_process;
 mod cross_compile;
@@ -84,10 +101,13 @@ mod doc;
 mod docscrape;
 mod edition;
 mod error;
+mod feature_powerset;
+mod feature_sets;
 mod features;
 mod features2;
 mod features_namespaced;
 mod fetch;
+mod fingerprint_diff;
 mod fix;
 mod freshness;
 mod future_incompat_report;
@@ -99,6 +119,8 @@ mod git_shallow;
 mod glob_targets;
 mod help;
 mod https;
+mod ice_report;
+mod incremental_recovery;
 mod inheritable_workspace_fields;
 mod install;
 mod install_upgrade;
@@ -108,31 +130,42 @@ mod list_availables;
 mod local_registry;
 mod locate_project;
 mod lockfile_compat;
+mod log_file;
 mod login;
 mod logout;
 mod lto;
+mod max_memory;
 mod member_discovery;
 mod member_errors;
+mod members;
 mod message_format;
 mod messages;
 mod metabuild;
 mod metadata;
+mod metadata_cache;
+mod metadata_hash;
 mod minimal_versions;
 mod multitarget;
+mod nested_workspaces;
 mod net_config;
 mod new;
+mod no_deps_rebuild;
 mod offline;
 mod old_cargos;
 mod out_dir;
 mod owner;
 mod package;
 mod package_features;
+mod package_target_dir;
 mod patch;
+mod patch_status;
 mod path;
 mod paths;
 mod pkgid;
 mod plugins;
+mod post_link_hooks;
 mod proc_macro;
+mod proc_macro_host;
 mod profile_config;
 mod profile_custom;
 mod profile_overrides;
@@ -143,11 +176,19 @@ mod pub_priv;
 mod publish;
 mod publish_lockfile;
 mod read_manifest;
+mod read_only_sources;
 mod registry;
 mod registry_auth;
+mod registry_replay;
+mod release_plan;
+mod remove_member;
 mod rename_deps;
 mod replace;
+mod report_features;
 mod required_features;
+mod requires_explicit;
+mod resolver_precheck;
+mod resolver_prefetch;
 mod run;
 mod rust_version;
 mod rustc;
@@ -159,13 +200,23 @@ mod rustflags;
 mod rustup;
 mod script;
 mod search;
+mod shared_build_cache;
 mod shell_quoting;
+mod short_errors;
+mod shuffle_units;
+mod source_override;
 mod source_replacement;
 mod ssh;
 mod standard_lib;
+mod static_linking;
+mod target_dir_gc;
+mod term_paths;
+mod term_verbosity;
 mod test;
+mod test_matrix;
 mod timings;
 mod tool_paths;
+mod tool_wrappers;
 mod tree;
 mod tree_graph_features;
 mod unit_graph;
@@ -173,6 +224,7 @@ mod update;
 mod vendor;
 mod verify_project;
 mod version;
+mod version_check;
 mod warn_on_failure;
 mod weak_dep_features;
 mod workspaces;
@@ -0,0 +1,63 @@
+//! Tests for `-Zmax-memory` and the `build.max-memory` config.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", "[build]\nmax-memory = \"bogus\"\n")
+        .build();
+
+    p.cargo("build").run();
+}
+
+#[cargo_test]
+fn invalid_value() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", "[build]\nmax-memory = \"8 gigs\"\n")
+        .build();
+
+    p.cargo("build -Zmax-memory")
+        .masquerade_as_nightly_cargo(&["max-memory"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.max-memory` must be a size such as `8GiB` or `512MiB`, found `8 gigs`",
+        )
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn records_peak_rss() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config.toml", "[build]\nmax-memory = \"1MiB\"\n")
+        .build();
+
+    p.cargo("build -Zmax-memory")
+        .masquerade_as_nightly_cargo(&["max-memory"])
+        .run();
+
+    let fingerprint = p.target_debug_dir().join(".fingerprint");
+    let recorded: Vec<u64> = fingerprint
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path().join("peak-rss"))
+        .filter(|path| path.is_file())
+        .map(|path| std::fs::read_to_string(path).unwrap().parse().unwrap())
+        .collect();
+    // One for the library, and one for the binary.
+    assert_eq!(recorded.len(), 2);
+    assert!(recorded.iter().all(|&peak_rss| peak_rss > 1024 * 1024));
+
+    // Both units now exceed the budget, which still lets each one run on
+    // its own.
+    p.change_file("src/lib.rs", "// changed");
+    p.change_file("src/main.rs", "fn main() {} // changed");
+    p.cargo("build -Zmax-memory")
+        .masquerade_as_nightly_cargo(&["max-memory"])
+        .run();
+}
@@ -0,0 +1,204 @@
+//! Tests for the `cargo members` command.
+
+use cargo_test_support::{basic_manifest, project, Project};
+
+fn workspace() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b", "c", "d"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "../a" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+                [package]
+                name = "c"
+                version = "0.1.0"
+
+                [dev-dependencies]
+                b = { path = "../b" }
+            "#,
+        )
+        .file("c/src/lib.rs", "")
+        .file("d/Cargo.toml", &basic_manifest("d", "0.1.0"))
+        .file("d/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = workspace();
+    p.cargo("members")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo members` command is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn all_members() {
+    let p = workspace();
+    p.cargo("members -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_stdout("a\nb\nc\nd")
+        .run();
+}
+
+#[cargo_test]
+fn dependents_of() {
+    let p = workspace();
+    p.cargo("members -Zunstable-options --dependents-of a")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_stdout("b\nc")
+        .run();
+    p.cargo("members -Zunstable-options --dependents-of c --dependents-of d")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_stdout("")
+        .run();
+}
+
+#[cargo_test]
+fn dependents_of_unknown_member() {
+    let p = workspace();
+    p.cargo("members -Zunstable-options --dependents-of nope")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_status(101)
+        .with_stderr("[ERROR] package ID specification `nope` did not match any packages")
+        .run();
+}
+
+#[cargo_test]
+fn paths_changed() {
+    let p = workspace();
+    p.cargo("members -Zunstable-options --paths-changed b/src/lib.rs d/Cargo.toml Cargo.toml")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_stdout("b\nc\nd")
+        .run();
+    p.cargo("members -Zunstable-options --paths-changed src/lib.rs")
+        .cwd("a")
+        .masquerade_as_nightly_cargo(&["members"])
+        .with_stdout("a\nb\nc")
+        .run();
+}
+
+#[cargo_test]
+fn test_affected_by() {
+    let p = workspace();
+    p.cargo("test -Zunstable-options --affected-by a/src/lib.rs")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_stderr_contains("[..]Selected a (`a/src/lib.rs` changed)")
+        .with_stderr_contains("[..]Selected b (depends on `a`)")
+        .with_stderr_contains("[..]Selected c (depends on `b`)")
+        .with_stderr_contains("[COMPILING] c v0.1.0 ([CWD]/c)")
+        .with_stderr_does_not_contain("[..]d v0.1.0[..]")
+        .run();
+}
+
+#[cargo_test]
+fn test_affected_by_nothing() {
+    let p = workspace();
+    p.change_file("README.md", "");
+    p.cargo("test -Zunstable-options --affected-by README.md")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_stderr("[NOTE] no packages are affected by the given files, nothing to test")
+        .run();
+}
+
+#[cargo_test]
+fn test_affected_by_dep_info() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file(
+            "a/src/lib.rs",
+            r#"pub const DATA: &str = include_str!("../../data.txt");"#,
+        )
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .file("data.txt", "hello")
+        .build();
+
+    // Without a previous build there is no dep-info to consult.
+    p.cargo("test -Zunstable-options --affected-by data.txt")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_stderr("[NOTE] no packages are affected by the given files, nothing to test")
+        .run();
+
+    p.cargo("build").run();
+    p.cargo("test -Zunstable-options --affected-by data.txt")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_stderr_contains("[..]Selected a (`data.txt` changed)")
+        .with_stderr_does_not_contain("[..]Selected b[..]")
+        .run();
+}
+
+#[cargo_test]
+fn test_affected_by_dep_info_long_hash() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            "[build]\nmetadata-hash = 'sha256'\nmetadata-hash-length = 32",
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file(
+            "a/src/lib.rs",
+            r#"pub const DATA: &str = include_str!("../../data.txt");"#,
+        )
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .file("data.txt", "hello")
+        .build();
+
+    p.cargo("build -Zmetadata-hash")
+        .masquerade_as_nightly_cargo(&["metadata-hash"])
+        .run();
+    p.cargo("test -Zunstable-options --affected-by data.txt")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_stderr_contains("[..]Selected a (`data.txt` changed)")
+        .with_stderr_does_not_contain("[..]Selected b[..]")
+        .run();
+}
+
+#[cargo_test]
+fn affected_by_requires_unstable_options() {
+    let p = workspace();
+    p.cargo("test --affected-by a/src/lib.rs")
+        .masquerade_as_nightly_cargo(&["affected-by"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--affected-by` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
@@ -110,6 +110,21 @@ fn cargo_renders_ansi() {
         .run();
 }
 
+#[cargo_test]
+fn cargo_embeds_short() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "")
+        .build();
+
+    p.cargo("check --message-format json-diagnostic-short")
+        .with_status(101)
+        .with_stdout_contains("[..]\"rendered\":\"[..]error[E0601][..]")
+        .with_stdout_does_not_contain("[..]= note[..]")
+        .with_stdout_does_not_contain("[..]\\u001b[..]")
+        .run();
+}
+
 #[cargo_test]
 fn cargo_renders_doctests() {
     let p = project()
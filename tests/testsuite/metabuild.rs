@@ -769,3 +769,253 @@ fn metabuild_failed_build_json() {
         )
         .run();
 }
+
+#[cargo_test]
+fn declarative_cfg_env() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                cfg-env = { has_foo = "FOO_ENABLED" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"#[cfg(not(has_foo))] compile_error!("has_foo is not set");"#,
+        )
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .env("FOO_ENABLED", "1")
+        .with_stderr_does_not_contain("[..]--crate-name declarative_build_foo[..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]--cfg has_foo[..]`")
+        .run();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .env("FOO_ENABLED", "0")
+        .with_status(101)
+        .with_stderr_contains("[..]has_foo is not set[..]")
+        .run();
+}
+
+#[cargo_test]
+fn declarative_ignored_without_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                cfg-env = { has_foo = "FOO_ENABLED" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"#[cfg(has_foo)] compile_error!("has_foo is set");"#,
+        )
+        .build();
+
+    p.cargo("check")
+        .env("FOO_ENABLED", "1")
+        .with_stderr(
+            "\
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn declarative_with_build_script() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                cfg-env = { has_foo = "FOO_ENABLED" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  cannot specify both `package.metadata.build` and a build script
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn declarative_unknown_directive() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                pkg-config = ["z"]
+                foo = 1
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .with_status(101)
+        .with_stderr_contains("  failed to parse `package.metadata.build`")
+        .with_stderr_contains("  unknown field `foo`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn declarative_missing_pkg_config() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                pkg-config = ["z"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .env("PKG_CONFIG", "nonexistent-pkg-config")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+
+Caused by:
+  failed to find system library `z`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn declarative_cc_not_found() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                cc = ["src/add.c"]
+            "#,
+        )
+        .file("src/add.c", "int add(int a, int b) { return a + b; }")
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .env("CC", "nonexistent-cc")
+        .env("RUST_BACKTRACE", "1")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+
+Caused by:
+  failed to run `nonexistent-cc` for declarative build of foo
+",
+        )
+        .with_stderr_does_not_contain("[..]To improve backtraces[..]")
+        .run();
+}
+
+#[cargo_test(requires_cc)]
+fn declarative_cc() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["metabuild"]
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [package.metadata.build]
+                cc = ["src/add.c"]
+            "#,
+        )
+        .file("src/add.c", "int add(int a, int b) { return a + b; }")
+        .file(
+            "src/main.rs",
+            r#"
+                extern "C" {
+                    fn add(a: i32, b: i32) -> i32;
+                }
+
+                fn main() {
+                    assert_eq!(unsafe { add(1, 2) }, 3);
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run -v")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .with_stderr_contains("[RUNNING] `cc -c src/add.c -o [..]-add.o -fPIC`")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]-l static=foo_cc[..]`")
+        .run();
+
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .with_stderr(
+            "\
+[FINISHED] dev [..]
+[RUNNING] `target/debug/foo[EXE]`
+",
+        )
+        .run();
+
+    p.change_file("src/add.c", "int add(int a, int b) { return a + b + 1; }");
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["metabuild"])
+        .with_status(101)
+        .with_stderr_contains("[..]assertion `left == right` failed[..]")
+        .run();
+}
@@ -3,7 +3,9 @@
 use cargo_test_support::install::cargo_home;
 use cargo_test_support::paths::CargoPathExt;
 use cargo_test_support::registry::Package;
-use cargo_test_support::{basic_bin_manifest, basic_lib_manifest, main_file, project, rustc_host};
+use cargo_test_support::{
+    basic_bin_manifest, basic_lib_manifest, main_file, project, rustc_host, Project,
+};
 use serde_json::json;
 
 #[cargo_test]
@@ -4248,3 +4250,310 @@ fn workspace_metadata_with_dependencies_no_deps_artifact() {
         )
         .run();
 }
+
+/// Runs `cargo metadata` with `args`, returning the parsed output.
+fn metadata_json(p: &Project, args: &str) -> serde_json::Value {
+    let output = p
+        .cargo(&format!(
+            "metadata --format-version 1 -Zunstable-options {args}"
+        ))
+        .masquerade_as_nightly_cargo(&["metadata options"])
+        .exec_with_output()
+        .unwrap();
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// The names of the packages and resolve nodes in `metadata`.
+fn metadata_names(metadata: &serde_json::Value) -> (Vec<&str>, Vec<&str>) {
+    let packages = metadata["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|pkg| pkg["name"].as_str().unwrap())
+        .collect();
+    let nodes = metadata["resolve"]["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["id"].as_str().unwrap().split(' ').next().unwrap())
+        .collect();
+    (packages, nodes)
+}
+
+#[cargo_test]
+fn depth_requires_unstable_options() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b" }
+
+                [features]
+                default = ["f"]
+                f = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                c = { path = "../c" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("metadata --format-version 1 --depth 1")
+        .masquerade_as_nightly_cargo(&["metadata options"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--depth` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn depth() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b" }
+
+                [features]
+                default = ["f"]
+                f = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                c = { path = "../c" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+
+    let metadata = metadata_json(&p, "--depth 0");
+    assert_eq!(metadata_names(&metadata), (vec!["foo"], vec!["foo"]));
+
+    let metadata = metadata_json(&p, "--depth 1");
+    let mut names = metadata_names(&metadata);
+    names.0.sort();
+    names.1.sort();
+    assert_eq!(names, (vec!["a", "foo"], vec!["a", "foo"]));
+    // Every dependency listed by a node has a node of its own, so the ones
+    // of the packages at the last level are left out.
+    let nodes = metadata["resolve"]["nodes"].as_array().unwrap();
+    let ids: Vec<_> = nodes.iter().map(|node| &node["id"]).collect();
+    for node in nodes {
+        for dep in node["dependencies"].as_array().unwrap() {
+            assert!(ids.contains(&dep), "dangling dependency {dep}");
+        }
+        for dep in node["deps"].as_array().unwrap() {
+            assert!(ids.contains(&&dep["pkg"]), "dangling dependency {dep}");
+        }
+    }
+    let a = nodes
+        .iter()
+        .find(|node| node["id"].as_str().unwrap().starts_with("a "))
+        .unwrap();
+    assert_eq!(a["dependencies"], json!([]));
+    assert_eq!(a["deps"], json!([]));
+
+    let metadata = metadata_json(&p, "--depth 3");
+    assert_eq!(metadata_names(&metadata).1.len(), 4);
+}
+
+#[cargo_test]
+fn no_deps_features() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b" }
+
+                [features]
+                default = ["f"]
+                f = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                c = { path = "../c" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+
+    let metadata = metadata_json(&p, "");
+    let nodes = metadata["resolve"]["nodes"].as_array().unwrap();
+    assert!(nodes
+        .iter()
+        .any(|node| node["features"] == json!(["default", "f"])));
+
+    let metadata = metadata_json(&p, "--no-deps-features");
+    assert_eq!(metadata_names(&metadata).0.len(), 4);
+    let nodes = metadata["resolve"]["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 4);
+    assert!(nodes.iter().all(|node| node["features"] == json!([])));
+}
+
+#[cargo_test]
+fn no_deps_features_optional_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                opt = { path = "opt", optional = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("opt/Cargo.toml", &basic_lib_manifest("opt"))
+        .file("opt/src/lib.rs", "")
+        .build();
+
+    let metadata = metadata_json(&p, "--no-deps-features");
+    assert_eq!(metadata_names(&metadata), (vec!["foo"], vec!["foo"]));
+
+    let metadata = metadata_json(&p, "--no-deps-features --features opt");
+    let mut names = metadata_names(&metadata);
+    names.0.sort();
+    names.1.sort();
+    assert_eq!(names, (vec!["foo", "opt"], vec!["foo", "opt"]));
+}
+
+#[cargo_test]
+fn no_deps_features_conflicts_with_filter_platform() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b" }
+
+                [features]
+                default = ["f"]
+                f = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                c = { path = "../c" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("metadata --no-deps-features --filter-platform x86_64-unknown-linux-gnu")
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] the argument '--no-deps-features' cannot be used with \
+             '--filter-platform <TRIPLE>'",
+        )
+        .run();
+}
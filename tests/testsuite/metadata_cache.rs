@@ -0,0 +1,210 @@
+//! Tests for caching the output of `cargo metadata` (`-Zmetadata-cache`).
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project, Project};
+
+/// Replaces the cached output with a marker, keeping its fingerprint, to
+/// tell whether the next invocation reuses it.
+fn tamper_cache(p: &Project) {
+    let path = p.root().join("target/.cargo-metadata-cache/metadata.json");
+    let cached = std::fs::read_to_string(&path).unwrap();
+    let fingerprint = cached.lines().next().unwrap();
+    std::fs::write(&path, format!("{fingerprint}\n{{\"cached\":true}}")).unwrap();
+}
+
+#[cargo_test]
+fn reuses_output() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout_contains(r#"{"packages":[[..]"name":"bar"[..]"#)
+        .run();
+    assert!(p
+        .root()
+        .join("target/.cargo-metadata-cache/metadata.json")
+        .is_file());
+
+    tamper_cache(&p);
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout(r#"{"cached":true}"#)
+        .with_stderr("")
+        .run();
+
+    // Other options don't share the cached output.
+    p.cargo("metadata --format-version 1 -Zmetadata-cache --filter-platform")
+        .arg(cargo_test_support::rustc_host())
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout_contains(r#"{"packages":[[..]"name":"bar"[..]"#)
+        .run();
+}
+
+#[cargo_test]
+fn invalidated_by_manifest_change() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .run();
+    tamper_cache(&p);
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            description = "changed"
+
+            [dependencies]
+            bar = "1.0"
+        "#,
+    );
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout_contains(r#"{"packages":[[..]"description":"changed"[..]"#)
+        .run();
+}
+
+#[cargo_test]
+fn invalidated_by_new_target() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .run();
+    tamper_cache(&p);
+
+    p.change_file("src/bin/extra.rs", "fn main() {}");
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout_contains(r#"{"packages":[[..]"name":"extra"[..]"#)
+        .run();
+}
+
+#[cargo_test]
+fn invalidated_by_config_change() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .run();
+    tamper_cache(&p);
+
+    p.change_file(".cargo/config.toml", "[net]\nretry = 5");
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .with_stdout_contains(r#"{"packages":[[..]"name":"bar"[..]"#)
+        .run();
+
+    tamper_cache(&p);
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .env("CARGO_NET_RETRY", "6")
+        .with_stdout_contains(r#"{"packages":[[..]"name":"bar"[..]"#)
+        .run();
+}
+
+#[cargo_test]
+fn not_cached_with_path_deps_outside_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 -Zmetadata-cache")
+        .masquerade_as_nightly_cargo(&["metadata-cache"])
+        .run();
+    assert!(!p.root().join("target/.cargo-metadata-cache").exists());
+}
+
+#[cargo_test]
+fn not_used_without_flag() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1").run();
+    assert!(!p.root().join("target/.cargo-metadata-cache").exists());
+}
@@ -0,0 +1,135 @@
+//! Tests for `-Zmetadata-hash` and `cargo explain-hash`.
+
+use cargo_test_support::{project, Project};
+
+/// The metadata hash in the file name of the rlib of `foo`.
+fn rlib_hash(p: &Project) -> String {
+    let rlibs: Vec<_> = p
+        .glob("target/debug/deps/libfoo-*.rlib")
+        .map(|path| path.unwrap())
+        .collect();
+    assert_eq!(rlibs.len(), 1, "{:?}", rlibs);
+    let stem = rlibs[0].file_stem().unwrap().to_str().unwrap();
+    stem.strip_prefix("libfoo-").unwrap().to_string()
+}
+
+#[cargo_test]
+fn sha256_length() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            "[build]\nmetadata-hash = 'sha256'\nmetadata-hash-length = 32",
+        )
+        .build();
+
+    p.cargo("build -Zmetadata-hash")
+        .masquerade_as_nightly_cargo(&["metadata-hash"])
+        .run();
+    assert_eq!(rlib_hash(&p).len(), 32);
+}
+
+#[cargo_test]
+fn requires_unstable_flag() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            "[build]\nmetadata-hash = 'sha256'\nmetadata-hash-length = 32",
+        )
+        .build();
+
+    p.cargo("build").run();
+    assert_eq!(rlib_hash(&p).len(), 16);
+}
+
+#[cargo_test]
+fn siphash_is_not_longer() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", "[build]\nmetadata-hash-length = 32")
+        .build();
+
+    p.cargo("build -Zmetadata-hash")
+        .masquerade_as_nightly_cargo(&["metadata-hash"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.metadata-hash-length` greater than 16 requires \
+             `build.metadata-hash = \"sha256\"`",
+        )
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        "[build]\nmetadata-hash = 'sha256'\nmetadata-hash-length = 80",
+    );
+    p.cargo("build -Zmetadata-hash")
+        .masquerade_as_nightly_cargo(&["metadata-hash"])
+        .with_status(101)
+        .with_stderr("[ERROR] `build.metadata-hash-length` must be between 16 and 64, found 80")
+        .run();
+}
+
+#[cargo_test]
+fn explain_hash() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build -Zmetadata-hash")
+        .masquerade_as_nightly_cargo(&["metadata-hash"])
+        .run();
+    let hash = rlib_hash(&p);
+
+    p.cargo(&format!(
+        "explain-hash -Zunstable-options target/debug/deps/libfoo-{}.rlib",
+        hash
+    ))
+    .masquerade_as_nightly_cargo(&["unstable-options"])
+    .with_stdout(&format!(
+        "\
+{}:
+  package: foo v0.0.1 ([CWD])
+  target: lib `foo`
+  mode: build
+  profile: dev
+  features: none
+  kind: host
+  directory: [CWD]/target/debug
+",
+        hash
+    ))
+    .run();
+
+    p.cargo("explain-hash -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_contains(&format!("{}:", hash))
+        .run();
+
+    p.cargo("explain-hash -Zunstable-options libfoo-0123456789abcdef.rlib")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] no unit with the metadata hash `0123456789abcdef` was found in \
+             `[CWD]/target`, hashes are only recorded by builds with `-Zmetadata-hash`",
+        )
+        .run();
+
+    p.cargo("explain-hash -Zunstable-options libfoo.rlib")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr("[ERROR] `libfoo.rlib` doesn't contain a metadata hash")
+        .run();
+}
+
+#[cargo_test]
+fn explain_hash_requires_unstable_flag() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("explain-hash")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo explain-hash` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
@@ -0,0 +1,231 @@
+//! Tests for workspaces nested in others (`-Znested-workspaces`).
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn requires_flag() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["top", "inner"]
+            "#,
+        )
+        .file("top/Cargo.toml", &basic_manifest("top", "0.1.0"))
+        .file("top/src/lib.rs", "")
+        .file(
+            "inner/Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+                exclude = ["crates/scratch"]
+            "#,
+        )
+        .file("inner/crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("inner/crates/a/src/lib.rs", "")
+        .file("inner/crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("inner/crates/b/src/lib.rs", "")
+        .file(
+            "inner/crates/scratch/Cargo.toml",
+            &basic_manifest("scratch", "0.1.0"),
+        )
+        .file("inner/crates/scratch/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] multiple workspace roots found in the same workspace:
+  [CWD]/inner
+  [CWD]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_from_top_level() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["top", "inner"]
+            "#,
+        )
+        .file("top/Cargo.toml", &basic_manifest("top", "0.1.0"))
+        .file("top/src/lib.rs", "")
+        .file(
+            "inner/Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+                exclude = ["crates/scratch"]
+            "#,
+        )
+        .file("inner/crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("inner/crates/a/src/lib.rs", "")
+        .file("inner/crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("inner/crates/b/src/lib.rs", "")
+        .file(
+            "inner/crates/scratch/Cargo.toml",
+            &basic_manifest("scratch", "0.1.0"),
+        )
+        .file("inner/crates/scratch/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Znested-workspaces")
+        .masquerade_as_nightly_cargo(&["nested-workspaces"])
+        .with_stderr_unordered(
+            "\
+[CHECKING] a v0.1.0 ([CWD]/inner/crates/a)
+[CHECKING] b v0.1.0 ([CWD]/inner/crates/b)
+[CHECKING] top v0.1.0 ([CWD]/top)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // The outer workspace owns the lock file and the target directory.
+    assert!(p.root().join("Cargo.lock").is_file());
+    assert!(p.root().join("target").is_dir());
+    assert!(!p.root().join("inner/Cargo.lock").exists());
+    assert!(!p.root().join("inner/target").exists());
+}
+
+#[cargo_test]
+fn build_from_nested_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["top", "inner"]
+            "#,
+        )
+        .file("top/Cargo.toml", &basic_manifest("top", "0.1.0"))
+        .file("top/src/lib.rs", "")
+        .file(
+            "inner/Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+                exclude = ["crates/scratch"]
+            "#,
+        )
+        .file("inner/crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("inner/crates/a/src/lib.rs", "")
+        .file("inner/crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("inner/crates/b/src/lib.rs", "")
+        .file(
+            "inner/crates/scratch/Cargo.toml",
+            &basic_manifest("scratch", "0.1.0"),
+        )
+        .file("inner/crates/scratch/src/lib.rs", "")
+        .build();
+
+    // Within the nested workspace, it is a workspace on its own.
+    p.cargo("check -Znested-workspaces")
+        .cwd("inner")
+        .masquerade_as_nightly_cargo(&["nested-workspaces"])
+        .with_stderr_unordered(
+            "\
+[CHECKING] a v0.1.0 ([CWD]/crates/a)
+[CHECKING] b v0.1.0 ([CWD]/crates/b)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    assert!(p.root().join("inner/Cargo.lock").is_file());
+    assert!(p.root().join("inner/target").is_dir());
+    assert!(!p.root().join("Cargo.lock").exists());
+}
+
+#[cargo_test]
+fn select_nested_member_from_top_level() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["top", "inner"]
+            "#,
+        )
+        .file("top/Cargo.toml", &basic_manifest("top", "0.1.0"))
+        .file("top/src/lib.rs", "")
+        .file(
+            "inner/Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+                exclude = ["crates/scratch"]
+            "#,
+        )
+        .file("inner/crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("inner/crates/a/src/lib.rs", "")
+        .file("inner/crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("inner/crates/b/src/lib.rs", "")
+        .file(
+            "inner/crates/scratch/Cargo.toml",
+            &basic_manifest("scratch", "0.1.0"),
+        )
+        .file("inner/crates/scratch/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Znested-workspaces -p b")
+        .masquerade_as_nightly_cargo(&["nested-workspaces"])
+        .with_stderr(
+            "\
+[CHECKING] b v0.1.0 ([CWD]/inner/crates/b)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // Excluded by the nested workspace.
+    p.cargo("check -Znested-workspaces -p scratch")
+        .masquerade_as_nightly_cargo(&["nested-workspaces"])
+        .with_status(101)
+        .with_stderr("[ERROR] package ID specification `scratch` did not match any packages")
+        .run();
+}
+
+#[cargo_test]
+fn missing_nested_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["inner"]
+            "#,
+        )
+        .file(
+            "inner/Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a"]
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Znested-workspaces")
+        .masquerade_as_nightly_cargo(&["nested-workspaces"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to load manifest for workspace member `[CWD]/inner/a`
+referenced by nested workspace at `[CWD]/inner/Cargo.toml`
+
+Caused by:
+  failed to read `[CWD]/inner/a/Cargo.toml`
+
+Caused by:
+  [..]
+",
+        )
+        .run();
+}
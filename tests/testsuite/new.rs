@@ -558,3 +558,60 @@ It is recommended to use a different name to avoid problems.
         )
         .run();
 }
+
+#[cargo_test]
+fn add_to_workspace_requires_nightly() {
+    cargo_process("new foo --add-to-workspace")
+        .masquerade_as_nightly_cargo(&["add-to-workspace"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--add-to-workspace` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn add_to_workspace() {
+    fs::write(
+        paths::root().join("Cargo.toml"),
+        r#"
+[workspace]
+resolver = "2"
+members = [
+    # the first member
+    "foo",
+]
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(paths::root().join("foo/src")).unwrap();
+    fs::write(
+        paths::root().join("foo/Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(paths::root().join("foo/src/lib.rs"), "").unwrap();
+    cargo_process("new crates/bar --vcs none --add-to-workspace -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["add-to-workspace"])
+        .with_stderr(
+            "\
+[ADDING] `crates/bar` to the members of the workspace at `[ROOT]/Cargo.toml`
+[CREATED] binary (application) `crates/bar` package
+",
+        )
+        .run();
+    assert_eq!(
+        fs::read_to_string(paths::root().join("Cargo.toml")).unwrap(),
+        r#"
+[workspace]
+resolver = "2"
+members = [
+    # the first member
+    "foo",
+    "crates/bar",
+]
+"#,
+    );
+}
@@ -0,0 +1,132 @@
+//! Tests for `cargo build --no-deps-rebuild`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    Package::new("baz", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+                baz = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build --no-deps-rebuild")
+        .masquerade_as_nightly_cargo(&["no-deps-rebuild"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--no-deps-rebuild` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dependencies_never_built() {
+    Package::new("baz", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+                baz = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build --no-deps-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["no-deps-rebuild"])
+        .with_status(101)
+        .with_stderr_unordered(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] baz v0.1.0 (registry `dummy-registry`)
+[DIRTY] bar v0.1.0 ([CWD]/bar): no previous build was found
+[DIRTY] baz v0.1.0: no previous build was found
+[ERROR] `--no-deps-rebuild` was passed, but these dependencies need to be rebuilt:
+  bar v0.1.0 ([CWD]/bar)
+  baz v0.1.0
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dirty_dependency() {
+    Package::new("baz", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+                baz = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build").run();
+
+    // Only the selected package is allowed to be rebuilt.
+    p.change_file("src/lib.rs", "pub fn foo() {}");
+    p.cargo("build --no-deps-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["no-deps-rebuild"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    p.change_file("bar/src/lib.rs", "pub fn bar() {}");
+    p.cargo("build --no-deps-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["no-deps-rebuild"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[DIRTY] bar v0.1.0 ([CWD]/bar): the file `bar/src/lib.rs` has changed ([..])
+[ERROR] `--no-deps-rebuild` was passed, but these dependencies need to be rebuilt:
+  bar v0.1.0 ([CWD]/bar)
+",
+        )
+        .run();
+
+    // Selecting the dependency allows rebuilding it.
+    p.cargo("build -p bar -p foo --no-deps-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["no-deps-rebuild"])
+        .with_stderr_unordered(
+            "\
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
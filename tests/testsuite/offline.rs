@@ -3,7 +3,7 @@
 use cargo_test_support::{
     basic_manifest, git, main_file, path2url, project,
     registry::{Package, RegistryBuilder},
-    Execs,
+    rustc_host, Execs,
 };
 use std::fs;
 
@@ -66,7 +66,9 @@ fn offline_missing_optional() {
 [ERROR] failed to download `opt_dep v1.0.0`
 
 Caused by:
-  attempting to make an HTTP request, but --offline was specified
+  `opt_dep v1.0.0` is not available offline, and can't be downloaded from `[..]` in offline mode (--offline)
+
+  help: run `cargo fetch` without `--offline` to download it beforehand
 ",
         )
         .with_status(101)
@@ -283,7 +285,10 @@ Caused by:
   Unable to update https://github.com/some_user/dep1.git
 
 Caused by:
-  can't checkout from 'https://github.com/some_user/dep1.git': you are in the offline mode (--offline)").run();
+  the git repository is not available offline, and can't be downloaded from `https://github.com/some_user/dep1.git` in offline mode (--offline)
+
+  help: run `cargo fetch` without `--offline` to download it beforehand
+").run();
 }
 
 #[cargo_test]
@@ -329,7 +334,9 @@ fn compile_offline_while_transitive_dep_not_cached() {
 [ERROR] failed to download `bar v0.1.0`
 
 Caused by:
-  attempting to make an HTTP request, but --offline was specified
+  `bar v0.1.0` is not available offline, and can't be downloaded from `[..]` in offline mode (--offline)
+
+  help: run `cargo fetch` without `--offline` to download it beforehand
 ",
         )
         .run();
@@ -746,3 +753,38 @@ remove the --locked flag and use --offline instead.
 ")
         .run();
 }
+
+#[cargo_test]
+fn offline_missing_dep_for_target() {
+    Package::new("dep", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            [dependencies]
+            dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    let target = rustc_host();
+    p.cargo("check --offline --target")
+        .arg(&target)
+        .with_status(101)
+        .with_stderr(&format!(
+            "\
+[ERROR] failed to download `dep v1.0.0`
+
+Caused by:
+  `dep v1.0.0` is not available offline, and can't be downloaded from `[..]` in offline mode (--offline)
+
+  help: run `cargo fetch --target {target}` without `--offline` to download it beforehand
+"
+        ))
+        .run();
+}
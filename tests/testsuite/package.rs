@@ -669,6 +669,105 @@ fn package_symlink_to_submodule() {
         .run();
 }
 
+#[cargo_test]
+fn package_uninitialized_git_submodule() {
+    let project = git::new("foo", |project| {
+        project
+            .file(
+                "Cargo.toml",
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = ["foo@example.com"]
+                    license = "MIT"
+                    description = "foo"
+                    repository = "foo"
+                "#,
+            )
+            .file("src/lib.rs", "pub fn foo() {}")
+    });
+    let library = git::new("bar", |library| {
+        library.no_manifest().file("Makefile", "all:")
+    });
+
+    let repository = git2::Repository::open(&project.root()).unwrap();
+    let url = path2url(library.root()).to_string();
+    git::add_submodule(&repository, &url, Path::new("bar"));
+    git::commit(&repository);
+
+    // As in a clone made without `--recurse-submodules`.
+    fs::remove_dir_all(project.root().join("bar")).unwrap();
+    fs::create_dir(project.root().join("bar")).unwrap();
+
+    project
+        .cargo("package --list --allow-dirty")
+        .with_stdout(
+            "\
+.gitmodules
+Cargo.toml
+Cargo.toml.orig
+src/lib.rs
+",
+        )
+        .with_stderr("[NOTE] skipped `bar`: git submodule is not checked out")
+        .run();
+}
+
+#[cargo_test]
+fn package_git_submodule_with_include() {
+    let project = git::new("foo", |project| {
+        project
+            .file(
+                "Cargo.toml",
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = ["foo@example.com"]
+                    license = "MIT"
+                    description = "foo"
+                    repository = "foo"
+                    include = ["src/*.rs", "bar/*.c"]
+                "#,
+            )
+            .file("src/lib.rs", "pub fn foo() {}")
+    });
+    let library = git::new("bar", |library| {
+        library
+            .no_manifest()
+            .file("Makefile", "all:")
+            .file("bar.c", "")
+    });
+
+    let repository = git2::Repository::open(&project.root()).unwrap();
+    let url = path2url(library.root()).to_string();
+    git::add_submodule(&repository, &url, Path::new("bar"));
+    git::commit(&repository);
+
+    let repository = git2::Repository::open(&project.root().join("bar")).unwrap();
+    repository
+        .reset(
+            &repository.revparse_single("HEAD").unwrap(),
+            git2::ResetType::Hard,
+            None,
+        )
+        .unwrap();
+
+    project
+        .cargo("package --list")
+        .with_stdout(
+            "\
+.cargo_vcs_info.json
+Cargo.toml
+Cargo.toml.orig
+bar/bar.c
+src/lib.rs
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn no_duplicates_from_modified_tracked_files() {
     let p = git::new("all", |p| p.file("src/main.rs", "fn main() {}"));
@@ -1021,6 +1120,39 @@ fn filesystem_loop() {
         .with_stderr_contains(
             "[WARNING] File system loop found: [..]/a/b/c/d/foo points to an ancestor [..]/a/b",
         )
+        .with_stderr_contains(
+            "[NOTE] skipped `a[..]foo`: symbolic link to one of its parent directories",
+        )
+        .run();
+}
+
+#[cargo_test]
+/// Tests that symlinks to directories outside of the package are followed,
+/// and reported.
+///
+/// This test requires you to be able to make symlinks.
+/// For windows, this may require you to enable developer mode.
+fn package_symlink_to_dir_outside() {
+    if !symlink_supported() {
+        return;
+    }
+
+    let outside = paths::root().join("outside");
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("data.txt"), "data").unwrap();
+    project()
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .file("bla/Makefile", "all:")
+        .symlink_dir("bla", "foo")
+        .symlink_dir("../outside", "outside-link")
+        .build()
+        .cargo("package --list")
+        .with_stdout_contains("foo/Makefile")
+        .with_stdout_contains("outside-link/data.txt")
+        .with_stderr_contains(
+            "[NOTE] including `outside-link`: \
+             symbolic link to a directory outside of the package",
+        )
         .run();
 }
 
@@ -3095,3 +3227,166 @@ src/main.rs
         &[],
     );
 }
+
+#[cargo_test]
+fn check_consistency_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("package --check-consistency")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--check-consistency` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn check_consistency_passes() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                license = "MIT"
+                description = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("package --check-consistency -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_stdout(
+            "\
+foo v0.0.1 ([CWD])
+  path-dependency-versions  passed
+  included-files            passed
+  vcs-clean                 passed
+  verify                    passed
+",
+        )
+        .with_stderr_contains("[VERIFYING] foo v0.0.1 ([CWD])")
+        .with_stderr_does_not_contain("[PACKAGED] [..]")
+        .run();
+
+    assert!(!p.root().join("target/package/foo-0.0.1.crate").exists());
+}
+
+#[cargo_test]
+fn check_consistency_reports_each_failure() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                license = "MIT"
+                description = "foo"
+                include = ["Cargo.toml", "src/lib.rs"]
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("package --check-consistency -Zunstable-options --no-verify")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_status(101)
+        .with_stdout(
+            "\
+foo v0.0.1 ([CWD])
+  path-dependency-versions  failed
+    all dependencies must have a version specified when packaging.
+    dependency `bar` does not specify a version
+    Note: The packaged dependency will use the version from crates.io,
+    the `path` specification will be removed from the dependency declaration.
+  included-files            failed
+    source files of targets are not included in the package: `src/main.rs`
+  vcs-clean                 passed
+  verify                    skipped, `--no-verify` was passed
+",
+        )
+        .with_stderr_contains("[ERROR] consistency checks failed for `foo v0.0.1 ([CWD])`")
+        .run();
+
+    p.cargo("package --check-consistency --format json -Zunstable-options --no-verify")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_status(101)
+        .with_json(
+            r#"
+{
+  "reason": "package-check",
+  "package_id": "foo 0.0.1 (path+file://[..])",
+  "check": "path-dependency-versions",
+  "status": "failed",
+  "message": "{...}"
+}
+
+{
+  "reason": "package-check",
+  "package_id": "foo 0.0.1 (path+file://[..])",
+  "check": "included-files",
+  "status": "failed",
+  "message": "source files of targets are not included in the package: `src/main.rs`"
+}
+
+{
+  "reason": "package-check",
+  "package_id": "foo 0.0.1 (path+file://[..])",
+  "check": "vcs-clean",
+  "status": "passed",
+  "message": null
+}
+
+{
+  "reason": "package-check",
+  "package_id": "foo 0.0.1 (path+file://[..])",
+  "check": "verify",
+  "status": "skipped",
+  "message": "`--no-verify` was passed"
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn check_consistency_dirty_repo() {
+    let p = git::new("foo", |p| {
+        p.file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                license = "MIT"
+                description = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+    });
+    p.change_file("src/lib.rs", "pub fn f() {}");
+
+    p.cargo("package --check-consistency -Zunstable-options --no-verify")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_status(101)
+        .with_stdout_contains("  vcs-clean                 failed")
+        .with_stdout_contains("    1 files in the working directory contain changes [..]")
+        .run();
+
+    p.cargo("package --check-consistency -Zunstable-options --no-verify --allow-dirty")
+        .masquerade_as_nightly_cargo(&["check-consistency"])
+        .with_stdout_contains("  vcs-clean                 skipped, `--allow-dirty` was passed")
+        .run();
+}
@@ -0,0 +1,184 @@
+//! Tests for `target-dir` in package profile overrides.
+
+use cargo_test_support::paths::CargoPathExt;
+use cargo_test_support::{basic_lib_manifest, project};
+
+#[cargo_test]
+fn requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [profile.dev.package.foo]
+                target-dir = "foo-target"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["package-target-dir"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `package-target-dir` is required
+
+  The package requires the Cargo feature called `package-target-dir`, but that feature is \
+  not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = [\"package-target-dir\"]` to the top of Cargo.toml \
+  (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#package-target-dir \
+  for more information about the status of this feature.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn only_in_package_override() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["package-target-dir"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [profile.dev]
+                target-dir = "foo-target"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["package-target-dir"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `target-dir` may only be specified in a package override, such as `[profile.dev.package.<name>]`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn isolated_layout() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["package-target-dir"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                mid = { path = "mid" }
+
+                [profile.dev.package.leaf]
+                target-dir = "leaf-target"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { mid::mid(); }")
+        .file(
+            "mid/Cargo.toml",
+            r#"
+                [package]
+                name = "mid"
+                version = "0.0.1"
+
+                [dependencies]
+                leaf = { path = "../leaf" }
+            "#,
+        )
+        .file("mid/src/lib.rs", "pub fn mid() { leaf::leaf(); }")
+        .file("leaf/Cargo.toml", &basic_lib_manifest("leaf"))
+        .file("leaf/src/lib.rs", "pub fn leaf() {}")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["package-target-dir"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name leaf [..]--out-dir [CWD]/leaf-target/debug/deps [..]",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-L dependency=[CWD]/target/debug/deps \
+             -L dependency=[CWD]/leaf-target/debug/deps [..]",
+        )
+        .run();
+
+    let leaf_fingerprints = p.root().join("leaf-target/debug/.fingerprint");
+    assert!(leaf_fingerprints.read_dir().unwrap().count() > 0);
+    let shared_fingerprints: Vec<_> = p
+        .root()
+        .join("target/debug/.fingerprint")
+        .read_dir()
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert!(shared_fingerprints
+        .iter()
+        .all(|name| !name.starts_with("leaf-")));
+    assert!(p.bin("foo").is_file());
+
+    // Removing the isolated directory only rebuilds that package and its
+    // dependents.
+    p.root().join("leaf-target").rm_rf();
+    p.cargo("build")
+        .masquerade_as_nightly_cargo(&["package-target-dir"])
+        .with_stderr(
+            "\
+[COMPILING] leaf v0.5.0 ([CWD]/leaf)
+[COMPILING] mid v0.0.1 ([CWD]/mid)
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn config_profile() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                leaf = { path = "leaf" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("leaf/Cargo.toml", &basic_lib_manifest("leaf"))
+        .file("leaf/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.dev.package.leaf]
+                target-dir = "leaf-target"
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zpackage-target-dir")
+        .masquerade_as_nightly_cargo(&["package-target-dir"])
+        .run();
+    assert!(p.root().join("leaf-target/debug/.fingerprint").is_dir());
+}
@@ -358,10 +358,8 @@ fn unused() {
             "\
 [UPDATING] `dummy-registry` index
 [WARNING] Patch `bar v0.2.0 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1.0` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [DOWNLOADING] crates ...
 [DOWNLOADED] bar v0.1.0 [..]
 [CHECKING] bar v0.1.0
@@ -374,10 +372,8 @@ version. [..]
         .with_stderr(
             "\
 [WARNING] Patch `bar v0.2.0 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1.0` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [FINISHED] [..]
 ",
         )
@@ -434,10 +430,8 @@ Perhaps you misspelled the source URL being patched.
 Possible URLs for `[patch.<URL>]`:
     crates-io
 [WARNING] Patch `bar v0.3.0 ([CWD]/baz)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1.0` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [DOWNLOADING] crates ...
 [DOWNLOADED] bar v0.1.0 [..]
 [CHECKING] bar v0.1.0
@@ -531,10 +525,8 @@ fn unused_from_config() {
             "\
 [UPDATING] `dummy-registry` index
 [WARNING] Patch `bar v0.2.0 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1.0` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [DOWNLOADING] crates ...
 [DOWNLOADED] bar v0.1.0 [..]
 [CHECKING] bar v0.1.0
@@ -547,10 +539,8 @@ version. [..]
         .with_stderr(
             "\
 [WARNING] Patch `bar v0.2.0 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1.0` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [FINISHED] [..]
 ",
         )
@@ -604,10 +594,8 @@ fn unused_git() {
 [UPDATING] git repository `file://[..]`
 [UPDATING] `dummy-registry` index
 [WARNING] Patch `bar v0.2.0 ([..])` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [DOWNLOADING] crates ...
 [DOWNLOADED] bar v0.1.0 [..]
 [CHECKING] bar v0.1.0
@@ -620,10 +608,8 @@ version. [..]
         .with_stderr(
             "\
 [WARNING] Patch `bar v0.2.0 ([..])` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+Its version doesn't match the requirement `^0.1` of `foo v0.0.1 ([CWD])`.
+Change the version of the patch, or the requirement of the dependency.
 [FINISHED] [..]
 ",
         )
@@ -806,10 +792,8 @@ fn add_ignored_patch() {
         .with_stderr(
             "\
 [WARNING] Patch `bar v0.1.1 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+`bar v0.1.0` is locked in Cargo.lock instead.
+Run `cargo update -p bar@0.1.0` to use the patch.
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]",
         )
         .run();
@@ -817,10 +801,8 @@ version. [..]
         .with_stderr(
             "\
 [WARNING] Patch `bar v0.1.1 ([CWD]/bar)` was not used in the crate graph.
-Check that [..]
-with the [..]
-what is [..]
-version. [..]
+`bar v0.1.0` is locked in Cargo.lock instead.
+Run `cargo update -p bar@0.1.0` to use the patch.
 [FINISHED] [..]",
         )
         .run();
@@ -1768,9 +1750,8 @@ fn two_semver_compatible() {
         .with_stderr(
             "\
 warning: Patch `bar v0.1.1 [..]` was not used in the crate graph.
-Perhaps you misspelled the source URL being patched.
-Possible URLs for `[patch.<URL>]`:
-    [CWD]/bar
+Another patch of the same package, `bar v0.1.2 ([CWD]/bar)`, is used instead.
+Remove one of them from `[patch]`.
 [FINISHED] [..]",
         )
         .run();
@@ -1822,9 +1803,8 @@ fn multipatch_select_big() {
         .with_stderr(
             "\
 warning: Patch `bar v0.1.0 [..]` was not used in the crate graph.
-Perhaps you misspelled the source URL being patched.
-Possible URLs for `[patch.<URL>]`:
-    [CWD]/bar
+Another patch of the same package, `bar v0.2.0 ([CWD]/bar)`, is used instead.
+Remove one of them from `[patch]`.
 [FINISHED] [..]",
         )
         .run();
@@ -2658,3 +2638,44 @@ failed to select a version for `qux` which could resolve this conflict"#,
         )
         .run();
 }
+
+#[cargo_test]
+fn unused_no_dependents() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1.0"
+
+                [patch.crates-io]
+                baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[WARNING] Patch `baz v0.1.0 ([CWD]/baz)` was not used in the crate graph.
+No package in the crate graph depends on `baz`.
+Remove the patch, unless it is for an optional dependency which is not enabled.
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 [..]
+[CHECKING] bar v0.1.0
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
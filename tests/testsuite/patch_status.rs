@@ -0,0 +1,111 @@
+//! Tests for `cargo patch status`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("patch status")
+        .masquerade_as_nightly_cargo(&["patch"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo patch` command is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_patches() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("patch status -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["patch"])
+        .with_stdout("")
+        .with_stderr("[NOTE] no packages are patched in the `[patch]` table")
+        .run();
+}
+
+#[cargo_test]
+fn used_and_unused() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+                baz = "0.1"
+
+                [patch.crates-io]
+                bar = { path = "bar" }
+                baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.1"))
+        .file("bar/src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.2.0"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("patch status -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["patch"])
+        .with_stdout(
+            "\
+bar v0.1.1 ([CWD]/bar) patches crates-io: used
+    required as `^0.1` by foo v0.0.1 ([CWD])
+    replaces bar v0.1.0
+baz v0.2.0 ([CWD]/baz) patches crates-io: unused
+    Its version doesn't match the requirement `^0.1` of `foo v0.0.1 ([CWD])`.
+    Change the version of the patch, or the requirement of the dependency.
+",
+        )
+        .with_stderr_contains(
+            "[WARNING] Patch `baz v0.2.0 ([CWD]/baz)` was not used in the crate graph.",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn released_upstream() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.1.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+
+                [patch.crates-io]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("patch status -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["patch"])
+        .with_stdout(
+            "\
+bar v0.1.1 ([CWD]/bar) patches crates-io: used
+    required as `^0.1` by foo v0.0.1 ([CWD])
+    replaces bar v0.1.1
+    bar v0.1.1 was released in crates-io, the patch may no longer be needed
+",
+        )
+        .run();
+}
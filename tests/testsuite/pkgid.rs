@@ -126,3 +126,79 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn format_json_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("pkgid --format json")
+        .masquerade_as_nightly_cargo(&["pkgid --format"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--format` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn format_json() {
+    Package::new("two-ver", "0.1.0").publish();
+    Package::new("two-ver", "0.2.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [dependencies]
+                two-ver = "0.1.0"
+                two-ver2 = { package = "two-ver", version = "0.2.0" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("pkgid --format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["pkgid --format"])
+        .with_json(
+            r#"
+                {
+                  "pkgid": "file://[..]/foo#0.1.0",
+                  "spec": "foo",
+                  "name": "foo",
+                  "version": "0.1.0",
+                  "source": "file://[..]/foo",
+                  "ambiguous": false
+                }
+            "#,
+        )
+        .run();
+
+    p.cargo("pkgid --format json -Zunstable-options two-ver@0.2.0")
+        .masquerade_as_nightly_cargo(&["pkgid --format"])
+        .with_json(
+            r#"
+                {
+                  "pkgid": "https://github.com/rust-lang/crates.io-index#two-ver@0.2.0",
+                  "spec": "two-ver@0.2.0",
+                  "name": "two-ver",
+                  "version": "0.2.0",
+                  "source": "https://github.com/rust-lang/crates.io-index",
+                  "ambiguous": true
+                }
+            "#,
+        )
+        .run();
+
+    p.cargo("pkgid --format text -Zunstable-options two-ver@0.1.0")
+        .masquerade_as_nightly_cargo(&["pkgid --format"])
+        .with_stdout("https://github.com/rust-lang/crates.io-index#two-ver@0.1.0")
+        .run();
+}
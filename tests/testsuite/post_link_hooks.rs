@@ -0,0 +1,142 @@
+//! Tests for `-Zpost-link-hooks` and the `build.post-link-hooks` config.
+
+use std::path::PathBuf;
+
+use cargo_test_support::{basic_manifest, paths, project};
+
+/// Builds a hook appending a marker to the file it is called with.
+fn marker_hook() -> PathBuf {
+    let p = project()
+        .at(paths::root().join("hook"))
+        .file("Cargo.toml", &basic_manifest("hook", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::io::Write;
+                fn main() {
+                    let path = std::env::args().last().unwrap();
+                    let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+                    file.write_all(b"post-link-marker").unwrap();
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build").run();
+    p.bin("hook")
+}
+
+fn has_marker(path: PathBuf) -> bool {
+    std::fs::read(path).unwrap().ends_with(b"post-link-marker")
+}
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            "[build]\npost-link-hooks = ['does-not-exist']\n",
+        )
+        .build();
+    p.cargo("build").run();
+}
+
+#[cargo_test]
+fn empty_command() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(".cargo/config.toml", "[build]\npost-link-hooks = [' ']\n")
+        .build();
+    p.cargo("build -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .with_status(101)
+        .with_stderr("[ERROR] `build.post-link-hooks` must not contain empty commands")
+        .run();
+}
+
+#[cargo_test]
+fn runs_on_binaries() {
+    let hook = marker_hook();
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!("[build]\npost-link-hooks = ['{}']\n", hook.display()),
+        )
+        .build();
+
+    p.cargo("build -v -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .with_stderr_contains("[RUNNING] `[..]hook[EXE] [CWD]/target/debug/deps/foo-[..][EXE]`")
+        .with_stderr_does_not_contain("[RUNNING] `[..]hook[EXE] [..]build_script_build[..]`")
+        .with_stderr_does_not_contain("[RUNNING] `[..]hook[EXE] [..].rlib`")
+        .run();
+    // The linked binary is the one modified by the hook.
+    assert!(has_marker(p.bin("foo")));
+    p.process(&p.bin("foo")).run();
+}
+
+#[cargo_test]
+fn rebuilds_when_hooks_change() {
+    let hook = marker_hook();
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!("[build]\npost-link-hooks = ['{}']\n", hook.display()),
+        )
+        .build();
+
+    p.cargo("build -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .run();
+    p.cargo("build -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .with_stderr("[FINISHED] [..]")
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        &format!(
+            "[build]\npost-link-hooks = ['{} --again']\n",
+            hook.display()
+        ),
+    );
+    p.cargo("build -v -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]): the config settings changed")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name foo src/lib.rs [..]")
+        .with_stderr_contains(
+            "[RUNNING] `[..]hook[EXE] --again [CWD]/target/debug/deps/foo-[..][EXE]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn failing_hook() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            "[build]\npost-link-hooks = ['does-not-exist']\n",
+        )
+        .build();
+
+    p.cargo("build -Zpost-link-hooks")
+        .masquerade_as_nightly_cargo(&["post-link-hooks"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] post-link hook `does-not-exist` failed on `[CWD]/target/debug/deps/foo-[..][EXE]`",
+        )
+        .run();
+}
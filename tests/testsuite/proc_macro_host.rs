@@ -0,0 +1,271 @@
+//! Tests for `--proc-macro-host`.
+
+use cargo_test_support::{cross_compile, project};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file("src/lib.rs", "pm::noop!();")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn noop(_input: TokenStream) -> TokenStream {
+                    TokenStream::new()
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build --proc-macro-host")
+        .arg(cross_compile::native())
+        .arg("--target")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["proc-macro-host"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--proc-macro-host` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn requires_target() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file("src/lib.rs", "pm::noop!();")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn noop(_input: TokenStream) -> TokenStream {
+                    TokenStream::new()
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build -Zunstable-options --proc-macro-host")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["proc-macro-host"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `--proc-macro-host` requires `--target`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn same_as_host() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file("src/lib.rs", "pm::noop!();")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn noop(_input: TokenStream) -> TokenStream {
+                    TokenStream::new()
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build -v -Zunstable-options --proc-macro-host")
+        .arg(cross_compile::native())
+        .arg("--target")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["proc-macro-host"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name pm [..]")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name pm [..]--target[..]")
+        .run();
+}
+
+#[cargo_test]
+fn missing_std() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file("src/lib.rs", "pm::noop!();")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn noop(_input: TokenStream) -> TokenStream {
+                    TokenStream::new()
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build -Zunstable-options --proc-macro-host sparc64-unknown-linux-gnu")
+        .arg("--target")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["proc-macro-host"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] cannot build proc-macro `pm v0.1.0 ([CWD]/pm)` for the host `sparc64-unknown-linux-gnu`
+
+The standard library for `sparc64-unknown-linux-gnu` is not installed at `[..]`, \
+install it with `rustup target add sparc64-unknown-linux-gnu`, or pass another `--proc-macro-host`.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_linker() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file("src/lib.rs", "pm::noop!();")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn noop(_input: TokenStream) -> TokenStream {
+                    TokenStream::new()
+                }
+            "#,
+        )
+        .build();
+    let alternate = cross_compile::alternate();
+    p.cargo("build -Zunstable-options --proc-macro-host")
+        .arg(alternate)
+        .arg("--target")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["proc-macro-host"])
+        .with_status(101)
+        .with_stderr(&format!(
+            "\
+[ERROR] cannot build proc-macro `pm v0.1.0 ([CWD]/pm)` for the host `{alternate}`
+
+No linker is configured for `{alternate}`, set `target.{alternate}.linker` \
+in the Cargo configuration.
+",
+        ))
+        .run();
+}
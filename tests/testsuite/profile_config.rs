@@ -323,6 +323,42 @@ fn profile_config_override_precedence() {
         .run();
 }
 
+#[cargo_test]
+fn profile_cli_override_precedence() {
+    // `--config` values take precedence over environment variables, config
+    // files and the manifest.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [profile.release]
+                debug = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", "[profile.release]\ndebug = false")
+        .build();
+
+    p.cargo("build -v --release")
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "false")
+        .with_stderr_does_not_contain("[..]-C debuginfo[..]")
+        .run();
+
+    p.cargo("build -v --release --config profile.release.debug=true")
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "false")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc --crate-name foo [..]-C debuginfo=2 [..]
+[FINISHED] release [optimized + debuginfo] target(s) in [..]",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn profile_config_no_warn_unknown_override() {
     let p = project()
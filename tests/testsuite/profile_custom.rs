@@ -548,6 +548,56 @@ fn clean_custom_dirname() {
     assert!(!p.build_dir().join("other").is_dir());
 }
 
+#[cargo_test]
+fn custom_profile_across_commands() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [profile.release-lto]
+                inherits = "release"
+                lto = true
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {}
+
+                #[test]
+                fn t() {}
+            "#,
+        )
+        .build();
+
+    p.cargo("build --profile release-lto -v")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]-C opt-level=3[..]-C lto[..]`")
+        .with_stderr_contains("[FINISHED] release-lto [optimized] target(s) in [..]")
+        .run();
+    let exe = format!("foo{}", std::env::consts::EXE_SUFFIX);
+    assert!(p.build_dir().join("release-lto").join(exe).is_file());
+    assert!(!p.build_dir().join("release").is_dir());
+
+    for command in ["test", "bench"] {
+        p.cargo(command)
+            .arg("--profile=release-lto")
+            .with_stderr_contains("[FINISHED] release-lto [optimized] target(s) in [..]")
+            .with_stderr_contains(
+                "[RUNNING] unittests src/main.rs (target/release-lto/deps/foo-[..][EXE])",
+            )
+            .run();
+    }
+
+    p.cargo("doc --profile release-lto")
+        .with_stderr_contains("[FINISHED] release-lto [optimized] target(s) in [..]")
+        .run();
+    assert!(p.build_dir().join("doc/foo/index.html").is_file());
+}
+
 #[cargo_test]
 fn unknown_profile() {
     let p = project()
@@ -513,3 +513,70 @@ fn build_override_shared() {
 
     p.cargo("run").run();
 }
+
+#[cargo_test]
+fn profile_override_change_rebuilds() {
+    let manifest = |opt_level: u32| {
+        format!(
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = {{path = "bar"}}
+
+                [profile.dev.package.bar]
+                opt-level = {}
+
+                [profile.dev.build-override]
+                opt-level = 3
+            "#,
+            opt_level
+        )
+    };
+    let p = project()
+        .file("Cargo.toml", &manifest(3))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rerun-if-changed=build.rs"); }"#,
+        )
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v")
+        .with_stderr_unordered(
+            "\
+[COMPILING] foo [..]
+[RUNNING] `rustc --crate-name build_script_build [..] -C opt-level=3 [..]`
+[RUNNING] `[..]/build-script-build`
+[CHECKING] bar [..]
+[RUNNING] `rustc --crate-name bar [..] -C opt-level=3 [..]`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] [..]
+",
+        )
+        .with_stderr_line_without(
+            &["[RUNNING] `rustc --crate-name foo [..]"],
+            &["-C opt-level"],
+        )
+        .run();
+
+    // Only the overridden package and its dependents are rebuilt.
+    p.change_file("Cargo.toml", &manifest(2));
+    p.cargo("check")
+        .with_stderr(
+            "\
+[CHECKING] bar [..]
+[CHECKING] foo [..]
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    // The artifacts of both settings are kept apart.
+    p.change_file("Cargo.toml", &manifest(3));
+    p.cargo("check").with_stderr("[FINISHED] [..]").run();
+}
@@ -353,6 +353,37 @@ must be a boolean (`true`/`false`) or a string (`\"thin\"`/`\"fat\"`/`\"off\"`)
         .run();
 }
 
+#[cargo_test]
+fn profile_split_debuginfo_invalid() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [profile.dev.package.bar]
+                split-debuginfo = "split"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  `profile.dev.package.bar.split-debuginfo` setting of `split` is not a valid setting, \
+must be `off`, `packed` or `unpacked`
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn profile_panic_test_bench() {
     let p = project()
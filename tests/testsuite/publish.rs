@@ -2961,3 +2961,247 @@ Caused by:
         .with_status(101)
         .run();
 }
+
+fn make_workspace_to_publish() -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b", "c"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+                description = "a"
+                license = "MIT"
+                documentation = "a"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+                description = "b"
+                license = "MIT"
+                documentation = "b"
+
+                [dependencies]
+                a = { path = "../a", version = "0.1.0" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+                [package]
+                name = "c"
+                version = "0.1.0"
+                publish = false
+            "#,
+        )
+        .file("c/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn publish_workspace_requires_unstable_options() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+
+    p.cargo("publish --workspace")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `-p` argument must be specified to select a single package to publish
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_workspace_in_dependency_order() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+
+    // `b` is verified against `a` from the registry, so this only succeeds if
+    // `a` is published first.
+    p.cargo("publish --workspace -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr_contains("[NOTE] skipping `c`, `package.publish` is set to `false`")
+        .with_stderr_contains("  Publishing 2 packages in order: a, b")
+        .with_stderr_contains("[PUBLISHED] a v0.1.0 at registry `crates-io`")
+        .with_stderr_contains("[VERIFYING] b v0.1.0 ([CWD]/b)")
+        .with_stderr_contains("[PUBLISHED] b v0.1.0 at registry `crates-io`")
+        .with_stderr_does_not_contain("[PACKAGING] c [..]")
+        .run();
+}
+
+#[cargo_test]
+fn publish_workspace_dry_run() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+
+    p.cargo("publish --workspace --exclude c --dry-run -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr_contains("  Publishing 2 packages in order: a, b")
+        .with_stderr_contains("[VERIFYING] a v0.1.0 ([CWD]/a)")
+        .with_stderr_contains(
+            "[NOTE] skipping verification of `b`, it depends on `a` which is not \
+             uploaded by a dry run",
+        )
+        .with_stderr_does_not_contain("[VERIFYING] b [..]")
+        .with_stderr_does_not_contain("[UPLOADED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn publish_multiple_packages_in_order() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+
+    p.cargo("publish -p b -p a --no-verify -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr_contains("  Publishing 2 packages in order: a, b")
+        .with_stderr_contains("[PUBLISHED] a v0.1.0 at registry `crates-io`")
+        .with_stderr_contains("[PUBLISHED] b v0.1.0 at registry `crates-io`")
+        .run();
+}
+
+#[cargo_test]
+fn publish_group() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [workspace]
+            members = ["a", "b", "c"]
+
+            [workspace.metadata.publish-groups]
+            runtime = ["a", "b"]
+            tools = ["c"]
+        "#,
+    );
+
+    p.cargo("publish --group runtime --no-verify -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr_contains("  Publishing 2 packages in order: a, b")
+        .with_stderr_contains("[PUBLISHED] a v0.1.0 at registry `crates-io`")
+        .with_stderr_contains("[PUBLISHED] b v0.1.0 at registry `crates-io`")
+        .run();
+
+    p.cargo("publish --group runtime --exclude b --dry-run -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr_contains("  Publishing 1 package in order: a")
+        .with_stderr_does_not_contain("[PACKAGING] b [..]")
+        .run();
+
+    p.cargo("publish --group tools --dry-run -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_stderr(
+            "\
+[NOTE] skipping `c`, `package.publish` is set to `false`
+[WARNING] none of the selected packages can be published
+",
+        )
+        .run();
+
+    p.cargo("publish --group other -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] publish group `other` is not defined in `[workspace.metadata.publish-groups]`
+
+available publish groups: runtime, tools
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_group_requires_unstable_options() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+
+    p.cargo("publish --group runtime")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `-p` argument must be specified to select a single package to publish",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_subset_with_unpublishable_dep() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = make_workspace_to_publish();
+    p.change_file(
+        "b/Cargo.toml",
+        r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            description = "b"
+            license = "MIT"
+            documentation = "b"
+
+            [dependencies]
+            a = { path = "../a" }
+            c = { path = "../c", version = "0.1.0" }
+        "#,
+    );
+
+    p.cargo("publish -p a -p b --dry-run -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `b` depends on `a` by path only, which cannot be published
+Add a `version` to the dependency on `a`.
+",
+        )
+        .run();
+
+    p.change_file(
+        "b/Cargo.toml",
+        r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            description = "b"
+            license = "MIT"
+            documentation = "b"
+
+            [dependencies]
+            a = { path = "../a", version = "0.1.0" }
+            c = { path = "../c", version = "0.1.0" }
+        "#,
+    );
+    p.cargo("publish -p a -p b --dry-run -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["publish --workspace"])
+        .with_status(101)
+        .with_stderr("[ERROR] `b` depends on `c`, which has `package.publish` set to `false`")
+        .run();
+}
@@ -390,13 +390,22 @@ dependencies = [
 [FINISHED] release [optimized] target(s) in [..]
 [INSTALLING] [..]/.cargo/bin/foo[EXE]
 [INSTALLED] package `foo v0.1.0` (executable `foo[EXE]`)
+[NOTE] the dependencies were pinned by the Cargo.lock of the package (--locked)
 [WARNING] be sure to add [..]
 ",
         )
         .run();
 
-    // Try again without --locked, make sure it uses 0.1.1 and does not warn.
+    // Without --locked, the lock file is still used and the hint changes.
     cargo_process("install --force foo")
+        .with_stderr_contains(
+            "[WARNING] package `bar v0.1.0` in Cargo.lock is yanked in registry \
+    `crates-io`, consider running with --ignore-lockfile",
+        )
+        .run();
+
+    // Try again with --ignore-lockfile, make sure it uses 0.1.1 and does not warn.
+    cargo_process("install --force --ignore-lockfile foo")
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -408,6 +417,7 @@ dependencies = [
 [FINISHED] release [optimized] target(s) in [..]
 [REPLACING] [..]/.cargo/bin/foo[EXE]
 [REPLACED] package `foo v0.1.0` with `foo v0.1.0` (executable `foo[EXE]`)
+[NOTE] the published Cargo.lock of the package was ignored (--ignore-lockfile)
 [WARNING] be sure to add [..]
 ",
         )
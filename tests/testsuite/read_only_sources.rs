@@ -0,0 +1,114 @@
+//! Tests for `-Zread-only-sources`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, paths, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zread-only-sources")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `-Z` flag is only accepted on the nightly channel of Cargo[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn lockfile_update_is_an_error() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zread-only-sources")
+        .masquerade_as_nightly_cargo(&["read-only-sources"])
+        .env("CARGO_TARGET_DIR", paths::root().join("target"))
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the lock file [CWD]/Cargo.lock needs to be updated but `-Zread-only-sources` \
+forbids writing to package roots
+Update the lock file in a writable checkout and commit it.
+",
+        )
+        .run();
+    assert!(!p.root().join("Cargo.lock").exists());
+}
+
+#[cargo_test]
+fn target_dir_inside_package_root() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("check -Zread-only-sources")
+        .masquerade_as_nightly_cargo(&["read-only-sources"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] cannot create build directory `[CWD]/target` inside `[CWD]` because \
+`-Zread-only-sources` is enabled
+Set `CARGO_TARGET_DIR` or `build.target-dir` to a writable location outside of the package sources.
+",
+        )
+        .run();
+    assert!(!p.root().join("target").exists());
+}
+
+#[cargo_test]
+fn target_dir_outside_package_root() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    let target = paths::root().join("out");
+    p.cargo("check -Zread-only-sources")
+        .masquerade_as_nightly_cargo(&["read-only-sources"])
+        .env("CARGO_TARGET_DIR", &target)
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    assert!(target.join("debug").is_dir());
+    assert!(!p.root().join("target").exists());
+}
+
+#[cargo_test]
+fn cache_home_overlay() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    let cache_home = paths::root().join("cache-home");
+    p.cargo("check -Zread-only-sources")
+        .masquerade_as_nightly_cargo(&["read-only-sources"])
+        .env("CARGO_CACHE_HOME", &cache_home)
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the lock file [CWD]/Cargo.lock needs to be updated[..]")
+        .run();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("check -Zread-only-sources")
+        .masquerade_as_nightly_cargo(&["read-only-sources"])
+        .env("CARGO_CACHE_HOME", &cache_home)
+        .env("CARGO_TARGET_DIR", paths::root().join("out"))
+        .with_stderr_contains("[DOWNLOADED] bar v1.0.0 [..]")
+        .run();
+    assert!(cache_home.join("registry/cache").is_dir());
+    assert!(cache_home.join("registry/src").is_dir());
+}
@@ -2627,6 +2627,12 @@ fn ignores_unknown_index_version() {
              └── bar v1.0.0\n\
             ",
         )
+        .with_stderr_contains(
+            "\
+[WARNING] some index entries of registry `[..]` use a newer schema than this version of \
+Cargo understands and were ignored, such as `bar v1.0.1`
+Updating Cargo may make more versions available.",
+        )
         .run();
 }
 
@@ -3529,3 +3535,66 @@ fn unpack_again_when_cargo_ok_is_unrecognized() {
     let ok = fs::read_to_string(&cargo_ok).unwrap();
     assert_eq!(&ok, r#"{"v":1}"#);
 }
+
+#[cargo_test]
+fn max_concurrent_downloads() {
+    let _server = setup_http();
+    for name in ["a", "b", "c"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+                c = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("fetch -Zmax-concurrent-downloads --config http.max-concurrent-downloads=1")
+        .masquerade_as_nightly_cargo(&["max-concurrent-downloads"])
+        .with_stderr_unordered(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] a v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] b v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] c v1.0.0 (registry `dummy-registry`)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn max_concurrent_downloads_zero() {
+    let _server = setup_http();
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("fetch -Zmax-concurrent-downloads --config http.max-concurrent-downloads=0")
+        .masquerade_as_nightly_cargo(&["max-concurrent-downloads"])
+        .with_status(101)
+        .with_stderr_contains("[..]`http.max-concurrent-downloads` must be at least 1")
+        .run();
+}
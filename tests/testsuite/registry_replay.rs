@@ -0,0 +1,73 @@
+//! Tests for replaying recorded registry exchanges in the test registry.
+
+use cargo_test_support::curr_dir;
+use cargo_test_support::project;
+use cargo_test_support::registry::RegistryBuilder;
+
+#[cargo_test]
+fn replays_recorded_exchanges() {
+    let _server = RegistryBuilder::new()
+        .replay(curr_dir!().join("registry_replay/bar.json"))
+        .build();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "pub use bar::bar;")
+        .build();
+
+    p.cargo("check")
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 (registry `dummy-registry`)
+[CHECKING] bar v0.1.0
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn unrecorded_request() {
+    let _server = RegistryBuilder::new()
+        .replay(curr_dir!().join("registry_replay/bar.json"))
+        .build();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                baz = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("fetch")
+        .env("CARGO_NET_RETRY", "0")
+        .with_status(101)
+        .with_stderr_contains(
+            "  failed to get successful HTTP response from \
+             `http://127.0.0.1:[..]/index/3/b/baz` (127.0.0.1), got 500",
+        )
+        .with_stderr_contains("  no recorded response for `get /index/3/b/baz`")
+        .run();
+}
@@ -0,0 +1,293 @@
+//! Tests for the `cargo release-plan` command.
+
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::{basic_manifest, git, project, Project};
+
+fn workspace() -> Project {
+    Package::new("a", "0.1.0").publish();
+    Package::new("b", "0.1.0").publish();
+    Package::new("c", "0.1.0").publish();
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b", "c", "d", "e"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.2.0"
+
+                [dependencies]
+                a = { path = "../a", version = "0.1.0" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+                [package]
+                name = "c"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b", version = "0.2.0" }
+            "#,
+        )
+        .file("c/src/lib.rs", "")
+        .file(
+            "d/Cargo.toml",
+            r#"
+                [package]
+                name = "d"
+                version = "0.1.0"
+                publish = false
+            "#,
+        )
+        .file("d/src/lib.rs", "")
+        .file(
+            "e/Cargo.toml",
+            r#"
+                [package]
+                name = "e"
+                version = "0.1.0"
+
+                [dependencies]
+                c = { path = "../c", version = "0.1.0" }
+
+                [dev-dependencies]
+                d = { path = "../d" }
+            "#,
+        )
+        .file("e/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = workspace();
+    p.cargo("release-plan")
+        .masquerade_as_nightly_cargo(&["release-plan"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo release-plan` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn plan() {
+    let p = workspace();
+    p.cargo("release-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["release-plan"])
+        .with_json(
+            r#"
+                {
+                  "version": 1,
+                  "registry": "crates-io",
+                  "packages": [
+                    {
+                      "name": "b",
+                      "version": "0.2.0",
+                      "manifest_version": "0.2.0",
+                      "registry_version": "0.1.0",
+                      "reason": "new-version",
+                      "dependencies": []
+                    },
+                    {
+                      "name": "c",
+                      "version": "0.1.1",
+                      "manifest_version": "0.1.0",
+                      "registry_version": "0.1.0",
+                      "reason": "dependency",
+                      "dependencies": ["b"]
+                    },
+                    {
+                      "name": "e",
+                      "version": "0.1.0",
+                      "manifest_version": "0.1.0",
+                      "registry_version": null,
+                      "reason": "unpublished",
+                      "dependencies": ["c"]
+                    }
+                  ],
+                  "skipped": [
+                    { "name": "a", "version": "0.1.0", "reason": "up-to-date" },
+                    { "name": "d", "version": "0.1.0", "reason": "not-publishable" }
+                  ],
+                  "tags": ["b-v0.2.0", "c-v0.1.1", "e-v0.1.0"],
+                  "commits": [
+                    {
+                      "message": "Bump versions of c",
+                      "bumps": [{ "name": "c", "from": "0.1.0", "to": "0.1.1" }]
+                    }
+                  ]
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dependency_cycle() {
+    registry::init();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                b = { path = "../b", version = "0.1.0" }
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [build-dependencies]
+                a = { path = "../a", version = "0.1.0" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("release-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["release-plan"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] cannot publish `a`, `b` as they depend on each other in a cycle",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn compatible_release_needs_no_dependency_bump() {
+    Package::new("a", "0.1.0").publish();
+    Package::new("b", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.1"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "../a", version = "0.1.1" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("release-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["release-plan"])
+        .with_json(
+            r#"
+                {
+                  "version": 1,
+                  "registry": "crates-io",
+                  "packages": [
+                    {
+                      "name": "a",
+                      "version": "0.1.1",
+                      "manifest_version": "0.1.1",
+                      "registry_version": "0.1.0",
+                      "reason": "new-version",
+                      "dependencies": []
+                    }
+                  ],
+                  "skipped": [
+                    { "name": "b", "version": "0.1.0", "reason": "up-to-date" }
+                  ],
+                  "tags": ["a-v0.1.1"],
+                  "commits": []
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn changed_since_base_rev() {
+    Package::new("a", "0.1.0").publish();
+    Package::new("b", "0.1.0").publish();
+    let (p, repo) = git::new_repo("foo", |p| {
+        p.file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+    });
+    git::tag(&repo, "v1");
+    p.change_file("a/src/lib.rs", "pub fn f() {}");
+    git::add(&repo);
+    git::commit(&repo);
+
+    p.cargo("release-plan -Zunstable-options --base-rev v1")
+        .masquerade_as_nightly_cargo(&["release-plan"])
+        .with_json(
+            r#"
+                {
+                  "version": 1,
+                  "registry": "crates-io",
+                  "packages": [
+                    {
+                      "name": "a",
+                      "version": "0.1.1",
+                      "manifest_version": "0.1.0",
+                      "registry_version": "0.1.0",
+                      "reason": "changed",
+                      "dependencies": []
+                    }
+                  ],
+                  "skipped": [
+                    { "name": "b", "version": "0.1.0", "reason": "up-to-date" }
+                  ],
+                  "tags": ["a-v0.1.1"],
+                  "commits": [
+                    {
+                      "message": "Bump versions of a",
+                      "bumps": [{ "name": "a", "from": "0.1.0", "to": "0.1.1" }]
+                    }
+                  ]
+                }
+            "#,
+        )
+        .run();
+}
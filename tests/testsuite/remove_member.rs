@@ -0,0 +1,265 @@
+//! Tests for the `cargo remove-member` command.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["a", "b", "c"]
+
+[workspace.dependencies]
+a = { path = "a", version = "0.1.0" }
+"#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a.workspace = true
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+[package]
+name = "c"
+version = "0.1.0"
+
+[dev-dependencies]
+a = { path = "../a", version = "0.1.0" }
+"#,
+        )
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("remove-member a")
+        .masquerade_as_nightly_cargo(&["remove-member"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo remove-member` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_a_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["a", "b", "c"]
+
+[workspace.dependencies]
+a = { path = "a", version = "0.1.0" }
+"#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a.workspace = true
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+[package]
+name = "c"
+version = "0.1.0"
+
+[dev-dependencies]
+a = { path = "../a", version = "0.1.0" }
+"#,
+        )
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("remove-member d -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["remove-member"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] package `d` is not a member of the workspace at `[ROOT]/foo/Cargo.toml`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn root_package() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("remove-member foo -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["remove-member"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] package `foo` is the root package of the workspace and cannot be removed",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rewrite_deps() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["a", "b", "c"]
+
+[workspace.dependencies]
+a = { path = "a", version = "0.1.0" }
+"#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a.workspace = true
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+[package]
+name = "c"
+version = "0.1.0"
+
+[dev-dependencies]
+a = { path = "../a", version = "0.1.0" }
+"#,
+        )
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("remove-member a --rewrite-deps -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["remove-member"])
+        .with_stderr(
+            "\
+[UPDATING] `workspace.dependencies.a` to version `0.1.0`
+[UPDATING] `dev-dependencies.a` of `c` to version `0.1.0`
+[REMOVING] `a` from the members of the workspace at `[ROOT]/foo/Cargo.toml`
+",
+        )
+        .run();
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+[workspace]
+members = ["b", "c"]
+
+[workspace.dependencies]
+a = { version = "0.1.0" }
+"#,
+    );
+    assert_eq!(
+        p.read_file("c/Cargo.toml"),
+        r#"
+[package]
+name = "c"
+version = "0.1.0"
+
+[dev-dependencies]
+a = { version = "0.1.0" }
+"#,
+    );
+}
+
+#[cargo_test]
+fn exclude_with_remaining_path_deps() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["a", "b", "c"]
+
+[workspace.dependencies]
+a = { path = "a", version = "0.1.0" }
+"#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                a.workspace = true
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+[package]
+name = "c"
+version = "0.1.0"
+
+[dev-dependencies]
+a = { path = "../a", version = "0.1.0" }
+"#,
+        )
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("remove-member a -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["remove-member"])
+        .with_stderr(
+            "\
+[REMOVING] `a` from the members of the workspace at `[ROOT]/foo/Cargo.toml`
+[WARNING] `a` is still a path dependency of:
+  `workspace.dependencies.a`
+  `dev-dependencies.a` of `c`
+it was added to `workspace.exclude`, \
+pass `--rewrite-deps` to depend on its version from the registry instead
+",
+        )
+        .run();
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+[workspace]
+members = ["b", "c"]
+exclude = ["a"]
+
+[workspace.dependencies]
+a = { path = "a", version = "0.1.0" }
+"#,
+    );
+}
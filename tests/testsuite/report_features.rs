@@ -0,0 +1,108 @@
+//! Tests for the `cargo report features` command.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("report features")
+        .masquerade_as_nightly_cargo(&["report features"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo report features` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn features_of_dependency() {
+    Package::new("bar", "1.0.0")
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .feature("serde", &[])
+        .feature("extra", &[])
+        .feature("unused", &[])
+        .publish();
+    Package::new("baz", "1.0.0")
+        .feature_dep("bar", "1.0", &["extra"])
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+
+                [features]
+                serde = ["bar/serde"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("report features -Zunstable-options -p bar --features serde")
+        .masquerade_as_nightly_cargo(&["report features"])
+        .with_stdout(
+            "\
+features of bar v1.0.0:
+  default: enabled by the `bar` dependency of baz v1.0.0
+  extra: enabled by the `bar` dependency of baz v1.0.0
+  serde: enabled by feature `serde` of foo v0.0.1 ([CWD]) (`bar/serde`)
+    which is enabled on the command line
+  std: enabled by feature `default` of bar v1.0.0
+    which is enabled by the `bar` dependency of baz v1.0.0
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn features_of_current_package() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                default = ["a"]
+                a = []
+                b = []
+                c = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("report features -Zunstable-options --features b")
+        .masquerade_as_nightly_cargo(&["report features"])
+        .with_stdout(
+            "\
+features of foo v0.0.1 ([CWD]):
+  a: enabled by feature `default` of foo v0.0.1 ([CWD])
+    which is enabled as a default feature of a package selected on the command line
+  b: enabled on the command line
+  default: enabled as a default feature of a package selected on the command line
+",
+        )
+        .run();
+
+    p.cargo("report features -Zunstable-options --no-default-features")
+        .masquerade_as_nightly_cargo(&["report features"])
+        .with_stdout(
+            "\
+features of foo v0.0.1 ([CWD]):
+  (none)
+",
+        )
+        .run();
+}
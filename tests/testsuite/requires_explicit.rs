@@ -0,0 +1,212 @@
+//! Tests for `requires-explicit` on targets.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [[bench]]
+                name = "huge"
+                requires-explicit = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("benches/huge.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `requires-explicit` is required
+
+  The package requires the Cargo feature called `requires-explicit`, but that feature is \
+  not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = [\"requires-explicit\"]` to the top of Cargo.toml \
+  (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#requires-explicit \
+  for more information about the status of this feature.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_on_lib() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["requires-explicit"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [lib]
+                requires-explicit = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  the `requires-explicit` key cannot be set on the library target
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn skipped_by_default_and_all_targets() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["requires-explicit"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[bin]]
+                name = "fuzz"
+                path = "src/bin/fuzz.rs"
+                requires-explicit = true
+
+                [[bench]]
+                name = "huge"
+                requires-explicit = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("src/bin/fuzz.rs", "fn main() {}")
+        .file("examples/ex.rs", "fn main() {}")
+        .file("benches/huge.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_does_not_contain("[..]--crate-name fuzz[..]")
+        .run();
+    assert!(p.bin("foo").is_file());
+    assert!(!p.bin("fuzz").is_file());
+
+    p.cargo("check --all-targets -v")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name ex [..]")
+        .with_stderr_does_not_contain("[..]--crate-name fuzz[..]")
+        .with_stderr_does_not_contain("[..]--crate-name huge[..]")
+        .run();
+
+    p.cargo("check --bins --benches -v")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_does_not_contain("[..]--crate-name fuzz[..]")
+        .with_stderr_does_not_contain("[..]--crate-name huge[..]")
+        .run();
+}
+
+#[cargo_test]
+fn built_when_named() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["requires-explicit"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[bin]]
+                name = "fuzz"
+                path = "src/bin/fuzz.rs"
+                requires-explicit = true
+
+                [[bench]]
+                name = "huge"
+                requires-explicit = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("src/bin/fuzz.rs", "fn main() {}")
+        .file("examples/ex.rs", "fn main() {}")
+        .file("benches/huge.rs", "")
+        .build();
+
+    p.cargo("build --bin fuzz")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .run();
+    assert!(p.bin("fuzz").is_file());
+
+    p.cargo("check --bench huge -v")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name huge [..]")
+        .run();
+
+    p.cargo("clean")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .run();
+    p.cargo("check --bench 'h*' -v")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name huge [..]")
+        .run();
+}
+
+#[cargo_test]
+fn run_picks_other_bin() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["requires-explicit"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[bin]]
+                name = "fuzz"
+                path = "src/bin/fuzz.rs"
+                requires-explicit = true
+
+                [[bench]]
+                name = "huge"
+                requires-explicit = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("src/bin/fuzz.rs", "fn main() {}")
+        .file("examples/ex.rs", "fn main() {}")
+        .file("benches/huge.rs", "")
+        .build();
+
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["requires-explicit"])
+        .with_stderr_contains("[RUNNING] `target/debug/foo[EXE]`")
+        .run();
+}
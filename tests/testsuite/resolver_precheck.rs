@@ -0,0 +1,125 @@
+//! Tests for `-Zresolver-precheck`.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn conflicting_member_requirements() {
+    Package::new("foo", "1.0.1").publish();
+    Package::new("foo", "1.0.2").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                foo = "=1.0.1"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+
+                [dependencies]
+                foo = "=1.0.2"
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zresolver-precheck")
+        .masquerade_as_nightly_cargo(&["resolver-precheck"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] failed to select a version for the requirement `foo = \"=1.0.2\"`
+  required by package `b v0.1.0 ([CWD]/b)`
+which conflicts with the requirement `foo = \"=1.0.1\"`
+  required by package `a v0.1.0 ([CWD]/a)`
+only one semver-compatible version of `foo` can be selected, and no version of `foo` matches both requirements
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn conflicting_transitive_requirements() {
+    Package::new("foo", "1.0.1").publish();
+    Package::new("foo", "1.0.2").publish();
+    Package::new("bar", "0.1.0").dep("foo", "=1.0.2").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "=0.1.0"
+                foo = "=1.0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zresolver-precheck")
+        .masquerade_as_nightly_cargo(&["resolver-precheck"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] failed to select a version for the requirement `foo = \"=1.0.2\"`
+  required by package `bar v0.1.0`
+which conflicts with the requirement `foo = \"=1.0.1\"`
+  required by package `a v0.1.0 ([CWD])`
+only one semver-compatible version of `foo` can be selected, and no version of `foo` matches both requirements
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn compatible_families_are_allowed() {
+    Package::new("foo", "1.0.0").publish();
+    Package::new("foo", "2.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+
+                [dependencies]
+                foo = "1.0"
+
+                [build-dependencies]
+                foo2 = { package = "foo", version = "2.0" }
+
+                [dev-dependencies]
+                foo = "=1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zresolver-precheck")
+        .masquerade_as_nightly_cargo(&["resolver-precheck"])
+        .run();
+}
@@ -0,0 +1,81 @@
+//! Tests for `-Zresolver-prefetch`.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::{Dependency, Package, RegistryBuilder};
+
+const PREFETCH_LOG: &str = "cargo::core::resolver::dep_cache=debug";
+
+#[cargo_test]
+fn same_lockfile() {
+    let _registry = RegistryBuilder::new().http_index().build();
+    Package::new("c", "0.1.0").publish();
+    Package::new("d", "0.1.0").publish();
+    Package::new("e", "0.1.0").publish();
+    Package::new("b", "0.1.0")
+        .dep("c", "0.1")
+        .add_dep(Dependency::new("d", "0.1").optional(true))
+        .dev_dep("e", "0.1")
+        .publish();
+    Package::new("a", "0.1.0").dep("b", "0.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zresolver-prefetch")
+        .masquerade_as_nightly_cargo(&["resolver-prefetch"])
+        .run();
+    let prefetched = p.read_lockfile();
+
+    p.cargo("generate-lockfile").run();
+    assert_eq!(prefetched, p.read_lockfile());
+    assert!(!prefetched.contains("name = \"d\""));
+    assert!(!prefetched.contains("name = \"e\""));
+}
+
+#[cargo_test]
+fn prefetches_next_dependencies() {
+    let _registry = RegistryBuilder::new().http_index().build();
+    Package::new("c", "0.1.0").publish();
+    Package::new("d", "0.1.0").publish();
+    Package::new("e", "0.1.0").publish();
+    Package::new("b", "0.1.0")
+        .dep("c", "0.1")
+        .add_dep(Dependency::new("d", "0.1").optional(true))
+        .dev_dep("e", "0.1")
+        .publish();
+    Package::new("a", "0.1.0").dep("b", "0.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zresolver-prefetch")
+        .masquerade_as_nightly_cargo(&["resolver-prefetch"])
+        .env("CARGO_LOG", PREFETCH_LOG)
+        .with_stderr_contains("[..]prefetching `b` ^0.1[..]")
+        .with_stderr_contains("[..]prefetching `c` ^0.1[..]")
+        .with_stderr_does_not_contain("[..]prefetching `d`[..]")
+        .with_stderr_does_not_contain("[..]prefetching `e`[..]")
+        .run();
+}
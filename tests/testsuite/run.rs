@@ -166,6 +166,41 @@ fn simple_with_args() {
     p.cargo("run hello world").run();
 }
 
+#[cargo_test]
+fn args_file() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let args: Vec<_> = std::env::args().skip(1).collect();
+                    assert_eq!(args, ["hello world", "--flag", "", "last"]);
+                }
+            "#,
+        )
+        .file("args.txt", "hello world\n--flag\n\n")
+        .build();
+
+    p.cargo("run --args-file args.txt -- last")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--args-file` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("run -Zunstable-options --args-file args.txt -- last")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] `target/debug/foo[EXE] [..]hello world[..] --flag [..] last`
+",
+        )
+        .run();
+}
+
 #[cfg(unix)]
 #[cargo_test]
 fn simple_with_non_utf8_args() {
@@ -192,3 +192,124 @@ fn rust_version_older_than_edition() {
         )
         .run();
 }
+
+#[cargo_test]
+fn msrv_policy_compatible_selects_older_version() {
+    Package::new("bar", "1.0.0")
+        .rust_version("1.60.0")
+        .file("src/lib.rs", "")
+        .publish();
+    Package::new("bar", "1.1.0")
+        .rust_version("1.2345.0")
+        .file("src/lib.rs", "")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            [dependencies]
+            bar = "1.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+            [resolver]
+            msrv-policy = "compatible"
+        "#,
+        )
+        .build();
+
+    // The policy is only read with `-Zmsrv-policy`.
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("version = \"1.1.0\""));
+
+    p.cargo("update -Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNGRADING] bar v1.1.0 -> v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("check -Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_stderr(
+            "\
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `[..]`)
+[CHECKING] bar v1.0.0
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn msrv_policy_compatible_no_candidate() {
+    Package::new("bar", "1.1.0")
+        .rust_version("1.2345.0")
+        .file("src/lib.rs", "")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            [dependencies]
+            bar = "1.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    p.cargo("check -Zmsrv-policy")
+        .arg("--config")
+        .arg("resolver.msrv-policy=\"compatible\"")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ERROR] failed to select a version for the requirement `bar = \"^1.1\"`
+candidate versions found which didn't match: 1.1.0
+location searched: `dummy-registry` index (which is replacing registry `crates-io`)
+required by package `foo v0.0.1 ([CWD])`
+
+versions skipped because they require a newer rustc than the active one, \
+as `resolver.msrv-policy` is `compatible`:
+
+  `bar v1.1.0` requires rustc 1.2345.0
+perhaps a crate was updated and forgotten to be re-vendored?
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn msrv_policy_invalid() {
+    let p = project().file("src/main.rs", "fn main(){}").build();
+
+    p.cargo("check -Zmsrv-policy")
+        .arg("--config")
+        .arg("resolver.msrv-policy=\"newest\"")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `resolver.msrv-policy` must be `compatible` or `ignore`, found `newest`",
+        )
+        .run();
+}
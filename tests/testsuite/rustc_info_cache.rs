@@ -1,7 +1,7 @@
 //! Tests for the cache file for the rustc version info.
 
 use cargo_test_support::{basic_bin_manifest, paths::CargoPathExt};
-use cargo_test_support::{basic_manifest, project};
+use cargo_test_support::{basic_manifest, project, rustc_host};
 use std::env;
 
 const MISS: &str = "[..] rustc info cache miss[..]";
@@ -184,3 +184,52 @@ fn rustc_info_cache_with_wrappers() {
             .run();
     }
 }
+
+#[cargo_test]
+fn rustc_info_cache_keyed_by_rustflags_and_target() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check")
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .with_stderr_contains(MISS)
+        .with_stderr_contains(UPDATE)
+        .run();
+
+    p.cargo("check")
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .env("RUSTFLAGS", "--cfg foo")
+        .with_stderr_contains(MISS)
+        .with_stderr_contains(UPDATE)
+        .run();
+
+    p.cargo("check")
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .env("RUSTFLAGS", "--cfg foo")
+        .with_stderr_contains(HIT)
+        .with_stderr_does_not_contain(MISS)
+        .with_stderr_does_not_contain(UPDATE)
+        .run();
+
+    // Entries for other flags are kept.
+    p.cargo("check")
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .with_stderr_contains(HIT)
+        .with_stderr_does_not_contain(MISS)
+        .with_stderr_does_not_contain(UPDATE)
+        .run();
+
+    p.cargo("check --target")
+        .arg(rustc_host())
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .with_stderr_contains(MISS)
+        .with_stderr_contains(UPDATE)
+        .run();
+
+    p.cargo("check --target")
+        .arg(rustc_host())
+        .env("CARGO_LOG", "cargo::util::rustc=debug")
+        .with_stderr_contains(HIT)
+        .with_stderr_does_not_contain(MISS)
+        .with_stderr_does_not_contain(UPDATE)
+        .run();
+}
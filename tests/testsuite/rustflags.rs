@@ -1200,6 +1200,39 @@ fn host_rustflags_for_build_scripts() {
         .run();
 }
 
+#[cargo_test]
+fn target_rustflags_change_only_rebuilds_target_units() {
+    let host = rustc_host();
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!("[target.{host}]\nrustflags = [\"--cfg=foo\"]"),
+        )
+        .build();
+
+    p.cargo("check --target").arg(&host).run();
+
+    // The build script is built for the host, so it stays fresh.
+    p.change_file(
+        ".cargo/config.toml",
+        &format!("[target.{host}]\nrustflags = [\"--cfg=bar\"]"),
+    );
+    p.cargo("check -v --target")
+        .arg(&host)
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]): the rustflags changed")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo src/lib.rs [..]--cfg=bar[..]`")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name build_script_build[..]")
+        .with_stderr_does_not_contain("[RUNNING] `[..]build-script-build`")
+        .run();
+
+    p.cargo("check --target")
+        .arg(&host)
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
+
 // target.{}.rustflags takes precedence over build.rustflags
 #[cargo_test]
 fn target_rustflags_precedence() {
@@ -190,3 +190,103 @@ fn auth_required() {
         .with_stdout_contains(SEARCH_RESULTS)
         .run();
 }
+
+#[cargo_test]
+fn format_requires_unstable_options() {
+    let registry = setup().build();
+
+    cargo_process("search postgres --format json")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["search-format"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--format` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn json_format() {
+    let registry = setup().build();
+
+    cargo_process("search -Zunstable-options postgres --format json")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["search-format"])
+        .with_json(
+            r#"
+{
+  "crates": [
+    {
+      "name": "hoare",
+      "max_version": "0.1.1",
+      "description": "Design by contract style assertions for Rust",
+      "downloads": 2
+    },
+    {
+      "name": "postgres",
+      "max_version": "0.17.3",
+      "description": "A native, synchronous PostgreSQL client",
+      "downloads": 535491
+    }
+  ],
+  "total": 2,
+  "page": 1
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn page_requires_unstable_options() {
+    let registry = setup().build();
+
+    cargo_process("search postgres --page 2")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["search-page"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--page` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn page() {
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .add_responder("/api/v1/crates", |req, _| {
+            // Only the second page is available, with more crates after it.
+            let query = req.url.query().unwrap_or_default();
+            if !query.split('&').any(|param| param == "page=2") {
+                return Response {
+                    code: 404,
+                    headers: vec![],
+                    body: format!("unexpected query `{query}`").into_bytes(),
+                };
+            }
+            let body = String::from_utf8(SEARCH_API_RESPONSE.to_vec()).unwrap();
+            Response {
+                code: 200,
+                headers: vec![],
+                body: body.replace(r#""total": 2"#, r#""total": 25"#).into_bytes(),
+            }
+        })
+        .build();
+
+    cargo_process("search -Zunstable-options postgres --page 2")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["search-page"])
+        .with_stdout(
+            "\
+hoare = \"0.1.1\"        # Design by contract style assertions for Rust
+postgres = \"0.17.3\"    # A native, synchronous PostgreSQL client
+... and 5 crates more (use --page 3 to see more)
+",
+        )
+        .run();
+}
@@ -0,0 +1,345 @@
+//! Tests for `-Zshared-build-cache`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{paths, project, Project};
+
+fn cache_entries() -> usize {
+    let Ok(keys) = std::fs::read_dir(paths::home().join(".cargo/build-cache")) else {
+        return 0;
+    };
+    keys.map(|key| {
+        std::fs::read_dir(key.unwrap().path())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().is_dir())
+            .count()
+    })
+    .sum()
+}
+
+#[cargo_test]
+fn shared_between_workspaces() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() {}")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file("src/lib.rs", "pub fn bar() { baz::baz(); }")
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name baz [..]")
+        .run();
+    assert_eq!(cache_entries(), 2);
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    b.cargo("build -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name baz [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name b [..]")
+        .run();
+    assert_eq!(cache_entries(), 2);
+
+    // The restored artifacts are fresh afterwards.
+    b.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn not_shared_without_flag() {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build").run();
+    assert_eq!(cache_entries(), 0);
+}
+
+#[cargo_test]
+fn build_scripts_are_not_shared() {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .file("build.rs", "fn main() {}")
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    assert_eq!(cache_entries(), 0);
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    b.cargo("build -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+}
+
+#[cargo_test]
+fn different_profiles_are_not_shared() {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    b.cargo("build -v --release -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(cache_entries(), 2);
+}
+
+#[cargo_test]
+fn keyed_by_tracked_env_vars() {
+    Package::new("bar", "1.0.0")
+        .file(
+            "src/lib.rs",
+            r#"pub fn bar() -> Option<&'static str> { option_env!("BAR_ENV") }"#,
+        )
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .env("BAR_ENV", "1")
+        .run();
+    assert_eq!(cache_entries(), 1);
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    b.cargo("build -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .env("BAR_ENV", "2")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(cache_entries(), 2);
+
+    let c = project()
+        .at("c")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "c"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    c.cargo("build -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .env("BAR_ENV", "1")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+
+    // The dep-info is restored too, so the restored artifacts are stale once
+    // the variable changes.
+    c.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .env("BAR_ENV", "1")
+        .with_stderr("[FINISHED] [..]")
+        .run();
+    c.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .env("BAR_ENV", "2")
+        .with_stderr_contains("[COMPILING] bar v1.0.0")
+        .run();
+}
+
+#[cargo_test]
+#[cfg(unix)]
+fn restored_artifacts_are_linked() {
+    use std::os::unix::fs::MetadataExt;
+
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    a.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bar::bar(); }")
+        .build();
+    b.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+
+    let rlib = |p: &Project| {
+        p.glob("target/debug/deps/libbar-*.rlib")
+            .next()
+            .unwrap()
+            .unwrap()
+    };
+    let a_rlib = std::fs::metadata(rlib(&a)).unwrap();
+    let b_rlib = std::fs::metadata(rlib(&b)).unwrap();
+    // Linked by both target directories and the cache.
+    assert_eq!(a_rlib.ino(), b_rlib.ino());
+    assert_eq!(b_rlib.nlink(), 3);
+}
@@ -0,0 +1,119 @@
+//! Tests for `--short-errors` and `term.short-errors`.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "fn bar() {}")
+        .build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "../bar" }
+            "#,
+        )
+        .file("src/lib.rs", "fn foo() {}")
+        .build();
+    p.cargo("check --short-errors")
+        .masquerade_as_nightly_cargo(&["short-errors"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--short-errors` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn short_errors_for_dependencies() {
+    project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "fn bar() {}")
+        .build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "../bar" }
+            "#,
+        )
+        .file("src/lib.rs", "fn foo() {}")
+        .build();
+    p.cargo("check --short-errors -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["short-errors"])
+        .with_stderr_contains("[..]/bar/src/lib.rs:1:4: warning: function `bar` is never used[..]")
+        .with_stderr_contains("[WARNING] function `foo` is never used")
+        .with_stderr_contains(" --> src/lib.rs:1:4")
+        .run();
+}
+
+#[cargo_test]
+fn short_errors_from_config() {
+    project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "fn bar() {}")
+        .build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "../bar" }
+            "#,
+        )
+        .file("src/lib.rs", "fn foo() {}")
+        .build();
+    p.cargo("check -Zshort-errors")
+        .masquerade_as_nightly_cargo(&["short-errors"])
+        .env("CARGO_TERM_SHORT_ERRORS", "true")
+        .with_stderr_contains("[..]/bar/src/lib.rs:1:4: warning: function `bar` is never used[..]")
+        .with_stderr_contains(" --> src/lib.rs:1:4")
+        .run();
+}
+
+#[cargo_test]
+fn config_requires_unstable_flag() {
+    project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "fn bar() {}")
+        .build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "../bar" }
+            "#,
+        )
+        .file("src/lib.rs", "fn foo() {}")
+        .build();
+    p.cargo("check")
+        .env("CARGO_TERM_SHORT_ERRORS", "true")
+        .with_stderr_contains(" --> [..]/bar/src/lib.rs:1:4")
+        .run();
+}
@@ -0,0 +1,96 @@
+//! Tests for `-Zshuffle-units`.
+
+use cargo_test_support::{basic_manifest, project, Project};
+
+/// The packages in the order they were checked.
+fn checked_order(p: &Project, seed: u64) -> Vec<String> {
+    p.cargo("clean").run();
+    let output = p
+        .cargo(&format!("check -j1 -Zshuffle-units={seed}"))
+        .masquerade_as_nightly_cargo(&["shuffle-units"])
+        .exec_with_output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!(
+        "note: shuffling the order of units with seed {seed}, \
+         pass `-Zshuffle-units={seed}` to reproduce it"
+    )));
+    stderr
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Checking "))
+        .map(|line| line.split(' ').next().unwrap().to_string())
+        .collect()
+}
+
+#[cargo_test]
+fn requires_nightly() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check -Zshuffle-units=1")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `-Z` flag is only accepted on the nightly channel of Cargo[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn same_seed_same_order() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = { path = "a" }
+                b = { path = "b" }
+                c = { path = "c" }
+                d = { path = "d" }
+                e = { path = "e" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .file("c/Cargo.toml", &basic_manifest("c", "0.1.0"))
+        .file("c/src/lib.rs", "")
+        .file("d/Cargo.toml", &basic_manifest("d", "0.1.0"))
+        .file("d/src/lib.rs", "")
+        .file("e/Cargo.toml", &basic_manifest("e", "0.1.0"))
+        .file("e/src/lib.rs", "")
+        .build();
+    let order = checked_order(&p, 1);
+    assert_eq!(order.len(), 6);
+    assert_eq!(order.last().unwrap(), "foo");
+    assert_eq!(order, checked_order(&p, 1));
+    assert!(
+        (2..20).any(|seed| checked_order(&p, seed) != order),
+        "the order never changed: {order:?}"
+    );
+}
+
+#[cargo_test]
+fn random_seed() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check -Zshuffle-units")
+        .masquerade_as_nightly_cargo(&["shuffle-units"])
+        .with_stderr_contains(
+            "[NOTE] shuffling the order of units with seed [..], \
+             pass `-Zshuffle-units=[..]` to reproduce it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_seed() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check -Zshuffle-units=abc")
+        .masquerade_as_nightly_cargo(&["shuffle-units"])
+        .with_status(101)
+        .with_stderr("[ERROR] `-Zshuffle-units` expects a number as the seed, found `abc`")
+        .run();
+}
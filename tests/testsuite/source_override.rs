@@ -0,0 +1,378 @@
+//! Tests for `-Zsource-override` and the `source-override.process` config.
+
+use std::path::PathBuf;
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, paths, project};
+
+/// Builds a source override answering with the contents of `{name}.json` in
+/// the directory it is given, or with no redirect if there is no such file.
+fn override_process() -> PathBuf {
+    let p = project()
+        .at(paths::root().join("override"))
+        .file("Cargo.toml", &basic_manifest("override", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::io::BufRead;
+                fn main() {
+                    let dir = std::path::PathBuf::from(std::env::args().nth(1).unwrap());
+                    let mut request = String::new();
+                    std::io::stdin().lock().read_line(&mut request).unwrap();
+                    let name = request.split("\"name\":\"").nth(1).unwrap();
+                    let name = name.split('"').next().unwrap();
+                    match std::fs::read_to_string(dir.join(format!("{name}.json"))) {
+                        Ok(response) => print!("{response}"),
+                        Err(_) => print!("{{}}"),
+                    }
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build").run();
+    p.bin("override")
+}
+
+/// Makes the source override redirect `bar` to `path`.
+fn redirect_bar(path: &str) {
+    std::fs::write(
+        paths::root().join("responses/bar.json"),
+        format!(r#"{{"path":{:?}}}"#, path),
+    )
+    .unwrap();
+}
+
+/// Creates a local copy of `bar` with the given version.
+fn local_bar(version: &str) -> PathBuf {
+    let path = paths::root().join("mono/bar");
+    std::fs::create_dir_all(path.join("src")).unwrap();
+    std::fs::write(path.join("Cargo.toml"), basic_manifest("bar", version)).unwrap();
+    std::fs::write(path.join("src/lib.rs"), "pub fn local() {}").unwrap();
+    path
+}
+
+#[cargo_test]
+fn ignored_without_flag() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    redirect_bar(local_bar("1.0.5").to_str().unwrap());
+    p.change_file("src/lib.rs", "");
+    p.cargo("check")
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn redirect() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    redirect_bar(local_bar("1.0.5").to_str().unwrap());
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_stderr(
+            "\
+[CHECKING] bar v1.0.5 ([ROOT]/mono/bar)
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_redirect() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn version_mismatch() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    redirect_bar(local_bar("2.0.0").to_str().unwrap());
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]dependency `bar` was redirected to [ROOT]/mono/bar by the source override, \
+             but its version 2.0.0 does not match the requirement `^1.0`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn wrong_name() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    let path = local_bar("1.0.5");
+    std::fs::write(path.join("Cargo.toml"), basic_manifest("baz", "1.0.5")).unwrap();
+    redirect_bar(path.to_str().unwrap());
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]dependency `bar` was redirected to [ROOT]/mono/bar by the source override, \
+             but the package there is not named `bar`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn relative_path() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    redirect_bar("mono/bar");
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]source override process `[..]` redirected dependency `bar` to `mono/bar`, \
+             but the path must be absolute",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_manifest() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    redirect_bar(paths::root().join("mono/bar").to_str().unwrap());
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]dependency `bar` was redirected to `[ROOT]/mono/bar` by the source override, \
+             but there is no `Cargo.toml` in that directory",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_response() {
+    Package::new("bar", "1.0.0").publish();
+    let process = override_process();
+    let responses = paths::root().join("responses");
+    std::fs::create_dir_all(&responses).unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { bar::local(); }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[source-override]\nprocess = ['{}', '{}']\n",
+                process.display(),
+                responses.display()
+            ),
+        )
+        .build();
+    std::fs::write(paths::root().join("responses/bar.json"), "not json").unwrap();
+    p.cargo("check -Zsource-override")
+        .masquerade_as_nightly_cargo(&["source-override"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]source override process `[..]` returned an invalid response \
+             for dependency `bar`: `not json`",
+        )
+        .run();
+}
@@ -248,3 +248,115 @@ fn undefined_default() {
         )
         .run();
 }
+
+/// A sparse registry which can't be reached.
+const UNREACHABLE_MIRROR: &str = "sparse+http://127.0.0.1:1/index/";
+
+#[cargo_test]
+fn mirror_fallback() {
+    // The fallback loads the replaced source itself, so it must be one the
+    // test registry serves, rather than crates.io.
+    let alt = RegistryBuilder::new().alternative().build();
+    Package::new("bar", "0.1.0").alternative(true).publish();
+    let root = paths::root();
+    t!(fs::create_dir(&root.join(".cargo")));
+    t!(fs::write(
+        root.join(".cargo/config"),
+        format!(
+            r#"
+            [source.alt]
+            registry = '{}'
+            replace-with = 'mirror'
+            fallback = true
+
+            [source.mirror]
+            registry = '{UNREACHABLE_MIRROR}'
+        "#,
+            alt.index_url()
+        ),
+    ));
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { version = "0.1", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zmirror-fallback")
+        .masquerade_as_nightly_cargo(&["mirror-fallback"])
+        .with_stderr_contains(
+            "[WARNING] source `mirror` replacing `alt` is unreachable, \
+             falling back to `alt`: \
+             failed to reach `http://127.0.0.1:1/index/config.json`: [..]",
+        )
+        .with_stderr_contains("[CHECKING] bar v0.1.0 (registry `alternative`)")
+        .with_stderr_contains("[CHECKING] foo v0.0.1 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn check_mirrors_unreachable() {
+    let crates_io = setup_replacement(&format!(
+        r#"
+        [source.crates-io]
+        replace-with = 'mirror'
+
+        [source.mirror]
+        registry = '{UNREACHABLE_MIRROR}'
+    "#
+    ));
+
+    cargo_process("config check-mirrors -Zunstable-options")
+        .replace_crates_io(crates_io.index_url())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_status(101)
+        .with_stdout(
+            "\
+crates-io -> mirror (sparse+http://127.0.0.1:1/index/): unreachable: \
+failed to reach `http://127.0.0.1:1/index/config.json`: [..]
+",
+        )
+        .with_stderr("[ERROR] 1 of 1 source replacements are unreachable")
+        .run();
+}
+
+#[cargo_test]
+fn check_mirrors_ok() {
+    let crates_io = setup_replacement(
+        r#"
+        [source.crates-io]
+        replace-with = 'mirror'
+    "#,
+    );
+    let _mirror = RegistryBuilder::new()
+        .http_index()
+        .alternative_named("mirror")
+        .build();
+
+    cargo_process("config check-mirrors -Zunstable-options")
+        .replace_crates_io(crates_io.index_url())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_stdout("crates-io -> mirror (sparse+http://127.0.0.1:[..]/index/): ok")
+        .run();
+}
+
+#[cargo_test]
+fn check_mirrors_none() {
+    let crates_io = setup_replacement("");
+
+    cargo_process("config check-mirrors -Zunstable-options")
+        .replace_crates_io(crates_io.index_url())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_stdout("")
+        .with_stderr("[NOTE] no sources are replaced in the `[source]` table")
+        .run();
+}
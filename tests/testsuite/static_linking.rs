@@ -0,0 +1,241 @@
+//! Tests for `cargo build --static`.
+
+use cargo_test_support::{basic_manifest, project, rustc_host};
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build --static")
+        .masquerade_as_nightly_cargo(&["static"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--static` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn crt_static_for_target_units_only() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+                sys = { path = "sys" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file("pm/src/lib.rs", "")
+        .file(
+            "sys/Cargo.toml",
+            r#"
+                [package]
+                name = "sys"
+                version = "0.1.0"
+                links = "z"
+
+                [features]
+                vendored = []
+            "#,
+        )
+        .file("sys/src/lib.rs", "")
+        .file("sys/build.rs", "fn main() {}")
+        .build();
+    let target = rustc_host();
+    p.cargo("build --static -Zunstable-options -v")
+        .masquerade_as_nightly_cargo(&["static"])
+        .with_stderr_contains(&format!(
+            "[RUNNING] `rustc --crate-name foo src/lib.rs [..]--target {target} \
+             [..]-C target-feature=+crt-static[..]`"
+        ))
+        .with_stderr_contains(&format!(
+            "[RUNNING] `rustc --crate-name sys sys/src/lib.rs [..]--target {target} \
+             [..]-C target-feature=+crt-static[..]`"
+        ))
+        .with_stderr_line_without(
+            &["[RUNNING] `rustc --crate-name pm "],
+            &["--target", "crt-static"],
+        )
+        .with_stderr_line_without(
+            &["[RUNNING] `rustc --crate-name build_script_build build.rs "],
+            &["--target", "crt-static"],
+        )
+        .with_stderr_line_without(
+            &["[RUNNING] `rustc --crate-name build_script_build sys/build.rs "],
+            &["--target", "crt-static"],
+        )
+        .run();
+
+    assert!(p
+        .build_dir()
+        .join(target)
+        .join("debug/libfoo.rlib")
+        .is_file());
+}
+
+#[cargo_test]
+fn crt_static_in_build_script_cfg() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let features = std::env::var("CARGO_CFG_TARGET_FEATURE").unwrap();
+                    assert!(features.split(',').any(|f| f == "crt-static"), "{features}");
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build --static -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["static"])
+        .run();
+}
+
+#[cargo_test]
+fn suggest_vendored_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+                sys = { path = "sys" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file("pm/src/lib.rs", "")
+        .file(
+            "sys/Cargo.toml",
+            r#"
+                [package]
+                name = "sys"
+                version = "0.1.0"
+                links = "z"
+
+                [features]
+                vendored = []
+            "#,
+        )
+        .file("sys/src/lib.rs", "")
+        .file("sys/build.rs", "fn main() {}")
+        .build();
+    p.cargo("build --static -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["static"])
+        .with_stderr_contains(
+            "[NOTE] `sys v0.1.0` links to the native library `z`, \
+             enabling one of its features `vendored` may link it statically",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn vendored_feature_enabled() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                pm = { path = "pm" }
+                sys = { path = "sys", features = ["vendored"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file("pm/src/lib.rs", "")
+        .file(
+            "sys/Cargo.toml",
+            r#"
+                [package]
+                name = "sys"
+                version = "0.1.0"
+                links = "z"
+
+                [features]
+                vendored = []
+            "#,
+        )
+        .file("sys/src/lib.rs", "")
+        .file("sys/build.rs", "fn main() {}")
+        .build();
+    p.cargo("build --static -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["static"])
+        .with_stderr_does_not_contain("[..]links to the native library[..]")
+        .run();
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[cargo_test]
+fn musl_hint_on_glibc() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    let target = rustc_host();
+    p.cargo("build --static -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["static"])
+        .with_stderr(&format!(
+            "\
+[NOTE] glibc does not fully support static linking, consider building for \
+`--target {}` instead of `{target}`
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+            target.replacen("-linux-gnu", "-linux-musl", 1)
+        ))
+        .run();
+}
@@ -0,0 +1,243 @@
+//! Tests for `-Ztarget-dir-gc` and `cargo clean gc`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, Project};
+
+fn manifest(deps: &str) -> String {
+    format!(
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            {deps}
+        "#
+    )
+}
+
+fn has_artifacts(p: &Project, name: &str) -> bool {
+    p.glob(format!("target/debug/deps/lib{name}-*"))
+        .next()
+        .is_some()
+        || p.glob(format!("target/debug/.fingerprint/{name}-*"))
+            .next()
+            .is_some()
+}
+
+/// Sets the last use of the units of `name` to the Unix epoch.
+fn make_unused(p: &Project, name: &str) {
+    let path = "target/debug/.last-use.json";
+    let mut last_use: serde_json::Value = serde_json::from_str(&p.read_file(path)).unwrap();
+    for unit in last_use["units"].as_object_mut().unwrap().values_mut() {
+        if unit["name"] == name {
+            unit["last-use"] = 0.into();
+        }
+    }
+    p.change_file(path, &last_use.to_string());
+}
+
+#[cargo_test]
+fn gc_requires_flag() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("clean gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo clean gc` command is unstable, \
+             pass `-Z target-dir-gc` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_recorded_without_flag() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("build").run();
+    assert!(!p.root().join("target/debug/.last-use.json").exists());
+}
+
+#[cargo_test]
+fn removes_packages_no_longer_locked() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("bar = \"1.0\"\nbaz = \"1.0\""))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    assert!(p
+        .read_file("target/debug/.last-use.json")
+        .contains("\"bar\""));
+
+    p.change_file("Cargo.toml", &manifest("baz = \"1.0\""));
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    assert!(has_artifacts(&p, "bar"));
+
+    p.cargo("clean gc --dry-run -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr(
+            "\
+[SUMMARY] [..] files, [..] total
+[WARNING] no files deleted due to --dry-run
+",
+        )
+        .run();
+    assert!(has_artifacts(&p, "bar"));
+
+    p.cargo("clean gc -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr("[..]Removed [..] files, [..] total")
+        .run();
+    assert!(!has_artifacts(&p, "bar"));
+    assert!(has_artifacts(&p, "baz"));
+    assert!(has_artifacts(&p, "foo"));
+    assert!(!p
+        .read_file("target/debug/.last-use.json")
+        .contains("\"bar\""));
+
+    // Nothing needs to be rebuilt.
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn max_unused_days() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("bar = \"1.0\"\nbaz = \"1.0\""))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    make_unused(&p, "bar");
+
+    p.cargo("clean gc --max-unused-days 7 -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    assert!(!has_artifacts(&p, "bar"));
+    assert!(has_artifacts(&p, "baz"));
+}
+
+#[cargo_test]
+fn max_size() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("bar = \"1.0\"\nbaz = \"1.0\""))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    make_unused(&p, "baz");
+
+    p.cargo("clean gc --max-size 1 -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    assert!(!has_artifacts(&p, "bar"));
+    assert!(!has_artifacts(&p, "baz"));
+    assert!(!has_artifacts(&p, "foo"));
+
+    p.cargo("clean gc --max-size 1TB -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_status(101)
+        .with_stderr("[ERROR] `--max-size` must be a size such as `20GiB` or `512MiB`, found `1TB`")
+        .run();
+}
+
+#[cargo_test]
+fn automatic() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("bar = \"1.0\"\nbaz = \"1.0\""))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [gc.target-dir]
+            max-unused-days = 30
+        "#,
+    );
+    p.change_file("Cargo.toml", &manifest("baz = \"1.0\""));
+
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr_contains("[..]Removed [..] files, [..] total")
+        .run();
+    assert!(!has_artifacts(&p, "bar"));
+    assert!(has_artifacts(&p, "baz"));
+
+    // The directory was just collected, so it isn't collected again today.
+    p.change_file("Cargo.toml", &manifest(""));
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr_does_not_contain("[..]Removed[..]")
+        .run();
+    assert!(has_artifacts(&p, "baz"));
+}
+
+#[cargo_test]
+fn automatic_keeps_units_in_use() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("bar = \"1.0\"\nbaz = \"1.0\""))
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .run();
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [gc.target-dir]
+            max-size = "1B"
+        "#,
+    );
+    p.change_file("Cargo.toml", &manifest("baz = \"1.0\""));
+
+    p.cargo("build -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_stderr_contains("[..]Removed [..] files, [..] total")
+        .run();
+    assert!(!has_artifacts(&p, "bar"));
+    assert!(has_artifacts(&p, "baz"));
+    assert!(has_artifacts(&p, "foo"));
+}
+
+#[cargo_test]
+fn invalid_max_size_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [gc.target-dir]
+                max-size = "lots"
+            "#,
+        )
+        .build();
+
+    p.cargo("clean gc -Ztarget-dir-gc")
+        .masquerade_as_nightly_cargo(&["target-dir-gc"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `gc.target-dir.max-size` must be a size such as `20GiB` or `512MiB`, \
+             found `lots`",
+        )
+        .run();
+}
@@ -0,0 +1,164 @@
+//! Tests for rendering paths relative to the workspace root (`-Zterm-paths`).
+
+use cargo_test_support::{basic_bin_manifest, basic_manifest, project};
+
+#[cargo_test]
+fn absolute_without_flag() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    p.change_file(".cargo/config.toml", "[term]\npaths = 'relative'");
+
+    p.cargo("build -Zbuild-summary")
+        .masquerade_as_nightly_cargo(&["build-summary"])
+        .with_stderr_contains("[..]Artifact [CWD]/target/debug/foo[EXE]")
+        .run();
+}
+
+#[cargo_test]
+fn relative_by_default() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Zbuild-summary -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["build-summary", "term-paths"])
+        .with_stderr_contains("[..]Artifact target/debug/foo[EXE]")
+        .run();
+}
+
+#[cargo_test]
+fn absolute_with_verbose() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v -Zbuild-summary -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["build-summary", "term-paths"])
+        .with_stderr_contains("[..]Artifact [CWD]/target/debug/foo[EXE]")
+        .run();
+}
+
+#[cargo_test]
+fn absolute_from_config() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    p.change_file(".cargo/config.toml", "[term]\npaths = 'absolute'");
+
+    p.cargo("build -Zbuild-summary -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["build-summary", "term-paths"])
+        .with_stderr_contains("[..]Artifact [CWD]/target/debug/foo[EXE]")
+        .run();
+}
+
+#[cargo_test]
+fn relative_in_errors() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("explain-hash -Zunstable-options -Zterm-paths libfoo-0123456789abcdef.rlib")
+        .masquerade_as_nightly_cargo(&["unstable-options", "term-paths"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] no unit with the metadata hash `0123456789abcdef` was found in \
+             `target`, hashes are only recorded by builds with `-Zmetadata-hash`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn relative_in_status_lines() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["term-paths"])
+        .with_stderr_unordered(
+            "\
+[CHECKING] bar v0.0.1 (bar)
+[CHECKING] foo v0.0.1 (.)
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn relative_in_workspace_errors() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar"]
+            "#,
+        )
+        .file("bar/Cargo.toml", "[package]\nname = ")
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["term-paths"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to load manifest for workspace member `bar`
+referenced by workspace at `Cargo.toml`
+
+Caused by:
+  failed to parse manifest at `bar/Cargo.toml`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn relative_in_resolver_errors() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar", version = "2.0" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zterm-paths")
+        .masquerade_as_nightly_cargo(&["term-paths"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to select a version for the requirement `bar = \"^2.0\"`
+candidate versions found which didn't match: 0.0.1
+location searched: bar
+required by package `foo v0.0.1 (.)`
+",
+        )
+        .run();
+}
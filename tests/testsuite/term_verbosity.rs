@@ -0,0 +1,254 @@
+//! Tests for `-Zterm-verbosity` and the `term.verbosity` config.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check --config term.verbosity.status=false")
+        .with_stderr(
+            "\
+[CHECKING] bar v0.0.1 ([CWD]/bar)
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn hide_status() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check -Zterm-verbosity --config term.verbosity.status=false")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr("")
+        .run();
+}
+
+#[cargo_test]
+fn status_with_quiet() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check -q -Zterm-verbosity --config term.verbosity.status=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr(
+            "\
+[CHECKING] bar v0.0.1 ([CWD]/bar)
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn process_echo_without_verbose() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("hello from build script"); }"#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check -Zterm-verbosity --config term.verbosity.process-echo=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]`")
+        .with_stderr_does_not_contain("[..]hello from build script[..]")
+        .run();
+}
+
+#[cargo_test]
+fn process_echo_with_quiet() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check -q -Zterm-verbosity --config term.verbosity.process-echo=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]`")
+        .with_stderr_does_not_contain("[CHECKING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn fingerprint_debug_with_quiet() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check").run();
+    p.cargo("check -q -Zterm-verbosity")
+        .arg("--config=term.verbosity.fingerprint-debug=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr_contains("[FRESH] bar v0.0.1 ([CWD]/bar)")
+        .with_stderr_does_not_contain("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn hide_process_echo_with_verbose() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check -v -Zterm-verbosity --config term.verbosity.process-echo=false")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr_contains("[CHECKING] bar v0.0.1 ([CWD]/bar)")
+        .with_stderr_does_not_contain("[RUNNING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_output() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("hello from build script"); }"#,
+        )
+        .build();
+    p.cargo("check -Zterm-verbosity --config term.verbosity.build-script-output=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stdout_contains("[foo 0.0.1] hello from build script")
+        .with_stderr_does_not_contain("[RUNNING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn fingerprint_debug() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("check").run();
+    p.change_file("src/lib.rs", "// changed");
+    p.cargo("check -Zterm-verbosity")
+        .arg("--config=term.verbosity.fingerprint-debug=true")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_stderr_contains("[FRESH] bar v0.0.1 ([CWD]/bar)")
+        .with_stderr_contains(
+            "[DIRTY] foo v0.0.1 ([CWD]): the file `src/lib.rs` has changed ([..])",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn unknown_category() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check -Zterm-verbosity --config term.verbosity.statuses=false")
+        .masquerade_as_nightly_cargo(&["term-verbosity"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] unknown category `statuses` in `term.verbosity`, expected one of `status`, \
+`process-echo`, `build-script-output`, `fingerprint-debug`
+",
+        )
+        .run();
+}
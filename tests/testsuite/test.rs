@@ -8,6 +8,7 @@ use cargo_test_support::{
 use cargo_test_support::{cross_compile, paths};
 use cargo_test_support::{rustc_host, rustc_host_env, sleep_ms};
 use std::fs;
+use std::path::Path;
 
 #[cargo_test]
 fn cargo_test_simple() {
@@ -794,6 +795,68 @@ fn pass_through_escaped() {
         .run();
 }
 
+#[cargo_test]
+fn pass_through_args_file() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            "
+            #[test] fn test_foo() {}
+            #[test] fn test_bar() {}
+            #[test] fn test_baz() {}
+        ",
+        )
+        .file("args.txt", "--exact\ntest_foo\n")
+        .build();
+
+    p.cargo("test -Zunstable-options --args-file args.txt -v -- test_bar")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains(
+            "[RUNNING] `[CWD]/target/debug/deps/foo-[..][EXE] --exact test_foo test_bar`",
+        )
+        .with_stdout_contains("running 2 tests")
+        .with_stdout_contains("test test_foo ... ok")
+        .with_stdout_contains("test test_bar ... ok")
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn pass_through_non_utf8_args() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [[test]]
+                name = "t"
+                harness = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "tests/t.rs",
+            r#"
+                use std::ffi::OsStr;
+                use std::os::unix::ffi::OsStrExt;
+
+                fn main() {
+                    assert_eq!(std::env::args_os().nth(1).unwrap(), OsStr::from_bytes(b"ab\xffcd"));
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("test --test t --")
+        .arg(std::ffi::OsStr::from_bytes(b"ab\xFFcd"))
+        .run();
+}
+
 // Unlike `pass_through_escaped`, doctests won't run when using `testname` as an optimization
 #[cargo_test]
 fn pass_through_testname() {
@@ -1469,6 +1532,77 @@ fn test_no_run_emit_json() {
         .run();
 }
 
+#[cargo_test]
+fn test_no_run_emit_json_test_executables() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                a = []
+            "#,
+        )
+        .file("src/lib.rs", "#[test] fn foo() {}")
+        .build();
+
+    p.cargo("test --no-run --lib --features a --message-format json")
+        .with_json_contains_unordered(
+            r#"
+                {
+                    "reason": "test-executable",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "manifest_path": "[..]/foo/Cargo.toml",
+                    "target": {
+                        "crate_types": [ "lib" ],
+                        "kind": [ "lib" ],
+                        "doc": true,
+                        "doctest": true,
+                        "edition": "2015",
+                        "name": "foo",
+                        "src_path": "[..]/foo/src/lib.rs",
+                        "test": true
+                    },
+                    "features": ["a"],
+                    "executable": "[..]/foo/target/debug/deps/foo-[..][EXE]",
+                    "program": "[..]/foo/target/debug/deps/foo-[..][EXE]",
+                    "args": [],
+                    "cwd": "[..]/foo",
+                    "env": "{...}"
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn test_no_run_writes_index() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn foo() {}")
+        .file("tests/bar.rs", "#[test] fn bar() {}")
+        .build();
+
+    p.cargo("test --no-run").run();
+
+    let index: serde_json::Value =
+        serde_json::from_str(&p.read_file("target/test-executables.json")).unwrap();
+    assert_eq!(index["version"], 1);
+    let executables = index["executables"].as_array().unwrap();
+    let mut names: Vec<_> = executables
+        .iter()
+        .map(|exe| exe["target"]["name"].as_str().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["bar", "foo"]);
+    for exe in executables {
+        assert!(Path::new(exe["executable"].as_str().unwrap()).exists());
+        assert_eq!(exe["env"]["CARGO_PKG_NAME"], "foo");
+    }
+}
+
 #[cargo_test]
 fn test_run_specific_bin_target() {
     let prj = project()
@@ -3555,6 +3689,70 @@ fn cyclic_dev() {
     p.cargo("test --workspace").run();
 }
 
+#[cargo_test]
+fn cyclic_dev_proc_macro() {
+    // The proc-macro is used by its sibling, which its tests depend on.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["mac", "user"]
+            "#,
+        )
+        .file(
+            "mac/Cargo.toml",
+            r#"
+                [package]
+                name = "mac"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+
+                [dev-dependencies]
+                user = { path = "../user" }
+            "#,
+        )
+        .file(
+            "mac/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn answer(_: TokenStream) -> TokenStream {
+                    "42".parse().unwrap()
+                }
+            "#,
+        )
+        .file(
+            "mac/tests/answer.rs",
+            "#[test] fn answer() { assert_eq!(user::answer(), 42); }",
+        )
+        .file(
+            "user/Cargo.toml",
+            r#"
+                [package]
+                name = "user"
+                version = "0.1.0"
+
+                [dependencies]
+                mac = { path = "../mac" }
+            "#,
+        )
+        .file(
+            "user/src/lib.rs",
+            "pub fn answer() -> u32 { mac::answer!() }",
+        )
+        .build();
+
+    p.cargo("check --workspace --all-targets").run();
+    p.cargo("test --workspace")
+        .with_stdout_contains("test answer ... ok")
+        .run();
+}
+
 #[cargo_test]
 fn publish_a_crate_without_tests() {
     Package::new("testless", "0.1.0")
@@ -3946,6 +4144,19 @@ fn json_artifact_includes_executable_for_library_tests() {
                 }
 
                 {"reason": "build-finished", "success": true}
+
+                {
+                    "reason": "test-executable",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "manifest_path": "[..]/foo/Cargo.toml",
+                    "target": "{...}",
+                    "features": [],
+                    "executable": "[..]/foo/target/debug/deps/foo-[..][EXE]",
+                    "program": "[..]/foo/target/debug/deps/foo-[..][EXE]",
+                    "args": "{...}",
+                    "cwd": "[..]/foo",
+                    "env": "{...}"
+                }
             "#,
         )
         .run();
@@ -3985,6 +4196,19 @@ fn json_artifact_includes_executable_for_integration_tests() {
                 }
 
                 {"reason": "build-finished", "success": true}
+
+                {
+                    "reason": "test-executable",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "manifest_path": "[..]/foo/Cargo.toml",
+                    "target": "{...}",
+                    "features": [],
+                    "executable": "[..]/foo/target/debug/deps/integration_test-[..][EXE]",
+                    "program": "[..]/foo/target/debug/deps/integration_test-[..][EXE]",
+                    "args": "{...}",
+                    "cwd": "[..]/foo",
+                    "env": "{...}"
+                }
             "#,
         )
         .run();
@@ -0,0 +1,160 @@
+//! Tests for `cargo test --target-matrix`.
+
+use cargo_test_support::{basic_lib_manifest, cross_compile, project, rustc_host, Project};
+
+fn foo(test: &str) -> Project {
+    project()
+        .file("Cargo.toml", &basic_lib_manifest("foo"))
+        .file(
+            "src/lib.rs",
+            &format!(
+                r#"
+                    #[test]
+                    fn it_works() {{
+                        {test}
+                    }}
+                "#
+            ),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = foo("");
+    p.cargo("test --target-matrix")
+        .arg(rustc_host())
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--target-matrix` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn host_passes() {
+    let p = foo("");
+    let host = rustc_host();
+    p.cargo("test -Zunstable-options --target-matrix")
+        .arg(host)
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_stderr(format!(
+            "\
+[COMPILING] foo v0.5.0 ([CWD])
+[FINISHED] test [unoptimized + debuginfo] target(s) in [..]
+[TESTING] {host}
+[RUNNING] unittests src/lib.rs (target/{host}/debug/deps/foo-[..][EXE])
+[DOCTEST] foo
+[SUMMARY] 1 of 1 targets passed
+  {host}  ok
+"
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn host_fails() {
+    let p = foo("panic!();");
+    let host = rustc_host();
+    p.cargo("test -Zunstable-options --no-fail-fast --target-matrix")
+        .arg(host)
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] test failed, to rerun pass `--lib`")
+        .with_stderr_contains(format!(
+            "\
+[SUMMARY] 0 of 1 targets passed
+  {host}  FAILED (`--lib`)
+[ERROR] tests failed for target `{host}`
+"
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn runner() {
+    let p = foo("");
+    let host = rustc_host();
+    p.cargo("test -v -Zunstable-options --target-matrix")
+        .arg(format!("{host}=nonexistent-runner -r"))
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(101)
+        .with_stderr_contains(format!(
+            "[RUNNING] `nonexistent-runner -r [..]/target/{host}/debug/deps/foo-[..][EXE]`"
+        ))
+        .with_stderr_contains(format!(
+            "\
+[SUMMARY] 0 of 1 targets passed
+  {host}  FAILED
+"
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn all_targets_run() {
+    if cross_compile::disabled() {
+        return;
+    }
+
+    let p = foo("");
+    let host = rustc_host();
+    let alternate = cross_compile::alternate();
+    // When the alternate target can't run here, its tests fail to launch but
+    // the tests of the host still run.
+    let (runner, status, outcome) = if cross_compile::can_run_on_host() {
+        ("", 0, "ok")
+    } else {
+        ("=nonexistent-runner", 101, "FAILED")
+    };
+    p.cargo("test -Zunstable-options --no-fail-fast --target-matrix")
+        .arg(format!("{alternate}{runner}"))
+        .arg("--target-matrix")
+        .arg(host)
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(status)
+        .with_stderr_contains(format!("[TESTING] {alternate}"))
+        .with_stderr_contains(format!(
+            "\
+[TESTING] {host}
+[RUNNING] unittests src/lib.rs (target/{host}/debug/deps/foo-[..][EXE])
+"
+        ))
+        .with_stderr_contains(format!("  {alternate} [..]{outcome}[..]"))
+        .with_stderr_contains(format!("  {host} [..]ok"))
+        .run();
+}
+
+#[cargo_test]
+fn duplicate_target() {
+    let p = foo("");
+    let host = rustc_host();
+    p.cargo("test -Zunstable-options --target-matrix")
+        .arg(host)
+        .arg("--target-matrix")
+        .arg(format!("{host}=runner"))
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(101)
+        .with_stderr(format!(
+            "[ERROR] target `{host}` is given more than once to `--target-matrix`"
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn conflicts_with_target() {
+    let p = foo("");
+    let host = rustc_host();
+    p.cargo("test -Zunstable-options --target-matrix")
+        .arg(host)
+        .arg("--target")
+        .arg(host)
+        .masquerade_as_nightly_cargo(&["target-matrix"])
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] the argument '--target-matrix <TARGET>' cannot be used with '--target <TRIPLE>'",
+        )
+        .run();
+}
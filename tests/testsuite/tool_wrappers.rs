@@ -0,0 +1,236 @@
+//! Tests for `-Ztool-wrappers`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+/// A tool wrapper printing the crates it wraps, and the version from
+/// `TOOL_VERSION` with `--version`.
+const WRAPPER: &str = r#"
+    fn main() {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args[0] == "--version" {
+            let version = std::env::var("TOOL_VERSION");
+            println!("wrapper {}", version.as_deref().unwrap_or("1.0.0"));
+            return;
+        }
+        if let Some(i) = args.iter().position(|arg| arg == "--crate-name") {
+            eprintln!("WRAPPED {}", args[i + 1]);
+        }
+        let status = std::process::Command::new(&args[0])
+            .args(&args[1..])
+            .status()
+            .unwrap();
+        std::process::exit(status.code().unwrap_or(1));
+    }
+"#;
+
+#[cargo_test]
+fn wraps_members_only() {
+    let wrapper = project()
+        .at("wrapper")
+        .file("Cargo.toml", &basic_manifest("wrapper", "1.0.0"))
+        .file("src/main.rs", WRAPPER)
+        .build();
+    wrapper.cargo("build").run();
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[build]\ntool-wrappers = ['{}']",
+                wrapper.bin("wrapper").display()
+            ),
+        )
+        .build();
+
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_stderr_contains("WRAPPED foo")
+        .with_stderr_does_not_contain("WRAPPED bar")
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_flag() {
+    let wrapper = project()
+        .at("wrapper")
+        .file("Cargo.toml", &basic_manifest("wrapper", "1.0.0"))
+        .file("src/main.rs", WRAPPER)
+        .build();
+    wrapper.cargo("build").run();
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[build]\ntool-wrappers = ['{}']",
+                wrapper.bin("wrapper").display()
+            ),
+        )
+        .build();
+
+    p.cargo("check")
+        .with_stderr_does_not_contain("WRAPPED [..]")
+        .run();
+}
+
+#[cargo_test]
+fn tool_version_rebuilds() {
+    let wrapper = project()
+        .at("wrapper")
+        .file("Cargo.toml", &basic_manifest("wrapper", "1.0.0"))
+        .file("src/main.rs", WRAPPER)
+        .build();
+    wrapper.cargo("build").run();
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[build]\ntool-wrappers = ['{}']",
+                wrapper.bin("wrapper").display()
+            ),
+        )
+        .build();
+
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_stderr_contains("WRAPPED foo")
+        .run();
+
+    // The cached output of the wrapped rustc is replayed.
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_stderr(
+            "\
+WRAPPED foo
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .env("TOOL_VERSION", "2.0.0")
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+WRAPPED foo
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn toggling_keeps_artifacts_apart() {
+    let wrapper = project()
+        .at("wrapper")
+        .file("Cargo.toml", &basic_manifest("wrapper", "1.0.0"))
+        .file("src/main.rs", WRAPPER)
+        .build();
+    wrapper.cargo("build").run();
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                "[build]\ntool-wrappers = ['{}']",
+                wrapper.bin("wrapper").display()
+            ),
+        )
+        .build();
+
+    p.cargo("check").run();
+
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+WRAPPED foo
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    // Neither build invalidated the other one.
+    p.cargo("check").with_stderr("[FINISHED] [..]").run();
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_stderr(
+            "\
+WRAPPED foo
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn failing_version_query() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            "[build]\ntool-wrappers = ['does-not-exist']",
+        )
+        .build();
+
+    p.cargo("check -Ztool-wrappers")
+        .masquerade_as_nightly_cargo(&["tool-wrappers"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to get the version of the tool wrapper `does-not-exist` \
+             from `build.tool-wrappers`",
+        )
+        .run();
+}
@@ -360,3 +360,186 @@ foo v0.1.0 ([..]/foo)
         )
         .run();
 }
+
+#[cargo_test]
+fn audit_defaults() {
+    Package::new("bar", "1.0.0")
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .publish();
+    Package::new("baz", "1.0.0").dep("bar", "1.0").publish();
+    Package::new("qux", "1.0.0")
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .publish();
+    Package::new("nodefaults", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { version = "1.0", default-features = false }
+            baz = "1.0"
+            qux = { version = "1.0", default-features = false }
+            nodefaults = { version = "1.0", default-features = false }
+
+            [features]
+            full = ["bar/default", "qux/std"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --audit-defaults")
+        .masquerade_as_nightly_cargo(&["audit-defaults"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--audit-defaults` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("tree --audit-defaults -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["audit-defaults"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo) -> bar v1.0.0: default features enabled by
+  baz v1.0.0 (dependency)
+foo v0.1.0 ([ROOT]/foo) -> qux v1.0.0: default features disabled
+",
+        )
+        .run();
+
+    p.cargo("tree --audit-defaults -Zunstable-options --features full")
+        .masquerade_as_nightly_cargo(&["audit-defaults"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo) -> bar v1.0.0: default features enabled by
+  baz v1.0.0 (dependency)
+  foo v0.1.0 ([ROOT]/foo) (feature `full`)
+foo v0.1.0 ([ROOT]/foo) -> qux v1.0.0: default features disabled
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn audit_defaults_none_disabled() {
+    Package::new("bar", "1.0.0")
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --audit-defaults -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["audit-defaults"])
+        .with_stdout("")
+        .with_stderr_contains("[NOTE] no dependency is declared with `default-features = false`")
+        .run();
+
+    p.cargo("tree --audit-defaults -Zunstable-options -d")
+        .masquerade_as_nightly_cargo(&["audit-defaults"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--audit-defaults` flag does not support `--invert` or `--duplicates`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn why_feature() {
+    Package::new("serde_derive", "1.0.0").publish();
+    Package::new("serde", "1.0.0")
+        .feature("derive", &["serde_derive"])
+        .add_dep(Dependency::new("serde_derive", "1.0").optional(true))
+        .publish();
+    Package::new("nameddep", "1.0.0")
+        .add_dep(Dependency::new("serde", "1.0").optional(true))
+        .feature("default", &["serde-stuff"])
+        .feature("serde-stuff", &["serde/derive"])
+        .feature("vehicle", &["car"])
+        .feature("car", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            nameddep = {version="1.0", features = ["vehicle"]}
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --why-feature serde/derive")
+        .masquerade_as_nightly_cargo(&["why-feature"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--why-feature` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("tree --why-feature serde/derive -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["why-feature"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([..]/foo)
+└── nameddep feature \"default\"
+    └── nameddep feature \"serde-stuff\"
+        └── serde feature \"derive\"
+            ├── serde v1.0.0
+            │   └── serde_derive feature \"default\"
+            │       └── serde_derive v1.0.0
+            └── serde feature \"serde_derive\"
+                └── serde v1.0.0 (*)
+",
+        )
+        .run();
+
+    p.cargo("tree --why-feature car -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["why-feature"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([..]/foo)
+└── nameddep feature \"vehicle\"
+    └── nameddep feature \"car\"
+        └── nameddep v1.0.0
+            └── serde feature \"default\"
+                └── serde v1.0.0
+                    └── serde_derive feature \"default\"
+                        └── serde_derive v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("tree --why-feature serde/std -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["why-feature"])
+        .with_status(101)
+        .with_stderr("[ERROR] feature `serde/std` is not enabled in the dependency graph")
+        .run();
+}
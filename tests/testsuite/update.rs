@@ -873,3 +873,173 @@ required by package `foo v0.1.0 ([ROOT]/foo)`
         )
         .run();
 }
+
+#[cargo_test]
+fn breaking_requires_unstable_options() {
+    Package::new("compat", "1.0.0").publish();
+    Package::new("incompat", "1.0.0").publish();
+    Package::new("pinned", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                compat = "1.0"
+                incompat = { version = "^1.0.0", features = [] }
+                pinned = "=1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("compat", "1.1.0").publish();
+    Package::new("incompat", "2.0.0").publish();
+    Package::new("incompat", "3.0.0-alpha.1").publish();
+    Package::new("pinned", "2.0.0").publish();
+    p.cargo("update --breaking")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--breaking` flag is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn breaking() {
+    Package::new("compat", "1.0.0").publish();
+    Package::new("incompat", "1.0.0").publish();
+    Package::new("pinned", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                compat = "1.0"
+                incompat = { version = "^1.0.0", features = [] }
+                pinned = "=1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("compat", "1.1.0").publish();
+    Package::new("incompat", "2.0.0").publish();
+    Package::new("incompat", "3.0.0-alpha.1").publish();
+    Package::new("pinned", "2.0.0").publish();
+    p.cargo("update --breaking -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr(
+            "\
+[UPDATING] [..] index
+[UPGRADING] incompat ^1.0.0 -> ^2.0.0 in `dependencies`
+[UPDATING] compat v1.0.0 -> v1.1.0
+[UPDATING] incompat v1.0.0 -> v2.0.0
+",
+        )
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains(r#"compat = "1.0""#));
+    assert!(manifest.contains(r#"incompat = { version = "^2.0.0", features = [] }"#));
+    assert!(manifest.contains(r#"pinned = "=1.0.0""#));
+}
+
+#[cargo_test]
+fn breaking_dry_run() {
+    Package::new("compat", "1.0.0").publish();
+    Package::new("incompat", "1.0.0").publish();
+    Package::new("pinned", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                compat = "1.0"
+                incompat = { version = "^1.0.0", features = [] }
+                pinned = "=1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("compat", "1.1.0").publish();
+    Package::new("incompat", "2.0.0").publish();
+    Package::new("incompat", "3.0.0-alpha.1").publish();
+    Package::new("pinned", "2.0.0").publish();
+    let old_manifest = p.read_file("Cargo.toml");
+    let old_lockfile = p.read_lockfile();
+    p.cargo("update --breaking --dry-run -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr(
+            "\
+[UPDATING] [..] index
+[UPGRADING] incompat ^1.0.0 -> ^2.0.0 in `dependencies`
+[WARNING] not updating the manifests due to dry run
+[UPDATING] compat v1.0.0 -> v1.1.0
+[WARNING] not updating lockfile due to dry run
+",
+        )
+        .run();
+    assert_eq!(old_manifest, p.read_file("Cargo.toml"));
+    assert_eq!(old_lockfile, p.read_lockfile());
+}
+
+#[cargo_test]
+fn breaking_spec_in_workspace() {
+    Package::new("incompat", "1.0.0").publish();
+    Package::new("other", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a"]
+
+                [workspace.dependencies]
+                incompat = "1.0"
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+
+                [dependencies]
+                incompat.workspace = true
+                other = "1"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("incompat", "2.1.0").publish();
+    Package::new("other", "2.0.0").publish();
+
+    p.cargo("update --breaking -p incompat -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr(
+            "\
+[UPDATING] [..] index
+[UPGRADING] incompat 1.0 -> 2.1 in `workspace.dependencies`
+[UPDATING] incompat v1.0.0 -> v2.1.0
+",
+        )
+        .run();
+    assert!(p.read_file("Cargo.toml").contains(r#"incompat = "2.1""#));
+    assert!(p.read_file("a/Cargo.toml").contains(r#"other = "1""#));
+}
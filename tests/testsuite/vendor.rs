@@ -1151,3 +1151,156 @@ fn vendor_crate_with_ws_inherit() {
         .with_stderr_contains("[..]foo/vendor/bar/src/lib.rs[..]")
         .run();
 }
+
+#[cargo_test]
+fn no_dev_and_platform_unstable() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("vendor --respect-source-config --no-dev")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `--no-dev` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_dev_and_platform_stub_unused_packages() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                log = "0.3.5"
+
+                [dev-dependencies]
+                devonly = "1.0.0"
+
+                [target.'cfg(never_set)'.dependencies]
+                never = "1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("log", "0.3.5")
+        .file("src/lib.rs", "pub fn log() {}")
+        .publish();
+    Package::new("devonly", "1.0.0")
+        .file("src/lib.rs", "pub fn devonly() {}")
+        .publish();
+    Package::new("never", "1.0.0")
+        .file("src/lib.rs", "pub fn never() {}")
+        .file("README.md", "")
+        .publish();
+
+    p.cargo(&format!(
+        "vendor --respect-source-config -Zunstable-options --no-dev --platform {}",
+        cargo_test_support::rustc_host()
+    ))
+    .masquerade_as_nightly_cargo(&["unstable-options"])
+    .run();
+
+    assert_eq!(p.read_file("vendor/log/src/lib.rs"), "pub fn log() {}");
+    // Packages that aren't needed are kept as stubs so the lock file loads.
+    assert_eq!(p.read_file("vendor/devonly/src/lib.rs"), "");
+    assert_eq!(p.read_file("vendor/never/src/lib.rs"), "");
+    assert!(p.root().join("vendor/never/Cargo.toml").exists());
+    assert!(!p.root().join("vendor/never/README.md").exists());
+
+    add_vendor_config(&p);
+    p.cargo("check").run();
+}
+
+#[cargo_test]
+fn thin() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .file("tests/t.rs", "")
+        .file("benches/b.rs", "")
+        .publish();
+
+    p.cargo("vendor --respect-source-config -Zunstable-options --thin")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+
+    assert!(p.root().join("vendor/bar/src/lib.rs").exists());
+    assert!(!p.root().join("vendor/bar/tests").exists());
+    assert!(!p.root().join("vendor/bar/benches").exists());
+    let cksum = p.read_file("vendor/bar/.cargo-checksum.json");
+    assert!(!cksum.contains("tests/t.rs"));
+
+    add_vendor_config(&p);
+    p.cargo("check").run();
+}
+
+#[cargo_test]
+fn versioned_dirs_checksum_mismatch() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("bar", "0.1.0").publish();
+
+    p.cargo("vendor --respect-source-config --versioned-dirs")
+        .run();
+    let cksum = p.read_file("vendor/bar-0.1.0/.cargo-checksum.json");
+    let lock = p.read_file("Cargo.lock");
+    let checksum = lock
+        .lines()
+        .find_map(|line| line.strip_prefix("checksum = \""))
+        .unwrap()
+        .trim_end_matches('"');
+    assert!(cksum.contains(checksum));
+
+    // A copy which doesn't match the lock file is vendored again.
+    p.change_file(
+        "vendor/bar-0.1.0/.cargo-checksum.json",
+        &cksum.replace(checksum, "1234"),
+    );
+    p.cargo("vendor --respect-source-config --versioned-dirs")
+        .with_stderr_contains(
+            "[WARNING] the vendored sources of `bar v0.1.0` at [..]bar-0.1.0 don't match \
+             the checksum in the lock file, vendoring them again",
+        )
+        .with_stderr_contains("   Vendoring bar v0.1.0 [..]")
+        .run();
+    let cksum = p.read_file("vendor/bar-0.1.0/.cargo-checksum.json");
+    assert!(cksum.contains(checksum));
+
+    // An up to date copy is left alone.
+    p.cargo("vendor --respect-source-config --versioned-dirs")
+        .with_stderr_does_not_contain("[..]Vendoring[..]")
+        .run();
+}
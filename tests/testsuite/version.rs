@@ -1,6 +1,6 @@
 //! Tests for displaying the cargo version.
 
-use cargo_test_support::{cargo_process, project};
+use cargo_test_support::{cargo_process, project, rustc_host};
 
 #[cargo_test]
 fn simple() {
@@ -52,3 +52,76 @@ fn verbose() {
         .with_stdout_contains("os: [..]")
         .run();
 }
+
+#[cargo_test]
+fn json() {
+    cargo_process("version --message-format json")
+        .with_json(
+            &r#"
+                {
+                  "version": "[VERSION]",
+                  "release-channel": "{...}",
+                  "commit-hash": "{...}",
+                  "commit-date": "{...}",
+                  "host": "[HOST]",
+                  "nightly-features-allowed": "{...}",
+                  "unstable-flags": [],
+                  "message-formats": [
+                    "human",
+                    "short",
+                    "json",
+                    "json-diagnostic-short",
+                    "json-diagnostic-rendered-ansi",
+                    "json-render-diagnostics"
+                  ],
+                  "metadata-format-versions": [1],
+                  "lockfile-versions": [1, 2, 3]
+                }
+            "#
+            .replace("[VERSION]", &cargo::version().version)
+            .replace("[HOST]", rustc_host()),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn json_unstable_flags() {
+    // Flags from both the command line and the `[unstable]` table are listed.
+    let p = project()
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [unstable]
+                next-lockfile-bump = true
+            "#,
+        )
+        .build();
+
+    p.cargo("version --message-format json -Zmtime-on-use")
+        .masquerade_as_nightly_cargo(&["mtime-on-use", "next-lockfile-bump"])
+        .with_json(
+            r#"
+                {
+                  "version": "{...}",
+                  "release-channel": "{...}",
+                  "commit-hash": "{...}",
+                  "commit-date": "{...}",
+                  "host": "{...}",
+                  "nightly-features-allowed": true,
+                  "unstable-flags": ["mtime-on-use", "next-lockfile-bump"],
+                  "message-formats": "{...}",
+                  "metadata-format-versions": [1],
+                  "lockfile-versions": [1, 2, 3, 4]
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_message_format() {
+    cargo_process("version --message-format xml")
+        .with_status(101)
+        .with_stderr("[ERROR] invalid message format specifier: `xml`")
+        .run();
+}
@@ -0,0 +1,162 @@
+//! Tests for the `cargo version-check` command.
+
+use cargo_test_support::{basic_manifest, git, Project};
+
+fn workspace() -> (Project, git2::Repository) {
+    git::new_repo("foo", |p| {
+        p.file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b", "c"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+                [package]
+                name = "c"
+                version = "0.1.0"
+                publish = false
+            "#,
+        )
+        .file("c/src/lib.rs", "")
+    })
+}
+
+fn commit(p: &Project, repo: &git2::Repository, changes: &[(&str, &str)]) {
+    for (path, contents) in changes {
+        p.change_file(path, contents);
+    }
+    git::add(repo);
+    git::commit(repo);
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let (p, _repo) = workspace();
+    p.cargo("version-check --base-rev HEAD")
+        .masquerade_as_nightly_cargo(&["version-check"])
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `cargo version-check` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_bump() {
+    let (p, repo) = workspace();
+    git::tag(&repo, "v1");
+    commit(
+        &p,
+        &repo,
+        &[
+            ("a/src/lib.rs", "pub fn f() {}"),
+            ("b/src/lib.rs", "pub fn f() {}"),
+            ("b/Cargo.toml", &basic_manifest("b", "0.1.1")),
+            ("c/src/lib.rs", "pub fn f() {}"),
+        ],
+    );
+
+    p.cargo("version-check -Zunstable-options --base-rev v1")
+        .masquerade_as_nightly_cargo(&["version-check"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[CHANGED] a v0.1.0
+[CHANGED] b v0.1.1
+[ERROR] detected changes in these packages but no version bump found:
+  a@0.1.0
+
+bump at least the patch version in each corresponding Cargo.toml
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn all_bumped() {
+    let (p, repo) = workspace();
+    git::tag(&repo, "v1");
+    commit(
+        &p,
+        &repo,
+        &[
+            ("a/src/lib.rs", "pub fn f() {}"),
+            ("a/Cargo.toml", &basic_manifest("a", "0.2.0")),
+        ],
+    );
+
+    p.cargo("version-check -Zunstable-options --base-rev v1")
+        .masquerade_as_nightly_cargo(&["version-check"])
+        .with_stderr(
+            "\
+[CHANGED] a v0.2.0
+[FINISHED] no version bump needed for changed members
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn head_and_baseline_rev() {
+    let (p, repo) = workspace();
+    git::tag(&repo, "v1");
+    commit(
+        &p,
+        &repo,
+        &[("a/Cargo.toml", &basic_manifest("a", "0.2.0"))],
+    );
+    git::tag(&repo, "v2");
+    commit(&p, &repo, &[("b/src/lib.rs", "pub fn f() {}")]);
+    git::tag(&repo, "v3");
+    // Unrelated changes after the head revision are ignored.
+    commit(&p, &repo, &[("a/src/lib.rs", "pub fn f() {}")]);
+
+    // Only `b` changed between `v2` and `v3`, and it still has the version
+    // it had at `v1`.
+    p.cargo("version-check -Zunstable-options --base-rev v2 --head-rev v3 --baseline-rev v1")
+        .masquerade_as_nightly_cargo(&["version-check"])
+        .with_status(101)
+        .with_stderr_contains("[CHANGED] b v0.1.0")
+        .with_stderr_does_not_contain("[CHANGED] a [..]")
+        .with_stderr_contains("  b@0.1.0")
+        .run();
+}
+
+#[cargo_test]
+fn new_member() {
+    let (p, repo) = workspace();
+    git::tag(&repo, "v1");
+    commit(
+        &p,
+        &repo,
+        &[
+            (
+                "Cargo.toml",
+                r#"
+                    [workspace]
+                    members = ["a", "b", "c", "d"]
+                "#,
+            ),
+            ("d/Cargo.toml", &basic_manifest("d", "0.1.0")),
+            ("d/src/lib.rs", ""),
+        ],
+    );
+
+    p.cargo("version-check -Zunstable-options --base-rev v1")
+        .masquerade_as_nightly_cargo(&["version-check"])
+        .with_stderr(
+            "\
+[CHANGED] d v0.1.0
+[FINISHED] no version bump needed for changed members
+",
+        )
+        .run();
+}
@@ -438,6 +438,7 @@ fn invalid_members() {
         .with_stderr(
             "\
 [ERROR] failed to load manifest for workspace member `[..]/foo`
+referenced by workspace at `[..]/Cargo.toml`
 
 Caused by:
   failed to read `[..]foo/foo/Cargo.toml`
@@ -1678,6 +1679,36 @@ fn excluded_default_members_still_must_be_members() {
             "\
 error: package `[..]bar` is listed in workspace’s default-members \
 but is not a member.
+It is excluded by `bar` in `workspace.exclude`, which only applies to paths \
+also listed in `workspace.members`.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn default_members_glob_matches_non_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/a"]
+                default-members = ["crates/*"]
+            "#,
+        )
+        .file("crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("crates/a/src/lib.rs", "")
+        .file("crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("crates/b/src/lib.rs", "");
+    let p = p.build();
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: package `[..]crates/b` is listed in workspace’s default-members \
+but is not a member.
+It was matched by `crates/*` in `workspace.default-members`.
 ",
         )
         .run();
@@ -1906,6 +1937,7 @@ fn glob_syntax_invalid_members() {
         .with_stderr(
             "\
 [ERROR] failed to load manifest for workspace member `[..]/crates/bar`
+referenced via `crates/*` by workspace at `[..]foo/Cargo.toml`
 
 Caused by:
   failed to read `[..]foo/crates/bar/Cargo.toml`
@@ -2392,6 +2424,7 @@ fn member_dep_missing() {
         .with_stderr(
             "\
 [ERROR] failed to load manifest for workspace member `[..]/bar`
+referenced by workspace at `[..]foo/Cargo.toml`
 
 Caused by:
   failed to load manifest for dependency `baz`
@@ -2,6 +2,7 @@
 
 use std::fs;
 
+use cargo_test_support::cargo_process;
 use cargo_test_support::paths::CargoPathExt;
 use cargo_test_support::project;
 use cargo_test_support::registry;
@@ -200,3 +201,122 @@ fn inline_and_explicit_version() {
         .with_stderr("error: cannot specify both `@0.0.1` and `--version`")
         .run();
 }
+
+fn publish_versions() {
+    for version in ["1.1.0", "1.2.0", "1.3.0", "1.4.0"] {
+        registry::Package::new("foo", version).publish();
+    }
+    registry::Package::new("foo", "1.2.1")
+        .yanked(true)
+        .publish();
+}
+
+#[cargo_test]
+fn versions_requires_unstable_options() {
+    let registry = registry::init();
+    publish_versions();
+
+    cargo_process("yank foo --versions 1.2 --yes")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--versions` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn versions_yanks_matching() {
+    let registry = registry::init();
+    publish_versions();
+    setup("foo", "1.2.0");
+    setup("foo", "1.3.0");
+
+    cargo_process("yank foo -Zunstable-options --yes")
+        .arg("--versions=>=1.2, <1.4")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_stderr_contains("     Matched 2 versions of `foo` to yank: 1.2.0, 1.3.0")
+        .with_stderr_contains("        Yank foo@1.2.0")
+        .with_stderr_contains("        Yank foo@1.3.0")
+        .with_stderr_contains(
+            "\
+[..]Summary 2 of 2 versions yanked
+  foo@1.2.0  ok
+  foo@1.3.0  ok
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn versions_reports_failures() {
+    let registry = registry::init();
+    publish_versions();
+    setup("foo", "1.3.0");
+
+    cargo_process("yank foo -Zunstable-options --yes")
+        .arg("--versions=>=1.2, <1.4")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to yank `foo@1.2.0` from the registry at file:///[..]",
+        )
+        .with_stderr_contains("        Yank foo@1.3.0")
+        .with_stderr_contains(
+            "\
+[..]Summary 1 of 2 versions yanked
+  foo@1.2.0  FAILED
+  foo@1.3.0  ok
+[ERROR] failed to yank 1 of 2 versions of `foo`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn versions_undo_matches_yanked() {
+    // A file based API can't answer the `PUT` request of an unyank.
+    let registry = registry::RegistryBuilder::new().http_api().build();
+    publish_versions();
+
+    cargo_process("yank foo --undo --versions =1.2 -Zunstable-options --yes")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_stderr_contains("     Matched 1 version of `foo` to unyank: 1.2.1")
+        .with_stderr_contains("      Unyank foo@1.2.1")
+        .with_stderr_contains("     Summary 1 of 1 versions unyanked")
+        .run();
+}
+
+#[cargo_test]
+fn versions_needs_confirmation() {
+    let registry = registry::init();
+    publish_versions();
+
+    cargo_process("yank foo --versions =1.2 -Zunstable-options")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_status(101)
+        .with_stderr_contains("     Matched 1 version of `foo` to yank: 1.2.0")
+        .with_stderr_contains("[ERROR] pass `--yes` to yank these versions without confirming them")
+        .with_stderr_does_not_contain("[..]Yank foo@[..]")
+        .run();
+}
+
+#[cargo_test]
+fn versions_without_match() {
+    let registry = registry::init();
+    publish_versions();
+
+    cargo_process("yank foo --versions 2 -Zunstable-options --yes")
+        .replace_crates_io(registry.index_url())
+        .masquerade_as_nightly_cargo(&["yank-versions"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] no versions of `foo` matching `2` to yank")
+        .run();
+}